@@ -94,12 +94,13 @@ export async function googleAuthHandler(c: Context) {
     `, 400);
   }
 
-  // Combine scopes for email and calendar
+  // Combine scopes for email, calendar and contacts
   const scopes = [
     'https://www.googleapis.com/auth/gmail.readonly',
     'https://www.googleapis.com/auth/gmail.send',
     'https://www.googleapis.com/auth/calendar.readonly',
     'https://www.googleapis.com/auth/calendar.events',
+    'https://www.googleapis.com/auth/contacts',
   ];
 
   const authUrl = getGoogleAuthUrl(userId, scopes);
@@ -0,0 +1,72 @@
+//! Quick Actions - Named one-shot operations over HTTP
+//!
+//! Simple clients (iOS Shortcuts, Stream Deck buttons) can't hold a
+//! conversation, so `node.yaml` defines named actions they can trigger with an
+//! API key instead of a natural-language turn:
+//!
+//! - `GET  /actions`        - list configured actions
+//! - `POST /actions/:name`  - run one (`?user_id=provider:id` selects whose history/chat)
+//!
+//! The key is read from `actions.api_key_env` and sent as `X-Api-Key` or
+//! `Authorization: Bearer <key>`. Without it the endpoints answer 404.
+
+use axum::http::{HeaderMap, StatusCode};
+use serde::Serialize;
+
+use crate::config::{ActionsConfig, QuickAction, QuickActionKind};
+
+/// Action as shown to clients (no inputs/messages leaked)
+#[derive(Debug, Serialize)]
+pub struct ActionSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl From<&QuickAction> for ActionSummary {
+    fn from(action: &QuickAction) -> Self {
+        let kind = match action.kind {
+            QuickActionKind::Workflow { .. } => "workflow",
+            QuickActionKind::ClearHistory => "clear_history",
+            QuickActionKind::Prompt { .. } => "prompt",
+        };
+        Self {
+            name: action.name.clone(),
+            kind,
+            description: action.description.clone(),
+        }
+    }
+}
+
+/// Check the request's API key against `actions.api_key_env`
+pub fn authorize(config: &ActionsConfig, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let expected = std::env::var(&config.api_key_env)
+        .ok()
+        .filter(|k| !k.trim().is_empty())
+        .ok_or((StatusCode::NOT_FOUND, "Quick actions disabled".to_string()))?;
+
+    let provided = headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .unwrap_or_default();
+
+    if crate::crypto::secrets_match(expected.trim(), provided) {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "Invalid API key".to_string()))
+    }
+}
+
+/// Find a configured action by name (case-insensitive)
+pub fn find<'a>(config: &'a ActionsConfig, name: &str) -> Option<&'a QuickAction> {
+    config.items.iter().find(|a| a.name.eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,130 @@
+//! Activity - What the daemon is doing right now
+//!
+//! Chat turns register while they run (`begin`, ended when the guard drops) and
+//! failures are kept in a short ring of recent errors. `GET /status` reports
+//! both, with jobs, channels and spend, for `oneclaw top`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Recent errors kept
+const MAX_ERRORS: usize = 50;
+
+/// Characters of a message shown for a turn in flight
+const PREVIEW_CHARS: usize = 60;
+
+/// A chat turn in flight
+#[derive(Debug, Clone, Serialize)]
+pub struct Turn {
+    pub user_id: String,
+    pub channel: String,
+    pub message: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentError {
+    pub at: DateTime<Utc>,
+    /// Where it happened: a channel, `llm` or a tool id
+    pub source: String,
+    pub message: String,
+}
+
+pub struct Activity {
+    started: Instant,
+    next_id: AtomicU64,
+    turns: Mutex<HashMap<u64, Turn>>,
+    errors: Mutex<VecDeque<RecentError>>,
+}
+
+/// Ends its turn when dropped
+pub struct TurnGuard {
+    activity: Arc<Activity>,
+    id: u64,
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        self.activity.turns.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl Default for Activity {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            next_id: AtomicU64::new(0),
+            turns: Mutex::new(HashMap::new()),
+            errors: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl Activity {
+    /// Register a turn for `user_id` on `channel`, until the guard is dropped
+    pub fn begin(self: &Arc<Self>, user_id: &str, channel: &str, message: &str) -> TurnGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.turns.lock().unwrap().insert(id, Turn {
+            user_id: user_id.to_string(),
+            channel: channel.to_string(),
+            message: message.chars().take(PREVIEW_CHARS).collect(),
+            started_at: Utc::now(),
+        });
+        TurnGuard { activity: Arc::clone(self), id }
+    }
+
+    /// Keep `message` among the recent errors
+    pub fn error(&self, source: &str, message: &str) {
+        let mut errors = self.errors.lock().unwrap();
+        if errors.len() == MAX_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(RecentError { at: Utc::now(), source: source.to_string(), message: message.to_string() });
+    }
+
+    /// Turns in flight, oldest first
+    pub fn turns(&self) -> Vec<Turn> {
+        let mut turns: Vec<Turn> = self.turns.lock().unwrap().values().cloned().collect();
+        turns.sort_by_key(|turn| turn.started_at);
+        turns
+    }
+
+    /// Recent errors, newest first
+    pub fn errors(&self) -> Vec<RecentError> {
+        self.errors.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns_end_with_their_guard() {
+        let activity = Arc::new(Activity::default());
+        let first = activity.begin("u1", "telegram", &"x".repeat(200));
+        let second = activity.begin("u2", "http", "hi");
+        assert_eq!(activity.turns().len(), 2);
+        assert_eq!(activity.turns()[0].message.len(), PREVIEW_CHARS);
+        drop(first);
+        assert_eq!(activity.turns()[0].user_id, "u2");
+        drop(second);
+        assert!(activity.turns().is_empty());
+
+        for i in 0..MAX_ERRORS + 5 {
+            activity.error("llm", &format!("error {}", i));
+        }
+        let errors = activity.errors();
+        assert_eq!(errors.len(), MAX_ERRORS);
+        assert_eq!(errors[0].message, format!("error {}", MAX_ERRORS + 4));
+    }
+}
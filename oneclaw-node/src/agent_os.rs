@@ -7,6 +7,11 @@ fn workspace_dir() -> Option<PathBuf> {
     dirs::home_dir().map(|h| h.join(".oneclaw").join("workspace"))
 }
 
+/// Workspace for a node config (honors `node.workspace`, e.g. fleet members)
+pub fn workspace_for(config: &crate::config::NodeConfig) -> Option<PathBuf> {
+    config.node.workspace.as_deref().map(crate::config::expand_path).or_else(workspace_dir)
+}
+
 /// Repo templates dir (for fallback / first-run when workspace is empty).
 fn templates_fallback_dirs() -> Vec<PathBuf> {
     let mut out = Vec::new();
@@ -33,17 +38,24 @@ pub struct AgentOS {
     pub skills: String,
     pub playbooks: String,
     pub memory: String,
+    /// Directory the brain was loaded from (and is saved back to), if not a template
+    #[serde(skip)]
+    pub dir: Option<PathBuf>,
 }
 
 impl AgentOS {
     /// Load agent OS: first from main workspace (~/.oneclaw/workspace), then from repo templates.
+    /// An explicit directory (`node.workspace`, e.g. a fleet member's) has to exist.
     pub fn load(templates_dir_override: Option<PathBuf>) -> anyhow::Result<Self> {
         // 1) Explicit override
         if let Some(d) = templates_dir_override {
-            if d.exists() {
-                tracing::info!("Agent OS dir (override): {}", d.display());
-                return Self::load_from_dir(&d);
+            if !d.exists() {
+                anyhow::bail!("Workspace {} does not exist", d.display());
             }
+            tracing::info!("Agent OS dir (override): {}", d.display());
+            let mut os = Self::load_from_dir(&d)?;
+            os.dir = Some(d);
+            return Ok(os);
         }
 
         // 2) Main folder: ~/.oneclaw/workspace (live agent, like OpenClaw)
@@ -71,6 +83,7 @@ impl AgentOS {
             skills: Self::load_file(dir, "SKILLS.md")?,
             playbooks: Self::load_file(dir, "PLAYBOOKS.md")?,
             memory: Self::load_file(dir, "MEMORY.md")?,
+            dir: None,
         })
     }
 
@@ -84,7 +97,13 @@ impl AgentOS {
         }
     }
 
-    pub fn build_system_prompt(&self, tool_registry: &[ToolDefinition]) -> String {
+    /// Full system prompt. `memory` is the semantic memory recalled for this turn
+    /// (`memory::semantic::recall`), not necessarily all of MEMORY.md.
+    ///
+    /// The pillars and tools only change when the brain evolves, so they come
+    /// first and end in a cache breakpoint; memory and anything appended after
+    /// it change from turn to turn.
+    pub fn build_system_prompt(&self, tool_registry: &[ToolDefinition], memory: &str) -> String {
         let tools_section = self.format_tool_registry(tool_registry);
         
         format!(
@@ -107,20 +126,21 @@ impl AgentOS {
 
 ---
 
-# MEMORY
+# AVAILABLE TOOLS (from Harness)
+{}
 {}
-
 ---
 
-# AVAILABLE TOOLS (from Harness)
+# MEMORY
 {}
 "#,
             self.soul,
             self.identity,
             self.skills,
             self.playbooks,
-            self.memory,
-            tools_section
+            tools_section,
+            crate::llm::CACHE_BREAKPOINT,
+            memory
         )
     }
 
@@ -185,9 +205,14 @@ Respond naturally. If you use a tool, I'll execute it and you can summarize the
         output
     }
 
+    /// Workspace this brain lives in (override dir, else ~/.oneclaw/workspace)
+    pub fn workspace(&self) -> Option<PathBuf> {
+        self.dir.clone().or_else(workspace_dir)
+    }
+
     /// Save the brain back to workspace (after learning)
     pub fn save(&self) -> anyhow::Result<()> {
-        let workspace = workspace_dir()
+        let workspace = self.workspace()
             .ok_or_else(|| anyhow::anyhow!("No workspace directory"))?;
         
         // Create directory if missing
@@ -278,3 +303,32 @@ impl HarnessToolRegistry {
         }
     }
 }
+
+/// Fetch the tool catalog from the harness, asking as node `node_id`
+pub async fn fetch_harness_tools(node_id: &str, harness_url: &str) -> anyhow::Result<Vec<ToolDefinition>> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(15))
+        .build()?;
+    let url = format!("{}/tools", harness_url);
+    let mut req = client.get(&url);
+    for (name, value) in crate::node_key::signed_headers(node_id, "GET", &url, &[]) {
+        req = req.header(name, value);
+    }
+    
+    let parsed: serde_json::Value = req.send().await?.json().await?;
+    let tools = parsed["tools"]
+        .as_array()
+        .map(|tools_arr| {
+            tools_arr.iter().filter_map(|t| {
+                Some(ToolDefinition {
+                    id: t["id"].as_str()?.to_string(),
+                    description: t["description"].as_str().unwrap_or("").to_string(),
+                    params_schema: t.get("paramsSchema").cloned(),
+                    cost_estimate: t["estimatedCostUsd"].as_f64(),
+                    tier: t["tier"].as_str().map(|s| s.to_string()),
+                })
+            }).collect()
+        })
+        .unwrap_or_default();
+    Ok(tools)
+}
@@ -0,0 +1,74 @@
+//! Auth - Bearer-token checks for the node's admin endpoints
+//!
+//! Endpoints that expose every user's data or act for the operator take
+//! `Authorization: Bearer $ONECLAW_ADMIN_TOKEN` (the env var is named by
+//! `security.admin_token_env`). Without a token configured they stay closed.
+
+use crate::config::SecurityConfig;
+
+/// Whether an `Authorization` header carries the admin token
+pub fn admin_authorized(security: &SecurityConfig, authorization: Option<&str>) -> bool {
+    let Some(expected) = std::env::var(&security.admin_token_env).ok().filter(|t| !t.trim().is_empty()) else {
+        return false;
+    };
+    let Some(given) = authorization.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    crate::crypto::secrets_match(&expected, given)
+}
+
+/// Who a `POST /chat` speaks for: an `http` sender (`provider_id`, default
+/// "anonymous"). Another provider's identity, or one of `security.owners`, is
+/// only taken on the admin token, so a caller can't skip that provider's
+/// pairing, approval or deny policy or act as an owner.
+pub fn chat_sender<'a>(
+    security: &SecurityConfig,
+    authorization: Option<&str>,
+    provider: Option<&'a str>,
+    provider_id: Option<&'a str>,
+) -> Result<(&'a str, &'a str), String> {
+    let provider = provider.unwrap_or("http");
+    let provider_id = provider_id.unwrap_or("anonymous");
+    let vouched = provider == "http" && !crate::impersonate::is_owner(security, provider, provider_id);
+    if !vouched && !admin_authorized(security, authorization) {
+        return Err(format!("Bearer token from {} required to chat as {}:{}", security.admin_token_env, provider, provider_id));
+    }
+    Ok((provider, provider_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closed_without_token() {
+        let security: SecurityConfig = serde_yaml::from_str(
+            "mode: strict\nallowed_executors: []\nadmin_token_env: ONECLAW_TEST_UNSET_ADMIN_TOKEN",
+        )
+        .unwrap();
+        assert!(!admin_authorized(&security, Some("Bearer anything")));
+        assert!(!admin_authorized(&security, None));
+    }
+
+    #[test]
+    fn test_chat_sender_is_http_without_token() {
+        let security: SecurityConfig = serde_yaml::from_str(
+            "mode: strict\nallowed_executors: []\nadmin_token_env: ONECLAW_TEST_UNSET_ADMIN_TOKEN",
+        )
+        .unwrap();
+        assert_eq!(chat_sender(&security, None, None, None), Ok(("http", "anonymous")));
+        assert_eq!(chat_sender(&security, None, Some("http"), Some("web-1")), Ok(("http", "web-1")));
+        assert!(chat_sender(&security, Some("Bearer anything"), Some("telegram"), Some("42")).is_err());
+    }
+
+    #[test]
+    fn test_chat_sender_is_never_an_owner_without_token() {
+        let security: SecurityConfig = serde_yaml::from_str(
+            "mode: strict\nallowed_executors: []\nadmin_token_env: ONECLAW_TEST_UNSET_ADMIN_TOKEN\nowners: ['telegram:42', 'http:ops']",
+        )
+        .unwrap();
+        assert!(chat_sender(&security, None, Some("telegram"), Some("42")).is_err());
+        assert!(chat_sender(&security, None, Some("http"), Some("ops")).is_err());
+        assert_eq!(chat_sender(&security, None, Some("http"), Some("guest")), Ok(("http", "guest")));
+    }
+}
@@ -12,6 +12,9 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use crate::agent_os::ToolDefinition;
+use crate::model_router::ModelRouter;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobPlan {
     pub description: String,
@@ -29,7 +32,7 @@ pub struct JobStep {
 
 /// Detect if a user request requires multi-step autonomous execution
 /// vs simple single-tool execution
-pub fn is_complex_request(user_message: &str, tool_results: &[serde_json::Value]) -> bool {
+pub fn is_complex_request<T>(user_message: &str, tool_results: &[T]) -> bool {
     let message_lower = user_message.to_lowercase();
     
     // Explicit multi-step indicators
@@ -96,21 +99,21 @@ pub fn is_complex_request(user_message: &str, tool_results: &[serde_json::Value]
 /// Generate a job plan using LLM
 /// This is a ONE-TIME upfront planning call
 pub async fn generate_job_plan(
+    router: &ModelRouter,
     user_message: &str,
-    llm_client: &reqwest::Client,
-    api_key: &str,
+    planner_context: Option<&str>,
+    tools: &[ToolDefinition],
 ) -> anyhow::Result<JobPlan> {
+    let actions = planner_actions(tools);
+    if actions.is_empty() {
+        anyhow::bail!("No plannable actions in the harness tool catalog (is the harness reachable?)");
+    }
+
     let prompt = format!(
         r#"You are a task planner for an AI agent. Given a user request, break it down into a sequence of executable steps.
 
-Available actions:
-- "discover": Find businesses by niche and location
-- "filter": Filter businesses by criteria (rating, reviews, etc.)
-- "enrich": Find owner/decision-maker contact information for a business
-- "audit": Analyze a business website
-- "analyze": Perform business analysis
-- "draft-email": Draft an outreach email
-
+Available actions (use ONLY these):
+{}{}
 User request: "{}"
 
 Create a step-by-step execution plan. Each step should have:
@@ -124,47 +127,212 @@ Plan:
 1. discover: {{ "niche": "hvac", "location": "Miami, FL", "limit": 50 }}
 2. enrich: {{ "businesses": "{{from_step_1}}" }}
 
-Respond with ONLY a JSON array of steps. No explanation.
+Submit the plan with the submit_plan tool.
 "#,
+        actions,
+        planner_context.map(|c| format!("\n{}", c)).unwrap_or_default(),
         user_message
     );
 
-    let request_body = serde_json::json!({
-        "model": "claude-sonnet-4-20250514",
-        "max_tokens": 2000,
-        "messages": [{
-            "role": "user",
-            "content": prompt
-        }]
-    });
+    let tool = submit_plan_tool(&available_actions(tools));
+    let input = planner_tool_call(router, crate::model_router::PLANNING, &prompt, PLANNER_MODEL, 2000, &tool).await?;
 
-    let response = llm_client
-        .post("https://api.anthropic.com/v1/messages")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .json(&request_body)
-        .send()
-        .await?;
+    Ok(JobPlan {
+        description: user_message.to_string(),
+        steps: parse_plan_steps(&input)?,
+    })
+}
 
-    let response_json: serde_json::Value = response.json().await?;
-    
-    // Extract steps from response
-    let content = response_json["content"][0]["text"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in LLM response"))?;
-    
-    // Parse JSON array from content (strip markdown if present)
-    let json_str = content
-        .trim()
-        .trim_start_matches("```json")
-        .trim_start_matches("```")
-        .trim_end_matches("```")
-        .trim();
-    
-    let steps_array: Vec<serde_json::Value> = serde_json::from_str(json_str)?;
+/// Plan actions the harness executes and the catalog tool behind each
+/// (mirrors `executeStep` in the harness)
+const HARNESS_ACTIONS: &[(&str, &str)] = &[
+    ("discover", "discover-businesses"),
+    ("enrich", "enrich-contact"),
+    ("audit", "audit-website"),
+    ("analyze", "analyze-business"),
+];
+
+/// Catalog tool a harness action runs (`None` for node-local or unknown actions)
+pub fn harness_tool_for(action: &str) -> Option<&'static str> {
+    HARNESS_ACTIONS.iter().find(|(a, _)| *a == action).map(|(_, tool_id)| *tool_id)
+}
+
+/// Planner lines for actions drafted on the node (see `outreach::LOCAL_ACTIONS`)
+const LOCAL_ACTION_LINES: &str = r#"- "call-script": Draft a phone call script for the top leads (params: { "limit": number })
+- "voicemail": Draft a voicemail message for the top leads (params: { "limit": number })
+"#;
+
+/// "Available actions" block built from the live harness catalog: only actions
+/// whose tool is present, with its description, params and cost. Empty when
+/// the catalog has none of them.
+pub fn planner_actions(tools: &[ToolDefinition]) -> String {
+    let mut block = String::new();
+    for (action, tool_id) in HARNESS_ACTIONS {
+        let Some(tool) = tools.iter().find(|t| t.id == *tool_id) else {
+            continue;
+        };
+        block.push_str(&format!("- \"{}\": {}", action, tool.description));
+        if let Some(params) = tool.params_schema.as_ref().and_then(params_summary) {
+            block.push_str(&format!(" (params: {})", params));
+        }
+        if let Some(cost) = tool.cost_estimate {
+            block.push_str(&format!(" [~${:.2}/run]", cost));
+        }
+        block.push('\n');
+    }
+    if block.is_empty() {
+        return block;
+    }
+    block.push_str(LOCAL_ACTION_LINES);
+    block
+}
+
+/// Action names the live catalog can run (harness actions first, then node-local)
+pub fn available_actions(tools: &[ToolDefinition]) -> Vec<&'static str> {
+    let mut actions: Vec<&'static str> = HARNESS_ACTIONS
+        .iter()
+        .filter(|(_, tool_id)| tools.iter().any(|t| t.id == *tool_id))
+        .map(|(action, _)| *action)
+        .collect();
+    if !actions.is_empty() {
+        actions.extend_from_slice(crate::outreach::LOCAL_ACTIONS);
+    }
+    actions
+}
+
+/// `submit_plan` tool the planner must call; `action` is restricted to `actions`
+pub fn submit_plan_tool(actions: &[&str]) -> serde_json::Value {
+    serde_json::json!({
+        "name": "submit_plan",
+        "description": "Submit the execution plan as an ordered list of steps",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "action": { "type": "string", "enum": actions },
+                            "params": { "type": "object" }
+                        },
+                        "required": ["action", "params"]
+                    }
+                }
+            },
+            "required": ["steps"]
+        }
+    })
+}
+
+fn submit_recovery_tool() -> serde_json::Value {
+    serde_json::json!({
+        "name": "submit_recovery",
+        "description": "Submit how to recover from the failed step",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "decision": { "type": "string", "enum": ["SKIP", "RETRY", "ALTERNATIVE", "ABORT"] },
+                "reason": { "type": "string" },
+                "modified_step": {
+                    "type": "object",
+                    "description": "Only for RETRY or ALTERNATIVE",
+                    "properties": {
+                        "action": { "type": "string" },
+                        "params": { "type": "object" }
+                    },
+                    "required": ["action", "params"]
+                }
+            },
+            "required": ["decision", "reason"]
+        }
+    })
+}
+
+/// `{ "name": type, "optional"?: type }` from a JSON Schema's properties
+fn params_summary(schema: &serde_json::Value) -> Option<String> {
+    let properties = schema["properties"].as_object()?;
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    let fields: Vec<String> = properties
+        .iter()
+        .map(|(name, prop)| {
+            let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+            format!("\"{}\"{}: {}", name, optional, prop["type"].as_str().unwrap_or("any"))
+        })
+        .collect();
+    Some(format!("{{ {} }}", fields.join(", ")))
+}
+
+/// Reject plans with actions the live catalog can't run, or step references
+/// (`{from_step_N}`) that don't point at an earlier step
+pub fn validate_plan(plan: &JobPlan, tools: &[ToolDefinition]) -> anyhow::Result<()> {
+    if plan.steps.is_empty() {
+        anyhow::bail!("Plan has no steps");
+    }
+
+    let step_ref = regex::Regex::new(r"\{from_step_(\d+)\}").expect("valid regex");
+    for step in &plan.steps {
+        let runnable = crate::outreach::LOCAL_ACTIONS.contains(&step.action.as_str())
+            || HARNESS_ACTIONS
+                .iter()
+                .any(|(action, tool_id)| *action == step.action && tools.iter().any(|t| t.id == *tool_id));
+        if !runnable {
+            anyhow::bail!("Step {} uses unknown action \"{}\"", step.order, step.action);
+        }
+
+        let params = step.params.to_string();
+        for cap in step_ref.captures_iter(&params) {
+            let target: i32 = cap[1].parse().unwrap_or(0);
+            if target < 1 || target >= step.order {
+                anyhow::bail!("Step {} references step {}, which doesn't run before it", step.order, target);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Model for full planning and recovery calls
+const PLANNER_MODEL: &str = "claude-sonnet-4-20250514";
+
+/// Single-prompt call forced to answer through `tool`; returns the tool input.
+/// Without a `routing` rule for `phase` it goes to Anthropic (`ANTHROPIC_API_KEY`)
+/// with `model`, or to the configured model when the node is routed elsewhere
+/// (`llm.provider: ollama` or `bedrock`).
+pub async fn planner_tool_call(
+    router: &ModelRouter,
+    phase: &'static str,
+    prompt: &str,
+    model: &str,
+    max_tokens: u32,
+    tool: &serde_json::Value,
+) -> anyhow::Result<serde_json::Value> {
+    let messages = vec![serde_json::json!({ "role": "user", "content": prompt })];
+    let default = router.routed().cloned().unwrap_or_else(|| crate::config::LlmConfig {
+        provider: "anthropic".to_string(),
+        api_key_env: "ANTHROPIC_API_KEY".to_string(),
+        model: model.to_string(),
+        base_url: None,
+        supports_tools: None,
+        region: None,
+        context_window: None,
+    });
+    router
+        .call_tool(phase, &default, &messages, max_tokens, tool)
+        .await
+        .map_err(|e| anyhow::anyhow!("Planner call failed: {}", e))
+}
+
+/// Steps from a `submit_plan` tool input
+pub fn parse_plan_steps(input: &serde_json::Value) -> anyhow::Result<Vec<JobStep>> {
+    let steps_array = input["steps"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("submit_plan input has no steps array"))?;
     
-    let steps: Vec<JobStep> = steps_array
-        .into_iter()
+    Ok(steps_array
+        .iter()
         .enumerate()
         .map(|(i, step)| JobStep {
             id: nanoid::nanoid!(10),
@@ -173,22 +341,16 @@ Respond with ONLY a JSON array of steps. No explanation.
             params: step["params"].clone(),
             status: "pending".to_string(),
         })
-        .collect();
-
-    Ok(JobPlan {
-        description: user_message.to_string(),
-        steps,
-    })
+        .collect())
 }
 
 /// Generate a recovery plan when a step fails
 /// This is the "adaptive" part - only called on failure
 pub async fn generate_recovery_plan(
+    router: &ModelRouter,
     failed_step: &JobStep,
     error: &str,
     remaining_steps: &[JobStep],
-    llm_client: &reqwest::Client,
-    api_key: &str,
 ) -> anyhow::Result<Vec<JobStep>> {
     let prompt = format!(
         r#"A task execution step failed. Decide how to recover.
@@ -203,12 +365,7 @@ Options:
 3. ALTERNATIVE: Use a different action to achieve the same goal
 4. ABORT: The failure is unrecoverable, abort the job
 
-Respond with a JSON object:
-{{
-  "decision": "SKIP" | "RETRY" | "ALTERNATIVE" | "ABORT",
-  "reason": "explanation",
-  "modified_step": {{ ... }} // Only if RETRY or ALTERNATIVE
-}}
+Submit your decision with the submit_recovery tool (modified_step only for RETRY or ALTERNATIVE).
 "#,
         failed_step.order,
         failed_step.action,
@@ -216,36 +373,7 @@ Respond with a JSON object:
         remaining_steps.iter().map(|s| &s.action).collect::<Vec<_>>()
     );
 
-    let request_body = serde_json::json!({
-        "model": "claude-sonnet-4-20250514",
-        "max_tokens": 1000,
-        "messages": [{
-            "role": "user",
-            "content": prompt
-        }]
-    });
-
-    let response = llm_client
-        .post("https://api.anthropic.com/v1/messages")
-        .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
-        .json(&request_body)
-        .send()
-        .await?;
-
-    let response_json: serde_json::Value = response.json().await?;
-    let content = response_json["content"][0]["text"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("No content in LLM response"))?;
-    
-    let json_str = content
-        .trim()
-        .trim_start_matches("```json")
-        .trim_start_matches("```")
-        .trim_end_matches("```")
-        .trim();
-    
-    let recovery: serde_json::Value = serde_json::from_str(json_str)?;
+    let recovery = planner_tool_call(router, crate::model_router::PLANNING, &prompt, PLANNER_MODEL, 1000, &submit_recovery_tool()).await?;
     
     match recovery["decision"].as_str() {
         Some("SKIP") => {
@@ -273,25 +401,44 @@ Respond with a JSON object:
     }
 }
 
-/// Create a job in the harness
+/// Create a job in the harness for node `node_id`
 pub async fn create_harness_job(
+    node_id: &str,
     user_id: &str,
     plan: &JobPlan,
     harness_url: &str,
+    scoring: &crate::config::ScoringConfig,
+    jobs: &crate::config::JobsConfig,
 ) -> anyhow::Result<String> {
     let client = reqwest::Client::new();
     
-    let request_body = serde_json::json!({
+    let mut request_body = serde_json::json!({
         "userId": user_id,
         "description": plan.description,
         "plan": plan.steps,
+        "scoring": scoring.to_harness(),
+        "stepTimeouts": jobs.to_harness(),
     });
+    if let Some(callback_url) = &jobs.callback_url {
+        match jobs.callback_token() {
+            Some(token) => {
+                request_body["callbackUrl"] = serde_json::Value::String(callback_url.clone());
+                request_body["callbackToken"] = serde_json::Value::String(token);
+            }
+            None => tracing::warn!("jobs.callback_url is set but {} isn't; polling without job events", jobs.callback_token_env),
+        }
+    }
+
+    let url = format!("{}/jobs/execute", harness_url);
+    let body = serde_json::to_vec(&request_body)?;
+    let mut req = client
+        .post(&url)
+        .header("Content-Type", "application/json");
+    for (name, value) in crate::node_key::signed_headers(node_id, "POST", &url, &body) {
+        req = req.header(name, value);
+    }
 
-    let response = client
-        .post(format!("{}/jobs/execute", harness_url))
-        .json(&request_body)
-        .send()
-        .await?;
+    let response = req.body(body).send().await?;
 
     if !response.status().is_success() {
         let error_text = response.text().await?;
@@ -306,17 +453,49 @@ pub async fn create_harness_job(
     Ok(job_id.to_string())
 }
 
+/// Replace a failed job's failed and remaining steps with recovery steps and resume it
+pub async fn recover_harness_job(
+    node_id: &str,
+    job_id: &str,
+    steps: &[JobStep],
+    harness_url: &str,
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+    
+    let url = format!("{}/autonomous-jobs/{}/recover", harness_url, job_id);
+    let body = serde_json::to_vec(&serde_json::json!({ "steps": steps }))?;
+    let mut req = client
+        .post(&url)
+        .header("Content-Type", "application/json");
+    for (name, value) in crate::node_key::signed_headers(node_id, "POST", &url, &body) {
+        req = req.header(name, value);
+    }
+
+    let response = req.body(body).send().await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        return Err(anyhow::anyhow!("Failed to recover job: {}", error_text));
+    }
+
+    Ok(())
+}
+
 /// Poll job status from harness
 pub async fn poll_job_status(
+    node_id: &str,
     job_id: &str,
     harness_url: &str,
 ) -> anyhow::Result<serde_json::Value> {
     let client = reqwest::Client::new();
     
-    let response = client
-        .get(format!("{}/autonomous-jobs/{}/status", harness_url, job_id))
-        .send()
-        .await?;
+    let url = format!("{}/autonomous-jobs/{}/status", harness_url, job_id);
+    let mut req = client.get(&url);
+    for (name, value) in crate::node_key::signed_headers(node_id, "GET", &url, &[]) {
+        req = req.header(name, value);
+    }
+
+    let response = req.send().await?;
 
     if !response.status().is_success() {
         let error_text = response.text().await?;
@@ -328,15 +507,19 @@ pub async fn poll_job_status(
 
 /// Get final job results from harness
 pub async fn get_job_results(
+    node_id: &str,
     job_id: &str,
     harness_url: &str,
 ) -> anyhow::Result<serde_json::Value> {
     let client = reqwest::Client::new();
     
-    let response = client
-        .get(format!("{}/autonomous-jobs/{}/results", harness_url, job_id))
-        .send()
-        .await?;
+    let url = format!("{}/autonomous-jobs/{}/results", harness_url, job_id);
+    let mut req = client.get(&url);
+    for (name, value) in crate::node_key::signed_headers(node_id, "GET", &url, &[]) {
+        req = req.header(name, value);
+    }
+
+    let response = req.send().await?;
 
     if !response.status().is_success() {
         let error_text = response.text().await?;
@@ -3,19 +3,102 @@
  * 
  * This module handles the polling loop that monitors job progress
  * and sends real-time updates to Telegram.
+ *
+ * Polls are frequent while a step is young and back off the longer it runs
+ * (a tenth of its running time, between `jobs.poll_min_secs` and
+ * `poll_max_secs`), so short jobs report quickly and long ones don't keep the
+ * harness busy. With `jobs.callback_url`, the harness posts job events to
+ * `POST /jobs/events`, with the token from `jobs.callback_token_env`, and the
+ * job is polled right away.
  */
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
 use crate::channels::{Channel, OutgoingMessage, ChannelType};
-use crate::autonomous_jobs::{poll_job_status, get_job_results, generate_recovery_plan};
+use crate::channels::outbound::Priority;
+use crate::autonomous_jobs::{poll_job_status, get_job_results, generate_recovery_plan, recover_harness_job, JobStep};
+use crate::monitor::JobMonitor;
+use crate::model_router::ModelRouter;
+use crate::snooze::Snoozes;
 
 pub struct JobPoller {
     job_id: String,
     channel_id: String,
     channel_type: ChannelType,
     harness_url: String,
+    /// The node the job belongs to, for the harness calls' signatures
+    node_id: String,
     last_step: i32,
+    monitor: Option<JobMonitor>,
+    recovery: Option<Recovery>,
+    snoozes: Option<Arc<Snoozes>>,
+    /// When the current step was first seen
+    step_started: Instant,
+    min_interval: Duration,
+    max_interval: Duration,
+}
+
+/// Wait after a failed poll, at least
+const ERROR_WAIT: Duration = Duration::from_secs(5);
+
+/// Events arriving this close together get one poll
+const EVENT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Jobs being polled, with what wakes their poller early
+static WATCHED: Mutex<Vec<(String, Arc<Notify>)>> = Mutex::new(Vec::new());
+
+/// A job's entry in `WATCHED`, removed when its poller stops
+struct Watched(String);
+
+impl Watched {
+    fn new(job_id: &str) -> (Self, Arc<Notify>) {
+        let notify = Arc::new(Notify::new());
+        if let Ok(mut watched) = WATCHED.lock() {
+            watched.push((job_id.to_string(), Arc::clone(&notify)));
+        }
+        (Self(job_id.to_string()), notify)
+    }
+}
+
+impl Drop for Watched {
+    fn drop(&mut self) {
+        if let Ok(mut watched) = WATCHED.lock() {
+            if let Some(i) = watched.iter().position(|(job_id, _)| *job_id == self.0) {
+                watched.remove(i);
+            }
+        }
+    }
+}
+
+/// Poll `job_id` now (a harness job event arrived); false when no poller is
+/// waiting on it
+pub fn wake(job_id: &str) -> bool {
+    let Ok(watched) = WATCHED.lock() else { return false };
+    let pollers: Vec<&Arc<Notify>> = watched.iter().filter(|(id, _)| id == job_id).map(|(_, notify)| notify).collect();
+    pollers.iter().for_each(|notify| notify.notify_one());
+    !pollers.is_empty()
+}
+
+/// Whether a job event's `Authorization` header carries the callback token;
+/// without a token configured, no event is
+pub fn event_authorized(expected: Option<&str>, authorization: Option<&str>) -> bool {
+    let (Some(expected), Some(given)) = (expected, authorization.and_then(|h| h.strip_prefix("Bearer "))) else {
+        return false;
+    };
+    crate::crypto::secrets_match(expected, given)
+}
+
+/// Wait before the next poll: a tenth of how long the step has been running,
+/// between `min` and `max`
+fn poll_interval(step_running: Duration, min: Duration, max: Duration) -> Duration {
+    (step_running / 10).clamp(min, max.max(min))
+}
+
+/// LLM access for recovering failed or timed-out steps
+struct Recovery {
+    attempts_left: u32,
+    router: Arc<ModelRouter>,
 }
 
 impl JobPoller {
@@ -24,13 +107,61 @@ impl JobPoller {
         channel_id: String,
         channel_type: ChannelType,
         harness_url: String,
+        node_id: String,
     ) -> Self {
         Self {
             job_id,
             channel_id,
             channel_type,
             harness_url,
+            node_id,
             last_step: 0,
+            monitor: None,
+            recovery: None,
+            snoozes: None,
+            step_started: Instant::now(),
+            min_interval: Duration::from_secs(2),
+            max_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// Bounds of the wait between polls (`jobs.poll_min_secs`, `poll_max_secs`)
+    pub fn with_poll_interval(mut self, min: Duration, max: Duration) -> Self {
+        self.min_interval = min.max(Duration::from_secs(1));
+        self.max_interval = max;
+        self
+    }
+
+    /// Ask the planner how to continue when a step fails (or the harness
+    /// watchdog times it out), up to `max_attempts` times per job
+    pub fn with_recovery(mut self, max_attempts: u32, router: Arc<ModelRouter>) -> Self {
+        self.recovery = Some(Recovery {
+            attempts_left: max_attempts,
+            router,
+        });
+        self
+    }
+
+    /// Mirror progress into the job monitor so chat can answer "how's that job going?"
+    pub fn with_monitor(mut self, monitor: JobMonitor) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    /// Make job outcomes snoozable ("snooze this for 2 hours", snooze buttons)
+    pub fn with_snoozes(mut self, snoozes: Arc<Snoozes>) -> Self {
+        self.snoozes = Some(snoozes);
+        self
+    }
+
+    /// Buttons for a job outcome; remembers it so "snooze this" refers to it
+    fn outcome_buttons(&self, content: &str) -> Vec<Vec<crate::channels::Button>> {
+        match &self.snoozes {
+            Some(snoozes) => {
+                snoozes.remember(&self.channel_type.to_string(), &self.channel_id, content);
+                crate::snooze::buttons()
+            }
+            None => vec![],
         }
     }
 
@@ -40,12 +171,22 @@ impl JobPoller {
         &mut self,
         channel: Arc<C>,
     ) -> anyhow::Result<bool> {
-        let status = poll_job_status(&self.job_id, &self.harness_url).await?;
+        let status = poll_job_status(&self.node_id, &self.job_id, &self.harness_url).await?;
         
         let job_status = status["status"].as_str().unwrap_or("unknown");
         let current_step = status["currentStep"].as_i64().unwrap_or(0) as i32;
         let total_steps = status["totalSteps"].as_i64().unwrap_or(1) as i32;
         
+        if let Some(monitor) = &self.monitor {
+            let step_label = status["steps"]
+                .as_array()
+                .and_then(|steps| steps.get((current_step.max(1) - 1) as usize))
+                .and_then(|step| step["action"].as_str())
+                .map(|action| format!("{}/{} ({})", current_step, total_steps, action));
+            let progress = current_step as f32 / total_steps.max(1) as f32;
+            monitor.record_progress(&self.job_id, job_status, step_label, progress).await;
+        }
+        
         // Send progress update if step changed
         if current_step > self.last_step && current_step <= total_steps {
             if let Some(steps) = status["steps"].as_array() {
@@ -60,6 +201,8 @@ impl JobPoller {
                         "audit" => "🌐",
                         "analyze" => "📊",
                         "draft-email" => "✉️",
+                        "call-script" => "☎️",
+                        "voicemail" => "📼",
                         _ => "⚙️",
                     };
                     
@@ -81,74 +224,151 @@ impl JobPoller {
                         )
                     };
                     
-                    channel.send(OutgoingMessage {
-                        channel_type: self.channel_type.clone(),
+                    channel.send_with_priority(OutgoingMessage {
+                        channel_type: self.channel_type,
                         channel_id: self.channel_id.clone(),
                         content: message,
                         reply_to: None,
+                        thread_id: None,
                         metadata: serde_json::json!({}),
-                    }).await?;
+                        attachments: vec![],
+                        buttons: vec![],
+                    }, Priority::Digest).await?;
                 }
             }
             
             self.last_step = current_step;
+            self.step_started = Instant::now();
         }
         
         // Check if job is complete
         match job_status {
             "completed" => {
-                channel.send(OutgoingMessage {
-                    channel_type: self.channel_type.clone(),
+                channel.send_with_priority(OutgoingMessage {
+                    channel_type: self.channel_type,
                     channel_id: self.channel_id.clone(),
                     content: "✅ Job completed! Fetching results...".to_string(),
                     reply_to: None,
+                    thread_id: None,
                     metadata: serde_json::json!({}),
-                }).await?;
+                    attachments: vec![],
+                    buttons: vec![],
+                }, Priority::Notification).await?;
                 Ok(true)
             }
             "failed" => {
                 let error = status["error"].as_str().unwrap_or("Unknown error");
-                channel.send(OutgoingMessage {
-                    channel_type: self.channel_type.clone(),
+                if let Some(steps) = self.try_recover(&status).await {
+                    channel.send_with_priority(OutgoingMessage {
+                        channel_type: self.channel_type,
+                        channel_id: self.channel_id.clone(),
+                        content: format!("⚠️ {}\n🔧 Recovering - continuing with {} step(s)", error, steps),
+                        reply_to: None,
+                        thread_id: None,
+                        metadata: serde_json::json!({}),
+                        attachments: vec![],
+                        buttons: vec![],
+                    }, Priority::Notification).await?;
+                    return Ok(false);
+                }
+                let content = format!("❌ Job failed: {}", error);
+                channel.send_with_priority(OutgoingMessage {
+                    channel_type: self.channel_type,
                     channel_id: self.channel_id.clone(),
-                    content: format!("❌ Job failed: {}", error),
+                    buttons: self.outcome_buttons(&content),
+                    content,
                     reply_to: None,
+                    thread_id: None,
                     metadata: serde_json::json!({}),
-                }).await?;
+                    attachments: vec![],
+                }, Priority::Notification).await?;
                 Ok(true)
             }
             "cancelled" => {
-                channel.send(OutgoingMessage {
-                    channel_type: self.channel_type.clone(),
+                let content = "🛑 Job was cancelled".to_string();
+                channel.send_with_priority(OutgoingMessage {
+                    channel_type: self.channel_type,
                     channel_id: self.channel_id.clone(),
-                    content: "🛑 Job was cancelled".to_string(),
+                    buttons: self.outcome_buttons(&content),
+                    content,
                     reply_to: None,
+                    thread_id: None,
                     metadata: serde_json::json!({}),
-                }).await?;
+                    attachments: vec![],
+                }, Priority::Notification).await?;
                 Ok(true)
             }
             _ => Ok(false), // Still running
         }
     }
 
+    /// Plan around the failed step and resume the job in the harness.
+    /// Returns the number of steps left to run, or None to let the job fail.
+    async fn try_recover(&mut self, status: &serde_json::Value) -> Option<usize> {
+        let recovery = self.recovery.as_mut().filter(|r| r.attempts_left > 0)?;
+        let steps: Vec<JobStep> = serde_json::from_value(status["steps"].clone()).ok()?;
+        let failed_index = steps.iter().position(|s| s.status == "failed")?;
+        let error = status["steps"][failed_index]["error"]
+            .as_str()
+            .or_else(|| status["error"].as_str())
+            .unwrap_or("Unknown error");
+        let remaining: Vec<JobStep> = steps[failed_index + 1..]
+            .iter()
+            .filter(|s| s.status == "pending")
+            .cloned()
+            .collect();
+
+        recovery.attempts_left -= 1;
+        let new_steps = match generate_recovery_plan(
+            &recovery.router,
+            &steps[failed_index],
+            error,
+            &remaining,
+        ).await {
+            Ok(new_steps) if !new_steps.is_empty() => new_steps,
+            Ok(_) => return None,
+            Err(e) => {
+                tracing::warn!("No recovery for job {}: {}", self.job_id, e);
+                return None;
+            }
+        };
+
+        if let Err(e) = recover_harness_job(&self.node_id, &self.job_id, &new_steps, &self.harness_url).await {
+            tracing::warn!("Failed to resume job {}: {}", self.job_id, e);
+            return None;
+        }
+
+        tracing::info!("🔧 Recovered job {} at step {}", self.job_id, failed_index + 1);
+        self.last_step = failed_index as i32;
+        self.step_started = Instant::now();
+        Some(new_steps.len())
+    }
+
     /// Main polling loop - runs until job completes
     pub async fn run_until_complete<C: Channel + Clone + Send + Sync + 'static>(
         mut self,
         channel: Arc<C>,
     ) -> anyhow::Result<serde_json::Value> {
+        let (_watched, events) = Watched::new(&self.job_id);
         loop {
-            match self.poll_and_notify(channel.clone()).await {
+            let interval = poll_interval(self.step_started.elapsed(), self.min_interval, self.max_interval);
+            let wait = match self.poll_and_notify(channel.clone()).await {
                 Ok(true) => {
                     // Job complete, fetch final results
-                    return get_job_results(&self.job_id, &self.harness_url).await;
-                }
-                Ok(false) => {
-                    // Still running, wait before next poll
-                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    return get_job_results(&self.node_id, &self.job_id, &self.harness_url).await;
                 }
+                // Still running, wait before next poll
+                Ok(false) => interval,
                 Err(e) => {
                     tracing::error!("Polling error: {}", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    interval.max(ERROR_WAIT)
+                }
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = events.notified() => {
+                    tracing::debug!("Job {} event from the harness; polling now", self.job_id);
+                    tokio::time::sleep(EVENT_DEBOUNCE).await;
                 }
             }
         }
@@ -178,40 +398,42 @@ pub fn format_job_results(results: &serde_json::Value) -> String {
         output.push_str("\n");
     }
     
-    // Format businesses
-    if let Some(businesses) = results["businesses"].as_array() {
-        if !businesses.is_empty() {
-            output.push_str(&format!("🏢 **Found {} Businesses:**\n\n", businesses.len()));
-            
-            for (i, business) in businesses.iter().take(10).enumerate() {
-                let name = business["name"].as_str().unwrap_or("Unknown");
-                let rating = business["rating"].as_f64();
-                let reviews = business["reviewCount"].as_i64();
-                let phone = business["phone"].as_str();
-                let website = business["website"].as_str();
-                
-                output.push_str(&format!("{}. **{}**", i + 1, name));
-                
-                if let Some(r) = rating {
-                    output.push_str(&format!(" ⭐ {:.1}", r));
-                }
-                if let Some(rev) = reviews {
-                    output.push_str(&format!(" ({} reviews)", rev));
-                }
-                output.push('\n');
-                
-                if let Some(p) = phone {
-                    output.push_str(&format!("   📞 {}\n", p));
-                }
-                if let Some(w) = website {
-                    output.push_str(&format!("   🌐 {}\n", w));
+    // Repeat runs of the same discovery: report only what's new or changed
+    let diff = &results["diff"];
+    let is_repeat_run = diff["previousJobId"].is_string();
+    if is_repeat_run {
+        output.push_str(&format!("🔁 **{}**\n\n", diff["summary"].as_str().unwrap_or("Compared to last run")));
+        
+        if let Some(new_businesses) = diff["new"].as_array() {
+            if !new_businesses.is_empty() {
+                output.push_str(&format!("🆕 **{} New:**\n\n", new_businesses.len()));
+                output.push_str(&format_business_list(new_businesses));
+            }
+        }
+        
+        if let Some(changed) = diff["changed"].as_array() {
+            if !changed.is_empty() {
+                output.push_str(&format!("✏️ **{} Changed:**\n\n", changed.len()));
+                for change in changed.iter().take(10) {
+                    let name = change["business"]["name"].as_str().unwrap_or("Unknown");
+                    let fields: Vec<String> = change["changes"]
+                        .as_object()
+                        .map(|c| c.iter().map(|(field, delta)| {
+                            format!("{}: {} → {}", field, display_value(&delta["from"]), display_value(&delta["to"]))
+                        }).collect())
+                        .unwrap_or_default();
+                    output.push_str(&format!("• **{}** - {}\n", name, fields.join(", ")));
                 }
                 output.push('\n');
             }
-            
-            if businesses.len() > 10 {
-                output.push_str(&format!("...and {} more\n\n", businesses.len() - 10));
-            }
+        }
+    }
+    
+    // Format businesses (full list only on first run)
+    if let Some(businesses) = results["businesses"].as_array().filter(|_| !is_repeat_run) {
+        if !businesses.is_empty() {
+            output.push_str(&format!("🏢 **Found {} Businesses:**\n\n", businesses.len()));
+            output.push_str(&format_business_list(businesses));
         }
     }
     
@@ -251,3 +473,76 @@ pub fn format_job_results(results: &serde_json::Value) -> String {
     
     output
 }
+
+/// Numbered business list (first 10, best lead score first) with score, rating, reviews, phone and website
+fn format_business_list(businesses: &[serde_json::Value]) -> String {
+    let mut output = String::new();
+    
+    for (i, business) in businesses.iter().take(10).enumerate() {
+        let name = business["name"].as_str().unwrap_or("Unknown");
+        let rating = business["rating"].as_f64();
+        let reviews = business["reviewCount"].as_i64();
+        let phone = business["phone"].as_str();
+        let website = business["website"].as_str();
+        
+        output.push_str(&format!("{}. **{}**", i + 1, name));
+        
+        if let Some(score) = business["score"].as_f64() {
+            output.push_str(&format!(" 🎯 {:.0}", score));
+        }
+        if let Some(r) = rating {
+            output.push_str(&format!(" ⭐ {:.1}", r));
+        }
+        if let Some(rev) = reviews {
+            output.push_str(&format!(" ({} reviews)", rev));
+        }
+        output.push('\n');
+        
+        if let Some(p) = phone {
+            output.push_str(&format!("   📞 {}\n", p));
+        }
+        if let Some(w) = website {
+            output.push_str(&format!("   🌐 {}\n", w));
+        }
+        output.push('\n');
+    }
+    
+    if businesses.len() > 10 {
+        output.push_str(&format!("...and {} more\n\n", businesses.len() - 10));
+    }
+    output
+}
+
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "none".to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_poll_interval_and_wake() {
+        let (min, max) = (Duration::from_secs(2), Duration::from_secs(30));
+        assert_eq!(poll_interval(Duration::from_secs(5), min, max), min);
+        assert_eq!(poll_interval(Duration::from_secs(90), min, max), Duration::from_secs(9));
+        assert_eq!(poll_interval(Duration::from_secs(3600), min, max), max);
+
+        assert!(!wake("job-1"));
+        let (watched, events) = Watched::new("job-1");
+        assert!(wake("job-1"));
+        // The event is kept until the poller waits again
+        tokio::time::timeout(Duration::from_secs(1), events.notified()).await.unwrap();
+        drop(watched);
+        assert!(!wake("job-1"));
+
+        assert!(event_authorized(Some("evt-token"), Some("Bearer evt-token")));
+        assert!(!event_authorized(Some("evt-token"), Some("Bearer forged")));
+        assert!(!event_authorized(Some("evt-token"), None));
+        assert!(!event_authorized(None, Some("Bearer anything")));
+    }
+}
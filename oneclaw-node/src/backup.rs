@@ -0,0 +1,201 @@
+//! Backup - Nightly encrypted node bundles in S3-compatible object storage
+//!
+//! With `backup.enabled`, the daemon seals a node bundle (see `bundle.rs`: config,
+//! workspace, preferences, a store snapshot and workflows) with the sync
+//! passphrase every night at `backup.hour_utc` and uploads it to
+//! `<bucket>/<prefix><node id>/<timestamp>.bundle`. Only the newest `backup.keep`
+//! are kept. Requests are signed with AWS Signature V4 and use path-style URLs,
+//! which AWS S3, Cloudflare R2, MinIO and Backblaze B2 all accept.
+//!
+//! Restoring: download a backup from `GET /backups/:name` and load it with
+//! `oneclaw import-bundle <file> --force` while the daemon is stopped.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::config::{BackupConfig, NodeConfig};
+use crate::health::{ComponentStatus, HealthState};
+use crate::{bundle, sigv4, sync};
+
+/// A stored backup
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupObject {
+    /// File name under this node's prefix (what `GET /backups/:name` takes)
+    pub name: String,
+    pub size: u64,
+    pub last_modified: String,
+}
+
+/// An S3 bucket reached with the `backup` credentials
+struct Bucket<'a> {
+    config: &'a BackupConfig,
+    endpoint: url::Url,
+    credentials: sigv4::Credentials,
+    client: reqwest::Client,
+}
+
+impl<'a> Bucket<'a> {
+    fn new(config: &'a BackupConfig) -> anyhow::Result<Self> {
+        if config.endpoint.is_empty() || config.bucket.is_empty() {
+            anyhow::bail!("backup.endpoint and backup.bucket must be set");
+        }
+        Ok(Self {
+            config,
+            endpoint: url::Url::parse(&config.endpoint)?,
+            credentials: sigv4::Credentials::from_env_vars(&config.access_key_env, &config.secret_key_env)?,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(300))
+                .build()?,
+        })
+    }
+
+    async fn send(&self, method: reqwest::Method, key: &str, query: &[(&str, &str)], body: Vec<u8>) -> anyhow::Result<reqwest::Response> {
+        let base = self.endpoint.path().trim_end_matches('/');
+        let path = sigv4::uri_encode(&format!("{}/{}/{}", base, self.config.bucket, key).replace("//", "/"), true);
+        let path = if key.is_empty() { path.trim_end_matches('/').to_string() } else { path };
+        let query = sigv4::canonical_query(query);
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        };
+        let request = sigv4::Request { method: method.as_str(), host: &host, path: &path, query: &query, body: &body };
+        let headers = sigv4::sign(&self.credentials, &self.config.region, "s3", &request, Utc::now());
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let mut request = self.client.request(method.clone(), url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let resp = request
+            .body(body)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Object storage error {} on {} {}: {}", resp.status(), method, key, resp.text().await.unwrap_or_default());
+        }
+        Ok(resp)
+    }
+
+    /// Objects under `prefix`
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<(String, u64, String)>> {
+        let resp = self.send(reqwest::Method::GET, "", &[("list-type", "2"), ("prefix", prefix)], vec![]).await?;
+        Ok(parse_list(&resp.text().await?))
+    }
+}
+
+/// (key, size, last modified) from a ListObjectsV2 response
+fn parse_list(xml: &str) -> Vec<(String, u64, String)> {
+    let field = |item: &str, tag: &str| {
+        let open = format!("<{}>", tag);
+        let start = item.find(&open)? + open.len();
+        let end = item[start..].find(&format!("</{}>", tag))? + start;
+        Some(item[start..end].to_string())
+    };
+    xml.split("<Contents>")
+        .skip(1)
+        .filter_map(|item| {
+            Some((
+                field(item, "Key")?,
+                field(item, "Size").and_then(|s| s.parse().ok()).unwrap_or_default(),
+                field(item, "LastModified").unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+/// Where this node's backups live in the bucket
+fn node_prefix(config: &NodeConfig) -> String {
+    format!("{}{}/", config.backup.prefix, config.node.id)
+}
+
+/// This node's backups, newest first
+pub async fn list(config: &NodeConfig) -> anyhow::Result<Vec<BackupObject>> {
+    let bucket = Bucket::new(&config.backup)?;
+    let prefix = node_prefix(config);
+    let mut backups: Vec<BackupObject> = bucket
+        .list(&prefix)
+        .await?
+        .into_iter()
+        .filter_map(|(key, size, last_modified)| {
+            let name = key.strip_prefix(&prefix)?.to_string();
+            name.ends_with(".bundle").then_some(BackupObject { name, size, last_modified })
+        })
+        .collect();
+    // Names are timestamps, so they sort by age
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// A sealed bundle as stored, ready for `oneclaw import-bundle`
+pub async fn download(config: &NodeConfig, name: &str) -> anyhow::Result<Vec<u8>> {
+    if name.contains('/') || !name.ends_with(".bundle") {
+        anyhow::bail!("Not a backup name: {}", name);
+    }
+    let bucket = Bucket::new(&config.backup)?;
+    let resp = bucket.send(reqwest::Method::GET, &format!("{}{}", node_prefix(config), name), &[], vec![]).await?;
+    Ok(resp.bytes().await?.to_vec())
+}
+
+/// Seal and upload a bundle now, then delete backups beyond `backup.keep`
+pub async fn backup_now(config: &'static NodeConfig) -> anyhow::Result<BackupObject> {
+    let bucket = Bucket::new(&config.backup)?;
+    let passphrase = sync::passphrase(&config.sync.passphrase_env)?;
+    let (bundle, sealed) = tokio::task::spawn_blocking(move || bundle::seal(config, &passphrase)).await??;
+
+    let name = format!("{}.bundle", bundle.created_at.format("%Y%m%dT%H%M%SZ"));
+    let size = sealed.len() as u64;
+    bucket.send(reqwest::Method::PUT, &format!("{}{}", node_prefix(config), name), &[], sealed).await?;
+    tracing::info!("💾 Backed up node {} to {} ({}; {} bytes)", bundle.node_id, name, bundle.summary(), size);
+
+    for old in list(config).await?.iter().skip(config.backup.keep.max(1)) {
+        match bucket.send(reqwest::Method::DELETE, &format!("{}{}", node_prefix(config), old.name), &[], vec![]).await {
+            Ok(_) => tracing::info!("Deleted old backup {}", old.name),
+            Err(e) => tracing::warn!("Could not delete old backup {}: {}", old.name, e),
+        }
+    }
+    Ok(BackupObject { name, size, last_modified: bundle.created_at.to_rfc3339() })
+}
+
+/// The next `hour_utc:00` after `now`
+fn next_run(now: DateTime<Utc>, hour_utc: u32) -> DateTime<Utc> {
+    let today = now.date_naive().and_hms_opt(hour_utc.min(23), 0, 0).unwrap_or_default().and_utc();
+    if today > now { today } else { today + Duration::days(1) }
+}
+
+/// Back up nightly until the process exits; the outcome shows as the `backup` component
+pub async fn run(config: &'static NodeConfig, health: Arc<HealthState>) {
+    health.set_component("backup", ComponentStatus::Ready(format!("nightly at {:02}:00 UTC", config.backup.hour_utc))).await;
+    loop {
+        let at = next_run(Utc::now(), config.backup.hour_utc);
+        let wait = (at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        match backup_now(config).await {
+            Ok(backup) => health.set_component("backup", ComponentStatus::Ready(format!("last: {}", backup.name))).await,
+            Err(e) => {
+                tracing::error!("Nightly backup failed: {}", e);
+                health.set_component("backup", ComponentStatus::Failed(e.to_string())).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_backup_helpers() {
+        let xml = "<ListBucketResult><Contents><Key>oneclaw/n1/20261016T030000Z.bundle</Key><LastModified>2026-10-16T03:00:05.000Z</LastModified><Size>2048</Size></Contents></ListBucketResult>";
+        assert_eq!(parse_list(xml), vec![("oneclaw/n1/20261016T030000Z.bundle".to_string(), 2048, "2026-10-16T03:00:05.000Z".to_string())]);
+
+        let evening = Utc.with_ymd_and_hms(2026, 10, 16, 22, 0, 0).unwrap();
+        assert_eq!(next_run(evening, 3), Utc.with_ymd_and_hms(2026, 10, 17, 3, 0, 0).unwrap());
+        let night = Utc.with_ymd_and_hms(2026, 10, 16, 1, 0, 0).unwrap();
+        assert_eq!(next_run(night, 3), Utc.with_ymd_and_hms(2026, 10, 16, 3, 0, 0).unwrap());
+    }
+}
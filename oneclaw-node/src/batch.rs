@@ -0,0 +1,277 @@
+//! Batch - Background LLM work through the Anthropic Message Batches API
+//!
+//! Work nobody is waiting on (post-turn reflection, conversation tagging) is
+//! queued instead of sent right away. Every `batch.flush_interval_secs` the queue
+//! goes out as one batch, billed at half the price of the same calls made one by
+//! one. Submitted batches are tracked in the store (`llm_batches`) and polled
+//! every `batch.poll_interval_secs`; when a batch ends each result is applied by
+//! kind: reflections update the Agent OS pillars, tags are saved on their
+//! messages. Open batches are picked up again after a restart.
+//!
+//! Off by default, and never used on local-only or Bedrock nodes.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::config::{LlmConfig, NodeConfig};
+use crate::daemon::AppState;
+use crate::llm::{ApiError, BatchRequest, Batches, ChatResponse};
+use crate::store::{LlmBatch, Store};
+use crate::{learning, tagging};
+
+pub const REFLECTION: &str = "reflection";
+pub const TAGGING: &str = "tagging";
+
+/// Oldest queued requests are dropped past this (e.g. while the API key is missing)
+const MAX_QUEUED: usize = 10_000;
+
+struct Queued {
+    kind: &'static str,
+    /// Messages API body
+    params: Value,
+    /// Kept with the batch to apply the result (tagging: the message ids)
+    context: Value,
+}
+
+/// Requests waiting for the next flush
+#[derive(Default)]
+pub struct BatchQueue {
+    pending: Mutex<Vec<Queued>>,
+}
+
+impl BatchQueue {
+    pub fn push(&self, kind: &'static str, params: Value, context: Value) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.push(Queued { kind, params, context });
+        if pending.len() > MAX_QUEUED {
+            let excess = pending.len() - MAX_QUEUED;
+            pending.drain(..excess);
+        }
+    }
+
+    /// Whether a `kind` request is queued or waiting in an open batch
+    pub async fn in_flight(&self, store: &dyn Store, kind: &str) -> anyhow::Result<bool> {
+        if self.pending.lock().unwrap().iter().any(|q| q.kind == kind) {
+            return Ok(true);
+        }
+        let open = store.open_llm_batches().await?;
+        Ok(open.iter().any(|batch| {
+            batch.requests.as_object().is_some_and(|requests| requests.keys().any(|id| kind_of(id) == kind))
+        }))
+    }
+
+    fn take(&self) -> Vec<Queued> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Put back requests that couldn't be submitted, ahead of newer ones
+    fn restore(&self, mut queued: Vec<Queued>) {
+        let mut pending = self.pending.lock().unwrap();
+        queued.append(&mut pending);
+        *pending = queued;
+    }
+}
+
+/// Whether background LLM work goes through batches on this node
+pub fn enabled(config: &NodeConfig) -> bool {
+    config.batch.enabled && crate::llm::routed(&config.llm).is_none()
+}
+
+/// Whether reflections are batched: they use the node's own model, so only on Anthropic
+pub fn batches_reflection(config: &NodeConfig) -> bool {
+    enabled(config) && config.llm.provider == "anthropic"
+}
+
+/// Request kind from its custom id (`<kind>-<n>`)
+fn kind_of(custom_id: &str) -> &str {
+    custom_id.rsplit_once('-').map_or(custom_id, |(kind, _)| kind)
+}
+
+/// Flush the queue and collect results until the process exits
+pub async fn run(state: Arc<AppState>) {
+    let config = &state.config.batch;
+    let mut flush = tokio::time::interval(std::time::Duration::from_secs(config.flush_interval_secs.max(60)));
+    let mut poll = tokio::time::interval(std::time::Duration::from_secs(config.poll_interval_secs.max(30)));
+
+    loop {
+        tokio::select! {
+            _ = flush.tick() => submit(&state).await,
+            _ = poll.tick() => collect(&state).await,
+        }
+    }
+}
+
+/// Send everything queued as one batch and record it in the store
+async fn submit(state: &AppState) {
+    let queued = state.batches.take();
+    if queued.is_empty() {
+        return;
+    }
+
+    let custom_ids: Vec<String> = queued.iter().enumerate().map(|(i, q)| format!("{}-{}", q.kind, i)).collect();
+    let requests: Vec<BatchRequest> = queued
+        .iter()
+        .zip(&custom_ids)
+        .map(|(q, custom_id)| BatchRequest { custom_id: custom_id.clone(), params: q.params.clone() })
+        .collect();
+    let created = match Batches::new(&state.config.llm) {
+        Ok(api) => api.create(&requests).await,
+        Err(e) => Err(e),
+    };
+    let status = match created {
+        Ok(status) => status,
+        Err(e) => {
+            tracing::warn!("Could not submit LLM batch of {} requests: {}", queued.len(), e);
+            state.batches.restore(queued);
+            return;
+        }
+    };
+
+    let requests: serde_json::Map<String, Value> = custom_ids
+        .into_iter()
+        .zip(queued)
+        .map(|(custom_id, q)| (custom_id, q.context))
+        .collect();
+    tracing::info!("📦 Submitted LLM batch {} ({} requests)", status.id, requests.len());
+    let batch = LlmBatch {
+        id: status.id,
+        status: status.processing_status,
+        requests: Value::Object(requests),
+        submitted_at: Utc::now(),
+        ended_at: None,
+    };
+    if let Err(e) = state.store.add_llm_batch(&batch).await {
+        tracing::warn!("Could not record LLM batch {}: {}", batch.id, e);
+    }
+}
+
+/// Results of batch `id`, or None while it is still processing
+async fn fetch(llm: &LlmConfig, id: &str) -> anyhow::Result<Option<Vec<(String, Result<ChatResponse, String>)>>> {
+    let api = Batches::new(llm)?;
+    let status = api.status(id).await?;
+    if !status.ended() {
+        return Ok(None);
+    }
+    api.results(&status).await.map(Some)
+}
+
+/// Apply the results of open batches that have ended
+async fn collect(state: &AppState) {
+    let open = match state.store.open_llm_batches().await {
+        Ok(open) => open,
+        Err(e) => {
+            tracing::warn!("Could not load open LLM batches: {}", e);
+            return;
+        }
+    };
+
+    for batch in open {
+        let fetched = fetch(&state.config.llm, &batch.id).await;
+
+        let ended_as = match fetched {
+            Ok(None) => continue,
+            Ok(Some(results)) => {
+                apply(state, &batch, results).await;
+                "ended"
+            }
+            Err(e) if e.downcast_ref::<ApiError>().is_some_and(|e| e.status == 404) => {
+                tracing::warn!("LLM batch {} no longer exists; dropping it", batch.id);
+                "lost"
+            }
+            Err(e) => {
+                tracing::warn!("Could not check LLM batch {}: {}", batch.id, e);
+                continue;
+            }
+        };
+        if let Err(e) = state.store.end_llm_batch(&batch.id, ended_as).await {
+            tracing::warn!("Could not mark LLM batch {} {}: {}", batch.id, ended_as, e);
+        }
+    }
+}
+
+async fn apply(state: &AppState, batch: &LlmBatch, results: Vec<(String, Result<ChatResponse, String>)>) {
+    let before = state.canary.current(&state.agent_os);
+    let mut agent_os = before.clone();
+    let mut learned = false;
+    let mut tagged = 0;
+
+    for (custom_id, result) in results {
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("LLM batch {} request {} failed: {}", batch.id, custom_id, e);
+                continue;
+            }
+        };
+        crate::spend::record_llm("anthropic", &response.model, &response.raw, 0.5);
+        match kind_of(&custom_id) {
+            REFLECTION => learned |= learning::apply_reflection(&response.content, &mut agent_os),
+            TAGGING => {
+                let ids: Vec<i64> = serde_json::from_value(batch.requests[&custom_id].clone()).unwrap_or_default();
+                let Some(input) = tagging::submitted_tags(&response) else { continue };
+                for (id, tags) in tagging::parse_tags(input, &ids) {
+                    match state.store.tag_message(id, tags).await {
+                        Ok(()) => tagged += 1,
+                        Err(e) => tracing::warn!("Tagging: could not save tags for message {}: {}", id, e),
+                    }
+                }
+            }
+            other => tracing::warn!("LLM batch {}: unknown request kind {}", batch.id, other),
+        }
+    }
+
+    if tagged > 0 {
+        tracing::debug!("🏷️ Tagged {} messages from batch {}", tagged, batch.id);
+    }
+    if learned && state.canary.enabled() {
+        state.canary.propose(before, agent_os);
+    } else if learned {
+        match agent_os.save() {
+            Ok(()) => {
+                tracing::info!("🧠 Brain evolved from batched reflections");
+                crate::sync::push_in_background(state.config);
+            }
+            Err(e) => tracing::warn!("Could not save batched reflections: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::SqliteStore;
+
+    #[tokio::test]
+    async fn test_in_flight_covers_queue_and_open_batches() {
+        let path = std::env::temp_dir().join(format!("oneclaw-batch-test-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = SqliteStore::new(path.clone()).await.unwrap();
+        let queue = BatchQueue::default();
+        assert_eq!(kind_of("tagging-12"), TAGGING);
+
+        assert!(!queue.in_flight(&store, TAGGING).await.unwrap());
+        queue.push(TAGGING, serde_json::json!({}), serde_json::json!([1, 2]));
+        assert!(queue.in_flight(&store, TAGGING).await.unwrap());
+        assert!(!queue.in_flight(&store, REFLECTION).await.unwrap());
+
+        let queued = queue.take();
+        let batch = LlmBatch {
+            id: "msgbatch_1".to_string(),
+            status: "in_progress".to_string(),
+            requests: serde_json::json!({ "tagging-0": queued[0].context }),
+            submitted_at: Utc::now(),
+            ended_at: None,
+        };
+        store.add_llm_batch(&batch).await.unwrap();
+        assert!(queue.in_flight(&store, TAGGING).await.unwrap());
+        assert_eq!(store.open_llm_batches().await.unwrap()[0].requests["tagging-0"], serde_json::json!([1, 2]));
+
+        store.end_llm_batch("msgbatch_1", "ended").await.unwrap();
+        assert!(store.open_llm_batches().await.unwrap().is_empty());
+        assert!(!queue.in_flight(&store, TAGGING).await.unwrap());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,285 @@
+//! Bootstrap - A first-run interview that drafts the brain
+//!
+//! Until the workspace has a SOUL.md, the node runs on the generic template
+//! persona. With `bootstrap.enabled`, the first message an owner
+//! (`security.owners`) sends in that state starts an interview instead: the
+//! agent asks about the business, its customers, the tone to take, its goals
+//! and what it must never do, one question per message.
+//!
+//! From the answers, one LLM call drafts SOUL.md, IDENTITY.md and SKILLS.md,
+//! built on the current pillars. The drafts wait for review next to the
+//! workspace (`<workspace>.bootstrap/`) and nothing changes until the owner
+//! approves them:
+//! - `/persona show` - the drafts
+//! - `/persona approve` - save them as the workspace brain and use them from
+//!   the next turn on
+//! - `/persona redo` - start the interview over
+//! - `/persona skip` - keep the defaults and don't ask again
+//!
+//! `/persona` starts the interview any time, e.g. to redo a node's persona.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::agent_os::{self, AgentOS};
+use crate::daemon::AppState;
+
+/// Asked in order; the answers go into the drafting prompt under these topics
+const QUESTIONS: &[(&str, &str)] = &[
+    ("Business", "What's your business, and what do you sell or offer?"),
+    ("Customers", "Who are your customers? Who should I be talking to, or looking for?"),
+    ("Tone", "How should I sound? For example friendly and casual, formal, or short and direct."),
+    ("Goals", "What should I help you get done over the next few months?"),
+    ("Boundaries", "Anything I must never do or say? Topics, promises, times to stay quiet."),
+];
+
+/// The pillars the interview drafts, with their files
+const DRAFTED: &[(&str, &str)] = &[("soul", "SOUL.md"), ("identity", "IDENTITY.md"), ("skills", "SKILLS.md")];
+
+const INTERVIEW_FILE: &str = "interview.json";
+
+const MAX_TOKENS: u32 = 8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Asking,
+    Drafted,
+    Approved,
+    Skipped,
+}
+
+/// An interview in progress or done, kept across restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interview {
+    /// `<provider>:<id>` of the owner being interviewed
+    owner: String,
+    answers: Vec<String>,
+    status: Status,
+}
+
+impl Interview {
+    fn new(owner: &str) -> Self {
+        Self { owner: owner.to_string(), answers: vec![], status: Status::Asking }
+    }
+
+    /// Take the answer to the current question; returns the next question, or
+    /// None once every question is answered
+    fn answer(&mut self, text: &str) -> Option<&'static str> {
+        self.answers.push(text.trim().to_string());
+        QUESTIONS.get(self.answers.len()).map(|(_, question)| *question)
+    }
+}
+
+/// Where the interview and the drafts are kept: next to the workspace, so the
+/// workspace itself isn't created before the brain is approved
+fn drafts_dir(workspace: &Path) -> PathBuf {
+    workspace.with_extension("bootstrap")
+}
+
+fn load(dir: &Path) -> Option<Interview> {
+    let text = std::fs::read_to_string(dir.join(INTERVIEW_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn save(dir: &Path, interview: &Interview) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(INTERVIEW_FILE), serde_json::to_string_pretty(interview)?)?;
+    Ok(())
+}
+
+fn start(dir: &Path, owner: &str) -> anyhow::Result<String> {
+    save(dir, &Interview::new(owner))?;
+    Ok(format!(
+        "👋 Let's make me yours. {} short questions about your business, then I'll draft my persona for you to review \
+         (/persona skip to keep the defaults).\n\n1/{}: {}",
+        QUESTIONS.len(),
+        QUESTIONS.len(),
+        QUESTIONS[0].1
+    ))
+}
+
+/// The owner's answers as prompt text
+fn brief(answers: &[String]) -> String {
+    QUESTIONS
+        .iter()
+        .zip(answers)
+        .map(|((topic, question), answer)| format!("## {}\nQ: {}\nA: {}", topic, question, answer))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Draft the pillars from the interview; returns them in `DRAFTED` order
+async fn draft(state: &AppState, answers: &[String]) -> anyhow::Result<Vec<String>> {
+    let current = state.canary.current(&state.agent_os);
+    let prompt = format!(
+        "You are setting up an AI agent for a business owner. Rewrite the agent's persona files for their business, \
+         using the interview below.\n\n\
+         Keep the operational parts of the current files (how tools, the harness and jobs work) unless they contradict \
+         the interview, and replace the generic persona: name, role, who it serves, tone, goals and hard rules. \
+         Write Markdown with the same kind of headings. Don't invent facts about the business.\n\n\
+         # Interview\n\n{}\n\n# Current SOUL.md\n\n{}\n\n# Current IDENTITY.md\n\n{}\n\n# Current SKILLS.md\n\n{}",
+        brief(answers),
+        current.soul,
+        current.identity,
+        current.skills,
+    );
+    let tool = json!({
+        "name": "submit_persona",
+        "description": "Submit the drafted persona files.",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "soul": { "type": "string", "description": "SOUL.md: purpose, principles, hard rules" },
+                "identity": { "type": "string", "description": "IDENTITY.md: name, role, personality, communication style" },
+                "skills": { "type": "string", "description": "SKILLS.md: what the agent does for this business and how" }
+            },
+            "required": ["soul", "identity", "skills"]
+        }
+    });
+    let messages = vec![json!({ "role": "user", "content": prompt })];
+    let input = state.router
+        .call_tool(crate::model_router::MAIN, &state.config.llm, &messages, MAX_TOKENS, &tool)
+        .await?;
+
+    DRAFTED
+        .iter()
+        .map(|(pillar, _)| match input[*pillar].as_str().map(str::trim) {
+            Some(text) if !text.is_empty() => Ok(format!("{}\n", text)),
+            _ => anyhow::bail!("The draft has no {}", pillar),
+        })
+        .collect()
+}
+
+fn read_drafts(dir: &Path) -> anyhow::Result<Vec<String>> {
+    DRAFTED
+        .iter()
+        .map(|(_, file)| std::fs::read_to_string(dir.join(file)).map_err(|e| anyhow::anyhow!("No {} draft: {}", file, e)))
+        .collect()
+}
+
+/// The last answer is in: draft the pillars and ask for review
+async fn finish(state: &AppState, dir: &Path, interview: &mut Interview) -> anyhow::Result<String> {
+    let drafts = draft(state, &interview.answers).await?;
+    for ((_, file), text) in DRAFTED.iter().zip(&drafts) {
+        std::fs::write(dir.join(file), text)?;
+    }
+    interview.status = Status::Drafted;
+    save(dir, interview)?;
+    let identity = drafts[1].lines().take(12).collect::<Vec<_>>().join("\n");
+    Ok(format!(
+        "✍️ Thanks! I drafted my SOUL, IDENTITY and SKILLS from your answers. Here's who I'd be:\n\n{}\n\n\
+         /persona show for the full drafts, /persona approve to start using them, /persona redo to start over.",
+        identity
+    ))
+}
+
+/// Save the drafts as the workspace brain and use it from the next turn
+fn approve(state: &AppState, dir: &Path, workspace: &Path, interview: &mut Interview) -> anyhow::Result<String> {
+    let drafts = read_drafts(dir)?;
+    let current = state.canary.current(&state.agent_os);
+    let brain = AgentOS {
+        soul: drafts[0].clone(),
+        identity: drafts[1].clone(),
+        skills: drafts[2].clone(),
+        dir: Some(workspace.to_path_buf()),
+        ..current
+    };
+    brain.save()?;
+    state.canary.adopt(brain);
+    crate::sync::push_in_background(state.config);
+    interview.status = Status::Approved;
+    save(dir, interview)?;
+    Ok(format!("✅ Persona saved to {}. I'll use it from your next message.", workspace.display()))
+}
+
+/// Reply to an owner's (`owner` is `<provider>:<id>`) message while the brain is
+/// being bootstrapped; None when the message is a regular turn
+pub async fn reply(state: &AppState, owner: &str, text: &str) -> Option<String> {
+    let workspace = agent_os::workspace_for(state.config)?;
+    let dir = drafts_dir(&workspace);
+    let interview = load(&dir);
+    let command = text.trim();
+
+    let result = if let Some(action) = command.strip_prefix("/persona") {
+        let action = action.trim();
+        match (action, interview) {
+            ("" | "redo", _) => start(&dir, owner),
+            ("skip" | "cancel", interview) => {
+                let mut interview = interview.unwrap_or_else(|| Interview::new(owner));
+                interview.status = Status::Skipped;
+                save(&dir, &interview).map(|_| "👍 Keeping the current persona. /persona starts the interview any time.".to_string())
+            }
+            ("show", Some(interview)) if interview.status == Status::Drafted => read_drafts(&dir).map(|drafts| {
+                DRAFTED
+                    .iter()
+                    .zip(drafts)
+                    .map(|((_, file), text)| format!("📄 {}\n\n{}", file, text.trim()))
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            }),
+            ("approve", Some(mut interview)) if interview.status == Status::Drafted => {
+                approve(state, &dir, &workspace, &mut interview)
+            }
+            ("show" | "approve", _) => Ok("No persona drafts to review. /persona starts the interview.".to_string()),
+            _ => Ok("Usage: /persona [show | approve | redo | skip]".to_string()),
+        }
+    } else {
+        match interview {
+            Some(mut interview) if interview.status == Status::Asking && interview.owner == owner => {
+                match interview.answer(command) {
+                    Some(question) => save(&dir, &interview)
+                        .map(|_| format!("{}/{}: {}", interview.answers.len() + 1, QUESTIONS.len(), question)),
+                    None => {
+                        finish(state, &dir, &mut interview).await.map_err(|e| {
+                            // Keep the answers; the last one is taken again on retry
+                            interview.answers.pop();
+                            let _ = save(&dir, &interview);
+                            anyhow::anyhow!("{}. Send your last answer again to retry", e)
+                        })
+                    }
+                }
+            }
+            // First run: no brain of its own yet and no interview so far
+            None if state.config.bootstrap.enabled && !workspace.join("SOUL.md").exists() => start(&dir, owner),
+            _ => return None,
+        }
+    };
+
+    Some(result.unwrap_or_else(|e| {
+        tracing::warn!("Persona bootstrap failed: {}", e);
+        format!("❌ Persona setup failed: {}", e)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interview_asks_in_order_and_persists() {
+        let mut interview = Interview::new("telegram:42");
+        assert_eq!(interview.answer("Apex Roofing, residential roofs in Austin"), Some(QUESTIONS[1].1));
+        for _ in 2..QUESTIONS.len() {
+            assert!(interview.answer("answer").is_some());
+        }
+        assert_eq!(interview.answer(" never quote prices "), None);
+        assert_eq!(interview.answers.len(), QUESTIONS.len());
+
+        let brief = brief(&interview.answers);
+        assert!(brief.starts_with("## Business\nQ: What's your business"));
+        assert!(brief.ends_with("## Boundaries\nQ: Anything I must never do or say? Topics, promises, times to stay quiet.\nA: never quote prices"));
+
+        let workspace = std::env::temp_dir().join(format!("oneclaw-bootstrap-{}", nanoid::nanoid!())).join("workspace");
+        let dir = drafts_dir(&workspace);
+        assert_eq!(dir.file_name().unwrap(), "workspace.bootstrap");
+        save(&dir, &interview).unwrap();
+        let loaded = load(&dir).unwrap();
+        assert_eq!((loaded.owner.as_str(), loaded.status), ("telegram:42", Status::Asking));
+        // The workspace itself isn't created
+        assert!(!workspace.exists());
+        let _ = std::fs::remove_dir_all(workspace.parent().unwrap());
+    }
+}
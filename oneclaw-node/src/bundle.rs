@@ -0,0 +1,282 @@
+//! Node Bundle - The complete node state in one encrypted file
+//!
+//! `oneclaw export-bundle <file>` collects:
+//! - `node.yaml`, with `control_plane.token` removed (other secrets live in env vars)
+//! - The Agent OS workspace: SOUL.md, IDENTITY.md, ... and template overrides
+//!   (generated reports are left out)
+//! - Preferences (territories, custom settings)
+//! - A consistent snapshot of the SQLite store
+//! - Workflows in `~/.oneclaw/workflows`
+//!
+//! and seals them with the brain sync passphrase (`sync.passphrase_env`).
+//! `oneclaw import-bundle <file>` puts every file back where the bundled config
+//! says it belongs. Env secrets (`.env`) are never bundled, so copy them separately.
+
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::{agent_os, config, crypto, workflow};
+
+/// `format` of bundle files this version writes and reads
+const FORMAT: &str = "oneclaw-bundle/v1";
+
+/// Where a bundled file goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    Config,
+    Workspace,
+    Preferences,
+    Store,
+    Workflows,
+}
+
+impl Section {
+    fn name(&self) -> &'static str {
+        match self {
+            Section::Config => "config",
+            Section::Workspace => "workspace",
+            Section::Preferences => "preferences",
+            Section::Store => "store",
+            Section::Workflows => "workflows",
+        }
+    }
+}
+
+/// One file in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    pub section: Section,
+    /// `/`-separated path inside the section's directory (empty for single-file sections)
+    #[serde(default)]
+    pub path: String,
+    /// Base64 contents
+    pub contents: String,
+}
+
+/// Plaintext contents of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeBundle {
+    pub node_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub files: Vec<BundleFile>,
+}
+
+impl NodeBundle {
+    /// File counts per section, e.g. "config 1, workspace 6, store 1"
+    pub fn summary(&self) -> String {
+        let sections = [Section::Config, Section::Workspace, Section::Preferences, Section::Store, Section::Workflows];
+        sections
+            .iter()
+            .map(|s| (s, self.files.iter().filter(|f| f.section == *s).count()))
+            .filter(|(_, count)| *count > 0)
+            .map(|(s, count)| format!("{} {}", s.name(), count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// What's written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SealedBundle {
+    format: String,
+    node_id: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    blob: crypto::SealedBlob,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+/// Where a bundled file is restored to, per `config`
+fn target(config: &config::NodeConfig, file: &BundleFile) -> anyhow::Result<PathBuf> {
+    let dir = match file.section {
+        Section::Config => return config::config_path(),
+        Section::Preferences => return Ok(config::expand_path(&config.memory.preferences_path)),
+        Section::Store => return Ok(config::expand_path(&config.store.sqlite_path)),
+        Section::Workspace => agent_os::workspace_for(config)
+            .ok_or_else(|| anyhow::anyhow!("No workspace directory"))?,
+        Section::Workflows => workflow::workflows_dir(),
+    };
+    Ok(dir.join(relative_path(&file.path)?))
+}
+
+/// A bundled path as a relative path; bundles from elsewhere must not write outside their section
+fn relative_path(path: &str) -> anyhow::Result<PathBuf> {
+    let relative = PathBuf::from_iter(path.split('/').filter(|p| !p.is_empty()));
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        anyhow::bail!("Bundle path escapes its directory: {}", path);
+    }
+    Ok(relative)
+}
+
+/// node.yaml without `control_plane.token`
+fn strip_secrets(yaml: &str) -> anyhow::Result<String> {
+    let mut value: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+    if let Some(control_plane) = value.get_mut("control_plane").and_then(|c| c.as_mapping_mut()) {
+        control_plane.remove("token");
+    }
+    Ok(serde_yaml::to_string(&value)?)
+}
+
+/// Carry this node's `control_plane.token` over into an imported node.yaml
+fn keep_token(imported: &str, existing: &str) -> anyhow::Result<String> {
+    let existing: serde_yaml::Value = serde_yaml::from_str(existing)?;
+    let Some(token) = existing.get("control_plane").and_then(|c| c.get("token")).cloned() else {
+        return Ok(imported.to_string());
+    };
+    let mut value: serde_yaml::Value = serde_yaml::from_str(imported)?;
+    if let Some(control_plane) = value.get_mut("control_plane").and_then(|c| c.as_mapping_mut()) {
+        control_plane.insert("token".into(), token);
+    }
+    Ok(serde_yaml::to_string(&value)?)
+}
+
+/// Add every file under `dir` (except the `skip` subdirectories) to `files`
+fn collect_dir(section: Section, dir: &Path, prefix: &str, skip: &[&str], files: &mut Vec<BundleFile>) -> anyhow::Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = if prefix.is_empty() { name.clone() } else { format!("{}/{}", prefix, name) };
+        if entry.file_type()?.is_dir() {
+            if !skip.contains(&path.as_str()) {
+                collect_dir(section, &entry.path(), &path, skip, files)?;
+            }
+        } else {
+            files.push(BundleFile { section, path, contents: b64().encode(std::fs::read(entry.path())?) });
+        }
+    }
+    Ok(())
+}
+
+/// Snapshot the SQLite store (safe while the daemon is running)
+fn store_snapshot(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let snapshot = std::env::temp_dir().join(format!("oneclaw-bundle-{}.db", nanoid::nanoid!()));
+    let conn = rusqlite::Connection::open(path)?;
+    conn.execute("VACUUM INTO ?1", [snapshot.to_string_lossy()])?;
+    let bytes = std::fs::read(&snapshot);
+    let _ = std::fs::remove_file(&snapshot);
+    Ok(bytes?)
+}
+
+/// Collect the node's state and write it to `out`, sealed with `passphrase`
+pub fn export(config: &config::NodeConfig, out: &Path, passphrase: &str) -> anyhow::Result<NodeBundle> {
+    let (bundle, sealed) = seal(config, passphrase)?;
+    std::fs::write(out, sealed)?;
+    tracing::info!("📦 Exported node {} to {}", bundle.node_id, out.display());
+    Ok(bundle)
+}
+
+/// Collect the node's state as a sealed bundle file's contents (what `export` writes)
+pub fn seal(config: &config::NodeConfig, passphrase: &str) -> anyhow::Result<(NodeBundle, Vec<u8>)> {
+    let raw_config = std::fs::read_to_string(config::config_path()?)?;
+    let mut files = vec![BundleFile {
+        section: Section::Config,
+        path: String::new(),
+        contents: b64().encode(strip_secrets(&raw_config)?),
+    }];
+
+    if let Some(workspace) = agent_os::workspace_for(config) {
+        collect_dir(Section::Workspace, &workspace, "", &["reports"], &mut files)?;
+    }
+
+    let preferences = config::expand_path(&config.memory.preferences_path);
+    if preferences.exists() {
+        files.push(BundleFile { section: Section::Preferences, path: String::new(), contents: b64().encode(std::fs::read(&preferences)?) });
+    }
+
+    let store = config::expand_path(&config.store.sqlite_path);
+    if config.store.store_type == "hosted" {
+        tracing::info!("Store is hosted; its data stays with the control plane");
+    } else if config.store.store_type == "postgres" {
+        tracing::info!("Store is Postgres; its data stays in the database");
+    } else if store.exists() {
+        files.push(BundleFile { section: Section::Store, path: String::new(), contents: b64().encode(store_snapshot(&store)?) });
+    }
+
+    collect_dir(Section::Workflows, &workflow::workflows_dir(), "", &[], &mut files)?;
+
+    let bundle = NodeBundle { node_id: config.node.id.clone(), created_at: chrono::Utc::now(), files };
+    let sealed = SealedBundle {
+        format: FORMAT.to_string(),
+        node_id: bundle.node_id.clone(),
+        created_at: bundle.created_at,
+        blob: crypto::seal_with_passphrase(passphrase, &serde_json::to_vec(&bundle)?)?,
+    };
+    let sealed = serde_json::to_vec(&sealed)?;
+    Ok((bundle, sealed))
+}
+
+/// Restore a bundle written by `export`. Existing files are only replaced with `force`;
+/// this node's `control_plane.token` is kept.
+pub fn import(path: &Path, passphrase: &str, force: bool) -> anyhow::Result<NodeBundle> {
+    let sealed: SealedBundle = serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|e| anyhow::anyhow!("Not a OneClaw bundle: {}", e))?;
+    if sealed.format != FORMAT {
+        anyhow::bail!("Unsupported bundle format {} (expected {})", sealed.format, FORMAT);
+    }
+    let plaintext = crypto::open_with_passphrase(passphrase, &sealed.blob)
+        .map_err(|_| anyhow::anyhow!("Could not decrypt bundle (wrong passphrase?)"))?;
+    let bundle: NodeBundle = serde_json::from_slice(&plaintext)?;
+
+    // Files go where the bundled config puts them
+    let config_file = bundle.files.iter().find(|f| f.section == Section::Config)
+        .ok_or_else(|| anyhow::anyhow!("Bundle has no config"))?;
+    let bundled_config = String::from_utf8(b64().decode(&config_file.contents)?)?;
+    let node_config: config::NodeConfig = serde_yaml::from_str(&bundled_config)?;
+
+    let targets = bundle.files.iter()
+        .map(|f| target(&node_config, f))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let existing: Vec<String> = targets.iter().filter(|t| t.exists()).map(|t| t.display().to_string()).collect();
+    if !force && !existing.is_empty() {
+        anyhow::bail!("Import would overwrite {} (pass --force to replace them)", existing.join(", "));
+    }
+
+    for (file, target) in bundle.files.iter().zip(&targets) {
+        let mut contents = b64().decode(&file.contents)?;
+        if file.section == Section::Config {
+            if let Ok(current) = std::fs::read_to_string(target) {
+                contents = keep_token(&bundled_config, &current)?.into_bytes();
+            }
+        }
+        if file.section == Section::Store {
+            // A leftover write-ahead log belongs to the replaced database
+            for suffix in ["-wal", "-shm"] {
+                let mut sidecar = target.as_os_str().to_owned();
+                sidecar.push(suffix);
+                let _ = std::fs::remove_file(sidecar);
+            }
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, contents)?;
+    }
+
+    tracing::info!("📦 Imported node {} (bundle {})", bundle.node_id, bundle.created_at.to_rfc3339());
+    Ok(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_paths_and_secrets() {
+        assert_eq!(relative_path("templates/outreach.md").unwrap(), PathBuf::from("templates").join("outreach.md"));
+        assert!(relative_path("../.ssh/authorized_keys").is_err());
+
+        let yaml = "node:\n  id: abc\ncontrol_plane:\n  url: https://cp\n  token: secret\n";
+        let stripped = strip_secrets(yaml).unwrap();
+        assert!(!stripped.contains("secret"));
+        assert!(keep_token(&stripped, yaml).unwrap().contains("token: secret"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Calendar - Availability-checked follow-up scheduling
+//!
+//! Proposes and books follow-up slots ("schedule a call with Smith HVAC Thursday
+//! 2-4pm") on the owner's Google Calendar. Free/busy is checked before anything is
+//! booked; the event carries popup/email reminders so the owner is nudged before
+//! the call.
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, Timelike};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::CalendarConfig;
+
+const CALENDAR_API: &str = "https://www.googleapis.com/calendar/v3";
+
+/// A candidate follow-up slot
+#[derive(Debug, Clone, Serialize)]
+pub struct Slot {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+}
+
+/// Busy intervals on the owner's calendar within a window
+pub async fn busy_intervals(
+    token: &str,
+    calendar_id: &str,
+    window_start: DateTime<FixedOffset>,
+    window_end: DateTime<FixedOffset>,
+) -> anyhow::Result<Vec<Slot>> {
+    let resp = reqwest::Client::new()
+        .post(format!("{}/freeBusy", CALENDAR_API))
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "timeMin": window_start.to_rfc3339(),
+            "timeMax": window_end.to_rfc3339(),
+            "items": [{ "id": calendar_id }],
+        }))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Calendar API error {}: {}", resp.status(), resp.text().await.unwrap_or_default());
+    }
+    let data: Value = resp.json().await?;
+
+    let offset = *window_start.offset();
+    Ok(data["calendars"][calendar_id]["busy"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|b| {
+            let start = DateTime::parse_from_rfc3339(b["start"].as_str()?).ok()?;
+            let end = DateTime::parse_from_rfc3339(b["end"].as_str()?).ok()?;
+            Some(Slot { start: start.with_timezone(&offset), end: end.with_timezone(&offset) })
+        })
+        .collect())
+}
+
+/// Free slots of `duration` inside the window, on a 15-minute grid, within working hours
+pub fn free_slots(
+    window_start: DateTime<FixedOffset>,
+    window_end: DateTime<FixedOffset>,
+    duration: Duration,
+    busy: &[Slot],
+    config: &CalendarConfig,
+    max: usize,
+) -> Vec<Slot> {
+    let day_start = NaiveTime::parse_from_str(&config.working_hours_start, "%H:%M").ok();
+    let day_end = NaiveTime::parse_from_str(&config.working_hours_end, "%H:%M").ok();
+
+    let mut slots = vec![];
+    let mut cursor = round_up_quarter(window_start);
+    while cursor + duration <= window_end && slots.len() < max {
+        let end = cursor + duration;
+        let in_hours = match (day_start, day_end) {
+            (Some(open), Some(close)) => cursor.time() >= open && end.time() <= close && end.date_naive() == cursor.date_naive(),
+            _ => true,
+        };
+        let clashes = busy.iter().any(|b| cursor < b.end && end > b.start);
+        if in_hours && !clashes {
+            slots.push(Slot { start: cursor, end });
+            // Spread proposals out instead of offering back-to-back slots
+            cursor = end;
+        } else {
+            cursor += Duration::minutes(15);
+        }
+    }
+    slots
+}
+
+/// Create the follow-up event with reminders, returning the event JSON
+pub async fn book(
+    token: &str,
+    calendar_id: &str,
+    slot: &Slot,
+    summary: &str,
+    description: Option<&str>,
+    attendee_email: Option<&str>,
+    reminder_minutes: u32,
+) -> anyhow::Result<Value> {
+    let mut event = serde_json::json!({
+        "summary": summary,
+        "start": { "dateTime": slot.start.to_rfc3339() },
+        "end": { "dateTime": slot.end.to_rfc3339() },
+        "reminders": {
+            "useDefault": false,
+            "overrides": [
+                { "method": "popup", "minutes": reminder_minutes },
+                { "method": "email", "minutes": reminder_minutes },
+            ],
+        },
+    });
+    if let Some(d) = description {
+        event["description"] = Value::String(d.to_string());
+    }
+    if let Some(email) = attendee_email {
+        event["attendees"] = serde_json::json!([{ "email": email }]);
+    }
+
+    let resp = reqwest::Client::new()
+        .post(format!("{}/calendars/{}/events", CALENDAR_API, urlencoding(calendar_id)))
+        .bearer_auth(token)
+        .json(&event)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Calendar API error {}: {}", resp.status(), resp.text().await.unwrap_or_default());
+    }
+    Ok(resp.json().await?)
+}
+
+/// Parse an RFC 3339 timestamp from tool input
+pub fn parse_time(value: &Value, field: &str) -> anyhow::Result<DateTime<FixedOffset>> {
+    let raw = value[field]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("{} required (RFC 3339, e.g. 2026-03-05T14:00:00-07:00)", field))?;
+    DateTime::parse_from_rfc3339(raw).map_err(|e| anyhow::anyhow!("Invalid {}: {}", field, e))
+}
+
+fn round_up_quarter(t: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    let t = t.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(t);
+    match t.minute() % 15 {
+        0 => t,
+        m => t + Duration::minutes((15 - m) as i64),
+    }
+}
+
+fn urlencoding(s: &str) -> String {
+    url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
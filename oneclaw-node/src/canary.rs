@@ -0,0 +1,305 @@
+//! Canary - Trying learned brain updates on part of the traffic first
+//!
+//! With `canary.enabled`, a learning update isn't saved right away. The updated
+//! brain (the candidate) goes on trial: most turns use it, while
+//! `canary.control_share` of them still get the pillars from before the update
+//! (the control). Each turn's outcome is counted for its arm:
+//! - task success: a reply, and no tool call that ended in an error
+//! - feedback: when the user's next message complains about the answer
+//!   ("that's wrong", "didn't work", ...), the turn before it counts as failed
+//!
+//! Once both arms have `canary.min_turns` turns, their success rates are
+//! compared. If the candidate does more than `canary.max_regression` worse, the
+//! update is reverted: it is dropped, and the saved brain never changed.
+//! Otherwise it is adopted: saved, and used for every turn from then on. An
+//! update arriving during a trial becomes the new candidate and counting starts over.
+//!
+//! `GET /canary` shows the trial and the last decision.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::agent_os::AgentOS;
+use crate::config::CanaryConfig;
+
+/// Phrases in a user's next message that mean the last answer missed
+const COMPLAINTS: &[&str] = &[
+    "that's wrong",
+    "that is wrong",
+    "that's not right",
+    "that's not what i",
+    "not what i asked",
+    "didn't work",
+    "doesn't work",
+    "did not work",
+    "wrong answer",
+    "you misunderstood",
+    "that's incorrect",
+    "useless",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arm {
+    /// The pillars from before the update
+    Control,
+    /// The updated pillars
+    Candidate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Adopted,
+    Reverted,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ArmStats {
+    pub turns: u32,
+    pub successes: u32,
+}
+
+impl ArmStats {
+    pub fn success_rate(&self) -> f64 {
+        if self.turns == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.turns as f64
+    }
+}
+
+/// How a trial ended
+#[derive(Debug, Clone, Serialize)]
+pub struct Decision {
+    pub at: DateTime<Utc>,
+    pub verdict: Verdict,
+    pub control: ArmStats,
+    pub candidate: ArmStats,
+}
+
+/// Shown on `GET /canary`
+#[derive(Debug, Clone, Serialize)]
+pub struct CanaryStatus {
+    pub enabled: bool,
+    pub trial: Option<TrialStatus>,
+    pub last_decision: Option<Decision>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialStatus {
+    pub started_at: DateTime<Utc>,
+    pub control: ArmStats,
+    pub candidate: ArmStats,
+    /// Turns each arm needs before the trial is decided
+    pub min_turns: u32,
+}
+
+struct Trial {
+    started_at: DateTime<Utc>,
+    control_brain: AgentOS,
+    candidate_brain: AgentOS,
+    control: ArmStats,
+    candidate: ArmStats,
+    /// Each user's last turn in the trial: its arm and whether it counted as a success
+    last_turns: HashMap<String, (Arm, bool)>,
+}
+
+impl Trial {
+    fn stats(&mut self, arm: Arm) -> &mut ArmStats {
+        match arm {
+            Arm::Control => &mut self.control,
+            Arm::Candidate => &mut self.candidate,
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// The brain adopted since startup, if any
+    live: Option<AgentOS>,
+    trial: Option<Trial>,
+    last_decision: Option<Decision>,
+}
+
+pub struct Canary {
+    config: CanaryConfig,
+    state: Mutex<State>,
+}
+
+/// Whether `message` complains about the answer before it
+pub fn is_complaint(message: &str) -> bool {
+    let message = message.to_lowercase();
+    COMPLAINTS.iter().any(|phrase| message.contains(phrase))
+}
+
+/// The verdict once both arms have `min_turns` turns (None until then)
+pub fn verdict(config: &CanaryConfig, control: &ArmStats, candidate: &ArmStats) -> Option<Verdict> {
+    if control.turns < config.min_turns || candidate.turns < config.min_turns {
+        return None;
+    }
+    if candidate.success_rate() < control.success_rate() - config.max_regression {
+        Some(Verdict::Reverted)
+    } else {
+        Some(Verdict::Adopted)
+    }
+}
+
+impl Canary {
+    pub fn new(config: &CanaryConfig) -> Self {
+        Self { config: config.clone(), state: Mutex::new(State::default()) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// The brain learning builds on: the candidate during a trial, else the live
+    /// one (`base`, the brain loaded at startup, until an update is adopted)
+    pub fn current(&self, base: &AgentOS) -> AgentOS {
+        let state = self.state.lock().unwrap();
+        match (&state.trial, &state.live) {
+            (Some(trial), _) => trial.candidate_brain.clone(),
+            (None, Some(live)) => live.clone(),
+            (None, None) => base.clone(),
+        }
+    }
+
+    /// The brain a turn uses, with its arm while a trial runs
+    pub fn brain_for_turn(&self, base: &AgentOS) -> (AgentOS, Option<Arm>) {
+        let state = self.state.lock().unwrap();
+        let Some(trial) = &state.trial else {
+            return (state.live.clone().unwrap_or_else(|| base.clone()), None);
+        };
+        if rand::thread_rng().gen_bool(self.config.control_share.clamp(0.0, 1.0)) {
+            (trial.control_brain.clone(), Some(Arm::Control))
+        } else {
+            (trial.candidate_brain.clone(), Some(Arm::Candidate))
+        }
+    }
+
+    /// Use `brain` for every turn from now on, without a trial (an update the
+    /// owner reviewed, see `bootstrap.rs`); a running trial is dropped
+    pub fn adopt(&self, brain: AgentOS) {
+        let mut state = self.state.lock().unwrap();
+        state.trial = None;
+        state.live = Some(brain);
+    }
+
+    /// Put a learning update on trial: `before` is the brain it was made from
+    pub fn propose(&self, before: AgentOS, candidate: AgentOS) {
+        let mut state = self.state.lock().unwrap();
+        // A trial already running keeps its control: the candidate builds on the last one
+        let control_brain = state.trial.take().map_or(before, |trial| trial.control_brain);
+        state.trial = Some(Trial {
+            started_at: Utc::now(),
+            control_brain,
+            candidate_brain: candidate,
+            control: ArmStats::default(),
+            candidate: ArmStats::default(),
+            last_turns: HashMap::new(),
+        });
+        tracing::info!("🐤 Brain update on trial ({:.0}% of turns keep the old pillars)", self.config.control_share * 100.0);
+    }
+
+    /// A new message from `user_id`: a complaint fails their last trial turn
+    pub fn feedback(&self, user_id: &str, message: &str) {
+        let mut state = self.state.lock().unwrap();
+        let Some(trial) = state.trial.as_mut() else { return };
+        let Some((arm, succeeded)) = trial.last_turns.remove(user_id) else { return };
+        if succeeded && is_complaint(message) {
+            let stats = trial.stats(arm);
+            stats.successes = stats.successes.saturating_sub(1);
+        }
+    }
+
+    /// Count a finished turn for `arm`; decides the trial once both arms have
+    /// enough turns, saving the candidate when it is adopted
+    pub fn record(&self, user_id: &str, arm: Arm, success: bool) -> Option<Decision> {
+        let mut state = self.state.lock().unwrap();
+        let trial = state.trial.as_mut()?;
+        let stats = trial.stats(arm);
+        stats.turns += 1;
+        stats.successes += u32::from(success);
+        trial.last_turns.insert(user_id.to_string(), (arm, success));
+
+        let verdict = verdict(&self.config, &trial.control, &trial.candidate)?;
+        let trial = state.trial.take()?;
+        let decision = Decision { at: Utc::now(), verdict, control: trial.control, candidate: trial.candidate };
+        match verdict {
+            Verdict::Adopted => {
+                if let Err(e) = trial.candidate_brain.save() {
+                    tracing::warn!("Could not save the adopted brain update: {}", e);
+                }
+                state.live = Some(trial.candidate_brain);
+            }
+            Verdict::Reverted => state.live = Some(trial.control_brain),
+        }
+        state.last_decision = Some(decision.clone());
+        Some(decision)
+    }
+
+    pub fn status(&self) -> CanaryStatus {
+        let state = self.state.lock().unwrap();
+        CanaryStatus {
+            enabled: self.config.enabled,
+            trial: state.trial.as_ref().map(|trial| TrialStatus {
+                started_at: trial.started_at,
+                control: trial.control.clone(),
+                candidate: trial.candidate.clone(),
+                min_turns: self.config.min_turns,
+            }),
+            last_decision: state.last_decision.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brain(memory: &str) -> AgentOS {
+        AgentOS {
+            soul: String::new(),
+            identity: String::new(),
+            skills: String::new(),
+            playbooks: String::new(),
+            memory: memory.to_string(),
+            dir: None,
+        }
+    }
+
+    #[test]
+    fn test_trial_reverts_a_regression() {
+        let config: CanaryConfig = serde_yaml::from_str("enabled: true\ncontrol_share: 0.5\nmin_turns: 4\nmax_regression: 0.1").unwrap();
+        let canary = Canary::new(&config);
+        let base = brain("old");
+        let (turn_brain, arm) = canary.brain_for_turn(&base);
+        assert_eq!((turn_brain.memory.as_str(), arm), ("old", None));
+
+        canary.propose(base.clone(), brain("new"));
+        assert_eq!(canary.current(&base).memory, "new");
+        for i in 0..4 {
+            assert!(canary.record(&format!("c{}", i), Arm::Control, true).is_none());
+        }
+        for i in 0..3 {
+            assert!(canary.record(&format!("u{}", i), Arm::Candidate, true).is_none());
+        }
+        // "that's wrong" fails u0's last turn: the candidate drops to 3/4
+        canary.feedback("u0", "No, that's wrong - I asked for Austin");
+        let decision = canary.record("u3", Arm::Candidate, true).unwrap();
+        assert_eq!(decision.verdict, Verdict::Reverted);
+        assert_eq!((decision.candidate.turns, decision.candidate.successes), (4, 3));
+        assert_eq!(canary.brain_for_turn(&base).0.memory, "old");
+        assert!(canary.status().trial.is_none());
+
+        let even = ArmStats { turns: 4, successes: 4 };
+        assert_eq!(verdict(&config, &even, &ArmStats { turns: 4, successes: 4 }), Some(Verdict::Adopted));
+        assert_eq!(verdict(&config, &even, &ArmStats { turns: 3, successes: 3 }), None);
+        assert!(!is_complaint("thanks, that worked"));
+    }
+}
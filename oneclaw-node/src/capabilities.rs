@@ -0,0 +1,232 @@
+//! Capability Self-Description
+//!
+//! `GET /capabilities` and the `/whatcanyoudo` chat command tell a new user
+//! what this node can actually do. Facts are gathered from live state (harness
+//! tool catalog, local executors, integrations, persona, limits) and written up
+//! by the LLM once; the statement is cached until the facts change, e.g. when
+//! harness discovery finishes or an integration gets connected.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+
+use crate::agent_os::{AgentOS, ToolDefinition};
+use crate::config::NodeConfig;
+use crate::executor::ExecutorStatus;
+use crate::integration::Integration;
+
+/// Chat command that returns the statement
+pub const COMMAND: &str = "/whatcanyoudo";
+
+#[derive(Debug, Clone, Serialize, Hash)]
+pub struct ToolFact {
+    pub id: String,
+    pub description: String,
+    /// Per-call estimate from the harness catalog, formatted ("$0.02")
+    pub cost: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Hash)]
+pub struct ExecutorFact {
+    pub id: String,
+    pub description: String,
+    /// False when required secrets are missing
+    pub runnable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Hash)]
+pub struct IntegrationFact {
+    pub name: String,
+    pub description: String,
+    pub connected: bool,
+}
+
+/// Everything the statement is written from
+#[derive(Debug, Clone, Serialize, Hash)]
+pub struct CapabilityFacts {
+    pub node_name: String,
+    /// Opening paragraph of IDENTITY.md (or SOUL.md)
+    pub persona: Option<String>,
+    pub tools: Vec<ToolFact>,
+    pub executors: Vec<ExecutorFact>,
+    pub integrations: Vec<IntegrationFact>,
+    /// Step timeouts, recovery attempts, security mode and the like
+    pub limits: Vec<String>,
+}
+
+impl CapabilityFacts {
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityStatement {
+    pub statement: String,
+    /// False when the LLM was unavailable and the facts were listed as-is
+    pub generated: bool,
+    pub generated_at: DateTime<Utc>,
+    pub facts: CapabilityFacts,
+}
+
+/// Text before the first heading after the title, if any
+fn opening_paragraph(markdown: &str) -> Option<String> {
+    let text = markdown
+        .lines()
+        .skip_while(|l| l.trim().is_empty() || l.trim().starts_with('#'))
+        .take_while(|l| !l.trim().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    (!text.is_empty() && !text.contains("Not Found")).then_some(text)
+}
+
+pub fn gather(
+    config: &NodeConfig,
+    agent_os: &AgentOS,
+    tools: &[ToolDefinition],
+    executors: &[ExecutorStatus],
+    integrations: &[Integration],
+) -> CapabilityFacts {
+    let tools = tools
+        .iter()
+        .map(|t| ToolFact {
+            id: t.id.clone(),
+            description: t.description.clone(),
+            cost: t.cost_estimate.filter(|c| *c > 0.0).map(|c| format!("${:.2}", c)),
+        })
+        .collect();
+    let executors = executors
+        .iter()
+        .map(|e| ExecutorFact {
+            id: e.manifest.id.clone(),
+            description: e.manifest.description.clone(),
+            runnable: e.runnable,
+        })
+        .collect();
+    let integrations = integrations
+        .iter()
+        .map(|i| IntegrationFact { name: i.name.clone(), description: i.description.clone(), connected: i.connected })
+        .collect();
+
+    let mut limits = vec![];
+    if crate::shadow::active() {
+        limits.push("Shadow mode is on: requests are planned but nothing is executed or sent".to_string());
+    }
+    limits.extend([
+        format!("Security mode: {}", config.security.mode),
+        format!("Job steps time out after {}s by default", config.jobs.step_timeout_secs),
+    ]);
+    let mut step_timeouts: Vec<_> = config.jobs.step_timeouts.iter().collect();
+    step_timeouts.sort();
+    limits.extend(step_timeouts.into_iter().map(|(action, secs)| format!("`{}` steps time out after {}s", action, secs)));
+    if config.jobs.recovery {
+        limits.push(format!("Failed steps are retried with a new plan up to {} time(s) per job", config.jobs.max_recoveries));
+    }
+    limits.push(format!("Chat history keeps the last {} messages", config.memory.session_max_messages));
+
+    CapabilityFacts {
+        node_name: config.node.name.clone(),
+        persona: opening_paragraph(&agent_os.identity).or_else(|| opening_paragraph(&agent_os.soul)),
+        tools,
+        executors,
+        integrations,
+        limits,
+    }
+}
+
+/// Plain listing of the facts, used as the LLM input and as the fallback statement
+pub fn render(facts: &CapabilityFacts) -> String {
+    let mut out = format!("🦞 **What {} can do**\n\n", facts.node_name);
+    if let Some(persona) = &facts.persona {
+        out.push_str(persona);
+        out.push_str("\n\n");
+    }
+
+    out.push_str("**Tools**\n");
+    if facts.tools.is_empty() {
+        out.push_str("• No harness tools available right now\n");
+    }
+    for tool in &facts.tools {
+        match &tool.cost {
+            Some(cost) => out.push_str(&format!("• {} - {} (~{} per call)\n", tool.id, tool.description, cost)),
+            None => out.push_str(&format!("• {} - {}\n", tool.id, tool.description)),
+        }
+    }
+    for executor in &facts.executors {
+        let note = if executor.runnable { "" } else { " (needs setup)" };
+        out.push_str(&format!("• {} - {}{}\n", executor.id, executor.description, note));
+    }
+
+    out.push_str("\n**Integrations**\n");
+    for integration in &facts.integrations {
+        let status = if integration.connected { "✅" } else { "not connected" };
+        out.push_str(&format!("• {} - {} ({})\n", integration.name, integration.description, status));
+    }
+
+    out.push_str("\n**Limits**\n");
+    for limit in &facts.limits {
+        out.push_str(&format!("• {}\n", limit));
+    }
+    out
+}
+
+/// Prompt asking the LLM to turn the facts into a short introduction
+pub fn prompt(facts: &CapabilityFacts) -> String {
+    format!(
+        r#"A new user asked this assistant what it can do. Using ONLY the facts below, write a short, friendly capability statement in Markdown:
+- One or two sentences of introduction in the assistant's voice
+- What it can do for the user, grouped by outcome (finding leads, outreach, scheduling, ...), naming the tools behind each
+- Which integrations are connected, and which could be connected to unlock more
+- The limits and costs worth knowing
+- Two or three example requests to try
+
+Do not invent tools, integrations or numbers that aren't listed.
+
+{}"#,
+        render(facts)
+    )
+}
+
+struct Cached {
+    fingerprint: u64,
+    statement: CapabilityStatement,
+}
+
+/// The last statement, reused while the facts are unchanged
+#[derive(Default)]
+pub struct Cache(tokio::sync::Mutex<Option<Cached>>);
+
+impl Cache {
+    /// Cached statement for `facts`, or a new one from `write` (None falls back to `render`).
+    /// Concurrent callers wait for the first to finish instead of generating twice.
+    pub async fn get_or_generate<F, Fut>(&self, facts: CapabilityFacts, write: F) -> CapabilityStatement
+    where
+        F: FnOnce(CapabilityFacts) -> Fut,
+        Fut: Future<Output = Option<String>>,
+    {
+        let fingerprint = facts.fingerprint();
+        let mut cached = self.0.lock().await;
+        if let Some(hit) = cached.as_ref().filter(|c| c.fingerprint == fingerprint) {
+            return hit.statement.clone();
+        }
+
+        let written = write(facts.clone()).await;
+        let statement = CapabilityStatement {
+            generated: written.is_some(),
+            statement: written.unwrap_or_else(|| render(&facts)),
+            generated_at: Utc::now(),
+            facts,
+        };
+        // Only cache LLM write-ups; a fallback is retried on the next ask
+        if statement.generated {
+            *cached = Some(Cached { fingerprint, statement: statement.clone() });
+        }
+        statement
+    }
+}
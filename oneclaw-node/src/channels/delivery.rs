@@ -0,0 +1,90 @@
+//! Delivery - What became of every message sent through an outbound queue
+//!
+//! The `OutboundQueue` (`outbound.rs`) gives each message and file it takes an
+//! id and records it in the store's `deliveries` table as it moves along:
+//! `queued`, then `sent` (with the provider's message id) or `retrying` after a
+//! rejected attempt, and `failed` once `MAX_ATTEMPTS` are used up. Messages the
+//! full queue pushes out are `dropped`.
+//!
+//! A failed send is logged with its delivery id, and `GET /messages/:id` (or
+//! `GET /messages?status=failed`) shows its attempts and the provider's error,
+//! so a message Telegram rejected is noticed instead of lost.
+//!
+//! Each node has its own `Recorder` (held by its outbound queues), writing to
+//! the node's own store in the background. Records are kept for `KEEP_DAYS`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+
+use super::ChannelType;
+use crate::store::{Delivery, Store};
+
+/// Attempts before a delivery is `failed`
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Days a delivery is kept
+const KEEP_DAYS: i64 = 14;
+
+/// Writes between prunes of the table
+const PRUNE_EVERY: usize = 100;
+
+/// Writes one node's deliveries to its store
+#[derive(Clone)]
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<Delivery>,
+}
+
+impl Recorder {
+    /// Record deliveries in `store`
+    pub fn start(store: Arc<dyn Store>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(write(store, rx));
+        Self { tx }
+    }
+
+    /// Move `delivery` to `status` and record it
+    pub fn update(&self, delivery: &mut Delivery, status: &str) {
+        delivery.status = status.to_string();
+        delivery.updated_at = chrono::Utc::now();
+        let _ = self.tx.send(delivery.clone());
+    }
+}
+
+async fn write(store: Arc<dyn Store>, mut rx: mpsc::UnboundedReceiver<Delivery>) {
+    let mut written = 0;
+    while let Some(delivery) = rx.recv().await {
+        if let Err(e) = store.save_delivery(&delivery).await {
+            tracing::warn!("Could not record delivery {}: {}", delivery.id, e);
+        }
+        if written % PRUNE_EVERY == 0 {
+            let before = chrono::Utc::now() - chrono::Duration::days(KEEP_DAYS);
+            if let Err(e) = store.prune_deliveries(before).await {
+                tracing::warn!("Could not prune deliveries: {}", e);
+            }
+        }
+        written += 1;
+    }
+}
+
+/// A new delivery to `chat_id`, not yet attempted
+pub fn queued(channel: ChannelType, chat_id: &str) -> Delivery {
+    let now = chrono::Utc::now();
+    Delivery {
+        id: nanoid::nanoid!(16),
+        channel: channel.to_string(),
+        chat_id: chat_id.to_string(),
+        status: "queued".to_string(),
+        attempts: 0,
+        provider_message_id: None,
+        error: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Wait before attempt `attempts + 1`: 2s, then 4s
+pub fn backoff(attempts: u32) -> Duration {
+    Duration::from_secs(2u64.pow(attempts.min(6)))
+}
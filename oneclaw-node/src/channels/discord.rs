@@ -1,28 +1,44 @@
 //! Discord Channel
-//! 
+//!
 //! Connects to Discord Gateway via WebSocket for real-time message events.
 //! Handles:
-//! - Gateway connection and heartbeat
-//! - Message events (mentions, DMs)
-//! - Sending responses back to Discord
+//! - Gateway connection, heartbeat and reconnects
+//! - Message events (mentions, DMs) filtered by `listen_guilds` / `listen_channels`
+//! - Sending responses and report attachments back through the REST API
 
 use async_trait::async_trait;
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
 use crate::config::DiscordChannelConfig;
 
+const GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+const API_BASE: &str = "https://discord.com/api/v10";
+
 // Discord Gateway Opcodes
 const OP_DISPATCH: u8 = 0;
 const OP_HEARTBEAT: u8 = 1;
 const OP_IDENTIFY: u8 = 2;
+const OP_RECONNECT: u8 = 7;
+const OP_INVALID_SESSION: u8 = 9;
 const OP_HELLO: u8 = 10;
 const OP_HEARTBEAT_ACK: u8 = 11;
 
+/// GUILDS | GUILD_MESSAGES | DIRECT_MESSAGES | MESSAGE_CONTENT
+const INTENTS: u64 = 1 | (1 << 9) | (1 << 12) | (1 << 15);
+
+/// Discord rejects messages longer than this
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Wait before reconnecting after the gateway drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GatewayPayload {
     op: u8,
@@ -53,48 +69,63 @@ struct DiscordUser {
 #[derive(Debug, Deserialize)]
 struct ReadyEvent {
     user: DiscordUser,
-    session_id: String,
 }
 
+#[derive(Clone)]
 pub struct DiscordChannel {
     config: DiscordChannelConfig,
     token: String,
     bot_user_id: Arc<RwLock<Option<String>>>,
     http_client: reqwest::Client,
+    connected: Arc<AtomicBool>,
 }
 
 impl DiscordChannel {
     pub fn new(config: DiscordChannelConfig) -> anyhow::Result<Self> {
         let token = std::env::var(&config.token_env)
-            .map_err(|_| anyhow::anyhow!("Discord token not found in env: {}", config.token_env))?;
-        
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Discord token not found in env: {}", config.token_env))?;
+
         Ok(Self {
             config,
             token,
             bot_user_id: Arc::new(RwLock::new(None)),
             http_client: reqwest::Client::new(),
+            connected: Arc::new(AtomicBool::new(false)),
         })
     }
-    
+
     /// Check if a message should trigger the bot
     fn should_respond(&self, msg: &DiscordMessage, bot_id: &str) -> bool {
-        // Ignore bot messages
+        // Ignore bot messages (including our own replies)
         if msg.author.bot {
             return false;
         }
-        
+
+        let guild_allowed = match &msg.guild_id {
+            Some(guild) => self.config.listen_guilds.iter().any(|g| g == "*" || g == guild),
+            None => true, // DMs
+        };
+        let channel_allowed = msg.guild_id.is_none()
+            || self.config.listen_channels.iter().any(|c| c == "*" || *c == msg.channel_id);
+        if !guild_allowed || !channel_allowed {
+            return false;
+        }
+
         match self.config.trigger.as_str() {
             "all" => true,
             "dm_only" => msg.guild_id.is_none(),
-            "mention" | _ => {
-                // Check if bot is mentioned
-                msg.mentions.iter().any(|u| u.id == bot_id)
+            // "mention": DMs are always addressed to the bot
+            _ => {
+                msg.guild_id.is_none()
+                    || msg.mentions.iter().any(|u| u.id == bot_id)
                     || msg.content.contains(&format!("<@{}>", bot_id))
                     || msg.content.contains(&format!("<@!{}>", bot_id))
             }
         }
     }
-    
+
     /// Remove bot mention from content
     fn clean_content(&self, content: &str, bot_id: &str) -> String {
         content
@@ -103,240 +134,272 @@ impl DiscordChannel {
             .trim()
             .to_string()
     }
-    
-    /// Send a message to a Discord channel
-    async fn send_message(&self, channel_id: &str, content: &str) -> anyhow::Result<()> {
-        let url = format!("https://discord.com/api/v10/channels/{}/messages", channel_id);
-        
+
+    /// Send a message to a Discord channel, optionally as a reply
+    async fn send_message(&self, channel_id: &str, content: &str, reply_to: Option<&str>) -> anyhow::Result<()> {
+        let url = format!("{}/channels/{}/messages", API_BASE, channel_id);
+        let mut body = serde_json::json!({ "content": content });
+        if let Some(message_id) = reply_to {
+            body["message_reference"] = serde_json::json!({ "message_id": message_id, "fail_if_not_exists": false });
+        }
+
         let response = self.http_client
             .post(&url)
             .header("Authorization", format!("Bot {}", self.token))
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({ "content": content }))
+            .json(&body)
             .send()
             .await?;
-        
+
         if !response.status().is_success() {
             let error = response.text().await?;
             anyhow::bail!("Discord API error: {}", error);
         }
-        
+
         Ok(())
     }
-}
 
-#[async_trait]
-impl Channel for DiscordChannel {
-    fn channel_type(&self) -> ChannelType {
-        ChannelType::Discord
-    }
-    
-    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
-        // Get gateway URL
-        let gateway_url = "wss://gateway.discord.gg/?v=10&encoding=json";
-        
+    /// One gateway session: identify, heartbeat and forward messages until the
+    /// socket closes or Discord asks us to reconnect
+    async fn run_session(&self, tx: &mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
         tracing::info!("Connecting to Discord Gateway...");
-        
-        let (ws_stream, _) = connect_async(gateway_url).await?;
+        let (ws_stream, _) = connect_async(GATEWAY_URL).await?;
         let (mut write, mut read) = ws_stream.split();
-        
+
         let mut sequence: Option<u64> = None;
-        let mut heartbeat_interval: u64 = 45000;
-        let bot_user_id = self.bot_user_id.clone();
-        let token = self.token.clone();
-        let config = self.config.clone();
-        
-        // Spawn heartbeat task
-        let heartbeat_tx = {
-            let (htx, mut hrx) = mpsc::channel::<()>(1);
-            
-            tokio::spawn(async move {
-                loop {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(heartbeat_interval)).await;
-                    if hrx.try_recv().is_ok() {
-                        break;
-                    }
+        // Replaced with the real interval once HELLO arrives
+        let mut heartbeat = tokio::time::interval(Duration::from_secs(45));
+        let mut hello_received = false;
+
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick(), if hello_received => {
+                    let payload = GatewayPayload {
+                        op: OP_HEARTBEAT,
+                        d: sequence.map(|s| serde_json::json!(s)),
+                        s: None,
+                        t: None,
+                    };
+                    write.send(Message::Text(serde_json::to_string(&payload)?)).await?;
                 }
-            });
-            
-            htx
-        };
-        
-        // Main event loop
-        while let Some(msg) = read.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Ok(payload) = serde_json::from_str::<GatewayPayload>(&text) {
-                        // Update sequence
-                        if let Some(s) = payload.s {
-                            sequence = Some(s);
+
+                frame = read.next() => {
+                    let text = match frame {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(frame))) => {
+                            tracing::warn!("Discord WebSocket closed: {:?}", frame);
+                            return Ok(());
                         }
-                        
-                        match payload.op {
-                            OP_HELLO => {
-                                // Extract heartbeat interval
-                                if let Some(d) = &payload.d {
-                                    heartbeat_interval = d["heartbeat_interval"].as_u64().unwrap_or(45000);
-                                }
-                                
-                                // Send IDENTIFY
-                                let identify = GatewayPayload {
-                                    op: OP_IDENTIFY,
-                                    d: Some(serde_json::json!({
-                                        "token": token,
-                                        "intents": 33281, // GUILDS + GUILD_MESSAGES + MESSAGE_CONTENT + DIRECT_MESSAGES
-                                        "properties": {
-                                            "os": "linux",
-                                            "browser": "oneclaw",
-                                            "device": "oneclaw"
-                                        }
-                                    })),
-                                    s: None,
-                                    t: None,
-                                };
-                                
-                                write.send(Message::Text(serde_json::to_string(&identify)?)).await?;
-                                tracing::info!("Sent IDENTIFY to Discord");
-                            }
-                            
-                            OP_HEARTBEAT => {
-                                // Send heartbeat immediately
-                                let heartbeat = GatewayPayload {
-                                    op: OP_HEARTBEAT,
-                                    d: sequence.map(|s| serde_json::json!(s)),
-                                    s: None,
-                                    t: None,
-                                };
-                                write.send(Message::Text(serde_json::to_string(&heartbeat)?)).await?;
-                            }
-                            
-                            OP_HEARTBEAT_ACK => {
-                                // Heartbeat acknowledged
-                            }
-                            
-                            OP_DISPATCH => {
-                                if let Some(event_name) = &payload.t {
-                                    match event_name.as_str() {
-                                        "READY" => {
-                                            if let Some(d) = payload.d {
-                                                if let Ok(ready) = serde_json::from_value::<ReadyEvent>(d) {
-                                                    *bot_user_id.write().await = Some(ready.user.id.clone());
-                                                    tracing::info!(
-                                                        bot_name = %ready.user.username,
-                                                        bot_id = %ready.user.id,
-                                                        "Discord bot connected"
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        
-                                        "MESSAGE_CREATE" => {
-                                            if let Some(d) = payload.d {
-                                                if let Ok(discord_msg) = serde_json::from_value::<DiscordMessage>(d.clone()) {
-                                                    let current_bot_id = bot_user_id.read().await;
-                                                    
-                                                    if let Some(ref bid) = *current_bot_id {
-                                                        // Check guild filter
-                                                        let guild_allowed = config.listen_guilds.contains(&"*".to_string())
-                                                            || discord_msg.guild_id.as_ref()
-                                                                .map(|g| config.listen_guilds.contains(g))
-                                                                .unwrap_or(true); // Allow DMs
-                                                        
-                                                        // Check channel filter
-                                                        let channel_allowed = config.listen_channels.contains(&"*".to_string())
-                                                            || config.listen_channels.contains(&discord_msg.channel_id);
-                                                        
-                                                        if guild_allowed && channel_allowed {
-                                                            // Create a local reference to avoid moving self
-                                                            let should_respond = {
-                                                                // Inline the should_respond logic
-                                                                if discord_msg.author.bot {
-                                                                    false
-                                                                } else {
-                                                                    match config.trigger.as_str() {
-                                                                        "all" => true,
-                                                                        "dm_only" => discord_msg.guild_id.is_none(),
-                                                                        "mention" | _ => {
-                                                                            discord_msg.mentions.iter().any(|u| u.id == *bid)
-                                                                                || discord_msg.content.contains(&format!("<@{}>", bid))
-                                                                                || discord_msg.content.contains(&format!("<@!{}>", bid))
-                                                                        }
-                                                                    }
-                                                                }
-                                                            };
-                                                            
-                                                            if should_respond {
-                                                                // Clean content
-                                                                let clean_content = discord_msg.content
-                                                                    .replace(&format!("<@{}>", bid), "")
-                                                                    .replace(&format!("<@!{}>", bid), "")
-                                                                    .trim()
-                                                                    .to_string();
-                                                                
-                                                                let incoming = IncomingMessage {
-                                                                    channel_type: ChannelType::Discord,
-                                                                    channel_id: discord_msg.channel_id.clone(),
-                                                                    provider_user_id: discord_msg.author.id.clone(),
-                                                                    username: Some(discord_msg.author.username.clone()),
-                                                                    content: clean_content,
-                                                                    timestamp: chrono::Utc::now(),
-                                                                    reply_to: Some(discord_msg.id.clone()),
-                                                                    metadata: d,
-                                                                };
-                                                                
-                                                                if let Err(e) = tx.send(incoming).await {
-                                                                    tracing::error!("Failed to send message to handler: {}", e);
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        
-                                        _ => {}
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => return Err(e.into()),
+                        None => return Ok(()),
+                    };
+                    let Ok(payload) = serde_json::from_str::<GatewayPayload>(&text) else {
+                        continue;
+                    };
+                    if let Some(s) = payload.s {
+                        sequence = Some(s);
+                    }
+
+                    match payload.op {
+                        OP_HELLO => {
+                            let interval_ms = payload.d.as_ref()
+                                .and_then(|d| d["heartbeat_interval"].as_u64())
+                                .unwrap_or(45000);
+                            let period = Duration::from_millis(interval_ms);
+                            heartbeat = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                            hello_received = true;
+
+                            let identify = GatewayPayload {
+                                op: OP_IDENTIFY,
+                                d: Some(serde_json::json!({
+                                    "token": self.token,
+                                    "intents": INTENTS,
+                                    "properties": {
+                                        "os": std::env::consts::OS,
+                                        "browser": "oneclaw",
+                                        "device": "oneclaw"
                                     }
-                                }
-                            }
-                            
-                            _ => {}
+                                })),
+                                s: None,
+                                t: None,
+                            };
+                            write.send(Message::Text(serde_json::to_string(&identify)?)).await?;
+                            tracing::info!("Sent IDENTIFY to Discord");
                         }
+
+                        OP_HEARTBEAT => {
+                            // Gateway asked for a heartbeat right away
+                            heartbeat.reset_immediately();
+                        }
+
+                        OP_RECONNECT | OP_INVALID_SESSION => {
+                            tracing::info!("Discord asked us to reconnect (op {})", payload.op);
+                            return Ok(());
+                        }
+
+                        OP_HEARTBEAT_ACK => {}
+
+                        OP_DISPATCH => self.handle_dispatch(payload, tx).await,
+
+                        _ => {}
                     }
                 }
-                
-                Ok(Message::Close(_)) => {
-                    tracing::warn!("Discord WebSocket closed");
-                    break;
+            }
+        }
+    }
+
+    async fn handle_dispatch(&self, payload: GatewayPayload, tx: &mpsc::Sender<IncomingMessage>) {
+        let (Some(event_name), Some(d)) = (payload.t.as_deref(), payload.d) else {
+            return;
+        };
+
+        match event_name {
+            "READY" => {
+                if let Ok(ready) = serde_json::from_value::<ReadyEvent>(d) {
+                    tracing::info!(
+                        bot_name = %ready.user.username,
+                        bot_id = %ready.user.id,
+                        "Discord bot connected"
+                    );
+                    *self.bot_user_id.write().await = Some(ready.user.id);
+                    self.connected.store(true, Ordering::Relaxed);
+                }
+            }
+
+            "MESSAGE_CREATE" => {
+                let Ok(discord_msg) = serde_json::from_value::<DiscordMessage>(d.clone()) else {
+                    return;
+                };
+                let Some(bot_id) = self.bot_user_id.read().await.clone() else {
+                    return;
+                };
+                if !self.should_respond(&discord_msg, &bot_id) {
+                    return;
                 }
-                
-                Err(e) => {
-                    tracing::error!("Discord WebSocket error: {}", e);
-                    break;
+
+                let incoming = IncomingMessage {
+                    channel_type: ChannelType::Discord,
+                    channel_id: discord_msg.channel_id.clone(),
+                    provider_user_id: discord_msg.author.id.clone(),
+                    username: Some(discord_msg.author.username.clone()),
+                    content: self.clean_content(&discord_msg.content, &bot_id),
+                    timestamp: chrono::Utc::now(),
+                    reply_to: Some(discord_msg.id.clone()),
+                    // Discord threads are channels, so in a server the channel is the thread
+                    thread_id: discord_msg.guild_id.as_ref().map(|_| discord_msg.channel_id.clone()),
+                    metadata: d,
+                };
+
+                if let Err(e) = tx.send(incoming).await {
+                    tracing::error!("Failed to send message to handler: {}", e);
                 }
-                
-                _ => {}
             }
-            
-            // Send periodic heartbeat
-            let heartbeat = GatewayPayload {
-                op: OP_HEARTBEAT,
-                d: sequence.map(|s| serde_json::json!(s)),
-                s: None,
-                t: None,
-            };
-            // Note: In production, this should be on a timer, not every message
+
+            _ => {}
+        }
+    }
+}
+
+/// Split a reply into chunks Discord accepts, preferring line boundaries
+fn split_message(content: &str) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    for line in content.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > MAX_MESSAGE_CHARS && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if line.chars().count() > MAX_MESSAGE_CHARS {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(MAX_MESSAGE_CHARS) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[async_trait]
+impl Channel for DiscordChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Discord
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        // Keep the session alive: reconnect whenever the gateway drops us
+        while !tx.is_closed() {
+            if let Err(e) = self.run_session(&tx).await {
+                tracing::error!("Discord gateway error: {}", e);
+            }
+            self.connected.store(false, Ordering::Relaxed);
+            tokio::time::sleep(RECONNECT_DELAY).await;
         }
-        
         Ok(())
     }
-    
+
     async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
-        self.send_message(&msg.channel_id, &msg.content).await
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(());
+        }
+        for (i, chunk) in split_message(&msg.content).iter().enumerate() {
+            let reply_to = if i == 0 { msg.reply_to.as_deref() } else { None };
+            self.send_message(&msg.channel_id, chunk, reply_to).await?;
+        }
+        Ok(())
     }
-    
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let url = format!("{}/channels/{}/messages", API_BASE, channel_id);
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let bytes = tokio::fs::read(path).await?;
+
+        let payload = serde_json::json!({ "content": caption.unwrap_or_default() });
+        let form = reqwest::multipart::Form::new()
+            .text("payload_json", payload.to_string())
+            .part("files[0]", reqwest::multipart::Part::bytes(bytes).file_name(file_name));
+
+        let response = self.http_client
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.token))
+            .multipart(form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let error = response.text().await?;
+            anyhow::bail!("Discord API error: {}", error);
+        }
+        Ok(())
+    }
+
+    /// Show "<bot> is typing..." (lasts ~10s or until the next message)
+    async fn indicate_activity(&self, channel_id: &str) -> anyhow::Result<()> {
+        let url = format!("{}/channels/{}/typing", API_BASE, channel_id);
+        self.http_client
+            .post(&url)
+            .header("Authorization", format!("Bot {}", self.token))
+            .header("Content-Length", "0")
+            .send()
+            .await?;
+        Ok(())
+    }
+
     async fn stop(&self) -> anyhow::Result<()> {
-        // Signal shutdown
         tracing::info!("Stopping Discord channel");
+        self.connected.store(false, Ordering::Relaxed);
         Ok(())
     }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
 }
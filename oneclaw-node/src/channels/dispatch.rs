@@ -0,0 +1,139 @@
+//! Dispatch - Acknowledge messages that have to wait their turn
+//!
+//! A channel's handler works through its messages one at a time, so a message
+//! sent during a long turn (a big search, a slow tool) sits unanswered until
+//! that turn is done. The listener's forwarder asks the channel's `Dispatcher`
+//! about every message it hands over; when the handler is busy, the chat gets
+//! an acknowledgment right away:
+//! - "Working on your last request — I'll get to this next." when the chat's
+//!   own earlier message is still being handled
+//! - "One moment — finishing another request, then I'll get to this." when
+//!   another chat's is
+//!
+//! A chat is acknowledged once until its queue drains, however many messages it
+//! sends meanwhile. `channels.busy_ack` replaces the text (empty turns acks off).
+//! Email isn't acknowledged: a wait of minutes is normal there.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::{ChannelType, IncomingMessage};
+
+const OWN_TURN_ACK: &str = "Working on your last request — I'll get to this next.";
+const OTHER_TURN_ACK: &str = "One moment — finishing another request, then I'll get to this.";
+
+#[derive(Default)]
+struct Queue {
+    /// Messages handed over and not yet handled, per chat
+    pending: HashMap<String, usize>,
+    /// Chats acknowledged since their queue was last empty
+    acknowledged: HashSet<String>,
+}
+
+/// What a channel's handler has in hand, for acknowledging messages that wait
+pub struct Dispatcher {
+    queue: Mutex<Queue>,
+    /// `channels.busy_ack`
+    text: Option<String>,
+}
+
+impl Dispatcher {
+    pub fn new(text: Option<String>) -> Arc<Self> {
+        Arc::new(Self { queue: Mutex::new(Queue::default()), text })
+    }
+
+    /// Count `msg` as queued for the handler. Returns the acknowledgment to send
+    /// when it has to wait.
+    pub fn admit(&self, msg: &IncomingMessage) -> Option<String> {
+        let mut queue = self.queue.lock().expect("dispatch lock");
+        let busy = queue.pending.values().any(|&n| n > 0);
+        let own = queue.pending.get(&msg.channel_id).copied().unwrap_or(0) > 0;
+        *queue.pending.entry(msg.channel_id.clone()).or_default() += 1;
+
+        if !busy || msg.channel_type == ChannelType::Email || !queue.acknowledged.insert(msg.channel_id.clone()) {
+            return None;
+        }
+        let text = match &self.text {
+            Some(text) => text.trim().to_string(),
+            None if own => OWN_TURN_ACK.to_string(),
+            None => OTHER_TURN_ACK.to_string(),
+        };
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Mark `msg` as being handled until the returned guard drops
+    pub fn begin(self: &Arc<Self>, msg: &IncomingMessage) -> Turn {
+        Turn { dispatcher: Arc::clone(self), chat: msg.channel_id.clone() }
+    }
+
+    fn finish(&self, chat: &str) {
+        let mut queue = self.queue.lock().expect("dispatch lock");
+        let Some(pending) = queue.pending.get_mut(chat) else { return };
+        *pending = pending.saturating_sub(1);
+        if *pending == 0 {
+            queue.pending.remove(chat);
+            queue.acknowledged.remove(chat);
+        }
+    }
+}
+
+/// A message being handled; dropping it (on any exit path) lets the next one through
+pub struct Turn {
+    dispatcher: Arc<Dispatcher>,
+    chat: String,
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        self.dispatcher.finish(&self.chat);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(chat: &str) -> IncomingMessage {
+        IncomingMessage {
+            channel_type: ChannelType::Telegram,
+            channel_id: chat.to_string(),
+            provider_user_id: "u1".to_string(),
+            username: None,
+            content: "hi".to_string(),
+            timestamp: chrono::Utc::now(),
+            reply_to: None,
+            thread_id: None,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_acknowledge_once_while_busy() {
+        let dispatcher = Dispatcher::new(None);
+        // Nothing running: no ack
+        assert_eq!(dispatcher.admit(&message("a")), None);
+        let turn = dispatcher.begin(&message("a"));
+
+        assert_eq!(dispatcher.admit(&message("a")).as_deref(), Some(OWN_TURN_ACK));
+        assert_eq!(dispatcher.admit(&message("a")), None);
+        assert_eq!(dispatcher.admit(&message("b")).as_deref(), Some(OTHER_TURN_ACK));
+
+        // "a" still has two queued after its first turn, so it stays acknowledged
+        drop(turn);
+        assert_eq!(dispatcher.admit(&message("a")), None);
+        for _ in 0..3 {
+            drop(dispatcher.begin(&message("a")));
+        }
+        drop(dispatcher.begin(&message("b")));
+        assert_eq!(dispatcher.admit(&message("a")), None);
+
+        // Email waits quietly; an empty busy_ack turns acks off
+        let _turn = dispatcher.begin(&message("a"));
+        let mut email = message("c");
+        email.channel_type = ChannelType::Email;
+        assert_eq!(dispatcher.admit(&email), None);
+        let quiet = Dispatcher::new(Some(String::new()));
+        quiet.admit(&message("a"));
+        assert_eq!(quiet.admit(&message("b")), None);
+    }
+}
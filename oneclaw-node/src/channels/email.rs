@@ -0,0 +1,386 @@
+//! Email Channel (IMAP in, SMTP out)
+//!
+//! Polls an IMAP mailbox every `poll_interval_secs` for unseen mail:
+//! - Each new message from an allowed sender becomes a chat turn for the
+//!   sender's address. The thread root (first `References` id, else
+//!   `In-Reply-To`, else its own `Message-ID`) is the thread id, so every
+//!   thread keeps its own context
+//! - Quoted history below the reply is dropped before the turn
+//! - Auto-replies and list traffic are marked read and ignored
+//!
+//! Replies go out over SMTP with `In-Reply-To`/`References` set so mail
+//! clients keep them in the thread; reports are sent as attachments.
+
+use async_trait::async_trait;
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use mail_parser::MessageParser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::EmailChannelConfig;
+
+/// Give up on an IMAP read/connect that stalls longer than this
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone)]
+pub struct EmailChannel {
+    config: EmailChannelConfig,
+    password: String,
+    smtp: AsyncSmtpTransport<Tokio1Executor>,
+    /// Only mail received from this day on is picked up, so enabling the
+    /// channel on an old inbox doesn't answer years of unread mail
+    since: chrono::NaiveDate,
+    connected: Arc<AtomicBool>,
+}
+
+impl EmailChannel {
+    pub fn new(config: EmailChannelConfig) -> anyhow::Result<Self> {
+        for (field, value) in [
+            ("imap_host", &config.imap_host),
+            ("smtp_host", &config.smtp_host),
+            ("username", &config.username),
+        ] {
+            if value.trim().is_empty() {
+                anyhow::bail!("channels.email.{} not set", field);
+            }
+        }
+        let password = std::env::var(&config.password_env)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Email password not found in env: {}", config.password_env))?;
+
+        let smtp = if config.smtp_port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+        }
+        .port(config.smtp_port)
+        .credentials(Credentials::new(config.username.clone(), password.clone()))
+        .build();
+
+        Ok(Self {
+            config,
+            password,
+            smtp,
+            since: chrono::Utc::now().date_naive(),
+            connected: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    fn reply_address(&self) -> &str {
+        self.config.from_address.as_deref().unwrap_or(&self.config.username)
+    }
+
+    fn mailbox(&self) -> anyhow::Result<Mailbox> {
+        Ok(Mailbox::new(Some(self.config.from_name.clone()), self.reply_address().parse()?))
+    }
+
+    fn sender_allowed(&self, address: &str) -> bool {
+        self.config.allowed_senders.iter().any(|allowed| {
+            let allowed = allowed.to_lowercase();
+            allowed == "*" || allowed == address || (allowed.starts_with('@') && address.ends_with(&allowed))
+        })
+    }
+
+    /// One pass over the mailbox: forward unseen mail and mark it read
+    async fn poll_once(&self, tx: &mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        let mut session = ImapSession::connect(&self.config.imap_host, self.config.imap_port).await?;
+        session.command(&format!("LOGIN {} {}", quote(&self.config.username), quote(&self.password))).await?;
+        session.command(&format!("SELECT {}", quote(&self.config.mailbox))).await?;
+        self.connected.store(true, Ordering::Relaxed);
+
+        let search = session
+            .command(&format!("UID SEARCH UNSEEN SINCE {}", self.since.format("%d-%b-%Y")))
+            .await?;
+        let uids: Vec<u32> = search
+            .lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("* SEARCH"))
+            .flat_map(|rest| rest.split_whitespace().filter_map(|uid| uid.parse().ok()))
+            .collect();
+
+        for uid in uids {
+            let fetched = session.command(&format!("UID FETCH {} BODY.PEEK[]", uid)).await?;
+            // Mark read before the turn so a message that breaks it isn't retried forever
+            session.command(&format!("UID STORE {} +FLAGS (\\Seen)", uid)).await?;
+
+            let Some(message) = fetched.literals.first().and_then(|raw| self.parse_message(raw)) else {
+                continue;
+            };
+            if tx.send(message).await.is_err() {
+                break;
+            }
+        }
+
+        let _ = session.command("LOGOUT").await;
+        Ok(())
+    }
+
+    /// Turn a raw RFC 822 message into a chat turn, if it is one
+    fn parse_message(&self, raw: &[u8]) -> Option<IncomingMessage> {
+        let message = MessageParser::default().parse(raw)?;
+        let sender = message.from()?.first()?;
+        let address = sender.address()?.to_lowercase();
+
+        if address == self.reply_address().to_lowercase() {
+            return None;
+        }
+        let auto_submitted = message
+            .header_raw("Auto-Submitted")
+            .is_some_and(|v| !v.trim().eq_ignore_ascii_case("no"));
+        let bulk = message
+            .header_raw("Precedence")
+            .is_some_and(|v| matches!(v.trim().to_lowercase().as_str(), "bulk" | "list" | "junk"));
+        if auto_submitted || bulk || message.header_raw("List-Id").is_some() {
+            tracing::info!("Ignoring automated email from {}", address);
+            return None;
+        }
+        if !self.sender_allowed(&address) {
+            tracing::info!("Ignoring email from unlisted sender {}", address);
+            return None;
+        }
+
+        let subject = message.subject().unwrap_or_default().trim().to_string();
+        let message_id = message.message_id().map(str::to_string);
+        let references: Vec<String> = message
+            .references()
+            .as_text_list()
+            .unwrap_or_default()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let in_reply_to = message.in_reply_to().as_text().map(str::to_string);
+
+        let body = strip_quoted(&message.body_text(0)?);
+        // The subject carries the request when a thread starts ("Audit acme.com")
+        let content = if in_reply_to.is_none() && !subject.is_empty() {
+            format!("{}\n\n{}", subject, body).trim().to_string()
+        } else {
+            body
+        };
+        if content.is_empty() {
+            return None;
+        }
+
+        let thread = references.first().cloned().or_else(|| in_reply_to.clone()).or_else(|| message_id.clone());
+
+        Some(IncomingMessage {
+            channel_type: ChannelType::Email,
+            channel_id: address.clone(),
+            provider_user_id: address.clone(),
+            username: Some(sender.name().map(str::to_string).unwrap_or(address)),
+            content,
+            timestamp: chrono::Utc::now(),
+            reply_to: message_id,
+            thread_id: thread,
+            metadata: serde_json::json!({ "subject": subject, "references": references }),
+        })
+    }
+}
+
+/// Drop quoted history and signatures from a reply body
+pub fn strip_quoted(body: &str) -> String {
+    let attribution = regex::Regex::new(r"^On .+ wrote:$").expect("valid regex");
+    let mut kept = vec![];
+    for line in body.lines() {
+        let trimmed = line.trim_end();
+        if trimmed == "--" || trimmed.starts_with("-----Original Message-----") || attribution.is_match(trimmed.trim_start()) {
+            break;
+        }
+        if trimmed.trim_start().starts_with('>') {
+            continue;
+        }
+        kept.push(trimmed);
+    }
+    kept.join("\n").trim().to_string()
+}
+
+/// IMAP quoted string
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Message id in the angle-bracket form headers expect
+fn bracketed(id: &str) -> String {
+    format!("<{}>", id.trim_matches(|c| c == '<' || c == '>'))
+}
+
+/// Byte count announced by a line ending in an IMAP literal marker (`{N}`)
+fn literal_len(line: &[u8]) -> Option<usize> {
+    let line = String::from_utf8_lossy(line);
+    let body = line.trim_end().strip_suffix('}')?;
+    let open = body.rfind('{')?;
+    body[open + 1..].parse().ok()
+}
+
+#[derive(Default)]
+struct ImapResponse {
+    /// Untagged response lines, literals cut out
+    lines: Vec<String>,
+    /// Raw literal payloads in order (message bodies for `FETCH`)
+    literals: Vec<Vec<u8>>,
+}
+
+/// Just enough IMAP4rev1 over TLS to search, fetch and flag mail
+struct ImapSession {
+    stream: BufReader<tokio_native_tls::TlsStream<TcpStream>>,
+    tag: u32,
+}
+
+impl ImapSession {
+    async fn connect(host: &str, port: u16) -> anyhow::Result<Self> {
+        let tcp = tokio::time::timeout(IO_TIMEOUT, TcpStream::connect((host, port))).await??;
+        let connector = tokio_native_tls::TlsConnector::from(tokio_native_tls::native_tls::TlsConnector::new()?);
+        let tls = tokio::time::timeout(IO_TIMEOUT, connector.connect(host, tcp)).await??;
+
+        let mut session = Self { stream: BufReader::new(tls), tag: 0 };
+        let greeting = String::from_utf8_lossy(&session.read_line().await?).to_string();
+        if !greeting.starts_with("* OK") {
+            anyhow::bail!("IMAP server refused connection: {}", greeting.trim());
+        }
+        Ok(session)
+    }
+
+    async fn read_line(&mut self) -> anyhow::Result<Vec<u8>> {
+        let mut line = vec![];
+        if tokio::time::timeout(IO_TIMEOUT, self.stream.read_until(b'\n', &mut line)).await?? == 0 {
+            anyhow::bail!("IMAP connection closed");
+        }
+        Ok(line)
+    }
+
+    /// Send a tagged command and collect its response; errors unless it ends `OK`
+    async fn command(&mut self, command: &str) -> anyhow::Result<ImapResponse> {
+        self.tag += 1;
+        let tag = format!("A{:04}", self.tag);
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{} {}\r\n", tag, command).as_bytes()).await?;
+        stream.flush().await?;
+
+        let mut response = ImapResponse::default();
+        loop {
+            let mut line = self.read_line().await?;
+            // `{N}` announces N raw bytes, after which the line carries on
+            while let Some(len) = literal_len(&line) {
+                let mut literal = vec![0; len];
+                tokio::time::timeout(IO_TIMEOUT, self.stream.read_exact(&mut literal)).await??;
+                response.literals.push(literal);
+                line.extend(self.read_line().await?);
+            }
+
+            let line = String::from_utf8_lossy(&line).trim_end().to_string();
+            if let Some(status) = line.strip_prefix(&tag) {
+                let status = status.trim_start();
+                if status.starts_with("OK") {
+                    return Ok(response);
+                }
+                // Only the verb - LOGIN arguments carry the password
+                let verb = command
+                    .split_whitespace()
+                    .take_while(|word| word.chars().all(|c| c.is_ascii_uppercase()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                anyhow::bail!("IMAP {} failed: {}", verb, status);
+            }
+            response.lines.push(line);
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for EmailChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Email
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        tracing::info!("📧 Email channel polling {} on {}", self.config.mailbox, self.config.imap_host);
+        let interval = Duration::from_secs(self.config.poll_interval_secs.max(10));
+
+        while !tx.is_closed() {
+            if let Err(e) = self.poll_once(&tx).await {
+                tracing::warn!("Email poll failed: {}", e);
+                self.connected.store(false, Ordering::Relaxed);
+            }
+            tokio::time::sleep(interval).await;
+        }
+        Ok(())
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(());
+        }
+        let subject = msg.metadata["subject"].as_str().unwrap_or_default();
+        let subject = if subject.to_lowercase().starts_with("re:") {
+            subject.to_string()
+        } else if subject.is_empty() {
+            format!("Re: message from {}", self.config.from_name)
+        } else {
+            format!("Re: {}", subject)
+        };
+
+        let mut builder = Message::builder()
+            .from(self.mailbox()?)
+            .to(msg.channel_id.parse()?)
+            .subject(subject);
+        if let Some(id) = &msg.reply_to {
+            let mut references: Vec<String> = msg.metadata["references"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r.as_str())
+                .map(bracketed)
+                .collect();
+            references.push(bracketed(id));
+            builder = builder.in_reply_to(bracketed(id)).references(references.join(" "));
+        }
+
+        let email = builder.header(ContentType::TEXT_PLAIN).body(msg.content)?;
+        self.smtp.send(email).await?;
+        Ok(())
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let mime = match path.extension().and_then(|e| e.to_str()) {
+            Some("pdf") => "application/pdf",
+            Some("html") => "text/html",
+            _ => "application/octet-stream",
+        };
+        let bytes = tokio::fs::read(path).await?;
+
+        let email = Message::builder()
+            .from(self.mailbox()?)
+            .to(channel_id.parse()?)
+            .subject(caption.unwrap_or(&file_name))
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(caption.unwrap_or_default().to_string()))
+                    .singlepart(Attachment::new(file_name).body(bytes, ContentType::parse(mime)?)),
+            )?;
+        self.smtp.send(email).await?;
+        Ok(())
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
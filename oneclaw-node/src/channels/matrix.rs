@@ -0,0 +1,414 @@
+//! Matrix Channel
+//!
+//! Talks to any Matrix homeserver over the client-server API, so the whole
+//! chat path can be self-hosted. Handles:
+//! - `/sync` long-polling, starting from "now" so old room history is not replayed
+//! - Joining rooms the bot is invited to (`auto_join`), filtered by `allowed_rooms`
+//! - Answering every message in 1:1 rooms and mentions elsewhere (`trigger`)
+//! - Sending replies as `m.text` (with an HTML body) and reports as `m.file`;
+//!   replies to messages in a thread (`m.thread`) stay in that thread
+//!
+//! Only unencrypted rooms are supported; encrypted events are skipped.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::MatrixChannelConfig;
+
+/// How long the homeserver holds a `/sync` open when nothing happens
+const SYNC_TIMEOUT_MS: u64 = 30_000;
+
+/// Wait before retrying after a failed sync (doubles up to `MAX_BACKOFF`)
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Keep message bodies well under the 64 KiB event limit
+const MAX_MESSAGE_CHARS: usize = 16_000;
+
+#[derive(Clone)]
+pub struct MatrixChannel {
+    config: MatrixChannelConfig,
+    access_token: String,
+    user_id: Arc<RwLock<Option<String>>>,
+    /// Joined member count per room; sync only reports it when it changes
+    room_sizes: Arc<RwLock<HashMap<String, u64>>>,
+    http_client: reqwest::Client,
+    connected: Arc<AtomicBool>,
+    txn_counter: Arc<AtomicU64>,
+}
+
+impl MatrixChannel {
+    pub fn new(config: MatrixChannelConfig) -> anyhow::Result<Self> {
+        if config.homeserver_url.trim().is_empty() {
+            anyhow::bail!("channels.matrix.homeserver_url not set");
+        }
+        let access_token = std::env::var(&config.token_env)
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Matrix token not found in env: {}", config.token_env))?;
+
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_millis(SYNC_TIMEOUT_MS) + Duration::from_secs(15))
+            .build()?;
+
+        Ok(Self {
+            config,
+            access_token,
+            user_id: Arc::new(RwLock::new(None)),
+            room_sizes: Arc::new(RwLock::new(HashMap::new())),
+            http_client,
+            connected: Arc::new(AtomicBool::new(false)),
+            txn_counter: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/_matrix/client/v3{}", self.config.homeserver_url.trim_end_matches('/'), path)
+    }
+
+    /// Call the client-server API; Matrix reports failures as `errcode`/`error`
+    async fn api_call(&self, request: reqwest::RequestBuilder) -> anyhow::Result<serde_json::Value> {
+        let response = request.bearer_auth(&self.access_token).send().await?;
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!(
+                "Matrix API error ({}): {} {}",
+                status,
+                body["errcode"].as_str().unwrap_or("unknown"),
+                body["error"].as_str().unwrap_or_default()
+            );
+        }
+        Ok(body)
+    }
+
+    /// Our own user id, looked up once via `whoami`
+    async fn whoami(&self) -> anyhow::Result<String> {
+        if let Some(id) = self.user_id.read().await.clone() {
+            return Ok(id);
+        }
+        let me = self.api_call(self.http_client.get(self.url("/account/whoami"))).await?;
+        let id = me["user_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Matrix whoami returned no user_id"))?
+            .to_string();
+        tracing::info!(user_id = %id, "Matrix bot authenticated");
+        *self.user_id.write().await = Some(id.clone());
+        Ok(id)
+    }
+
+    async fn sync(&self, since: Option<&str>, timeout_ms: u64) -> anyhow::Result<serde_json::Value> {
+        let mut query = vec![("timeout", timeout_ms.to_string())];
+        if let Some(since) = since {
+            query.push(("since", since.to_string()));
+        }
+        self.api_call(self.http_client.get(self.url("/sync")).query(&query)).await
+    }
+
+    fn room_allowed(&self, room_id: &str) -> bool {
+        self.config.allowed_rooms.iter().any(|r| r == "*" || r == room_id)
+    }
+
+    /// Join rooms we were invited to since the last sync
+    async fn accept_invites(&self, sync: &serde_json::Value) {
+        let Some(invites) = sync["rooms"]["invite"].as_object() else {
+            return;
+        };
+        for room_id in invites.keys() {
+            if !self.config.auto_join || !self.room_allowed(room_id) {
+                tracing::info!("Ignoring Matrix invite to {}", room_id);
+                continue;
+            }
+            let url = self.url(&format!("/rooms/{}/join", encode(room_id)));
+            match self.api_call(self.http_client.post(url).json(&serde_json::json!({}))).await {
+                Ok(_) => tracing::info!("Joined Matrix room {}", room_id),
+                Err(e) => tracing::warn!("Failed to join Matrix room {}: {}", room_id, e),
+            }
+        }
+    }
+
+    async fn update_room_sizes(&self, sync: &serde_json::Value) {
+        let Some(rooms) = sync["rooms"]["join"].as_object() else {
+            return;
+        };
+        let mut sizes = self.room_sizes.write().await;
+        for (room_id, room) in rooms {
+            if let Some(count) = room["summary"]["m.joined_member_count"].as_u64() {
+                sizes.insert(room_id.clone(), count);
+            }
+        }
+    }
+
+    /// Forward new text messages from joined rooms
+    async fn handle_timeline(&self, sync: &serde_json::Value, own_id: &str, tx: &mpsc::Sender<IncomingMessage>) {
+        let Some(rooms) = sync["rooms"]["join"].as_object() else {
+            return;
+        };
+        for (room_id, room) in rooms {
+            if !self.room_allowed(room_id) {
+                continue;
+            }
+            // A room with just us and one other person is a DM
+            let direct = self.room_sizes.read().await.get(room_id) == Some(&2);
+
+            for event in room["timeline"]["events"].as_array().into_iter().flatten() {
+                let sender = event["sender"].as_str().unwrap_or_default();
+                if sender == own_id || sender.is_empty() {
+                    continue;
+                }
+                match event["type"].as_str() {
+                    Some("m.room.message") => {}
+                    Some("m.room.encrypted") => {
+                        tracing::warn!("Skipping encrypted message in {} (encrypted rooms aren't supported)", room_id);
+                        continue;
+                    }
+                    _ => continue,
+                }
+                if event["content"]["msgtype"].as_str() != Some("m.text") {
+                    continue;
+                }
+                let body = event["content"]["body"].as_str().unwrap_or_default();
+                let mentioned = body.contains(own_id) || mentions_user(&event["content"], own_id);
+                if !(direct || mentioned || self.config.trigger == "all") {
+                    continue;
+                }
+
+                let content = strip_reply_fallback(body).replace(own_id, "").trim().to_string();
+                if content.is_empty() {
+                    continue;
+                }
+                let incoming = IncomingMessage {
+                    channel_type: ChannelType::Matrix,
+                    channel_id: room_id.clone(),
+                    provider_user_id: sender.to_string(),
+                    username: Some(sender.to_string()),
+                    content,
+                    timestamp: chrono::Utc::now(),
+                    reply_to: event["event_id"].as_str().map(str::to_string),
+                    thread_id: thread_root(&event["content"]),
+                    metadata: event.clone(),
+                };
+                if let Err(e) = tx.send(incoming).await {
+                    tracing::error!("Failed to send message to handler: {}", e);
+                }
+            }
+        }
+    }
+
+    /// PUT an event into a room with a fresh transaction id
+    async fn send_event(&self, room_id: &str, content: serde_json::Value) -> anyhow::Result<()> {
+        let txn = format!(
+            "oneclaw-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            self.txn_counter.fetch_add(1, Ordering::Relaxed)
+        );
+        let url = self.url(&format!("/rooms/{}/send/m.room.message/{}", encode(room_id), txn));
+        self.api_call(self.http_client.put(url).json(&content)).await?;
+        Ok(())
+    }
+}
+
+/// Whether an event's `m.mentions` names us
+fn mentions_user(content: &serde_json::Value, user_id: &str) -> bool {
+    content["m.mentions"]["user_ids"]
+        .as_array()
+        .is_some_and(|ids| ids.iter().any(|id| id.as_str() == Some(user_id)))
+}
+
+/// Root event of the thread an event's `content` was posted in
+fn thread_root(content: &serde_json::Value) -> Option<String> {
+    let relation = &content["m.relates_to"];
+    if relation["rel_type"].as_str() != Some("m.thread") {
+        return None;
+    }
+    relation["event_id"].as_str().map(str::to_string)
+}
+
+/// Drop the quoted `> <@user> …` block clients prepend to replies
+fn strip_reply_fallback(body: &str) -> String {
+    body.lines()
+        .skip_while(|line| line.starts_with("> "))
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Percent-encode a room/user id for use in a URL path segment
+fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Minimal Markdown → HTML for `formatted_body` (bold, links, line breaks)
+fn to_html(content: &str) -> String {
+    let escaped = content.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let bold = regex::Regex::new(r"\*\*(.+?)\*\*").expect("valid regex");
+    let link = regex::Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").expect("valid regex");
+
+    let html = bold.replace_all(&escaped, "<strong>$1</strong>");
+    let html = link.replace_all(&html, "<a href=\"$2\">$1</a>");
+    html.replace('\n', "<br>")
+}
+
+/// Split a reply into event-sized chunks, preferring line boundaries
+fn split_message(content: &str) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    for line in content.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > MAX_MESSAGE_CHARS && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if line.chars().count() > MAX_MESSAGE_CHARS {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(MAX_MESSAGE_CHARS) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[async_trait]
+impl Channel for MatrixChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Matrix
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        let own_id = self.whoami().await?;
+
+        // An initial zero-timeout sync gives us a token for "now"; history before it is skipped
+        let initial = self.sync(None, 0).await?;
+        self.update_room_sizes(&initial).await;
+        let mut next_batch = initial["next_batch"].as_str().map(str::to_string);
+        self.connected.store(true, Ordering::Relaxed);
+        tracing::info!("🟢 Matrix channel syncing with {}", self.config.homeserver_url);
+
+        let mut backoff = MIN_BACKOFF;
+        while !tx.is_closed() {
+            match self.sync(next_batch.as_deref(), SYNC_TIMEOUT_MS).await {
+                Ok(sync) => {
+                    self.connected.store(true, Ordering::Relaxed);
+                    backoff = MIN_BACKOFF;
+                    self.accept_invites(&sync).await;
+                    self.update_room_sizes(&sync).await;
+                    self.handle_timeline(&sync, &own_id, &tx).await;
+                    if let Some(token) = sync["next_batch"].as_str() {
+                        next_batch = Some(token.to_string());
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Matrix sync failed, retrying in {:?}: {}", backoff, e);
+                    self.connected.store(false, Ordering::Relaxed);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(());
+        }
+        for (i, chunk) in split_message(&msg.content).iter().enumerate() {
+            let mut content = serde_json::json!({
+                "msgtype": "m.text",
+                "body": chunk,
+                "format": "org.matrix.custom.html",
+                "formatted_body": to_html(chunk),
+            });
+            if let Some(root) = &msg.thread_id {
+                // Later chunks fall back to replying to the thread root
+                let reply_to = msg.reply_to.as_deref().filter(|_| i == 0).unwrap_or(root);
+                content["m.relates_to"] = serde_json::json!({
+                    "rel_type": "m.thread",
+                    "event_id": root,
+                    "is_falling_back": i > 0 || msg.reply_to.is_none(),
+                    "m.in_reply_to": { "event_id": reply_to },
+                });
+            } else if let (0, Some(event_id)) = (i, &msg.reply_to) {
+                content["m.relates_to"] = serde_json::json!({ "m.in_reply_to": { "event_id": event_id } });
+            }
+            self.send_event(&msg.channel_id, content).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let mime = match path.extension().and_then(|e| e.to_str()) {
+            Some("pdf") => "application/pdf",
+            Some("html") => "text/html",
+            _ => "application/octet-stream",
+        };
+        let bytes = tokio::fs::read(path).await?;
+        let size = bytes.len();
+
+        let upload_url = format!("{}/_matrix/media/v3/upload", self.config.homeserver_url.trim_end_matches('/'));
+        let uploaded = self
+            .api_call(
+                self.http_client
+                    .post(upload_url)
+                    .query(&[("filename", file_name.as_str())])
+                    .header("Content-Type", mime)
+                    .body(bytes),
+            )
+            .await?;
+        let Some(content_uri) = uploaded["content_uri"].as_str() else {
+            anyhow::bail!("Matrix upload returned no content_uri");
+        };
+
+        self.send_event(channel_id, serde_json::json!({
+            "msgtype": "m.file",
+            "body": file_name,
+            "url": content_uri,
+            "info": { "mimetype": mime, "size": size },
+        }))
+        .await?;
+        if let Some(caption) = caption {
+            self.send_event(channel_id, serde_json::json!({ "msgtype": "m.text", "body": caption })).await?;
+        }
+        Ok(())
+    }
+
+    /// Show "typing…" in a room while a reply is being worked on
+    async fn indicate_activity(&self, room_id: &str) -> anyhow::Result<()> {
+        let own_id = self.whoami().await?;
+        let url = self.url(&format!("/rooms/{}/typing/{}", encode(room_id), encode(&own_id)));
+        self.api_call(self.http_client.put(url).json(&serde_json::json!({ "typing": true, "timeout": 10_000 })))
+            .await?;
+        Ok(())
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
@@ -3,23 +3,43 @@
 //! Channels provide different ways to interact with the node:
 //! - Discord: WebSocket connection to Discord Gateway
 //! - Slack: Socket Mode connection
+//! - Matrix: Client-server API sync long-polling
 //! - Telegram: Long polling
+//! - Voice: Twilio phone-call webhooks (routes in daemon.rs)
+//! - WhatsApp: Meta Cloud API webhooks (routes in daemon.rs)
+//! - Email: IMAP inbox polling, SMTP replies
 //! - HTTP: REST API (handled by daemon.rs)
 
+pub mod delivery;
 pub mod discord;
+pub mod dispatch;
+pub mod email;
+pub mod matrix;
+pub mod outbound;
+pub mod presence;
+pub mod registry;
+pub mod slack;
 pub mod telegram;
+pub mod voice;
+pub mod whatsapp;
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
+pub use presence::Presence;
+
 /// Channel type identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ChannelType {
     Discord,
     Slack,
+    Matrix,
     Telegram,
+    Voice,
+    WhatsApp,
+    Email,
     Http,
 }
 
@@ -28,7 +48,11 @@ impl std::fmt::Display for ChannelType {
         match self {
             ChannelType::Discord => write!(f, "discord"),
             ChannelType::Slack => write!(f, "slack"),
+            ChannelType::Matrix => write!(f, "matrix"),
             ChannelType::Telegram => write!(f, "telegram"),
+            ChannelType::Voice => write!(f, "voice"),
+            ChannelType::WhatsApp => write!(f, "whatsapp"),
+            ChannelType::Email => write!(f, "email"),
             ChannelType::Http => write!(f, "http"),
         }
     }
@@ -43,10 +67,26 @@ pub struct IncomingMessage {
     pub username: Option<String>,  // Display name
     pub content: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
-    pub reply_to: Option<String>,  // For threading
+    pub reply_to: Option<String>,  // Message to reply to
+    /// Conversation thread within the channel (Slack thread, email thread,
+    /// Telegram reply chain); each thread keeps its own context
+    #[serde(default)]
+    pub thread_id: Option<String>,
     pub metadata: serde_json::Value,
 }
 
+impl IncomingMessage {
+    /// The button action, if this message is a button press rather than typed text
+    pub fn action(&self) -> Option<&str> {
+        self.metadata["action"].as_str()
+    }
+
+    /// Text of the message the pressed button was attached to
+    pub fn source_message(&self) -> Option<&str> {
+        self.metadata["source_message"].as_str()
+    }
+}
+
 /// Outgoing message to send via a channel
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutgoingMessage {
@@ -54,7 +94,50 @@ pub struct OutgoingMessage {
     pub channel_id: String,
     pub content: String,
     pub reply_to: Option<String>,
+    /// Thread to post in, from the `IncomingMessage` being answered
+    #[serde(default)]
+    pub thread_id: Option<String>,
     pub metadata: serde_json::Value,
+    /// Files sent along with the message (channels without inline attachments use `send_file`)
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Rows of inline buttons (Telegram); channels without them send the text only
+    #[serde(default)]
+    pub buttons: Vec<Vec<Button>>,
+}
+
+/// A file to send with an `OutgoingMessage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub path: std::path::PathBuf,
+    #[serde(default)]
+    pub caption: Option<String>,
+}
+
+impl Attachment {
+    /// Whether the file can be shown inline as a photo (by extension)
+    pub fn is_image(&self) -> bool {
+        matches!(
+            self.path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref(),
+            Some("jpg" | "jpeg" | "png" | "gif" | "webp")
+        )
+    }
+}
+
+/// An inline button under a message. Pressing it comes back as an `IncomingMessage`
+/// whose content is `action`, with `metadata.action` set (see `IncomingMessage::action`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Button {
+    pub label: String,
+    /// What the press says on the user's behalf: a command (`/snooze 1h`), a reply
+    /// ("show more") or an id the handler recognizes. At most 64 bytes.
+    pub action: String,
+}
+
+impl Button {
+    pub fn new(label: impl Into<String>, action: impl Into<String>) -> Self {
+        Self { label: label.into(), action: action.into() }
+    }
 }
 
 /// Channel trait - all channels implement this
@@ -69,60 +152,77 @@ pub trait Channel: Send + Sync {
     /// Send a message through this channel
     async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()>;
     
+    /// Send, returning the provider's id for the sent message where it gives one
+    /// (recorded by the `OutboundQueue`, see `delivery.rs`)
+    async fn deliver(&self, msg: OutgoingMessage) -> anyhow::Result<Option<String>> {
+        self.send(msg).await.map(|_| None)
+    }
+    
+    /// Send at a given priority; only matters behind an `OutboundQueue`
+    async fn send_with_priority(&self, msg: OutgoingMessage, priority: outbound::Priority) -> anyhow::Result<()> {
+        let _ = priority;
+        self.send(msg).await
+    }
+    
     /// Stop the channel gracefully
     async fn stop(&self) -> anyhow::Result<()>;
-}
-
-/// Channel manager - coordinates all active channels
-pub struct ChannelManager {
-    channels: Vec<Box<dyn Channel>>,
-    message_tx: mpsc::Sender<IncomingMessage>,
-    message_rx: Option<mpsc::Receiver<IncomingMessage>>,
-}
-
-impl ChannelManager {
-    pub fn new() -> Self {
-        let (tx, rx) = mpsc::channel(100);
-        Self {
-            channels: Vec::new(),
-            message_tx: tx,
-            message_rx: Some(rx),
-        }
-    }
     
-    /// Add a channel to the manager
-    pub fn add_channel(&mut self, channel: Box<dyn Channel>) {
-        self.channels.push(channel);
+    /// Send a file (e.g. a generated report) with an optional caption
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        let _ = (channel_id, caption);
+        anyhow::bail!("{} channel does not support attachments ({})", self.channel_type(), path.display())
     }
     
-    /// Start all channels
-    pub async fn start_all(&self) -> anyhow::Result<()> {
-        for channel in &self.channels {
-            let tx = self.message_tx.clone();
-            let channel_type = channel.channel_type();
-            
-            // Start each channel in its own task
-            tokio::spawn(async move {
-                tracing::info!(channel = %channel_type, "Starting channel");
-                // Note: This would need a reference to the channel
-                // In practice, we'd use Arc<dyn Channel> or similar
-            });
-        }
+    /// Show that a reply is being worked on in `channel_id` ("typing…"); no-op by
+    /// default. Indicators expire after a few seconds, so `ActivityGuard` refreshes them
+    /// (one loop per chat, see `presence.rs`).
+    async fn indicate_activity(&self, channel_id: &str) -> anyhow::Result<()> {
+        let _ = channel_id;
         Ok(())
     }
     
-    /// Get the message receiver (takes ownership)
-    pub fn take_receiver(&mut self) -> Option<mpsc::Receiver<IncomingMessage>> {
-        self.message_rx.take()
+    /// Whether the channel is currently connected to its provider (used by `/readyz`)
+    fn is_connected(&self) -> bool {
+        true
     }
-    
-    /// Send a message to a specific channel
-    pub async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
-        for channel in &self.channels {
-            if channel.channel_type() == msg.channel_type {
-                return channel.send(msg).await;
-            }
-        }
-        anyhow::bail!("No channel found for type: {:?}", msg.channel_type)
+}
+
+/// Shared channels (`Arc<dyn Channel>` from the registry) are channels too
+#[async_trait]
+impl<T: Channel + ?Sized> Channel for std::sync::Arc<T> {
+    fn channel_type(&self) -> ChannelType {
+        (**self).channel_type()
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        (**self).start(tx).await
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        (**self).send(msg).await
+    }
+
+    async fn deliver(&self, msg: OutgoingMessage) -> anyhow::Result<Option<String>> {
+        (**self).deliver(msg).await
+    }
+
+    async fn send_with_priority(&self, msg: OutgoingMessage, priority: outbound::Priority) -> anyhow::Result<()> {
+        (**self).send_with_priority(msg, priority).await
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        (**self).stop().await
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        (**self).send_file(channel_id, path, caption).await
+    }
+
+    async fn indicate_activity(&self, channel_id: &str) -> anyhow::Result<()> {
+        (**self).indicate_activity(channel_id).await
+    }
+
+    fn is_connected(&self) -> bool {
+        (**self).is_connected()
     }
 }
@@ -0,0 +1,313 @@
+//! Outbound Send Queue
+//!
+//! Wraps a channel so sends are paced to the provider's limits instead of
+//! being throttled (and dropped) by it:
+//! - A global rate across all chats and a per-chat rate (`channels.rate_limits`)
+//! - Priority classes: interactive replies go out before job notifications,
+//!   which go out before progress digests; FIFO within a class
+//! - A bounded backlog: when full, the oldest lowest-priority message is dropped
+//! - Rejected sends are retried with backoff, and every message's fate is
+//!   recorded (`delivery.rs`)
+//!
+//! Keep one queue per channel for the life of the process; its worker task
+//! runs until shutdown. Each `send` resolves once the message is delivered,
+//! or its last attempt failed.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex, Notify};
+use tokio::time::Instant;
+
+use super::delivery::{self, Recorder, MAX_ATTEMPTS};
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::RateLimitConfig;
+use crate::store::Delivery;
+
+/// Send order when a queue is backed up (highest first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Replies to something the user just said
+    Interactive,
+    /// Job outcomes (completed, failed, recovering)
+    Notification,
+    /// Step-by-step progress and other noise
+    Digest,
+}
+
+#[derive(Clone)]
+enum Outbound {
+    Message(OutgoingMessage),
+    File { channel_id: String, path: PathBuf, caption: Option<String> },
+}
+
+struct Queued {
+    priority: Priority,
+    seq: u64,
+    chat: String,
+    item: Outbound,
+    delivery: Delivery,
+    /// Not before this, after a failed attempt
+    retry_at: Option<Instant>,
+    done: oneshot::Sender<anyhow::Result<()>>,
+}
+
+#[derive(Default)]
+struct Pacing {
+    queue: Vec<Queued>,
+    seq: u64,
+    next_global: Option<Instant>,
+    next_chat: HashMap<String, Instant>,
+}
+
+enum Step {
+    Send(Box<Queued>),
+    Wait(Instant),
+    Idle,
+}
+
+impl Pacing {
+    /// Next message allowed out now, or how long until one is
+    fn next_step(&mut self, limits: &RateLimitConfig) -> Step {
+        let now = Instant::now();
+        if let Some(at) = self.next_global.filter(|at| *at > now) {
+            return if self.queue.is_empty() { Step::Idle } else { Step::Wait(at) };
+        }
+        self.next_chat.retain(|_, at| *at > now);
+
+        let ready = self
+            .queue
+            .iter()
+            .enumerate()
+            .filter(|(_, q)| !self.next_chat.contains_key(&q.chat) && q.retry_at.is_none_or(|at| at <= now))
+            .min_by_key(|(_, q)| (q.priority, q.seq))
+            .map(|(i, _)| i);
+        if let Some(index) = ready {
+            let queued = self.queue.remove(index);
+            self.next_global = Some(now + interval(limits.global_per_sec));
+            self.next_chat.insert(queued.chat.clone(), now + interval(limits.per_chat_per_sec));
+            return Step::Send(Box::new(queued));
+        }
+
+        // Everything queued is waiting on its chat's limit or its retry
+        self.queue
+            .iter()
+            .filter_map(|q| self.next_chat.get(&q.chat).copied().max(q.retry_at))
+            .min()
+            .map_or(Step::Idle, Step::Wait)
+    }
+}
+
+fn interval(per_sec: f64) -> Duration {
+    if per_sec > 0.0 {
+        Duration::from_secs_f64(1.0 / per_sec)
+    } else {
+        Duration::ZERO
+    }
+}
+
+pub struct OutboundQueue<C> {
+    inner: Arc<C>,
+    pacing: Arc<Mutex<Pacing>>,
+    wake: Arc<Notify>,
+    max_queued: usize,
+    deliveries: Recorder,
+}
+
+impl<C> Clone for OutboundQueue<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            pacing: Arc::clone(&self.pacing),
+            wake: Arc::clone(&self.wake),
+            max_queued: self.max_queued,
+            deliveries: self.deliveries.clone(),
+        }
+    }
+}
+
+impl<C: Channel + 'static> OutboundQueue<C> {
+    /// Wrap `inner` and start the worker that drains the queue, recording
+    /// each message's fate with `deliveries`
+    pub fn new(inner: C, limits: RateLimitConfig, deliveries: Recorder) -> Self {
+        let queue = Self {
+            inner: Arc::new(inner),
+            pacing: Arc::new(Mutex::new(Pacing::default())),
+            wake: Arc::new(Notify::new()),
+            max_queued: limits.max_queued.max(1),
+            deliveries,
+        };
+
+        let (inner, pacing, wake) = (queue.inner.clone(), queue.pacing.clone(), queue.wake.clone());
+        let deliveries = queue.deliveries.clone();
+        tokio::spawn(async move {
+            loop {
+                let step = pacing.lock().await.next_step(&limits);
+                match step {
+                    Step::Send(mut queued) => {
+                        let result = match queued.item.clone() {
+                            Outbound::Message(msg) => inner.deliver(msg).await,
+                            Outbound::File { channel_id, path, caption } => {
+                                inner.send_file(&channel_id, &path, caption.as_deref()).await.map(|_| None)
+                            }
+                        };
+                        queued.delivery.attempts += 1;
+                        match result {
+                            Ok(message_id) => {
+                                queued.delivery.provider_message_id = message_id;
+                                queued.delivery.error = None;
+                                deliveries.update(&mut queued.delivery, "sent");
+                                let _ = queued.done.send(Ok(()));
+                            }
+                            Err(e) if queued.delivery.attempts < MAX_ATTEMPTS => {
+                                queued.delivery.error = Some(e.to_string());
+                                deliveries.update(&mut queued.delivery, "retrying");
+                                queued.retry_at = Some(Instant::now() + delivery::backoff(queued.delivery.attempts));
+                                pacing.lock().await.queue.push(*queued);
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "{} delivery {} failed after {} attempts: {}",
+                                    inner.channel_type(), queued.delivery.id, queued.delivery.attempts, e
+                                );
+                                queued.delivery.error = Some(e.to_string());
+                                deliveries.update(&mut queued.delivery, "failed");
+                                let _ = queued.done.send(Err(e));
+                            }
+                        }
+                    }
+                    Step::Wait(until) => {
+                        tokio::select! {
+                            _ = tokio::time::sleep_until(until) => {}
+                            _ = wake.notified() => {}
+                        }
+                    }
+                    Step::Idle => wake.notified().await,
+                }
+            }
+        });
+        queue
+    }
+
+    async fn enqueue(&self, chat: String, priority: Priority, item: Outbound) -> anyhow::Result<()> {
+        let (done, result) = oneshot::channel();
+        {
+            let mut pacing = self.pacing.lock().await;
+            if pacing.queue.len() >= self.max_queued {
+                let victim = pacing
+                    .queue
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, q)| (q.priority, std::cmp::Reverse(q.seq)))
+                    .map(|(i, q)| (i, q.priority));
+                match victim {
+                    Some((index, victim_priority)) if victim_priority >= priority => {
+                        let mut dropped = pacing.queue.remove(index);
+                        tracing::warn!("{} outbound queue full, dropping a {:?} message", self.inner.channel_type(), dropped.priority);
+                        dropped.delivery.error = Some("Outbound queue full".to_string());
+                        self.deliveries.update(&mut dropped.delivery, "dropped");
+                        let _ = dropped.done.send(Err(anyhow::anyhow!("Dropped: outbound queue full")));
+                    }
+                    _ => anyhow::bail!("{} outbound queue full", self.inner.channel_type()),
+                }
+            }
+            pacing.seq += 1;
+            let seq = pacing.seq;
+            let mut delivery = delivery::queued(self.inner.channel_type(), &chat);
+            self.deliveries.update(&mut delivery, "queued");
+            pacing.queue.push(Queued { priority, seq, chat, item, delivery, retry_at: None, done });
+        }
+        self.wake.notify_one();
+
+        result
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Outbound queue stopped")))
+    }
+}
+
+#[async_trait]
+impl<C: Channel + 'static> Channel for OutboundQueue<C> {
+    fn channel_type(&self) -> ChannelType {
+        self.inner.channel_type()
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        self.inner.start(tx).await
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        self.send_with_priority(msg, Priority::Interactive).await
+    }
+
+    async fn send_with_priority(&self, msg: OutgoingMessage, priority: Priority) -> anyhow::Result<()> {
+        self.enqueue(msg.channel_id.clone(), priority, Outbound::Message(msg)).await
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        let item = Outbound::File {
+            channel_id: channel_id.to_string(),
+            path: path.to_path_buf(),
+            caption: caption.map(str::to_string),
+        };
+        self.enqueue(channel_id.to_string(), Priority::Interactive, item).await
+    }
+
+    /// Typing indicators bypass the queue
+    async fn indicate_activity(&self, channel_id: &str) -> anyhow::Result<()> {
+        self.inner.indicate_activity(channel_id).await
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        self.inner.stop().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(pacing: &mut Pacing, chat: &str, priority: Priority) {
+        pacing.seq += 1;
+        pacing.queue.push(Queued {
+            priority,
+            seq: pacing.seq,
+            chat: chat.to_string(),
+            item: Outbound::File { channel_id: chat.to_string(), path: PathBuf::new(), caption: None },
+            delivery: delivery::queued(ChannelType::Telegram, chat),
+            retry_at: None,
+            done: oneshot::channel().0,
+        });
+    }
+
+    #[test]
+    fn test_priority_first_and_chats_paced() {
+        let limits = RateLimitConfig { global_per_sec: 0.0, per_chat_per_sec: 1.0, max_queued: 10 };
+        let mut pacing = Pacing::default();
+        queued(&mut pacing, "a", Priority::Digest);
+        queued(&mut pacing, "a", Priority::Interactive);
+        queued(&mut pacing, "b", Priority::Notification);
+
+        let mut order = vec![];
+        while let Step::Send(q) = pacing.next_step(&limits) {
+            order.push((q.chat, q.priority));
+        }
+        // Chat "a" used its slot on the reply; its digest waits for the next second
+        assert_eq!(order, vec![("a".to_string(), Priority::Interactive), ("b".to_string(), Priority::Notification)]);
+        assert!(matches!(pacing.next_step(&limits), Step::Wait(_)));
+
+        // A message waiting to be retried doesn't hold up the ones behind it
+        let mut pacing = Pacing::default();
+        queued(&mut pacing, "c", Priority::Interactive);
+        queued(&mut pacing, "d", Priority::Digest);
+        let retry_at = Instant::now() + Duration::from_secs(5);
+        pacing.queue[0].retry_at = Some(retry_at);
+        assert!(matches!(pacing.next_step(&limits), Step::Send(q) if q.chat == "d"));
+        assert!(matches!(pacing.next_step(&limits), Step::Wait(at) if at == retry_at));
+    }
+}
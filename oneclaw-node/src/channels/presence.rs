@@ -0,0 +1,188 @@
+//! Presence - One "typing…" loop per chat, however many turns are running
+//!
+//! Every turn holds an `ActivityGuard` while it works. Guards for the same chat
+//! share one refresh loop: the first starts it, the last to drop stops it, so
+//! several messages sent in a row don't race each other's indicators (or stop
+//! one while another turn is still working).
+//!
+//! A loop also stops by itself `ACTIVITY_TIMEOUT` after the newest guard was
+//! taken, so a stuck turn doesn't show "typing…" forever; a new guard for the
+//! chat starts it again.
+//!
+//! Each node has its own `Presence` (in its `AppState`), so fleet nodes whose
+//! bots share a chat show and stop their indicators independently.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{Channel, ChannelType};
+
+/// How often the indicator is refreshed (Telegram's lasts ~5s)
+const ACTIVITY_REFRESH: Duration = Duration::from_secs(4);
+
+/// Longest a chat shows activity without a new guard
+const ACTIVITY_TIMEOUT: Duration = Duration::from_secs(180);
+
+type ChatKey = (ChannelType, String);
+
+struct Chat {
+    /// Guards alive for the chat
+    active: usize,
+    /// When the loop gives up
+    until: Instant,
+    refresh: tokio::task::JoinHandle<()>,
+}
+
+/// Reference-counted activity per chat
+pub struct Presence {
+    chats: Mutex<HashMap<ChatKey, Chat>>,
+    refresh: Duration,
+    timeout: Duration,
+}
+
+impl Default for Presence {
+    fn default() -> Self {
+        Self::new(ACTIVITY_REFRESH, ACTIVITY_TIMEOUT)
+    }
+}
+
+impl Presence {
+    fn new(refresh: Duration, timeout: Duration) -> Self {
+        Self { chats: Mutex::new(HashMap::new()), refresh, timeout }
+    }
+
+    /// Take a guard for `channel_id`, starting the chat's loop if it isn't running
+    pub fn acquire(self: &Arc<Self>, channel: Arc<dyn Channel>, channel_id: &str) -> ActivityGuard {
+        let key: ChatKey = (channel.channel_type(), channel_id.to_string());
+        let until = Instant::now() + self.timeout;
+        let mut chats = self.chats.lock().expect("presence lock");
+        match chats.get_mut(&key) {
+            Some(chat) => {
+                chat.active += 1;
+                chat.until = until;
+                if chat.refresh.is_finished() {
+                    chat.refresh = self.spawn_refresh(channel, key.clone());
+                }
+            }
+            None => {
+                let refresh = self.spawn_refresh(channel, key.clone());
+                chats.insert(key.clone(), Chat { active: 1, until, refresh });
+            }
+        }
+        ActivityGuard { presence: Arc::clone(self), key }
+    }
+
+    fn spawn_refresh(self: &Arc<Self>, channel: Arc<dyn Channel>, key: ChatKey) -> tokio::task::JoinHandle<()> {
+        let presence = Arc::clone(self);
+        tokio::spawn(async move {
+            while presence.showing(&key) {
+                let _ = channel.indicate_activity(&key.1).await;
+                tokio::time::sleep(presence.refresh).await;
+            }
+        })
+    }
+
+    /// Whether the chat still has guards and hasn't timed out
+    fn showing(&self, key: &ChatKey) -> bool {
+        let chats = self.chats.lock().expect("presence lock");
+        chats.get(key).is_some_and(|chat| chat.active > 0 && Instant::now() < chat.until)
+    }
+
+    fn release(&self, key: &ChatKey) {
+        let mut chats = self.chats.lock().expect("presence lock");
+        let Some(chat) = chats.get_mut(key) else { return };
+        chat.active = chat.active.saturating_sub(1);
+        if chat.active == 0 {
+            chat.refresh.abort();
+            chats.remove(key);
+        }
+    }
+
+    /// Guards alive for a chat
+    #[cfg(test)]
+    fn active(&self, key: &ChatKey) -> usize {
+        self.chats.lock().expect("presence lock").get(key).map_or(0, |chat| chat.active)
+    }
+}
+
+/// Keeps a chat's activity indicator up until dropped, so it stops on every exit
+/// path of a handler, including a panic
+pub struct ActivityGuard {
+    presence: Arc<Presence>,
+    key: ChatKey,
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        self.presence.release(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl Channel for Counting {
+        fn channel_type(&self) -> ChannelType {
+            ChannelType::Telegram
+        }
+
+        async fn start(&self, _tx: tokio::sync::mpsc::Sender<super::super::IncomingMessage>) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn send(&self, _msg: super::super::OutgoingMessage) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn indicate_activity(&self, _channel_id: &str) -> anyhow::Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_one_loop_per_chat_until_last_guard() {
+        let presence = Arc::new(Presence::new(Duration::from_millis(40), Duration::from_secs(60)));
+        let channel = Arc::new(Counting::default());
+        let key: ChatKey = (ChannelType::Telegram, "chat".to_string());
+
+        let first = presence.acquire(channel.clone(), "chat");
+        let second = presence.acquire(channel.clone(), "chat");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // One loop: ~3 refreshes in 100ms, not ~6
+        let shown = channel.0.load(Ordering::SeqCst);
+        assert!((2..=4).contains(&shown), "{} refreshes", shown);
+        assert_eq!(presence.active(&key), 2);
+
+        // The loop outlives the first turn...
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(channel.0.load(Ordering::SeqCst) > shown);
+        // ...and stops with the last
+        drop(second);
+        assert_eq!(presence.active(&key), 0);
+        let stopped = channel.0.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(channel.0.load(Ordering::SeqCst), stopped);
+
+        // A chat that timed out stops refreshing while its guard is held
+        let presence = Arc::new(Presence::new(Duration::from_millis(20), Duration::from_millis(50)));
+        let guard = presence.acquire(channel.clone(), "other");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let timed_out = channel.0.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(channel.0.load(Ordering::SeqCst), timed_out);
+        drop(guard);
+    }
+}
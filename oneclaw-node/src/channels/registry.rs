@@ -0,0 +1,381 @@
+//! Channel Registry - Config-driven channel activation
+//!
+//! Every channel the node supports has an entry in `SPECS`: how to build it from
+//! the `[channels]` config section (nothing when it isn't configured) and whether
+//! it runs its own listener or receives messages on daemon webhook routes.
+//!
+//! `activate` builds each configured channel, registers it with `HealthState`,
+//! starts its listener (restartable by self-check) and hands the daemon one
+//! receiver per listener. `statuses` backs `GET /channels`.
+//!
+//! Adding a channel means adding a builder and a spec here; the daemon runs a
+//! regular chat turn for every message from a channel it has no special handler for.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::{mpsc, RwLock};
+
+use super::discord::DiscordChannel;
+use super::dispatch::Dispatcher;
+use super::email::EmailChannel;
+use super::matrix::MatrixChannel;
+use super::delivery::Recorder;
+use super::outbound::OutboundQueue;
+use super::slack::SlackChannel;
+use super::telegram::TelegramChannel;
+use super::voice::VoiceChannel;
+use super::whatsapp::WhatsAppChannel;
+use super::{Channel, IncomingMessage, OutgoingMessage};
+use crate::config::NodeConfig;
+use crate::health::{self, ComponentStatus, HealthState};
+
+/// How a channel receives messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Runs its own listener (long polling, websocket, sync)
+    Listener,
+    /// Messages arrive on daemon webhook routes
+    Webhook,
+}
+
+/// A channel built from config
+pub struct Built {
+    channel: Arc<dyn Channel>,
+    outbound: Arc<dyn Channel>,
+    /// Status detail when a webhook channel isn't connected after starting
+    unconfigured: Option<String>,
+}
+
+impl Built {
+    /// Sends are paced by an `OutboundQueue` with the configured limits for `name`
+    fn queued<C: Channel + Clone + 'static>(name: &str, channel: C, config: &NodeConfig, deliveries: &Recorder) -> Self {
+        let limits = config.channels.rate_limit_for(name);
+        Self {
+            channel: Arc::new(channel.clone()),
+            outbound: Arc::new(OutboundQueue::new(channel, limits, deliveries.clone())),
+            unconfigured: None,
+        }
+    }
+
+    /// Sends go straight to the provider
+    fn direct<C: Channel + 'static>(channel: C) -> Self {
+        let channel: Arc<dyn Channel> = Arc::new(channel);
+        Self { channel: Arc::clone(&channel), outbound: channel, unconfigured: None }
+    }
+
+    fn unconfigured(mut self, detail: String) -> Self {
+        self.unconfigured = Some(detail);
+        self
+    }
+}
+
+/// What a node's channels start with besides its config
+pub struct Start<'a> {
+    /// Records what happens to queued sends
+    pub deliveries: &'a Recorder,
+    /// Taking over from a running daemon (`--takeover`)
+    pub takeover: bool,
+}
+
+/// How to activate one kind of channel
+struct Spec {
+    name: &'static str,
+    kind: Kind,
+    /// Status detail once the channel is up
+    mode: &'static str,
+    /// `None` when the channel isn't configured
+    build: fn(&NodeConfig, &Start) -> Option<anyhow::Result<Built>>,
+}
+
+/// Supported channels, in start order
+const SPECS: &[Spec] = &[
+    Spec { name: "telegram", kind: Kind::Listener, mode: "polling", build: telegram },
+    Spec { name: "discord", kind: Kind::Listener, mode: "gateway", build: discord },
+    Spec { name: "slack", kind: Kind::Listener, mode: "socket mode", build: slack },
+    Spec { name: "matrix", kind: Kind::Listener, mode: "sync", build: matrix },
+    Spec { name: "email", kind: Kind::Listener, mode: "imap polling", build: email },
+    Spec { name: "voice", kind: Kind::Webhook, mode: "webhooks", build: voice },
+    Spec { name: "whatsapp", kind: Kind::Webhook, mode: "webhooks", build: whatsapp },
+];
+
+/// The token in env var `name`, if set
+fn env_token(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|t| !t.trim().is_empty())
+}
+
+fn telegram_token(config: &NodeConfig) -> Option<String> {
+    env_token(&config.channels.telegram.token_env).filter(|t| t != "your_telegram_bot_token_here")
+}
+
+/// The tokens `config`'s listeners would connect with, by channel name. Two
+/// nodes listening with one token would each get only part of its messages.
+pub fn listener_tokens(config: &NodeConfig) -> Vec<(&'static str, String)> {
+    let channels = &config.channels;
+    let slack = env_token(&channels.slack.token_env).and(env_token(&channels.slack.app_token_env));
+    let discord = channels.discord.enabled.then(|| env_token(&channels.discord.token_env)).flatten();
+    let matrix = channels.matrix.enabled.then(|| env_token(&channels.matrix.token_env)).flatten();
+    [("telegram", telegram_token(config)), ("slack", slack), ("discord", discord), ("matrix", matrix)]
+        .into_iter()
+        .filter_map(|(name, token)| Some((name, token?)))
+        .collect()
+}
+
+/// Telegram starts whenever its bot token is set
+fn telegram(config: &NodeConfig, start: &Start) -> Option<anyhow::Result<Built>> {
+    let token = telegram_token(config)?;
+    let telegram = TelegramChannel::new(token)
+        .with_group_trigger(config.channels.telegram.group_trigger.clone())
+        .taking_over(start.takeover);
+    Some(Ok(Built::queued("telegram", telegram, config, start.deliveries)))
+}
+
+fn discord(config: &NodeConfig, start: &Start) -> Option<anyhow::Result<Built>> {
+    config.channels.discord.enabled.then(|| {
+        DiscordChannel::new(config.channels.discord.clone()).map(|c| Built::queued("discord", c, config, start.deliveries))
+    })
+}
+
+/// Slack starts when both tokens are configured
+fn slack(config: &NodeConfig, start: &Start) -> Option<anyhow::Result<Built>> {
+    let slack = SlackChannel::new(config.channels.slack.clone()).ok()?;
+    Some(Ok(Built::queued("slack", slack, config, start.deliveries)))
+}
+
+fn matrix(config: &NodeConfig, start: &Start) -> Option<anyhow::Result<Built>> {
+    config.channels.matrix.enabled.then(|| {
+        MatrixChannel::new(config.channels.matrix.clone()).map(|c| Built::queued("matrix", c, config, start.deliveries))
+    })
+}
+
+/// Email replies are one per thread, so they skip the outbound queue
+fn email(config: &NodeConfig, _: &Start) -> Option<anyhow::Result<Built>> {
+    config.channels.email.enabled.then(|| EmailChannel::new(config.channels.email.clone()).map(Built::direct))
+}
+
+fn voice(config: &NodeConfig, _: &Start) -> Option<anyhow::Result<Built>> {
+    let voice = &config.channels.voice;
+    voice.enabled.then(|| {
+        Ok(Built::direct(VoiceChannel::new(voice.clone()))
+            .unconfigured(format!("{} or channels.voice.public_url not set", voice.auth_token_env)))
+    })
+}
+
+fn whatsapp(config: &NodeConfig, _: &Start) -> Option<anyhow::Result<Built>> {
+    let whatsapp = &config.channels.whatsapp;
+    whatsapp.enabled.then(|| {
+        Ok(Built::direct(WhatsAppChannel::new(whatsapp.clone()))
+            .unconfigured(format!("{}, {} or channels.whatsapp.phone_number_id not set", whatsapp.token_env, whatsapp.app_secret_env)))
+    })
+}
+
+/// An activated channel
+pub struct ActiveChannel {
+    pub name: &'static str,
+    pub kind: Kind,
+    /// The provider connection (connection state, listener)
+    channel: Arc<dyn Channel>,
+    /// Where replies go: paced by an `OutboundQueue` where the provider rate-limits sends
+    pub outbound: Arc<dyn Channel>,
+    /// What the handler has in hand; messages that wait behind it are acknowledged
+    pub dispatcher: Arc<Dispatcher>,
+    last_message_at: std::sync::Mutex<Option<DateTime<Utc>>>,
+}
+
+impl ActiveChannel {
+    fn record_message(&self) {
+        if let Ok(mut last) = self.last_message_at.lock() {
+            *last = Some(Utc::now());
+        }
+    }
+
+    fn last_message_at(&self) -> Option<DateTime<Utc>> {
+        self.last_message_at.lock().ok().and_then(|last| *last)
+    }
+}
+
+/// One channel as listed by `GET /channels`
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelStatus {
+    pub name: String,
+    pub kind: Kind,
+    pub connected: bool,
+    /// Startup status from `HealthState`
+    pub status: Option<ComponentStatus>,
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// Channels configured under `[channels]`
+#[derive(Default)]
+pub struct ChannelRegistry {
+    active: RwLock<Vec<Arc<ActiveChannel>>>,
+    /// Configured channels that could not be built, with the error
+    failed: RwLock<Vec<(&'static str, Kind, String)>>,
+}
+
+impl ChannelRegistry {
+    /// Build and start every configured channel. Returns the listener channels
+    /// with the receiver their incoming messages arrive on.
+    pub async fn activate(
+        &self,
+        config: &NodeConfig,
+        health: &Arc<HealthState>,
+        start: Start<'_>,
+    ) -> Vec<(Arc<ActiveChannel>, mpsc::Receiver<IncomingMessage>)> {
+        let mut listeners = vec![];
+        for spec in SPECS {
+            let built = match (spec.build)(config, &start) {
+                None => continue,
+                Some(Ok(built)) => built,
+                Some(Err(e)) => {
+                    tracing::warn!("{} channel not started: {}", spec.name, e);
+                    health.set_component(spec.name, ComponentStatus::Failed(e.to_string())).await;
+                    self.failed.write().await.push((spec.name, spec.kind, e.to_string()));
+                    continue;
+                }
+            };
+            let active = Arc::new(ActiveChannel {
+                name: spec.name,
+                kind: spec.kind,
+                channel: built.channel,
+                outbound: built.outbound,
+                dispatcher: Dispatcher::new(config.channels.busy_ack.clone()),
+                last_message_at: std::sync::Mutex::new(None),
+            });
+            self.active.write().await.push(Arc::clone(&active));
+
+            match spec.kind {
+                Kind::Webhook => {
+                    let _ = active.channel.start(mpsc::channel(1).0).await;
+                    let status = if active.channel.is_connected() {
+                        ComponentStatus::Ready(spec.mode.to_string())
+                    } else {
+                        ComponentStatus::Failed(built.unconfigured.unwrap_or_else(|| "not configured".to_string()))
+                    };
+                    health.set_component(spec.name, status).await;
+                }
+                Kind::Listener => {
+                    health.register_channel(Arc::clone(&active.channel)).await;
+                    health.set_component(spec.name, ComponentStatus::Starting).await;
+                    listeners.push((Arc::clone(&active), listen(health, &active).await));
+                    tracing::info!("✅ {} channel initialized", spec.name);
+                    health.set_component(spec.name, ComponentStatus::Ready(spec.mode.to_string())).await;
+                }
+            }
+        }
+        listeners
+    }
+
+    /// Note a message that arrived on a webhook channel's route
+    pub async fn record_message(&self, name: &str) {
+        if let Some(active) = self.active.read().await.iter().find(|c| c.name == name) {
+            active.record_message();
+        }
+    }
+
+    /// Where to send on a running channel
+    pub async fn outbound(&self, name: &str) -> Option<Arc<dyn Channel>> {
+        self.active.read().await.iter().find(|c| c.name == name).map(|c| Arc::clone(&c.outbound))
+    }
+
+    /// Stop the listener channels, e.g. when a new daemon takes over; sending still works
+    pub async fn stop_listeners(&self) {
+        for active in self.active.read().await.iter().filter(|c| c.kind == Kind::Listener) {
+            if let Err(e) = active.channel.stop().await {
+                tracing::warn!("Failed to stop {} channel: {}", active.name, e);
+            }
+        }
+    }
+
+    /// Status of every configured channel, in start order
+    pub async fn statuses(&self, health: &HealthState) -> Vec<ChannelStatus> {
+        let components = health.components().await;
+        let mut statuses: Vec<ChannelStatus> = self
+            .active
+            .read()
+            .await
+            .iter()
+            .map(|active| {
+                let status = components.get(active.name).cloned();
+                let error = match &status {
+                    Some(ComponentStatus::Failed(e)) => Some(e.clone()),
+                    _ => None,
+                };
+                ChannelStatus {
+                    name: active.name.to_string(),
+                    kind: active.kind,
+                    connected: error.is_none() && active.channel.is_connected(),
+                    status,
+                    last_message_at: active.last_message_at(),
+                    error,
+                }
+            })
+            .collect();
+        statuses.extend(self.failed.read().await.iter().map(|(name, kind, error)| ChannelStatus {
+            name: name.to_string(),
+            kind: *kind,
+            connected: false,
+            status: components.get(*name).cloned(),
+            last_message_at: None,
+            error: Some(error.clone()),
+        }));
+        let order = |name: &str| SPECS.iter().position(|s| s.name == name);
+        statuses.sort_by_key(|s| order(&s.name));
+        statuses
+    }
+}
+
+/// Run a channel's listener, recording when messages arrive and acknowledging
+/// the ones that wait behind a busy handler. If the listener stops
+/// with an error the component is marked failed, and self-check can restart it
+/// through the registered hook.
+async fn listen(health: &Arc<HealthState>, active: &Arc<ActiveChannel>) -> mpsc::Receiver<IncomingMessage> {
+    let (tx, mut incoming) = mpsc::channel::<IncomingMessage>(100);
+    let (forward, rx) = mpsc::channel(100);
+    let recorder = Arc::clone(active);
+    tokio::spawn(async move {
+        while let Some(msg) = incoming.recv().await {
+            recorder.record_message();
+            if let Some(ack) = recorder.dispatcher.admit(&msg) {
+                let outbound = Arc::clone(&recorder.outbound);
+                let ack = OutgoingMessage {
+                    channel_type: msg.channel_type,
+                    channel_id: msg.channel_id.clone(),
+                    content: ack,
+                    reply_to: msg.reply_to.clone(),
+                    thread_id: msg.thread_id.clone(),
+                    metadata: msg.metadata.clone(),
+                    attachments: vec![],
+                    buttons: vec![],
+                };
+                tokio::spawn(async move {
+                    if let Err(e) = outbound.send(ack).await {
+                        tracing::warn!("Failed to acknowledge a queued message: {}", e);
+                    }
+                });
+            }
+            if forward.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let (name, channel, health_state) = (active.name, Arc::clone(&active.channel), Arc::clone(health));
+    let start: health::Restart = Arc::new(move || {
+        let channel = Arc::clone(&channel);
+        let tx = tx.clone();
+        let health_state = Arc::clone(&health_state);
+        tokio::spawn(async move {
+            if let Err(e) = channel.start(tx).await {
+                tracing::error!("{} channel error: {}", name, e);
+                health_state.set_component(name, ComponentStatus::Failed(e.to_string())).await;
+            }
+        });
+    });
+    health.register_restart(name, Arc::clone(&start)).await;
+    start();
+    rx
+}
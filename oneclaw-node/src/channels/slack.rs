@@ -0,0 +1,373 @@
+//! Slack Channel
+//!
+//! Connects to Slack over Socket Mode, so no public URL is needed.
+//! Handles:
+//! - Opening a Socket Mode connection (app-level `xapp-` token) and reconnecting
+//!   when Slack rotates or drops it
+//! - Acknowledging event envelopes, forwarding DMs and `@mentions` filtered by
+//!   `listen_channels`
+//! - Posting replies as Block Kit sections (bot `xoxb-` token), threaded under
+//!   channel mentions, and uploading report attachments
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::SlackChannelConfig;
+
+const API_BASE: &str = "https://slack.com/api";
+
+/// Slack's limit for the text of one section block
+const MAX_SECTION_CHARS: usize = 3000;
+
+/// Slack's limit for blocks in one message
+const MAX_BLOCKS: usize = 50;
+
+/// Wait before reconnecting after the socket drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    kind: String,
+    envelope_id: Option<String>,
+    payload: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    user: Option<String>,
+    channel: Option<String>,
+    /// "im" for direct messages
+    channel_type: Option<String>,
+    #[serde(default)]
+    text: String,
+    ts: Option<String>,
+    thread_ts: Option<String>,
+    bot_id: Option<String>,
+    subtype: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SlackChannel {
+    config: SlackChannelConfig,
+    bot_token: String,
+    app_token: String,
+    bot_user_id: Arc<RwLock<Option<String>>>,
+    http_client: reqwest::Client,
+    connected: Arc<AtomicBool>,
+}
+
+impl SlackChannel {
+    pub fn new(config: SlackChannelConfig) -> anyhow::Result<Self> {
+        let token = |env: &str| {
+            std::env::var(env)
+                .ok()
+                .filter(|t| !t.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("Slack token not found in env: {}", env))
+        };
+        let bot_token = token(&config.token_env)?;
+        let app_token = token(&config.app_token_env)?;
+
+        Ok(Self {
+            config,
+            bot_token,
+            app_token,
+            bot_user_id: Arc::new(RwLock::new(None)),
+            http_client: reqwest::Client::new(),
+            connected: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Call a Web API method with a JSON body; Slack reports failures as `ok: false`
+    async fn api_call(&self, method: &str, token: &str, body: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let response: serde_json::Value = self.http_client
+            .post(format!("{}/{}", API_BASE, method))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if response["ok"].as_bool() != Some(true) {
+            anyhow::bail!("Slack API error ({}): {}", method, response["error"].as_str().unwrap_or("unknown"));
+        }
+        Ok(response)
+    }
+
+    /// Check if an event should trigger the bot
+    fn should_respond(&self, event: &SlackEvent) -> bool {
+        // Ignore bots (including our own replies) and edits/joins/etc.
+        if event.bot_id.is_some() || event.subtype.is_some() || event.user.is_none() {
+            return false;
+        }
+
+        match event.kind.as_str() {
+            // DMs are always addressed to the bot
+            "message" => event.channel_type.as_deref() == Some("im"),
+            // Channel posts only count as `app_mention`; the plain `message`
+            // event Slack also sends for them is dropped by the arm above
+            "app_mention" => {
+                let channel = event.channel.as_deref().unwrap_or_default();
+                self.config.listen_channels.iter().any(|c| c == "*" || c == channel)
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove bot mention from content
+    fn clean_content(&self, content: &str, bot_id: Option<&str>) -> String {
+        match bot_id {
+            Some(id) => content.replace(&format!("<@{}>", id), ""),
+            None => content.to_string(),
+        }
+        .trim()
+        .to_string()
+    }
+
+    /// One Socket Mode session: open a connection URL, then ack and forward
+    /// events until the socket closes or Slack asks us to reconnect
+    async fn run_session(&self, tx: &mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        if self.bot_user_id.read().await.is_none() {
+            let auth = self.api_call("auth.test", &self.bot_token, serde_json::json!({})).await?;
+            tracing::info!(
+                bot_name = %auth["user"].as_str().unwrap_or("unknown"),
+                team = %auth["team"].as_str().unwrap_or("unknown"),
+                "Slack bot authenticated"
+            );
+            *self.bot_user_id.write().await = auth["user_id"].as_str().map(str::to_string);
+        }
+
+        tracing::info!("Opening Slack Socket Mode connection...");
+        let open = self.api_call("apps.connections.open", &self.app_token, serde_json::json!({})).await?;
+        let url = open["url"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Slack returned no Socket Mode URL"))?;
+        let (ws_stream, _) = connect_async(url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        while let Some(frame) = read.next().await {
+            let text = match frame? {
+                Message::Text(text) => text,
+                Message::Close(frame) => {
+                    tracing::warn!("Slack WebSocket closed: {:?}", frame);
+                    return Ok(());
+                }
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<Envelope>(&text) else {
+                continue;
+            };
+
+            // Slack redelivers envelopes that aren't acknowledged within 3s
+            if let Some(id) = &envelope.envelope_id {
+                write.send(Message::Text(serde_json::json!({ "envelope_id": id }).to_string())).await?;
+            }
+
+            match envelope.kind.as_str() {
+                "hello" => {
+                    tracing::info!("Slack Socket Mode connected");
+                    self.connected.store(true, Ordering::Relaxed);
+                }
+                "disconnect" => {
+                    tracing::info!("Slack asked us to reconnect");
+                    return Ok(());
+                }
+                "events_api" => {
+                    if let Some(event) = envelope.payload.and_then(|p| p.get("event").cloned()) {
+                        self.handle_event(event, tx).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_event(&self, raw: serde_json::Value, tx: &mpsc::Sender<IncomingMessage>) {
+        let Ok(event) = serde_json::from_value::<SlackEvent>(raw.clone()) else {
+            return;
+        };
+        if !self.should_respond(&event) {
+            return;
+        }
+        let (Some(channel), Some(user)) = (event.channel.clone(), event.user.clone()) else {
+            return;
+        };
+
+        // Keep threads together; channel mentions start a thread under the mention,
+        // and each thread is its own conversation
+        let reply_to = event.thread_ts.clone().or_else(|| {
+            if event.kind == "app_mention" { event.ts.clone() } else { None }
+        });
+
+        let bot_id = self.bot_user_id.read().await.clone();
+        let incoming = IncomingMessage {
+            channel_type: ChannelType::Slack,
+            channel_id: channel,
+            provider_user_id: user.clone(),
+            username: Some(user),
+            content: self.clean_content(&event.text, bot_id.as_deref()),
+            timestamp: chrono::Utc::now(),
+            thread_id: reply_to.clone(),
+            reply_to,
+            metadata: raw,
+        };
+
+        if let Err(e) = tx.send(incoming).await {
+            tracing::error!("Failed to send message to handler: {}", e);
+        }
+    }
+}
+
+/// Convert the Markdown our replies use into Slack mrkdwn
+fn to_mrkdwn(content: &str) -> String {
+    let bold = regex::Regex::new(r"\*\*(.+?)\*\*").expect("valid regex");
+    let link = regex::Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").expect("valid regex");
+    let heading = regex::Regex::new(r"(?m)^#{1,6}\s+\**(.+?)\**$").expect("valid regex");
+
+    let text = heading.replace_all(content, "*$1*");
+    let text = link.replace_all(&text, "<$2|$1>");
+    bold.replace_all(&text, "*$1*").into_owned()
+}
+
+/// Block Kit sections for a reply, split on line boundaries to fit Slack's limits
+fn to_blocks(content: &str) -> Vec<serde_json::Value> {
+    let mut sections = vec![];
+    let mut current = String::new();
+    for line in content.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > MAX_SECTION_CHARS && !current.is_empty() {
+            sections.push(std::mem::take(&mut current));
+        }
+        if line.chars().count() > MAX_SECTION_CHARS {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(MAX_SECTION_CHARS) {
+                sections.push(piece.iter().collect());
+            }
+            continue;
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        sections.push(current);
+    }
+
+    sections
+        .into_iter()
+        .filter(|s| !s.trim().is_empty())
+        .map(|text| serde_json::json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text },
+        }))
+        .collect()
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Slack
+    }
+
+    async fn start(&self, tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        // Keep the session alive: Slack rotates Socket Mode connections regularly
+        while !tx.is_closed() {
+            if let Err(e) = self.run_session(&tx).await {
+                tracing::error!("Slack Socket Mode error: {}", e);
+            }
+            self.connected.store(false, Ordering::Relaxed);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+        Ok(())
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(());
+        }
+        let text = to_mrkdwn(&msg.content);
+        let blocks = to_blocks(&text);
+
+        // Replies longer than one message's worth of blocks go out as several messages
+        for chunk in blocks.chunks(MAX_BLOCKS) {
+            let fallback: String = chunk
+                .iter()
+                .filter_map(|b| b["text"]["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("")
+                .chars()
+                .take(MAX_SECTION_CHARS)
+                .collect();
+            let mut body = serde_json::json!({
+                "channel": msg.channel_id,
+                "text": fallback,
+                "blocks": chunk,
+            });
+            if let Some(thread_ts) = msg.thread_id.as_ref().or(msg.reply_to.as_ref()) {
+                body["thread_ts"] = serde_json::json!(thread_ts);
+            }
+            self.api_call("chat.postMessage", &self.bot_token, body).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let bytes = tokio::fs::read(path).await?;
+
+        // External upload flow: reserve an upload URL, send the bytes, then share
+        let reserved: serde_json::Value = self.http_client
+            .get(format!("{}/files.getUploadURLExternal", API_BASE))
+            .bearer_auth(&self.bot_token)
+            .query(&[("filename", file_name.clone()), ("length", bytes.len().to_string())])
+            .send()
+            .await?
+            .json()
+            .await?;
+        if reserved["ok"].as_bool() != Some(true) {
+            anyhow::bail!("Slack API error (files.getUploadURLExternal): {}", reserved["error"].as_str().unwrap_or("unknown"));
+        }
+        let (Some(upload_url), Some(file_id)) = (reserved["upload_url"].as_str(), reserved["file_id"].as_str()) else {
+            anyhow::bail!("Slack returned no upload URL");
+        };
+
+        let upload = self.http_client.post(upload_url).body(bytes).send().await?;
+        if !upload.status().is_success() {
+            anyhow::bail!("Slack file upload failed: {}", upload.status());
+        }
+
+        let mut body = serde_json::json!({
+            "files": [{ "id": file_id, "title": file_name }],
+            "channel_id": channel_id,
+        });
+        if let Some(caption) = caption {
+            body["initial_comment"] = serde_json::json!(caption);
+        }
+        self.api_call("files.completeUploadExternal", &self.bot_token, body).await?;
+        Ok(())
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        tracing::info!("Stopping Slack channel");
+        self.connected.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
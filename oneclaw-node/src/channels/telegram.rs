@@ -2,35 +2,198 @@
 //!
 //! Connects to Telegram Bot API using long polling to receive messages
 //! and send responses back to users.
+//!
+//! The polling loop rides out the usual failure modes without a restart:
+//! - 409 Conflict: another `getUpdates` consumer (a second deployment) or a
+//!   leftover webhook. Webhooks are deleted; otherwise we back off and retry
+//!   until the other consumer goes away.
+//! - 429 Too Many Requests: sends and polls wait out `retry_after`.
+//! - Network drops (laptop sleep/wake, Wi-Fi changes): every request has a
+//!   timeout, so a dead connection errors out instead of hanging the loop,
+//!   and polling backs off exponentially until the network is back.
+//!
+//! Photos, voice notes and documents are downloaded to `~/.oneclaw/media/telegram`
+//! and passed on in `metadata.attachments`. Outgoing attachments go out with
+//! `sendPhoto` (images) or `sendDocument`.
+//!
+//! In group chats the bot only answers when addressed (an @mention, a reply to
+//! one of its messages, or a command) unless `group_trigger` is "all". Group
+//! messages are passed on with the sender's name in front so the shared
+//! conversation stays readable.
+//!
+//! The next update id is saved after every batch, so a restart resumes where the
+//! last run stopped. Messages sent while the daemon was down are still waiting
+//! on the API; they're handled in order, and each chat gets one note first
+//! saying the bot was offline.
+//!
+//! `OutgoingMessage::buttons` become an inline keyboard. A press is acknowledged,
+//! the keyboard is removed so the choice is made once, and the button's action
+//! comes back as a user message with `metadata.action` / `metadata.source_message`.
+//!
+//! A reply chain is a thread: a reply continues the thread of the message it
+//! answers, or starts one there (quoting that message, so the new thread has
+//! its context). Group topics are threads too. Replies in a thread are sent
+//! as replies, so the chain carries on.
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::mpsc;
+use std::time::Duration;
 use tracing::{error, info, warn};
 
-use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use super::{Button, Channel, ChannelType, IncomingMessage, OutgoingMessage};
+
+/// Long-poll wait passed to `getUpdates`
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Extra time on top of the long-poll wait before a request counts as dead
+const REQUEST_GRACE_SECS: u64 = 15;
+
+/// Backoff bounds for network errors and 409 conflicts
+const MIN_BACKOFF_SECS: u64 = 1;
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// Times a send waits out flood control before giving up
+const MAX_SEND_RETRIES: u32 = 3;
+
+/// Where photos, voice notes and documents from users are downloaded to
+const MEDIA_DIR: &str = "~/.oneclaw/media/telegram";
+
+/// Where the next update id is saved, one file per bot
+const OFFSET_DIR: &str = "~/.oneclaw/telegram";
+
+/// Sent once per chat before replying to messages that arrived while the daemon was down
+const MISSED_NOTE: &str = "👋 Sorry, I was offline when you sent this. Catching up on your messages now.";
+
+/// Largest file the Bot API lets bots download
+const MAX_DOWNLOAD_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Messages whose reply-chain thread is remembered; older ones are forgotten
+const MAX_CHAIN_MESSAGES: usize = 10_000;
+
+/// Characters of the replied-to message quoted when a chain starts
+const MAX_QUOTE_CHARS: usize = 500;
 
 #[derive(Debug, Clone)]
 pub struct TelegramChannel {
     bot_token: String,
     base_url: String,
+    client: reqwest::Client,
     offset: std::sync::Arc<tokio::sync::Mutex<i64>>,
+    connected: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// Set by `stop`: polling ends, leaving unconfirmed updates to whoever polls next
+    stopped: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// "mention" or "all"; see `addressed_to`
+    group_trigger: String,
+    /// Set from `getMe` on start
+    bot: std::sync::Arc<std::sync::OnceLock<BotIdentity>>,
+    /// Thread of each message in a reply chain, by (chat, message id)
+    chains: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<(i64, i64), String>>>,
+    /// Started with `--takeover`: the old daemon polled until just now
+    took_over: bool,
+}
+
+#[derive(Debug)]
+struct BotIdentity {
+    id: i64,
+    username: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct TelegramUpdate {
     update_id: i64,
     message: Option<TelegramMessage>,
+    callback_query: Option<CallbackQuery>,
+}
+
+/// Inline keyboard button press
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    id: String,
+    from: TelegramUser,
+    message: Option<TelegramMessage>,
+    data: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct TelegramMessage {
     message_id: i64,
+    /// Unix time the message was sent
+    #[serde(default)]
+    date: i64,
     from: TelegramUser,
     chat: TelegramChat,
     text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
+    /// Available sizes of a photo, smallest first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    photo: Option<Vec<TelegramFile>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    voice: Option<TelegramFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    document: Option<TelegramFile>,
+    /// Inline keyboard on a message we sent (present on callback queries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<Value>,
+    /// The message this one replies to (used to spot replies to the bot in groups)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reply_to_message: Option<Box<TelegramMessage>>,
+    /// Group topic the message was posted in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message_thread_id: Option<i64>,
+}
+
+impl TelegramMessage {
+    fn is_group(&self) -> bool {
+        matches!(self.chat.chat_type.as_str(), "group" | "supergroup")
+    }
+
+    /// Whether a group message is meant for the bot: it @mentions the bot, replies to
+    /// one of its messages, or is a command (`/help` or `/help@thisbot`, not `/help@otherbot`)
+    fn addressed_to(&self, bot: &BotIdentity) -> bool {
+        let text = self.text.as_deref().or(self.caption.as_deref()).unwrap_or_default();
+        if text.to_lowercase().contains(&format!("@{}", bot.username.to_lowercase())) {
+            return true;
+        }
+        if self.reply_to_message.as_ref().is_some_and(|r| r.from.id == bot.id) {
+            return true;
+        }
+        text.starts_with('/') && text.split_whitespace().next().is_some_and(|command| !command.contains('@'))
+    }
+
+    /// `text` as the handler should see it: without the bot's @mention and, in groups,
+    /// prefixed with the sender's name
+    fn content_for_handler(&self, text: &str, bot: Option<&BotIdentity>) -> String {
+        let text = match bot {
+            Some(bot) => regex::Regex::new(&format!(r"(?i)@{}\b", regex::escape(&bot.username)))
+                .map(|mention| mention.replace_all(text, "").trim().to_string())
+                .unwrap_or_else(|_| text.to_string()),
+            None => text.to_string(),
+        };
+        if !self.is_group() {
+            return text;
+        }
+        match &self.from.username {
+            Some(username) => format!("{} (@{}): {}", self.from.first_name, username, text),
+            None => format!("{}: {}", self.from.first_name, text),
+        }
+    }
+}
+
+/// A photo size, voice note or document that can be fetched with `getFile`
+#[derive(Debug, Deserialize, Serialize)]
+struct TelegramFile {
+    file_id: String,
+    file_unique_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_size: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -45,6 +208,9 @@ struct TelegramChat {
     id: i64,
     #[serde(rename = "type")]
     chat_type: String,
+    /// Group name (absent for private chats)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -52,78 +218,447 @@ struct SendMessageRequest {
     chat_id: i64,
     text: String,
     parse_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_parameters: Option<Value>,
+}
+
+/// `content` with the message it replies to quoted in front, when a reply chain starts
+fn with_quote(content: String, quote: Option<String>) -> String {
+    match quote {
+        Some(quote) => format!("[Replying to: \"{}\"]\n\n{}", quote, content),
+        None => content,
+    }
+}
+
+/// Bot API limit on `callback_data`
+const MAX_CALLBACK_DATA_BYTES: usize = 64;
+
+/// `reply_markup` for a message's buttons; None without buttons
+fn inline_keyboard(buttons: &[Vec<Button>]) -> Option<Value> {
+    let rows: Vec<Vec<Value>> = buttons
+        .iter()
+        .map(|row| {
+            row.iter()
+                .filter(|b| {
+                    let fits = b.action.len() <= MAX_CALLBACK_DATA_BYTES;
+                    if !fits {
+                        warn!("Dropping Telegram button {:?}: action over {} bytes", b.label, MAX_CALLBACK_DATA_BYTES);
+                    }
+                    fits
+                })
+                .map(|b| serde_json::json!({ "text": b.label, "callback_data": b.action }))
+                .collect::<Vec<_>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect();
+    (!rows.is_empty()).then(|| serde_json::json!({ "inline_keyboard": rows }))
+}
+
+/// Label of the button with `action` in a message's inline keyboard
+fn button_label(reply_markup: Option<&Value>, action: &str) -> Option<String> {
+    reply_markup?["inline_keyboard"]
+        .as_array()?
+        .iter()
+        .filter_map(|row| row.as_array())
+        .flatten()
+        .find(|b| b["callback_data"] == action)
+        .and_then(|b| b["text"].as_str())
+        .map(str::to_string)
+}
+
+/// Error reply from the Bot API (`ok: false`)
+#[derive(Debug)]
+struct ApiError {
+    code: i64,
+    description: String,
+    /// Seconds to wait before retrying (flood control)
+    retry_after: Option<u64>,
 }
 
+impl ApiError {
+    fn from_body(body: &Value) -> Self {
+        Self {
+            code: body["error_code"].as_i64().unwrap_or(0),
+            description: body["description"].as_str().unwrap_or("unknown error").to_string(),
+            retry_after: body["parameters"]["retry_after"].as_u64(),
+        }
+    }
+
+    /// The text's formatting didn't parse ("can't parse entities")
+    fn is_bad_markup(&self) -> bool {
+        self.code == 400 && self.description.contains("parse entities")
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Telegram API error {}: {}", self.code, self.description)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
 impl TelegramChannel {
     pub fn new(bot_token: String) -> Self {
         Self {
             base_url: "https://api.telegram.org".to_string(),
             bot_token,
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(Duration::from_secs(POLL_TIMEOUT_SECS + REQUEST_GRACE_SECS))
+                .build()
+                .unwrap_or_default(),
             offset: std::sync::Arc::new(tokio::sync::Mutex::new(0)),
+            connected: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            stopped: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            group_trigger: "mention".to_string(),
+            bot: std::sync::Arc::new(std::sync::OnceLock::new()),
+            chains: std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            took_over: false,
+        }
+    }
+
+    fn offset_path(bot_id: i64) -> std::path::PathBuf {
+        crate::config::expand_path(OFFSET_DIR).join(format!("offset-{}", bot_id))
+    }
+
+    /// The update id the last run stopped at, if it saved one
+    fn load_offset(bot_id: i64) -> Option<i64> {
+        std::fs::read_to_string(Self::offset_path(bot_id)).ok()?.trim().parse().ok()
+    }
+
+    async fn save_offset(&self) {
+        let Some(bot) = self.bot.get() else {
+            return;
+        };
+        let path = Self::offset_path(bot.id);
+        let offset = *self.offset.lock().await;
+        let saved = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::write(&path, offset.to_string()));
+        if let Err(e) = saved {
+            warn!("Could not save Telegram offset to {}: {}", path.display(), e);
         }
     }
 
+    /// Which group messages to answer: "mention" (default) or "all"
+    pub fn with_group_trigger(mut self, trigger: String) -> Self {
+        self.group_trigger = trigger;
+        self
+    }
+
+    /// Taking over from a running daemon: waiting messages weren't missed, so
+    /// they get no `MISSED_NOTE`
+    pub fn taking_over(mut self, takeover: bool) -> Self {
+        self.took_over = takeover;
+        self
+    }
+
     async fn get_updates(&self) -> Result<Vec<TelegramUpdate>> {
         let offset = *self.offset.lock().await;
         let url = format!(
-            "{}/bot{}/getUpdates?offset={}&timeout=30",
-            self.base_url, self.bot_token, offset
+            "{}/bot{}/getUpdates?offset={}&timeout={}",
+            self.base_url, self.bot_token, offset, POLL_TIMEOUT_SECS
         );
 
-        let client = reqwest::Client::new();
-        let response = client.get(&url).send().await?;
+        let response = self.client.get(&url).send().await?;
         let body: Value = response.json().await?;
 
         if body["ok"].as_bool() != Some(true) {
-            anyhow::bail!("Telegram API error: {:?}", body);
+            return Err(ApiError::from_body(&body).into());
         }
 
         let updates: Vec<TelegramUpdate> = serde_json::from_value(body["result"].clone())?;
+        Ok(updates)
+    }
 
-        // Update offset to mark these messages as processed
-        if let Some(last_update) = updates.last() {
-            *self.offset.lock().await = last_update.update_id + 1;
+    /// Send a note of the channel's own (command answers, catch-up notes); nothing
+    /// waits on these, so a failure is only logged
+    async fn send_message(&self, chat_id: i64, text: String) {
+        if let Err(e) = self.send_message_with_markup(chat_id, text, None, None).await {
+            warn!("Failed to send Telegram message to {}: {}", chat_id, e);
         }
-
-        Ok(updates)
     }
 
-    async fn send_message(&self, chat_id: i64, text: String) -> Result<()> {
+    /// Send `text`, as a reply to `reply_to` if given; returns the sent message's id
+    async fn send_message_with_markup(
+        &self,
+        chat_id: i64,
+        text: String,
+        reply_markup: Option<Value>,
+        reply_to: Option<i64>,
+    ) -> Result<Option<i64>> {
         let url = format!("{}/bot{}/sendMessage", self.base_url, self.bot_token);
 
-        let request = SendMessageRequest {
+        let mut request = SendMessageRequest {
             chat_id,
             text,
             parse_mode: Some("Markdown".to_string()),
+            reply_markup,
+            reply_parameters: reply_to
+                .map(|message_id| serde_json::json!({ "message_id": message_id, "allow_sending_without_reply": true })),
         };
 
-        let client = reqwest::Client::new();
-        let response = client.post(&url).json(&request).send().await?;
-        let body: Value = response.json().await?;
+        let body = match self.call_with_retry("sendMessage", || self.client.post(&url).json(&request)).await {
+            // Replies aren't always valid Markdown; Telegram rejects those, so send them plain
+            Err(e) if e.downcast_ref::<ApiError>().is_some_and(ApiError::is_bad_markup) => {
+                warn!("Telegram rejected the message's Markdown, sending it as plain text");
+                request.parse_mode = None;
+                self.call_with_retry("sendMessage", || self.client.post(&url).json(&request)).await
+            }
+            result => result,
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to send message: {}", e))?;
+        Ok(body["result"]["message_id"].as_i64())
+    }
 
-        if body["ok"].as_bool() != Some(true) {
-            anyhow::bail!("Failed to send message: {:?}", body);
+    /// The thread `msg` belongs to: its group topic, else the reply chain it
+    /// continues. When it starts a chain, also the text it replies to.
+    fn thread_of(&self, msg: &TelegramMessage) -> (Option<String>, Option<String>) {
+        if let Some(topic) = msg.message_thread_id {
+            return (Some(format!("topic-{}", topic)), None);
         }
+        let Some(parent) = &msg.reply_to_message else {
+            return (None, None);
+        };
+        let known = self.chains.lock().ok().and_then(|chains| chains.get(&(msg.chat.id, parent.message_id)).cloned());
+        let (thread, quote) = match known {
+            Some(thread) => (thread, None),
+            None => {
+                let text = parent.text.as_deref().or(parent.caption.as_deref()).unwrap_or_default();
+                let quote = (!text.is_empty()).then(|| text.chars().take(MAX_QUOTE_CHARS).collect());
+                (parent.message_id.to_string(), quote)
+            }
+        };
+        self.remember_chain(msg.chat.id, msg.message_id, &thread);
+        (Some(thread), quote)
+    }
 
-        Ok(())
+    fn remember_chain(&self, chat_id: i64, message_id: i64, thread: &str) {
+        if let Ok(mut chains) = self.chains.lock() {
+            if chains.len() >= MAX_CHAIN_MESSAGES {
+                chains.clear();
+            }
+            chains.insert((chat_id, message_id), thread.to_string());
+        }
     }
-    
-    pub async fn send_typing(&self, channel_user_id: &str) -> Result<()> {
-        let chat_id: i64 = channel_user_id.parse()?;
-        let url = format!("{}/bot{}/sendChatAction", self.base_url, self.bot_token);
 
-        let client = reqwest::Client::new();
-        let _response = client
-            .post(&url)
-            .json(&serde_json::json!({
-                "chat_id": chat_id,
-                "action": "typing"
-            }))
+    /// Call a Bot API method, waiting out 429 flood control up to `MAX_SEND_RETRIES` times
+    async fn call_with_retry<F>(&self, method: &str, request: F) -> Result<Value>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        let mut attempt = 0;
+        loop {
+            let body: Value = request().send().await?.json().await?;
+            if body["ok"].as_bool() == Some(true) {
+                return Ok(body);
+            }
+
+            let error = ApiError::from_body(&body);
+            match error.retry_after {
+                Some(wait) if error.code == 429 && attempt < MAX_SEND_RETRIES => {
+                    attempt += 1;
+                    warn!("Telegram flood control on {}, retrying in {}s", method, wait);
+                    tokio::time::sleep(Duration::from_secs(wait)).await;
+                }
+                _ => return Err(error.into()),
+            }
+        }
+    }
+
+    /// Acknowledge a button press, remove the keyboard, and forward the button's
+    /// action as a message from the user who pressed it
+    async fn handle_callback(&self, query: CallbackQuery, tx: &mpsc::Sender<IncomingMessage>) {
+        let url = format!("{}/bot{}/answerCallbackQuery", self.base_url, self.bot_token);
+        let answer = serde_json::json!({ "callback_query_id": query.id });
+        if let Err(e) = self.call_with_retry("answerCallbackQuery", || self.client.post(&url).json(&answer)).await {
+            warn!("Failed to answer Telegram callback: {}", e);
+        }
+
+        let (Some(action), Some(message)) = (query.data, query.message) else {
+            return;
+        };
+        let url = format!("{}/bot{}/editMessageReplyMarkup", self.base_url, self.bot_token);
+        let clear = serde_json::json!({ "chat_id": message.chat.id, "message_id": message.message_id });
+        if let Err(e) = self.call_with_retry("editMessageReplyMarkup", || self.client.post(&url).json(&clear)).await {
+            warn!("Failed to remove Telegram keyboard: {}", e);
+        }
+
+        let pressed = IncomingMessage {
+            channel_type: ChannelType::Telegram,
+            channel_id: message.chat.id.to_string(),
+            provider_user_id: query.from.id.to_string(),
+            username: query.from.username.clone(),
+            content: action.clone(),
+            timestamp: chrono::Utc::now(),
+            reply_to: Some(message.message_id.to_string()),
+            thread_id: self.chains.lock().ok().and_then(|chains| chains.get(&(message.chat.id, message.message_id)).cloned()),
+            metadata: serde_json::json!({
+                "action": action,
+                "label": button_label(message.reply_markup.as_ref(), &action),
+                "source_message": message.text,
+                "source_message_id": message.message_id,
+                "chat": message.chat,
+            }),
+        };
+        if let Err(e) = tx.send(pressed).await {
+            error!("Failed to send button press to handler: {}", e);
+        }
+    }
+
+    /// Download a user's file into `MEDIA_DIR`, named by its stable unique id
+    async fn download_file(&self, file: &TelegramFile) -> Result<std::path::PathBuf> {
+        if file.file_size.is_some_and(|size| size > MAX_DOWNLOAD_BYTES) {
+            anyhow::bail!("files over 20 MB can't be downloaded by bots");
+        }
+
+        let url = format!("{}/bot{}/getFile", self.base_url, self.bot_token);
+        let body = self
+            .call_with_retry("getFile", || self.client.post(&url).json(&serde_json::json!({ "file_id": file.file_id })))
+            .await?;
+        let remote_path = body["result"]["file_path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("getFile returned no file_path"))?;
+
+        let bytes = self
+            .client
+            .get(format!("{}/file/bot{}/{}", self.base_url, self.bot_token, remote_path))
             .send()
+            .await?
+            .error_for_status()?
+            .bytes()
             .await?;
 
+        let dir = crate::config::expand_path(MEDIA_DIR);
+        tokio::fs::create_dir_all(&dir).await?;
+        let mut path = dir.join(&file.file_unique_id);
+        if let Some(ext) = std::path::Path::new(remote_path).extension() {
+            path.set_extension(ext);
+        }
+        tokio::fs::write(&path, &bytes).await?;
+        Ok(path)
+    }
+
+    /// Photo, voice note or document as an incoming message. The file is
+    /// downloaded and listed in `metadata.attachments`; the content is the
+    /// caption plus a note of what was attached.
+    async fn media_message(&self, msg: &TelegramMessage) -> Option<IncomingMessage> {
+        let (kind, file) = if let Some(photo) = msg.photo.as_ref().and_then(|sizes| sizes.last()) {
+            ("photo", photo)
+        } else if let Some(voice) = &msg.voice {
+            ("voice", voice)
+        } else if let Some(document) = &msg.document {
+            ("document", document)
+        } else {
+            return None;
+        };
+
+        let path = match self.download_file(file).await {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Failed to download Telegram {}: {}", kind, e);
+                self.send_message(msg.chat.id, format!("⚠️ Couldn't download that {}: {}", kind, e)).await;
+                return None;
+            }
+        };
+
+        let note = format!("[{} attached: {}]", kind, path.display());
+        let content = match msg.caption.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            Some(caption) => format!("{}\n\n{}", caption, note),
+            None => note,
+        };
+        let (thread_id, quote) = self.thread_of(msg);
+        let content = with_quote(msg.content_for_handler(&content, self.bot.get()), quote);
+        let mut metadata = serde_json::to_value(msg).unwrap_or_default();
+        metadata["attachments"] = serde_json::json!([{
+            "kind": kind,
+            "path": path,
+            "file_name": file.file_name,
+            "mime_type": file.mime_type,
+            "file_size": file.file_size,
+        }]);
+
+        Some(IncomingMessage {
+            channel_type: ChannelType::Telegram,
+            channel_id: msg.chat.id.to_string(),
+            provider_user_id: msg.from.id.to_string(),
+            username: msg.from.username.clone(),
+            content,
+            timestamp: chrono::Utc::now(),
+            reply_to: thread_id.as_ref().map(|_| msg.message_id.to_string()),
+            thread_id,
+            metadata,
+        })
+    }
+
+    /// Upload a file with `sendPhoto` (`field` = "photo") or `sendDocument` ("document")
+    async fn upload(&self, method: &str, field: &'static str, chat_id: i64, path: &std::path::Path, caption: Option<&str>) -> Result<()> {
+        let url = format!("{}/bot{}/{}", self.base_url, self.bot_token, method);
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let bytes = tokio::fs::read(path).await?;
+
+        // Multipart bodies are consumed on send, so each attempt builds its own
+        let form = || {
+            let mut form = reqwest::multipart::Form::new()
+                .text("chat_id", chat_id.to_string())
+                .part(field, reqwest::multipart::Part::bytes(bytes.clone()).file_name(file_name.clone()));
+            if let Some(caption) = caption {
+                form = form.text("caption", caption.to_string());
+            }
+            form
+        };
+
+        self.call_with_retry(method, || self.client.post(&url).multipart(form()))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send {}: {}", field, e))?;
+        Ok(())
+    }
+
+    /// Drop a webhook left behind by another deployment; Telegram refuses
+    /// `getUpdates` (409) while one is set
+    async fn delete_webhook(&self) -> Result<()> {
+        let url = format!("{}/bot{}/deleteWebhook", self.base_url, self.bot_token);
+        self.call_with_retry("deleteWebhook", || self.client.post(&url)).await?;
         Ok(())
     }
+
+    /// How long to wait after a failed poll, and what to log
+    async fn recover_from_poll_error(&self, e: &anyhow::Error, backoff: &mut u64) -> Duration {
+        if let Some(api) = e.downcast_ref::<ApiError>() {
+            match api.code {
+                409 if api.description.contains("webhook") => {
+                    warn!("Telegram webhook is set, deleting it to resume polling");
+                    if let Err(e) = self.delete_webhook().await {
+                        warn!("Failed to delete Telegram webhook: {}", e);
+                    }
+                    return Duration::from_secs(MIN_BACKOFF_SECS);
+                }
+                409 => {
+                    warn!(
+                        "Another instance is polling this bot (409 Conflict). Retrying in {}s; stop the other deployment to fix this",
+                        backoff
+                    );
+                }
+                429 => {
+                    let wait = api.retry_after.unwrap_or(*backoff);
+                    warn!("Telegram flood control on getUpdates, retrying in {}s", wait);
+                    return Duration::from_secs(wait);
+                }
+                _ => warn!("Error polling Telegram updates: {}", api),
+            }
+        } else {
+            warn!("Telegram unreachable, retrying in {}s: {}", backoff, e);
+        }
+
+        let wait = Duration::from_secs(*backoff);
+        *backoff = (*backoff * 2).min(MAX_BACKOFF_SECS);
+        wait
+    }
 }
 
 #[async_trait]
@@ -137,8 +672,7 @@ impl Channel for TelegramChannel {
 
         // Test connection
         let url = format!("{}/bot{}/getMe", self.base_url, self.bot_token);
-        let client = reqwest::Client::new();
-        let response = client.get(&url).send().await?;
+        let response = self.client.get(&url).send().await?;
         let body: Value = response.json().await?;
 
         if body["ok"].as_bool() != Some(true) {
@@ -149,17 +683,68 @@ impl Channel for TelegramChannel {
             .as_str()
             .unwrap_or("unknown");
         info!("✅ Telegram bot connected: @{}", bot_username);
+        let _ = self.bot.set(BotIdentity {
+            id: body["result"]["id"].as_i64().unwrap_or_default(),
+            username: bot_username.to_string(),
+        });
+        self.connected.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        // Resume after the last update handled before a restart. Messages older than
+        // this start arrived while we were down (unless we took over a running daemon).
+        let started_at = if self.took_over { i64::MIN } else { chrono::Utc::now().timestamp() };
+        let mut apologized = std::collections::HashSet::new();
+        {
+            let mut offset = self.offset.lock().await;
+            if *offset == 0 {
+                if let Some(saved) = self.bot.get().and_then(|bot| Self::load_offset(bot.id)) {
+                    info!("Resuming Telegram updates from {}", saved);
+                    *offset = saved;
+                }
+            }
+        }
 
         // Start polling loop
+        let mut backoff = MIN_BACKOFF_SECS;
         loop {
-            match self.get_updates().await {
+            let polled = self.get_updates().await;
+            // Stopped for an upgrade: the new daemon picks up from the saved offset
+            if self.stopped.load(std::sync::atomic::Ordering::Relaxed) {
+                info!("Telegram polling stopped");
+                return Ok(());
+            }
+            match polled {
                 Ok(updates) => {
+                    if !self.is_connected() {
+                        info!("✅ Telegram polling recovered");
+                        self.connected.store(true, std::sync::atomic::Ordering::Relaxed);
+                    }
+                    backoff = MIN_BACKOFF_SECS;
+
+                    let missed = updates.iter().filter_map(|u| u.message.as_ref()).filter(|m| m.date < started_at).count();
+                    if missed > 0 {
+                        info!("📬 {} Telegram message(s) arrived while offline; catching up", missed);
+                    }
+                    let next_offset = updates.last().map(|u| u.update_id + 1);
+                    
                     for update in updates {
+                        if let Some(query) = update.callback_query {
+                            self.handle_callback(query, &tx).await;
+                            continue;
+                        }
                         if let Some(msg) = update.message {
+                            let ignored = msg.is_group()
+                                && self.group_trigger != "all"
+                                && self.bot.get().is_some_and(|bot| !msg.addressed_to(bot));
+                            if ignored {
+                                continue;
+                            }
+                            if msg.date < started_at && apologized.insert(msg.chat.id) {
+                                self.send_message(msg.chat.id, MISSED_NOTE.to_string()).await;
+                            }
                             if let Some(ref text) = msg.text {
                                 // Handle commands
                                 if text.starts_with("/start") {
-                                    let _ = self
+                                    self
                                         .send_message(
                                             msg.chat.id,
                                             "🦞 **OneClaw Agent Online**\n\nI can help you with:\n• Find businesses\n• Run outreach campaigns\n• Execute workflows\n\nJust ask me what you need!\n\nCommands:\n/help - Show this message\n/status - Check processing status".to_string(),
@@ -169,17 +754,17 @@ impl Channel for TelegramChannel {
                                 }
                                 
                                 if text.starts_with("/help") {
-                                    let _ = self
+                                    self
                                         .send_message(
                                             msg.chat.id,
-                                            "🦞 **OneClaw Commands**\n\n/start - Welcome message\n/help - Show this help\n/status - Check agent status\n/logs - View recent logs\n\n**What I can do:**\n• Find local businesses\n• Analyze competitors\n• Generate outreach emails\n• Run automated workflows\n\nJust tell me what you need!".to_string(),
+                                            "🦞 **OneClaw Commands**\n\n/start - Welcome message\n/help - Show this help\n/whatcanyoudo - What I can do with your setup\n/status - Check agent status\n/logs - View recent logs\n/snooze 2h - Remind me about the last update later\n/simulate <request> - Preview a job without running it\n\n**What I can do:**\n• Find local businesses\n• Analyze competitors\n• Generate outreach emails\n• Run automated workflows\n\nJust tell me what you need!".to_string(),
                                         )
                                         .await;
                                     continue;
                                 }
                                 
                                 if text.starts_with("/status") {
-                                    let _ = self
+                                    self
                                         .send_message(
                                             msg.chat.id,
                                             "✅ **Agent Status: Online**\n\nReady to process your requests!\n\nConnected to harness with 6 tools available.".to_string(),
@@ -189,6 +774,14 @@ impl Channel for TelegramChannel {
                                 }
                                 
                                 if text.starts_with("/logs") {
+                                    if cfg!(windows) {
+                                        self.send_message(
+                                            msg.chat.id,
+                                            "📋 Logs aren't available over chat on Windows. Check the OneClaw Node service in Event Viewer.".to_string(),
+                                        ).await;
+                                        continue;
+                                    }
+                                    
                                     // Read recent logs from journalctl
                                     let logs_output = std::process::Command::new("journalctl")
                                         .args(&["-u", "oneclaw-node", "-n", "20", "--no-pager"])
@@ -210,18 +803,20 @@ impl Channel for TelegramChannel {
                                         Err(e) => format!("❌ Could not fetch logs: {}", e)
                                     };
                                     
-                                    let _ = self.send_message(msg.chat.id, log_text).await;
+                                    self.send_message(msg.chat.id, log_text).await;
                                     continue;
                                 }
 
+                                let (thread_id, quote) = self.thread_of(&msg);
                                 let channel_msg = IncomingMessage {
                                     channel_type: ChannelType::Telegram,
                                     channel_id: msg.chat.id.to_string(),
                                     provider_user_id: msg.from.id.to_string(),
                                     username: msg.from.username.clone(),
-                                    content: text.clone(),
+                                    content: with_quote(msg.content_for_handler(text, self.bot.get()), quote),
                                     timestamp: chrono::Utc::now(),
-                                    reply_to: None,
+                                    reply_to: thread_id.as_ref().map(|_| msg.message_id.to_string()),
+                                    thread_id,
                                     metadata: serde_json::to_value(&msg).unwrap_or_default(),
                                 };
 
@@ -235,25 +830,122 @@ impl Channel for TelegramChannel {
                                 if let Err(e) = tx.send(channel_msg).await {
                                     error!("Failed to send message to handler: {}", e);
                                 }
+                            } else if let Some(channel_msg) = self.media_message(&msg).await {
+                                info!(
+                                    "📎 Media from @{}: {}",
+                                    msg.from.username.as_deref().unwrap_or("unknown"),
+                                    channel_msg.content
+                                );
+                                if let Err(e) = tx.send(channel_msg).await {
+                                    error!("Failed to send message to handler: {}", e);
+                                }
                             }
                         }
                     }
+                    // Confirmed (and saved for the next daemon) only once the whole batch is handled
+                    if let Some(next_offset) = next_offset {
+                        *self.offset.lock().await = next_offset;
+                        self.save_offset().await;
+                    }
                 }
                 Err(e) => {
-                    warn!("Error polling Telegram updates: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
+                    let wait = self.recover_from_poll_error(&e, &mut backoff).await;
+                    tokio::time::sleep(wait).await;
                 }
             }
         }
     }
 
     async fn send(&self, msg: OutgoingMessage) -> Result<()> {
+        self.deliver(msg).await.map(|_| ())
+    }
+
+    /// The id is the text message's (`message_id`)
+    async fn deliver(&self, msg: OutgoingMessage) -> Result<Option<String>> {
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(None);
+        }
         let chat_id: i64 = msg.channel_id.parse()?;
-        self.send_message(chat_id, msg.content).await
+        let mut message_id = None;
+        if !msg.content.trim().is_empty() || msg.attachments.is_empty() {
+            // In a thread, reply so the chain continues, and remember the reply as part of it
+            let reply_to = msg.thread_id.as_ref().and(msg.reply_to.as_deref()).and_then(|id| id.parse().ok());
+            let sent = self.send_message_with_markup(chat_id, msg.content, inline_keyboard(&msg.buttons), reply_to).await?;
+            if let (Some(thread), Some(sent)) = (&msg.thread_id, sent) {
+                self.remember_chain(chat_id, sent, thread);
+            }
+            message_id = sent.map(|id| id.to_string());
+        }
+        for attachment in &msg.attachments {
+            let (method, field) = if attachment.is_image() { ("sendPhoto", "photo") } else { ("sendDocument", "document") };
+            self.upload(method, field, chat_id, &attachment.path, attachment.caption.as_deref()).await?;
+        }
+        Ok(message_id)
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let chat_id: i64 = channel_id.parse()?;
+        self.upload("sendDocument", "document", chat_id, path, caption).await
+    }
+
+    /// Show "typing…" in the chat (lasts ~5s or until the next message)
+    async fn indicate_activity(&self, channel_user_id: &str) -> Result<()> {
+        let chat_id: i64 = channel_user_id.parse()?;
+        let url = format!("{}/bot{}/sendChatAction", self.base_url, self.bot_token);
+
+        let _response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({
+                "chat_id": chat_id,
+                "action": "typing"
+            }))
+            .send()
+            .await?;
+
+        Ok(())
     }
 
     async fn stop(&self) -> Result<()> {
         info!("🛑 Stopping Telegram bot...");
+        self.stopped.store(true, std::sync::atomic::Ordering::Relaxed);
+        self.connected.store(false, std::sync::atomic::Ordering::Relaxed);
         Ok(())
     }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group_message(text: &str) -> TelegramMessage {
+        serde_json::from_value(serde_json::json!({
+            "message_id": 1,
+            "from": { "id": 42, "first_name": "Dana", "username": "dana" },
+            "chat": { "id": -100, "type": "supergroup", "title": "Sales" },
+            "text": text,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_group_mention_gating() {
+        let bot = BotIdentity { id: 7, username: "OneClawBot".to_string() };
+        assert!(group_message("@oneclawbot find roofers in Austin").addressed_to(&bot));
+        assert!(group_message("/help").addressed_to(&bot));
+        assert!(group_message("/help@OneClawBot").addressed_to(&bot));
+        assert!(!group_message("/help@OtherBot").addressed_to(&bot));
+        assert!(!group_message("anyone up for lunch?").addressed_to(&bot));
+
+        let msg = group_message("@OneClawBot find roofers in Austin");
+        assert_eq!(msg.content_for_handler("@OneClawBot find roofers in Austin", Some(&bot)), "Dana (@dana): find roofers in Austin");
+    }
 }
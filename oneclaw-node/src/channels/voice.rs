@@ -0,0 +1,173 @@
+//! Voice Channel (Twilio)
+//!
+//! Answers phone calls as an after-hours assistant. Twilio does speech-to-text
+//! (`<Gather input="speech">`) and text-to-speech (`<Say>`); the node turns each
+//! caller utterance into a chat turn and speaks the reply back:
+//!
+//! 1. `POST /voice/incoming` - call connects, greet and listen
+//! 2. `POST /voice/respond`  - `SpeechResult` goes through the chat pipeline,
+//!    reply is spoken and the node listens again
+//!
+//! Webhook-driven, so `start` only logs; replies are returned as TwiML rather
+//! than pushed through `send`.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::VoiceChannelConfig;
+
+/// Longest reply we read out before pointing the caller elsewhere
+const MAX_SPOKEN_CHARS: usize = 600;
+
+pub struct VoiceChannel {
+    config: VoiceChannelConfig,
+    auth_token: Option<String>,
+}
+
+impl VoiceChannel {
+    pub fn new(config: VoiceChannelConfig) -> Self {
+        let auth_token = std::env::var(&config.auth_token_env).ok();
+        Self { config, auth_token }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Extra system prompt for phone conversations
+    pub fn instructions(&self) -> String {
+        let mut block = String::from(
+            "## Phone Call\nYou are answering a phone call for the business. The caller is a customer, \
+             not the owner. Replies are read aloud: one to three short sentences. \
+             If you can't help, take a message (name, callback number, reason).",
+        );
+        if let Some(extra) = &self.config.instructions {
+            block.push('\n');
+            block.push_str(extra);
+        }
+        block
+    }
+
+    /// Verify `X-Twilio-Signature`: HMAC-SHA1 over the full URL followed by the
+    /// POST params sorted by name, keyed with the auth token.
+    /// Every request fails when `public_url` or the auth token is not set.
+    pub fn verify(&self, path: &str, params: &HashMap<String, String>, signature: Option<&str>) -> bool {
+        let Some(public_url) = &self.config.public_url else {
+            tracing::warn!("channels.voice.public_url not set - refusing Twilio webhook");
+            return false;
+        };
+        let (Some(token), Some(signature)) = (&self.auth_token, signature) else {
+            return false;
+        };
+
+        let mut payload = format!("{}{}", public_url.trim_end_matches('/'), path);
+        let mut keys: Vec<&String> = params.keys().collect();
+        keys.sort();
+        for key in keys {
+            payload.push_str(key);
+            payload.push_str(&params[key]);
+        }
+        crate::crypto::verify_hmac_sha1(token.as_bytes(), payload.as_bytes(), signature)
+    }
+
+    /// TwiML for a new call: greet and listen
+    pub fn greeting_twiml(&self, business_name: &str) -> String {
+        let greeting = self.config.greeting.clone().unwrap_or_else(|| {
+            format!("Thanks for calling {}. I'm the after-hours assistant. How can I help?", business_name)
+        });
+        self.gather(&greeting)
+    }
+
+    /// TwiML speaking a reply and listening for the next utterance
+    pub fn reply_twiml(&self, reply: &str) -> String {
+        self.gather(&speakable(reply))
+    }
+
+    /// TwiML ending the call
+    pub fn goodbye_twiml(&self, text: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Response>{}<Hangup/></Response>"#,
+            self.say(text)
+        )
+    }
+
+    fn gather(&self, prompt: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Response><Gather input="speech" action="/voice/respond" method="POST" speechTimeout="auto" language="{}">{}</Gather>{}<Redirect method="POST">/voice/respond</Redirect></Response>"#,
+            xml_escape(&self.config.language),
+            self.say(prompt),
+            self.say("Are you still there?"),
+        )
+    }
+
+    fn say(&self, text: &str) -> String {
+        format!(
+            r#"<Say voice="{}" language="{}">{}</Say>"#,
+            xml_escape(&self.config.voice),
+            xml_escape(&self.config.language),
+            xml_escape(text)
+        )
+    }
+}
+
+/// Strip markdown and links so TTS reads naturally, and keep it short
+pub fn speakable(text: &str) -> String {
+    let mut spoken: String = text
+        .lines()
+        .map(|line| line.trim().trim_start_matches(['#', '-', '*', '•', '>']).trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split_whitespace()
+                .map(|word| if word.starts_with("http") { "the link I'll send you" } else { word })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(". ")
+        .replace(['*', '_', '`', '#'], "");
+
+    if spoken.chars().count() > MAX_SPOKEN_CHARS {
+        spoken = spoken.chars().take(MAX_SPOKEN_CHARS).collect();
+        if let Some(end) = spoken.rfind(['.', '!', '?']) {
+            spoken.truncate(end + 1);
+        }
+    }
+    spoken
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[async_trait]
+impl Channel for VoiceChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::Voice
+    }
+
+    async fn start(&self, _tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        if !self.is_connected() {
+            anyhow::bail!("Twilio webhooks can't be verified: set channels.voice.public_url and {}", self.config.auth_token_env);
+        }
+        tracing::info!("📞 Voice channel ready (Twilio webhooks: /voice/incoming, /voice/respond)");
+        Ok(())
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        anyhow::bail!("Voice replies are spoken in-call; can't push to {}", msg.channel_id)
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.auth_token.is_some() && self.config.public_url.is_some()
+    }
+}
@@ -0,0 +1,270 @@
+//! WhatsApp Channel (Meta Cloud API)
+//!
+//! Inbound messages arrive as webhooks on the daemon router:
+//!
+//! 1. `GET  /whatsapp/webhook` - Meta's subscription check (`hub.verify_token`,
+//!    echo `hub.challenge`)
+//! 2. `POST /whatsapp/webhook` - message deliveries, signed with the app secret
+//!    (`X-Hub-Signature-256`, required); each text message becomes a chat turn
+//!
+//! Replies and report attachments go out through the Graph API from the
+//! business phone number (`phone_number_id`).
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::{Channel, ChannelType, IncomingMessage, OutgoingMessage};
+use crate::config::WhatsAppChannelConfig;
+
+const GRAPH_API: &str = "https://graph.facebook.com/v20.0";
+
+/// WhatsApp rejects text bodies longer than this
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+pub struct WhatsAppChannel {
+    config: WhatsAppChannelConfig,
+    access_token: Option<String>,
+    verify_token: Option<String>,
+    app_secret: Option<String>,
+    http_client: reqwest::Client,
+}
+
+impl WhatsAppChannel {
+    pub fn new(config: WhatsAppChannelConfig) -> Self {
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            access_token: env(&config.token_env),
+            verify_token: env(&config.verify_token_env),
+            app_secret: env(&config.app_secret_env),
+            config,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Answer Meta's subscription check: the challenge to echo, if the token matches
+    pub fn verify_subscription(&self, mode: Option<&str>, token: Option<&str>, challenge: Option<&str>) -> Option<String> {
+        let expected = self.verify_token.as_deref()?;
+        if mode != Some("subscribe") || !crate::crypto::secrets_match(expected, token.unwrap_or_default()) {
+            return None;
+        }
+        challenge.map(str::to_string)
+    }
+
+    /// Verify `X-Hub-Signature-256` (`sha256=<hex HMAC of the raw body>`).
+    /// Every delivery fails when the app secret is not set.
+    pub fn verify(&self, body: &[u8], signature: Option<&str>) -> bool {
+        let Some(secret) = &self.app_secret else {
+            tracing::warn!("{} not set - refusing WhatsApp webhook", self.config.app_secret_env);
+            return false;
+        };
+        let Some(hex) = signature.and_then(|s| s.strip_prefix("sha256=")) else {
+            return false;
+        };
+        crate::crypto::verify_hmac_sha256_hex(secret.as_bytes(), body, hex)
+    }
+
+    /// Text messages in a webhook delivery from allowed senders
+    pub fn parse_webhook(&self, payload: &Value) -> Vec<IncomingMessage> {
+        let mut messages = vec![];
+        let changes = payload["entry"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|entry| entry["changes"].as_array().into_iter().flatten());
+
+        for change in changes {
+            let value = &change["value"];
+            let contacts = value["contacts"].as_array().cloned().unwrap_or_default();
+
+            for msg in value["messages"].as_array().into_iter().flatten() {
+                let Some(from) = msg["from"].as_str() else { continue };
+                let text = match msg["type"].as_str() {
+                    Some("text") => msg["text"]["body"].as_str(),
+                    // Quick-reply buttons and list picks read like typed replies
+                    Some("button") => msg["button"]["text"].as_str(),
+                    Some("interactive") => msg["interactive"]["button_reply"]["title"]
+                        .as_str()
+                        .or_else(|| msg["interactive"]["list_reply"]["title"].as_str()),
+                    _ => None,
+                };
+                let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+                    continue;
+                };
+                if !self.config.allowed_numbers.iter().any(|n| n == "*" || n.trim_start_matches('+') == from) {
+                    tracing::info!("Ignoring WhatsApp message from unlisted number {}", from);
+                    continue;
+                }
+
+                let username = contacts
+                    .iter()
+                    .find(|c| c["wa_id"].as_str() == Some(from))
+                    .and_then(|c| c["profile"]["name"].as_str())
+                    .map(str::to_string);
+
+                messages.push(IncomingMessage {
+                    channel_type: ChannelType::WhatsApp,
+                    channel_id: from.to_string(),
+                    provider_user_id: from.to_string(),
+                    username,
+                    content: text.to_string(),
+                    timestamp: chrono::Utc::now(),
+                    reply_to: msg["id"].as_str().map(str::to_string),
+                    thread_id: None,
+                    metadata: msg.clone(),
+                });
+            }
+        }
+        messages
+    }
+
+    fn credentials(&self) -> anyhow::Result<&str> {
+        if self.config.phone_number_id.is_empty() {
+            anyhow::bail!("channels.whatsapp.phone_number_id not set");
+        }
+        self.access_token
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("WhatsApp token not found in env: {}", self.config.token_env))
+    }
+
+    /// POST a message object to the business number's `/messages` edge
+    async fn post_message(&self, message: Value) -> anyhow::Result<()> {
+        let token = self.credentials()?;
+        let url = format!("{}/{}/messages", GRAPH_API, self.config.phone_number_id);
+        let response = self.http_client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&message)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error = response.text().await?;
+            anyhow::bail!("WhatsApp API error: {}", error);
+        }
+        Ok(())
+    }
+}
+
+/// WhatsApp bolds with single asterisks
+fn to_whatsapp_markup(content: &str) -> String {
+    let bold = regex::Regex::new(r"\*\*(.+?)\*\*").expect("valid regex");
+    bold.replace_all(content, "*$1*").into_owned()
+}
+
+/// Split a reply into chunks WhatsApp accepts, preferring line boundaries
+fn split_message(content: &str) -> Vec<String> {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    for line in content.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > MAX_MESSAGE_CHARS && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if line.chars().count() > MAX_MESSAGE_CHARS {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(MAX_MESSAGE_CHARS) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[async_trait]
+impl Channel for WhatsAppChannel {
+    fn channel_type(&self) -> ChannelType {
+        ChannelType::WhatsApp
+    }
+
+    async fn start(&self, _tx: mpsc::Sender<IncomingMessage>) -> anyhow::Result<()> {
+        if self.app_secret.is_none() {
+            anyhow::bail!("WhatsApp webhooks can't be verified: set {}", self.config.app_secret_env);
+        }
+        tracing::info!("💬 WhatsApp channel ready (Meta webhook: /whatsapp/webhook)");
+        Ok(())
+    }
+
+    async fn send(&self, msg: OutgoingMessage) -> anyhow::Result<()> {
+        if crate::shadow::intercept_send(&msg) {
+            return Ok(());
+        }
+        for (i, chunk) in split_message(&to_whatsapp_markup(&msg.content)).iter().enumerate() {
+            let mut message = serde_json::json!({
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "to": msg.channel_id,
+                "type": "text",
+                "text": { "body": chunk, "preview_url": false },
+            });
+            if let (0, Some(message_id)) = (i, &msg.reply_to) {
+                message["context"] = serde_json::json!({ "message_id": message_id });
+            }
+            self.post_message(message).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_file(&self, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> anyhow::Result<()> {
+        if crate::shadow::intercept_file(self.channel_type(), channel_id, path, caption) {
+            return Ok(());
+        }
+        let token = self.credentials()?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "report".to_string());
+        let mime = match path.extension().and_then(|e| e.to_str()) {
+            Some("pdf") => "application/pdf",
+            Some("html") => "text/html",
+            _ => "application/octet-stream",
+        };
+        let bytes = tokio::fs::read(path).await?;
+
+        // Upload to the number's media store, then send it by id
+        let form = reqwest::multipart::Form::new()
+            .text("messaging_product", "whatsapp")
+            .text("type", mime)
+            .part("file", reqwest::multipart::Part::bytes(bytes).file_name(file_name.clone()).mime_str(mime)?);
+        let uploaded: Value = self.http_client
+            .post(format!("{}/{}/media", GRAPH_API, self.config.phone_number_id))
+            .bearer_auth(token)
+            .multipart(form)
+            .send()
+            .await?
+            .json()
+            .await?;
+        let Some(media_id) = uploaded["id"].as_str() else {
+            anyhow::bail!("WhatsApp media upload failed: {}", uploaded["error"]["message"].as_str().unwrap_or("no media id"));
+        };
+
+        let mut document = serde_json::json!({ "id": media_id, "filename": file_name });
+        if let Some(caption) = caption {
+            document["caption"] = serde_json::json!(caption);
+        }
+        self.post_message(serde_json::json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": channel_id,
+            "type": "document",
+            "document": document,
+        }))
+        .await
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.access_token.is_some() && self.app_secret.is_some() && !self.config.phone_number_id.is_empty()
+    }
+}
@@ -0,0 +1,235 @@
+//! Context Budget - Per-turn token accounting for chat responses
+//!
+//! Each `/chat` response carries a `budget` so API users and the UI can see why
+//! answers degrade as a conversation grows: how many tokens the turn's LLM calls
+//! used (as reported by the provider), how much of the prompt is tool
+//! definitions and system prompt, and whether older history was left out.
+//!
+//! Before each call of a chat turn, the prompt is also fitted to the model's
+//! context window (`fit`): measured, and when it wouldn't leave room for the
+//! reply, the oldest history is dropped until it does. Long conversations
+//! lose their oldest messages instead of failing with provider errors.
+//! Anthropic prompts near the window are counted by the provider's tokenizer;
+//! the rest are estimated, and `window.estimate_reason` says why. The fitted
+//! counts go on the turn's receipt (`receipt::write_turn_receipt`).
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::executor::ExecutorResult;
+use crate::memory::TierUsage;
+
+/// Token and payload accounting for one chat turn
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContextBudget {
+    /// Prompt (input) tokens across the turn's LLM calls
+    pub prompt_tokens: u64,
+    /// Completion (output) tokens across the turn's LLM calls
+    pub completion_tokens: u64,
+    /// Prompt tokens read from the provider's prompt cache (included in `prompt_tokens`)
+    pub cache_read_tokens: u64,
+    /// Prompt tokens written to the prompt cache (included in `prompt_tokens`)
+    pub cache_write_tokens: u64,
+    /// Dollars the turn's LLM calls cost, priced by `spend.prices`
+    pub cost_usd: f64,
+    /// LLM calls made: one per agent loop round, plus a follow-up summary when
+    /// the loop stopped on tool calls
+    pub llm_calls: u32,
+    /// Agent loop rounds that ended in native tool calls
+    pub tool_rounds: u32,
+    /// Tool definitions sent with the main call
+    pub tools_count: usize,
+    /// Serialized size of those definitions in bytes
+    pub tools_bytes: usize,
+    /// System prompt size in characters (Agent OS, territories, jobs, context)
+    pub system_prompt_chars: usize,
+    pub history: HistoryWindow,
+    /// Characters each memory tier put into the prompt
+    pub memory: TierUsage,
+    /// How the prompt was fitted to the model's context window
+    pub window: WindowFit,
+}
+
+/// The main call's prompt measured against the model's context window
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WindowFit {
+    /// The model's context window in tokens
+    pub context_window: u64,
+    /// Largest prompt of the turn's main calls, after fitting
+    pub prompt_tokens: u64,
+    /// Of those, the system prompt's
+    pub system_tokens: u64,
+    /// Of those, the history's (including the current message and tool rounds)
+    pub history_tokens: u64,
+    /// Of those, the tool definitions'
+    pub tools_tokens: u64,
+    /// Whether the provider's tokenizer counted the prompt (otherwise estimated)
+    pub counted: bool,
+    /// Why the prompt was only estimated (characters / 4), when it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate_reason: Option<String>,
+    /// History messages left out to make room
+    pub dropped_messages: usize,
+}
+
+/// Which stored history made it into the prompt
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistoryWindow {
+    /// Messages included (including the current one)
+    pub included: usize,
+    /// The window limit (`memory.session_max_messages`)
+    pub max_messages: usize,
+    /// Whether older messages were left out
+    pub truncated: bool,
+    /// Everything stored before this was left out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated_before: Option<DateTime<Utc>>,
+}
+
+impl ContextBudget {
+    /// Count an LLM call and the usage its provider reported
+    pub fn record_llm(&mut self, result: &ExecutorResult) {
+        self.llm_calls += 1;
+        if let ExecutorResult::Executed { output, .. } = result {
+            if let Some((prompt, completion)) = usage(&output["raw"]) {
+                self.prompt_tokens += prompt;
+                self.completion_tokens += completion;
+            }
+            let (read, written) = cache_usage(&output["raw"]);
+            self.cache_read_tokens += read;
+            self.cache_write_tokens += written;
+            let (provider, model) = (output["provider"].as_str().unwrap_or_default(), output["model"].as_str().unwrap_or_default());
+            self.cost_usd += crate::spend::llm_cost_usd(provider, model, &output["raw"], 1.0).unwrap_or_default();
+        }
+    }
+}
+
+/// Characters per token for the local estimate (English prose and JSON average)
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens held back from the window for the reply
+pub const REPLY_RESERVE: u64 = 4_096;
+
+/// Estimates below this share of the room are trusted without asking the provider
+pub const ESTIMATE_TRUSTED_BELOW: f64 = 0.75;
+
+/// Rough prompt tokens of `messages` and `tools`, without a tokenizer
+pub fn estimate_tokens(messages: &[Value], tools: &[Value]) -> u64 {
+    let chars: usize = messages.iter().chain(tools).map(|v| v.to_string().len()).sum();
+    chars.div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// `total` prompt tokens split into (system prompt, history, tool definitions)
+/// in proportion to their estimates
+pub fn split_tokens(messages: &[Value], tools: &[Value], total: u64) -> (u64, u64, u64) {
+    let first = usize::from(messages.first().is_some_and(|m| m["role"] == "system"));
+    let system = estimate_tokens(&messages[..first], &[]);
+    let history = estimate_tokens(&messages[first..], &[]);
+    let scale = total as f64 / (system + history + estimate_tokens(&[], tools)).max(1) as f64;
+    let system = (system as f64 * scale).round() as u64;
+    let history = ((history as f64 * scale).round() as u64).min(total - system.min(total));
+    (system, history, total.saturating_sub(system + history))
+}
+
+/// Drop the oldest history from `messages` until the prompt fits in `room`
+/// tokens. `measured` is the exact count of the prompt as given; later sizes
+/// are estimated, scaled to match it. The system prompt, the current message
+/// and everything after it (tool rounds) are kept. Returns the prompt tokens
+/// after fitting and how many messages were dropped.
+pub fn fit(messages: &mut Vec<Value>, tools: &[Value], room: u64, measured: u64) -> (u64, usize) {
+    let estimate = estimate_tokens(messages, tools).max(1);
+    let scale = measured as f64 / estimate as f64;
+    let scaled = |messages: &[Value]| (estimate_tokens(messages, tools) as f64 * scale).ceil() as u64;
+
+    let first = usize::from(messages.first().is_some_and(|m| m["role"] == "system"));
+    let current = messages.iter().rposition(|m| m["role"] == "user" && m["content"].is_string()).unwrap_or(messages.len());
+    let mut dropped = 0;
+    let mut tokens = measured;
+    while tokens > room && first + dropped < current {
+        dropped += 1;
+        // History has to start with a user turn
+        while first + dropped < current && messages[first + dropped]["role"] != "user" {
+            dropped += 1;
+        }
+        let candidate: Vec<Value> = messages[..first].iter().chain(&messages[first + dropped..]).cloned().collect();
+        tokens = scaled(&candidate);
+    }
+    if dropped > 0 {
+        messages.drain(first..first + dropped);
+    }
+    (tokens, dropped)
+}
+
+/// (prompt, completion) tokens from a provider response: Anthropic's
+/// `input_tokens`/`output_tokens` or OpenAI-style `prompt_tokens`/`completion_tokens`
+pub fn usage(raw: &serde_json::Value) -> Option<(u64, u64)> {
+    let usage = raw.get("usage")?;
+    let tokens = |keys: &[&str]| keys.iter().filter_map(|k| usage[k].as_u64()).sum::<u64>();
+    match (usage.get("input_tokens"), usage.get("prompt_tokens")) {
+        // Cached prompt tokens still take up context
+        (Some(_), _) => Some((
+            tokens(&["input_tokens", "cache_creation_input_tokens", "cache_read_input_tokens"]),
+            tokens(&["output_tokens"]),
+        )),
+        (None, Some(_)) => Some((tokens(&["prompt_tokens"]), tokens(&["completion_tokens"]))),
+        (None, None) => None,
+    }
+}
+
+/// (read, written) prompt-cache tokens from an Anthropic response; zero elsewhere
+pub fn cache_usage(raw: &serde_json::Value) -> (u64, u64) {
+    let usage = &raw["usage"];
+    (
+        usage["cache_read_input_tokens"].as_u64().unwrap_or_default(),
+        usage["cache_creation_input_tokens"].as_u64().unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_from_provider_responses() {
+        let anthropic = serde_json::json!({ "usage": { "input_tokens": 1200, "cache_read_input_tokens": 300, "output_tokens": 80 } });
+        assert_eq!(usage(&anthropic), Some((1500, 80)));
+        assert_eq!(cache_usage(&anthropic), (300, 0));
+        let openai = serde_json::json!({ "usage": { "prompt_tokens": 900, "completion_tokens": 40, "total_tokens": 940 } });
+        assert_eq!(usage(&openai), Some((900, 40)));
+        assert_eq!(usage(&serde_json::json!({ "choices": [] })), None);
+    }
+
+    #[test]
+    fn test_fit_drops_oldest_history() {
+        let text = |role: &str, chars: usize| serde_json::json!({ "role": role, "content": "x".repeat(chars) });
+        let mut messages = vec![
+            text("system", 4000),
+            text("user", 2000),
+            text("assistant", 2000),
+            text("user", 2000),
+            text("assistant", 2000),
+            text("user", 400),
+        ];
+        let measured = estimate_tokens(&messages, &[]);
+        assert_eq!(fit(&mut messages.clone(), &[], measured, measured), (measured, 0));
+
+        // Room for the system prompt, one exchange and the current message
+        let (tokens, dropped) = fit(&mut messages, &[], 2300, measured);
+        assert_eq!(dropped, 2);
+        assert!(tokens <= 2300);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[1]["role"], "user");
+
+        // The system prompt and current message stay even when they don't fit
+        let (tokens, dropped) = fit(&mut messages, &[], 10, tokens);
+        assert_eq!((dropped, messages.len()), (2, 2));
+        assert!(tokens > 10);
+
+        // Exact counts split by part, adding up to the count
+        let tools = vec![serde_json::json!({ "name": "t", "description": "x".repeat(100) })];
+        let (system, history, tool_tokens) = split_tokens(&messages, &tools, 3000);
+        assert_eq!(system + history + tool_tokens, 3000);
+        assert!(system > history && history > tool_tokens && tool_tokens > 0);
+    }
+}
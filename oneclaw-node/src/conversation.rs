@@ -2,9 +2,14 @@
 //! 
 //! Handles persistent, channel-agnostic conversation history.
 //! - Stores messages with channel metadata
-//! - Provides context for LLM calls
+//! - Keeps threads apart: a Slack thread, email thread or Telegram reply chain
+//!   gets its own context, separate from the user's unthreaded messages
+//! - Provides context for LLM calls (working and episodic memory tiers)
 //! - Supports conversation clearing
 
+use crate::config::MemoryConfig;
+use crate::context_budget::HistoryWindow;
+use crate::memory::{episodic, working};
 use crate::store::{ConversationMessage, Store};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -27,14 +32,28 @@ pub struct ToolCall {
     pub duration_ms: u64,
 }
 
+/// Characters of a tool output kept in the history
+const MAX_STORED_OUTPUT_CHARS: usize = 4000;
+
+/// `output` as kept with a reply: whole when short, cut off (as text) otherwise
+pub fn stored_output(output: &serde_json::Value) -> serde_json::Value {
+    let text = output.to_string();
+    if text.chars().count() <= MAX_STORED_OUTPUT_CHARS {
+        return output.clone();
+    }
+    let cut: String = text.chars().take(MAX_STORED_OUTPUT_CHARS).collect();
+    serde_json::Value::String(format!("{}... (cut off)", cut))
+}
+
 pub struct ConversationManager {
     store: Arc<dyn Store>,
     max_messages: usize,
+    memory: MemoryConfig,
 }
 
 impl ConversationManager {
-    pub fn new(store: Arc<dyn Store>, max_messages: usize) -> Self {
-        Self { store, max_messages }
+    pub fn new(store: Arc<dyn Store>, memory: &MemoryConfig) -> Self {
+        Self { store, max_messages: memory.session_max_messages, memory: memory.clone() }
     }
     
     /// Get conversation history for a user
@@ -56,9 +75,9 @@ impl ConversationManager {
         }).collect())
     }
     
-    /// Add a user message to the conversation
-    pub async fn add_user_message(&self, user_id: &str, content: &str, channel: &str) -> anyhow::Result<()> {
-        self.store.add_message(user_id, "user", content, channel, None).await?;
+    /// Add a user message to the conversation, in `thread_id` if it came from a thread
+    pub async fn add_user_message(&self, user_id: &str, content: &str, channel: &str, thread_id: Option<&str>) -> anyhow::Result<()> {
+        self.store.add_message(user_id, "user", content, channel, None, thread_id).await?;
         Ok(())
     }
     
@@ -69,15 +88,16 @@ impl ConversationManager {
         content: &str,
         channel: &str,
         tool_calls: Option<&[ToolCall]>,
+        thread_id: Option<&str>,
     ) -> anyhow::Result<()> {
         let tool_calls_json = tool_calls.map(|tc| serde_json::to_string(tc).unwrap_or_default());
-        self.store.add_message(user_id, "assistant", content, channel, tool_calls_json.as_deref()).await?;
+        self.store.add_message(user_id, "assistant", content, channel, tool_calls_json.as_deref(), thread_id).await?;
         Ok(())
     }
     
     /// Add a tool result message
-    pub async fn add_tool_message(&self, user_id: &str, content: &str, channel: &str) -> anyhow::Result<()> {
-        self.store.add_message(user_id, "tool", content, channel, None).await?;
+    pub async fn add_tool_message(&self, user_id: &str, content: &str, channel: &str, thread_id: Option<&str>) -> anyhow::Result<()> {
+        self.store.add_message(user_id, "tool", content, channel, None, thread_id).await?;
         Ok(())
     }
     
@@ -89,13 +109,27 @@ impl ConversationManager {
     }
     
     /// Build messages array for LLM API call
-    /// Includes system prompt and conversation history
+    /// Includes system prompt and the history of `thread_id` (or of the unthreaded messages)
     pub async fn build_llm_messages(
         &self,
         user_id: &str,
+        thread_id: Option<&str>,
         system_prompt: &str,
     ) -> anyhow::Result<Vec<serde_json::Value>> {
-        let history = self.get_history(user_id).await?;
+        Ok(self.build_llm_messages_with_window(user_id, thread_id, system_prompt).await?.0)
+    }
+    
+    /// `build_llm_messages`, plus which part of the stored history made it in.
+    /// Only the current session is sent (working memory); see `memory::working`.
+    pub async fn build_llm_messages_with_window(
+        &self,
+        user_id: &str,
+        thread_id: Option<&str>,
+        system_prompt: &str,
+    ) -> anyhow::Result<(Vec<serde_json::Value>, HistoryWindow)> {
+        // One extra message tells whether anything older was left out
+        let history = self.store.get_thread(user_id, thread_id, self.max_messages + 1).await?;
+        let (history, window) = working::window(history, &self.memory);
         
         let mut messages = vec![
             serde_json::json!({
@@ -111,7 +145,12 @@ impl ConversationManager {
             }));
         }
         
-        Ok(messages)
+        Ok((messages, window))
+    }
+    
+    /// Summaries of the user's past sessions for the system prompt (episodic memory)
+    pub async fn episodes_prompt(&self, user_id: &str) -> anyhow::Result<Option<String>> {
+        episodic::recall(self.store.as_ref(), user_id, self.memory.budgets.episodic_chars).await
     }
     
     /// Get conversation stats
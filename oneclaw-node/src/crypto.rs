@@ -0,0 +1,213 @@
+//! Crypto helpers - AES-256-GCM sealing for data that leaves the node
+//!
+//! Keys are either raw 32-byte keys or derived from a passphrase with
+//! PBKDF2-HMAC-SHA256. Sealed blobs are serialized as base64 JSON so they can
+//! travel over the control plane API unchanged.
+
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+
+/// Encrypted payload with everything needed to decrypt it (except the key)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedBlob {
+    /// Base64 PBKDF2 salt (empty when sealed with a raw key)
+    #[serde(default)]
+    pub salt: String,
+    /// Base64 AES-GCM nonce
+    pub nonce: String,
+    /// Base64 ciphertext with appended auth tag
+    pub ciphertext: String,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+/// Derive a 256-bit key from a passphrase and salt
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+/// Decode a base64 32-byte key (e.g. from an env var)
+pub fn decode_key(encoded: &str) -> anyhow::Result<[u8; 32]> {
+    let bytes = b64()
+        .decode(encoded.trim())
+        .map_err(|e| anyhow::anyhow!("Key is not valid base64: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Key must be 32 bytes"))
+}
+
+/// Account that keychain items are stored under
+pub const KEYCHAIN_ACCOUNT: &str = "oneclaw";
+
+/// The secret in OS keychain item `item`: the macOS Keychain (`security`) or
+/// the Secret Service on Linux (`secret-tool`, e.g. GNOME Keyring)
+pub fn keychain_secret(item: &str) -> anyhow::Result<String> {
+    let (program, args) = if cfg!(target_os = "macos") {
+        ("security", vec!["find-generic-password", "-s", item, "-a", KEYCHAIN_ACCOUNT, "-w"])
+    } else if cfg!(target_os = "linux") {
+        ("secret-tool", vec!["lookup", "service", item, "account", KEYCHAIN_ACCOUNT])
+    } else {
+        anyhow::bail!("Reading keys from the keychain isn't supported on this OS; use an env var");
+    };
+    let output = std::process::Command::new(program)
+        .args(&args)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Could not run {} to read the keychain: {}", program, e))?;
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || secret.is_empty() {
+        anyhow::bail!("Keychain item {} (account {}) not found", item, KEYCHAIN_ACCOUNT);
+    }
+    Ok(secret)
+}
+
+/// Encrypt with a raw key
+pub fn seal(key: &[u8; 32], plaintext: &[u8]) -> anyhow::Result<SealedBlob> {
+    let (nonce, ciphertext) = seal_raw(key, plaintext)?;
+    Ok(SealedBlob {
+        salt: String::new(),
+        nonce: b64().encode(nonce),
+        ciphertext: b64().encode(ciphertext),
+    })
+}
+
+/// Decrypt with a raw key
+pub fn open(key: &[u8; 32], blob: &SealedBlob) -> anyhow::Result<Vec<u8>> {
+    let nonce = b64().decode(&blob.nonce)?;
+    let ciphertext = b64().decode(&blob.ciphertext)?;
+    open_raw(key, &nonce, ciphertext)
+}
+
+/// Encrypt with a passphrase (fresh random salt per blob)
+pub fn seal_with_passphrase(passphrase: &str, plaintext: &[u8]) -> anyhow::Result<SealedBlob> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| anyhow::anyhow!("Failed to generate salt"))?;
+
+    let key = derive_key(passphrase, &salt);
+    let (nonce, ciphertext) = seal_raw(&key, plaintext)?;
+    Ok(SealedBlob {
+        salt: b64().encode(salt),
+        nonce: b64().encode(nonce),
+        ciphertext: b64().encode(ciphertext),
+    })
+}
+
+/// Decrypt a blob sealed with `seal_with_passphrase`
+pub fn open_with_passphrase(passphrase: &str, blob: &SealedBlob) -> anyhow::Result<Vec<u8>> {
+    let salt = b64().decode(&blob.salt)?;
+    let key = derive_key(passphrase, &salt);
+    open(&key, blob)
+}
+
+/// Verify a base64 HMAC-SHA1 signature (legacy webhook schemes, e.g. Twilio)
+pub fn verify_hmac_sha1(key: &[u8], message: &[u8], signature_b64: &str) -> bool {
+    let Ok(signature) = b64().decode(signature_b64.trim()) else {
+        return false;
+    };
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key);
+    ring::hmac::verify(&key, message, &signature).is_ok()
+}
+
+/// Verify a hex HMAC-SHA256 signature (e.g. Meta's `X-Hub-Signature-256: sha256=<hex>`)
+pub fn verify_hmac_sha256_hex(key: &[u8], message: &[u8], signature_hex: &str) -> bool {
+    let hex = signature_hex.trim();
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return false;
+    }
+    let Ok(signature) = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::verify(&key, message, &signature).is_ok()
+}
+
+/// Compare two secrets without leaking where they differ (HMAC both under a
+/// throwaway key, then verify)
+pub fn secrets_match(expected: &str, provided: &str) -> bool {
+    let Ok(key) = ring::hmac::Key::generate(ring::hmac::HMAC_SHA256, &SystemRandom::new()) else {
+        return false;
+    };
+    let tag = ring::hmac::sign(&key, expected.as_bytes());
+    ring::hmac::verify(&key, provided.as_bytes(), tag.as_ref()).is_ok()
+}
+
+fn seal_raw(key: &[u8; 32], plaintext: &[u8]) -> anyhow::Result<([u8; NONCE_LEN], Vec<u8>)> {
+    let sealing_key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow::anyhow!("Invalid encryption key"))?,
+    );
+
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow::anyhow!("Failed to generate nonce"))?;
+
+    let mut in_out = plaintext.to_vec();
+    sealing_key
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+    Ok((nonce, in_out))
+}
+
+fn open_raw(key: &[u8; 32], nonce: &[u8], mut ciphertext: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let opening_key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow::anyhow!("Invalid encryption key"))?,
+    );
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| anyhow::anyhow!("Invalid nonce"))?;
+
+    let plaintext = opening_key
+        .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+        .map_err(|_| anyhow::anyhow!("Decryption failed (wrong key or corrupted data)"))?;
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passphrase_roundtrip() {
+        let blob = seal_with_passphrase("correct horse", b"SOUL.md contents").unwrap();
+        assert_eq!(open_with_passphrase("correct horse", &blob).unwrap(), b"SOUL.md contents");
+        assert!(open_with_passphrase("wrong horse", &blob).is_err());
+    }
+
+    #[test]
+    fn test_verify_hmac_sha256_hex() {
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"app-secret");
+        let tag = ring::hmac::sign(&key, b"{\"object\":\"whatsapp_business_account\"}");
+        let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+
+        assert!(verify_hmac_sha256_hex(b"app-secret", b"{\"object\":\"whatsapp_business_account\"}", &hex));
+        assert!(!verify_hmac_sha256_hex(b"app-secret", b"{}", &hex));
+        assert!(!verify_hmac_sha256_hex(b"app-secret", b"{}", "not-hex"));
+    }
+
+    #[test]
+    fn test_secrets_match() {
+        assert!(secrets_match("s3cret", "s3cret"));
+        assert!(!secrets_match("s3cret", "s3cre"));
+        assert!(!secrets_match("s3cret", ""));
+    }
+}
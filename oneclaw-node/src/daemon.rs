@@ -2,22 +2,110 @@ use axum::{extract::State, http::StatusCode, response::Html, routing::{get, post
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
-use crate::{agent_os, config, conversation, executor, heartbeat, identity, integration, memory, monitor, oauth_config, receipt, store, workflow};
+use crate::{actions, activity, agent_os, auth, backup, batch, bootstrap, canary, capabilities, config, context_budget, conversation, debug_bundle, deliverability, dry_run, email_parse, embeddings, executor, followup, health, heartbeat, identity, impersonate, integration, lead_dedup, llm_audit, localize, managed, memory, model_router, monitor, node_key, notify, oauth_config, outreach, plugin, policy, receipt, report, response_style, scratchpad, self_check, shadow, snooze, spend, store, store_cache, store_outbox, structured, tagging, territory, tool_catalog, tool_insights, tool_limits, tool_server, upgrade, usage, workflow};
 
 pub struct AppState {
     pub config: &'static config::NodeConfig,
     pub executor_registry: Arc<executor::Registry>,
+    /// Which provider and model each of this node's LLM calls goes to
+    pub router: Arc<model_router::ModelRouter>,
     pub store: Arc<dyn store::Store>,
     pub identity_manager: Arc<identity::IdentityManager>,
     pub conversation_manager: Arc<conversation::ConversationManager>,
     pub agent_os: agent_os::AgentOS,
-    pub harness_tools: Vec<agent_os::ToolDefinition>,
+    /// Filled in by background harness discovery after the server is up; self-check re-fetches it
+    pub harness_tools: Arc<std::sync::RwLock<Vec<agent_os::ToolDefinition>>>,
     pub job_monitor: monitor::JobMonitor,
+    pub health: Arc<health::HealthState>,
+    pub snoozes: Arc<snooze::Snoozes>,
+    /// Rest of replies cut to fit a channel, sent on "more"
+    pub continuations: response_style::Continuations,
+    /// Channels started from `[channels]` config (listed on /channels)
+    pub channels: crate::channels::registry::ChannelRegistry,
+    /// Writes what happened to each queued reply to this node's store
+    pub deliveries: crate::channels::delivery::Recorder,
+    /// "typing…" loops of this node's chats
+    pub presence: Arc<crate::channels::Presence>,
+    /// Last capability statement, rewritten when the facts behind it change
+    pub capabilities: capabilities::Cache,
+    /// Node-wide concurrency caps from `executors.max_concurrency`
+    pub tool_limits: tool_limits::ToolLimits,
+    /// Background LLM requests waiting for the next batch
+    pub batches: Arc<batch::BatchQueue>,
+    /// Chat turns in flight and recent errors (`GET /status`)
+    pub activity: Arc<activity::Activity>,
+    /// Learning updates on trial, and the brain adopted since startup
+    pub canary: Arc<canary::Canary>,
+    /// Jobs watched for chats, handed over when a new daemon takes over
+    pub upgrade: upgrade::Upgrade,
 }
 
-pub async fn start(port: u16) -> anyhow::Result<()> {
+impl AppState {
+    /// Snapshot of the harness tool catalog (empty until discovery finishes)
+    pub fn harness_tools(&self) -> Vec<agent_os::ToolDefinition> {
+        self.harness_tools.read().map(|t| t.clone()).unwrap_or_default()
+    }
+
+    fn set_harness_tools(&self, tools: Vec<agent_os::ToolDefinition>) {
+        if let Ok(mut guard) = self.harness_tools.write() {
+            *guard = tools;
+        }
+    }
+}
+
+/// With `takeover`, this daemon takes over the port and state of the one
+/// already running (see `upgrade.rs`)
+pub async fn start(port: u16, takeover: bool) -> anyhow::Result<()> {
     let config = config::load()?;
-    let executor_registry = executor::Registry::load()?;
+    let node_key = node_key::load_or_generate()?;
+    tracing::info!("Node public key: {}", node_key.public_key());
+    shadow::activate(&config.shadow);
+    crate::retry::activate(&config.retry);
+    crate::spend::activate(&config.spend);
+    
+    if config.fleet.is_empty() {
+        return run_node(config, port, takeover).await;
+    }
+    
+    // Fleet mode: the primary node on `port`, and each member as a full logical
+    // node beside it (own port, store, channels, persona)
+    let mut ports = vec![port];
+    for member in &config.fleet {
+        if ports.contains(&member.port) {
+            anyhow::bail!("Fleet member {} wants port {}, which another node already has", member.id, member.port);
+        }
+        ports.push(member.port);
+    }
+    let members: Vec<(&'static config::NodeConfig, u16)> = config
+        .fleet
+        .iter()
+        .map(|member| (&*Box::leak(Box::new(config.for_fleet_member(member))), member.port))
+        .collect();
+    // Members inherit the top-level channels unless they set their own, so
+    // make sure no two nodes poll the same bot
+    let mut listening: std::collections::HashMap<(&str, String), &str> = std::collections::HashMap::new();
+    for node in std::iter::once(config).chain(members.iter().map(|(member, _)| *member)) {
+        for (channel, token) in crate::channels::registry::listener_tokens(node) {
+            if let Some(other) = listening.insert((channel, token), &node.node.id) {
+                anyhow::bail!(
+                    "Fleet nodes {} and {} would both listen with the same {} token; give fleet member {} its own channels.{}",
+                    other, node.node.id, channel, node.node.id, channel
+                );
+            }
+        }
+    }
+    tracing::info!("🚢 Fleet mode: starting {} nodes", ports.len());
+    let members = members.into_iter().map(|(member, port)| run_node(member, port, takeover));
+    futures::future::try_join_all(std::iter::once(run_node(config, port, takeover)).chain(members)).await?;
+    Ok(())
+}
+
+async fn run_node(config: &'static config::NodeConfig, port: u16, takeover: bool) -> anyhow::Result<()> {
+    if !takeover && upgrade::in_use(port).await {
+        anyhow::bail!("Port {} is in use; to upgrade a running daemon, start the new one with --takeover", port);
+    }
+
+    let executor_registry = executor::Registry::load()?.with_plugins(&config.plugins);
     
     // Initialize store based on config
     let store_instance: Arc<dyn store::Store> = match config.store.store_type.as_str() {
@@ -26,29 +114,84 @@ pub async fn start(port: u16) -> anyhow::Result<()> {
                 .unwrap_or_else(|| "http://localhost:3000".to_string());
             let token = config.control_plane.token.clone()
                 .unwrap_or_default();
-            Arc::new(store::HostedStore::new(api_url, token))
+            let mut hosted_store = store::HostedStore::new(api_url, token, config.node.id.clone());
+            if config.store.offline_queue {
+                let mut outbox = store_outbox::Outbox::open(config::expand_path(&config.store.outbox_path)).await?;
+                if config.store.encrypt_messages {
+                    outbox = outbox.with_content_key(store::content_key(&config.store)?);
+                }
+                if outbox.pending() > 0 {
+                    tracing::info!("📤 {} store writes queued while offline; replaying", outbox.pending());
+                }
+                hosted_store = hosted_store.with_outbox(outbox);
+                hosted_store.replay_in_background();
+            }
+            Arc::new(hosted_store)
+        }
+        "postgres" => {
+            let url = store::postgres_url(&config.store)?;
+            let mut postgres_store = store::PostgresStore::new(&url, config.store.postgres_max_connections).await?;
+            if config.store.encrypt_messages {
+                postgres_store = postgres_store.with_content_key(store::content_key(&config.store)?);
+                tracing::info!("🔐 Message content is encrypted at rest");
+            }
+            Arc::new(postgres_store)
         }
         _ => {
             // Default to SQLite
             let path = config::expand_path(&config.store.sqlite_path);
-            let sqlite_store = store::SqliteStore::new(path).await?;
+            let mut sqlite_store = store::SqliteStore::new(path).await?;
+            if config.store.encrypt_messages {
+                sqlite_store = sqlite_store.with_content_key(store::content_key(&config.store)?);
+                tracing::info!("🔐 Message content is encrypted at rest");
+            }
             Arc::new(sqlite_store)
         }
     };
+    let store_instance: Arc<dyn store::Store> = if config.store.cache.enabled {
+        match store_cache::CachedStore::connect(store_instance.clone(), &config.store.cache).await {
+            Ok(mut cached) => {
+                if config.store.encrypt_messages {
+                    cached = cached.with_content_key(store::content_key(&config.store)?);
+                }
+                tracing::info!("⚡ Store cache on (Redis)");
+                Arc::new(cached)
+            }
+            Err(e) => {
+                tracing::warn!("Store cache unavailable, using the store directly: {}", e);
+                store_instance
+            }
+        }
+    } else {
+        store_instance
+    };
+    let router = Arc::new(
+        model_router::ModelRouter::for_node(config).with_audit(llm_audit::Recorder::start(store_instance.clone(), &config.llm_audit)),
+    );
+    let executor_registry = executor_registry
+        .with_embedding_cache(store_instance.clone())
+        .with_lead_store(store_instance.clone())
+        .with_router(router.clone());
     
     // Initialize managers
     let identity_manager = identity::IdentityManager::new(
         store_instance.clone(),
-        config.identity.auto_create,
+        &config.identity,
+        &config.node.id,
     );
     
     let conversation_manager = conversation::ConversationManager::new(
         store_instance.clone(),
-        config.memory.session_max_messages,
+        &config.memory,
     );
     
     // Load Agent OS (SOUL.md, IDENTITY.md, etc.)
-    let agent_os = agent_os::AgentOS::load(None).unwrap_or_else(|e| {
+    // A configured workspace (e.g. a fleet member's persona) has to exist
+    let workspace_override = config.node.workspace.as_deref().map(config::expand_path);
+    if let Some(workspace) = workspace_override.as_ref().filter(|w| !w.exists()) {
+        anyhow::bail!("Workspace {} of node {} does not exist", workspace.display(), config.node.id);
+    }
+    let agent_os = agent_os::AgentOS::load(workspace_override).unwrap_or_else(|e| {
         tracing::warn!("Failed to load Agent OS templates: {}", e);
         agent_os::AgentOS {
             soul: "You are OneClaw, a helpful AI agent.".to_string(),
@@ -56,6 +199,7 @@ pub async fn start(port: u16) -> anyhow::Result<()> {
             skills: "".to_string(),
             playbooks: "".to_string(),
             memory: "".to_string(),
+            dir: None,
         }
     });
     let soul_loaded = !agent_os.soul.is_empty() && !agent_os.soul.contains("Not Found");
@@ -68,7 +212,6 @@ pub async fn start(port: u16) -> anyhow::Result<()> {
         if agent_os.memory.contains("Not Found") { "missing" } else { "loaded" },
     );
     
-    // Fetch tools from harness (run blocking HTTP in spawn_blocking to avoid runtime panic)
     let harness_url = std::env::var("HARNESS_URL")
         .unwrap_or_else(|_| {
             if cfg!(debug_assertions) {
@@ -77,43 +220,6 @@ pub async fn start(port: u16) -> anyhow::Result<()> {
                 "https://oneclaw.chat".to_string()
             }
         });
-    let harness_url_clone = harness_url.clone();
-    let harness_tools = tokio::task::spawn_blocking(move || {
-        match reqwest::blocking::get(format!("{}/tools", harness_url_clone)) {
-            Ok(resp) => {
-                if let Ok(body) = resp.text() {
-                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&body) {
-                        if let Some(tools_arr) = parsed["tools"].as_array() {
-                            tools_arr.iter().filter_map(|t| {
-                                Some(agent_os::ToolDefinition {
-                                    id: t["id"].as_str()?.to_string(),
-                                    description: t["description"].as_str().unwrap_or("").to_string(),
-                                    params_schema: t.get("paramsSchema").cloned(),
-                                    cost_estimate: t["estimatedCostUsd"].as_f64(),
-                                    tier: t["tier"].as_str().map(|s| s.to_string()),
-                                })
-                            }).collect()
-                        } else {
-                            vec![]
-                        }
-                    } else {
-                        vec![]
-                    }
-                } else {
-                    vec![]
-                }
-            }
-            Err(e) => {
-                tracing::warn!("Could not fetch harness tools: {}", e);
-                vec![]
-            }
-        }
-    })
-    .await
-    .unwrap_or_else(|_| vec![]);
-    
-    tracing::info!("Harness URL: {} (set HARNESS_URL to override)", harness_url);
-    tracing::info!("Loaded {} harness tools", harness_tools.len());
     
     // Initialize job monitor
     let job_monitor = monitor::JobMonitor::default();
@@ -125,503 +231,1675 @@ pub async fn start(port: u16) -> anyhow::Result<()> {
     
     let state = Arc::new(AppState { 
         config, 
-        executor_registry: executor_registry.clone(),
+        executor_registry,
+        router,
         store: store_instance.clone(),
-        identity_manager: identity_manager.clone(),
-        conversation_manager: conversation_manager.clone(),
-        agent_os: agent_os.clone(),
-        harness_tools: harness_tools.clone(),
+        identity_manager,
+        conversation_manager,
+        agent_os,
+        harness_tools: Arc::new(std::sync::RwLock::new(vec![])),
         job_monitor,
+        health: Arc::new(health::HealthState::default()),
+        snoozes: Arc::new(snooze::Snoozes::new(store_instance.clone())),
+        continuations: response_style::Continuations::default(),
+        channels: crate::channels::registry::ChannelRegistry::default(),
+        deliveries: crate::channels::delivery::Recorder::start(store_instance.clone()),
+        presence: Arc::default(),
+        capabilities: capabilities::Cache::default(),
+        tool_limits: tool_limits::ToolLimits::new(&config.executors.max_concurrency),
+        batches: Arc::new(batch::BatchQueue::default()),
+        activity: Arc::new(activity::Activity::default()),
+        canary: Arc::new(canary::Canary::new(&config.canary)),
+        upgrade: upgrade::Upgrade::default(),
     });
 
+    // Taking over: everything is set up, so the old daemon can let go now
+    let mut resumed = vec![];
+    if takeover {
+        let handoff = upgrade::request_handoff(&config.node.id, port).await?;
+        tracing::info!(
+            "🔁 Took over from the running daemon: {} job watch(es), {} tracked job(s)",
+            handoff.watches.len(),
+            handoff.jobs.len()
+        );
+        state.job_monitor.restore(handoff.jobs).await;
+        state.continuations.import(handoff.continuations);
+        resumed = handoff.watches;
+    }
+
+    // Bring up harness discovery, heartbeat and channels in the background so
+    // /health and /livez answer immediately even if the harness is slow.
+    let self_hosted = config.tool_server.enabled && tool_server::is_self(&harness_url, port);
+    plugin::supervise(state.executor_registry.plugins().to_vec(), &config.plugins);
+    tokio::spawn(bring_up(state.clone(), harness_url.clone(), self_hosted, takeover, resumed));
+
+    let app = Router::new()
+        .route("/", get(ui_dashboard))
+        .route("/chat.html", get(ui_chat))
+        .route("/setup.html", get(ui_setup))
+        .route("/receipts.html", get(ui_receipts))
+        .route("/integrations.html", get(ui_integrations))
+        .route("/static/style.css", get(ui_style))
+        .route("/health", get(health))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .route("/config", get(get_config))
+        .route("/config/drift", get(get_config_drift))
+        .route("/capabilities", get(get_capabilities))
+        .route("/channels", get(list_channels))
+        .route("/shadow", get(get_shadow_log))
+        .route("/spend", get(get_spend))
+        .route("/status", get(get_status))
+        .route("/canary", get(get_canary))
+        .route("/usage", get(get_usage))
+        .route("/audit/llm", get(get_llm_audit))
+        .route("/audit/outreach", get(get_outreach_audit))
+        .route("/messages", get(list_deliveries))
+        .route("/messages/:id", get(get_delivery))
+        .route("/debug/bundle", get(get_debug_bundle))
+        .route("/run", post(run_workflow))
+        .route("/jobs/simulate", post(simulate_job))
+        .route("/jobs/events", post(job_event))
+        .route("/deliverability/check", post(check_deliverability))
+        .route("/chat", post(chat))
+        .route("/chat/as", post(chat_as))
+        .route("/identity/requests", get(list_access_requests))
+        .route("/identity/requests/:id/approve", post(approve_access_request))
+        .route("/identity/requests/:id/deny", post(deny_access_request))
+        .route("/identity/pairing-codes", post(create_pairing_code))
+        .route("/chat/history", get(get_chat_history))
+        .route("/chat/clear", post(clear_chat))
+        .route("/notify", post(notify_user))
+        .route("/analytics/conversations", get(conversation_analytics))
+        .route("/actions", get(list_actions))
+        .route("/actions/:name", post(run_action))
+        .route("/voice/incoming", post(voice_incoming))
+        .route("/voice/respond", post(voice_respond))
+        .route("/whatsapp/webhook", get(whatsapp_verify).post(whatsapp_webhook))
+        .route("/receipts", get(list_receipts))
+        .route("/reports/:file", get(get_report))
+        .route("/backups", get(list_backups).post(run_backup))
+        .route("/backups/:name", get(download_backup))
+        .route("/memory/preferences", get(get_preferences))
+        .route("/memory/territories", get(get_territories).put(set_territories))
+        .route("/executors", get(list_executors))
+        .route("/executors/:id", get(get_executor))
+        .route("/plugins", get(list_plugins))
+        .route("/harness/tools", get(serve_harness_tools))
+        .route("/harness/execute", post(serve_harness_execute))
+        .route("/integrations", get(get_integrations))
+        .route("/integrations/gmail/connect", get(connect_gmail))
+        .route("/integrations/gmail/status", get(gmail_status))
+        .route("/api/oauth/config", post(oauth_config::save_oauth_config_handler))
+        .route(upgrade::HANDOFF_PATH, post(upgrade_handoff))
+        .layer(CorsLayer::permissive())
+        .with_state(Arc::clone(&state));
+
+    println!("\n🦞 OneClaw Node Daemon (Rust)");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("  Node:   {} ({})", config.node.name, config.node.id);
+    println!("  Mode:   {}", config.node.environment);
+    println!("  UI:     http://localhost:{}", port);
+    println!("  Harness: {} (tools execute here)", harness_url);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("\nPress Ctrl+C to stop\n");
+
+    let listener = upgrade::bind(port, &config.upgrade, takeover).await?;
+    // After handing off, keep accepting while the new daemon binds, then finish
+    // what's in flight and exit
+    let handed_off = Arc::clone(&state);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            handed_off.upgrade.handed_over().await;
+            tokio::time::sleep(std::time::Duration::from_secs(handed_off.config.upgrade.overlap_secs)).await;
+            tracing::info!("🔁 Handed off; no longer accepting connections");
+        })
+        .await?;
+    upgrade::drain(&state.activity, &config.upgrade).await;
+    Ok(())
+}
+
+/// Background startup: harness discovery, then heartbeat and channels.
+/// Each component reports its progress to `HealthState` (visible on /health and /readyz).
+/// A `self_hosted` node is its own harness (`tool_server`) and reads its catalog directly.
+/// With `takeover`, channels start where the daemon taken over from left off.
+async fn bring_up(
+    state: Arc<AppState>,
+    harness_url: String,
+    self_hosted: bool,
+    takeover: bool,
+    mut resumed: Vec<upgrade::JobWatch>,
+) {
+    let config = state.config;
+    
+    state.health.set_component("harness", health::ComponentStatus::Starting).await;
+    let discovered = if self_hosted {
+        Ok(tool_server::catalog(&config.tool_server, &state.executor_registry))
+    } else {
+        agent_os::fetch_harness_tools(&config.node.id, &harness_url).await
+    };
+    match discovered {
+        Ok(tools) => {
+            tracing::info!("Harness URL: {} (set HARNESS_URL to override)", harness_url);
+            tracing::info!("Loaded {} harness tools", tools.len());
+            state.health.set_component(
+                "harness",
+                health::ComponentStatus::Ready(format!("{} tools", tools.len())),
+            ).await;
+            state.set_harness_tools(tools);
+        }
+        Err(e) => {
+            tracing::warn!("Could not fetch harness tools: {}", e);
+            state.health.set_component("harness", health::ComponentStatus::Failed(e.to_string())).await;
+        }
+    }
+
     // Start heartbeat service in background
     let heartbeat_config = heartbeat::HeartbeatConfig::default();
     if heartbeat_config.enabled {
-        let heartbeat_service = Arc::new(heartbeat::HeartbeatService::new(
-            heartbeat_config,
-            agent_os,
-            executor_registry,
-            conversation_manager,
-            identity_manager,
-            harness_tools,
+        let self_check = heartbeat_config.self_check.then(|| self_check::SelfCheck::new(
             config,
+            Arc::clone(&state.store),
+            Arc::clone(&state.health),
+            harness_url.clone(),
+            Arc::clone(&state.harness_tools),
+            heartbeat_config.min_free_disk_mb,
+            heartbeat_config.ops_channel.clone(),
         ));
+        let mut heartbeat_service = heartbeat::HeartbeatService::new(
+            heartbeat_config,
+            state.agent_os.clone(),
+            state.executor_registry.clone(),
+            state.conversation_manager.clone(),
+            state.identity_manager.clone(),
+            state.harness_tools(),
+            config,
+        );
+        if let Some(mut self_check) = self_check {
+            // Tool catalog changes found on refresh are announced, not applied silently
+            if config.tool_catalog.notify_owners || config.tool_catalog.update_skills {
+                let (changes, rx) = tokio::sync::mpsc::unbounded_channel();
+                self_check = self_check.with_catalog_changes(changes);
+                tokio::spawn(tool_catalog::run(Arc::clone(&state), rx));
+            }
+            heartbeat_service = heartbeat_service.with_self_check(self_check);
+        }
+        let heartbeat_service = Arc::new(heartbeat_service);
         tokio::spawn(async move {
             heartbeat_service.start().await;
         });
+        state.health.set_component("heartbeat", health::ComponentStatus::Ready("running".to_string())).await;
+    }
+
+    // Redeliver snoozed notifications once they're due
+    tokio::spawn(Arc::clone(&state.snoozes).run());
+
+    // Nightly encrypted node bundles to object storage
+    if config.backup.enabled {
+        tokio::spawn(backup::run(config, Arc::clone(&state.health)));
+    }
+
+    // Background LLM work at batch prices
+    if batch::enabled(config) {
+        tokio::spawn(batch::run(Arc::clone(&state)));
+    }
+
+    // Intent/sentiment tagging of stored user messages
+    if config.tagging.enabled {
+        let batches = batch::enabled(config).then(|| Arc::clone(&state.batches));
+        tokio::spawn(tagging::run(Arc::clone(&state.store), Arc::clone(&state.router), config.tagging.clone(), batches));
+    }
+
+    // Measured tool performance written into SKILLS.md
+    if config.tool_insights.enabled {
+        tokio::spawn(tool_insights::run(Arc::clone(&state)));
+    }
+
+    // Fleet settings from the control plane, with drift reported back
+    if config.managed.enabled && config.control_plane.url.is_some() {
+        tokio::spawn(managed::run(config));
     }
 
-    // Initialize Telegram channel if bot token is configured
-    if let Ok(bot_token) = std::env::var("TELEGRAM_BOT_TOKEN") {
-        if !bot_token.is_empty() && bot_token != "your_telegram_bot_token_here" {
-            use crate::channels::{telegram::TelegramChannel, Channel};
+    // Similar old exchanges compressed into summaries
+    if config.memory.compression.enabled {
+        tokio::spawn(memory::compression::run(Arc::clone(&state.store), Arc::clone(&state.executor_registry), config));
+    }
+
+    // Channels configured under [channels]. Telegram has its own handler (commands,
+    // autonomous jobs); messages from every other channel are regular chat turns.
+    let start = crate::channels::registry::Start { deliveries: &state.deliveries, takeover };
+    for (active, rx) in state.channels.activate(config, &state.health, start).await {
+        state.snoozes.register_channel(active.name, Arc::clone(&active.outbound)).await;
+        match active.name {
+            "telegram" => {
+                for watch in resumed.drain(..) {
+                    watch_job(Arc::clone(&state), Arc::clone(&active.outbound), watch);
+                }
+                spawn_telegram_handler(Arc::clone(&state), Arc::clone(&active.outbound), Arc::clone(&active.dispatcher), harness_url.clone(), rx)
+            }
+            _ => spawn_chat_handler(Arc::clone(&state), active, rx),
+        }
+    }
+    if !resumed.is_empty() {
+        tracing::warn!("Telegram isn't running; {} handed-over job(s) won't be reported", resumed.len());
+    }
+}
+
+/// Poll a job started from a Telegram chat, then send its results there. A
+/// watch handed to a new daemon stops here; that daemon reports the results.
+fn watch_job(state: Arc<AppState>, telegram: Arc<dyn crate::channels::Channel>, watch: upgrade::JobWatch) {
+    let tracked = state.upgrade.track(&watch);
+    let mut poller = crate::autonomous_jobs_poller::JobPoller::new(
+        watch.job_id.clone(),
+        watch.channel_id.clone(),
+        crate::channels::ChannelType::Telegram,
+        watch.harness_url.clone(),
+        state.config.node.id.clone(),
+    ).with_monitor(state.job_monitor.clone())
+    .with_snoozes(state.snoozes.clone())
+    .with_poll_interval(
+        std::time::Duration::from_secs(state.config.jobs.poll_min_secs),
+        std::time::Duration::from_secs(state.config.jobs.poll_max_secs),
+    );
+    if state.config.jobs.recovery {
+        poller = poller.with_recovery(state.config.jobs.max_recoveries, Arc::clone(&state.router));
+    }
+    
+    let correlation = llm_audit::Correlation::job(&watch.job_id);
+    tokio::spawn(llm_audit::scope(correlation, async move {
+        let outcome = tokio::select! {
+            outcome = poller.run_until_complete(Arc::new(telegram.clone())) => outcome,
+            _ = state.upgrade.handed_over(), if tracked => return,
+        };
+        if tracked && !state.upgrade.finish(&watch.job_id) {
+            return;
+        }
+        let node_config = state.config;
+        match outcome {
+            Ok(results) => {
+                tracing::info!("✅ Job completed, formatting results");
+                usage::record(state.store.as_ref(), usage::job(&watch.job_id, &watch.user_id, watch.estimate, &results)).await;
+                
+                if let Some(workspace) = agent_os::workspace_for(node_config) {
+                    if let Err(e) = crate::plan_cache::remember(&workspace, &watch.request, &watch.planned) {
+                        tracing::warn!("Failed to cache plan: {}", e);
+                    }
+                }
+                
+                // Format and send final results
+                let mut formatted = localize::for_display(node_config, &crate::autonomous_jobs_poller::format_job_results(&results));
+                if !watch.local_steps.is_empty() {
+                    // Leads contacted in other campaigns are left out
+                    let (screened, skipped) = lead_dedup::screen(state.store.as_ref(), node_config, &results, &watch.job_id).await;
+                    formatted.push_str(&outreach::draft_for_results(
+                        &watch.local_steps,
+                        &screened,
+                        &node_config.node.name,
+                        agent_os::workspace_for(node_config).as_deref(),
+                    ));
+                    if !skipped.is_empty() {
+                        formatted.push_str(&lead_dedup::skipped_note(&skipped));
+                    }
+                    for (action, business) in outreach::drafted_leads(&watch.local_steps, &screened) {
+                        let lead = lead_dedup::Lead::from_business(business);
+                        lead_dedup::record(Some(state.store.as_ref()), &lead, Some(watch.job_id.clone()), action, None).await;
+                    }
+                }
+                
+                // Put enriched leads straight into the owner's phone
+                if node_config.google_contacts.auto_sync {
+                    let leads = crate::google_contacts::leads_from_results(&results);
+                    if !leads.is_empty() {
+                        let synced = match state.executor_registry.get("google.contacts") {
+                            Some(exec) => Some(exec.execute(
+                                serde_json::json!({ "action": "push", "contacts": leads }),
+                                node_config,
+                            ).await),
+                            None => None,
+                        };
+                        match synced {
+                            Some(executor::ExecutorResult::Executed { output, .. }) => {
+                                formatted.push_str(&format!(
+                                    "📇 Added {} contact(s) to Google Contacts ({} already saved)\n",
+                                    output["created"].as_array().map_or(0, |c| c.len()),
+                                    output["skipped"].as_array().map_or(0, |c| c.len()),
+                                ));
+                            }
+                            other => tracing::warn!("Google Contacts sync failed: {:?}", other),
+                        }
+                    }
+                }
+                
+                // Save to conversation
+                let _ = state.conversation_manager.add_assistant_message(
+                    &watch.user_id,
+                    &formatted,
+                    "telegram",
+                    None,
+                    watch.thread_id.as_deref(),
+                ).await;
+                
+                state.snoozes.remember("telegram", &watch.channel_id, &formatted);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: watch.channel_id.clone(),
+                    content: formatted,
+                    reply_to: watch.reply_to.clone(),
+                    thread_id: watch.thread_id.clone(),
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: snooze::buttons(),
+                }).await;
+            }
+            Err(e) => {
+                tracing::error!("❌ Job execution failed: {}", e);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: watch.channel_id.clone(),
+                    content: format!("❌ Job failed: {}\n\nTry `/logs` for details.", e),
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+            }
+        }
+    }));
+}
+
+/// Telegram messages: slash commands, button presses and chat turns that can hand
+/// off to autonomous jobs. Replies and job updates share one paced queue.
+fn spawn_telegram_handler(
+    state: Arc<AppState>,
+    telegram: Arc<dyn crate::channels::Channel>,
+    dispatcher: Arc<crate::channels::dispatch::Dispatcher>,
+    harness_url: String,
+    mut rx: tokio::sync::mpsc::Receiver<crate::channels::IncomingMessage>,
+) {
+    // Each message is a turn in the LLM audit log
+    tokio::spawn(llm_audit::scope(llm_audit::Correlation::default(), async move {
+        while let Some(msg) = rx.recv().await {
+            // Messages that arrive until this is dropped are acknowledged
+            let _turn = dispatcher.begin(&msg);
+            llm_audit::new_turn();
+            match msg.action() {
+                Some(action) => tracing::info!("🔘 Telegram button from {}: {}",
+                    msg.username.as_deref().unwrap_or("unknown"),
+                    action
+                ),
+                None => tracing::info!("📨 Telegram message from {}: {}", 
+                    msg.username.as_deref().unwrap_or("unknown"),
+                    msg.content
+                ),
+            }
             
-            let telegram = TelegramChannel::new(bot_token);
-            let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+            // "typing…" until the guard is dropped
+            let activity = state.presence.acquire(Arc::clone(&telegram), &msg.channel_id);
             
-            // Spawn Telegram listener
-            let telegram_clone = telegram.clone();
-            tokio::spawn(async move {
-                if let Err(e) = telegram_clone.start(tx).await {
-                    tracing::error!("Telegram channel error: {}", e);
+            // Resolve user identity; a group chat is one shared conversation (and job
+            // context), with each message carrying its sender's name
+            let chat = &msg.metadata["chat"];
+            let admitted = if matches!(chat["type"].as_str(), Some("group" | "supergroup")) {
+                admit_sender(&state, "telegram_group", &msg.channel_id, chat["title"].as_str(), &msg.content).await
+            } else {
+                admit_sender(&state, "telegram", &msg.provider_user_id, msg.username.as_deref(), &msg.content).await
+            };
+            let user_id = match admitted {
+                Ok(user_id) => user_id,
+                Err(reply) => {
+                    drop(activity);
+                    if let Some(reply) = reply {
+                        let _ = telegram.send(crate::channels::OutgoingMessage {
+                            channel_type: crate::channels::ChannelType::Telegram,
+                            channel_id: msg.channel_id,
+                            content: reply,
+                            reply_to: None,
+                            thread_id: None,
+                            metadata: serde_json::json!({}),
+                            attachments: vec![],
+                            buttons: vec![],
+                        }).await;
+                    }
+                    continue;
                 }
-            });
+            };
             
-            // Spawn message handler
-            let state_clone = state.clone();
-            let telegram_clone = telegram.clone();
-            tokio::spawn(async move {
-                while let Some(msg) = rx.recv().await {
-                    tracing::info!("📨 Telegram message from {}: {}", 
-                        msg.username.as_deref().unwrap_or("unknown"),
-                        msg.content
-                    );
-                    
-                    let chat_id = msg.channel_id.clone();
-                    let telegram_for_typing = telegram_clone.clone();
-                    
-                    // Spawn typing indicator that runs until we're done
-                    let typing_task = tokio::spawn(async move {
-                        loop {
-                            let _ = telegram_for_typing.send_typing(&chat_id).await;
-                            tokio::time::sleep(tokio::time::Duration::from_secs(4)).await;
-                        }
-                    });
-                    
-                    // Resolve user identity
-                    let (user_id, _) = match state_clone
-                        .identity_manager
-                        .resolve("telegram", &msg.provider_user_id, msg.username.as_deref())
-                        .await {
-                            Ok(result) => result,
-                            Err(e) => {
-                                typing_task.abort();
-                                tracing::error!("Identity resolution error: {}", e);
-                                continue;
-                            }
-                        };
-                    
-                    // Store user message
-                    let _ = state_clone
-                        .conversation_manager
-                        .add_user_message(&user_id, &msg.content, "telegram")
-                        .await;
+            // "more": the rest of a reply that was cut to fit
+            let style = response_style::ResponseStyle::for_channel(&state.config.channels, "telegram");
+            if let Some(next) = state.continuations.more(&style, "telegram", &msg.channel_id, &msg.content) {
+                drop(activity);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: msg.channel_id,
+                    content: next,
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+                continue;
+            }
+            
+            // "snooze this for 2 hours" or a snooze button: put off the last notification
+            if let Some(duration) = snooze::parse_request(&msg.content) {
+                let notification = msg.source_message();
+                let reply = match state.snoozes.snooze("telegram", &msg.channel_id, notification, duration).await {
+                    Ok(deliver_at) => snooze::confirmation(deliver_at),
+                    Err(e) => format!("❌ Couldn't snooze: {}", e),
+                };
+                drop(activity);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: msg.channel_id,
+                    content: reply,
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+                continue;
+            }
+            
+            // Owner commands: "/as <user> <message>", "/pair", "/approve <id>", ...
+            if let Some(reply) = owner_command(&state, "telegram", &msg.provider_user_id, &msg.content).await {
+                drop(activity);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    content: state.continuations.prepare(&style, "telegram", &msg.channel_id, &reply),
+                    channel_id: msg.channel_id,
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+                continue;
+            }
+            
+            if msg.content.trim() == capabilities::COMMAND {
+                let described = describe_capabilities(&state).await;
+                drop(activity);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: msg.channel_id,
+                    content: described.statement,
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+                continue;
+            }
+            
+            // Dry run: plan and simulate without creating a harness job
+            if let Some(request) = msg.content.strip_prefix("/simulate") {
+                let preview = match simulate_request(&state, request.trim()).await {
+                    Ok(sim) => crate::simulation::format_preview(&sim),
+                    Err(e) => format!("❌ Simulation failed: {}", e),
+                };
+                drop(activity);
+                let _ = telegram.send(crate::channels::OutgoingMessage {
+                    channel_type: crate::channels::ChannelType::Telegram,
+                    channel_id: msg.channel_id,
+                    content: preview,
+                    reply_to: None,
+                    thread_id: None,
+                    metadata: serde_json::json!({}),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await;
+                continue;
+            }
+            
+            // Store user message
+            let _ = state
+                .conversation_manager
+                .add_user_message(&user_id, &msg.content, "telegram", msg.thread_id.as_deref())
+                .await;
+            
+            // Build system prompt with Telegram's length and formatting limits
+            state.canary.feedback(&user_id, &msg.content);
+            let (brain, arm) = state.canary.brain_for_turn(&state.agent_os);
+            let (mut system_prompt, _) = system_prompt_with_memory(&state, &brain, &user_id, &msg.content).await;
+            if let Some(style_block) = style.prompt_block("telegram") {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&style_block);
+            }
+            if let Some(territories_block) = territory::prompt_block(&memory::load_preferences(state.config).unwrap_or_default()) {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&territories_block);
+            }
+            let jobs_block = state.job_monitor.active_jobs_prompt(&user_id).await;
+            let has_jobs = jobs_block.is_some();
+            if let Some(jobs_block) = jobs_block {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(&jobs_block);
+            }
+            
+            // Build messages
+            let mut messages = match state
+                .conversation_manager
+                .build_llm_messages_with_window(&user_id, msg.thread_id.as_deref(), &system_prompt)
+                .await {
+                    Ok((msgs, history)) => {
+                        close_sessions_if_new(&state, &user_id, &history);
+                        msgs
+                    }
+                    Err(e) => {
+                        tracing::error!("Message building error: {}", e);
+                        continue;
+                    }
+                };
+            
+            // Convert harness tools to Claude format
+            let mut claude_tools: Vec<serde_json::Value> = state.harness_tools()
+                .iter()
+                .map(|tool| {
+                    serde_json::json!({
+                        "name": tool.id,
+                        "description": tool.description,
+                        "input_schema": tool.params_schema
+                    })
+                })
+                .collect();
+            if has_jobs {
+                claude_tools.push(executor::JobControlExecutor::tool_schema());
+            }
+            claude_tools.push(executor::OutreachExecutor::tool_schema(outreach::DraftKind::CallScript));
+            claude_tools.push(executor::OutreachExecutor::tool_schema(outreach::DraftKind::Voicemail));
+            claude_tools.push(executor::ScratchpadExecutor::tool_schema(scratchpad::Access::Read));
+            claude_tools.push(executor::ScratchpadExecutor::tool_schema(scratchpad::Access::Write));
+            claude_tools.push(executor::EmailParseExecutor::tool_schema());
+            if state.config.control_plane.url.is_some() {
+                claude_tools.push(executor::GoogleContactsExecutor::tool_schema());
+                claude_tools.push(executor::CalendarFollowupExecutor::tool_schema());
+            }
+            if executor::SecretRequirement::WebSearchBackend.is_satisfied(state.config) {
+                claude_tools.push(executor::WebSearchExecutor::tool_schema());
+            }
+            claude_tools.extend(state.executor_registry.plugin_tools(state.config));
+            let mut budget = context_budget::ContextBudget::default();
+            fit_context_window(&state, &mut messages, &claude_tools, &mut budget.window).await;
+            let _turn = state.activity.begin(&user_id, "telegram", &msg.content);
+            
+            match run_llm_streaming_tools(&state, &user_id, messages.clone(), claude_tools).await {
+                Ok((result, streamed_tools)) => {
+                    tracing::info!("✅ LLM response received");
+                    budget.record_llm(&result);
                     
-                    // Build system prompt with Telegram formatting instructions
-                    let mut system_prompt = state_clone.agent_os.build_system_prompt(&state_clone.harness_tools);
-                    system_prompt.push_str("\n\n## Telegram Formatting\nYou are communicating via Telegram. Format your responses to be:\n- Concise and easy to read on mobile\n- Use **bold** for business names and key info\n- Use bullet points (•) for lists\n- Avoid raw CLI output - present data in a friendly way\n- When showing businesses, format like:\n\n**Business Name** ⭐ 4.8\n📞 (720) 442-0474\n✅ Has website | ✅ Has reviews\n");
+                    // Send initial thinking message
+                    let _ = telegram.send(crate::channels::OutgoingMessage {
+                        channel_type: crate::channels::ChannelType::Telegram,
+                        channel_id: msg.channel_id.clone(),
+                        content: "💭 Analyzing your request...".to_string(),
+                        reply_to: None,
+                        thread_id: None,
+                        metadata: serde_json::json!({}),
+                        attachments: vec![],
+                        buttons: vec![],
+                    }).await;
                     
-                    // Build messages
-                    let messages = match state_clone
-                        .conversation_manager
-                        .build_llm_messages(&user_id, &system_prompt)
-                        .await {
-                            Ok(msgs) => msgs,
-                            Err(e) => {
-                                tracing::error!("Message building error: {}", e);
-                                continue;
-                            }
-                        };
+                    let content = extract_content(&result);
+                    tracing::info!("✅ Content extracted, looking for tools...");
+                    let tool_results = match streamed_tools {
+                        Some(calls) => calls.into_iter().filter_map(|(_, result)| result).collect(),
+                        None => find_and_execute_tools(&state, &user_id, &content, &result).await,
+                    };
+                    tracing::info!("✅ Tools executed: {} results", tool_results.len());
                     
-                    // Convert harness tools to Claude format
-                    let claude_tools: Vec<serde_json::Value> = state_clone.harness_tools
-                        .iter()
-                        .map(|tool| {
-                            serde_json::json!({
-                                "name": tool.id,
-                                "description": tool.description,
-                                "input_schema": tool.params_schema
-                            })
-                        })
-                        .collect();
+                    // Stop typing indicator
+                    drop(activity);
                     
-                    let input = serde_json::json!({ 
-                        "messages": messages,
-                        "tools": claude_tools
-                    });
+                    // Check if this is a complex multi-step request requiring autonomous job
+                    let is_complex = crate::autonomous_jobs::is_complex_request(&msg.content, &tool_results);
                     
-                    match run_llm_with_timeout(Arc::clone(&state_clone), input, "main").await {
-                        Ok(result) => {
-                            tracing::info!("✅ LLM response received");
-                            
-                            // Send initial thinking message
-                            let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                channel_type: crate::channels::ChannelType::Telegram,
-                                channel_id: msg.channel_id.clone(),
-                                content: "💭 Analyzing your request...".to_string(),
-                                reply_to: None,
-                                metadata: serde_json::json!({}),
-                            }).await;
-                            
-                            let content = extract_content(&result);
-                            tracing::info!("✅ Content extracted, looking for tools...");
-                            let tool_results = find_and_execute_tools(&state_clone, &content, &result).await;
-                            tracing::info!("✅ Tools executed: {} results", tool_results.len());
-                            
-                            // Stop typing indicator
-                            typing_task.abort();
-                            
-                            // Check if this is a complex multi-step request requiring autonomous job
-                            let is_complex = crate::autonomous_jobs::is_complex_request(&msg.content, &tool_results);
-                            
-                            if is_complex {
-                                tracing::info!("🤖 Complex request detected, creating autonomous job plan");
-                                
-                                // Get LLM API key from environment
-                                let api_key = std::env::var("ANTHROPIC_API_KEY")
-                                    .unwrap_or_else(|_| std::env::var("OPENAI_API_KEY").unwrap_or_default());
-                                
-                                if api_key.is_empty() {
-                                    tracing::warn!("No LLM API key found, falling back to simple execution");
-                                } else {
-                                    // Generate job plan
-                                    match crate::autonomous_jobs::generate_job_plan(
-                                        &msg.content,
-                                        &reqwest::Client::new(),
-                                        &api_key
+                    if is_complex {
+                        tracing::info!("🤖 Complex request detected, creating autonomous job plan");
+                        
+                        // Get LLM API key from environment
+                        let api_key = std::env::var("ANTHROPIC_API_KEY")
+                            .unwrap_or_else(|_| std::env::var("OPENAI_API_KEY").unwrap_or_default());
+                        
+                        if api_key.is_empty() && state.router.routed().is_none() {
+                            tracing::warn!("No LLM API key found, falling back to simple execution");
+                        } else {
+                            // Generate job plan
+                            let prefs = memory::load_preferences(state.config).unwrap_or_default();
+                            let territories_block = territory::prompt_block(&prefs);
+                            let workspace = agent_os::workspace_for(state.config);
+                            let tools = state.harness_tools();
+                            match crate::plan_cache::plan_or_generate(
+                                &state.router,
+                                &msg.content,
+                                territories_block.as_deref(),
+                                &tools,
+                                workspace.as_deref(),
+                            ).await {
+                                Ok((mut plan, source)) => {
+                                    tracing::info!("✅ Plan with {} steps ({:?})", plan.steps.len(), source);
+                                    let planned = plan.clone();
+                                    
+                                    // Expand territory references, enforce territory limits and
+                                    // reject actions the live tool catalog can't run
+                                    let checked = territory::apply_to_plan(&mut plan, &prefs)
+                                        .and_then(|_| crate::autonomous_jobs::validate_plan(&plan, &tools));
+                                    if let Err(e) = checked {
+                                        tracing::warn!("Plan rejected: {}", e);
+                                        let _ = telegram.send(crate::channels::OutgoingMessage {
+                                            channel_type: crate::channels::ChannelType::Telegram,
+                                            channel_id: msg.channel_id.clone(),
+                                            content: format!("⚠️ {}", e),
+                                            reply_to: None,
+                                            thread_id: None,
+                                            metadata: serde_json::json!({}),
+                                            attachments: vec![],
+                                            buttons: vec![],
+                                        }).await;
+                                        continue;
+                                    }
+                                    
+                                    // Send acknowledgment
+                                    let _ = telegram.send(crate::channels::OutgoingMessage {
+                                        channel_type: crate::channels::ChannelType::Telegram,
+                                        channel_id: msg.channel_id.clone(),
+                                        content: format!("🦞 Got it! Breaking this into {} steps...", plan.steps.len()),
+                                        reply_to: None,
+                                        thread_id: None,
+                                        metadata: serde_json::json!({}),
+                                        attachments: vec![],
+                                        buttons: vec![],
+                                    }).await;
+                                    
+                                    // Call scripts / voicemails are drafted locally from the results
+                                    let local_steps = outreach::take_local_steps(&mut plan);
+                                    
+                                    if shadow::active() {
+                                        shadow::record(shadow::ShadowKind::Job, &user_id, serde_json::json!({
+                                            "request": msg.content,
+                                            "plan": plan,
+                                            "local_steps": local_steps,
+                                        }));
+                                        continue;
+                                    }
+                                    
+                                    // Create job in harness
+                                    match crate::autonomous_jobs::create_harness_job(
+                                        &state.config.node.id,
+                                        &user_id,
+                                        &plan,
+                                        &harness_url,
+                                        &state.config.scoring,
+                                        &state.config.jobs
                                     ).await {
-                                        Ok(plan) => {
-                                            tracing::info!("✅ Generated plan with {} steps", plan.steps.len());
+                                        Ok(job_id) => {
+                                            tracing::info!("✅ Created job: {}", job_id);
+                                            state.job_monitor.track_job(&job_id, &user_id, &plan.description).await;
                                             
-                                            // Send acknowledgment
-                                            let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                                channel_type: crate::channels::ChannelType::Telegram,
+                                            watch_job(Arc::clone(&state), telegram.clone(), upgrade::JobWatch {
+                                                job_id,
+                                                user_id: user_id.clone(),
                                                 channel_id: msg.channel_id.clone(),
-                                                content: format!("🦞 Got it! Breaking this into {} steps...", plan.steps.len()),
-                                                reply_to: None,
-                                                metadata: serde_json::json!({}),
-                                            }).await;
+                                                reply_to: msg.reply_to.clone(),
+                                                thread_id: msg.thread_id.clone(),
+                                                request: msg.content.clone(),
+                                                planned,
+                                                local_steps,
+                                                estimate: crate::simulation::simulate(&plan, &state.harness_tools()).estimated_cost_usd,
+                                                harness_url: harness_url.clone(),
+                                            });
                                             
-                                            // Create job in harness
-                                            match crate::autonomous_jobs::create_harness_job(
-                                                &user_id,
-                                                &plan,
-                                                &harness_url
-                                            ).await {
-                                                Ok(job_id) => {
-                                                    tracing::info!("✅ Created job: {}", job_id);
-                                                    
-                                                    // Start polling in background
-                                                    let poller = crate::autonomous_jobs_poller::JobPoller::new(
-                                                        job_id.clone(),
-                                                        msg.channel_id.clone(),
-                                                        crate::channels::ChannelType::Telegram,
-                                                        harness_url.clone()
-                                                    );
-                                                    
-                                                    let telegram_for_polling = telegram_clone.clone();
-                                                    let user_id_for_conv = user_id.clone();
-                                                    let conv_manager = state_clone.conversation_manager.clone();
-                                                    
-                                                    tokio::spawn(async move {
-                                                        match poller.run_until_complete(Arc::new(telegram_for_polling.clone())).await {
-                                                            Ok(results) => {
-                                                                tracing::info!("✅ Job completed, formatting results");
-                                                                
-                                                                // Format and send final results
-                                                                let formatted = crate::autonomous_jobs_poller::format_job_results(&results);
-                                                                
-                                                                // Save to conversation
-                                                                let _ = conv_manager.add_assistant_message(
-                                                                    &user_id_for_conv,
-                                                                    &formatted,
-                                                                    "telegram",
-                                                                    None
-                                                                ).await;
-                                                                
-                                                                let _ = telegram_for_polling.send(crate::channels::OutgoingMessage {
-                                                                    channel_type: crate::channels::ChannelType::Telegram,
-                                                                    channel_id: msg.channel_id.clone(),
-                                                                    content: formatted,
-                                                                    reply_to: None,
-                                                                    metadata: serde_json::json!({}),
-                                                                }).await;
-                                                            }
-                                                            Err(e) => {
-                                                                tracing::error!("❌ Job execution failed: {}", e);
-                                                                let _ = telegram_for_polling.send(crate::channels::OutgoingMessage {
-                                                                    channel_type: crate::channels::ChannelType::Telegram,
-                                                                    channel_id: msg.channel_id.clone(),
-                                                                    content: format!("❌ Job failed: {}\n\nTry `/logs` for details.", e),
-                                                                    reply_to: None,
-                                                                    metadata: serde_json::json!({}),
-                                                                }).await;
-                                                            }
-                                                        }
-                                                    });
-                                                    
-                                                    // Don't continue with normal flow - job is running in background
-                                                    continue;
-                                                }
-                                                Err(e) => {
-                                                    tracing::error!("Failed to create job: {}", e);
-                                                }
-                                            }
+                                            // Don't continue with normal flow - job is running in background
+                                            continue;
                                         }
                                         Err(e) => {
-                                            tracing::error!("Failed to generate plan: {}", e);
+                                            tracing::error!("Failed to create job: {}", e);
                                         }
                                     }
                                 }
+                                Err(e) => {
+                                    tracing::error!("Failed to generate plan: {}", e);
+                                }
                             }
-                            
-                            // Send status update if tools were called
-                            if !tool_results.is_empty() {
-                                let tool_names: Vec<&str> = tool_results.iter()
-                                    .map(|r| r.tool.as_str())
-                                    .collect();
-                                let status_msg = format!("🔧 Executing: {}...", tool_names.join(", "));
-                                tracing::info!("Tool execution started: {}", status_msg);
-                                let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                    channel_type: crate::channels::ChannelType::Telegram,
-                                    channel_id: msg.channel_id.clone(),
-                                    content: status_msg,
-                                    reply_to: None,
-                                    metadata: serde_json::json!({}),
-                                }).await;
-                                
-                                // Give user time to see the status before final response
-                                tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
-                                
-                                // Extract and show steps completed (if harness returned them)
-                                for result in &tool_results {
-                                    if let Some(steps) = result.output.get("steps_completed").and_then(|s| s.as_array()) {
-                                        let mut steps_msg = String::from("📋 **Execution Steps:**\n\n");
-                                        for (i, step) in steps.iter().enumerate() {
-                                            if let Some(step_str) = step.as_str() {
-                                                steps_msg.push_str(&format!("{}. {}\n", i + 1, step_str));
-                                            }
-                                        }
-                                        
-                                        tracing::info!("Showing execution steps to user");
-                                        let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                            channel_type: crate::channels::ChannelType::Telegram,
-                                            channel_id: msg.channel_id.clone(),
-                                            content: steps_msg,
-                                            reply_to: None,
-                                            metadata: serde_json::json!({}),
-                                        }).await;
-                                        
-                                        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                        }
+                    }
+                    
+                    // Send status update if tools were called
+                    if !tool_results.is_empty() {
+                        let tool_names: Vec<&str> = tool_results.iter()
+                            .map(|r| r.tool.as_str())
+                            .collect();
+                        let status_msg = format!("🔧 Executing: {}...", tool_names.join(", "));
+                        tracing::info!("Tool execution started: {}", status_msg);
+                        let _ = telegram.send(crate::channels::OutgoingMessage {
+                            channel_type: crate::channels::ChannelType::Telegram,
+                            channel_id: msg.channel_id.clone(),
+                            content: status_msg,
+                            reply_to: None,
+                            thread_id: None,
+                            metadata: serde_json::json!({}),
+                            attachments: vec![],
+                            buttons: vec![],
+                        }).await;
+                        
+                        // Give user time to see the status before final response
+                        tokio::time::sleep(tokio::time::Duration::from_millis(800)).await;
+                        
+                        // Extract and show steps completed (if harness returned them)
+                        for result in &tool_results {
+                            if let Some(steps) = result.output.get("steps_completed").and_then(|s| s.as_array()) {
+                                let mut steps_msg = String::from("📋 **Execution Steps:**\n\n");
+                                for (i, step) in steps.iter().enumerate() {
+                                    if let Some(step_str) = step.as_str() {
+                                        steps_msg.push_str(&format!("{}. {}\n", i + 1, step_str));
                                     }
                                 }
                                 
-                                // Send completion status
-                                let _ = telegram_clone.send(crate::channels::OutgoingMessage {
+                                tracing::info!("Showing execution steps to user");
+                                let _ = telegram.send(crate::channels::OutgoingMessage {
                                     channel_type: crate::channels::ChannelType::Telegram,
                                     channel_id: msg.channel_id.clone(),
-                                    content: "✅ Complete! Formatting results...".to_string(),
+                                    content: steps_msg,
                                     reply_to: None,
+                                    thread_id: None,
                                     metadata: serde_json::json!({}),
+                                    attachments: vec![],
+                                    buttons: vec![],
                                 }).await;
-                            }
-                            
-                            tracing::info!("Starting followup formatting...");
-                            // Get final response with Telegram formatting
-                            let final_content = if tool_results.is_empty() {
-                                tracing::info!("No tools used, returning direct content");
-                                content
-                            } else {
-                                tracing::info!("Tool results found, formatting for Telegram...");
-                                
-                                // Save tool results to conversation
-                                for result in &tool_results {
-                                    let _ = state_clone
-                                        .conversation_manager
-                                        .add_tool_message(
-                                            &user_id,
-                                            &format!("[{} result]", result.tool),
-                                            "telegram",
-                                        )
-                                        .await;
-                                }
-                                
-                                // Build a simpler prompt that just asks Claude to format, not regenerate
-                                let formatted_response = format!(
-                                    "I found the results. Here's a nicely formatted summary:\n\n{}",
-                                    content
-                                );
                                 
-                                // If content is already good, use it directly
-                                if content.contains("**") || content.contains("⭐") {
-                                    tracing::info!("Content already formatted, using directly");
-                                    formatted_response
-                                } else {
-                                    tracing::info!("Content needs formatting, asking Claude...");
-                                    // Ask Claude to format the raw tool output
-                                    let mut followup_messages = vec![
-                                        serde_json::json!({
-                                            "role": "user",
-                                            "content": format!(
-                                                "Format this business data for Telegram with:\n\
-                                                - **Bold** business names\n\
-                                                - ⭐ ratings, 📞 phones\n\
-                                                - ✅/❌ for features\n\
-                                                - Quick stats summary\n\n\
-                                                Data: {}",
-                                                serde_json::to_string_pretty(&tool_results[0].output)
-                                                    .unwrap_or_default()
-                                                    .chars()
-                                                    .take(4000)
-                                                    .collect::<String>()
-                                            )
-                                        })
-                                    ];
-                                    
-                                    let simple_input = serde_json::json!({ 
-                                        "messages": followup_messages
-                                    });
-                                    
-                                    match run_llm_with_timeout(Arc::clone(&state_clone), simple_input, "format").await {
-                                        Ok(result) => {
-                                            tracing::info!("✅ Formatting complete");
-                                            extract_content(&result)
-                                        },
-                                        Err(e) => {
-                                            tracing::error!("❌ Formatting failed: {}", e);
-                                            formatted_response
-                                        }
-                                    }
-                                }
-                            };
-                            
-                            tracing::info!("Preparing final response...");
-                            let final_content = if final_content.trim().is_empty() {
-                                "I didn't get a response. Try again?".to_string()
-                            } else {
-                                final_content
-                            };
-                            
-                            tracing::info!("Saving conversation messages...");
-                            // Save assistant message
-                            let _ = state_clone
+                                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                            }
+                        }
+                        
+                        // Send completion status
+                        let _ = telegram.send(crate::channels::OutgoingMessage {
+                            channel_type: crate::channels::ChannelType::Telegram,
+                            channel_id: msg.channel_id.clone(),
+                            content: "✅ Complete! Formatting results...".to_string(),
+                            reply_to: None,
+                            thread_id: None,
+                            metadata: serde_json::json!({}),
+                            attachments: vec![],
+                            buttons: vec![],
+                        }).await;
+                    }
+                    
+                    tracing::info!("Starting followup formatting...");
+                    // Get final response with Telegram formatting
+                    let final_content = if tool_results.is_empty() {
+                        tracing::info!("No tools used, returning direct content");
+                        content
+                    } else {
+                        tracing::info!("Tool results found, formatting for Telegram...");
+                        
+                        // Save tool results to conversation
+                        for result in &tool_results {
+                            let _ = state
                                 .conversation_manager
-                                .add_assistant_message(&user_id, &final_content, "telegram", None)
+                                .add_tool_message(
+                                    &user_id,
+                                    &format!("[{} result]", result.tool),
+                                    "telegram",
+                                    msg.thread_id.as_deref(),
+                                )
                                 .await;
-                            
-                            tracing::info!("Sending final response to Telegram...");
-                            // Send reply via Telegram
-                            let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                channel_type: crate::channels::ChannelType::Telegram,
-                                channel_id: msg.channel_id,
-                                content: final_content,
-                                reply_to: None,
-                                metadata: serde_json::json!({}),
-                            }).await;
-                            tracing::info!("✅ Telegram response sent successfully");
                         }
-                        Err(e) => {
-                            typing_task.abort();
-                            tracing::error!("❌ LLM error: {}", e);
-                            
-                            // Send detailed error to user
-                            let error_msg = format!("❌ **Error Processing Request**\n\n{}\n\nCheck `/logs` for details.", 
-                                e.to_string().chars().take(200).collect::<String>());
-                            
-                            let _ = telegram_clone.send(crate::channels::OutgoingMessage {
-                                channel_type: crate::channels::ChannelType::Telegram,
-                                channel_id: msg.channel_id,
-                                content: error_msg,
-                                reply_to: None,
-                                metadata: serde_json::json!({}),
-                            }).await;
+                        
+                        get_followup_response(&state, "telegram", &messages, &content, &tool_results, Some(&mut budget)).await
+                    };
+                    usage::record(state.store.as_ref(), usage::chat("telegram", &user_id, &budget)).await;
+                    
+                    tracing::info!("Preparing final response...");
+                    if let Some(arm) = arm {
+                        record_canary_turn(&state, &user_id, arm, &final_content, &tool_results);
+                    }
+                    let final_content = if final_content.trim().is_empty() {
+                        "I didn't get a response. Try again?".to_string()
+                    } else {
+                        final_content
+                    };
+                    
+                    tracing::info!("Saving conversation messages...");
+                    // Save assistant message
+                    let _ = state
+                        .conversation_manager
+                        .add_assistant_message(&user_id, &final_content, "telegram", None, msg.thread_id.as_deref())
+                        .await;
+                    
+                    tracing::info!("Sending final response to Telegram...");
+                    // Send reply via Telegram, cut to fit with the rest held for "more"
+                    let chat_id_for_files = msg.channel_id.clone();
+                    let reply = state.continuations.prepare(&style, "telegram", &msg.channel_id, &final_content);
+                    let _ = telegram.send(crate::channels::OutgoingMessage {
+                        channel_type: crate::channels::ChannelType::Telegram,
+                        channel_id: msg.channel_id,
+                        content: reply,
+                        reply_to: msg.reply_to,
+                        thread_id: msg.thread_id,
+                        metadata: serde_json::json!({}),
+                        attachments: vec![],
+                        buttons: vec![],
+                    }).await;
+                    tracing::info!("✅ Telegram response sent successfully");
+                    
+                    // Deliver branded audit reports as attachments
+                    for artifact in generate_audit_reports(&state, &tool_results).await {
+                        if let Err(e) = telegram.send_file(
+                            &chat_id_for_files,
+                            artifact.deliverable(),
+                            Some("📄 Audit report - ready to forward"),
+                        ).await {
+                            tracing::warn!("Failed to send report: {}", e);
                         }
                     }
                 }
-            });
-            
-            tracing::info!("✅ Telegram channel initialized");
+                Err(e) => {
+                    drop(activity);
+                    tracing::error!("❌ LLM error: {}", e);
+                    state.activity.error("telegram", &e);
+                    
+                    // Send detailed error to user
+                    let error_msg = format!("❌ **Error Processing Request**\n\n{}\n\nCheck `/logs` for details.", 
+                        e.to_string().chars().take(200).collect::<String>());
+                    
+                    let _ = telegram.send(crate::channels::OutgoingMessage {
+                        channel_type: crate::channels::ChannelType::Telegram,
+                        channel_id: msg.channel_id,
+                        content: error_msg,
+                        reply_to: None,
+                        thread_id: None,
+                        metadata: serde_json::json!({}),
+                        attachments: vec![],
+                        buttons: vec![],
+                    }).await;
+                }
+            }
         }
-    }
+    }));
+}
 
-    let app = Router::new()
-        .route("/", get(ui_dashboard))
-        .route("/chat.html", get(ui_chat))
-        .route("/setup.html", get(ui_setup))
-        .route("/receipts.html", get(ui_receipts))
-        .route("/integrations.html", get(ui_integrations))
-        .route("/static/style.css", get(ui_style))
-        .route("/health", get(health))
-        .route("/config", get(get_config))
-        .route("/run", post(run_workflow))
-        .route("/chat", post(chat))
-        .route("/chat/history", get(get_chat_history))
-        .route("/chat/clear", post(clear_chat))
-        .route("/receipts", get(list_receipts))
-        .route("/memory/preferences", get(get_preferences))
-        .route("/executors", get(list_executors))
-        .route("/integrations", get(get_integrations))
-        .route("/integrations/gmail/connect", get(connect_gmail))
-        .route("/integrations/gmail/status", get(gmail_status))
-        .route("/api/oauth/config", post(oauth_config::save_oauth_config_handler))
-        .layer(CorsLayer::permissive())
-        .with_state(state);
+/// Every other channel: each message is a regular chat turn, replied to in place
+fn spawn_chat_handler(
+    state: Arc<AppState>,
+    active: Arc<crate::channels::registry::ActiveChannel>,
+    mut rx: tokio::sync::mpsc::Receiver<crate::channels::IncomingMessage>,
+) {
+    let (name, outbound) = (active.name, Arc::clone(&active.outbound));
+    tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            // Messages that arrive until this is dropped are acknowledged
+            let _turn = active.dispatcher.begin(&msg);
+            tracing::info!("📨 {} message from {}: {}",
+                name,
+                msg.username.as_deref().unwrap_or(&msg.provider_user_id),
+                msg.content
+            );
 
-    let harness_url = std::env::var("HARNESS_URL")
-        .unwrap_or_else(|_| {
-            if cfg!(debug_assertions) {
-                "http://localhost:9000".to_string()
-            } else {
-                "https://oneclaw.chat".to_string()
+            // "more": the rest of a reply that was cut to fit
+            let style = response_style::ResponseStyle::for_channel(&state.config.channels, name);
+            if let Some(next) = state.continuations.more(&style, name, &msg.channel_id, &msg.content) {
+                if let Err(e) = outbound.send(crate::channels::OutgoingMessage {
+                    channel_type: outbound.channel_type(),
+                    channel_id: msg.channel_id.clone(),
+                    content: next,
+                    reply_to: msg.reply_to.clone(),
+                    thread_id: msg.thread_id.clone(),
+                    metadata: msg.metadata.clone(),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await {
+                    tracing::warn!("Failed to send {} reply: {}", name, e);
+                }
+                continue;
             }
+
+            // Unknown senders get in per `identity.policies`
+            if let Err(reply) = admit_sender(&state, name, &msg.provider_user_id, msg.username.as_deref(), &msg.content).await {
+                let Some(reply) = reply else { continue };
+                if let Err(e) = outbound.send(crate::channels::OutgoingMessage {
+                    channel_type: outbound.channel_type(),
+                    channel_id: msg.channel_id.clone(),
+                    content: reply,
+                    reply_to: msg.reply_to.clone(),
+                    thread_id: msg.thread_id.clone(),
+                    metadata: msg.metadata.clone(),
+                    attachments: vec![],
+                    buttons: vec![],
+                }).await {
+                    tracing::warn!("Failed to send {} reply: {}", name, e);
+                }
+                continue;
+            }
+
+            let activity = state.presence.acquire(Arc::clone(&outbound), &msg.channel_id);
+
+            // Owner commands: "/as <user> <message>", "/pair", "/approve <id>", ...
+            let (content, reports) = match owner_command(&state, name, &msg.provider_user_id, &msg.content).await {
+                Some(reply) => (reply, vec![]),
+                None => {
+                    // Inbound email is parsed so replies to outreach read as lead updates
+                    let context = match outbound.channel_type() {
+                        crate::channels::ChannelType::Email => {
+                            let parts = email_parse::EmailParts {
+                                sender_name: msg.username.clone().filter(|n| *n != msg.provider_user_id),
+                                sender_email: Some(msg.provider_user_id.clone()),
+                                subject: msg.metadata["subject"].as_str().map(str::to_string).filter(|s| !s.is_empty()),
+                                body: msg.content.clone(),
+                            };
+                            Some(email_parse::context_block(&email_parse::parse(&state.router, &parts, &state.config.tagging.model).await))
+                        }
+                        _ => None,
+                    };
+                    let request = ChatRequest {
+                        message: msg.content.clone(),
+                        channel: name.to_string(),
+                        provider: Some(name.to_string()),
+                        provider_id: Some(msg.provider_user_id.clone()),
+                        username: msg.username.clone(),
+                        context,
+                        response_format: None,
+                        thread_id: msg.thread_id.clone(),
+                        dry_run: false,
+                    };
+                    match chat_from(Arc::clone(&state), request).await {
+                        Ok(Json(reply)) => (reply.response, reply.reports),
+                        Err((_, e)) => {
+                            tracing::error!("❌ {} turn failed: {}", name, e);
+                            (format!("❌ Something went wrong: {}", e.chars().take(200).collect::<String>()), vec![])
+                        }
+                    }
+                }
+            };
+            drop(activity);
+            // Metadata goes back with the reply (email threads on it)
+            if let Err(e) = outbound.send(crate::channels::OutgoingMessage {
+                channel_type: outbound.channel_type(),
+                channel_id: msg.channel_id.clone(),
+                content: state.continuations.prepare(&style, name, &msg.channel_id, &content),
+                reply_to: msg.reply_to.clone(),
+                thread_id: msg.thread_id.clone(),
+                metadata: msg.metadata.clone(),
+                attachments: vec![],
+                buttons: vec![],
+            }).await {
+                tracing::warn!("Failed to send {} reply: {}", name, e);
+            }
+
+            // Deliver branded audit reports as attachments
+            if let Some(workspace) = state.agent_os.workspace() {
+                for link in reports {
+                    let Some(file) = link.strip_prefix("/reports/") else { continue };
+                    let path = report::reports_dir(&workspace).join(file);
+                    if let Err(e) = outbound.send_file(&msg.channel_id, &path, Some("📄 Audit report - ready to forward")).await {
+                        tracing::warn!("Failed to send report: {}", e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Render branded report artifacts for any website-audit results
+async fn generate_audit_reports(state: &Arc<AppState>, tool_results: &[ToolCallResult]) -> Vec<report::ReportArtifact> {
+    let Some(workspace) = state.agent_os.workspace() else {
+        return vec![];
+    };
+
+    let mut artifacts = vec![];
+    for result in tool_results {
+        let Some(audit) = report::find_audit(&result.output).cloned() else {
+            continue;
+        };
+        let params = result.input.get("params").unwrap_or(&result.input);
+        let business_name = params["businessName"].as_str().unwrap_or("Business").to_string();
+        let url = params["url"].as_str().unwrap_or("").to_string();
+        let config = state.config;
+
+        match report::generate(&audit, &business_name, &url, &config.reports, &config.node.name, &workspace).await {
+            Ok(artifact) => artifacts.push(artifact),
+            Err(e) => tracing::warn!("Audit report generation failed: {}", e),
+        }
+    }
+    artifacts
+}
+
+/// Serve a generated report from the workspace reports dir
+async fn get_report(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(file): axum::extract::Path<String>,
+) -> Result<([(&'static str, &'static str); 1], Vec<u8>), (StatusCode, String)> {
+    if file.contains('/') || file.contains('\\') || file.contains("..") {
+        return Err((StatusCode::BAD_REQUEST, "Invalid report name".to_string()));
+    }
+    let workspace = state.agent_os.workspace()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No workspace".to_string()))?;
+    let bytes = tokio::fs::read(report::reports_dir(&workspace).join(&file))
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "Report not found".to_string()))?;
+    let content_type = if file.ends_with(".pdf") { "application/pdf" } else { "text/html; charset=utf-8" };
+    Ok(([("content-type", content_type)], bytes))
+}
+
+/// GET /backups - this node's backups in object storage, newest first
+async fn list_backups(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<Vec<backup::BackupObject>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    backup::list(state.config)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
+}
+
+/// POST /backups - back up now (same as the nightly run, including retention)
+async fn run_backup(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<backup::BackupObject>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    backup::backup_now(state.config)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
+}
+
+/// GET /backups/:name - a sealed bundle, for `oneclaw import-bundle`
+async fn download_backup(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<([(&'static str, String); 2], Vec<u8>), (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let bytes = backup::download(state.config, &name)
+        .await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+    Ok((
+        [
+            ("content-type", "application/json".to_string()),
+            ("content-disposition", format!("attachment; filename=\"{}\"", name)),
+        ],
+        bytes,
+    ))
+}
+
+async fn ui_dashboard() -> Html<&'static str> { Html(include_str!("ui/index.html")) }
+async fn ui_chat() -> Html<&'static str> { Html(include_str!("ui/chat.html")) }
+async fn ui_setup() -> Html<&'static str> { Html(include_str!("ui/setup.html")) }
+async fn ui_receipts() -> Html<&'static str> { Html(include_str!("ui/receipts.html")) }
+async fn ui_integrations() -> Html<&'static str> { Html(include_str!("ui/integrations.html")) }
+async fn ui_style() -> ([(&'static str, &'static str); 1], &'static str) {
+    ([("content-type", "text/css")], include_str!("ui/style.css"))
+}
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: String,
+    node_id: String,
+    node_name: String,
+    public_key: Option<String>,
+    startup: std::collections::HashMap<String, health::ComponentStatus>,
+}
+
+async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        node_id: state.config.node.id.clone(),
+        node_name: state.config.node.name.clone(),
+        public_key: node_key::load_or_generate().ok().map(|k| k.public_key().to_string()),
+        startup: state.health.components().await,
+    })
+}
+
+async fn livez() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<health::Readiness>) {
+    let readiness = state.health.readiness(state.config, &state.store).await;
+    let status = if readiness.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(readiness))
+}
+
+async fn get_config(State(state): State<Arc<AppState>>) -> Json<&'static config::NodeConfig> {
+    Json(state.config)
+}
+
+/// GET /config/drift - managed settings this node differs from (see `managed.rs`)
+async fn get_config_drift(State(state): State<Arc<AppState>>) -> Json<managed::DriftReport> {
+    Json(managed::report(state.config))
+}
+
+/// GET /channels - configured channels: connection, last message, error
+async fn list_channels(State(state): State<Arc<AppState>>) -> Json<Vec<crate::channels::registry::ChannelStatus>> {
+    Json(state.channels.statuses(&state.health).await)
+}
+
+/// GET /capabilities - what this node can do, in words and as the facts behind them
+async fn get_capabilities(State(state): State<Arc<AppState>>) -> Json<capabilities::CapabilityStatement> {
+    Json(describe_capabilities(&state).await)
+}
+
+/// Capability statement for the current tools, integrations and limits;
+/// the LLM write-up is reused until one of them changes
+async fn describe_capabilities(state: &Arc<AppState>) -> capabilities::CapabilityStatement {
+    let integrations = integration::get_integrations_list(&state.config.node.id, state.config.control_plane.url.as_deref()).await;
+    let facts = capabilities::gather(
+        state.config,
+        &state.agent_os,
+        &state.harness_tools(),
+        &state.executor_registry.describe(state.config),
+        &integrations,
+    );
+
+    state.capabilities.get_or_generate(facts, |facts| async move {
+        let input = serde_json::json!({
+            "messages": [{ "role": "user", "content": capabilities::prompt(&facts) }]
         });
-    println!("\n🦞 OneClaw Node Daemon (Rust)");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("  Node:   {} ({})", config.node.name, config.node.id);
-    println!("  Mode:   {}", config.node.environment);
-    println!("  UI:     http://localhost:{}", port);
-    println!("  Harness: {} (tools execute here)", harness_url);
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("\nPress Ctrl+C to stop\n");
+        match run_llm_with_timeout(Arc::clone(state), input, model_router::CAPABILITIES).await {
+            Ok(executor::ExecutorResult::Executed { output, .. }) => {
+                output["content"].as_str().filter(|c| !c.trim().is_empty()).map(str::to_string)
+            }
+            other => {
+                tracing::warn!("Capability write-up failed, listing facts instead: {:?}", other);
+                None
+            }
+        }
+    }).await
+}
+
+#[derive(Deserialize)]
+struct ShadowQuery {
+    limit: Option<usize>,
+}
+
+/// GET /spend - the spend throttle's bucket; 404 while the throttle is off
+async fn get_spend(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<spend::SpendStatus>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    spend::throttle()
+        .map(|throttle| Json(throttle.status()))
+        .ok_or((StatusCode::NOT_FOUND, "Spend throttle is off (spend.max_usd_per_hour)".to_string()))
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    node_id: String,
+    node_name: String,
+    uptime_secs: u64,
+    turns: Vec<activity::Turn>,
+    jobs: Vec<monitor::JobStatus>,
+    channels: Vec<crate::channels::registry::ChannelStatus>,
+    /// None while the spend throttle is off
+    spend: Option<spend::SpendStatus>,
+    errors: Vec<activity::RecentError>,
+}
+
+/// GET /canary - the brain update on trial, if any, and the last decision
+async fn get_canary(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<canary::CanaryStatus>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.canary.status()))
+}
+
+#[derive(Deserialize)]
+struct UsageQuery {
+    days: Option<u32>,
+}
+
+/// GET /usage - what chat turns, tool calls and jobs cost, per day
+async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<UsageQuery>,
+) -> Result<Json<usage::UsageReport>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let days = query.days.unwrap_or(usage::DEFAULT_DAYS).clamp(1, usage::MAX_DAYS);
+    let since = (chrono::Utc::now() - chrono::Duration::days(i64::from(days) - 1))
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let records = state.store
+        .usage_since(since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(usage::report(since, &records)))
+}
+
+#[derive(Deserialize)]
+struct LlmAuditParams {
+    /// RFC 3339 time or a span back from now (`30m`, `6h`, `7d`)
+    since: Option<String>,
+    turn_id: Option<String>,
+    job_id: Option<String>,
+    limit: Option<usize>,
+}
+
+/// GET /audit/llm - raw LLM exchanges, newest first (admin token required:
+/// they hold every user's prompts)
+async fn get_llm_audit(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(params): axum::extract::Query<LlmAuditParams>,
+) -> Result<Json<Vec<store::LlmExchange>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let since = match params.since.as_deref() {
+        Some(value) => Some(tagging::parse_since(value).ok_or((StatusCode::BAD_REQUEST, format!("Invalid since: {}", value)))?),
+        None => None,
+    };
+    let query = store::LlmAuditQuery {
+        since,
+        turn_id: params.turn_id,
+        job_id: params.job_id,
+        limit: params.limit.unwrap_or(50).clamp(1, 500),
+    };
+    let exchanges = state.store
+        .llm_exchanges(&query)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(exchanges))
+}
+
+#[derive(Deserialize)]
+struct OutreachAuditParams {
+    /// RFC 3339 time or a span back from now (`30m`, `6h`, `7d`)
+    since: Option<String>,
+    /// Only contacts made despite matching an earlier one
+    #[serde(default)]
+    overridden: bool,
+    limit: Option<usize>,
+}
+
+/// GET /audit/outreach - businesses outreach reached, newest first (see
+/// `lead_dedup.rs`; admin token required)
+async fn get_outreach_audit(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(params): axum::extract::Query<OutreachAuditParams>,
+) -> Result<Json<Vec<store::ContactedLead>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let since = match params.since.as_deref() {
+        Some(value) => Some(tagging::parse_since(value).ok_or((StatusCode::BAD_REQUEST, format!("Invalid since: {}", value)))?),
+        None => None,
+    };
+    let query = store::ContactedLeadQuery {
+        since,
+        overridden: params.overridden,
+        limit: params.limit.unwrap_or(50).clamp(1, 500),
+    };
+    let contacts = state.store
+        .contacted_leads(&query)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(contacts))
+}
+
+#[derive(Deserialize)]
+struct DebugBundleParams {
+    /// User id, or `<provider>:<id>` of one of the user's identities
+    user_id: String,
+    /// Newest messages to include
+    limit: Option<usize>,
+}
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    axum::serve(listener, app).await?;
-    Ok(())
+/// GET /debug/bundle - transcript with tool calls, redacted config and logs for
+/// one user, as a file to attach to a support ticket (see `debug_bundle.rs`)
+async fn get_debug_bundle(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(params): axum::extract::Query<DebugBundleParams>,
+) -> Result<([(&'static str, String); 2], Json<serde_json::Value>), (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    let user_id = match params.user_id.split_once(':') {
+        Some((provider, provider_id)) => match state.store.get_identity(provider, provider_id).await.map_err(internal)? {
+            Some(identity) => identity.user_id,
+            None => params.user_id.clone(),
+        },
+        None => params.user_id.clone(),
+    };
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let bundle = debug_bundle::collect(state.store.as_ref(), state.config, &user_id, limit)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, format!("No user {}", params.user_id)))?;
+    let file = format!(
+        "oneclaw-debug-{}-{}.json",
+        user_id.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_"),
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    Ok((
+        [
+            ("content-type", "application/json".to_string()),
+            ("content-disposition", format!("attachment; filename=\"{}\"", file)),
+        ],
+        Json(bundle),
+    ))
 }
 
-async fn ui_dashboard() -> Html<&'static str> { Html(include_str!("ui/index.html")) }
-async fn ui_chat() -> Html<&'static str> { Html(include_str!("ui/chat.html")) }
-async fn ui_setup() -> Html<&'static str> { Html(include_str!("ui/setup.html")) }
-async fn ui_receipts() -> Html<&'static str> { Html(include_str!("ui/receipts.html")) }
-async fn ui_integrations() -> Html<&'static str> { Html(include_str!("ui/integrations.html")) }
-async fn ui_style() -> ([(&'static str, &'static str); 1], &'static str) {
-    ([("content-type", "text/css")], include_str!("ui/style.css"))
+#[derive(Deserialize)]
+struct DeliveryParams {
+    /// RFC 3339 time or a span back from now (`30m`, `6h`, `7d`)
+    since: Option<String>,
+    status: Option<String>,
+    channel: Option<String>,
+    limit: Option<usize>,
 }
 
-#[derive(Serialize)]
-struct HealthResponse { status: String, node_id: String, node_name: String }
+/// GET /messages - outgoing messages and what became of them, newest first
+/// (`?status=failed` for the ones the provider rejected)
+async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(params): axum::extract::Query<DeliveryParams>,
+) -> Result<Json<Vec<store::Delivery>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let since = match params.since.as_deref() {
+        Some(value) => Some(tagging::parse_since(value).ok_or((StatusCode::BAD_REQUEST, format!("Invalid since: {}", value)))?),
+        None => None,
+    };
+    let query = store::DeliveryQuery {
+        since,
+        status: params.status,
+        channel: params.channel,
+        limit: params.limit.unwrap_or(50).clamp(1, 500),
+    };
+    let deliveries = state.store
+        .deliveries(&query)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(deliveries))
+}
 
-async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "ok".to_string(),
+/// GET /messages/:id - delivery status of one outgoing message
+async fn get_delivery(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<store::Delivery>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    state.store
+        .get_delivery(&id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("No message {}", id)))
+}
+
+/// GET /status - what the node is doing right now (`oneclaw top`)
+async fn get_status(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<StatusResponse>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(StatusResponse {
         node_id: state.config.node.id.clone(),
         node_name: state.config.node.name.clone(),
-    })
+        uptime_secs: state.activity.uptime_secs(),
+        turns: state.activity.turns(),
+        jobs: state.job_monitor.active_jobs().await,
+        channels: state.channels.statuses(&state.health).await,
+        spend: spend::throttle().map(|throttle| throttle.status()),
+        errors: state.activity.errors(),
+    }))
 }
 
-async fn get_config(State(state): State<Arc<AppState>>) -> Json<&'static config::NodeConfig> {
-    Json(state.config)
+/// Signature check for `/harness/*` calls; 404 while the tool server is off
+fn tool_server_caller(
+    state: &AppState,
+    headers: &axum::http::HeaderMap,
+    method: &str,
+    uri: &axum::http::Uri,
+    body: &[u8],
+) -> Result<(), (StatusCode, String)> {
+    if !state.config.tool_server.enabled {
+        return Err((StatusCode::NOT_FOUND, "Tool server is off (tool_server.enabled)".to_string()));
+    }
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let signature = tool_server::Signature {
+        public_key: header("x-oneclaw-public-key"),
+        timestamp: header("x-oneclaw-timestamp"),
+        signature: header("x-oneclaw-signature"),
+    };
+    let own_key = node_key::load_or_generate().ok().map(|k| k.public_key());
+    let path = uri.path_and_query().map_or(uri.path(), |p| p.as_str());
+    tool_server::authorize(&state.config.tool_server, own_key, &signature, method, path, body, chrono::Utc::now().timestamp())
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))
+}
+
+async fn serve_harness_tools(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    uri: axum::http::Uri,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    tool_server_caller(&state, &headers, "GET", &uri, b"")?;
+    let tools = tool_server::catalog(&state.config.tool_server, &state.executor_registry);
+    Ok(Json(tool_server::catalog_json(&tools)))
+}
+
+async fn serve_harness_execute(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    uri: axum::http::Uri,
+    body: axum::body::Bytes,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    tool_server_caller(&state, &headers, "POST", &uri, &body)?;
+    let request: serde_json::Value = serde_json::from_slice(&body).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    tracing::info!("🧰 Tool server: {} for {}", request["workflowId"], headers.get("x-oneclaw-node-id").and_then(|v| v.to_str().ok()).unwrap_or("?"));
+
+    tool_server::execute(&state.config.tool_server, &state.executor_registry, state.config, &request)
+        .await
+        .map(Json)
+        .map_err(|(status, e)| (StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR), e))
+}
+
+/// GET /shadow - what the node would have done while in shadow mode, newest first
+async fn get_shadow_log(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<ShadowQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let actions = shadow::recent(query.limit.unwrap_or(100))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(serde_json::json!({
+        "enabled": shadow::active(),
+        "actions": actions,
+    })))
 }
 
 #[derive(Deserialize)]
-struct RunRequest { workflow_id: String, inputs: serde_json::Value }
+struct RunRequest {
+    workflow_id: String,
+    inputs: serde_json::Value,
+    /// Simulate every step instead of running it (see `dry_run.rs`)
+    #[serde(default)]
+    dry_run: bool,
+}
 
 async fn run_workflow(State(_state): State<Arc<AppState>>, Json(req): Json<RunRequest>) -> Result<Json<receipt::WorkflowReceipt>, (StatusCode, String)> {
-    workflow::run(&req.workflow_id, req.inputs).await
+    workflow::run(&req.workflow_id, req.inputs, req.dry_run).await
         .map(Json)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
+#[derive(Deserialize)]
+struct SimulateRequest { message: String }
+
+/// POST /jobs/simulate - plan a request and dry-run it against mock executors.
+/// Planning goes through the router, so it is charged to the spend throttle.
+async fn simulate_job(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<SimulateRequest>,
+) -> Result<Json<crate::simulation::Simulation>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    simulate_request(&state, &req.message)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+#[derive(Deserialize)]
+struct JobEvent {
+    #[serde(rename = "jobId")]
+    job_id: String,
+}
+
+/// POST /jobs/events - the harness reporting progress on a job
+/// (`jobs.callback_url`): its status is polled right away. Events must carry
+/// the `jobs.callback_token_env` token; the status still comes from the harness.
+async fn job_event(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(event): Json<JobEvent>,
+) -> StatusCode {
+    let authorization = headers.get("authorization").and_then(|v| v.to_str().ok());
+    if !crate::autonomous_jobs_poller::event_authorized(state.config.jobs.callback_token().as_deref(), authorization) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    match crate::autonomous_jobs_poller::wake(&event.job_id) {
+        true => StatusCode::ACCEPTED,
+        false => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Plan a request the way a real job would be planned, then simulate it
+async fn simulate_request(state: &Arc<AppState>, message: &str) -> anyhow::Result<crate::simulation::Simulation> {
+    if message.is_empty() {
+        anyhow::bail!("Nothing to simulate - try `/simulate find HVAC companies in Miami and get me the owners`");
+    }
+    let api_key = std::env::var("ANTHROPIC_API_KEY")
+        .unwrap_or_else(|_| std::env::var("OPENAI_API_KEY").unwrap_or_default());
+    if api_key.is_empty() && state.router.routed().is_none() {
+        anyhow::bail!("No LLM API key configured for planning");
+    }
+
+    let prefs = memory::load_preferences(state.config).unwrap_or_default();
+    let tools = state.harness_tools();
+    let workspace = agent_os::workspace_for(state.config);
+    let (mut plan, _) = crate::plan_cache::plan_or_generate(
+        &state.router,
+        message,
+        territory::prompt_block(&prefs).as_deref(),
+        &tools,
+        workspace.as_deref(),
+    ).await?;
+    territory::apply_to_plan(&mut plan, &prefs)?;
+    crate::autonomous_jobs::validate_plan(&plan, &tools)?;
+    Ok(crate::simulation::simulate(&plan, &tools))
+}
+
+#[derive(Deserialize)]
+struct DeliverabilityRequest {
+    /// Sending address (defaults to `deliverability.sending_domain`)
+    from: Option<String>,
+    to: String,
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    body: String,
+}
+
+/// POST /deliverability/check - SPF/DKIM, recipient MX and spam-phrase checks for a draft
+async fn check_deliverability(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<DeliverabilityRequest>,
+) -> Result<Json<deliverability::Report>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(deliverability::check(&state.config.deliverability, req.from.as_deref(), &req.to, &req.subject, &req.body).await))
+}
+
 async fn list_receipts() -> Result<Json<Vec<String>>, (StatusCode, String)> {
     receipt::list_receipts().map(Json).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
 }
 
-async fn get_preferences() -> Result<Json<memory::Preferences>, (StatusCode, String)> {
-    memory::load_preferences().map(Json).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+async fn get_preferences(State(state): State<Arc<AppState>>) -> Result<Json<memory::Preferences>, (StatusCode, String)> {
+    memory::load_preferences(state.config).map(Json).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// GET /memory/territories - open like GET /memory/preferences, which already serves the same list
+async fn get_territories(State(state): State<Arc<AppState>>) -> Result<Json<Vec<territory::Territory>>, (StatusCode, String)> {
+    memory::load_preferences(state.config)
+        .map(|prefs| Json(prefs.territories))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// PUT /memory/territories - replace the territory list; it scopes outreach, so it takes the admin token
+async fn set_territories(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(territories): Json<Vec<territory::Territory>>,
+) -> Result<Json<Vec<territory::Territory>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    territory::validate(&territories).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut prefs = memory::load_preferences(state.config).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    prefs.territories = territories;
+    memory::save_preferences(state.config, &prefs).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(prefs.territories))
+}
+
+/// GET /executors - every executor with capabilities and whether it can run here
+async fn list_executors(State(state): State<Arc<AppState>>) -> Json<Vec<executor::ExecutorStatus>> {
+    Json(state.executor_registry.describe(state.config))
+}
+
+/// GET /plugins - subprocess executors and their processes
+async fn list_plugins(State(state): State<Arc<AppState>>) -> Json<Vec<plugin::PluginStatus>> {
+    Json(state.executor_registry.plugins().iter().map(|p| p.status()).collect())
+}
+
+/// POST /upgrade/handoff - a new daemon taking over (see `upgrade.rs`)
+async fn upgrade_handoff(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Json<upgrade::Handoff>, (StatusCode, String)> {
+    if !state.config.upgrade.enabled {
+        return Err((StatusCode::NOT_FOUND, "Takeover is off (upgrade.enabled)".to_string()));
+    }
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let signature = tool_server::Signature {
+        public_key: header("x-oneclaw-public-key"),
+        timestamp: header("x-oneclaw-timestamp"),
+        signature: header("x-oneclaw-signature"),
+    };
+    let own_key = node_key::load_or_generate().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    upgrade::authorize(own_key.public_key(), &signature, &body, chrono::Utc::now().timestamp())
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e))?;
+
+    let Some(watches) = state.upgrade.hand_off() else {
+        return Err((StatusCode::CONFLICT, "Already handed off to another daemon".to_string()));
+    };
+    tracing::info!("🔁 Handing off to a new daemon ({} job watch(es))", watches.len());
+    state.channels.stop_listeners().await;
+    Ok(Json(upgrade::Handoff {
+        watches,
+        jobs: state.job_monitor.snapshot().await,
+        continuations: state.continuations.export(),
+    }))
 }
 
-async fn list_executors(State(state): State<Arc<AppState>>) -> Json<Vec<executor::ExecutorManifest>> {
-    Json(state.executor_registry.list())
+/// GET /executors/:id
+async fn get_executor(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<executor::ExecutorStatus>, (StatusCode, String)> {
+    state
+        .executor_registry
+        .describe_one(&id, state.config)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("Unknown executor: {}", id)))
 }
 
 // ============================================
@@ -639,6 +1917,18 @@ struct ChatRequest {
     provider_id: Option<String>,   // e.g., "397102686660591616"
     #[serde(default)]
     username: Option<String>,
+    /// Extra system prompt for this turn (e.g. phone-call instructions)
+    #[serde(default)]
+    context: Option<String>,
+    /// JSON schema for a structured copy of the reply (see `structured.rs`)
+    #[serde(default)]
+    response_format: Option<serde_json::Value>,
+    /// Conversation thread the message belongs to; each thread has its own history
+    #[serde(default)]
+    thread_id: Option<String>,
+    /// Drive the full loop with simulated tool outputs (see `dry_run.rs`)
+    #[serde(default)]
+    dry_run: bool,
 }
 
 fn default_channel() -> String { "http".to_string() }
@@ -649,6 +1939,20 @@ struct ChatResponse {
     tool_calls: Vec<ToolCallResult>,
     milestones: Vec<String>,
     duration_ms: u64,
+    /// Links to generated report artifacts (`/reports/<file>`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reports: Vec<String>,
+    /// Token and history accounting for this turn
+    budget: context_budget::ContextBudget,
+    /// The reply as JSON matching the request's `response_format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    structured: Option<serde_json::Value>,
+    /// Where `structured` still breaks the schema, or why there is none
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    structured_errors: Vec<String>,
+    /// The turn's LLM calls in `GET /audit/llm?turn_id=`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    turn_id: Option<String>,
 }
 
 #[derive(Serialize, Clone)]
@@ -657,6 +1961,8 @@ struct ToolCallResult {
     input: serde_json::Value,
     output: serde_json::Value,
     duration_ms: u64,
+    /// Time spent waiting for the tool's concurrency limit
+    queued_ms: u64,
 }
 
 fn llm_timeout_secs() -> u64 {
@@ -667,23 +1973,172 @@ fn llm_timeout_secs() -> u64 {
         .unwrap_or(65)
 }
 
+/// `llm.chat` for a `model_router` phase, giving up after `LLM_CALL_TIMEOUT_SECS`
 async fn run_llm_with_timeout(
     state: Arc<AppState>,
-    input: serde_json::Value,
+    mut input: serde_json::Value,
     phase: &'static str,
 ) -> Result<executor::ExecutorResult, String> {
     let timeout_secs = llm_timeout_secs();
-    let task = tokio::task::spawn_blocking(move || {
+    input["phase"] = serde_json::json!(phase);
+    let task = async {
         match state.executor_registry.get("llm.chat") {
-            Some(exec) => exec.execute(input, state.config),
+            Some(exec) => exec.execute(input, state.config).await,
             None => executor::ExecutorResult::Error { error: "LLM executor not found".to_string() },
         }
-    });
+    };
+
+    tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), task)
+        .await
+        .map_err(|_| format!("{} timed out after {}s", phase, timeout_secs))
+}
+
+/// Fit `messages` for the main call into the model's context window (see
+/// `context_budget::fit`), recording the measurement in `window`. Anthropic
+/// counts the prompt with its tokenizer once the estimate gets close to the limit.
+async fn fit_context_window(
+    state: &Arc<AppState>,
+    messages: &mut Vec<serde_json::Value>,
+    tools: &[serde_json::Value],
+    window: &mut context_budget::WindowFit,
+) {
+    let routed = state.router.route(model_router::MAIN, &state.config.llm);
+    let context_window = crate::llm::context_window(&routed);
+    let room = context_window.saturating_sub(context_budget::REPLY_RESERVE);
+
+    let estimate = context_budget::estimate_tokens(messages, tools);
+    let mut measured = estimate;
+    let mut estimate_reason = None;
+    if routed.provider != "anthropic" {
+        estimate_reason = Some(format!("{} has no token counting endpoint", routed.provider));
+    } else if (estimate as f64) < room as f64 * context_budget::ESTIMATE_TRUSTED_BELOW {
+        estimate_reason = Some("well within the window, not counted".to_string());
+    } else {
+        match state.router.count_tokens(model_router::MAIN, &state.config.llm, messages, tools).await {
+            Ok(tokens) => measured = tokens,
+            Err(e) => {
+                tracing::warn!("Token count failed, using the estimate: {}", e);
+                estimate_reason = Some(format!("token count failed: {}", e));
+            }
+        }
+    }
+
+    let (tokens, dropped) = context_budget::fit(messages, tools, room, measured);
+    if dropped > 0 {
+        tracing::info!(
+            "Prompt of {} tokens over the {}-token window of {}: dropped {} oldest message(s)",
+            measured, context_window, routed.model, dropped
+        );
+    }
+    window.context_window = context_window;
+    window.dropped_messages += dropped;
+    if tokens >= window.prompt_tokens {
+        (window.system_tokens, window.history_tokens, window.tools_tokens) = context_budget::split_tokens(messages, tools, tokens);
+        window.prompt_tokens = tokens;
+        window.counted = estimate_reason.is_none();
+        window.estimate_reason = estimate_reason;
+    }
+}
+
+/// The main LLM call, streamed: each native tool call starts running as soon as
+/// the model has finished writing it, while the rest of the reply is still being
+/// generated. Returns the `llm.chat`-shaped result and, when the reply had
+/// native tool calls, each call with its result (None when it didn't run).
+async fn run_llm_streaming_tools(
+    state: &Arc<AppState>,
+    user_id: &str,
+    messages: Vec<serde_json::Value>,
+    tools: Vec<serde_json::Value>,
+) -> Result<(executor::ExecutorResult, Option<Vec<StreamedCall>>), String> {
+    let timeout_secs = llm_timeout_secs();
+    let config = state.config;
+    let (calls_tx, mut calls_rx) = tokio::sync::mpsc::unbounded_channel::<crate::llm::ToolCall>();
+    let stream = async move {
+        let start = std::time::Instant::now();
+        let mut on_tool_call = |call: &crate::llm::ToolCall| {
+            let _ = calls_tx.send(call.clone());
+        };
+        state.router
+            .chat_stream(model_router::MAIN, &config.llm, &messages, &tools, &mut |_| {}, &mut on_tool_call)
+            .await
+            .map(|(response, provider)| (response, provider, start.elapsed().as_millis() as u64))
+    };
+
+    let mut running = Vec::new();
+    let receive = async {
+        // Ends when the stream does (the sender is dropped with it)
+        while let Some(call) = calls_rx.recv().await {
+            tracing::info!("Found tool call (streamed): {}", call.name);
+            let state = Arc::clone(state);
+            let task = {
+                let call = call.clone();
+                let user_id = user_id.to_string();
+                let dry_run = dry_run::active();
+                tokio::spawn(dry_run::scope(dry_run, async move { execute_tool(&state, &user_id, &call.name, call.input).await }))
+            };
+            running.push((call, task));
+        }
+    };
+    let streamed = tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), async {
+        tokio::join!(stream, receive).0
+    })
+    .await;
+
+    let (response, provider, duration_ms) = match streamed {
+        Ok(Ok(streamed)) => streamed,
+        Ok(Err(e)) => {
+            state.activity.error("llm", &e.to_string());
+            return Ok((executor::ExecutorResult::Error { error: e.to_string() }, None));
+        }
+        Err(_) => {
+            // Nothing will read their results: cancel the calls still running
+            let unfinished: Vec<_> = running.iter().filter(|(_, task)| !task.is_finished()).collect();
+            if !unfinished.is_empty() {
+                tracing::warn!("LLM stream timed out; cancelling {} unfinished tool call(s)", unfinished.len());
+                for (_, task) in unfinished {
+                    task.abort();
+                }
+            }
+            return Err(format!("{} timed out after {}s", model_router::MAIN, timeout_secs));
+        }
+    };
+
+    let native = !response.tool_calls.is_empty();
+    let mut calls = Vec::new();
+    for (call, task) in running {
+        calls.push((call, task.await.ok().flatten()));
+    }
+    let result = executor::ExecutorResult::Executed {
+        output: executor::LlmExecutor::output(response, &provider),
+        duration_ms,
+    };
+    Ok((result, native.then_some(calls)))
+}
 
-    match tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), task).await {
-        Ok(joined) => joined.map_err(|e| format!("{} join error: {}", phase, e)),
-        Err(_) => Err(format!("{} timed out after {}s", phase, timeout_secs)),
+/// A streamed tool call and its result
+type StreamedCall = (crate::llm::ToolCall, Option<ToolCallResult>);
+
+/// The conversation after one round of native tool calls: the assistant turn
+/// that made them (`tool_use` blocks) and their results (`tool_result` blocks).
+/// Calls without an id (Ollama) get `call_<round>_<n>`.
+fn push_tool_round(conversation: &mut Vec<serde_json::Value>, content: &str, round: u32, calls: &[StreamedCall]) {
+    let mut uses = Vec::new();
+    let mut results = Vec::new();
+    for (i, (call, result)) in calls.iter().enumerate() {
+        let mut call = call.clone();
+        let id = call.id.get_or_insert_with(|| format!("call_{}_{}", round, i)).clone();
+        let (output, failed) = match result {
+            Some(result) => (
+                followup::truncated_json(&result.output),
+                result.output.get("error").is_some() || result.output.get("denied").is_some(),
+            ),
+            None => (format!("{} could not be run", call.name), true),
+        };
+        uses.push(call);
+        results.push((id, output, failed));
     }
+    conversation.push(crate::llm::tool_use_message(content, &uses));
+    conversation.push(crate::llm::tool_results_message(&results));
 }
 
 fn format_tools(tools: &[agent_os::ToolDefinition]) -> String {
@@ -700,92 +2155,207 @@ fn format_tools(tools: &[agent_os::ToolDefinition]) -> String {
 fn extract_content(result: &executor::ExecutorResult) -> String {
     match result {
         executor::ExecutorResult::Executed { output, .. } => {
-            // For Claude, content is usually in output["content"] (from executor's processing)
-            // But we should check raw too for native format
-            if let Some(content_str) = output["content"].as_str() {
-                return content_str.to_string();
-            }
-            
-            // Check raw response for Claude's array format
-            if let Some(raw_content) = output.get("raw").and_then(|r| r.get("content")).and_then(|c| c.as_array()) {
-                let mut text_parts = Vec::new();
-                for block in raw_content {
-                    if block.get("type").and_then(|t| t.as_str()) == Some("text") {
-                        if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
-                            text_parts.push(text);
-                        }
-                    }
-                }
-                if !text_parts.is_empty() {
-                    return text_parts.join("\n");
-                }
-            }
-            
-            String::new()
+            // llm.chat normalizes every provider's reply text into output["content"]
+            output["content"].as_str().unwrap_or_default().to_string()
         }
         executor::ExecutorResult::Error { error } => format!("Error: {}", error),
         executor::ExecutorResult::Denied { denial_reason } => format!("Denied: {}", denial_reason.policy),
     }
 }
 
+/// Run a tool the model called in `user_id`'s conversation
 async fn execute_tool(
     state: &Arc<AppState>,
+    user_id: &str,
     tool_name: &str,
     tool_input: serde_json::Value,
 ) -> Option<ToolCallResult> {
-    execute_tool_internal(state, tool_name, tool_input, None).await
+    execute_tool_internal(state, user_id, tool_name, tool_input, None).await
 }
 
 async fn execute_tool_with_progress(
     state: &Arc<AppState>,
+    user_id: &str,
     tool_name: &str,
     tool_input: serde_json::Value,
     telegram_sender: &crate::channels::telegram::TelegramChannel,
     chat_id: &str,
 ) -> Option<ToolCallResult> {
-    execute_tool_internal(state, tool_name, tool_input, Some((telegram_sender, chat_id))).await
+    execute_tool_internal(state, user_id, tool_name, tool_input, Some((telegram_sender, chat_id))).await
 }
 
 async fn execute_tool_internal(
     state: &Arc<AppState>,
+    user_id: &str,
     tool_name: &str,
     tool_input: serde_json::Value,
     progress_callback: Option<(&crate::channels::telegram::TelegramChannel, &str)>,
 ) -> Option<ToolCallResult> {
-    let state = Arc::clone(state);
-    let tool_name_owned = tool_name.to_string();
     let tool_input_for_result = tool_input.clone();
 
+    // The model names the job; only the caller's own jobs can be controlled,
+    // and the executor is told who the caller is
+    let mut tool_input = tool_input;
+    if tool_name == "job.control" {
+        let job_id = tool_input["job_id"].as_str().unwrap_or_default();
+        let owned = state.job_monitor.jobs_for_user(user_id).await.iter().any(|job| job.job_id == job_id);
+        if !owned {
+            tracing::warn!("job.control for job {} refused: not a job of user {}", job_id, user_id);
+            return Some(ToolCallResult {
+                tool: tool_name.to_string(),
+                input: tool_input_for_result,
+                output: serde_json::json!({ "error": format!("No job {} among this user's jobs (see Active Jobs)", job_id) }),
+                duration_ms: 0,
+                queued_ms: 0,
+            });
+        }
+        tool_input["user_id"] = serde_json::Value::String(user_id.to_string());
+    }
+
+    // Expand "territory": "<name>" into concrete locations
+    if tool_input.get("territory").is_some() {
+        let prefs = memory::load_preferences(state.config).unwrap_or_default();
+        if let Err(e) = territory::resolve_params(&mut tool_input, &prefs.territories) {
+            return Some(ToolCallResult {
+                tool: tool_name.to_string(),
+                input: tool_input_for_result,
+                output: serde_json::json!({ "error": e.to_string() }),
+                duration_ms: 0,
+                queued_ms: 0,
+            });
+        }
+    }
+
+    // Arguments that don't fit the tool's schema go back to the model to correct,
+    // instead of to the harness for an opaque 400
+    if let Some(errors) = input_errors(state, tool_name, &mut tool_input) {
+        tracing::info!("Tool {} called with invalid input: {}", tool_name, errors.join("; "));
+        return Some(ToolCallResult {
+            tool: tool_name.to_string(),
+            input: tool_input_for_result,
+            output: serde_json::json!({
+                "error": format!("Invalid input for {}; correct it and call the tool again", tool_name),
+                "validation_errors": errors,
+            }),
+            duration_ms: 0,
+            queued_ms: 0,
+        });
+    }
+
+    if let Some(denial_reason) = policy_denial(state, tool_name, &tool_input) {
+        tracing::warn!("Tool {} denied: {}", tool_name, denial_reason.policy);
+        return Some(ToolCallResult {
+            tool: tool_name.to_string(),
+            input: tool_input_for_result,
+            output: serde_json::json!({ "denied": denial_reason.policy }),
+            duration_ms: 0,
+            queued_ms: 0,
+        });
+    }
+
+    // Nothing runs in a dry run: no spend, no slot, no telemetry
+    if dry_run::active() {
+        let output_schema = if state.harness_tools().iter().any(|t| t.id == tool_name) {
+            None
+        } else {
+            state.executor_registry.get(tool_name)?.manifest().capabilities.output_schema
+        };
+        tracing::info!("Tool {} simulated (dry run)", tool_name);
+        return Some(ToolCallResult {
+            tool: tool_name.to_string(),
+            input: tool_input_for_result,
+            output: dry_run::simulated(state.config, tool_name, output_schema.as_ref()),
+            duration_ms: 0,
+            queued_ms: 0,
+        });
+    }
+
+    // Paid tools wait while the node's spend bucket is empty
+    let cost = state
+        .harness_tools()
+        .iter()
+        .find(|t| t.id == tool_name)
+        .and_then(|t| t.cost_estimate)
+        .filter(|cost| *cost > 0.0);
+    if let (Some(_), Some(throttle)) = (cost, spend::throttle()) {
+        if let Err(e) = throttle.wait().await {
+            tracing::warn!("Tool {} not run: {}", tool_name, e);
+            return Some(ToolCallResult {
+                tool: tool_name.to_string(),
+                input: tool_input_for_result,
+                output: serde_json::json!({ "error": e.to_string() }),
+                duration_ms: 0,
+                queued_ms: 0,
+            });
+        }
+    }
+
+    // Held until the call returns, so limited tools run at most N at a time node-wide
+    let slot = state.tool_limits.acquire(tool_name).await;
+
     // Check if this is a harness tool (if it's in harness_tools list)
-    let is_harness_tool = state.harness_tools.iter().any(|t| t.id == tool_name);
+    let is_harness_tool = state.harness_tools().iter().any(|t| t.id == tool_name);
     
-    let result = if is_harness_tool {
+    let (executor, input) = if is_harness_tool {
         // Execute via harness - use "executor" key (harness expects this)
         let harness_input = serde_json::json!({
             "executor": tool_name,
             "params": tool_input
         });
-        tokio::task::spawn_blocking(move || {
-            state
-                .executor_registry
-                .get("harness.execute")
-                .map(|exec| exec.execute(harness_input, state.config))
-        })
-        .await
-        .ok()
-        .flatten()?
+        (state.executor_registry.get("harness.execute")?, harness_input)
     } else {
         // Execute via direct executor
-        tokio::task::spawn_blocking(move || {
-            state
-                .executor_registry
-                .get(&tool_name_owned)
-                .map(|exec| exec.execute(tool_input, state.config))
-        })
-        .await
-        .ok()
-        .flatten()?
+        (state.executor_registry.get(tool_name)?, tool_input)
+    };
+
+    // A hung call is cancelled (its future dropped) so the turn can go on with
+    // the results of the other tools
+    let limit = executor::timeout(&state.config.executors, tool_name, &executor.manifest());
+    let started = std::time::Instant::now();
+    let (result, timed_out) = match tokio::time::timeout(limit, executor.execute(input, state.config)).await {
+        Ok(result) => (result, false),
+        Err(_) => {
+            let error = format!("{} timed out after {}s and was cancelled", tool_name, limit.as_secs());
+            (executor::ExecutorResult::Error { error }, true)
+        }
+    };
+
+    // How the call went, for the measured notes in SKILLS.md
+    let call = match &result {
+        executor::ExecutorResult::Executed { output, duration_ms } => {
+            Some(tool_insights::call_record(tool_name, output["error"].as_str(), *duration_ms))
+        }
+        executor::ExecutorResult::Error { error } => {
+            Some(tool_insights::call_record(tool_name, Some(error), started.elapsed().as_millis() as u64))
+        }
+        executor::ExecutorResult::Denied { .. } => None,
     };
+    if let Some(call) = call {
+        tool_insights::record(state.store.as_ref(), call).await;
+    }
+
+    // Keep the job monitor in sync so the next turn sees the new state
+    if tool_name == "job.control" {
+        if let executor::ExecutorResult::Executed { output, .. } = &result {
+            if let (Some(job_id), Some(status)) = (output["job_id"].as_str(), output["status"].as_str()) {
+                state.job_monitor.set_status(job_id, status).await;
+            }
+        }
+    }
+
+    if let executor::ExecutorResult::Executed { output, .. } = &result {
+        // Paid tools, and any that report what they cost, are charged and recorded
+        if cost.is_some() || usage::reported_cost(output).is_some() {
+            let record = usage::tool(tool_name, cost, output);
+            if let Some(throttle) = spend::throttle() {
+                throttle.charge(record.cost_usd);
+            }
+            usage::record(state.store.as_ref(), record).await;
+        }
+    }
+
+    let queued_ms = slot.queued_ms;
+    drop(slot);
 
     match result {
         executor::ExecutorResult::Executed { output, duration_ms } => Some(ToolCallResult {
@@ -793,14 +2363,21 @@ async fn execute_tool_internal(
             input: tool_input_for_result,
             output,
             duration_ms,
+            queued_ms,
         }),
         executor::ExecutorResult::Error { error } => {
             tracing::warn!("Tool error: {}", error);
+            state.activity.error(tool_name, &error);
+            let mut output = serde_json::json!({ "error": error });
+            if timed_out {
+                output["timed_out"] = serde_json::json!(true);
+            }
             Some(ToolCallResult {
                 tool: tool_name.to_string(),
                 input: tool_input_for_result,
-                output: serde_json::json!({ "error": error }),
-                duration_ms: 0,
+                output,
+                duration_ms: if timed_out { limit.as_millis() as u64 } else { 0 },
+                queued_ms,
             })
         }
         executor::ExecutorResult::Denied { denial_reason } => Some(ToolCallResult {
@@ -808,12 +2385,40 @@ async fn execute_tool_internal(
             input: tool_input_for_result,
             output: serde_json::json!({ "denied": denial_reason.policy }),
             duration_ms: 0,
+            queued_ms,
         }),
     }
 }
 
+/// A `security.policies` time window the call falls outside of
+fn policy_denial(state: &AppState, tool_name: &str, input: &serde_json::Value) -> Option<executor::DenialReason> {
+    let policies = &state.config.security.policies;
+    if policies.is_empty() {
+        return None;
+    }
+    let paid = match state.harness_tools().iter().find(|t| t.id == tool_name) {
+        Some(tool) => tool.cost_estimate.is_some_and(|cost| cost > 0.0),
+        None => state
+            .executor_registry
+            .get(tool_name)
+            .is_some_and(|e| e.manifest().capabilities.cost_model.is_paid()),
+    };
+    let timezone = memory::load_preferences(state.config).ok().and_then(|p| p.timezone);
+    policy::check(policies, &policy::ToolMeta { id: tool_name, paid }, input, timezone.as_deref(), chrono::Utc::now())
+}
+
+/// Where `input` breaks the harness tool's `params_schema`, after repairing
+/// near misses in place (numbers sent as strings and the like; see `structured.rs`)
+fn input_errors(state: &AppState, tool_name: &str, input: &mut serde_json::Value) -> Option<Vec<String>> {
+    let schema = state.harness_tools().into_iter().find(|t| t.id == tool_name)?.params_schema?;
+    structured::repair(&schema, input);
+    let errors = structured::validate(&schema, input);
+    (!errors.is_empty()).then_some(errors)
+}
+
 async fn find_and_execute_tools(
     state: &Arc<AppState>,
+    user_id: &str,
     content: &str,
     llm_result: &executor::ExecutorResult,
 ) -> Vec<ToolCallResult> {
@@ -821,26 +2426,19 @@ async fn find_and_execute_tools(
 
     tracing::debug!("Parsing content for tool calls (length: {})", content.len());
 
-    // Strategy 0: Claude's native tool_use format (in raw response)
+    // Strategy 0: native tool calls, normalized across providers by llm.chat
     if let executor::ExecutorResult::Executed { output, .. } = llm_result {
-        if let Some(raw_content) = output.get("raw").and_then(|r| r.get("content")).and_then(|c| c.as_array()) {
-            for block in raw_content {
-                if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
-                    if let (Some(tool_name), Some(tool_input)) = (
-                        block.get("name").and_then(|n| n.as_str()),
-                        block.get("input")
-                    ) {
-                        tracing::info!("Found tool call (Claude native format): {}", tool_name);
-                        if let Some(result) = execute_tool(state, tool_name, tool_input.clone()).await {
-                            results.push(result);
-                        }
-                    }
+        for call in output["tool_calls"].as_array().into_iter().flatten() {
+            if let Some(tool_name) = call["name"].as_str() {
+                tracing::info!("Found tool call (native format): {}", tool_name);
+                if let Some(result) = execute_tool(state, user_id, tool_name, call["input"].clone()).await {
+                    results.push(result);
                 }
             }
         }
     }
 
-    // If Claude native format found tools, return early
+    // If the provider returned native tool calls, return early
     if !results.is_empty() {
         return results;
     }
@@ -859,7 +2457,7 @@ async fn find_and_execute_tools(
             None => continue,
         };
         tracing::info!("Found tool call (standard format)");
-        if let Some(result) = parse_and_execute_tool(state, tool_json).await {
+        if let Some(result) = parse_and_execute_tool(state, user_id, tool_json).await {
             results.push(result);
         }
     }
@@ -873,7 +2471,7 @@ async fn find_and_execute_tools(
     for cap in minimax_regex.captures_iter(content) {
         if let Some(tool_json) = cap.get(1) {
             tracing::info!("Found tool call (minimax format)");
-            if let Some(result) = parse_and_execute_tool(state, tool_json.as_str()).await {
+            if let Some(result) = parse_and_execute_tool(state, user_id, tool_json.as_str()).await {
                 results.push(result);
             }
         }
@@ -889,7 +2487,7 @@ async fn find_and_execute_tools(
     for cap in bracket_regex.captures_iter(content) {
         if let Some(tool_json) = cap.get(1) {
             tracing::info!("Found tool call (bracket format), length: {}", tool_json.as_str().len());
-            if let Some(result) = parse_and_execute_tool(state, tool_json.as_str()).await {
+            if let Some(result) = parse_and_execute_tool(state, user_id, tool_json.as_str()).await {
                 results.push(result);
             }
         }
@@ -904,6 +2502,7 @@ async fn find_and_execute_tools(
 
 async fn parse_and_execute_tool(
     state: &Arc<AppState>,
+    user_id: &str,
     tool_json: &str,
 ) -> Option<ToolCallResult> {
     // Clean up common LLM format issues
@@ -926,95 +2525,275 @@ async fn parse_and_execute_tool(
     let tool_input = tool_call["input"].clone();
     
     tracing::info!("Executing tool: {}", tool_name);
-    execute_tool(state, tool_name, tool_input).await
+    execute_tool(state, user_id, tool_name, tool_input).await
 }
 
+/// Final reply after tools ran, following the channel's followup policy
 async fn get_followup_response(
     state: &Arc<AppState>,
+    channel: &str,
     messages: &[serde_json::Value],
+    content: &str,
     tool_results: &[ToolCallResult],
+    budget: Option<&mut context_budget::ContextBudget>,
 ) -> String {
-    // Check if any tool result has a formattedResponse - if so, use it directly
-    for result in tool_results {
-        if let Some(output) = result.output.get("output") {
-            if let Some(formatted) = output.get("formattedResponse") {
-                if let Some(formatted_str) = formatted.as_str() {
-                    if !formatted_str.is_empty() {
-                        tracing::info!("Using pre-formatted response from tool: {}", result.tool);
-                        return formatted_str.to_string();
-                    }
-                }
-            }
+    let policy = state.config.channels.followup_for(channel);
+    let results: Vec<(&str, &serde_json::Value)> = tool_results
+        .iter()
+        .map(|r| (r.tool.as_str(), &r.output))
+        .collect();
+
+    if policy.mode == config::FollowupMode::Skip {
+        if content.trim().is_empty() {
+            return followup::render_template(policy, &results);
         }
+        return content.to_string();
     }
-    
-    // No formatted response - ask Claude to summarize
-    let mut new_messages = messages.to_vec();
-    for result in tool_results {
-        let result_msg = format!(
-            "[Tool Result: {}]\n{}",
-            result.tool,
-            serde_json::to_string_pretty(&result.output).unwrap_or_default()
-        );
-        new_messages.push(serde_json::json!({
-            "role": "system",
-            "content": result_msg
-        }));
+
+    let outputs: Vec<&serde_json::Value> = results.iter().map(|(_, output)| *output).collect();
+    if let Some(formatted) = followup::pre_formatted(&outputs) {
+        tracing::info!("Using pre-formatted response from tool");
+        return localize::for_display(state.config, &formatted);
     }
-    new_messages.push(serde_json::json!({
-        "role": "user",
-        "content": "Summarize these results for the user in plain language. No tool blocks."
-    }));
-    let input = serde_json::json!({ "messages": new_messages });
-    match run_llm_with_timeout(Arc::clone(state), input, "followup").await {
+
+    if policy.mode == config::FollowupMode::Template {
+        return followup::render_template(policy, &results);
+    }
+
+    let mut prompt = followup::prompt_for(policy, channel).to_string();
+    if let Some(language) = localize::preferred_language(state.config) {
+        prompt.push(' ');
+        prompt.push_str(&localize::prompt_instruction(&language));
+    }
+    let input = serde_json::json!({ "messages": followup::llm_messages(messages, &results, &prompt) });
+    let result = run_llm_with_timeout(Arc::clone(state), input, model_router::FOLLOWUP).await;
+    if let (Some(budget), Ok(result)) = (budget, &result) {
+        budget.record_llm(result);
+    }
+    match result {
         Ok(executor::ExecutorResult::Executed { output, .. }) => {
             output["content"].as_str().unwrap_or("").to_string()
         }
-        _ => "Tool executed but could not generate summary.".to_string(),
+        other => {
+            tracing::warn!("Followup summary failed, rendering results locally: {:?}", other);
+            followup::render_template(policy, &results)
+        }
     }
 }
 
+/// POST /chat - a chat turn from an `http` sender, or from the identity the
+/// body names with the admin token
 async fn chat(
     State(state): State<Arc<AppState>>,
-    Json(req): Json<ChatRequest>,
+    headers: axum::http::HeaderMap,
+    Json(mut req): Json<ChatRequest>,
 ) -> Result<Json<ChatResponse>, (StatusCode, String)> {
+    let authorization = headers.get("authorization").and_then(|v| v.to_str().ok());
+    let (provider, provider_id) =
+        auth::chat_sender(&state.config.security, authorization, req.provider.as_deref(), req.provider_id.as_deref())
+            .map_err(|e| (StatusCode::FORBIDDEN, e))?;
+    (req.provider, req.provider_id) = (Some(provider.to_string()), Some(provider_id.to_string()));
+    chat_from(state, req).await
+}
+
+/// A chat turn from the sender `req` names; channel handlers vouch for it
+async fn chat_from(state: Arc<AppState>, req: ChatRequest) -> Result<Json<ChatResponse>, (StatusCode, String)> {
     let start = std::time::Instant::now();
     let milestones = vec!["Received your message".to_string()];
 
     let msg_preview = req.message.chars().take(60).collect::<String>();
     tracing::info!("Chat: \"{}\"", msg_preview);
 
+    if req.message.trim() == capabilities::COMMAND {
+        let described = describe_capabilities(&state).await;
+        return Ok(Json(ChatResponse {
+            response: described.statement,
+            tool_calls: vec![],
+            milestones,
+            duration_ms: start.elapsed().as_millis() as u64,
+            reports: vec![],
+            budget: context_budget::ContextBudget::default(),
+            structured: None,
+            structured_errors: vec![],
+            turn_id: None,
+        }));
+    }
+
     // Resolve user identity
     let provider = req.provider.as_deref().unwrap_or("http");
     let provider_id = req.provider_id.as_deref().unwrap_or("anonymous");
 
-    let (user_id, _) = state
-        .identity_manager
-        .resolve(provider, provider_id, req.username.as_deref())
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let user_id = match admit_sender(&state, provider, provider_id, req.username.as_deref(), &req.message).await {
+        Ok(user_id) => user_id,
+        Err(Some(reply)) => return Err((StatusCode::FORBIDDEN, reply)),
+        Err(None) => return Err((StatusCode::INTERNAL_SERVER_ERROR, "Identity resolution failed".to_string())),
+    };
+
+    let channel = req.channel.clone();
+    let turn_id = nanoid::nanoid!(12);
+    let correlation = llm_audit::Correlation { turn_id: Some(turn_id.clone()), job_id: None };
+    let turn = dry_run::scope(req.dry_run, chat_turn(Arc::clone(&state), req, &user_id, start, milestones));
+    let reply = llm_audit::scope(correlation, turn).await;
+    if let Err((_, e)) = &reply {
+        state.activity.error(&channel, e);
+    }
+    reply.map(|reply| {
+        let receipt = receipt::TurnReceipt {
+            turn_id: turn_id.clone(),
+            user_id,
+            channel,
+            completed_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: reply.duration_ms,
+            budget: reply.budget.clone(),
+        };
+        if let Err(e) = receipt::write_turn_receipt(&receipt) {
+            tracing::warn!("Could not write the receipt of turn {}: {}", turn_id, e);
+        }
+        Json(ChatResponse { turn_id: Some(turn_id), ..reply })
+    })
+}
+
+/// Count a finished turn for its canary arm: it succeeded if it got a reply and
+/// none of its tools failed
+fn record_canary_turn(state: &AppState, user_id: &str, arm: canary::Arm, reply: &str, tool_results: &[ToolCallResult]) {
+    let success = !reply.trim().is_empty() && tool_results.iter().all(|r| r.output.get("error").is_none());
+    let Some(decision) = state.canary.record(user_id, arm, success) else { return };
+    let (control, candidate) = (&decision.control, &decision.candidate);
+    match decision.verdict {
+        canary::Verdict::Adopted => {
+            tracing::info!(
+                "🐤 Brain update adopted: {}/{} turns succeeded with it, {}/{} without",
+                candidate.successes, candidate.turns, control.successes, control.turns
+            );
+            crate::sync::push_in_background(state.config);
+        }
+        canary::Verdict::Reverted => tracing::warn!(
+            "🐤 Brain update reverted: {}/{} turns succeeded with it, {}/{} without",
+            candidate.successes, candidate.turns, control.successes, control.turns
+        ),
+    }
+}
+
+/// The MEMORY.md entries for a turn about `message`, by embedding when
+/// `embeddings.memory_search` is on (word overlap if that fails)
+async fn semantic_memory(state: &AppState, brain: &agent_os::AgentOS, message: &str) -> String {
+    let budget = state.config.memory.budgets.semantic_chars;
+    let embeddings = &state.config.embeddings;
+    if embeddings.memory_search && embeddings::ready(embeddings) {
+        match memory::semantic::recall_by_embedding(&brain.memory, message, budget, embeddings, state.store.as_ref()).await {
+            Ok(semantic) => return semantic,
+            Err(e) => tracing::warn!("Memory search fell back to word overlap: {}", e),
+        }
+    }
+    memory::semantic::recall(&brain.memory, message, budget)
+}
+
+/// `brain`'s system prompt with the semantic memory relevant to `message` and
+/// the user's past-session summaries, each within its `memory.budgets` share
+async fn system_prompt_with_memory(
+    state: &AppState,
+    brain: &agent_os::AgentOS,
+    user_id: &str,
+    message: &str,
+) -> (String, memory::TierUsage) {
+    let semantic = semantic_memory(state, brain, message).await;
+    let mut system_prompt = brain.build_system_prompt(&state.harness_tools(), &semantic);
+    let episodes = state.conversation_manager.episodes_prompt(user_id).await.unwrap_or_else(|e| {
+        tracing::warn!("Could not load past sessions for {}: {}", user_id, e);
+        None
+    });
+    let usage = memory::TierUsage {
+        working_chars: 0,
+        episodic_chars: episodes.as_ref().map_or(0, |e| e.chars().count()),
+        semantic_chars: semantic.chars().count(),
+    };
+    if let Some(episodes) = episodes {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&episodes);
+    }
+    (system_prompt, usage)
+}
+
+/// When this turn's message started a new session, summarize the sessions
+/// before it into episodes in the background
+fn close_sessions_if_new(state: &Arc<AppState>, user_id: &str, history: &context_budget::HistoryWindow) {
+    if history.included != 1 || !history.truncated {
+        return;
+    }
+    let store = Arc::clone(&state.store);
+    let executor_registry = Arc::clone(&state.executor_registry);
+    let config = state.config;
+    let user_id = user_id.to_string();
+    tokio::spawn(async move {
+        match memory::episodic::close_sessions(store, executor_registry, config, &user_id).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("📔 Summarized {} past session(s) for {}", n, user_id),
+            Err(e) => tracing::warn!("Could not summarize past sessions for {}: {}", user_id, e),
+        }
+    });
+}
+
+/// One chat turn for a resolved user: their history, active jobs and tools
+async fn chat_turn(
+    state: Arc<AppState>,
+    req: ChatRequest,
+    user_id: &str,
+    start: std::time::Instant,
+    milestones: Vec<String>,
+) -> Result<ChatResponse, (StatusCode, String)> {
+    let user_id = user_id.to_string();
+    let _turn = state.activity.begin(&user_id, &req.channel, &req.message);
 
     // Store user message
     let _ = state
         .conversation_manager
-        .add_user_message(&user_id, &req.message, &req.channel)
+        .add_user_message(&user_id, &req.message, &req.channel, req.thread_id.as_deref())
         .await;
 
-    // Build system prompt using FULL Agent OS (SOUL, IDENTITY, SKILLS, PLAYBOOKS, MEMORY + tools)
-    let system_prompt = state.agent_os.build_system_prompt(&state.harness_tools);
+    // Build system prompt using FULL Agent OS (SOUL, IDENTITY, SKILLS, PLAYBOOKS, MEMORY + tools);
+    // while a brain update is on trial, some turns still get the pillars from before it
+    state.canary.feedback(&user_id, &req.message);
+    let (brain, arm) = state.canary.brain_for_turn(&state.agent_os);
+    let (mut system_prompt, mut memory_usage) = system_prompt_with_memory(&state, &brain, &user_id, &req.message).await;
+    if let Some(style_block) = response_style::ResponseStyle::for_channel(&state.config.channels, &req.channel).prompt_block(&req.channel) {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&style_block);
+    }
+    if let Some(territories_block) = territory::prompt_block(&memory::load_preferences(state.config).unwrap_or_default()) {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&territories_block);
+    }
+    let jobs_block = state.job_monitor.active_jobs_prompt(&user_id).await;
+    let has_jobs = jobs_block.is_some();
+    if let Some(jobs_block) = jobs_block {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(&jobs_block);
+    }
+    if let Some(context) = &req.context {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(context);
+    }
 
     // Build messages
-    let messages = state
+    let (mut messages, history) = state
         .conversation_manager
-        .build_llm_messages(&user_id, &system_prompt)
+        .build_llm_messages_with_window(&user_id, req.thread_id.as_deref(), &system_prompt)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    close_sessions_if_new(&state, &user_id, &history);
+    memory_usage.working_chars = messages[1..].iter().map(|m| m["content"].as_str().unwrap_or_default().chars().count()).sum();
+    let mut budget = context_budget::ContextBudget {
+        system_prompt_chars: system_prompt.chars().count(),
+        history,
+        memory: memory_usage,
+        ..Default::default()
+    };
 
     // Call LLM
     tracing::info!("Calling LLM...");
     
     // Convert harness tools to Claude format
-    let claude_tools: Vec<serde_json::Value> = state.harness_tools
+    let mut claude_tools: Vec<serde_json::Value> = state.harness_tools()
         .iter()
         .map(|tool| {
             serde_json::json!({
@@ -1024,23 +2803,70 @@ async fn chat(
             })
         })
         .collect();
+    if has_jobs {
+        claude_tools.push(executor::JobControlExecutor::tool_schema());
+    }
+    claude_tools.push(executor::OutreachExecutor::tool_schema(outreach::DraftKind::CallScript));
+    claude_tools.push(executor::OutreachExecutor::tool_schema(outreach::DraftKind::Voicemail));
+    claude_tools.push(executor::ScratchpadExecutor::tool_schema(scratchpad::Access::Read));
+    claude_tools.push(executor::ScratchpadExecutor::tool_schema(scratchpad::Access::Write));
+    claude_tools.push(executor::EmailParseExecutor::tool_schema());
+    if state.config.control_plane.url.is_some() {
+        claude_tools.push(executor::GoogleContactsExecutor::tool_schema());
+        claude_tools.push(executor::CalendarFollowupExecutor::tool_schema());
+    }
+    if executor::SecretRequirement::WebSearchBackend.is_satisfied(state.config) {
+        claude_tools.push(executor::WebSearchExecutor::tool_schema());
+    }
+    claude_tools.extend(state.executor_registry.plugin_tools(state.config));
     
     tracing::info!("Sending {} tools to Claude", claude_tools.len());
     tracing::debug!("Tools: {}", serde_json::to_string_pretty(&claude_tools).unwrap_or_default());
+    budget.tools_count = claude_tools.len();
+    budget.tools_bytes = serde_json::to_vec(&claude_tools).map(|b| b.len()).unwrap_or_default();
+    fit_context_window(&state, &mut messages, &claude_tools, &mut budget.window).await;
     
-    let input = serde_json::json!({ 
-        "messages": messages,
-        "tools": claude_tools
-    });
-    let result = run_llm_with_timeout(Arc::clone(&state), input, "main")
-        .await
-        .map_err(|e| (StatusCode::GATEWAY_TIMEOUT, e))?;
+    // Agent loop: native tool calls go back to the model as tool_result blocks
+    // until it answers without tools or `agent_loop.max_iterations` is used up;
+    // then the followup writes the reply from everything gathered
+    let policy = state.config.channels.followup_for(&req.channel);
+    let mut conversation = messages.clone();
+    let mut tool_results: Vec<ToolCallResult> = Vec::new();
+    let mut answered = false;
+    let content = loop {
+        if budget.tool_rounds > 0 {
+            fit_context_window(&state, &mut conversation, &claude_tools, &mut budget.window).await;
+        }
+        let (result, streamed_tools) = run_llm_streaming_tools(&state, &user_id, conversation.clone(), claude_tools.clone())
+            .await
+            .map_err(|e| (StatusCode::GATEWAY_TIMEOUT, e))?;
+        budget.record_llm(&result);
+        let content = extract_content(&result);
+
+        let Some(calls) = streamed_tools else {
+            // Text-format tool calls (models without native tools) get one round
+            let found = find_and_execute_tools(&state, &user_id, &content, &result).await;
+            answered = found.is_empty() && !content.trim().is_empty();
+            tool_results.extend(found);
+            break content;
+        };
 
-    let content = extract_content(&result);
-    let tool_results = find_and_execute_tools(&state, &content, &result).await;
+        budget.tool_rounds += 1;
+        push_tool_round(&mut conversation, &content, budget.tool_rounds, &calls);
+        let round: Vec<ToolCallResult> = calls.into_iter().filter_map(|(_, result)| result).collect();
+        let outputs: Vec<&serde_json::Value> = round.iter().map(|r| &r.output).collect();
+        let done = budget.tool_rounds >= state.config.agent_loop.max_iterations
+            || policy.mode != config::FollowupMode::Llm
+            || followup::pre_formatted(&outputs).is_some();
+        tool_results.extend(round);
+        if done {
+            break content;
+        }
+        tracing::info!("Tool round {} done, asking the model again", budget.tool_rounds);
+    };
 
     // Get final response
-    let final_content = if tool_results.is_empty() {
+    let final_content = if tool_results.is_empty() || answered {
         content
     } else {
         for result in &tool_results {
@@ -1050,30 +2876,63 @@ async fn chat(
                     &user_id,
                     &format!("[{} result]", result.tool),
                     &req.channel,
+                    req.thread_id.as_deref(),
                 )
                 .await;
         }
-        get_followup_response(&state, &messages, &tool_results).await
+        get_followup_response(&state, &req.channel, &messages, &content, &tool_results, Some(&mut budget)).await
     };
 
+    // Simulated outputs say nothing about how the brain is doing
+    let learns = !req.dry_run;
+    if let (Some(arm), true) = (arm, learns) {
+        record_canary_turn(&state, &user_id, arm, &final_content, &tool_results);
+    }
     let final_content = if final_content.trim().is_empty() {
         "I didn't get a response. Try again?".to_string()
     } else {
         final_content
     };
 
+    // Kept with the reply for the history and debug bundles
+    let calls: Vec<conversation::ToolCall> = tool_results
+        .iter()
+        .map(|r| conversation::ToolCall {
+            tool: r.tool.clone(),
+            input: r.input.clone(),
+            output: Some(conversation::stored_output(&r.output)),
+            success: r.output.get("error").is_none(),
+            duration_ms: r.duration_ms,
+        })
+        .collect();
     let _ = state
         .conversation_manager
-        .add_assistant_message(&user_id, &final_content, &req.channel, None)
+        .add_assistant_message(
+            &user_id,
+            &final_content,
+            &req.channel,
+            (!calls.is_empty()).then_some(calls.as_slice()),
+            req.thread_id.as_deref(),
+        )
         .await;
 
+    let structured = match &req.response_format {
+        Some(schema) => structured_reply(&state, &messages, &tool_results, &final_content, schema).await,
+        None => structured::Structured::default(),
+    };
+    budget.llm_calls += structured.llm_calls;
+    usage::record(state.store.as_ref(), usage::chat(&req.channel, &user_id, &budget)).await;
+
     // Learning phase: reflect on the interaction
-    if !tool_results.is_empty() {
-        let mut agent_os_clone = state.agent_os.clone();
+    if learns && !tool_results.is_empty() {
+        let canary = Arc::clone(&state.canary);
+        let before = canary.current(&state.agent_os);
+        let mut agent_os_clone = before.clone();
         let executor_registry = Arc::clone(&state.executor_registry);
         let config = state.config;
         let goal = req.message.clone();
         let tool_results_clone = tool_results.clone();
+        let batches = batch::batches_reflection(config).then(|| Arc::clone(&state.batches));
         
         // Spawn learning as background task (don't block response)
         tokio::spawn(async move {
@@ -1088,6 +2947,14 @@ async fn chat(
                 })
                 .collect();
             
+            // Nobody waits on reflection: send it with the next batch
+            if let Some(batches) = batches {
+                let messages = crate::learning::reflection_messages(&goal, &steps, true);
+                let request = crate::llm::ChatRequest::new(&config.llm.model, messages, vec![]);
+                batches.push(batch::REFLECTION, crate::llm::anthropic_body(&request, false), serde_json::Value::Null);
+                return;
+            }
+            
             match crate::learning::reflect_and_evolve(
                 &goal,
                 &steps,
@@ -1098,7 +2965,14 @@ async fn chat(
             )
             .await
             {
-                Ok(true) => tracing::info!("🧠 Brain evolved after interaction"),
+                Ok(true) if canary.enabled() => canary.propose(before, agent_os_clone),
+                Ok(true) => match agent_os_clone.save() {
+                    Ok(()) => {
+                        tracing::info!("🧠 Brain evolved after interaction");
+                        crate::sync::push_in_background(config);
+                    }
+                    Err(e) => tracing::warn!("Could not save learning updates: {}", e),
+                },
                 Ok(false) => tracing::debug!("No learning updates needed"),
                 Err(e) => tracing::warn!("Learning reflection failed: {}", e),
             }
@@ -1106,14 +2980,342 @@ async fn chat(
     }
 
     let duration_ms = start.elapsed().as_millis() as u64;
-    tracing::info!("Chat done in {}ms ({} tools)", duration_ms, tool_results.len());
+    tracing::info!(
+        "Chat done in {}ms ({} tools, {} prompt / {} completion tokens, {} from cache)",
+        duration_ms,
+        tool_results.len(),
+        budget.prompt_tokens,
+        budget.completion_tokens,
+        budget.cache_read_tokens
+    );
+    if let Some(reason) = &budget.window.estimate_reason {
+        tracing::info!("Window fit of {} prompt tokens is an estimate: {}", budget.window.prompt_tokens, reason);
+    }
+
+    let reports = generate_audit_reports(&state, &tool_results)
+        .await
+        .iter()
+        .filter_map(|a| a.deliverable().file_name().map(|n| format!("/reports/{}", n.to_string_lossy())))
+        .collect();
 
-    Ok(Json(ChatResponse {
+    Ok(ChatResponse {
         response: final_content,
         tool_calls: tool_results,
         milestones,
         duration_ms,
-    }))
+        reports,
+        budget,
+        structured: structured.value,
+        structured_errors: structured.errors,
+        turn_id: None,
+    })
+}
+
+/// The turn's reply as JSON matching the request's `response_format`
+async fn structured_reply(
+    state: &Arc<AppState>,
+    messages: &[serde_json::Value],
+    tool_results: &[ToolCallResult],
+    reply: &str,
+    schema: &serde_json::Value,
+) -> structured::Structured {
+    let results: Vec<(&str, &serde_json::Value)> = tool_results.iter().map(|r| (r.tool.as_str(), &r.output)).collect();
+    let respond = structured::respond(&state.router, &state.config.llm, messages, &results, reply, schema);
+    match tokio::time::timeout(std::time::Duration::from_secs(llm_timeout_secs()), respond).await {
+        Ok(structured) => structured,
+        Err(_) => structured::Structured {
+            errors: vec![format!("Structured output timed out after {}s", llm_timeout_secs())],
+            ..Default::default()
+        },
+    }
+}
+
+#[derive(Deserialize)]
+struct ImpersonateRequest {
+    /// User id or linked identity (`provider:provider_id`)
+    user: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ImpersonateResponse {
+    user_id: String,
+    #[serde(flatten)]
+    reply: ChatResponse,
+}
+
+/// POST /chat/as - Run a chat turn as another user (admin token required)
+async fn chat_as(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<ImpersonateRequest>,
+) -> Result<Json<ImpersonateResponse>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    if req.message.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "message is empty".to_string()));
+    }
+    let (user_id, reply) = impersonated_turn(&state, &req.user, &req.message, "admin API").await?;
+    Ok(Json(ImpersonateResponse { user_id, reply }))
+}
+
+/// Run `message` as the user `target` names; `operator` is who asked (for the log)
+async fn impersonated_turn(
+    state: &Arc<AppState>,
+    target: &str,
+    message: &str,
+    operator: &str,
+) -> Result<(String, ChatResponse), (StatusCode, String)> {
+    let user_id = impersonate::resolve_target(state.store.as_ref(), target)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, format!("No user {}", target)))?;
+    tracing::warn!("🎭 {} is sending a message as {}", operator, user_id);
+
+    let request = ChatRequest {
+        message: message.to_string(),
+        channel: impersonate::CHANNEL.to_string(),
+        provider: None,
+        provider_id: None,
+        username: None,
+        context: None,
+        response_format: None,
+        thread_id: None,
+        dry_run: false,
+    };
+    let milestones = vec![format!("Impersonating {}", user_id)];
+    let reply = chat_turn(Arc::clone(state), request, &user_id, std::time::Instant::now(), milestones).await?;
+    Ok((user_id, reply))
+}
+
+/// Reply to an `/as` command from `provider:provider_id`, or None when `text` isn't one
+async fn impersonate_command(state: &Arc<AppState>, provider: &str, provider_id: &str, text: &str) -> Option<String> {
+    if !impersonate::is_command(text) {
+        return None;
+    }
+    if !impersonate::is_owner(&state.config.security, provider, provider_id) {
+        return Some("❌ /as is only available to the node's owners.".to_string());
+    }
+    let Some((target, message)) = impersonate::parse_command(text) else {
+        return Some("Usage: /as <user id or provider:id> <message>".to_string());
+    };
+    let operator = format!("{}:{}", provider, provider_id);
+    Some(match impersonated_turn(state, target, message, &operator).await {
+        Ok((user_id, reply)) => format!("👤 As {}:\n\n{}", user_id, reply.response),
+        Err((_, e)) => format!("❌ {}", e),
+    })
+}
+
+/// Owner chat commands: `/as`, plus `/pair`, `/requests`, `/approve`, `/deny` and `/persona`
+async fn owner_command(state: &Arc<AppState>, provider: &str, provider_id: &str, text: &str) -> Option<String> {
+    if let Some(reply) = impersonate_command(state, provider, provider_id, text).await {
+        return Some(reply);
+    }
+    if let Some(reply) = access_command(state, provider, provider_id, text).await {
+        return Some(reply);
+    }
+    persona_command(state, provider, provider_id, text).await
+}
+
+/// `/persona ...`, and the answers of a persona interview (see `bootstrap.rs`)
+async fn persona_command(state: &Arc<AppState>, provider: &str, provider_id: &str, text: &str) -> Option<String> {
+    if !impersonate::is_owner(&state.config.security, provider, provider_id) {
+        return text.starts_with("/persona").then(|| "❌ /persona is only available to the node's owners.".to_string());
+    }
+    bootstrap::reply(state, &format!("{}:{}", provider, provider_id), text).await
+}
+
+/// `/pair [provider]`, `/requests`, `/approve <id>` and `/deny <id>` (see `identity.rs`)
+async fn access_command(state: &Arc<AppState>, provider: &str, provider_id: &str, text: &str) -> Option<String> {
+    let mut words = text.split_whitespace();
+    let command = words.next().filter(|c| matches!(*c, "/pair" | "/requests" | "/approve" | "/deny"))?;
+    if !impersonate::is_owner(&state.config.security, provider, provider_id) {
+        return Some(format!("❌ {} is only available to the node's owners.", command));
+    }
+    Some(match (command, words.next()) {
+        ("/pair", only) => {
+            let code = state.identity_manager.pairing_code(only);
+            format!(
+                "🔑 Pairing code{}: {}\nValid until {}. Whoever sends it first gets in.",
+                only.map(|p| format!(" for {}", p)).unwrap_or_default(),
+                code.code,
+                code.expires_at.format("%Y-%m-%d %H:%M UTC"),
+            )
+        }
+        ("/requests", _) => {
+            let pending = state.identity_manager.pending();
+            if pending.is_empty() {
+                "No one is waiting for approval.".to_string()
+            } else {
+                pending
+                    .iter()
+                    .map(|r| format!("• {} — {} on {}: \"{}\"", r.id, r.username.as_deref().unwrap_or(&r.provider_id), r.provider, r.message))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        ("/approve", Some(id)) => match approve_access(state, id).await {
+            Ok(request) => format!("✅ Approved {} on {}", request.username.as_deref().unwrap_or(&request.provider_id), request.provider),
+            Err(e) => format!("❌ {}", e),
+        },
+        ("/deny", Some(id)) => match state.identity_manager.deny(id) {
+            Ok(request) => format!("🚫 Denied {} on {}", request.username.as_deref().unwrap_or(&request.provider_id), request.provider),
+            Err(e) => format!("❌ {}", e),
+        },
+        _ => format!("Usage: {} <request id> (see /requests)", command),
+    })
+}
+
+/// The user id of a message's sender, letting unknown senders in per
+/// `identity.policies`. Otherwise the reply to send them instead (None after
+/// an error); owners hear about new approval requests.
+async fn admit_sender(
+    state: &Arc<AppState>,
+    provider: &str,
+    provider_id: &str,
+    username: Option<&str>,
+    message: &str,
+) -> Result<String, Option<String>> {
+    match state.identity_manager.admit(provider, provider_id, username, message).await {
+        Ok(identity::Admission::User { user_id, is_new }) => {
+            if is_new {
+                tracing::info!("👤 First contact from {}:{}, created user {}", provider, provider_id, user_id);
+            }
+            Ok(user_id)
+        }
+        Ok(identity::Admission::Reply(reply)) => Err(Some(reply)),
+        Ok(identity::Admission::Requested(request)) => {
+            notify_owners(state, &request).await;
+            Err(Some(identity::PENDING_REPLY.to_string()))
+        }
+        Err(e) => {
+            tracing::error!("Identity resolution error: {}", e);
+            Err(None)
+        }
+    }
+}
+
+/// Ask every owner to approve or deny `request`
+async fn notify_owners(state: &AppState, request: &identity::AccessRequest) {
+    if state.config.security.owners.is_empty() {
+        tracing::warn!("Access request {} is waiting, but no security.owners are set to approve it", request.id);
+        return;
+    }
+    let text = format!(
+        "🔐 {} on {} asked to use the assistant:\n\"{}\"\n\nReply /approve {} or /deny {}",
+        request.username.as_deref().unwrap_or(&request.provider_id),
+        request.provider,
+        request.message,
+        request.id,
+        request.id,
+    );
+    notify::notify_owners(state.store.as_ref(), &state.channels, &state.snoozes, &state.config.security.owners, &text).await;
+}
+
+/// Approve an access request and tell the requester
+async fn approve_access(state: &AppState, request_id: &str) -> anyhow::Result<identity::AccessRequest> {
+    let (request, user_id) = state.identity_manager.approve(request_id).await?;
+    if let Err(e) = notify::notify_user(state.store.as_ref(), &state.channels, &state.snoozes, &user_id, identity::APPROVED_REPLY, None).await {
+        tracing::warn!("Approved request {}, but couldn't tell them: {}", request.id, e);
+    }
+    Ok(request)
+}
+
+/// Admin endpoints need `Authorization: Bearer <security.admin_token_env>`
+fn require_admin(state: &AppState, headers: &axum::http::HeaderMap) -> Result<(), (StatusCode, String)> {
+    let authorization = headers.get("authorization").and_then(|v| v.to_str().ok());
+    if !auth::admin_authorized(&state.config.security, authorization) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Bearer token from {} required", state.config.security.admin_token_env),
+        ));
+    }
+    Ok(())
+}
+
+/// GET /identity/requests - identities waiting for an owner's approval
+async fn list_access_requests(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<Vec<identity::AccessRequest>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.identity_manager.pending()))
+}
+
+/// POST /identity/requests/:id/approve
+async fn approve_access_request(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<identity::AccessRequest>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    approve_access(&state, &id).await.map(Json).map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+/// POST /identity/requests/:id/deny
+async fn deny_access_request(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<identity::AccessRequest>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    state.identity_manager.deny(&id).map(Json).map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+#[derive(Deserialize)]
+struct PairingCodeRequest {
+    /// Only valid on this provider
+    #[serde(default)]
+    provider: Option<String>,
+}
+
+/// POST /identity/pairing-codes
+async fn create_pairing_code(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<PairingCodeRequest>,
+) -> Result<Json<identity::PairingCode>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    Ok(Json(state.identity_manager.pairing_code(req.provider.as_deref())))
+}
+
+#[derive(Deserialize)]
+struct AnalyticsQuery {
+    /// `7d`, `24h` or an RFC 3339 timestamp
+    since: Option<String>,
+    intent: Option<store::Intent>,
+    sentiment: Option<store::Sentiment>,
+    channel: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Counts this many recent matches at most
+const ANALYTICS_SCAN_LIMIT: usize = 5000;
+
+async fn conversation_analytics(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<AnalyticsQuery>,
+) -> Result<Json<tagging::ConversationAnalytics>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    let since = match query.since.as_deref() {
+        Some(value) => Some(tagging::parse_since(value).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, format!("Invalid since: {} (use 7d, 24h or an RFC 3339 time)", value))
+        })?),
+        None => None,
+    };
+
+    let messages = state.store
+        .tagged_messages(&store::TagQuery {
+            intent: query.intent,
+            sentiment: query.sentiment,
+            channel: query.channel,
+            since,
+            limit: ANALYTICS_SCAN_LIMIT,
+        })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(tagging::summarize(messages, since, query.limit.unwrap_or(50))))
 }
 
 #[derive(Deserialize)]
@@ -1181,6 +3383,309 @@ async fn clear_chat(
     Ok(Json(serde_json::json!({ "cleared": true, "user_id": actual_user_id })))
 }
 
+#[derive(Deserialize)]
+struct NotifyRequest {
+    /// Internal user id
+    #[serde(default)]
+    user_id: Option<String>,
+    /// Or any identity linked to the user, as `provider:provider_id`
+    #[serde(default)]
+    identity: Option<String>,
+    message: String,
+    /// Only deliver on these channels (default: every linked channel)
+    #[serde(default)]
+    channels: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct NotifyResponse {
+    user_id: String,
+    delivered: usize,
+    receipts: Vec<notify::Receipt>,
+}
+
+/// POST /notify - Message a user on every channel they're linked to
+async fn notify_user(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<NotifyRequest>,
+) -> Result<Json<NotifyResponse>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+    if req.message.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "message is empty".to_string()));
+    }
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    // Unknown users are not created here: there would be nowhere to reach them
+    let user_id = match (req.user_id, req.identity) {
+        (Some(user_id), _) => state.store.get_user(&user_id).await.map_err(internal)?.map(|u| u.id),
+        (None, Some(identity)) => {
+            let (provider, provider_id) = identity.split_once(':').ok_or((
+                StatusCode::BAD_REQUEST,
+                "identity must be provider:provider_id".to_string(),
+            ))?;
+            state.store.get_identity(provider, provider_id).await.map_err(internal)?.map(|i| i.user_id)
+        }
+        (None, None) => return Err((StatusCode::BAD_REQUEST, "user_id or identity is required".to_string())),
+    }
+    .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+    let receipts = notify::notify_user(
+        state.store.as_ref(),
+        &state.channels,
+        &state.snoozes,
+        &user_id,
+        &req.message,
+        req.channels.as_deref(),
+    )
+    .await
+    .map_err(internal)?;
+    let delivered = receipts.iter().filter(|r| r.status == notify::DeliveryStatus::Delivered).count();
+    Ok(Json(NotifyResponse { user_id, delivered, receipts }))
+}
+
+// ============================================
+// Quick Action Endpoints
+// ============================================
+
+/// GET /actions - configured quick actions
+async fn list_actions(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<Vec<actions::ActionSummary>>, (StatusCode, String)> {
+    actions::authorize(&state.config.actions, &headers)?;
+    Ok(Json(state.config.actions.items.iter().map(actions::ActionSummary::from).collect()))
+}
+
+/// POST /actions/:name - run one quick action
+async fn run_action(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<HistoryQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    actions::authorize(&state.config.actions, &headers)?;
+    let action = actions::find(&state.config.actions, &name)
+        .ok_or((StatusCode::NOT_FOUND, format!("Unknown action: {}", name)))?;
+    let user_id = query.user_id.unwrap_or_else(|| "http:anonymous".to_string());
+    tracing::info!("⚡ Quick action \"{}\" for {}", action.name, user_id);
+
+    match &action.kind {
+        config::QuickActionKind::Workflow { workflow_id, inputs } => {
+            let receipt = workflow::run(workflow_id, inputs.clone(), false)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            Ok(Json(serde_json::json!({ "action": action.name, "receipt": receipt })))
+        }
+        config::QuickActionKind::ClearHistory => {
+            let Json(cleared) = clear_chat(State(state.clone()), axum::extract::Query(HistoryQuery { user_id: Some(user_id) })).await?;
+            Ok(Json(serde_json::json!({ "action": action.name, "result": cleared })))
+        }
+        config::QuickActionKind::Prompt { message } => {
+            let (provider, provider_id) = user_id.split_once(':').unwrap_or(("http", user_id.as_str()));
+            let request = ChatRequest {
+                message: message.clone(),
+                channel: "http".to_string(),
+                provider: Some(provider.to_string()),
+                provider_id: Some(provider_id.to_string()),
+                username: None,
+                context: None,
+                response_format: None,
+                thread_id: None,
+                dry_run: false,
+            };
+            let Json(reply) = chat_from(state.clone(), request).await?;
+            Ok(Json(serde_json::json!({ "action": action.name, "response": reply.response, "reports": reply.reports })))
+        }
+    }
+}
+
+// ============================================
+// Voice Endpoints (Twilio webhooks)
+// ============================================
+
+type TwimlResponse = Result<([(&'static str, &'static str); 1], String), (StatusCode, String)>;
+
+fn twiml(body: String) -> TwimlResponse {
+    Ok(([("content-type", "text/xml")], body))
+}
+
+/// Verify the Twilio signature and return the voice channel
+fn voice_channel(
+    state: &AppState,
+    path: &str,
+    headers: &axum::http::HeaderMap,
+    params: &std::collections::HashMap<String, String>,
+) -> Result<crate::channels::voice::VoiceChannel, (StatusCode, String)> {
+    let voice = crate::channels::voice::VoiceChannel::new(state.config.channels.voice.clone());
+    if !voice.enabled() {
+        return Err((StatusCode::NOT_FOUND, "Voice channel disabled".to_string()));
+    }
+    let signature = headers.get("x-twilio-signature").and_then(|v| v.to_str().ok());
+    if !voice.verify(path, params, signature) {
+        return Err((StatusCode::FORBIDDEN, "Invalid Twilio signature".to_string()));
+    }
+    Ok(voice)
+}
+
+/// POST /voice/incoming - call connected
+async fn voice_incoming(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Form(params): axum::extract::Form<std::collections::HashMap<String, String>>,
+) -> TwimlResponse {
+    let voice = voice_channel(&state, "/voice/incoming", &headers, &params)?;
+    state.channels.record_message("voice").await;
+    tracing::info!("📞 Incoming call from {}", params.get("From").map(String::as_str).unwrap_or("unknown"));
+    twiml(voice.greeting_twiml(&state.config.node.name))
+}
+
+/// POST /voice/respond - caller finished speaking
+async fn voice_respond(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Form(params): axum::extract::Form<std::collections::HashMap<String, String>>,
+) -> TwimlResponse {
+    let voice = voice_channel(&state, "/voice/respond", &headers, &params)?;
+
+    let speech = params.get("SpeechResult").map(|s| s.trim()).unwrap_or_default();
+    if speech.is_empty() {
+        return twiml(voice.goodbye_twiml("Sorry, I didn't catch that. Please call back or leave us a message. Goodbye."));
+    }
+
+    let caller = params.get("From").cloned().unwrap_or_else(|| "unknown".to_string());
+    let request = ChatRequest {
+        message: speech.to_string(),
+        channel: "voice".to_string(),
+        provider: Some("voice".to_string()),
+        provider_id: Some(caller.clone()),
+        username: None,
+        context: Some(voice.instructions()),
+        response_format: None,
+        thread_id: None,
+        dry_run: false,
+    };
+
+    match chat_from(Arc::clone(&state), request).await {
+        // Nothing is spoken for the agent in shadow mode; the caller just gets a polite goodbye
+        Ok(Json(reply)) if shadow::active() => {
+            shadow::record(shadow::ShadowKind::Message, &format!("voice:{}", caller), serde_json::json!({ "content": reply.response }));
+            twiml(voice.goodbye_twiml("Thanks for calling. Someone will get back to you shortly. Goodbye."))
+        }
+        Ok(Json(reply)) => twiml(voice.reply_twiml(&reply.response)),
+        Err((_, e)) => {
+            tracing::warn!("Voice turn failed: {}", e);
+            twiml(voice.goodbye_twiml("Sorry, I'm having trouble right now. Please try again later. Goodbye."))
+        }
+    }
+}
+
+// ============================================
+// WhatsApp Endpoints (Meta Cloud API webhooks)
+// ============================================
+
+/// GET /whatsapp/webhook - Meta's subscription check
+async fn whatsapp_verify(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> Result<String, (StatusCode, String)> {
+    let whatsapp = crate::channels::whatsapp::WhatsAppChannel::new(state.config.channels.whatsapp.clone());
+    if !whatsapp.enabled() {
+        return Err((StatusCode::NOT_FOUND, "WhatsApp channel disabled".to_string()));
+    }
+    whatsapp
+        .verify_subscription(
+            params.get("hub.mode").map(String::as_str),
+            params.get("hub.verify_token").map(String::as_str),
+            params.get("hub.challenge").map(String::as_str),
+        )
+        .ok_or((StatusCode::FORBIDDEN, "Verification failed".to_string()))
+}
+
+/// POST /whatsapp/webhook - message deliveries. Acknowledged right away (Meta
+/// retries slow deliveries); each message is answered in the background.
+async fn whatsapp_webhook(
+    State(state): State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<StatusCode, (StatusCode, String)> {
+    use crate::channels::Channel;
+
+    let whatsapp = crate::channels::whatsapp::WhatsAppChannel::new(state.config.channels.whatsapp.clone());
+    if !whatsapp.enabled() {
+        return Err((StatusCode::NOT_FOUND, "WhatsApp channel disabled".to_string()));
+    }
+    if !whatsapp.is_connected() {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "WhatsApp channel not configured".to_string()));
+    }
+    let signature = headers.get("x-hub-signature-256").and_then(|v| v.to_str().ok());
+    if !whatsapp.verify(&body, signature) {
+        return Err((StatusCode::FORBIDDEN, "Invalid WhatsApp signature".to_string()));
+    }
+    let payload: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let messages = whatsapp.parse_webhook(&payload);
+    if messages.is_empty() {
+        // Delivery/read receipts and unsupported message types
+        return Ok(StatusCode::OK);
+    }
+    state.channels.record_message("whatsapp").await;
+
+    tokio::spawn(async move {
+        for msg in messages {
+            tracing::info!("📨 WhatsApp message from {}: {}",
+                msg.username.as_deref().unwrap_or(&msg.provider_user_id),
+                msg.content
+            );
+
+            let request = ChatRequest {
+                message: msg.content.clone(),
+                channel: "whatsapp".to_string(),
+                provider: Some("whatsapp".to_string()),
+                provider_id: Some(msg.provider_user_id.clone()),
+                username: msg.username.clone(),
+                context: None,
+                response_format: None,
+                thread_id: msg.thread_id.clone(),
+                dry_run: false,
+            };
+            let (content, reports) = match chat_from(Arc::clone(&state), request).await {
+                Ok(Json(reply)) => (reply.response, reply.reports),
+                Err((_, e)) => {
+                    tracing::error!("❌ WhatsApp turn failed: {}", e);
+                    (format!("❌ Something went wrong: {}", e.chars().take(200).collect::<String>()), vec![])
+                }
+            };
+            if let Err(e) = whatsapp.send(crate::channels::OutgoingMessage {
+                channel_type: crate::channels::ChannelType::WhatsApp,
+                channel_id: msg.channel_id.clone(),
+                content,
+                reply_to: msg.reply_to.clone(),
+                thread_id: msg.thread_id.clone(),
+                metadata: serde_json::json!({}),
+                attachments: vec![],
+                buttons: vec![],
+            }).await {
+                tracing::warn!("Failed to send WhatsApp reply: {}", e);
+            }
+
+            // Deliver branded audit reports as documents
+            if let Some(workspace) = state.agent_os.workspace() {
+                for link in reports {
+                    let Some(file) = link.strip_prefix("/reports/") else { continue };
+                    let path = report::reports_dir(&workspace).join(file);
+                    if let Err(e) = whatsapp.send_file(&msg.channel_id, &path, Some("📄 Audit report - ready to forward")).await {
+                        tracing::warn!("Failed to send report: {}", e);
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(StatusCode::OK)
+}
+
 // ============================================
 // Integration Endpoints
 // ============================================
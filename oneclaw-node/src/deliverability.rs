@@ -0,0 +1,218 @@
+//! Deliverability - Checks on outreach email before it goes out
+//!
+//! Cold email that lands in spam is worse than none, so `google.gmail` sends
+//! are checked first:
+//! - the sending domain publishes SPF (`v=spf1` TXT) and DKIM (a key under one
+//!   of `deliverability.dkim_selectors`)
+//! - the recipient's domain accepts mail (has MX records)
+//! - the draft avoids phrases and habits spam filters punish (all-caps
+//!   subjects, "act now", "100% free", stacked exclamation marks, ...)
+//!
+//! DNS goes through a DNS-over-HTTPS resolver (`deliverability.doh_url`), so
+//! this works the same on every platform. With `hold_on_warnings`, an email
+//! with warnings isn't sent: the warnings go back to the agent, which shows
+//! them to the user and sends again with `confirmed: true` once they approve.
+//! `POST /deliverability/check` runs the same checks on a draft.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::DeliverabilityConfig;
+
+/// Phrases spam filters weigh against cold email
+const SPAM_PHRASES: &[&str] = &[
+    "act now",
+    "100% free",
+    "risk-free",
+    "risk free",
+    "guaranteed",
+    "no obligation",
+    "limited time",
+    "click here",
+    "buy now",
+    "cash bonus",
+    "double your",
+    "earn money",
+    "make money",
+    "once in a lifetime",
+    "urgent",
+    "winner",
+    "you have been selected",
+    "dear friend",
+    "increase sales",
+    "no credit check",
+    "special promotion",
+    "this isn't spam",
+];
+
+/// Links in a cold email past which filters get suspicious
+const MAX_LINKS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    /// `spf`, `dkim`, `mx` or `content`
+    pub check: &'static str,
+    pub message: String,
+}
+
+/// Shown on `POST /deliverability/check`, and with held sends
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    pub warnings: Vec<Warning>,
+}
+
+impl Report {
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// One line per warning, for the agent and the user
+    pub fn summary(&self) -> String {
+        self.warnings.iter().map(|w| format!("- {}: {}", w.check, w.message)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+fn warning(check: &'static str, message: impl Into<String>) -> Warning {
+    Warning { check, message: message.into() }
+}
+
+/// The domain of an email address (`"Ana <ana@acme.co>"` works too)
+pub fn domain_of(address: &str) -> Option<String> {
+    let address = address.rsplit('<').next().unwrap_or(address).trim_end_matches('>').trim();
+    let (local, domain) = address.rsplit_once('@')?;
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    (!local.is_empty() && domain.contains('.') && !domain.contains(char::is_whitespace)).then_some(domain)
+}
+
+/// Content warnings for a draft's `subject` and `body`
+pub fn lint(subject: &str, body: &str) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let text = format!("{}\n{}", subject, body).to_lowercase();
+    let phrases: Vec<&str> = SPAM_PHRASES.iter().copied().filter(|phrase| text.contains(phrase)).collect();
+    if !phrases.is_empty() {
+        warnings.push(warning("content", format!("Spam-trigger phrases: {}", phrases.join(", "))));
+    }
+    let letters: Vec<char> = subject.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() >= 8 && letters.iter().all(|c| c.is_uppercase()) {
+        warnings.push(warning("content", "Subject is in all caps"));
+    }
+    if text.contains("!!") {
+        warnings.push(warning("content", "Repeated exclamation marks"));
+    }
+    let links = text.matches("http://").count() + text.matches("https://").count();
+    if links > MAX_LINKS {
+        warnings.push(warning("content", format!("{} links; cold email does best with {} or fewer", links, MAX_LINKS)));
+    }
+    if subject.trim().is_empty() {
+        warnings.push(warning("content", "No subject"));
+    }
+    warnings
+}
+
+/// Records of `kind` (TXT, MX) for `name`; empty when the name doesn't exist
+async fn lookup(config: &DeliverabilityConfig, name: &str, kind: &str) -> anyhow::Result<Vec<String>> {
+    let response: Value = reqwest::Client::new()
+        .get(&config.doh_url)
+        .header("accept", "application/dns-json")
+        .query(&[("name", name), ("type", kind)])
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(answers(&response))
+}
+
+/// Record data from a DNS JSON response, TXT strings unquoted and joined
+fn answers(response: &Value) -> Vec<String> {
+    response["Answer"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|answer| answer["data"].as_str())
+        .map(|data| match data.contains('"') {
+            true => data.split('"').skip(1).step_by(2).collect(),
+            false => data.to_string(),
+        })
+        .collect()
+}
+
+/// SPF and DKIM for the domain mail is sent from
+async fn check_sender(config: &DeliverabilityConfig, domain: &str) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    match lookup(config, domain, "TXT").await {
+        Ok(records) if records.iter().any(|r| r.starts_with("v=spf1")) => {}
+        Ok(_) => warnings.push(warning("spf", format!("{} publishes no SPF record", domain))),
+        Err(e) => warnings.push(warning("spf", format!("Couldn't look up SPF for {}: {}", domain, e))),
+    }
+
+    let mut dkim = false;
+    for selector in &config.dkim_selectors {
+        let name = format!("{}._domainkey.{}", selector, domain);
+        if let Ok(records) = lookup(config, &name, "TXT").await {
+            if records.iter().any(|r| r.contains("p=")) {
+                dkim = true;
+                break;
+            }
+        }
+    }
+    if !dkim {
+        warnings.push(warning(
+            "dkim",
+            format!("No DKIM key for {} under selectors {}", domain, config.dkim_selectors.join(", ")),
+        ));
+    }
+    warnings
+}
+
+/// Whether the recipient's domain accepts mail
+async fn check_recipient(config: &DeliverabilityConfig, to: &str) -> Vec<Warning> {
+    let Some(domain) = domain_of(to) else {
+        return vec![warning("mx", format!("{} isn't a valid email address", to))];
+    };
+    match lookup(config, &domain, "MX").await {
+        Ok(records) if records.iter().any(|r| !r.ends_with(" .")) => vec![],
+        Ok(_) => vec![warning("mx", format!("{} has no mail servers (MX records); the email would bounce", domain))],
+        Err(e) => vec![warning("mx", format!("Couldn't look up mail servers for {}: {}", domain, e))],
+    }
+}
+
+/// Every check for an email from `from` (or `deliverability.sending_domain`) to `to`
+pub async fn check(config: &DeliverabilityConfig, from: Option<&str>, to: &str, subject: &str, body: &str) -> Report {
+    let sending_domain = from.and_then(domain_of).or_else(|| config.sending_domain.clone());
+    let mut warnings = Vec::new();
+    if let Some(domain) = &sending_domain {
+        warnings.extend(check_sender(config, domain).await);
+    }
+    warnings.extend(check_recipient(config, to).await);
+    warnings.extend(lint(subject, body));
+    Report { warnings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_and_dns_answers() {
+        let warnings = lint("FREE AUDIT FOR YOUR SITE", "Act now!! This is 100% free, click here.");
+        let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages[0], "Spam-trigger phrases: act now, 100% free, click here");
+        assert!(messages.contains(&"Subject is in all caps"));
+        assert!(messages.contains(&"Repeated exclamation marks"));
+        assert!(lint("Quick question about Smith HVAC", "Hi Ana, I noticed your site loads slowly on phones.").is_empty());
+
+        assert_eq!(domain_of("Ana Diaz <Ana@Acme.co>").as_deref(), Some("acme.co"));
+        assert_eq!(domain_of("not an address"), None);
+
+        let response = serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                { "type": 16, "data": "\"v=DKIM1; k=rsa; \" \"p=MIGfMA0\"" },
+                { "type": 15, "data": "10 mx.acme.co." }
+            ]
+        });
+        assert_eq!(answers(&response), vec!["v=DKIM1; k=rsa; p=MIGfMA0".to_string(), "10 mx.acme.co.".to_string()]);
+    }
+}
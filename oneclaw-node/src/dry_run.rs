@@ -0,0 +1,126 @@
+//! Dry Run - The full agent loop with simulated tool outputs
+//!
+//! `"dry_run": true` on `/chat` or `/run` (or `oneclaw run --dry-run`) still
+//! drives the LLM through every round, but no tool runs: each call returns a
+//! simulated output instead. Prompt and playbook changes can be tried without
+//! spending money or emailing real prospects.
+//!
+//! A simulated output is, in order:
+//! 1. The recorded fixture `<workspace>/fixtures/<tool>.json`, if there is one
+//! 2. A mock built from the executor's `output_schema`
+//! 3. An empty object
+//!
+//! Objects get `"dry_run": true` so the model (and whoever reads the receipt)
+//! can tell. Unlike shadow mode (`shadow.rs`), which is process-wide, dry runs
+//! are per request: the flag is scoped to the task handling it.
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::config::NodeConfig;
+
+tokio::task_local! {
+    static DRY_RUN: bool;
+}
+
+/// Run `f` with dry-run mode `on` for every tool call it makes
+pub async fn scope<F: Future>(on: bool, f: F) -> F::Output {
+    DRY_RUN.scope(on, f).await
+}
+
+/// Whether the current task is a dry run. Tasks spawned from one don't inherit
+/// it; wrap them in `scope(active(), ..)`.
+pub fn active() -> bool {
+    DRY_RUN.try_with(|on| *on).unwrap_or(false)
+}
+
+fn fixture_path(config: &NodeConfig, tool_id: &str) -> Option<PathBuf> {
+    let file = format!("{}.json", tool_id.replace(['/', '\\'], "_"));
+    crate::agent_os::workspace_for(config).map(|w| w.join("fixtures").join(file))
+}
+
+/// The recorded output for `tool_id`, if the workspace has one
+fn fixture(config: &NodeConfig, tool_id: &str) -> Option<Value> {
+    let path = fixture_path(config, tool_id)?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(output) => Some(output),
+        Err(e) => {
+            tracing::warn!("Ignoring fixture {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// A value shaped like `schema`: examples and defaults when given, the first
+/// enum value, otherwise a placeholder of the right type
+pub fn mock(schema: &Value) -> Value {
+    if let Some(example) = schema.get("examples").and_then(|e| e.get(0)).or_else(|| schema.get("example")) {
+        return example.clone();
+    }
+    if let Some(value) = schema.get("default").or_else(|| schema.get("const")) {
+        return value.clone();
+    }
+    if let Some(first) = schema.get("enum").and_then(|e| e.get(0)) {
+        return first.clone();
+    }
+    let kind = match &schema["type"] {
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).find(|k| *k != "null").unwrap_or("null"),
+        kind => kind.as_str().unwrap_or(if schema.get("properties").is_some() { "object" } else { "null" }),
+    };
+    match kind {
+        "object" => {
+            let properties = schema["properties"].as_object().cloned().unwrap_or_default();
+            Value::Object(properties.iter().map(|(name, property)| (name.clone(), mock(property))).collect())
+        }
+        "array" => json!([mock(&schema["items"])]),
+        "string" => json!(format!("sample {}", schema["format"].as_str().unwrap_or("text"))),
+        "integer" => json!(1),
+        "number" => json!(1.0),
+        "boolean" => json!(true),
+        _ => Value::Null,
+    }
+}
+
+/// What a call of `tool_id` returns in a dry run
+pub fn simulated(config: &NodeConfig, tool_id: &str, output_schema: Option<&Value>) -> Value {
+    let mut output = fixture(config, tool_id)
+        .or_else(|| output_schema.map(mock))
+        .unwrap_or_else(|| json!({}));
+    if let Value::Object(fields) = &mut output {
+        fields.insert("dry_run".to_string(), json!(true));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_schema_mock_and_scope() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "status": { "type": "integer" },
+                "sent_to": { "type": "string", "format": "email" },
+                "state": { "type": "string", "enum": ["queued", "sent"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "note": { "type": ["null", "string"], "default": "none" }
+            }
+        });
+        assert_eq!(
+            mock(&schema),
+            json!({ "status": 1, "sent_to": "sample email", "state": "queued", "tags": ["sample text"], "note": "none" })
+        );
+
+        // A bare schema without a type
+        assert_eq!(mock(&json!({ "properties": { "ok": { "type": "boolean" } } })), json!({ "ok": true }));
+
+        assert!(!active());
+        assert!(scope(true, async { active() }).await);
+        assert!(!scope(false, async { active() }).await);
+    }
+}
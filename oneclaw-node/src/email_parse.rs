@@ -0,0 +1,293 @@
+//! Email Parsing - Structured lead data from messy inbound email
+//!
+//! `email.parse` turns an email into what a lead update needs: who sent it,
+//! their company, what they want (intent), the action they ask for, and any
+//! phone number or meeting time they mention. The input can be a raw RFC 822
+//! message, a pasted thread, or just a body with the sender given separately.
+//!
+//! Headers, quoted history and signatures are handled locally, along with the
+//! company (from a non-webmail sender domain) and phone numbers. The latest
+//! reply then goes to a small model through a forced `submit_email` tool call
+//! (`tagging` phase, `tagging.model`). Without an LLM a keyword guess sets the intent.
+//!
+//! The email channel parses each message it turns into a chat turn and adds
+//! the result to the turn's context, so a reply to outreach ("sure, call me
+//! Tuesday at 555-013-4477") reads as an actionable lead update.
+
+use mail_parser::MessageParser;
+use serde::{Deserialize, Serialize};
+
+use crate::model_router::ModelRouter;
+
+const SUBMIT_EMAIL: &str = "submit_email";
+
+/// Longest body sent to the model
+const MAX_BODY_CHARS: usize = 4_000;
+
+/// Sender domains that say nothing about the company
+const WEBMAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.com",
+    "hotmail.com",
+    "outlook.com",
+    "live.com",
+    "aol.com",
+    "icloud.com",
+    "me.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+];
+
+/// What the sender wants
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Intent {
+    Interested,
+    MeetingRequest,
+    Question,
+    NotInterested,
+    Unsubscribe,
+    OutOfOffice,
+    Referral,
+    #[default]
+    Other,
+}
+
+/// The parts of an email before parsing
+#[derive(Debug, Clone, Default)]
+pub struct EmailParts {
+    pub sender_name: Option<String>,
+    pub sender_email: Option<String>,
+    pub subject: Option<String>,
+    pub body: String,
+}
+
+/// Structured data from one email
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedEmail {
+    pub sender_name: Option<String>,
+    pub sender_email: Option<String>,
+    pub company: Option<String>,
+    pub subject: Option<String>,
+    pub intent: Intent,
+    /// What the sender asks for, as an instruction ("Call back Tuesday afternoon")
+    pub requested_action: Option<String>,
+    pub phone: Option<String>,
+    /// A meeting time as written ("Thursday at 3pm")
+    pub meeting_time: Option<String>,
+    /// One sentence on what the email says
+    pub summary: Option<String>,
+    /// The latest reply, without quoted history or signature
+    pub body: String,
+}
+
+/// Split a raw email (or a bare body) into its parts
+pub fn split(raw: &str) -> EmailParts {
+    let message = MessageParser::default().parse(raw.as_bytes()).filter(|m| m.from().is_some());
+    let Some(message) = message else {
+        return EmailParts { body: raw.trim().to_string(), ..Default::default() };
+    };
+    let sender = message.from().and_then(|from| from.first());
+    EmailParts {
+        sender_name: sender.and_then(|s| s.name()).map(str::to_string),
+        sender_email: sender.and_then(|s| s.address()).map(str::to_lowercase),
+        subject: message.subject().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        body: message.body_text(0).map(|b| b.to_string()).unwrap_or_default(),
+    }
+}
+
+/// The company a sender's address points to (`jane@smithhvac.com` -> `smithhvac.com`)
+fn company_domain(address: &str) -> Option<String> {
+    let domain = address.rsplit_once('@')?.1.trim().to_lowercase();
+    (!domain.is_empty() && !WEBMAIL_DOMAINS.contains(&domain.as_str())).then_some(domain)
+}
+
+fn find_phone(text: &str) -> Option<String> {
+    let phone = regex::Regex::new(r"\+?\(?\d[\d\s().-]{7,}\d").expect("valid regex");
+    let found = phone
+        .find_iter(text)
+        .map(|m| m.as_str().trim().to_string())
+        .find(|candidate| candidate.chars().filter(char::is_ascii_digit).count() >= 10);
+    found
+}
+
+/// Keyword guess at the intent, for when no model is available
+fn guess_intent(subject: &str, body: &str) -> Intent {
+    let text = format!("{}\n{}", subject, body).to_lowercase();
+    let has = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+    if has(&["unsubscribe", "remove me", "stop emailing", "take me off"]) {
+        Intent::Unsubscribe
+    } else if has(&["out of office", "out of the office", "on vacation", "limited access to email", "automatic reply"]) {
+        Intent::OutOfOffice
+    } else if has(&["not interested", "no thanks", "no thank you", "we're all set", "we are all set", "not a good fit"]) {
+        Intent::NotInterested
+    } else if has(&["you should talk to", "reach out to", "the right person", "i've copied", "i have copied", "forwarding this to"]) {
+        Intent::Referral
+    } else if has(&["schedule", "call me", "give me a call", "book a time", "set up a call", "meet", "calendar", "available on", "free on"]) {
+        Intent::MeetingRequest
+    } else if has(&["interested", "sounds good", "tell me more", "send it over", "send me", "let's do it", "sure"]) {
+        Intent::Interested
+    } else if text.contains('?') {
+        Intent::Question
+    } else {
+        Intent::Other
+    }
+}
+
+/// Everything that can be read from `parts` without a model
+pub fn parse_locally(parts: &EmailParts) -> ParsedEmail {
+    let stripped = crate::channels::email::strip_quoted(&parts.body);
+    let body = if stripped.is_empty() { parts.body.trim().to_string() } else { stripped };
+    let subject = parts.subject.clone().unwrap_or_default();
+    ParsedEmail {
+        sender_name: parts.sender_name.clone(),
+        sender_email: parts.sender_email.clone(),
+        company: parts.sender_email.as_deref().and_then(company_domain),
+        subject: parts.subject.clone(),
+        intent: guess_intent(&subject, &body),
+        requested_action: None,
+        phone: find_phone(&body),
+        meeting_time: None,
+        summary: None,
+        body,
+    }
+}
+
+fn submit_email_tool() -> serde_json::Value {
+    let text = |description: &str| serde_json::json!({ "type": "string", "description": description });
+    serde_json::json!({
+        "name": SUBMIT_EMAIL,
+        "description": "Submit what the email says",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "sender_name": text("Full name of the sender, from the signature or sign-off if not given"),
+                "company": text("The sender's company or business name, if stated or clear from the signature"),
+                "intent": {
+                    "type": "string",
+                    "enum": ["interested", "meeting_request", "question", "not_interested", "unsubscribe", "out_of_office", "referral", "other"]
+                },
+                "requested_action": text("What the sender wants done next, as a short instruction. Omit if nothing."),
+                "phone": text("Phone number to reach the sender, if given"),
+                "meeting_time": text("Proposed meeting or call time, as written"),
+                "summary": text("One sentence on what the email says")
+            },
+            "required": ["intent", "summary"]
+        }
+    })
+}
+
+fn prompt(parsed: &ParsedEmail) -> String {
+    format!(
+        "Read this email to a small business owner and submit what it says with the submit_email tool.\n\n\
+         From: {} <{}>\nSubject: {}\n\n{}",
+        parsed.sender_name.as_deref().unwrap_or("unknown"),
+        parsed.sender_email.as_deref().unwrap_or("unknown"),
+        parsed.subject.as_deref().unwrap_or(""),
+        parsed.body.chars().take(MAX_BODY_CHARS).collect::<String>()
+    )
+}
+
+/// Fill `parsed` from a `submit_email` input; what the model left out keeps
+/// the local value
+pub fn apply_submitted(parsed: &mut ParsedEmail, input: &serde_json::Value) {
+    let text = |key: &str| input[key].as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+    if let Ok(intent) = serde_json::from_value(input["intent"].clone()) {
+        parsed.intent = intent;
+    }
+    parsed.sender_name = parsed.sender_name.take().or_else(|| text("sender_name"));
+    parsed.company = text("company").or(parsed.company.take());
+    parsed.requested_action = text("requested_action");
+    parsed.phone = text("phone").or(parsed.phone.take());
+    parsed.meeting_time = text("meeting_time");
+    parsed.summary = text("summary");
+}
+
+/// Parse an email; `model` is the small model used without a routing rule
+pub async fn parse(router: &ModelRouter, parts: &EmailParts, model: &str) -> ParsedEmail {
+    let mut parsed = parse_locally(parts);
+    if parsed.body.is_empty() {
+        return parsed;
+    }
+    if std::env::var("ANTHROPIC_API_KEY").unwrap_or_default().is_empty() && router.routed().is_none() {
+        return parsed;
+    }
+    match crate::autonomous_jobs::planner_tool_call(router, crate::model_router::TAGGING, &prompt(&parsed), model, 600, &submit_email_tool()).await {
+        Ok(input) => apply_submitted(&mut parsed, &input),
+        Err(e) => tracing::warn!("Email parsing fell back to keywords: {}", e),
+    }
+    parsed
+}
+
+/// Context block for the chat turn an email starts
+pub fn context_block(parsed: &ParsedEmail) -> String {
+    let mut lines = vec![format!("- Intent: {}", serde_json::to_value(parsed.intent).unwrap_or_default().as_str().unwrap_or("other"))];
+    let fields = [
+        ("Sender", parsed.sender_name.as_deref()),
+        ("Company", parsed.company.as_deref()),
+        ("Requested action", parsed.requested_action.as_deref()),
+        ("Phone", parsed.phone.as_deref()),
+        ("Meeting time", parsed.meeting_time.as_deref()),
+        ("Summary", parsed.summary.as_deref()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            lines.push(format!("- {}: {}", label, value));
+        }
+    }
+    format!(
+        "## This Email\nParsed from the message you're answering. If it's a reply to outreach, update the lead accordingly:\n{}",
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reply_locally_and_with_model_fields() {
+        let raw = "From: Dana Smith <Dana@SmithHVAC.com>\r\n\
+                   To: owner@example.com\r\n\
+                   Subject: Re: Your website audit\r\n\
+                   Message-ID: <abc@smithhvac.com>\r\n\
+                   \r\n\
+                   Sure, give me a call Tuesday afternoon at (305) 555-0134.\r\n\
+                   \r\n\
+                   On Mon, Mar 2, 2026 at 9:00 AM Owner <owner@example.com> wrote:\r\n\
+                   > Hi Dana, I ran an audit of your site at 555-000-0000...\r\n";
+        let parts = split(raw);
+        assert_eq!(parts.sender_email.as_deref(), Some("dana@smithhvac.com"));
+        assert_eq!(parts.subject.as_deref(), Some("Re: Your website audit"));
+
+        let mut parsed = parse_locally(&parts);
+        assert_eq!(parsed.body, "Sure, give me a call Tuesday afternoon at (305) 555-0134.");
+        assert_eq!(parsed.company.as_deref(), Some("smithhvac.com"));
+        assert_eq!(parsed.phone.as_deref(), Some("(305) 555-0134"));
+        assert_eq!(parsed.intent, Intent::MeetingRequest);
+
+        apply_submitted(&mut parsed, &serde_json::json!({
+            "intent": "meeting_request",
+            "company": "Smith HVAC",
+            "requested_action": "Call Dana Tuesday afternoon",
+            "meeting_time": "Tuesday afternoon",
+            "summary": "Dana agrees to a call on Tuesday afternoon."
+        }));
+        assert_eq!(parsed.company.as_deref(), Some("Smith HVAC"));
+        assert_eq!(parsed.sender_name.as_deref(), Some("Dana Smith"));
+        assert_eq!(parsed.phone.as_deref(), Some("(305) 555-0134"));
+        assert!(context_block(&parsed).contains("- Requested action: Call Dana Tuesday afternoon"));
+
+        // A bare body, webmail sender
+        let parts = EmailParts {
+            sender_email: Some("bob@gmail.com".to_string()),
+            body: "Please remove me from your list".to_string(),
+            ..Default::default()
+        };
+        let parsed = parse_locally(&parts);
+        assert_eq!(parsed.company, None);
+        assert_eq!(parsed.intent, Intent::Unsubscribe);
+    }
+}
@@ -0,0 +1,224 @@
+//! Embeddings - One embedding service for every feature that needs vectors
+//!
+//! `embed` turns texts into vectors with the provider in `embeddings`:
+//! - `openai` / `openai-compatible`: `POST <base>/embeddings`
+//! - `voyage`: Voyage AI, the embeddings Anthropic recommends (`anthropic`
+//!   is accepted as an alias, since Anthropic has no embeddings API itself)
+//! - `ollama`: a local server, `POST <base>/api/embed`, no key needed
+//!
+//! Texts go out in batches of `batch_size`. With a store, vectors are cached
+//! by model and text hash (the `embeddings` table), so the same text is only
+//! embedded once, across restarts.
+//!
+//! Used by the `embeddings.generate` executor, MEMORY.md search
+//! (`embeddings.memory_search`) and history compression.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::EmbeddingsConfig;
+use crate::store::{EmbeddingRecord, Store};
+
+/// Longest text embedded (longer ones are cut)
+const MAX_TEXT_CHARS: usize = 8_000;
+
+enum Provider {
+    OpenAi,
+    Voyage,
+    Ollama,
+}
+
+fn provider(config: &EmbeddingsConfig) -> anyhow::Result<Provider> {
+    match config.provider.as_str() {
+        "openai" | "openai-compatible" => Ok(Provider::OpenAi),
+        "voyage" | "anthropic" => Ok(Provider::Voyage),
+        "ollama" => Ok(Provider::Ollama),
+        other => anyhow::bail!("Unknown embeddings provider: {}", other),
+    }
+}
+
+fn base_url(config: &EmbeddingsConfig) -> anyhow::Result<String> {
+    if let Some(url) = &config.base_url {
+        return Ok(url.trim_end_matches('/').to_string());
+    }
+    match config.provider.as_str() {
+        "openai" => Ok("https://api.openai.com/v1".to_string()),
+        "voyage" | "anthropic" => Ok("https://api.voyageai.com/v1".to_string()),
+        "ollama" => Ok("http://localhost:11434".to_string()),
+        other => anyhow::bail!("embeddings.base_url is required for {}", other),
+    }
+}
+
+fn api_key_env(config: &EmbeddingsConfig) -> Option<&str> {
+    config.api_key_env.as_deref().or(match config.provider.as_str() {
+        "openai" => Some("OPENAI_API_KEY"),
+        "voyage" | "anthropic" => Some("VOYAGE_API_KEY"),
+        _ => None,
+    })
+}
+
+fn api_key(config: &EmbeddingsConfig) -> Option<String> {
+    api_key_env(config).and_then(|env| std::env::var(env).ok()).filter(|key| !key.trim().is_empty())
+}
+
+/// Whether embeddings can be requested: a known provider, with its key if it needs one
+pub fn ready(config: &EmbeddingsConfig) -> bool {
+    match provider(config) {
+        Ok(Provider::Ollama) => true,
+        // A local OpenAI-compatible server may not need a key
+        Ok(_) if config.provider == "openai-compatible" && config.api_key_env.is_none() => true,
+        Ok(_) => api_key(config).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Cosine similarity of two vectors (0 when either is all zeros)
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let (na, nb) = (norm(a), norm(b));
+    if na == 0.0 || nb == 0.0 {
+        return 0.0;
+    }
+    dot / (na * nb)
+}
+
+/// A vector as stored: little-endian f32s
+pub fn encode(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// Cache key of a text
+fn hash(text: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, text.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// One request for `texts`
+async fn request(config: &EmbeddingsConfig, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+    let provider = provider(config)?;
+    let base = base_url(config)?;
+    let (url, body) = match provider {
+        Provider::OpenAi | Provider::Voyage => (format!("{}/embeddings", base), json!({ "model": config.model, "input": texts })),
+        Provider::Ollama => (format!("{}/api/embed", base), json!({ "model": config.model, "input": texts })),
+    };
+    let mut req = reqwest::Client::new().post(&url).json(&body).timeout(std::time::Duration::from_secs(60));
+    if let Some(key) = api_key(config) {
+        req = req.bearer_auth(key);
+    }
+    let resp = req.send().await?.error_for_status()?;
+
+    let embeddings = match provider {
+        Provider::OpenAi | Provider::Voyage => {
+            let mut response: OpenAiResponse = resp.json().await?;
+            response.data.sort_by_key(|d| d.index);
+            response.data.into_iter().map(|d| d.embedding).collect::<Vec<_>>()
+        }
+        Provider::Ollama => resp.json::<OllamaResponse>().await?.embeddings,
+    };
+    if embeddings.len() != texts.len() {
+        anyhow::bail!("Expected {} embeddings, got {}", texts.len(), embeddings.len());
+    }
+    Ok(embeddings)
+}
+
+/// Embed `texts`, in order. Cached vectors are reused, and new ones cached,
+/// when `cache` is given; cache failures only cost a re-embed.
+pub async fn embed(config: &EmbeddingsConfig, cache: Option<&dyn Store>, texts: &[String]) -> anyhow::Result<Vec<Vec<f32>>> {
+    let texts: Vec<String> = texts.iter().map(|t| t.chars().take(MAX_TEXT_CHARS).collect()).collect();
+    let hashes: Vec<String> = texts.iter().map(|t| hash(t)).collect();
+
+    let mut known: HashMap<String, Vec<f32>> = HashMap::new();
+    if let Some(store) = cache {
+        match store.embeddings(&config.model, &hashes).await {
+            Ok(records) => known.extend(records.into_iter().map(|r| (r.hash, r.vector))),
+            Err(e) => tracing::warn!("Embedding cache lookup failed: {}", e),
+        }
+    }
+
+    // Each missing text once, even if it repeats
+    let mut missing: Vec<usize> = Vec::new();
+    for (i, h) in hashes.iter().enumerate() {
+        if !known.contains_key(h) && !missing.iter().any(|&j| hashes[j] == *h) {
+            missing.push(i);
+        }
+    }
+    let mut fresh: Vec<EmbeddingRecord> = Vec::new();
+    for chunk in missing.chunks(config.batch_size.max(1)) {
+        let batch: Vec<String> = chunk.iter().map(|&i| texts[i].clone()).collect();
+        for (&i, vector) in chunk.iter().zip(request(config, &batch).await?) {
+            fresh.push(EmbeddingRecord { hash: hashes[i].clone(), vector });
+        }
+    }
+    if let (Some(store), false) = (cache, fresh.is_empty()) {
+        if let Err(e) = store.add_embeddings(&config.model, &fresh).await {
+            tracing::warn!("Could not cache {} embedding(s): {}", fresh.len(), e);
+        }
+    }
+    known.extend(fresh.into_iter().map(|r| (r.hash, r.vector)));
+
+    Ok(hashes.iter().map(|h| known.get(h).cloned().unwrap_or_default()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_embeddings_skip_the_provider() {
+        let vector = vec![0.25, -1.5, 3.0];
+        assert_eq!(decode(&encode(&vector)), vector);
+        assert!((cosine(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+
+        let path = std::env::temp_dir().join(format!("oneclaw-embeddings-{}.db", nanoid::nanoid!()));
+        let store = crate::store::SqliteStore::new(path.clone()).await.unwrap();
+        let texts = vec!["roofers in Austin".to_string(), "HVAC in Miami".to_string()];
+        let records: Vec<EmbeddingRecord> = texts
+            .iter()
+            .zip([vec![1.0, 0.0], vec![0.0, 1.0]])
+            .map(|(t, vector)| EmbeddingRecord { hash: hash(t), vector })
+            .collect();
+        store.add_embeddings("test-model", &records).await.unwrap();
+
+        // Nothing is requested from the (unreachable) provider when all are cached
+        let config = EmbeddingsConfig {
+            model: "test-model".to_string(),
+            base_url: Some("http://127.0.0.1:9".to_string()),
+            ..Default::default()
+        };
+        let repeated = vec![texts[1].clone(), texts[0].clone(), texts[1].clone()];
+        let embedded = embed(&config, Some(&store), &repeated).await.unwrap();
+        assert_eq!(embedded, vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![0.0, 1.0]]);
+        // Another model's cache doesn't count
+        let other = EmbeddingsConfig { model: "other".to_string(), ..config };
+        assert!(embed(&other, Some(&store), &texts).await.is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -8,6 +9,98 @@ pub struct ExecutorManifest {
     pub version: String,
     pub description: String,
     pub permissions: Vec<String>,
+    #[serde(default)]
+    pub capabilities: ExecutorCapabilities,
+}
+
+/// What an executor can do and needs, for UIs and the planner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutorCapabilities {
+    /// Returns before the work is done (results arrive later, e.g. via a job)
+    #[serde(rename = "async")]
+    pub is_async: bool,
+    /// Emits partial output while running
+    pub streaming: bool,
+    pub cost_model: CostModel,
+    pub required_secrets: Vec<SecretRequirement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Seconds a call may take before it's cancelled (`executors.timeouts` wins)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CostModel {
+    #[default]
+    Free,
+    /// Billed by the LLM provider per token
+    PerToken,
+    /// Depends on what runs (e.g. harness workflows; see the harness tool catalog)
+    Variable,
+}
+
+impl CostModel {
+    /// Whether calls cost money (what `paid_only` policies match)
+    pub fn is_paid(&self) -> bool {
+        !matches!(self, CostModel::Free)
+    }
+}
+
+/// A credential an executor needs before it can run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SecretRequirement {
+    /// Environment variable
+    Env { name: String },
+    /// The LLM provider key (env var named by `llm.api_key_env`); optional for local servers
+    LlmApiKey,
+    /// Google OAuth grant, brokered by the control plane (`control_plane.url`)
+    GoogleOauth,
+    /// A `web_search.backend`, plus its API key where the backend needs one
+    WebSearchBackend,
+}
+
+impl SecretRequirement {
+    pub fn is_satisfied(&self, config: &crate::config::NodeConfig) -> bool {
+        let env_set = |name: &str| std::env::var(name).map(|v| !v.trim().is_empty()).unwrap_or(false);
+        match self {
+            SecretRequirement::Env { name } => env_set(name),
+            SecretRequirement::LlmApiKey => {
+                !crate::llm::needs_api_key(&config.llm.provider) || env_set(&config.llm.api_key_env)
+            }
+            SecretRequirement::GoogleOauth => config.control_plane.url.is_some(),
+            SecretRequirement::WebSearchBackend => {
+                let search = &config.web_search;
+                match search.backend.as_deref() {
+                    Some("searxng") => search.searxng_url.is_some(),
+                    Some(_) => search.key_env().is_some_and(|env| env_set(&env)),
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+/// Manifest plus whether this node can run the executor right now
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutorStatus {
+    #[serde(flatten)]
+    pub manifest: ExecutorManifest,
+    pub runnable: bool,
+    pub missing_secrets: Vec<SecretRequirement>,
+}
+
+/// JSON Schema for a flat object of `(field, type)` pairs
+fn object_schema(fields: &[(&str, &str)]) -> Value {
+    let properties: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|(name, kind)| (name.to_string(), serde_json::json!({ "type": kind })))
+        .collect();
+    serde_json::json!({ "type": "object", "properties": properties })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +123,7 @@ pub struct DenialReason {
 
 pub struct Registry {
     executors: HashMap<String, Box<dyn Executor + Send + Sync>>,
+    plugins: Vec<std::sync::Arc<crate::plugin::Plugin>>,
 }
 
 impl Registry {
@@ -45,28 +139,206 @@ impl Registry {
         
         let mut executors: HashMap<String, Box<dyn Executor + Send + Sync>> = HashMap::new();
         executors.insert("http.request".to_string(), Box::new(HttpExecutor));
-        executors.insert("llm.chat".to_string(), Box::new(LlmExecutor));
-        executors.insert("google.gmail".to_string(), Box::new(GoogleGmailExecutor));
-        executors.insert("harness.execute".to_string(), Box::new(HarnessExecutor::new(harness_url)));
-        Ok(Self { executors })
+        executors.insert("llm.chat".to_string(), Box::new(LlmExecutor::default()));
+        executors.insert("google.gmail".to_string(), Box::new(GoogleGmailExecutor::default()));
+        executors.insert("google.contacts".to_string(), Box::new(GoogleContactsExecutor));
+        executors.insert("calendar.followup".to_string(), Box::new(CalendarFollowupExecutor));
+        executors.insert("web.search".to_string(), Box::new(WebSearchExecutor));
+        executors.insert("email.parse".to_string(), Box::new(EmailParseExecutor::default()));
+        executors.insert("embeddings.generate".to_string(), Box::new(EmbeddingsExecutor { cache: None }));
+        executors.insert("harness.execute".to_string(), Box::new(HarnessExecutor::new(harness_url.clone())));
+        executors.insert("job.control".to_string(), Box::new(JobControlExecutor::new(harness_url)));
+        executors.insert(
+            "outreach.call_script".to_string(),
+            Box::new(OutreachExecutor::new(crate::outreach::DraftKind::CallScript)),
+        );
+        executors.insert(
+            "outreach.voicemail".to_string(),
+            Box::new(OutreachExecutor::new(crate::outreach::DraftKind::Voicemail)),
+        );
+        executors.insert(
+            "scratchpad.read".to_string(),
+            Box::new(ScratchpadExecutor::new(crate::scratchpad::Access::Read)),
+        );
+        executors.insert(
+            "scratchpad.write".to_string(),
+            Box::new(ScratchpadExecutor::new(crate::scratchpad::Access::Write)),
+        );
+        let executors = executors
+            .into_iter()
+            .map(|(id, executor)| {
+                let executor: Box<dyn Executor + Send + Sync> = if LOCAL_EXECUTORS.contains(&id.as_str()) {
+                    executor
+                } else {
+                    Box::new(Shadowed(executor))
+                };
+                (id, executor)
+            })
+            .collect();
+        Ok(Self { executors, plugins: vec![] })
+    }
+
+    /// Add the subprocess executors from `plugins.dir` (see `plugin.rs`); a
+    /// plugin can't replace a built-in executor
+    pub fn with_plugins(mut self, config: &crate::config::PluginsConfig) -> Self {
+        for plugin in crate::plugin::load(config) {
+            let id = plugin.id().to_string();
+            if self.executors.contains_key(&id) {
+                tracing::warn!("Skipping plugin {}: an executor with that id already exists", id);
+                continue;
+            }
+            let executor = crate::plugin::PluginExecutor(std::sync::Arc::clone(&plugin));
+            self.executors.insert(id, Box::new(Shadowed(Box::new(executor))));
+            self.plugins.push(plugin);
+        }
+        self
+    }
+
+    /// Cache `embeddings.generate` vectors in `store`
+    pub fn with_embedding_cache(mut self, store: std::sync::Arc<dyn crate::store::Store>) -> Self {
+        let executor = EmbeddingsExecutor { cache: Some(store) };
+        self.executors.insert("embeddings.generate".to_string(), Box::new(executor));
+        self
+    }
+
+    /// Check and record `google.gmail` and `outreach.*` contacts in `store`
+    /// (see `lead_dedup.rs`)
+    pub fn with_lead_store(mut self, store: std::sync::Arc<dyn crate::store::Store>) -> Self {
+        let gmail = GoogleGmailExecutor { leads: Some(store.clone()) };
+        self.executors.insert("google.gmail".to_string(), Box::new(Shadowed(Box::new(gmail))));
+        for kind in [crate::outreach::DraftKind::CallScript, crate::outreach::DraftKind::Voicemail] {
+            let outreach = OutreachExecutor { kind, leads: Some(store.clone()) };
+            self.executors.insert(outreach.id().to_string(), Box::new(Shadowed(Box::new(outreach))));
+        }
+        self
+    }
+
+    pub fn plugins(&self) -> &[std::sync::Arc<crate::plugin::Plugin>] {
+        &self.plugins
+    }
+
+    /// Tool definitions for the plugins that can run here, for the model
+    pub fn plugin_tools(&self, config: &crate::config::NodeConfig) -> Vec<Value> {
+        self.plugins
+            .iter()
+            .map(|plugin| Self::status(plugin.manifest().clone(), config))
+            .filter(|status| status.runnable)
+            .map(|status| {
+                let manifest = status.manifest;
+                serde_json::json!({
+                    "name": manifest.id,
+                    "description": manifest.description,
+                    "input_schema": manifest.capabilities.input_schema.unwrap_or_else(|| serde_json::json!({ "type": "object" })),
+                })
+            })
+            .collect()
+    }
+
+    /// Send `llm.chat` and `email.parse` calls through the node's `router`
+    pub fn with_router(mut self, router: std::sync::Arc<crate::model_router::ModelRouter>) -> Self {
+        let llm = LlmExecutor { router: router.clone() };
+        self.executors.insert("llm.chat".to_string(), Box::new(llm));
+        self.executors.insert("email.parse".to_string(), Box::new(EmailParseExecutor { router }));
+        self
     }
 
     pub fn get(&self, id: &str) -> Option<&(dyn Executor + Send + Sync)> {
         self.executors.get(id).map(|e| e.as_ref())
     }
 
-    pub fn list(&self) -> Vec<ExecutorManifest> {
-        self.executors.values().map(|e| e.manifest()).collect()
+    /// Every executor with its runnable status, sorted by id
+    pub fn describe(&self, config: &crate::config::NodeConfig) -> Vec<ExecutorStatus> {
+        let mut statuses: Vec<ExecutorStatus> = self
+            .executors
+            .values()
+            .map(|e| Self::status(e.manifest(), config))
+            .collect();
+        statuses.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+        statuses
     }
+
+    pub fn describe_one(&self, id: &str, config: &crate::config::NodeConfig) -> Option<ExecutorStatus> {
+        self.get(id).map(|e| Self::status(e.manifest(), config))
+    }
+
+    fn status(manifest: ExecutorManifest, config: &crate::config::NodeConfig) -> ExecutorStatus {
+        let missing_secrets: Vec<SecretRequirement> = manifest
+            .capabilities
+            .required_secrets
+            .iter()
+            .filter(|s| !s.is_satisfied(config))
+            .cloned()
+            .collect();
+        ExecutorStatus {
+            runnable: missing_secrets.is_empty(),
+            missing_secrets,
+            manifest,
+        }
+    }
+}
+
+/// How long a call to `tool_id` (run by `executor`) may take: its entry in
+/// `executors.timeouts`, else the executor's manifest, else `executors.default_timeout_secs`
+pub fn timeout(config: &crate::config::ExecutorsConfig, tool_id: &str, executor: &ExecutorManifest) -> std::time::Duration {
+    let secs = config
+        .timeouts
+        .get(tool_id)
+        .copied()
+        .or(executor.capabilities.timeout_secs)
+        .unwrap_or(config.default_timeout_secs);
+    std::time::Duration::from_secs(secs.max(1))
 }
 
+/// A tool the node can run. `execute` is async so callers can run it straight
+/// on the runtime and cancel it by dropping the future (e.g. on a timeout).
+#[async_trait]
 pub trait Executor {
     fn manifest(&self) -> ExecutorManifest;
-    fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult;
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult;
+}
+
+/// Executors that only think (LLM calls, drafts, notes) or look things up, and keep running in shadow mode
+const LOCAL_EXECUTORS: &[&str] = &[
+    "email.parse",
+    "embeddings.generate",
+    "llm.chat",
+    "outreach.call_script",
+    "outreach.voicemail",
+    "scratchpad.read",
+    "scratchpad.write",
+    "web.search",
+];
+
+/// Logs the call instead of running it while shadow mode is on
+struct Shadowed(Box<dyn Executor + Send + Sync>);
+
+#[async_trait]
+impl Executor for Shadowed {
+    fn manifest(&self) -> ExecutorManifest {
+        self.0.manifest()
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        if !crate::shadow::active() {
+            return self.0.execute(input, config).await;
+        }
+        let id = self.0.manifest().id;
+        crate::shadow::record(crate::shadow::ShadowKind::ToolCall, &id, input.clone());
+        ExecutorResult::Executed {
+            output: serde_json::json!({
+                "shadow": true,
+                "executed": false,
+                "message": format!("Shadow mode: {} was not run. Tell the user what it would have done.", id),
+                "input": input,
+            }),
+            duration_ms: 0,
+        }
+    }
 }
 
 pub struct HttpExecutor;
 
+#[async_trait]
 impl Executor for HttpExecutor {
     fn manifest(&self) -> ExecutorManifest {
         ExecutorManifest {
@@ -74,10 +346,25 @@ impl Executor for HttpExecutor {
             version: "0.1.0".to_string(),
             description: "HTTP requests (curl parity)".to_string(),
             permissions: vec!["network".to_string()],
+            capabilities: ExecutorCapabilities {
+                input_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "method": { "type": "string", "enum": ["GET", "POST", "PUT", "DELETE"] },
+                        "url": { "type": "string" },
+                        "headers": { "type": "object" },
+                        "body": { "type": "string" },
+                        "sign": { "type": "boolean", "description": "Add node signature headers" }
+                    },
+                    "required": ["url"]
+                })),
+                output_schema: Some(object_schema(&[("status", "integer"), ("body", "string")])),
+                ..Default::default()
+            },
         }
     }
 
-    fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
         let start = std::time::Instant::now();
         let method = input["method"].as_str().unwrap_or("GET");
         let url = match input["url"].as_str() {
@@ -100,7 +387,7 @@ impl Executor for HttpExecutor {
             }
         }
 
-        let client = reqwest::blocking::Client::new();
+        let client = reqwest::Client::new();
         let mut req = match method {
             "POST" => client.post(url),
             "PUT" => client.put(url),
@@ -115,14 +402,22 @@ impl Executor for HttpExecutor {
                 }
             }
         }
-        if let Some(body) = input["body"].as_str() {
-            req = req.body(body.to_string());
+        let body = input["body"].as_str().unwrap_or("").to_string();
+        
+        // Opt-in signing so webhook receivers can verify which node sent the request
+        if input["sign"].as_bool() == Some(true) {
+            for (name, value) in crate::node_key::signed_headers(&config.node.id, method, url, body.as_bytes()) {
+                req = req.header(name, value);
+            }
+        }
+        if input["body"].is_string() {
+            req = req.body(body);
         }
 
-        match req.send() {
+        match req.send().await {
             Ok(resp) => {
                 let status = resp.status().as_u16();
-                let body = resp.text().unwrap_or_default();
+                let body = resp.text().await.unwrap_or_default();
                 ExecutorResult::Executed {
                     output: serde_json::json!({ "status": status, "body": body }),
                     duration_ms: start.elapsed().as_millis() as u64,
@@ -137,423 +432,848 @@ impl Executor for HttpExecutor {
 // LLM Executor - Chat with AI
 // ============================================
 
-pub struct LlmExecutor;
-
-fn extract_text_from_value(value: &Value) -> String {
-    match value {
-        Value::String(s) => s.clone(),
-        Value::Array(items) => {
-            let mut parts: Vec<String> = Vec::new();
-            for item in items {
-                if let Some(s) = item.as_str() {
-                    if !s.trim().is_empty() {
-                        parts.push(s.to_string());
-                    }
-                    continue;
-                }
-                if let Some(s) = item.get("text").and_then(|v| v.as_str()) {
-                    if !s.trim().is_empty() {
-                        parts.push(s.to_string());
-                    }
-                    continue;
-                }
-                if let Some(s) = item.get("content").and_then(|v| v.as_str()) {
-                    if !s.trim().is_empty() {
-                        parts.push(s.to_string());
-                    }
-                    continue;
-                }
-                if let Some(s) = item.get("value").and_then(|v| v.as_str()) {
-                    if !s.trim().is_empty() {
-                        parts.push(s.to_string());
-                    }
-                    continue;
-                }
-                if let Some(nested) = item.get("content") {
-                    let nested_text = extract_text_from_value(nested);
-                    if !nested_text.trim().is_empty() {
-                        parts.push(nested_text);
-                    }
-                }
-            }
-            parts.join("\n")
-        }
-        Value::Object(map) => {
-            for key in ["text", "content", "value"] {
-                if let Some(v) = map.get(key) {
-                    let text = extract_text_from_value(v);
-                    if !text.trim().is_empty() {
-                        return text;
-                    }
-                }
-            }
-            String::new()
+/// Thin wrapper over the configured `llm::LlmProvider`
+#[derive(Default)]
+pub struct LlmExecutor {
+    router: std::sync::Arc<crate::model_router::ModelRouter>,
+}
+
+impl LlmExecutor {
+    /// `llm.chat` output for a reply from `provider` (also built for streamed replies)
+    pub fn output(response: crate::llm::ChatResponse, provider: &str) -> Value {
+        serde_json::json!({
+            "content": response.content,
+            "model": response.model,
+            "provider": provider,
+            "tool_calls": response.tool_calls,
+            "raw": response.raw
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for LlmExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "llm.chat".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Chat with LLM (Anthropic/OpenAI/OpenRouter/OpenAI-compatible)".to_string(),
+            permissions: vec!["network".to_string(), "llm".to_string()],
+            capabilities: ExecutorCapabilities {
+                cost_model: CostModel::PerToken,
+                required_secrets: vec![SecretRequirement::LlmApiKey],
+                input_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "messages": { "type": "array", "items": { "type": "object" } },
+                        "tools": { "type": "array", "items": { "type": "object" } },
+                        "phase": { "type": "string" }
+                    },
+                    "required": ["messages"]
+                })),
+                output_schema: Some(object_schema(&[
+                    ("content", "string"),
+                    ("model", "string"),
+                    ("provider", "string"),
+                    ("tool_calls", "array"),
+                    ("raw", "object"),
+                ])),
+                ..Default::default()
+            },
+        }
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+
+        let messages = match input.get("messages").and_then(|m| m.as_array()) {
+            Some(m) => m.clone(),
+            None => return ExecutorResult::Error { error: "messages required".to_string() },
+        };
+
+        let tools = input.get("tools").and_then(|t| t.as_array()).cloned().unwrap_or_default();
+        let phase = input.get("phase").and_then(|p| p.as_str()).unwrap_or(crate::model_router::MAIN).to_string();
+
+        match self.router.chat(&phase, &config.llm, &messages, &tools).await {
+            Ok((response, provider)) => ExecutorResult::Executed {
+                output: Self::output(response, &provider),
+                duration_ms: start.elapsed().as_millis() as u64,
+            },
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
         }
-        _ => String::new(),
     }
 }
 
-fn collect_string_values(value: &Value, out: &mut Vec<String>) {
-    match value {
-        Value::String(s) => {
-            let trimmed = s.trim();
-            if !trimmed.is_empty() {
-                out.push(trimmed.to_string());
+// ============================================
+// Job Control Executor - pause/resume/skip/cancel autonomous jobs
+// ============================================
+
+pub struct JobControlExecutor {
+    pub harness_url: String,
+}
+
+impl JobControlExecutor {
+    pub fn new(harness_url: String) -> Self {
+        Self { harness_url }
+    }
+
+    /// Tool definition exposed to the LLM when the user has active jobs
+    pub fn tool_schema() -> Value {
+        serde_json::json!({
+            "name": "job.control",
+            "description": "Control one of the user's background jobs (see Active Jobs). Use when the user says things like \"pause that job\", \"resume\", \"skip the enrichment step\" or \"stop the search\".",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "job_id": { "type": "string", "description": "Job id from the Active Jobs list" },
+                    "action": { "type": "string", "enum": ["pause", "resume", "skip", "cancel"] },
+                    "step": { "type": "string", "description": "For skip: step action name (e.g. \"enrich\") or step number. Defaults to the next pending step." }
+                },
+                "required": ["job_id", "action"]
             }
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for JobControlExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "job.control".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Pause, resume, skip steps of, or cancel autonomous jobs".to_string(),
+            permissions: vec!["network".to_string(), "harness".to_string()],
+            capabilities: ExecutorCapabilities {
+                input_schema: Some(Self::tool_schema()["input_schema"].clone()),
+                output_schema: Some(object_schema(&[("job_id", "string"), ("action", "string"), ("status", "string")])),
+                ..Default::default()
+            },
         }
-        Value::Array(items) => {
-            for item in items {
-                collect_string_values(item, out);
-            }
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+
+        let job_id = match input["job_id"].as_str() {
+            Some(id) if !id.is_empty() => id,
+            _ => return ExecutorResult::Error { error: "job_id required".to_string() },
+        };
+        // Set by the daemon after checking the job is the calling user's
+        let Some(user_id) = input["user_id"].as_str().filter(|id| !id.is_empty()) else {
+            return ExecutorResult::Error { error: "Job control needs the calling user".to_string() };
+        };
+        let action = match input["action"].as_str() {
+            Some(a @ ("pause" | "resume" | "skip" | "cancel")) => a,
+            Some(other) => return ExecutorResult::Error { error: format!("Unknown job action: {}", other) },
+            None => return ExecutorResult::Error { error: "action required".to_string() },
+        };
+
+        // Step may be given as an order number or an action name
+        let mut payload = match &input["step"] {
+            Value::Number(n) => serde_json::json!({ "step": n }),
+            Value::String(s) => match s.parse::<u64>() {
+                Ok(n) => serde_json::json!({ "step": n }),
+                Err(_) => serde_json::json!({ "step": s }),
+            },
+            _ => serde_json::json!({}),
+        };
+        payload["userId"] = Value::String(user_id.to_string());
+
+        let url = format!("{}/autonomous-jobs/{}/{}", self.harness_url, job_id, action);
+        let body = serde_json::to_vec(&payload).unwrap_or_default();
+        let mut req = reqwest::Client::new()
+            .post(&url)
+            .header("Content-Type", "application/json");
+        for (name, value) in crate::node_key::signed_headers(&config.node.id, "POST", &url, &body) {
+            req = req.header(name, value);
         }
-        Value::Object(map) => {
-            for (_k, v) in map {
-                collect_string_values(v, out);
+
+        match req.body(body).send().await {
+            Ok(resp) => {
+                let status = resp.status().as_u16();
+                let parsed: Value = resp.json().await.unwrap_or_else(|_| serde_json::json!({}));
+                if status >= 400 {
+                    return ExecutorResult::Error {
+                        error: parsed["error"].as_str()
+                            .map(|e| e.to_string())
+                            .unwrap_or_else(|| format!("Job control failed ({})", status)),
+                    };
+                }
+                ExecutorResult::Executed {
+                    output: serde_json::json!({
+                        "job_id": job_id,
+                        "action": action,
+                        "status": parsed["job"]["status"],
+                    }),
+                    duration_ms: start.elapsed().as_millis() as u64,
+                }
             }
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
         }
-        _ => {}
     }
 }
 
-fn extract_assistant_content(parsed: &Value, provider: &str) -> String {
-    // Anthropic format: content is usually an array of blocks with .text
-    if provider == "anthropic" {
-        let text = extract_text_from_value(&parsed["content"]);
-        if !text.trim().is_empty() {
-            return text;
+// ============================================
+// Outreach Executor - call scripts and voicemail drafts
+// ============================================
+
+pub struct OutreachExecutor {
+    pub kind: crate::outreach::DraftKind,
+    /// Where contacts are checked and recorded
+    leads: Option<std::sync::Arc<dyn crate::store::Store>>,
+}
+
+impl OutreachExecutor {
+    pub fn new(kind: crate::outreach::DraftKind) -> Self {
+        Self { kind, leads: None }
+    }
+
+    fn id(&self) -> &'static str {
+        match self.kind {
+            crate::outreach::DraftKind::CallScript => "outreach.call_script",
+            crate::outreach::DraftKind::Voicemail => "outreach.voicemail",
         }
     }
 
-    // OpenAI/OpenRouter common format.
-    let text = extract_text_from_value(&parsed["choices"][0]["message"]["content"]);
-    if !text.trim().is_empty() {
-        return text;
+    /// Tool definition exposed to the LLM alongside harness tools
+    pub fn tool_schema(kind: crate::outreach::DraftKind) -> Value {
+        let exec = Self::new(kind);
+        let description = match kind {
+            crate::outreach::DraftKind::CallScript => "Draft a phone call script (opener, hook, value, ask, objection handling) for a lead, personalized from its discovery, enrichment and audit data.",
+            crate::outreach::DraftKind::Voicemail => "Draft a short voicemail message for a lead, personalized from its discovery, enrichment and audit data.",
+        };
+        serde_json::json!({
+            "name": exec.id(),
+            "description": description,
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "business": { "type": "object", "description": "Lead record (name, city, rating, reviewCount, website)" },
+                    "contact": { "type": "object", "description": "Decision-maker from enrichment (name, title)" },
+                    "audit": { "type": "object", "description": "Website audit output (score, issues)" },
+                    "sender_name": { "type": "string", "description": "Who is calling. Defaults to the node name." },
+                    "campaign": { "type": "string", "description": "Campaign the call is part of; calls within one campaign aren't duplicates" },
+                    "override_duplicate": { "type": "boolean", "description": "Draft although the business was contacted in another campaign (after the user approved)" }
+                },
+                "required": ["business"]
+            }
+        })
     }
+}
 
-    // Alternative formats seen across some compatible providers/models.
-    let text = extract_text_from_value(&parsed["choices"][0]["text"]);
-    if !text.trim().is_empty() {
-        return text;
+#[async_trait]
+impl Executor for OutreachExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: self.id().to_string(),
+            version: "0.1.0".to_string(),
+            description: match self.kind {
+                crate::outreach::DraftKind::CallScript => "Draft call scripts from lead data".to_string(),
+                crate::outreach::DraftKind::Voicemail => "Draft voicemail text from lead data".to_string(),
+            },
+            permissions: vec!["workspace.read".to_string()],
+            capabilities: ExecutorCapabilities {
+                input_schema: Some(Self::tool_schema(self.kind)["input_schema"].clone()),
+                output_schema: Some(object_schema(&[("business", "string"), ("text", "string"), ("duplicate_of", "string")])),
+                ..Default::default()
+            },
+        }
     }
 
-    let text = extract_text_from_value(&parsed["output_text"]);
-    if !text.trim().is_empty() {
-        return text;
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+
+        if !input["business"].is_object() {
+            return ExecutorResult::Error { error: "business required".to_string() };
+        }
+        let sender_name = input["sender_name"].as_str().unwrap_or(&config.node.name);
+        let contact = Some(&input["contact"]).filter(|c| c.is_object());
+        let audit = Some(&input["audit"]).filter(|a| a.is_object());
+
+        let ctx = crate::outreach::lead_context(&input["business"], contact, audit, sender_name);
+
+        // Businesses reached in another campaign aren't drafted for until the user approves
+        let lead = crate::lead_dedup::Lead::from_business(&input["business"]);
+        let campaign = crate::lead_dedup::campaign(input["campaign"].as_str());
+        let duplicate = crate::lead_dedup::check(self.leads.as_deref(), config, &lead, campaign.as_deref()).await;
+        if let (Some(duplicate), false) = (&duplicate, input["override_duplicate"].as_bool() == Some(true)) {
+            return ExecutorResult::Executed {
+                output: serde_json::json!({
+                    "business": ctx["business_name"],
+                    "duplicate_of": duplicate.describe(),
+                    "message": format!(
+                        "Not drafted: already contacted ({}).\nShow this to the user; draft again with override_duplicate: true only if they approve.",
+                        duplicate.describe()
+                    ),
+                }),
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+        }
+
+        let workspace = crate::agent_os::workspace_for(config);
+        let text = crate::outreach::draft(self.kind, &ctx, workspace.as_deref());
+        let action = match self.kind {
+            crate::outreach::DraftKind::CallScript => "call-script",
+            crate::outreach::DraftKind::Voicemail => "voicemail",
+        };
+        crate::lead_dedup::record(self.leads.as_deref(), &lead, campaign, action, duplicate.as_ref()).await;
+
+        ExecutorResult::Executed {
+            output: serde_json::json!({
+                "business": ctx["business_name"],
+                "text": text,
+            }),
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
     }
+}
+
+// ============================================
+// Scratchpad Executor - per-job working notes
+// ============================================
+
+pub struct ScratchpadExecutor {
+    pub access: crate::scratchpad::Access,
+}
 
-    let text = extract_text_from_value(&parsed["output"]);
-    if !text.trim().is_empty() {
-        return text;
+impl ScratchpadExecutor {
+    pub fn new(access: crate::scratchpad::Access) -> Self {
+        Self { access }
     }
 
-    // Provider-agnostic named fields seen in compatible APIs.
-    for key in ["response", "answer", "assistant", "final", "reasoning"] {
-        let text = extract_text_from_value(&parsed[key]);
-        if !text.trim().is_empty() {
-            return text;
+    fn id(&self) -> &'static str {
+        match self.access {
+            crate::scratchpad::Access::Read => "scratchpad.read",
+            crate::scratchpad::Access::Write => "scratchpad.write",
         }
     }
 
-    // Last resort: deep scan for non-trivial strings and pick the longest.
-    let mut strings = Vec::new();
-    collect_string_values(parsed, &mut strings);
-    strings.retain(|s| s.len() >= 8);
-    if let Some(best) = strings.into_iter().max_by_key(|s| s.len()) {
-        return best;
+    /// Tool definition exposed to the LLM alongside harness tools
+    pub fn tool_schema(access: crate::scratchpad::Access) -> Value {
+        let job = serde_json::json!({ "type": "string", "description": "Job id, or a short name for the task" });
+        let (description, input_schema) = match access {
+            crate::scratchpad::Access::Read => (
+                "Read your working notes for a job. Without a job, lists the scratchpads you have.",
+                serde_json::json!({ "type": "object", "properties": { "job": job } }),
+            ),
+            crate::scratchpad::Access::Write => (
+                "Save working notes for a multi-step job (findings so far, partial results, next steps) so you can pick up where you left off in a later turn. Appends unless replace is true.",
+                serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "job": job,
+                        "text": { "type": "string" },
+                        "replace": { "type": "boolean", "description": "Overwrite the notes instead of appending" }
+                    },
+                    "required": ["job", "text"]
+                }),
+            ),
+        };
+        let exec = Self::new(access);
+        serde_json::json!({ "name": exec.id(), "description": description, "input_schema": input_schema })
     }
-
-    String::new()
 }
 
-impl Executor for LlmExecutor {
+#[async_trait]
+impl Executor for ScratchpadExecutor {
     fn manifest(&self) -> ExecutorManifest {
         ExecutorManifest {
-            id: "llm.chat".to_string(),
+            id: self.id().to_string(),
             version: "0.1.0".to_string(),
-            description: "Chat with LLM (OpenRouter/Anthropic/OpenAI)".to_string(),
-            permissions: vec!["network".to_string(), "llm".to_string()],
+            description: match self.access {
+                crate::scratchpad::Access::Read => "Read per-job working notes".to_string(),
+                crate::scratchpad::Access::Write => "Write per-job working notes".to_string(),
+            },
+            permissions: vec![match self.access {
+                crate::scratchpad::Access::Read => "workspace.read".to_string(),
+                crate::scratchpad::Access::Write => "workspace.write".to_string(),
+            }],
+            capabilities: ExecutorCapabilities {
+                input_schema: Some(Self::tool_schema(self.access)["input_schema"].clone()),
+                ..Default::default()
+            },
         }
     }
 
-    fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
         let start = std::time::Instant::now();
-        
-        // Get messages from input
-        let messages = match input.get("messages") {
-            Some(m) => m.clone(),
-            None => return ExecutorResult::Error { error: "messages required".to_string() },
-        };
-        
-        // Get API key from environment
-        let api_key = match std::env::var(&config.llm.api_key_env) {
-            Ok(k) => k,
-            Err(_) => return ExecutorResult::Error { 
-                error: format!("API key not found in env: {}", config.llm.api_key_env) 
-            },
+        let Some(workspace) = crate::agent_os::workspace_for(config) else {
+            return ExecutorResult::Error { error: "No workspace directory".to_string() };
         };
-        
-        // Build request based on provider
-        let (url, mut body, auth_header) = match config.llm.provider.as_str() {
-            "openrouter" => {
-                let url = "https://openrouter.ai/api/v1/chat/completions";
-                
-                // Extract system message if present (Claude via OpenRouter needs it separate)
-                let mut system_content: Option<String> = None;
-                let mut filtered_messages = vec![];
-                
-                if let Some(msgs) = messages.as_array() {
-                    for msg in msgs {
-                        if msg["role"].as_str() == Some("system") {
-                            system_content = msg["content"].as_str().map(|s| s.to_string());
-                        } else if msg["role"].as_str() == Some("tool") {
-                            // Skip "tool" messages - Claude doesn't support them
-                            // Convert to assistant message instead
-                            let mut assistant_msg = msg.clone();
-                            assistant_msg["role"] = serde_json::json!("assistant");
-                            filtered_messages.push(assistant_msg);
-                        } else {
-                            filtered_messages.push(msg.clone());
-                        }
-                    }
-                }
-                
-                let final_messages = if filtered_messages.is_empty() {
-                    messages.clone()
-                } else {
-                    serde_json::json!(filtered_messages)
-                };
-                
-                let mut body = serde_json::json!({
-                    "model": config.llm.model,
-                    "messages": final_messages,
-                    "max_tokens": 4096
-                });
-                
-                // If using Claude model, add system as separate parameter
-                if config.llm.model.contains("claude") || config.llm.model.contains("anthropic") {
-                    if let Some(sys) = system_content {
-                        body["system"] = serde_json::json!(sys);
-                    }
-                    // Add tools if present
-                    if let Some(tools) = input.get("tools") {
-                        body["tools"] = tools.clone();
-                    }
-                }
-                
-                (url, body, format!("Bearer {}", api_key))
+        let dir = crate::scratchpad::dir(&workspace);
+        let job = input["job"].as_str().filter(|j| !j.trim().is_empty());
+
+        let output = match (self.access, job) {
+            (crate::scratchpad::Access::Read, None) => crate::scratchpad::list(&dir).map(|pads| serde_json::json!({ "scratchpads": pads })),
+            (crate::scratchpad::Access::Read, Some(job)) => {
+                crate::scratchpad::read(&dir, job).map(|notes| serde_json::json!({ "job": job, "notes": notes }))
             }
-            "anthropic" => {
-                let url = "https://api.anthropic.com/v1/messages";
-                
-                // Extract system message (required as separate parameter for Claude API)
-                let mut system_content: Option<String> = None;
-                let mut filtered_messages = vec![];
-                
-                if let Some(msgs) = messages.as_array() {
-                    for msg in msgs {
-                        if msg["role"].as_str() == Some("system") {
-                            system_content = msg["content"].as_str().map(|s| s.to_string());
-                        } else if msg["role"].as_str() == Some("tool") {
-                            // Skip "tool" messages - Claude doesn't support them
-                            // Convert to assistant message instead
-                            let mut assistant_msg = msg.clone();
-                            assistant_msg["role"] = serde_json::json!("assistant");
-                            filtered_messages.push(assistant_msg);
-                        } else {
-                            filtered_messages.push(msg.clone());
-                        }
-                    }
-                }
-                
-                let final_messages = if filtered_messages.is_empty() {
-                    messages.clone()
-                } else {
-                    serde_json::json!(filtered_messages)
+            (crate::scratchpad::Access::Write, None) => Err(anyhow::anyhow!("job required")),
+            (crate::scratchpad::Access::Write, Some(job)) => {
+                let Some(text) = input["text"].as_str() else {
+                    return ExecutorResult::Error { error: "text required".to_string() };
                 };
-                
-                let mut body = serde_json::json!({
-                    "model": config.llm.model,
-                    "messages": final_messages,
-                    "max_tokens": 4096
-                });
-                
-                // Add system as top-level parameter (required by Claude API)
-                if let Some(sys) = system_content {
-                    body["system"] = serde_json::json!(sys);
-                }
-                
-                // Add tools if present
-                if let Some(tools) = input.get("tools") {
-                    body["tools"] = tools.clone();
-                }
-                
-                (url, body, api_key.clone())
+                let replace = input["replace"].as_bool().unwrap_or(false);
+                crate::scratchpad::write(&dir, job, text, replace).map(|chars| serde_json::json!({ "job": job, "chars": chars }))
             }
-            "openai" => {
-                let url = "https://api.openai.com/v1/chat/completions";
-                let body = serde_json::json!({
-                    "model": config.llm.model,
-                    "messages": messages,
-                    "max_tokens": 4096
-                });
-                (url, body, format!("Bearer {}", api_key))
+        };
+        match output {
+            Ok(output) => ExecutorResult::Executed { output, duration_ms: start.elapsed().as_millis() as u64 },
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
+        }
+    }
+}
+
+// ============================================
+// Email Parse Executor - lead data from inbound email
+// ============================================
+
+#[derive(Default)]
+pub struct EmailParseExecutor {
+    router: std::sync::Arc<crate::model_router::ModelRouter>,
+}
+
+impl EmailParseExecutor {
+    /// Tool definition exposed to the LLM alongside harness tools
+    pub fn tool_schema() -> Value {
+        serde_json::json!({
+            "name": "email.parse",
+            "description": "Extract structured data from an email or email thread (e.g. a reply to outreach): sender, company, intent (interested, meeting_request, question, not_interested, unsubscribe, out_of_office, referral), the action they ask for, phone and meeting time. Use it to turn replies into lead updates.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "email": { "type": "string", "description": "Raw email with headers, a pasted thread, or just the body" },
+                    "from": { "type": "string", "description": "Sender address, when `email` has no headers" },
+                    "subject": { "type": "string" }
+                },
+                "required": ["email"]
             }
-            _ => return ExecutorResult::Error { 
-                error: format!("Unknown provider: {}", config.llm.provider) 
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for EmailParseExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "email.parse".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Extract sender, company, intent and requested action from inbound email".to_string(),
+            permissions: vec![],
+            capabilities: ExecutorCapabilities {
+                cost_model: CostModel::PerToken,
+                input_schema: Some(Self::tool_schema()["input_schema"].clone()),
+                output_schema: Some(object_schema(&[
+                    ("sender_email", "string"),
+                    ("company", "string"),
+                    ("intent", "string"),
+                    ("requested_action", "string"),
+                    ("summary", "string"),
+                ])),
+                ..Default::default()
             },
+        }
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+        let Some(email) = input["email"].as_str().filter(|e| !e.trim().is_empty()) else {
+            return ExecutorResult::Error { error: "email required".to_string() };
         };
 
-        // Optional fallback model for transient provider failures.
-        let fallback_model = std::env::var("LLM_FALLBACK_MODEL").ok();
+        let mut parts = crate::email_parse::split(email);
+        if let Some(from) = input["from"].as_str() {
+            parts.sender_email.get_or_insert_with(|| from.trim().to_lowercase());
+        }
+        if let Some(subject) = input["subject"].as_str() {
+            parts.subject.get_or_insert_with(|| subject.trim().to_string());
+        }
+        let parsed = crate::email_parse::parse(&self.router, &parts, &config.tagging.model).await;
 
-        // Timeouts + retry to avoid hanging when provider has transient 5xx issues.
-        let client = match reqwest::blocking::Client::builder()
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .timeout(std::time::Duration::from_secs(45))
-            .build() {
-            Ok(c) => c,
-            Err(e) => return ExecutorResult::Error { error: format!("Failed to build HTTP client: {}", e) },
-        };
+        ExecutorResult::Executed {
+            output: serde_json::to_value(parsed).unwrap_or_default(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+}
 
-        let max_attempts = 3;
-        let mut attempt_error = String::new();
-        let mut used_model = config.llm.model.clone();
-
-        for attempt in 1..=max_attempts {
-            let mut req = client.post(url)
-                .header("Content-Type", "application/json")
-                .json(&body);
-
-            // Add auth header
-            if config.llm.provider == "anthropic" {
-                req = req.header("x-api-key", auth_header.clone())
-                         .header("anthropic-version", "2023-06-01");
-            } else {
-                req = req.header("Authorization", auth_header.clone());
-            }
+// ============================================
+// Embeddings Executor - vectors for search and RAG
+// ============================================
 
-            match req.send() {
-                Ok(resp) => {
-                    let status = resp.status().as_u16();
-                    let body_text = resp.text().unwrap_or_default();
+pub struct EmbeddingsExecutor {
+    cache: Option<std::sync::Arc<dyn crate::store::Store>>,
+}
 
-                    // Retry on provider-side errors.
-                    if status >= 500 && attempt < max_attempts {
-                        attempt_error = format!("LLM API error {} on attempt {}", status, attempt);
-                        std::thread::sleep(std::time::Duration::from_millis(400 * attempt as u64));
-                        continue;
+#[async_trait]
+impl Executor for EmbeddingsExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "embeddings.generate".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Embed texts with the configured embeddings provider (cached by text)".to_string(),
+            permissions: vec![],
+            capabilities: ExecutorCapabilities {
+                cost_model: CostModel::PerToken,
+                input_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "texts": { "type": "array", "items": { "type": "string" } },
+                        "text": { "type": "string", "description": "A single text, instead of `texts`" }
                     }
+                })),
+                output_schema: Some(object_schema(&[
+                    ("embeddings", "array"),
+                    ("model", "string"),
+                    ("dimensions", "integer"),
+                ])),
+                ..Default::default()
+            },
+        }
+    }
 
-                    // Optional fallback model after retries exhausted on 5xx.
-                    if status >= 500 && attempt == max_attempts {
-                        if let Some(fallback) = &fallback_model {
-                            if fallback != &used_model {
-                                used_model = fallback.clone();
-                                body["model"] = serde_json::Value::String(used_model.clone());
-                                attempt_error = format!("Primary model failed with {}, retrying once with fallback model {}", status, used_model);
-                                // One additional fallback request.
-                                let mut fb_req = client.post(url)
-                                    .header("Content-Type", "application/json")
-                                    .json(&body);
-                                if config.llm.provider == "anthropic" {
-                                    fb_req = fb_req.header("x-api-key", auth_header.clone())
-                                                   .header("anthropic-version", "2023-06-01");
-                                } else {
-                                    fb_req = fb_req.header("Authorization", auth_header.clone());
-                                }
-
-                                match fb_req.send() {
-                                    Ok(fb_resp) => {
-                                        let fb_status = fb_resp.status().as_u16();
-                                        let fb_body_text = fb_resp.text().unwrap_or_default();
-                                        if fb_status >= 400 {
-                                            let snippet = fb_body_text.chars().take(500).collect::<String>();
-                                            return ExecutorResult::Error {
-                                                error: format!("LLM API error {} (fallback model {}): {}", fb_status, used_model, snippet),
-                                            };
-                                        }
-
-                                        let parsed: Value = match serde_json::from_str(&fb_body_text) {
-                                            Ok(v) => v,
-                                            Err(e) => return ExecutorResult::Error { error: format!("Parse error (fallback): {}", e) },
-                                        };
-
-                                        let content = extract_assistant_content(&parsed, &config.llm.provider);
-
-                                        return ExecutorResult::Executed {
-                                            output: serde_json::json!({
-                                                "content": content,
-                                                "model": used_model,
-                                                "provider": config.llm.provider,
-                                                "raw": parsed
-                                            }),
-                                            duration_ms: start.elapsed().as_millis() as u64,
-                                        };
-                                    }
-                                    Err(e) => {
-                                        return ExecutorResult::Error { error: format!("Fallback request failed: {}", e) };
-                                    }
-                                }
-                            }
-                        }
-                    }
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+        let texts: Vec<String> = match (input["texts"].as_array(), input["text"].as_str()) {
+            (Some(texts), _) => texts.iter().filter_map(|t| t.as_str()).map(str::to_string).collect(),
+            (None, Some(text)) => vec![text.to_string()],
+            (None, None) => vec![],
+        };
+        if texts.is_empty() {
+            return ExecutorResult::Error { error: "texts required".to_string() };
+        }
+        if !crate::embeddings::ready(&config.embeddings) {
+            return ExecutorResult::Error {
+                error: format!("Embeddings provider {} is not configured (see `embeddings` in node.yaml)", config.embeddings.provider),
+            };
+        }
 
-                    if status >= 400 {
-                        let snippet = body_text.chars().take(500).collect::<String>();
-                        return ExecutorResult::Error { 
-                            error: format!("LLM API error {}: {}", status, snippet) 
-                        };
-                    }
+        match crate::embeddings::embed(&config.embeddings, self.cache.as_deref(), &texts).await {
+            Ok(embeddings) => ExecutorResult::Executed {
+                output: serde_json::json!({
+                    "dimensions": embeddings.first().map_or(0, Vec::len),
+                    "model": config.embeddings.model,
+                    "embeddings": embeddings,
+                }),
+                duration_ms: start.elapsed().as_millis() as u64,
+            },
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
+        }
+    }
+}
 
-                    // Parse response to extract content
-                    let parsed: Value = match serde_json::from_str(&body_text) {
-                        Ok(v) => v,
-                        Err(e) => return ExecutorResult::Error { error: format!("Parse error: {}", e) },
-                    };
+// ============================================
+// Google Gmail Executor
+// ============================================
 
-                    // Extract assistant message based on provider format
-                    let content = extract_assistant_content(&parsed, &config.llm.provider);
-
-                    return ExecutorResult::Executed {
-                        output: serde_json::json!({
-                            "content": content,
-                            "model": used_model,
-                            "provider": config.llm.provider,
-                            "raw": parsed
-                        }),
-                        duration_ms: start.elapsed().as_millis() as u64,
-                    };
+#[derive(Default)]
+pub struct GoogleGmailExecutor {
+    /// Where contacts are checked and recorded
+    leads: Option<std::sync::Arc<dyn crate::store::Store>>,
+}
+
+// ============================================
+// Google Contacts Executor - sync leads to the owner's phone
+// ============================================
+
+pub struct GoogleContactsExecutor;
+
+impl GoogleContactsExecutor {
+    /// Tool definition exposed to the LLM alongside harness tools
+    pub fn tool_schema() -> Value {
+        serde_json::json!({
+            "name": "google.contacts",
+            "description": "Sync leads with the user's Google Contacts (shows up in their phone). \"push\" adds contacts, skipping ones already saved (matched by email or phone); \"pull\" lists existing contacts.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "action": { "type": "string", "enum": ["push", "pull"] },
+                    "contacts": {
+                        "type": "array",
+                        "description": "For push: contacts with name, email, phone, role and company (business name)",
+                        "items": { "type": "object" }
+                    },
+                    "label": { "type": "string", "description": "Contact group to add new contacts to" }
+                },
+                "required": ["action"]
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for GoogleContactsExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "google.contacts".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Two-way sync of leads with Google Contacts".to_string(),
+            permissions: vec!["network".to_string(), "oauth".to_string()],
+            capabilities: ExecutorCapabilities {
+                required_secrets: vec![SecretRequirement::GoogleOauth],
+                input_schema: Some(Self::tool_schema()["input_schema"].clone()),
+                ..Default::default()
+            },
+        }
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+
+        let control_plane_url = match &config.control_plane.url {
+            Some(url) => url,
+            None => return ExecutorResult::Error {
+                error: "control_plane.url not configured".to_string()
+            },
+        };
+        let user_id = input["user_id"].as_str().unwrap_or(&config.node.id);
+
+        let token = match crate::integration::google_access_token(&config.node.id, control_plane_url, user_id).await {
+            Ok(t) => t,
+            Err(e) => return ExecutorResult::Error { error: e.to_string() },
+        };
+        let existing = match crate::google_contacts::list_contacts(&token).await {
+            Ok(c) => c,
+            Err(e) => return ExecutorResult::Error { error: e.to_string() },
+        };
+
+        let output = match input["action"].as_str().unwrap_or("push") {
+            "pull" => serde_json::json!({
+                "count": existing.len(),
+                "contacts": existing,
+            }),
+            "push" => {
+                let leads = input["contacts"].as_array().cloned().unwrap_or_default();
+                if leads.is_empty() {
+                    return ExecutorResult::Error { error: "contacts required for push".to_string() };
                 }
-                Err(e) => {
-                    attempt_error = format!("LLM request failed on attempt {}: {}", attempt, e);
-                    if attempt < max_attempts {
-                        std::thread::sleep(std::time::Duration::from_millis(400 * attempt as u64));
-                        continue;
-                    }
+                let label = input["label"].as_str().or(config.google_contacts.label.as_deref());
+                match crate::google_contacts::push_contacts(&token, &leads, &existing, label).await {
+                    Ok(report) => serde_json::json!(report),
+                    Err(e) => return ExecutorResult::Error { error: e.to_string() },
                 }
             }
+            other => return ExecutorResult::Error { error: format!("Unknown contacts action: {}", other) },
+        };
+
+        ExecutorResult::Executed {
+            output,
+            duration_ms: start.elapsed().as_millis() as u64,
         }
+    }
+}
+
+// ============================================
+// Calendar Follow-up Executor - propose and book follow-up calls
+// ============================================
 
-        ExecutorResult::Error {
-            error: if attempt_error.is_empty() {
-                "LLM request failed after retries".to_string()
-            } else {
-                attempt_error
+pub struct CalendarFollowupExecutor;
+
+impl CalendarFollowupExecutor {
+    /// Tool definition exposed to the LLM (includes "now" so relative days resolve correctly)
+    pub fn tool_schema() -> Value {
+        let now = chrono::Local::now();
+        serde_json::json!({
+            "name": "calendar.followup",
+            "description": format!(
+                "Schedule a follow-up on the owner's Google Calendar, e.g. \"schedule a call with Smith HVAC Thursday 2-4pm\". \
+                 \"propose\" returns free slots inside the window; \"book\" checks availability and creates the event with a reminder. \
+                 Propose first and confirm with the user before booking. Current local time: {}.",
+                now.format("%A %Y-%m-%d %H:%M %:z")
+            ),
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "action": { "type": "string", "enum": ["propose", "book"] },
+                    "window_start": { "type": "string", "description": "RFC 3339 start of the requested window" },
+                    "window_end": { "type": "string", "description": "RFC 3339 end of the requested window" },
+                    "start": { "type": "string", "description": "For book: exact RFC 3339 start of the chosen slot (defaults to the first free slot)" },
+                    "duration_minutes": { "type": "integer" },
+                    "title": { "type": "string", "description": "Event title, e.g. \"Call with Smith HVAC\"" },
+                    "notes": { "type": "string", "description": "Context for the call (phone number, audit highlights)" },
+                    "attendee_email": { "type": "string" }
+                },
+                "required": ["action", "window_start", "window_end"]
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for CalendarFollowupExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "calendar.followup".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Propose and book availability-checked follow-ups on Google Calendar".to_string(),
+            permissions: vec!["network".to_string(), "oauth".to_string()],
+            capabilities: ExecutorCapabilities {
+                required_secrets: vec![SecretRequirement::GoogleOauth],
+                input_schema: Some(Self::tool_schema()["input_schema"].clone()),
+                ..Default::default()
             },
         }
     }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+        let cal = &config.calendar;
+
+        let (window_start, window_end) = match (
+            crate::calendar::parse_time(&input, "window_start"),
+            crate::calendar::parse_time(&input, "window_end"),
+        ) {
+            (Ok(s), Ok(e)) if s < e => (s, e),
+            (Ok(_), Ok(_)) => return ExecutorResult::Error { error: "window_end must be after window_start".to_string() },
+            (Err(e), _) | (_, Err(e)) => return ExecutorResult::Error { error: e.to_string() },
+        };
+        let duration = chrono::Duration::minutes(
+            input["duration_minutes"].as_u64().unwrap_or(cal.default_duration_minutes as u64) as i64,
+        );
+
+        let control_plane_url = match &config.control_plane.url {
+            Some(url) => url,
+            None => return ExecutorResult::Error {
+                error: "control_plane.url not configured".to_string()
+            },
+        };
+        let user_id = input["user_id"].as_str().unwrap_or(&config.node.id);
+        let token = match crate::integration::google_access_token(&config.node.id, control_plane_url, user_id).await {
+            Ok(t) => t,
+            Err(e) => return ExecutorResult::Error { error: e.to_string() },
+        };
+
+        let busy = match crate::calendar::busy_intervals(&token, &cal.calendar_id, window_start, window_end).await {
+            Ok(b) => b,
+            Err(e) => return ExecutorResult::Error { error: e.to_string() },
+        };
+
+        let output = match input["action"].as_str().unwrap_or("propose") {
+            "propose" => {
+                let slots = crate::calendar::free_slots(window_start, window_end, duration, &busy, cal, 3);
+                serde_json::json!({
+                    "available": !slots.is_empty(),
+                    "slots": slots,
+                })
+            }
+            "book" => {
+                let slot = match input["start"].as_str() {
+                    Some(_) => match crate::calendar::parse_time(&input, "start") {
+                        Ok(s) => {
+                            let requested = crate::calendar::Slot { start: s, end: s + duration };
+                            if busy.iter().any(|b| requested.start < b.end && requested.end > b.start) {
+                                return ExecutorResult::Error {
+                                    error: format!("Owner is busy at {} - propose another slot", s.to_rfc3339()),
+                                };
+                            }
+                            requested
+                        }
+                        Err(e) => return ExecutorResult::Error { error: e.to_string() },
+                    },
+                    None => match crate::calendar::free_slots(window_start, window_end, duration, &busy, cal, 1).into_iter().next() {
+                        Some(s) => s,
+                        None => return ExecutorResult::Error { error: "No free slot in that window".to_string() },
+                    },
+                };
+
+                let title = input["title"].as_str().unwrap_or("Follow-up call");
+                match crate::calendar::book(
+                    &token,
+                    &cal.calendar_id,
+                    &slot,
+                    title,
+                    input["notes"].as_str(),
+                    input["attendee_email"].as_str(),
+                    cal.reminder_minutes,
+                ).await {
+                    Ok(event) => serde_json::json!({
+                        "booked": true,
+                        "title": title,
+                        "start": slot.start,
+                        "end": slot.end,
+                        "reminder_minutes": cal.reminder_minutes,
+                        "event_id": event["id"],
+                        "link": event["htmlLink"],
+                    }),
+                    Err(e) => return ExecutorResult::Error { error: e.to_string() },
+                }
+            }
+            other => return ExecutorResult::Error { error: format!("Unknown calendar action: {}", other) },
+        };
+
+        ExecutorResult::Executed {
+            output,
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
 }
 
 // ============================================
-// Google Gmail Executor
+// Web Search Executor - current events and competitor lookups
 // ============================================
 
-pub struct GoogleGmailExecutor;
+pub struct WebSearchExecutor;
+
+impl WebSearchExecutor {
+    /// Tool definition exposed to the LLM alongside harness tools
+    pub fn tool_schema() -> Value {
+        serde_json::json!({
+            "name": "web.search",
+            "description": "Search the web for current events, competitors, prices or anything after your training data. Returns titles, URLs and snippets.",
+            "input_schema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "count": { "type": "integer", "description": "Number of results (default 5, max 20)" }
+                },
+                "required": ["query"]
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl Executor for WebSearchExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        ExecutorManifest {
+            id: "web.search".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Web search via Brave, SerpAPI or SearXNG".to_string(),
+            permissions: vec!["network".to_string()],
+            capabilities: ExecutorCapabilities {
+                cost_model: CostModel::Variable,
+                required_secrets: vec![SecretRequirement::WebSearchBackend],
+                input_schema: Some(Self::tool_schema()["input_schema"].clone()),
+                ..Default::default()
+            },
+        }
+    }
+
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+        let start = std::time::Instant::now();
+        let query = match input["query"].as_str().filter(|q| !q.trim().is_empty()) {
+            Some(q) => q,
+            None => return ExecutorResult::Error { error: "query required".to_string() },
+        };
+        let count = input["count"].as_u64().map(|c| c as usize);
+
+        match crate::web_search::search(&config.web_search, query, count).await {
+            Ok(results) => ExecutorResult::Executed {
+                output: serde_json::json!({ "query": query, "results": results }),
+                duration_ms: start.elapsed().as_millis() as u64,
+            },
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
+        }
+    }
+}
 
 // ============================================
 // Harness Executor - Bridge to TypeScript
 // ============================================
 
+/// Long workflows get 5 minutes
+const HARNESS_TIMEOUT_SECS: u64 = 300;
+
 pub struct HarnessExecutor {
     pub harness_url: String,
 }
@@ -564,6 +1284,7 @@ impl HarnessExecutor {
     }
 }
 
+#[async_trait]
 impl Executor for HarnessExecutor {
     fn manifest(&self) -> ExecutorManifest {
         ExecutorManifest {
@@ -571,10 +1292,25 @@ impl Executor for HarnessExecutor {
             version: "0.1.0".to_string(),
             description: "Execute workflows on the TypeScript Harness".to_string(),
             permissions: vec!["network".to_string(), "harness".to_string()],
+            capabilities: ExecutorCapabilities {
+                cost_model: CostModel::Variable,
+                input_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "executor": { "type": "string", "description": "Harness workflow id" },
+                        "params": { "type": "object" },
+                        "tenant_id": { "type": "string" },
+                        "tier": { "type": "string" }
+                    },
+                    "required": ["executor"]
+                })),
+                timeout_secs: Some(HARNESS_TIMEOUT_SECS),
+                ..Default::default()
+            },
         }
     }
     
-    fn execute(&self, input: Value, _config: &crate::config::NodeConfig) -> ExecutorResult {
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
         let start = std::time::Instant::now();
         
         let executor_id = match input["executor"].as_str() {
@@ -586,10 +1322,10 @@ impl Executor for HarnessExecutor {
         let tenant_id = input["tenant_id"].as_str().unwrap_or("default");
         let tier = input["tier"].as_str().unwrap_or("pro");
         
-        let client = reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(300)) // 5 min timeout for long workflows
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(HARNESS_TIMEOUT_SECS))
             .build()
-            .unwrap_or_else(|_| reqwest::blocking::Client::new());
+            .unwrap_or_else(|_| reqwest::Client::new());
         
         // Collect secrets from environment variables
         let mut secrets = serde_json::Map::new();
@@ -618,15 +1354,18 @@ impl Executor for HarnessExecutor {
         });
         
         let url = format!("{}/execute", self.harness_url);
+        let body = serde_json::to_vec(&payload).unwrap_or_default();
+        
+        let mut req = client.post(&url)
+            .header("Content-Type", "application/json");
+        for (name, value) in crate::node_key::signed_headers(&config.node.id, "POST", &url, &body) {
+            req = req.header(name, value);
+        }
         
-        match client.post(&url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-        {
+        match req.body(body).send().await {
             Ok(resp) => {
                 let status = resp.status().as_u16();
-                let body_text = resp.text().unwrap_or_default();
+                let body_text = resp.text().await.unwrap_or_default();
                 
                 if status >= 400 {
                     return ExecutorResult::Error { 
@@ -644,10 +1383,6 @@ impl Executor for HarnessExecutor {
                     };
                 }
                 
-                // NOTE: Monitor+heal spawn removed from here - execute() runs in spawn_blocking,
-                // and tokio::task::spawn from a blocking thread causes runtime panic. Re-enable
-                // by having the daemon spawn the monitor in async context after getting the response.
-                
                 ExecutorResult::Executed {
                     output: parsed,
                     duration_ms: start.elapsed().as_millis() as u64,
@@ -658,6 +1393,7 @@ impl Executor for HarnessExecutor {
     }
 }
 
+#[async_trait]
 impl Executor for GoogleGmailExecutor {
     fn manifest(&self) -> ExecutorManifest {
         ExecutorManifest {
@@ -665,10 +1401,41 @@ impl Executor for GoogleGmailExecutor {
             version: "0.1.0".to_string(),
             description: "Send emails via Gmail API".to_string(),
             permissions: vec!["network".to_string(), "oauth".to_string()],
+            capabilities: ExecutorCapabilities {
+                required_secrets: vec![SecretRequirement::GoogleOauth],
+                input_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "user_id": { "type": "string" },
+                        "to": { "type": "string" },
+                        "subject": { "type": "string" },
+                        "body": { "type": "string" },
+                        "from_name": { "type": "string" },
+                        "gmail_account_id": { "type": "string" },
+                        "from": { "type": "string", "description": "Sending address, for the SPF/DKIM check" },
+                        "confirmed": { "type": "boolean", "description": "Send despite deliverability warnings (after the user approved)" },
+                        "business_name": { "type": "string", "description": "Business the email is for, to check it wasn't contacted before" },
+                        "campaign": { "type": "string", "description": "Campaign the email is part of; emails within one campaign aren't duplicates" },
+                        "override_duplicate": { "type": "boolean", "description": "Send although the business was contacted in another campaign (after the user approved)" }
+                    },
+                    "required": ["user_id", "to", "body"]
+                })),
+                output_schema: Some(object_schema(&[
+                    ("success", "boolean"),
+                    ("to", "string"),
+                    ("subject", "string"),
+                    ("gmail_message_id", "string"),
+                    ("sent_at", "string"),
+                    ("held", "boolean"),
+                    ("warnings", "array"),
+                    ("duplicate_of", "string"),
+                ])),
+                ..Default::default()
+            },
         }
     }
     
-    fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
+    async fn execute(&self, input: Value, config: &crate::config::NodeConfig) -> ExecutorResult {
         let start = std::time::Instant::now();
         
         // Extract required fields
@@ -699,8 +1466,53 @@ impl Executor for GoogleGmailExecutor {
             },
         };
         
+        // Businesses reached in another campaign: held until the user approves
+        let lead = crate::lead_dedup::Lead::from_email(to, input["business_name"].as_str());
+        let campaign = crate::lead_dedup::campaign(input["campaign"].as_str());
+        let duplicate = crate::lead_dedup::check(self.leads.as_deref(), config, &lead, campaign.as_deref()).await;
+        let overridden = input["override_duplicate"].as_bool() == Some(true);
+        if let (Some(duplicate), false) = (&duplicate, overridden) {
+            return ExecutorResult::Executed {
+                output: serde_json::json!({
+                    "success": false,
+                    "held": true,
+                    "to": to,
+                    "subject": subject,
+                    "duplicate_of": duplicate.describe(),
+                    "message": format!(
+                        "Not sent: already contacted ({}).\nShow this to the user; send again with override_duplicate: true only if they approve.",
+                        duplicate.describe()
+                    ),
+                }),
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+        }
+        
+        // Deliverability: held until the user approves sending despite warnings
+        let checks = &config.deliverability;
+        let report = match checks.enabled {
+            true => crate::deliverability::check(checks, input["from"].as_str(), to, subject, body).await,
+            false => crate::deliverability::Report::default(),
+        };
+        if !report.is_clean() && checks.hold_on_warnings && input["confirmed"].as_bool() != Some(true) {
+            return ExecutorResult::Executed {
+                output: serde_json::json!({
+                    "success": false,
+                    "held": true,
+                    "to": to,
+                    "subject": subject,
+                    "warnings": report.warnings,
+                    "message": format!(
+                        "Not sent: deliverability warnings.\n{}\nShow these to the user; send again with confirmed: true only if they approve.",
+                        report.summary()
+                    ),
+                }),
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+        }
+        
         // Call Harness API to send email
-        let client = reqwest::blocking::Client::new();
+        let client = reqwest::Client::new();
         let mut payload = serde_json::json!({
             "user_id": user_id,
             "to": to,
@@ -716,16 +1528,20 @@ impl Executor for GoogleGmailExecutor {
             payload["gmail_account_id"] = Value::String(account_id.to_string());
         }
         
-        let result = client
-            .post(format!("{}/api/v1/oauth/google/send", control_plane_url))
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send();
+        let url = format!("{}/api/v1/oauth/google/send", control_plane_url);
+        let payload_bytes = serde_json::to_vec(&payload).unwrap_or_default();
+        let mut req = client
+            .post(&url)
+            .header("Content-Type", "application/json");
+        for (name, value) in crate::node_key::signed_headers(&config.node.id, "POST", &url, &payload_bytes) {
+            req = req.header(name, value);
+        }
+        let result = req.body(payload_bytes).send().await;
         
         match result {
             Ok(resp) => {
                 let status = resp.status().as_u16();
-                let body_text = resp.text().unwrap_or_default();
+                let body_text = resp.text().await.unwrap_or_default();
                 
                 if status >= 400 {
                     return ExecutorResult::Error {
@@ -735,6 +1551,7 @@ impl Executor for GoogleGmailExecutor {
                 
                 let parsed: Value = serde_json::from_str(&body_text)
                     .unwrap_or_else(|_| serde_json::json!({ "raw": body_text }));
+                crate::lead_dedup::record(self.leads.as_deref(), &lead, campaign, "email", duplicate.as_ref()).await;
                 
                 ExecutorResult::Executed {
                     output: serde_json::json!({
@@ -743,6 +1560,7 @@ impl Executor for GoogleGmailExecutor {
                         "subject": subject,
                         "gmail_message_id": parsed["gmail_message_id"],
                         "sent_at": parsed["sent_at"],
+                        "warnings": report.warnings,
                     }),
                     duration_ms: start.elapsed().as_millis() as u64,
                 }
@@ -752,3 +1570,24 @@ impl Executor for GoogleGmailExecutor {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_precedence() {
+        let mut config: crate::config::ExecutorsConfig = serde_yaml::from_str("enabled: []").unwrap();
+        let harness = HarnessExecutor::new("http://localhost:9000".to_string()).manifest();
+        let http = HttpExecutor.manifest();
+
+        assert_eq!(timeout(&config, "http.request", &http).as_secs(), 120);
+        assert_eq!(timeout(&config, "enrich-contacts", &harness).as_secs(), HARNESS_TIMEOUT_SECS);
+
+        config.timeouts.insert("enrich-contacts".to_string(), 30);
+        config.default_timeout_secs = 10;
+        assert_eq!(timeout(&config, "enrich-contacts", &harness).as_secs(), 30);
+        assert_eq!(timeout(&config, "google-maps-search", &harness).as_secs(), HARNESS_TIMEOUT_SECS);
+        assert_eq!(timeout(&config, "http.request", &http).as_secs(), 10);
+    }
+}
@@ -0,0 +1,80 @@
+//! Followup - Turning tool results into the final reply
+//!
+//! After a turn executes tools, each channel decides how the user sees the
+//! results (`channels.<name>.followup`):
+//!
+//! - `skip`     - send the main LLM reply as-is (template fallback if it's empty)
+//! - `template` - render each result locally, no extra LLM call
+//! - `llm`      - ask the LLM to summarize with a channel-specific prompt
+//!
+//! A tool's own `formattedResponse` always wins over `template` and `llm`.
+
+use serde_json::Value;
+
+use crate::config::FollowupConfig;
+
+/// Most characters of a single tool output shown to the LLM or a template
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+const DEFAULT_TEMPLATE: &str = "**{tool}**\n{output}";
+
+const DEFAULT_PROMPT: &str = "Summarize these results for the user in plain language. No tool blocks.";
+
+const TELEGRAM_PROMPT: &str = "Format these results for Telegram:\n\
+    - **Bold** business names\n\
+    - ⭐ ratings, 📞 phones\n\
+    - ✅/❌ for features\n\
+    - Quick stats summary\n\
+    No tool blocks.";
+
+const VOICE_PROMPT: &str = "Summarize these results in one to three short spoken sentences. \
+    No markdown, lists or links.";
+
+/// Summarization instruction for a channel (configured prompt or the channel default)
+pub fn prompt_for<'a>(policy: &'a FollowupConfig, channel: &str) -> &'a str {
+    policy.prompt.as_deref().unwrap_or(match channel {
+        "telegram" => TELEGRAM_PROMPT,
+        "voice" => VOICE_PROMPT,
+        _ => DEFAULT_PROMPT,
+    })
+}
+
+/// Pre-formatted reply a tool returned itself (harness `output.formattedResponse`)
+pub fn pre_formatted(outputs: &[&Value]) -> Option<String> {
+    outputs
+        .iter()
+        .filter_map(|output| output["output"]["formattedResponse"].as_str())
+        .find(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Render `(tool, output)` pairs with a template (`{tool}`, `{output}`)
+pub fn render_template(policy: &FollowupConfig, results: &[(&str, &Value)]) -> String {
+    let template = policy.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+    results
+        .iter()
+        .map(|(tool, output)| template.replace("{tool}", tool).replace("{output}", &truncated_json(output)))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Conversation plus tool results and the summarization prompt, ready for `llm.chat`
+pub fn llm_messages(messages: &[Value], results: &[(&str, &Value)], prompt: &str) -> Vec<Value> {
+    let mut new_messages = messages.to_vec();
+    for (tool, output) in results {
+        new_messages.push(serde_json::json!({
+            "role": "system",
+            "content": format!("[Tool Result: {}]\n{}", tool, truncated_json(output)),
+        }));
+    }
+    new_messages.push(serde_json::json!({ "role": "user", "content": prompt }));
+    new_messages
+}
+
+pub fn truncated_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value)
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_OUTPUT_CHARS)
+        .collect()
+}
@@ -0,0 +1,208 @@
+//! Google Contacts - Two-way sync of enriched leads with the owner's address book
+//!
+//! Access tokens come from the control plane (see `integration::google_access_token`),
+//! the same place the Gmail executor gets its credentials. Existing contacts are pulled
+//! first so leads already in the phone (matched by email or phone number) are not
+//! duplicated.
+
+use serde::Serialize;
+use serde_json::Value;
+
+const PEOPLE_API: &str = "https://people.googleapis.com/v1";
+
+/// A contact already in the owner's Google account
+#[derive(Debug, Clone, Serialize)]
+pub struct ExistingContact {
+    pub resource_name: String,
+    pub name: Option<String>,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+}
+
+/// Outcome of a push
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+/// Pull every contact in the owner's address book
+pub async fn list_contacts(token: &str) -> anyhow::Result<Vec<ExistingContact>> {
+    let client = reqwest::Client::new();
+    let mut contacts = vec![];
+    let mut page_token: Option<String> = None;
+
+    loop {
+        let mut req = client
+            .get(format!("{}/people/me/connections", PEOPLE_API))
+            .bearer_auth(token)
+            .query(&[("personFields", "names,emailAddresses,phoneNumbers"), ("pageSize", "1000")]);
+        if let Some(pt) = &page_token {
+            req = req.query(&[("pageToken", pt.as_str())]);
+        }
+
+        let resp = req.send().await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("People API error {}: {}", resp.status(), resp.text().await.unwrap_or_default());
+        }
+        let page: Value = resp.json().await?;
+
+        for person in page["connections"].as_array().into_iter().flatten() {
+            let values = |field: &str| -> Vec<String> {
+                person[field]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v["value"].as_str().map(str::to_string))
+                    .collect()
+            };
+            contacts.push(ExistingContact {
+                resource_name: person["resourceName"].as_str().unwrap_or_default().to_string(),
+                name: person["names"][0]["displayName"].as_str().map(str::to_string),
+                emails: values("emailAddresses"),
+                phones: values("phoneNumbers"),
+            });
+        }
+
+        match page["nextPageToken"].as_str() {
+            Some(next) => page_token = Some(next.to_string()),
+            None => break,
+        }
+    }
+    Ok(contacts)
+}
+
+/// Push leads into Google Contacts, skipping any already present.
+///
+/// Each lead is a harness contact (`name`, `email`, `phone`, `role`) optionally
+/// carrying `company` (the business name).
+pub async fn push_contacts(
+    token: &str,
+    leads: &[Value],
+    existing: &[ExistingContact],
+    label: Option<&str>,
+) -> anyhow::Result<SyncReport> {
+    let client = reqwest::Client::new();
+    let group = match label {
+        Some(label) => Some(ensure_group(&client, token, label).await?),
+        None => None,
+    };
+
+    let mut known_emails: Vec<String> = existing.iter().flat_map(|c| c.emails.iter().map(|e| e.to_lowercase())).collect();
+    let mut known_phones: Vec<String> = existing.iter().flat_map(|c| c.phones.iter().map(|p| normalize_phone(p))).collect();
+
+    let mut report = SyncReport::default();
+    for lead in leads {
+        let display = lead["name"]
+            .as_str()
+            .or(lead["company"].as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        let email = lead["email"].as_str().map(str::to_lowercase);
+        let phone = lead["phone"].as_str().map(normalize_phone).filter(|p| !p.is_empty());
+
+        if email.is_none() && phone.is_none() {
+            report.skipped.push(format!("{} (no email or phone)", display));
+            continue;
+        }
+        let duplicate = email.as_ref().is_some_and(|e| known_emails.contains(e))
+            || phone.as_ref().is_some_and(|p| known_phones.contains(p));
+        if duplicate {
+            report.skipped.push(display);
+            continue;
+        }
+
+        let mut person = serde_json::json!({
+            "names": [{ "unstructuredName": display }],
+        });
+        if let Some(e) = lead["email"].as_str() {
+            person["emailAddresses"] = serde_json::json!([{ "value": e, "type": "work" }]);
+        }
+        if let Some(p) = lead["phone"].as_str() {
+            person["phoneNumbers"] = serde_json::json!([{ "value": p, "type": "work" }]);
+        }
+        if lead["company"].is_string() || lead["role"].is_string() {
+            person["organizations"] = serde_json::json!([{ "name": lead["company"], "title": lead["role"] }]);
+        }
+        if let Some(group) = &group {
+            person["memberships"] = serde_json::json!([{ "contactGroupMembership": { "contactGroupResourceName": group } }]);
+        }
+
+        let resp = client
+            .post(format!("{}/people:createContact", PEOPLE_API))
+            .bearer_auth(token)
+            .json(&person)
+            .send()
+            .await;
+        match resp {
+            Ok(r) if r.status().is_success() => {
+                known_emails.extend(email);
+                known_phones.extend(phone);
+                report.created.push(display);
+            }
+            Ok(r) => report.failed.push(format!("{} ({})", display, r.status())),
+            Err(e) => report.failed.push(format!("{} ({})", display, e)),
+        }
+    }
+    Ok(report)
+}
+
+/// Leads from a finished job's results, with the business name attached as company
+pub fn leads_from_results(results: &Value) -> Vec<Value> {
+    let businesses = results["businesses"].as_array().cloned().unwrap_or_default();
+    results["contacts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|contact| {
+            let mut lead = contact.clone();
+            if let Some(business) = businesses.iter().find(|b| b["id"] == contact["businessId"]) {
+                lead["company"] = business["name"].clone();
+                if lead["phone"].is_null() {
+                    lead["phone"] = business["phone"].clone();
+                }
+            }
+            lead
+        })
+        .collect()
+}
+
+/// Find or create a contact group, returning its resource name
+async fn ensure_group(client: &reqwest::Client, token: &str, label: &str) -> anyhow::Result<String> {
+    let groups: Value = client
+        .get(format!("{}/contactGroups", PEOPLE_API))
+        .bearer_auth(token)
+        .query(&[("pageSize", "1000")])
+        .send()
+        .await?
+        .json()
+        .await?;
+    if let Some(found) = groups["contactGroups"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|g| g["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(label)))
+    {
+        return Ok(found["resourceName"].as_str().unwrap_or_default().to_string());
+    }
+
+    let created: Value = client
+        .post(format!("{}/contactGroups", PEOPLE_API))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "contactGroup": { "name": label } }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    created["resourceName"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("Failed to create contact group '{}'", label))
+}
+
+/// Compare phone numbers by their last 10 digits ("+1 (303) 555-0100" == "303-555-0100")
+fn normalize_phone(phone: &str) -> String {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits[digits.len().saturating_sub(10)..].to_string()
+}
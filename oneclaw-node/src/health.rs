@@ -0,0 +1,209 @@
+//! Health - Liveness and readiness probes
+//!
+//! - `/livez`: the process is up and serving HTTP
+//! - `/readyz`: the node can actually serve a chat turn (store reachable,
+//!   config valid, LLM provider reachable, channels connected)
+//!
+//! Container orchestrators should route traffic on `/readyz` and restart on `/livez`.
+//!
+//! The HTTP server starts before harness discovery, heartbeat and channels, which
+//! come up in the background and report progress via `set_component`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::channels::Channel;
+use crate::{config, store};
+
+/// How long an LLM reachability result is reused before probing again
+const LLM_PROBE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self { ok: true, detail: detail.into() }
+    }
+
+    fn fail(detail: impl Into<String>) -> Self {
+        Self { ok: false, detail: detail.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: HashMap<String, CheckResult>,
+}
+
+/// Startup status of a background component (harness, heartbeat, channels)
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state", content = "detail", rename_all = "lowercase")]
+pub enum ComponentStatus {
+    Starting,
+    Ready(String),
+    Failed(String),
+}
+
+/// Restarts a failed component (e.g. re-runs a channel listener)
+pub type Restart = Arc<dyn Fn() + Send + Sync>;
+
+/// Shared health state: registered channels, startup progress and cached probe results
+#[derive(Default)]
+pub struct HealthState {
+    channels: RwLock<Vec<Arc<dyn Channel>>>,
+    components: RwLock<HashMap<String, ComponentStatus>>,
+    restarts: RwLock<HashMap<String, Restart>>,
+    llm_probe: Mutex<Option<(Instant, CheckResult)>>,
+}
+
+impl HealthState {
+    /// Record the startup status of a background component
+    pub async fn set_component(&self, name: &str, status: ComponentStatus) {
+        self.components.write().await.insert(name.to_string(), status);
+    }
+
+    /// Snapshot of all component statuses
+    pub async fn components(&self) -> HashMap<String, ComponentStatus> {
+        self.components.read().await.clone()
+    }
+
+    /// Track a channel so readiness reflects its connection state
+    pub async fn register_channel(&self, channel: Arc<dyn Channel>) {
+        self.channels.write().await.push(channel);
+    }
+
+    /// A registered channel by name ("telegram", "slack", ...)
+    pub async fn channel(&self, name: &str) -> Option<Arc<dyn Channel>> {
+        self.channels.read().await.iter().find(|c| c.channel_type().to_string() == name).cloned()
+    }
+
+    /// Let self-check restart this component after it fails
+    pub async fn register_restart(&self, name: &str, restart: Restart) {
+        self.restarts.write().await.insert(name.to_string(), restart);
+    }
+
+    /// Restart a component that has a restart hook; false if it has none
+    pub async fn restart(&self, name: &str) -> bool {
+        let Some(restart) = self.restarts.read().await.get(name).cloned() else {
+            return false;
+        };
+        // Marked before the restart so a fresh failure isn't overwritten
+        self.set_component(name, ComponentStatus::Ready("restarted".to_string())).await;
+        restart();
+        true
+    }
+
+    pub async fn readiness(&self, config: &config::NodeConfig, store: &Arc<dyn store::Store>) -> Readiness {
+        let mut checks = HashMap::new();
+        checks.insert("store".to_string(), check_store(store).await);
+        checks.insert("config".to_string(), check_config(config));
+        checks.insert("llm".to_string(), self.check_llm(config).await);
+        checks.insert("channels".to_string(), self.check_channels().await);
+        checks.insert("startup".to_string(), self.check_startup().await);
+
+        Readiness {
+            ready: checks.values().all(|c| c.ok),
+            checks,
+        }
+    }
+
+    async fn check_llm(&self, config: &config::NodeConfig) -> CheckResult {
+        let mut cached = self.llm_probe.lock().await;
+        if let Some((at, result)) = cached.as_ref() {
+            if at.elapsed() < LLM_PROBE_TTL {
+                return result.clone();
+            }
+        }
+
+        let result = probe_llm(config).await;
+        *cached = Some((Instant::now(), result.clone()));
+        result
+    }
+
+    async fn check_startup(&self) -> CheckResult {
+        let components = self.components.read().await;
+        let pending: Vec<String> = components
+            .iter()
+            .filter_map(|(name, status)| match status {
+                ComponentStatus::Ready(_) => None,
+                ComponentStatus::Starting => Some(format!("{} starting", name)),
+                ComponentStatus::Failed(e) => Some(format!("{} failed: {}", name, e)),
+            })
+            .collect();
+
+        if pending.is_empty() {
+            CheckResult::ok(format!("{} component(s) up", components.len()))
+        } else {
+            CheckResult::fail(pending.join("; "))
+        }
+    }
+
+    async fn check_channels(&self) -> CheckResult {
+        let channels = self.channels.read().await;
+        let down: Vec<String> = channels
+            .iter()
+            .filter(|c| !c.is_connected())
+            .map(|c| c.channel_type().to_string())
+            .collect();
+
+        if down.is_empty() {
+            CheckResult::ok(format!("{} channel(s) connected", channels.len()))
+        } else {
+            CheckResult::fail(format!("not connected: {}", down.join(", ")))
+        }
+    }
+}
+
+async fn check_store(store: &Arc<dyn store::Store>) -> CheckResult {
+    match store.get_user("__readyz__").await {
+        Ok(_) => CheckResult::ok("reachable"),
+        Err(e) => CheckResult::fail(e.to_string()),
+    }
+}
+
+fn check_config(config: &config::NodeConfig) -> CheckResult {
+    if config.llm.provider == "bedrock" {
+        return match crate::sigv4::Credentials::from_env() {
+            Ok(_) => CheckResult::ok(format!("provider bedrock ({})", crate::llm::bedrock_region(&config.llm))),
+            Err(e) => CheckResult::fail(format!("AWS credentials missing: {}", e)),
+        };
+    }
+    if !crate::llm::needs_api_key(&config.llm.provider) {
+        return CheckResult::ok(format!("provider {}", config.llm.provider));
+    }
+    match std::env::var(&config.llm.api_key_env) {
+        Ok(key) if !key.trim().is_empty() => CheckResult::ok(format!("provider {}", config.llm.provider)),
+        _ => CheckResult::fail(format!("LLM API key missing: {}", config.llm.api_key_env)),
+    }
+}
+
+async fn probe_llm(config: &config::NodeConfig) -> CheckResult {
+    let url = match (config.llm.provider.as_str(), crate::llm::base_url(&config.llm)) {
+        ("anthropic" | "openrouter" | "openai" | "openai-compatible", Some(base)) => format!("{}/models", base),
+        ("ollama", Some(base)) => format!("{}/api/tags", base),
+        ("bedrock", _) => format!("https://bedrock-runtime.{}.amazonaws.com/", crate::llm::bedrock_region(&config.llm)),
+        (other, _) => return CheckResult::fail(format!("unknown provider or missing llm.base_url: {}", other)),
+    };
+
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(3)).build() {
+        Ok(c) => c,
+        Err(e) => return CheckResult::fail(e.to_string()),
+    };
+
+    // Any HTTP response (even 401) means the provider is reachable
+    match client.get(url).send().await {
+        Ok(resp) if resp.status().is_server_error() => {
+            CheckResult::fail(format!("{} returned {}", config.llm.provider, resp.status()))
+        }
+        Ok(_) => CheckResult::ok(format!("{} reachable", config.llm.provider)),
+        Err(e) => CheckResult::fail(format!("{} unreachable: {}", config.llm.provider, e)),
+    }
+}
@@ -1,12 +1,17 @@
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::interval;
-use crate::{agent_os, config, conversation, executor, identity};
+use crate::{agent_os, config, conversation, executor, identity, self_check};
 
 pub struct HeartbeatConfig {
     pub enabled: bool,
     pub interval_secs: u64,
     pub target_channel: String,
+    /// Run store/LLM/harness/disk checks and remediations each tick
+    pub self_check: bool,
+    /// Where self-check reports go, as `<channel>:<chat id>` (e.g. `telegram:123456`)
+    pub ops_channel: Option<String>,
+    pub min_free_disk_mb: u64,
 }
 
 impl Default for HeartbeatConfig {
@@ -22,6 +27,17 @@ impl Default for HeartbeatConfig {
                 .unwrap_or(1800), // 30 minutes default
             target_channel: std::env::var("HEARTBEAT_TARGET")
                 .unwrap_or_else(|_| "last".to_string()),
+            self_check: std::env::var("HEARTBEAT_SELF_CHECK")
+                .ok()
+                .and_then(|v| v.parse::<bool>().ok())
+                .unwrap_or(true),
+            ops_channel: std::env::var("HEARTBEAT_OPS_CHANNEL")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+            min_free_disk_mb: std::env::var("HEARTBEAT_MIN_FREE_DISK_MB")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(500),
         }
     }
 }
@@ -34,6 +50,7 @@ pub struct HeartbeatService {
     identity_manager: Arc<identity::IdentityManager>,
     harness_tools: Vec<agent_os::ToolDefinition>,
     node_config: &'static config::NodeConfig,
+    self_check: Option<self_check::SelfCheck>,
 }
 
 impl HeartbeatService {
@@ -54,9 +71,16 @@ impl HeartbeatService {
             identity_manager,
             harness_tools,
             node_config,
+            self_check: None,
         }
     }
 
+    /// Run `check` (and report to the ops channel) at the start of every tick
+    pub fn with_self_check(mut self, check: self_check::SelfCheck) -> Self {
+        self.self_check = Some(check);
+        self
+    }
+
     pub async fn start(self: Arc<Self>) {
         if !self.config.enabled {
             tracing::info!("Heartbeat service disabled (set HEARTBEAT_ENABLED=true to enable)");
@@ -83,6 +107,11 @@ impl HeartbeatService {
     async fn run_heartbeat(&self) -> anyhow::Result<()> {
         tracing::debug!("Running heartbeat check...");
 
+        if let Some(check) = &self.self_check {
+            let findings = check.run().await;
+            check.report(&findings).await;
+        }
+
         // Load HEARTBEAT.md if it exists
         let heartbeat_md = self.load_heartbeat_md()?;
         
@@ -118,14 +147,7 @@ impl HeartbeatService {
             .get("llm.chat")
             .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?;
         
-        let executor_registry = Arc::clone(&self.executor_registry);
-        let config = self.node_config;
-        let result = tokio::task::spawn_blocking(move || {
-            let executor = executor_registry.get("llm.chat")
-                .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?;
-            Ok::<_, anyhow::Error>(executor.execute(input, config))
-        })
-        .await??;
+        let result = llm_executor.execute(input, self.node_config).await;
 
         // Extract response
         let content = match result {
@@ -244,6 +266,7 @@ impl HeartbeatService {
                 &format!("🔔 Heartbeat: {}", content),
                 "heartbeat",
                 None,
+                None,
             )
             .await?;
         
@@ -2,26 +2,118 @@
 //! 
 //! Handles unified user identity across channels.
 //! - Resolves provider:provider_id to internal user_id
-//! - Auto-creates users on first contact
+//! - Lets unknown identities in per provider (`identity.policies`): auto-create
+//!   a user, ask for a pairing code, queue for owner approval, or deny
 //! - Supports identity linking across channels
+//!
+//! Owners (`security.owners`) hand out pairing codes with `/pair` and decide on
+//! requests with `/approve <id>` and `/deny <id>`, or over the admin API. Pending
+//! requests, codes and denials are kept in `~/.oneclaw/access-<node id>.json`.
 
+use crate::config::{IdentityConfig, IdentityPolicy};
 use crate::store::Store;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Sent to an unknown identity a `deny` policy (or an owner) turned away
+pub const DENIED_REPLY: &str = "🔒 This assistant is private.";
+
+/// Sent while an owner hasn't decided on a request
+pub const PENDING_REPLY: &str = "⏳ Thanks! The owner has been asked to let you in. You'll hear back here once they do.";
+
+/// Sent to an unknown identity on a `pairing_code` provider without a valid code
+pub const PAIRING_REPLY: &str = "🔒 This assistant is private. Send the pairing code you got from its owner.";
+
+/// Sent when a pairing code let someone in
+pub const PAIRED_REPLY: &str = "✅ Paired! What can I do for you?";
+
+/// Sent to a requester an owner approved
+pub const APPROVED_REPLY: &str = "✅ You're in! Send your request again and I'll get going.";
+
+/// Characters of a first message kept with a request
+const PREVIEW_CHARS: usize = 200;
+
+/// Someone an `approval` policy is holding back until an owner decides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessRequest {
+    pub id: String,
+    pub provider: String,
+    pub provider_id: String,
+    pub username: Option<String>,
+    /// Their first message, shortened
+    pub message: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+/// A one-time code that lets someone in on a `pairing_code` provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingCode {
+    pub code: String,
+    /// Only valid on this provider, when set
+    pub provider: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Pending requests, unused codes and denied identities (`provider:provider_id`)
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Access {
+    #[serde(default)]
+    requests: Vec<AccessRequest>,
+    #[serde(default)]
+    codes: Vec<PairingCode>,
+    #[serde(default)]
+    denied: Vec<String>,
+}
+
+/// Whether a message's sender gets a chat turn
+#[derive(Debug)]
+pub enum Admission {
+    /// Known, or just created under `auto_create`
+    User { user_id: String, is_new: bool },
+    /// Answer with this instead of a turn
+    Reply(String),
+    /// Just queued for approval: answer with `PENDING_REPLY` and tell the owners
+    Requested(AccessRequest),
+}
 
 pub struct IdentityManager {
     store: Arc<dyn Store>,
-    auto_create: bool,
+    config: IdentityConfig,
+    access: Mutex<Access>,
+    /// Where `access` is kept; None keeps it in memory only
+    access_path: Option<PathBuf>,
 }
 
 impl IdentityManager {
-    pub fn new(store: Arc<dyn Store>, auto_create: bool) -> Self {
-        Self { store, auto_create }
+    /// `node_id` names the file pending requests and pairing codes are kept in
+    pub fn new(store: Arc<dyn Store>, config: &IdentityConfig, node_id: &str) -> Self {
+        let access_path = crate::config::expand_path(&format!("~/.oneclaw/access-{}.json", node_id));
+        let access = std::fs::read_to_string(&access_path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { store, config: config.clone(), access: Mutex::new(access), access_path: Some(access_path) }
+    }
+    
+    /// The policy for unknown identities on `provider`
+    pub fn policy_for(&self, provider: &str) -> IdentityPolicy {
+        let listed = self.config.policies.get(provider).or_else(|| match provider {
+            "telegram_group" => self.config.policies.get("telegram"),
+            _ => None,
+        });
+        match listed {
+            Some(policy) => *policy,
+            None if self.config.auto_create => IdentityPolicy::AutoCreate,
+            None => IdentityPolicy::Deny,
+        }
     }
     
     /// Resolve a channel identity to a unified user ID
     /// 
-    /// If auto_create is enabled and no identity exists, creates a new user
-    /// and links the identity.
+    /// If the provider's policy is `auto_create` and no identity exists, creates
+    /// a new user and links the identity.
     /// 
     /// Returns: (user_id, is_new_user)
     pub async fn resolve(
@@ -42,15 +134,144 @@ impl IdentityManager {
         }
         
         // Identity doesn't exist
-        if !self.auto_create {
-            anyhow::bail!("Identity not found and auto_create is disabled");
+        if self.policy_for(provider) != IdentityPolicy::AutoCreate {
+            anyhow::bail!("Identity {}:{} not found and {} doesn't auto-create users", provider, provider_id, provider);
+        }
+        
+        Ok((self.create_user(provider, provider_id, username).await?, true))
+    }
+    
+    /// `resolve` for someone who just sent `message`: unknown identities are let
+    /// in, asked for a pairing code, queued for approval or turned away, per policy
+    pub async fn admit(
+        &self,
+        provider: &str,
+        provider_id: &str,
+        username: Option<&str>,
+        message: &str,
+    ) -> anyhow::Result<Admission> {
+        if let Some(identity) = self.store.get_identity(provider, provider_id).await? {
+            return Ok(Admission::User { user_id: identity.user_id, is_new: false });
+        }
+        let key = format!("{}:{}", provider, provider_id);
+        if self.access.lock().unwrap().denied.contains(&key) {
+            return Ok(Admission::Reply(DENIED_REPLY.to_string()));
         }
         
-        // Create new user
+        match self.policy_for(provider) {
+            IdentityPolicy::AutoCreate => {
+                let user_id = self.create_user(provider, provider_id, username).await?;
+                Ok(Admission::User { user_id, is_new: true })
+            }
+            IdentityPolicy::Deny => Ok(Admission::Reply(DENIED_REPLY.to_string())),
+            IdentityPolicy::PairingCode => {
+                if !self.redeem(provider, message.trim()) {
+                    return Ok(Admission::Reply(PAIRING_REPLY.to_string()));
+                }
+                self.create_user(provider, provider_id, username).await?;
+                Ok(Admission::Reply(PAIRED_REPLY.to_string()))
+            }
+            IdentityPolicy::Approval => {
+                let mut access = self.access.lock().unwrap();
+                if access.requests.iter().any(|r| r.provider == provider && r.provider_id == provider_id) {
+                    return Ok(Admission::Reply(PENDING_REPLY.to_string()));
+                }
+                let request = AccessRequest {
+                    id: nanoid::nanoid!(6, &nanoid::alphabet::SAFE[2..]).to_lowercase(),
+                    provider: provider.to_string(),
+                    provider_id: provider_id.to_string(),
+                    username: username.map(String::from),
+                    message: message.chars().take(PREVIEW_CHARS).collect(),
+                    requested_at: Utc::now(),
+                };
+                access.requests.push(request.clone());
+                self.save(&access);
+                tracing::info!(provider = %provider, provider_id = %provider_id, request = %request.id, "Queued access request");
+                Ok(Admission::Requested(request))
+            }
+        }
+    }
+    
+    /// Requests waiting for an owner, oldest first
+    pub fn pending(&self) -> Vec<AccessRequest> {
+        self.access.lock().unwrap().requests.clone()
+    }
+    
+    /// Let the requester of `request_id` in. Returns the request and their new user id.
+    pub async fn approve(&self, request_id: &str) -> anyhow::Result<(AccessRequest, String)> {
+        let request = self.take_request(request_id)?;
+        let user_id = self.create_user(&request.provider, &request.provider_id, request.username.as_deref()).await?;
+        Ok((request, user_id))
+    }
+    
+    /// Turn the requester of `request_id` away; they aren't asked about again
+    pub fn deny(&self, request_id: &str) -> anyhow::Result<AccessRequest> {
+        let request = self.take_request(request_id)?;
+        let mut access = self.access.lock().unwrap();
+        access.denied.push(format!("{}:{}", request.provider, request.provider_id));
+        self.save(&access);
+        Ok(request)
+    }
+    
+    /// A new pairing code, valid for `identity.pairing_code_hours` on `provider` (or any)
+    pub fn pairing_code(&self, provider: Option<&str>) -> PairingCode {
+        let code = PairingCode {
+            code: format!("PAIR-{}", nanoid::nanoid!(8, &nanoid::alphabet::SAFE[2..]).to_uppercase()),
+            provider: provider.map(String::from),
+            expires_at: Utc::now() + chrono::Duration::hours(self.config.pairing_code_hours as i64),
+        };
+        let mut access = self.access.lock().unwrap();
+        access.codes.retain(|c| c.expires_at > Utc::now());
+        access.codes.push(code.clone());
+        self.save(&access);
+        code
+    }
+    
+    /// Use up `code` if it's valid on `provider`
+    fn redeem(&self, provider: &str, code: &str) -> bool {
+        let mut access = self.access.lock().unwrap();
+        let now = Utc::now();
+        let found = access.codes.iter().position(|c| {
+            c.code.eq_ignore_ascii_case(code)
+                && c.expires_at > now
+                && c.provider.as_deref().is_none_or(|p| p == provider || provider.strip_suffix("_group") == Some(p))
+        });
+        let Some(index) = found else {
+            return false;
+        };
+        access.codes.remove(index);
+        self.save(&access);
+        true
+    }
+    
+    fn take_request(&self, request_id: &str) -> anyhow::Result<AccessRequest> {
+        let mut access = self.access.lock().unwrap();
+        let index = access
+            .requests
+            .iter()
+            .position(|r| r.id.eq_ignore_ascii_case(request_id))
+            .ok_or_else(|| anyhow::anyhow!("No pending request {}", request_id))?;
+        let request = access.requests.remove(index);
+        self.save(&access);
+        Ok(request)
+    }
+    
+    fn save(&self, access: &Access) {
+        let Some(path) = &self.access_path else {
+            return;
+        };
+        let written = serde_json::to_vec_pretty(access)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(path, json)?));
+        if let Err(e) = written {
+            tracing::warn!("Failed to save {}: {}", path.display(), e);
+        }
+    }
+    
+    /// Create a user with this identity linked
+    async fn create_user(&self, provider: &str, provider_id: &str, username: Option<&str>) -> anyhow::Result<String> {
         let user_id = format!("user_{}", nanoid::nanoid!(12));
         self.store.create_user(&user_id).await?;
-        
-        // Link identity
         self.store.link_identity(&user_id, provider, provider_id, username).await?;
         
         tracing::info!(
@@ -60,7 +281,7 @@ impl IdentityManager {
             "Created new user and linked identity"
         );
         
-        Ok((user_id, true))
+        Ok(user_id)
     }
     
     /// Link an additional identity to an existing user
@@ -149,4 +370,41 @@ mod tests {
         let code = "LINK-USER_ABC1-XYZ12345";
         assert!(code.starts_with("LINK-"));
     }
+    
+    #[tokio::test]
+    async fn test_policies_per_provider() {
+        let path = std::env::temp_dir().join(format!("oneclaw-identity-test-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store: Arc<dyn Store> = Arc::new(crate::store::SqliteStore::new(path).await.unwrap());
+        let config: IdentityConfig =
+            serde_yaml::from_str("policies: { telegram: pairing_code, slack: approval, discord: deny }").unwrap();
+        let manager = IdentityManager { store, config, access: Mutex::new(Access::default()), access_path: None };
+        
+        assert!(matches!(manager.admit("http", "dev", None, "hi").await.unwrap(), Admission::User { is_new: true, .. }));
+        assert!(matches!(manager.admit("discord", "1", None, "hi").await.unwrap(), Admission::Reply(r) if r == DENIED_REPLY));
+        assert!(manager.resolve("discord", "1", None).await.is_err());
+        
+        // Pairing codes work once, in groups too
+        let code = manager.pairing_code(Some("telegram"));
+        assert!(matches!(manager.admit("telegram", "7", None, "hello").await.unwrap(), Admission::Reply(r) if r == PAIRING_REPLY));
+        assert!(matches!(manager.admit("telegram", "7", None, &code.code).await.unwrap(), Admission::Reply(r) if r == PAIRED_REPLY));
+        assert!(matches!(manager.admit("telegram", "7", None, "hello").await.unwrap(), Admission::User { is_new: false, .. }));
+        assert!(!manager.redeem("telegram_group", &code.code));
+        assert!(manager.redeem("telegram_group", &manager.pairing_code(Some("telegram")).code));
+        
+        // Approval queues once, then the owner decides
+        let Admission::Requested(request) = manager.admit("slack", "U1", Some("ana"), "can you help?").await.unwrap() else {
+            panic!("expected a request");
+        };
+        assert!(matches!(manager.admit("slack", "U1", None, "hello?").await.unwrap(), Admission::Reply(r) if r == PENDING_REPLY));
+        let (_, user_id) = manager.approve(&request.id).await.unwrap();
+        assert!(matches!(manager.admit("slack", "U1", None, "thanks").await.unwrap(), Admission::User { user_id: id, .. } if id == user_id));
+        
+        let Admission::Requested(request) = manager.admit("slack", "U2", None, "hi").await.unwrap() else {
+            panic!("expected a request");
+        };
+        manager.deny(&request.id).unwrap();
+        assert!(manager.pending().is_empty());
+        assert!(matches!(manager.admit("slack", "U2", None, "hi again").await.unwrap(), Admission::Reply(r) if r == DENIED_REPLY));
+    }
 }
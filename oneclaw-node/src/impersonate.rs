@@ -0,0 +1,82 @@
+//! Impersonate - Owner-only "test as user" turns
+//!
+//! Operators reproduce a user's issue by sending a message *as* that user: the
+//! turn runs with the user's conversation history, active jobs and stored
+//! preferences, and tools run exactly as they would for them (turn on shadow mode
+//! to keep side effects out of a test). Two ways in:
+//!
+//! - `/as <user> <message>` from a chat identity listed in `security.owners`
+//! - `POST /chat/as` with `Authorization: Bearer $ONECLAW_ADMIN_TOKEN`
+//!
+//! `<user>` is a user id (`user_abc123`) or a linked identity (`telegram:12345`).
+//! Impersonated messages are stored on the `impersonation` channel so they can be
+//! told apart from what the user really sent.
+
+use crate::config::SecurityConfig;
+use crate::store::Store;
+
+/// Chat command: `/as <user> <message>`
+pub const COMMAND: &str = "/as";
+
+/// Channel impersonated turns are stored and styled under
+pub const CHANNEL: &str = "impersonation";
+
+/// Whether `text` is an `/as` command (well-formed or not); "/ask" is not
+pub fn is_command(text: &str) -> bool {
+    text.trim()
+        .strip_prefix(COMMAND)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+}
+
+/// `(target, message)` from `/as <user> <message>`
+pub fn parse_command(text: &str) -> Option<(&str, &str)> {
+    if !is_command(text) {
+        return None;
+    }
+    let rest = text.trim().strip_prefix(COMMAND)?;
+    let (target, message) = rest.trim_start().split_once(char::is_whitespace)?;
+    let message = message.trim();
+    (!message.is_empty()).then_some((target, message))
+}
+
+/// Whether `provider:provider_id` is listed in `security.owners`
+pub fn is_owner(security: &SecurityConfig, provider: &str, provider_id: &str) -> bool {
+    security
+        .owners
+        .iter()
+        .any(|owner| owner.split_once(':') == Some((provider, provider_id)))
+}
+
+/// The user `target` names (a user id or `provider:provider_id`), if they exist.
+/// Users are never created here.
+pub async fn resolve_target(store: &dyn Store, target: &str) -> anyhow::Result<Option<String>> {
+    if let Some(user) = store.get_user(target).await? {
+        return Ok(Some(user.id));
+    }
+    Ok(match target.split_once(':') {
+        Some((provider, provider_id)) => store.get_identity(provider, provider_id).await?.map(|i| i.user_id),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_and_owners() {
+        assert_eq!(parse_command("/as user_abc why no leads?"), Some(("user_abc", "why no leads?")));
+        assert_eq!(parse_command("/as telegram:123   hi "), Some(("telegram:123", "hi")));
+        assert_eq!(parse_command("/as user_abc"), None);
+        assert_eq!(parse_command("/ask something"), None);
+        assert!(is_command("/as") && !is_command("/ask"));
+
+        let security: SecurityConfig = serde_yaml::from_str(
+            "mode: strict\nallowed_executors: []\nowners: ['telegram:42', 'slack:U123']",
+        )
+        .unwrap();
+        assert!(is_owner(&security, "telegram", "42"));
+        assert!(!is_owner(&security, "telegram", "4"));
+        assert!(!is_owner(&security, "discord", "42"));
+    }
+}
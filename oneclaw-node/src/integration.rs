@@ -60,6 +60,28 @@ pub async fn get_gmail_info(
     Some((email, connected_at))
 }
 
+/// Fetch a fresh Google access token for a user from the control plane, asking as node `node_id`
+pub async fn google_access_token(node_id: &str, control_plane_url: &str, user_id: &str) -> anyhow::Result<String> {
+    let url = format!("{}/api/v1/oauth/google/token", control_plane_url);
+    let body = serde_json::to_vec(&serde_json::json!({ "user_id": user_id }))?;
+    let mut req = reqwest::Client::new()
+        .post(&url)
+        .header("Content-Type", "application/json");
+    for (name, value) in crate::node_key::signed_headers(node_id, "POST", &url, &body) {
+        req = req.header(name, value);
+    }
+
+    let resp = req.body(body).send().await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Google account not connected ({}) - connect it from /integrations", resp.status());
+    }
+    let data: Value = resp.json().await?;
+    data["access_token"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("No access_token in control plane response"))
+}
+
 /// Get list of available integrations with connection status
 pub async fn get_integrations_list(
     user_id: &str,
@@ -104,7 +126,7 @@ pub async fn get_integrations_list(
             name: "Google Calendar".to_string(),
             icon: "📅".to_string(),
             description: "Manage calendar events and scheduling".to_string(),
-            connected: false,
+            connected: gmail_connected,
             email: None,
             connected_at: None,
             required_for: vec![
@@ -116,6 +138,21 @@ pub async fn get_integrations_list(
                 "calendar.events".to_string(),
             ],
         },
+        Integration {
+            id: "google_contacts".to_string(),
+            name: "Google Contacts".to_string(),
+            icon: "📇".to_string(),
+            description: "Save enriched leads to your phone's contact list".to_string(),
+            connected: gmail_connected,
+            email: None,
+            connected_at: None,
+            required_for: vec![
+                "contact sync".to_string(),
+            ],
+            scopes: vec![
+                "contacts".to_string(),
+            ],
+        },
         Integration {
             id: "slack".to_string(),
             name: "Slack".to_string(),
@@ -143,7 +180,7 @@ pub fn generate_oauth_url(
     control_plane_url: &str,
 ) -> Option<String> {
     match integration_id {
-        "gmail" => Some(format!(
+        "gmail" | "google_calendar" | "google_contacts" => Some(format!(
             "{}/oauth/google?user={}&source=node",
             control_plane_url,
             user_id
@@ -0,0 +1,313 @@
+//! Lead dedup - Not contacting the same business twice
+//!
+//! Every outreach the node drafts or sends is recorded in the store's
+//! `contacted_leads` table: the business, its domain (from the website or the
+//! recipient's address), the campaign (a named `campaign`, or the job it was
+//! part of) and what was done. Before the next one, the lead is checked against
+//! the contacts from other campaigns in the last `lead_dedup.lookback_days`:
+//! - the same domain (not free mail like gmail.com) or the same address
+//! - the same name, ignoring case, punctuation and suffixes like "LLC"
+//! - with `use_embeddings` and `embeddings` set up, a name and domain at least
+//!   `similarity` close by embedding ("Smith Heating & Air" and "Smith HVAC")
+//!
+//! A match holds a `google.gmail` send or an `outreach.*` draft and tells the
+//! agent who was contacted when, and a finished job's drafts skip matching
+//! leads. Calling again with `override_duplicate: true` goes ahead; the contact
+//! is then recorded with what it matched, and listed by `GET /audit/outreach`.
+//!
+//! Each node checks and records contacts in its own store. If the store can't
+//! be read, outreach isn't held.
+
+use serde_json::Value;
+
+use crate::config::{EmbeddingsConfig, LeadDedupConfig, NodeConfig};
+use crate::store::{ContactedLead, ContactedLeadQuery, Store};
+
+/// Earlier contacts compared against, newest first
+const MAX_COMPARED: usize = 2000;
+
+/// Domains shared by unrelated senders, so matching them says nothing
+const FREE_MAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.com",
+    "hotmail.com",
+    "outlook.com",
+    "live.com",
+    "msn.com",
+    "aol.com",
+    "icloud.com",
+    "me.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+    "mail.com",
+    "zoho.com",
+];
+
+/// Words left out when comparing names ("Smith HVAC, LLC" is "smith hvac")
+const NAME_SUFFIXES: &[&str] = &["llc", "inc", "incorporated", "co", "corp", "corporation", "company", "ltd", "the"];
+
+/// A business outreach is about to reach
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lead {
+    pub business: String,
+    pub domain: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Lead {
+    /// From a harness lead record (name, website, email)
+    pub fn from_business(business: &Value) -> Self {
+        let email = business["email"].as_str().map(str::to_lowercase);
+        let domain = business["website"]
+            .as_str()
+            .and_then(website_domain)
+            .or_else(|| email.as_deref().and_then(crate::deliverability::domain_of));
+        Self {
+            business: business["name"].as_str().unwrap_or_default().trim().to_string(),
+            domain: domain.filter(|d| !is_free_mail(d)),
+            email,
+        }
+    }
+
+    /// The recipient of an email, for `business` when the agent named it
+    pub fn from_email(to: &str, business: Option<&str>) -> Self {
+        let domain = crate::deliverability::domain_of(to);
+        let email = to.rsplit('<').next().unwrap_or(to).trim_end_matches('>').trim().to_lowercase();
+        Self {
+            business: business.map(str::trim).filter(|b| !b.is_empty()).or(domain.as_deref()).unwrap_or(&email).to_string(),
+            domain: domain.filter(|d| !is_free_mail(d)),
+            email: Some(email),
+        }
+    }
+
+    /// What's embedded for similarity: name and domain
+    fn text(business: &str, domain: Option<&str>) -> String {
+        match domain {
+            Some(domain) => format!("{} ({})", business, domain),
+            None => business.to_string(),
+        }
+    }
+}
+
+/// The earlier contact a lead matched
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub earlier: ContactedLead,
+    /// `domain`, `email`, `name` or `similar`
+    pub matched: &'static str,
+}
+
+impl Duplicate {
+    /// "Smith HVAC (smithhvac.com): email on 2026-09-12, campaign spring-hvac, same domain"
+    pub fn describe(&self) -> String {
+        let earlier = &self.earlier;
+        format!(
+            "{}: {} on {}{}, same {}",
+            Lead::text(&earlier.business, earlier.domain.as_deref()),
+            earlier.action,
+            earlier.created_at.date_naive(),
+            earlier.campaign.as_deref().map(|c| format!(", campaign {}", c)).unwrap_or_default(),
+            if self.matched == "similar" { "business by name" } else { self.matched },
+        )
+    }
+}
+
+/// The domain of a website address, without `www.`
+pub fn website_domain(website: &str) -> Option<String> {
+    let host = website.trim().split("://").last()?.split(['/', '?', '#']).next()?.split(':').next()?;
+    let host = host.trim_end_matches('.').to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    (host.contains('.') && !host.contains(char::is_whitespace)).then(|| host.to_string())
+}
+
+fn is_free_mail(domain: &str) -> bool {
+    FREE_MAIL_DOMAINS.contains(&domain)
+}
+
+/// Lowercased words of a business name, without punctuation or company suffixes
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace('&', " and ")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !NAME_SUFFIXES.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The campaign of a contact made now: the one named, else the job being run
+pub fn campaign(named: Option<&str>) -> Option<String> {
+    named
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .or_else(|| crate::llm_audit::current().job_id)
+}
+
+/// Why `lead` is the business of `earlier`, by domain, address or name
+fn exact_match(lead: &Lead, earlier: &ContactedLead) -> Option<&'static str> {
+    if lead.domain.is_some() && lead.domain == earlier.domain {
+        return Some("domain");
+    }
+    if lead.email.is_some() && lead.email.as_deref().map(str::to_lowercase) == earlier.email.as_deref().map(str::to_lowercase) {
+        return Some("email");
+    }
+    let name = normalize_name(&lead.business);
+    (!name.is_empty() && name == normalize_name(&earlier.business)).then_some("name")
+}
+
+/// The contacts from other campaigns than `campaign`; contacts within one
+/// campaign (a follow-up, a voicemail after the call script) aren't duplicates
+fn other_campaigns(earlier: Vec<ContactedLead>, campaign: Option<&str>) -> Vec<ContactedLead> {
+    earlier
+        .into_iter()
+        .filter(|e| campaign.is_none() || e.campaign.as_deref() != campaign)
+        .collect()
+}
+
+/// The earlier contact from another campaign that `lead` matches, if any
+async fn find(
+    store: &dyn Store,
+    dedup: &LeadDedupConfig,
+    embeddings: &EmbeddingsConfig,
+    lead: &Lead,
+    campaign: Option<&str>,
+) -> anyhow::Result<Option<Duplicate>> {
+    let query = ContactedLeadQuery {
+        since: Some(chrono::Utc::now() - chrono::Duration::days(dedup.lookback_days.max(1))),
+        overridden: false,
+        limit: MAX_COMPARED,
+    };
+    let earlier = other_campaigns(store.contacted_leads(&query).await?, campaign);
+    if let Some(duplicate) = earlier.iter().find_map(|e| exact_match(lead, e).map(|matched| Duplicate { earlier: e.clone(), matched })) {
+        return Ok(Some(duplicate));
+    }
+    if earlier.is_empty() || lead.business.is_empty() || !dedup.use_embeddings || !crate::embeddings::ready(embeddings) {
+        return Ok(None);
+    }
+
+    let mut texts: Vec<String> = earlier.iter().map(|e| Lead::text(&e.business, e.domain.as_deref())).collect();
+    texts.push(Lead::text(&lead.business, lead.domain.as_deref()));
+    let mut vectors = crate::embeddings::embed(embeddings, Some(store), &texts).await?;
+    let query = vectors.pop().unwrap_or_default();
+    let closest = vectors
+        .iter()
+        .map(|vector| crate::embeddings::cosine(vector, &query))
+        .enumerate()
+        .filter(|(_, similarity)| *similarity >= dedup.similarity)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(closest.map(|(i, _)| Duplicate { earlier: earlier[i].clone(), matched: "similar" }))
+}
+
+/// The earlier contact `lead` matches in `store`; None when there's none, no
+/// store, or dedup is off
+pub async fn check(store: Option<&dyn Store>, config: &NodeConfig, lead: &Lead, campaign: Option<&str>) -> Option<Duplicate> {
+    let store = store.filter(|_| config.lead_dedup.enabled)?;
+    match find(store, &config.lead_dedup, &config.embeddings, lead, campaign).await {
+        Ok(duplicate) => duplicate,
+        Err(e) => {
+            tracing::warn!("Could not check {} against earlier outreach: {}", lead.business, e);
+            None
+        }
+    }
+}
+
+fn contacted(lead: &Lead, campaign: Option<String>, action: &str, duplicate: Option<&Duplicate>) -> ContactedLead {
+    ContactedLead {
+        business: lead.business.clone(),
+        domain: lead.domain.clone(),
+        email: lead.email.clone(),
+        campaign,
+        action: action.to_string(),
+        override_of: duplicate.map(Duplicate::describe),
+        created_at: chrono::Utc::now(),
+    }
+}
+
+/// Record in `store` that `action` reached `lead`; `overridden` is the earlier
+/// contact it matched when it went ahead anyway
+pub async fn record(
+    store: Option<&dyn Store>,
+    lead: &Lead,
+    campaign: Option<String>,
+    action: &str,
+    overridden: Option<&Duplicate>,
+) {
+    let Some(store) = store else { return };
+    if let Some(duplicate) = overridden {
+        tracing::warn!("Outreach ({}) to {} despite an earlier contact: {}", action, lead.business, duplicate.describe());
+    }
+    if let Err(e) = store.add_contacted_lead(&contacted(lead, campaign, action, overridden)).await {
+        tracing::warn!("Could not record outreach to {}: {}", lead.business, e);
+    }
+}
+
+/// `results` without the businesses contacted in other campaigns, and those
+/// left out, for drafting a finished job's outreach
+pub async fn screen(store: &dyn Store, config: &NodeConfig, results: &Value, campaign: &str) -> (Value, Vec<Duplicate>) {
+    let mut screened = results.clone();
+    let mut skipped = Vec::new();
+    if let Some(businesses) = screened["businesses"].as_array_mut() {
+        let mut kept = Vec::new();
+        for business in businesses.drain(..) {
+            match check(Some(store), config, &Lead::from_business(&business), Some(campaign)).await {
+                Some(duplicate) => skipped.push(duplicate),
+                None => kept.push(business),
+            }
+        }
+        *businesses = kept;
+    }
+    (screened, skipped)
+}
+
+/// The note under a job's drafts about the leads left out
+pub fn skipped_note(skipped: &[Duplicate]) -> String {
+    let mut note = format!("⏭️ Skipped {} lead(s) already contacted in other campaigns:\n", skipped.len());
+    for duplicate in skipped {
+        note.push_str(&format!("• {}\n", duplicate.describe()));
+    }
+    note.push_str("Ask me to draft for one with override_duplicate if you want to reach it again.\n");
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_matches_across_campaigns_only() {
+        let lead = Lead::from_business(&serde_json::json!({ "name": "Smith HVAC, LLC", "website": "https://www.SmithHVAC.com/contact" }));
+        assert_eq!(lead.domain.as_deref(), Some("smithhvac.com"));
+        assert_eq!(normalize_name("Smith Heating & Air Co."), "smith heating and air");
+        let freemail = Lead::from_email("Bob <Bob@Gmail.com>", Some("Bob's Plumbing"));
+        assert_eq!((freemail.domain, freemail.email.as_deref()), (None, Some("bob@gmail.com")));
+
+        let path = std::env::temp_dir().join(format!("oneclaw-lead-dedup-{}.db", nanoid::nanoid!()));
+        let store = crate::store::SqliteStore::new(path.clone()).await.unwrap();
+        let dedup = LeadDedupConfig { use_embeddings: false, ..Default::default() };
+        let embeddings = EmbeddingsConfig::default();
+
+        store.add_contacted_lead(&contacted(&lead, Some("spring-hvac".to_string()), "email", None)).await.unwrap();
+        let bob = Lead::from_email("bob@gmail.com", Some("Bob's Plumbing"));
+        store.add_contacted_lead(&contacted(&bob, Some("spring-hvac".to_string()), "email", None)).await.unwrap();
+
+        // Same domain, other campaign
+        let again = Lead::from_email("owner@smithhvac.com", None);
+        let duplicate = find(&store, &dedup, &embeddings, &again, Some("fall-hvac")).await.unwrap().unwrap();
+        assert_eq!(duplicate.matched, "domain");
+        assert!(duplicate.describe().starts_with("Smith HVAC, LLC (smithhvac.com): email on "));
+        assert!(duplicate.describe().ends_with(", campaign spring-hvac, same domain"));
+        // Same name, no website
+        let by_name = Lead::from_business(&serde_json::json!({ "name": "smith hvac" }));
+        assert_eq!(find(&store, &dedup, &embeddings, &by_name, None).await.unwrap().unwrap().matched, "name");
+        // Gmail is no business's domain, but the address itself counts
+        let other_gmail = Lead::from_email("ana@gmail.com", Some("Ana's Bakery"));
+        assert!(find(&store, &dedup, &embeddings, &other_gmail, Some("fall-hvac")).await.unwrap().is_none());
+        assert_eq!(find(&store, &dedup, &embeddings, &bob, Some("fall-hvac")).await.unwrap().unwrap().matched, "email");
+        // A follow-up in the same campaign isn't a duplicate
+        assert!(find(&store, &dedup, &embeddings, &again, Some("spring-hvac")).await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
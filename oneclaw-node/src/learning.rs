@@ -23,7 +23,8 @@ struct PillarUpdate {
     content: String,
 }
 
-/// Analyze the interaction and update brain if something new was learned
+/// Analyze the interaction and update `agent_os` (not saved) if something new
+/// was learned; true if anything changed
 pub async fn reflect_and_evolve(
     goal: &str,
     steps_executed: &[StepRecord],
@@ -37,6 +38,30 @@ pub async fn reflect_and_evolve(
         return Ok(false);
     }
 
+    // Call LLM to analyze
+    let input = serde_json::json!({
+        "messages": reflection_messages(goal, steps_executed, success),
+        "phase": crate::model_router::REFLECTION,
+    });
+    
+    let result = executor_registry
+        .get("llm.chat")
+        .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?
+        .execute(input, config)
+        .await;
+    
+    let content = match result {
+        executor::ExecutorResult::Executed { output, .. } => {
+            output["content"].as_str().unwrap_or("{}").to_string()
+        }
+        _ => return Ok(false),
+    };
+    
+    Ok(apply_reflection(&content, agent_os))
+}
+
+/// Reflection request for an interaction (sent directly or through a batch)
+pub fn reflection_messages(goal: &str, steps_executed: &[StepRecord], success: bool) -> Vec<Value> {
     // Build the reflection prompt
     let steps_summary = steps_executed
         .iter()
@@ -90,40 +115,25 @@ Be specific - include actual details (tool names, timings, user preferences).
         steps_summary
     );
 
-    // Call LLM to analyze
-    let input = serde_json::json!({
-        "messages": [
-            {
-                "role": "system", 
-                "content": "You are a reflection assistant. Analyze interactions and suggest brain updates. Always respond with valid JSON."
-            },
-            {"role": "user", "content": prompt}
-        ]
-    });
-    
-    let executor_registry = Arc::clone(executor_registry);
-    let result = tokio::task::spawn_blocking(move || {
-        let executor = executor_registry
-            .get("llm.chat")
-            .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?;
-        Ok::<_, anyhow::Error>(executor.execute(input, config))
-    })
-    .await??;
-    
-    let content = match result {
-        executor::ExecutorResult::Executed { output, .. } => {
-            output["content"].as_str().unwrap_or("{}").to_string()
-        }
-        _ => return Ok(false),
-    };
-    
+    vec![
+        serde_json::json!({
+            "role": "system",
+            "content": "You are a reflection assistant. Analyze interactions and suggest brain updates. Always respond with valid JSON."
+        }),
+        serde_json::json!({"role": "user", "content": prompt}),
+    ]
+}
+
+/// Apply the pillar updates in a reflection reply to `agent_os` (not saved);
+/// true if anything changed
+pub fn apply_reflection(content: &str, agent_os: &mut agent_os::AgentOS) -> bool {
     // Parse the response (try to extract JSON from potential markdown)
-    let json_content = extract_json_from_content(&content);
+    let json_content = extract_json_from_content(content);
     let suggested: ReflectionResponse = match serde_json::from_str(&json_content) {
         Ok(v) => v,
         Err(e) => {
             tracing::warn!("Reflection parse failed: {}, content: {}", e, json_content);
-            return Ok(false);
+            return false;
         }
     };
     
@@ -142,12 +152,7 @@ Be specific - include actual details (tool names, timings, user preferences).
         }
     }
     
-    if has_updates {
-        agent_os.save()?;
-        tracing::info!("✅ Brain evolution complete");
-    }
-    
-    Ok(has_updates)
+    has_updates
 }
 
 fn truncate_json(value: &Value) -> String {
@@ -0,0 +1,1284 @@
+//! LLM - Provider abstraction behind the `llm.chat` executor
+//!
+//! `LlmProvider` hides each API's wire format. Requests carry the messages the
+//! node already builds (`system`/`user`/`assistant`/`tool` roles) and tool
+//! definitions in Anthropic's shape (`name`, `description`, `input_schema`).
+//! Responses come back as text plus `ToolCall`s, whether the provider answered
+//! with Claude `tool_use` blocks or OpenAI `tool_calls`. Calls and their results
+//! go back to the model as Anthropic-style `tool_use` / `tool_result` blocks
+//! (`tool_use_message`, `tool_results_message`), converted for other APIs.
+//!
+//! Providers (`llm.provider`):
+//! - `anthropic`: Messages API
+//! - `openai`: Chat Completions
+//! - `openrouter`: Chat Completions on OpenRouter
+//! - `openai-compatible`: Chat Completions at `llm.base_url` (vLLM, LM Studio, ...)
+//! - `ollama`: a local Ollama server (`llm.base_url`, default `http://localhost:11434`)
+//! - `bedrock`: Claude on AWS Bedrock in `llm.region`, signed with AWS credentials
+//!
+//! System prompts can mark where their stable prefix ends (`CACHE_BREAKPOINT`).
+//! On the Anthropic API that prefix, with the tool definitions before it, is
+//! sent with `cache_control`, so repeat turns read it from the prompt cache at a
+//! tenth of the input price. Other providers get the marker removed.
+//!
+//! With `ollama` or `bedrock` every call of the node is routed there: planner
+//! calls (job plans, recovery, tagging, plan adaptation) use the configured
+//! model too instead of going to api.anthropic.com. Each fleet member decides
+//! this by its own `llm`.
+//!
+//! Calls are async (`reqwest::Client`); streamed replies are read as they arrive.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::LlmConfig;
+
+const ANTHROPIC_API: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const OPENAI_API: &str = "https://api.openai.com/v1";
+const OPENROUTER_API: &str = "https://openrouter.ai/api/v1";
+const OLLAMA_API: &str = "http://localhost:11434";
+
+const MAX_TOKENS: u32 = 4096;
+
+/// Ends the part of a system prompt that stays the same from turn to turn
+pub const CACHE_BREAKPOINT: &str = "\n<!-- cache breakpoint -->\n";
+
+/// `text` without its cache breakpoint, for providers that don't cache
+fn without_breakpoint(text: &str) -> String {
+    text.replace(CACHE_BREAKPOINT, "\n")
+}
+
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<Value>,
+    /// Anthropic-style tool definitions; providers convert as needed
+    pub tools: Vec<Value>,
+    pub max_tokens: u32,
+    /// Name of a tool the model must call
+    pub tool_choice: Option<String>,
+}
+
+impl ChatRequest {
+    pub fn new(model: &str, messages: Vec<Value>, tools: Vec<Value>) -> Self {
+        Self { model: model.to_string(), messages, tools, max_tokens: MAX_TOKENS, tool_choice: None }
+    }
+
+    /// The system prompt and the remaining messages, with `tool` messages
+    /// (tool results the daemon feeds back as text) sent as assistant turns
+    fn split_system(&self) -> (Option<String>, Vec<Value>) {
+        let mut system = Vec::new();
+        let mut messages = Vec::new();
+        for msg in &self.messages {
+            match msg["role"].as_str() {
+                Some("system") => {
+                    if let Some(text) = msg["content"].as_str() {
+                        system.push(text.to_string());
+                    }
+                }
+                Some("tool") => {
+                    let mut assistant = msg.clone();
+                    assistant["role"] = json!("assistant");
+                    messages.push(assistant);
+                }
+                _ => messages.push(msg.clone()),
+            }
+        }
+        ((!system.is_empty()).then(|| system.join("\n\n")), messages)
+    }
+}
+
+/// A tool the model asked to call
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub input: Value,
+}
+
+/// The assistant turn that made `calls` (each with an id), as content blocks
+pub fn tool_use_message(text: &str, calls: &[ToolCall]) -> Value {
+    let mut blocks = Vec::new();
+    if !text.trim().is_empty() {
+        blocks.push(json!({ "type": "text", "text": text }));
+    }
+    for call in calls {
+        blocks.push(json!({ "type": "tool_use", "id": call.id, "name": call.name, "input": call.input }));
+    }
+    json!({ "role": "assistant", "content": blocks })
+}
+
+/// Results for the calls of the preceding `tool_use_message`: (call id, output, failed)
+pub fn tool_results_message(results: &[(String, String, bool)]) -> Value {
+    let blocks: Vec<Value> = results
+        .iter()
+        .map(|(id, output, failed)| json!({ "type": "tool_result", "tool_use_id": id, "content": output, "is_error": failed }))
+        .collect();
+    json!({ "role": "user", "content": blocks })
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatResponse {
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub model: String,
+    /// The provider's response body (assembled from events when streamed)
+    pub raw: Value,
+}
+
+/// An error status from the provider
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    pub body: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LLM API error {}: {}", self.status, self.body.chars().take(500).collect::<String>())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Streamed reply text, piece by piece
+pub type OnText<'a> = dyn FnMut(&str) + Send + 'a;
+
+/// A streamed tool call, once its input is complete
+pub type OnToolCall<'a> = dyn FnMut(&ToolCall) + Send + 'a;
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn chat(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse>;
+
+    /// Like `chat`, passing each piece of the reply text to `on_text` as it
+    /// arrives, and each tool call to `on_tool_call` as soon as its input is
+    /// complete (possibly while the model is still writing the rest)
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<ChatResponse>;
+
+    /// Prompt tokens `request` would use; estimated at ~4 characters per token
+    /// unless the provider can count them
+    async fn count_tokens(&self, request: &ChatRequest) -> anyhow::Result<u64> {
+        let chars: usize = request.messages.iter().chain(&request.tools).map(|v| v.to_string().len()).sum();
+        Ok(chars.div_ceil(4) as u64)
+    }
+
+    /// Whether the model can be given tool definitions
+    fn supports_tools(&self) -> bool;
+
+    /// Have the model answer through `tool` (Anthropic-style definition) and
+    /// return the tool input
+    async fn call_tool(&self, request: &ChatRequest, tool: &Value) -> anyhow::Result<Value> {
+        let name = tool["name"].as_str().unwrap_or_default();
+        let request = ChatRequest {
+            model: request.model.clone(),
+            messages: request.messages.clone(),
+            tools: vec![tool.clone()],
+            max_tokens: request.max_tokens,
+            tool_choice: Some(name.to_string()),
+        };
+        self.chat(&request)
+            .await?
+            .tool_calls
+            .into_iter()
+            .find(|call| call.name == name)
+            .map(|call| call.input)
+            .ok_or_else(|| anyhow::anyhow!("LLM did not call {}", name))
+    }
+}
+
+/// Tokens `config`'s model can take (prompt plus reply): `llm.context_window`,
+/// else by model family. Ollama defaults to 8k, since its servers often run
+/// with a small context unless configured otherwise.
+pub fn context_window(config: &LlmConfig) -> u64 {
+    if let Some(window) = config.context_window {
+        return window;
+    }
+    let model = config.model.to_lowercase();
+    match config.provider.as_str() {
+        _ if model.contains("claude") => 200_000,
+        "ollama" => 8_192,
+        _ if model.contains("gpt-4.1") => 1_000_000,
+        _ if model.contains("gpt-4o") || model.contains("gpt-4-turbo") || model.starts_with("o1") || model.starts_with("o3") => 128_000,
+        _ if model.contains("gpt-4") => 8_192,
+        _ => 128_000,
+    }
+}
+
+/// `config` when every call of a node with that `llm` goes through it: a
+/// local provider (keep them on the machine) or Bedrock (keep them in the AWS account)
+pub fn routed(config: &LlmConfig) -> Option<&LlmConfig> {
+    matches!(config.provider.as_str(), "ollama" | "bedrock").then_some(config)
+}
+
+/// Whether `provider` needs an API key (local servers usually don't, Bedrock uses AWS credentials)
+pub fn needs_api_key(provider: &str) -> bool {
+    !matches!(provider, "ollama" | "openai-compatible" | "bedrock")
+}
+
+/// `llm.region`, else `AWS_REGION`, else us-east-1
+pub fn bedrock_region(config: &LlmConfig) -> String {
+    config
+        .region
+        .clone()
+        .or_else(|| std::env::var("AWS_REGION").ok().filter(|r| !r.trim().is_empty()))
+        .unwrap_or_else(|| "us-east-1".to_string())
+}
+
+/// API base URL: `llm.base_url`, or the provider's default (openai-compatible has none)
+pub fn base_url(config: &LlmConfig) -> Option<String> {
+    let default = match config.provider.as_str() {
+        "anthropic" => Some(ANTHROPIC_API),
+        "openai" => Some(OPENAI_API),
+        "openrouter" => Some(OPENROUTER_API),
+        "ollama" => Some(OLLAMA_API),
+        _ => None,
+    };
+    config.base_url.as_deref().or(default).map(|url| url.trim_end_matches('/').to_string())
+}
+
+/// The provider configured in `llm`
+pub fn provider(config: &LlmConfig) -> anyhow::Result<Box<dyn LlmProvider>> {
+    let api_key = std::env::var(&config.api_key_env).ok().filter(|k| !k.trim().is_empty());
+    let required_key = || api_key.clone().ok_or_else(|| anyhow::anyhow!("API key not found in env: {}", config.api_key_env));
+    let base_url = || base_url(config).ok_or_else(|| anyhow::anyhow!("llm.base_url is required for {}", config.provider));
+
+    let provider: Box<dyn LlmProvider> = match config.provider.as_str() {
+        "anthropic" => Box::new(Anthropic {
+            api_key: required_key()?,
+            base_url: base_url()?,
+            tools: config.supports_tools.unwrap_or(true),
+        }),
+        "openai" | "openrouter" => Box::new(OpenAi {
+            name: config.provider.clone(),
+            api_key: Some(required_key()?),
+            base_url: base_url()?,
+            tools: config.supports_tools.unwrap_or(true),
+        }),
+        // Local servers usually don't need a key
+        "openai-compatible" => Box::new(OpenAi {
+            name: config.provider.clone(),
+            api_key,
+            base_url: base_url()?,
+            tools: config.supports_tools.unwrap_or(false),
+        }),
+        "ollama" => Box::new(Ollama {
+            base_url: base_url()?,
+            tools: config.supports_tools.unwrap_or(true),
+        }),
+        "bedrock" => Box::new(Bedrock {
+            region: bedrock_region(config),
+            credentials: crate::sigv4::Credentials::from_env()?,
+            tools: config.supports_tools.unwrap_or(true),
+        }),
+        other => anyhow::bail!("Unknown provider: {}", other),
+    };
+    Ok(provider)
+}
+
+/// `chat`, retried with backoff on rate limits, server and network errors
+/// (`retry.rs`), then tried once on `LLM_FALLBACK_MODEL` if the provider kept failing
+pub async fn chat_with_retry(provider: &dyn LlmProvider, request: &mut ChatRequest) -> anyhow::Result<ChatResponse> {
+    let server_error = |e: &anyhow::Error| e.downcast_ref::<ApiError>().is_some_and(|e| e.status >= 500);
+    let retried = {
+        let request = &*request;
+        crate::retry::with_backoff(provider.name(), || provider.chat(request)).await
+    };
+    let error = match retried {
+        Ok(response) => return Ok(response),
+        Err(e) => e,
+    };
+
+    if server_error(&error) {
+        if let Ok(fallback) = std::env::var("LLM_FALLBACK_MODEL") {
+            if fallback != request.model {
+                tracing::warn!("Primary model failed ({}), retrying once with fallback model {}", error, fallback);
+                request.model = fallback;
+                return provider
+                    .chat(request)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{} (fallback model {})", e, request.model));
+            }
+        }
+    }
+    Err(error)
+}
+
+fn client(timeout_secs: u64) -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .timeout(std::time::Duration::from_secs(timeout_secs))
+        .build()?)
+}
+
+/// Send `request`, turning error statuses into `ApiError`
+async fn send(request: reqwest::RequestBuilder) -> anyhow::Result<reqwest::Response> {
+    let resp = request.send().await?;
+    let status = resp.status().as_u16();
+    if status >= 400 {
+        return Err(ApiError { status, body: resp.text().await.unwrap_or_default() }.into());
+    }
+    Ok(resp)
+}
+
+/// Each line of a response body as it arrives, until `on_line` returns false
+async fn each_line(mut resp: reqwest::Response, mut on_line: impl FnMut(&str) -> bool) -> anyhow::Result<()> {
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            if !on_line(String::from_utf8_lossy(&line).trim_end()) {
+                return Ok(());
+            }
+        }
+    }
+    if !buffer.is_empty() {
+        on_line(String::from_utf8_lossy(&buffer).trim_end());
+    }
+    Ok(())
+}
+
+/// `data:` payloads of a server-sent event stream, until `[DONE]`
+async fn sse_events(resp: reqwest::Response, mut on_event: impl FnMut(Value)) -> anyhow::Result<()> {
+    each_line(resp, |line| {
+        let Some(data) = line.strip_prefix("data:") else { return true };
+        let data = data.trim();
+        if data == "[DONE]" {
+            return false;
+        }
+        if let Ok(event) = serde_json::from_str(data) {
+            on_event(event);
+        }
+        true
+    })
+    .await
+}
+
+// ============================================
+// Anthropic
+// ============================================
+
+struct Anthropic {
+    api_key: String,
+    base_url: String,
+    tools: bool,
+}
+
+/// Messages API body for `request`
+pub fn anthropic_body(request: &ChatRequest, tools: bool) -> Value {
+    let (system, messages) = request.split_system();
+    let mut body = json!({
+        "model": request.model,
+        "messages": messages,
+        "max_tokens": request.max_tokens,
+    });
+    if let Some(system) = system {
+        body["system"] = match system.split_once(CACHE_BREAKPOINT) {
+            Some((stable, rest)) => {
+                let mut blocks = vec![json!({ "type": "text", "text": stable, "cache_control": { "type": "ephemeral" } })];
+                if !rest.trim().is_empty() {
+                    blocks.push(json!({ "type": "text", "text": rest }));
+                }
+                json!(blocks)
+            }
+            None => json!(system),
+        };
+    }
+    if tools && !request.tools.is_empty() {
+        body["tools"] = json!(request.tools);
+        if let Some(name) = &request.tool_choice {
+            body["tool_choice"] = json!({ "type": "tool", "name": name });
+        }
+    }
+    body
+}
+
+impl Anthropic {
+    fn body(&self, request: &ChatRequest) -> Value {
+        anthropic_body(request, self.tools)
+    }
+
+    async fn post(&self, client: &reqwest::Client, path: &str, body: &Value) -> anyhow::Result<reqwest::Response> {
+        send(
+            client
+                .post(format!("{}/{}", self.base_url, path))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(body),
+        )
+        .await
+    }
+}
+
+/// Text and `tool_use` blocks of a Messages API response
+fn parse_anthropic(raw: Value, model: &str) -> ChatResponse {
+    let blocks = raw["content"].as_array().cloned().unwrap_or_default();
+    let content = blocks
+        .iter()
+        .filter(|b| b["type"] == "text")
+        .filter_map(|b| b["text"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let tool_calls = blocks
+        .iter()
+        .filter(|b| b["type"] == "tool_use")
+        .filter_map(|b| {
+            Some(ToolCall {
+                id: b["id"].as_str().map(String::from),
+                name: b["name"].as_str()?.to_string(),
+                input: b["input"].clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let content = if content.trim().is_empty() && tool_calls.is_empty() {
+        extract_assistant_content(&raw, "anthropic")
+    } else {
+        content
+    };
+    ChatResponse {
+        content,
+        tool_calls,
+        model: raw["model"].as_str().unwrap_or(model).to_string(),
+        raw,
+    }
+}
+
+#[async_trait]
+impl LlmProvider for Anthropic {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        let raw: Value = self.post(&client(45)?, "messages", &self.body(request)).await?.json().await?;
+        Ok(parse_anthropic(raw, &request.model))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<ChatResponse> {
+        let mut body = self.body(request);
+        body["stream"] = json!(true);
+        let resp = self.post(&client(300)?, "messages", &body).await?;
+
+        // Rebuild the non-streamed response shape from the events
+        let mut message = json!({ "model": request.model, "content": [], "usage": {} });
+        let mut partial_json: Vec<String> = Vec::new();
+        sse_events(resp, |event| {
+            let index = event["index"].as_u64().unwrap_or(0) as usize;
+            match event["type"].as_str() {
+                Some("message_start") => {
+                    message["model"] = event["message"]["model"].clone();
+                    message["usage"] = event["message"]["usage"].clone();
+                }
+                Some("content_block_start") => {
+                    if let Some(blocks) = message["content"].as_array_mut() {
+                        blocks.push(event["content_block"].clone());
+                        partial_json.push(String::new());
+                    }
+                }
+                Some("content_block_delta") => {
+                    let delta = &event["delta"];
+                    if let Some(text) = delta["text"].as_str() {
+                        on_text(text);
+                        let block = &mut message["content"][index];
+                        block["text"] = json!(format!("{}{}", block["text"].as_str().unwrap_or_default(), text));
+                    } else if let (Some(json), Some(buffer)) = (delta["partial_json"].as_str(), partial_json.get_mut(index)) {
+                        buffer.push_str(json);
+                    }
+                }
+                Some("content_block_stop") => {
+                    let block = &mut message["content"][index];
+                    if block["type"] == "tool_use" {
+                        if let Some(buffer) = partial_json.get_mut(index).filter(|b| !b.is_empty()) {
+                            block["input"] = serde_json::from_str(buffer).unwrap_or(Value::Null);
+                            buffer.clear();
+                        }
+                        if let Some(name) = block["name"].as_str() {
+                            on_tool_call(&ToolCall {
+                                id: block["id"].as_str().map(String::from),
+                                name: name.to_string(),
+                                input: block["input"].clone(),
+                            });
+                        }
+                    }
+                }
+                Some("message_delta") => {
+                    if let Some(output) = event["usage"]["output_tokens"].as_u64() {
+                        message["usage"]["output_tokens"] = json!(output);
+                    }
+                }
+                _ => {}
+            }
+        })
+        .await?;
+
+        for (index, buffer) in partial_json.iter().enumerate() {
+            if !buffer.is_empty() {
+                message["content"][index]["input"] = serde_json::from_str(buffer).unwrap_or(Value::Null);
+            }
+        }
+        Ok(parse_anthropic(message, &request.model))
+    }
+
+    async fn count_tokens(&self, request: &ChatRequest) -> anyhow::Result<u64> {
+        let mut body = self.body(request);
+        body.as_object_mut().map(|b| b.remove("max_tokens"));
+        let counted: Value = self.post(&client(20)?, "messages/count_tokens", &body).await?.json().await?;
+        counted["input_tokens"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("count_tokens response has no input_tokens"))
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.tools
+    }
+}
+
+// ============================================
+// Anthropic Message Batches
+// ============================================
+
+/// One request of a batch; `params` is a Messages API body (`anthropic_body`)
+pub struct BatchRequest {
+    pub custom_id: String,
+    pub params: Value,
+}
+
+pub struct BatchStatus {
+    pub id: String,
+    /// `in_progress`, `canceling` or `ended`
+    pub processing_status: String,
+    pub results_url: Option<String>,
+}
+
+impl BatchStatus {
+    fn parse(raw: Value) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: raw["id"].as_str().ok_or_else(|| anyhow::anyhow!("batch response has no id"))?.to_string(),
+            processing_status: raw["processing_status"].as_str().unwrap_or("in_progress").to_string(),
+            results_url: raw["results_url"].as_str().map(String::from),
+        })
+    }
+
+    pub fn ended(&self) -> bool {
+        self.processing_status == "ended"
+    }
+}
+
+/// The Message Batches API: asynchronous Messages requests at half price,
+/// usually done within the hour
+pub struct Batches(Anthropic);
+
+impl Batches {
+    /// Uses the `llm` key when the node runs on Anthropic, `ANTHROPIC_API_KEY` otherwise
+    pub fn new(config: &LlmConfig) -> anyhow::Result<Self> {
+        let (key_env, base_url) = match config.provider.as_str() {
+            "anthropic" => (config.api_key_env.as_str(), base_url(config).unwrap_or_else(|| ANTHROPIC_API.to_string())),
+            _ => ("ANTHROPIC_API_KEY", ANTHROPIC_API.to_string()),
+        };
+        let api_key = std::env::var(key_env)
+            .ok()
+            .filter(|k| !k.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("API key not found in env: {}", key_env))?;
+        Ok(Self(Anthropic { api_key, base_url, tools: true }))
+    }
+
+    pub async fn create(&self, requests: &[BatchRequest]) -> anyhow::Result<BatchStatus> {
+        let requests: Vec<Value> = requests
+            .iter()
+            .map(|r| json!({ "custom_id": r.custom_id, "params": r.params }))
+            .collect();
+        let raw: Value = self.0.post(&client(60)?, "messages/batches", &json!({ "requests": requests })).await?.json().await?;
+        BatchStatus::parse(raw)
+    }
+
+    pub async fn status(&self, id: &str) -> anyhow::Result<BatchStatus> {
+        let raw: Value = send(self.get(&client(20)?, &format!("{}/messages/batches/{}", self.0.base_url, id))).await?.json().await?;
+        BatchStatus::parse(raw)
+    }
+
+    /// Result of each request of an ended batch, by `custom_id`; errored,
+    /// canceled and expired requests come back as `Err`
+    pub async fn results(&self, status: &BatchStatus) -> anyhow::Result<Vec<(String, Result<ChatResponse, String>)>> {
+        let url = status
+            .results_url
+            .clone()
+            .unwrap_or_else(|| format!("{}/messages/batches/{}/results", self.0.base_url, status.id));
+        let resp = send(self.get(&client(120)?, &url)).await?;
+        let mut results = Vec::new();
+        each_line(resp, |line| {
+            let Ok(entry) = serde_json::from_str::<Value>(line) else { return true };
+            let Some(custom_id) = entry["custom_id"].as_str() else { return true };
+            let result = &entry["result"];
+            let outcome = match result["type"].as_str() {
+                Some("succeeded") => Ok(parse_anthropic(result["message"].clone(), "")),
+                Some("errored") => Err(result["error"]["error"]["message"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| result["error"].to_string())),
+                other => Err(other.unwrap_or("unknown").to_string()),
+            };
+            results.push((custom_id.to_string(), outcome));
+            true
+        })
+        .await?;
+        Ok(results)
+    }
+
+    fn get(&self, client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+        client
+            .get(url)
+            .header("x-api-key", &self.0.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+    }
+}
+
+// ============================================
+// AWS Bedrock
+// ============================================
+
+/// Claude models on Bedrock: the Messages API body posted to `InvokeModel`
+struct Bedrock {
+    region: String,
+    credentials: crate::sigv4::Credentials,
+    tools: bool,
+}
+
+impl Bedrock {
+    async fn invoke(&self, request: &ChatRequest) -> anyhow::Result<Value> {
+        let mut body = anthropic_body(request, self.tools);
+        // Not every Bedrock model takes cache_control
+        for block in body["system"].as_array_mut().into_iter().flatten() {
+            if let Some(block) = block.as_object_mut() {
+                block.remove("cache_control");
+            }
+        }
+        if let Some(body) = body.as_object_mut() {
+            body.remove("model");
+            body.insert("anthropic_version".to_string(), json!("bedrock-2023-05-31"));
+        }
+        let body = serde_json::to_vec(&body)?;
+
+        // Model ids like `anthropic.claude-sonnet-4-20250514-v1:0` are encoded in
+        // the URL, and encoded once more in the signed path
+        let host = format!("bedrock-runtime.{}.amazonaws.com", self.region);
+        let path = format!("/model/{}/invoke", crate::sigv4::uri_encode(&request.model, false));
+        let signed_path = crate::sigv4::uri_encode(&path, true);
+        let signed = crate::sigv4::Request { method: "POST", host: &host, path: &signed_path, query: "", body: &body };
+        let headers = crate::sigv4::sign(&self.credentials, &self.region, "bedrock", &signed, chrono::Utc::now());
+
+        let mut req = client(60)?
+            .post(format!("https://{}{}", host, path))
+            .header("content-type", "application/json")
+            .header("accept", "application/json");
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        Ok(send(req.body(body)).await?.json().await?)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for Bedrock {
+    fn name(&self) -> &str {
+        "bedrock"
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        Ok(parse_anthropic(self.invoke(request).await?, &request.model))
+    }
+
+    /// Bedrock streams in AWS event-stream framing; the reply is passed on whole
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<ChatResponse> {
+        let response = self.chat(request).await?;
+        if !response.content.is_empty() {
+            on_text(&response.content);
+        }
+        response.tool_calls.iter().for_each(on_tool_call);
+        Ok(response)
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.tools
+    }
+}
+
+// ============================================
+// OpenAI and compatible Chat Completions APIs
+// ============================================
+
+struct OpenAi {
+    name: String,
+    api_key: Option<String>,
+    base_url: String,
+    tools: bool,
+}
+
+/// Function names may only use `[a-zA-Z0-9_-]`; tool ids like `google.gmail` are
+/// sent as `google__gmail` and mapped back through the request's tool list
+fn encode_tool_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c.to_string() } else { "__".to_string() })
+        .collect()
+}
+
+/// Anthropic-style tool definitions as OpenAI-style functions (also Ollama's format)
+fn function_tools(tools: &[Value]) -> Vec<Value> {
+    tools
+        .iter()
+        .filter_map(|tool| {
+            Some(json!({
+                "type": "function",
+                "function": {
+                    "name": encode_tool_name(tool["name"].as_str()?),
+                    "description": tool["description"],
+                    "parameters": tool.get("input_schema").cloned().unwrap_or_else(|| json!({ "type": "object" })),
+                }
+            }))
+        })
+        .collect()
+}
+
+/// Messages for OpenAI-style APIs (also Ollama's, with `ollama`: arguments as
+/// objects and no call ids). Tool results the daemon feeds back as text become
+/// assistant turns; `tool_use` / `tool_result` blocks become `tool_calls` and
+/// `tool` messages.
+fn function_messages(messages: &[Value], ollama: bool) -> Vec<Value> {
+    let mut converted = Vec::new();
+    for msg in messages {
+        let blocks = msg["content"].as_array().filter(|blocks| {
+            blocks.iter().any(|b| b["type"] == "tool_use" || b["type"] == "tool_result")
+        });
+        let Some(blocks) = blocks else {
+            let mut msg = msg.clone();
+            if msg["role"] == "tool" {
+                msg["role"] = json!("assistant");
+            }
+            if let Some(text) = msg["content"].as_str().filter(|t| t.contains(CACHE_BREAKPOINT)) {
+                msg["content"] = json!(without_breakpoint(text));
+            }
+            converted.push(msg);
+            continue;
+        };
+
+        let text = blocks
+            .iter()
+            .filter(|b| b["type"] == "text")
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let calls: Vec<Value> = blocks
+            .iter()
+            .filter(|b| b["type"] == "tool_use")
+            .map(|b| {
+                let name = encode_tool_name(b["name"].as_str().unwrap_or_default());
+                if ollama {
+                    json!({ "function": { "name": name, "arguments": b["input"] } })
+                } else {
+                    json!({ "id": b["id"], "type": "function", "function": { "name": name, "arguments": b["input"].to_string() } })
+                }
+            })
+            .collect();
+        if !calls.is_empty() {
+            converted.push(json!({ "role": "assistant", "content": text, "tool_calls": calls }));
+            continue;
+        }
+        for result in blocks.iter().filter(|b| b["type"] == "tool_result") {
+            let mut tool = json!({ "role": "tool", "content": result["content"] });
+            if !ollama {
+                tool["tool_call_id"] = result["tool_use_id"].clone();
+            }
+            converted.push(tool);
+        }
+        if !text.is_empty() {
+            converted.push(json!({ "role": msg["role"], "content": text }));
+        }
+    }
+    converted
+}
+
+fn decode_tool_name(name: &str, tools: &[Value]) -> String {
+    tools
+        .iter()
+        .filter_map(|t| t["name"].as_str())
+        .find(|original| encode_tool_name(original) == name)
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// One OpenAI-style `tool_calls` entry (also Ollama's shape); arguments may be
+/// a JSON string or an object
+fn openai_tool_call(call: &Value, tools: &[Value]) -> Option<ToolCall> {
+    let function = &call["function"];
+    let input = match &function["arguments"] {
+        Value::String(args) if args.trim().is_empty() => json!({}),
+        Value::String(args) => serde_json::from_str(args).unwrap_or_else(|_| json!({ "raw": args })),
+        Value::Null => json!({}),
+        other => other.clone(),
+    };
+    Some(ToolCall {
+        id: call["id"].as_str().map(String::from),
+        name: decode_tool_name(function["name"].as_str()?, tools),
+        input,
+    })
+}
+
+/// `choices[0].message` text and `tool_calls` of a Chat Completions response
+fn parse_openai(raw: Value, request: &ChatRequest, provider: &str) -> ChatResponse {
+    let message = &raw["choices"][0]["message"];
+    let tool_calls = message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|call| openai_tool_call(call, &request.tools))
+        .collect::<Vec<_>>();
+    let content = extract_text_from_value(&message["content"]);
+    let content = if content.trim().is_empty() && tool_calls.is_empty() {
+        extract_assistant_content(&raw, provider)
+    } else {
+        content
+    };
+    ChatResponse {
+        content,
+        tool_calls,
+        model: raw["model"].as_str().unwrap_or(&request.model).to_string(),
+        raw,
+    }
+}
+
+impl OpenAi {
+    fn body(&self, request: &ChatRequest) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": function_messages(&request.messages, false),
+            "max_tokens": request.max_tokens,
+        });
+        if self.tools && !request.tools.is_empty() {
+            body["tools"] = json!(function_tools(&request.tools));
+            if let Some(name) = &request.tool_choice {
+                body["tool_choice"] = json!({ "type": "function", "function": { "name": encode_tool_name(name) } });
+            }
+        }
+        body
+    }
+
+    async fn post(&self, client: &reqwest::Client, body: &Value) -> anyhow::Result<reqwest::Response> {
+        let mut req = client.post(format!("{}/chat/completions", self.base_url)).json(body);
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+        send(req).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAi {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        let raw: Value = self.post(&client(45)?, &self.body(request)).await?.json().await?;
+        Ok(parse_openai(raw, request, &self.name))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<ChatResponse> {
+        let mut body = self.body(request);
+        body["stream"] = json!(true);
+        if self.name != "openai-compatible" {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        let resp = self.post(&client(300)?, &body).await?;
+
+        // Rebuild the non-streamed response shape from the chunks
+        let mut model = request.model.clone();
+        let mut content = String::new();
+        let mut calls: Vec<Value> = Vec::new();
+        // Calls arrive in index order: one is complete once the next starts
+        let mut emitted = 0;
+        let mut emit_until = |calls: &[Value], end: usize, emitted: &mut usize| {
+            for call in &calls[*emitted..end] {
+                if let Some(call) = openai_tool_call(call, &request.tools) {
+                    on_tool_call(&call);
+                }
+            }
+            *emitted = end.max(*emitted);
+        };
+        let mut usage = Value::Null;
+        sse_events(resp, |chunk| {
+            if let Some(m) = chunk["model"].as_str() {
+                model = m.to_string();
+            }
+            if chunk["usage"].is_object() {
+                usage = chunk["usage"].clone();
+            }
+            let delta = &chunk["choices"][0]["delta"];
+            if let Some(text) = delta["content"].as_str() {
+                on_text(text);
+                content.push_str(text);
+            }
+            for call in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = call["index"].as_u64().unwrap_or(0) as usize;
+                if index > emitted {
+                    emit_until(&calls, index.min(calls.len()), &mut emitted);
+                }
+                if calls.len() <= index {
+                    calls.resize(index + 1, json!({ "function": { "name": "", "arguments": "" } }));
+                }
+                let slot = &mut calls[index];
+                if let Some(id) = call["id"].as_str() {
+                    slot["id"] = json!(id);
+                }
+                for field in ["name", "arguments"] {
+                    if let Some(part) = call["function"][field].as_str() {
+                        let joined = format!("{}{}", slot["function"][field].as_str().unwrap_or_default(), part);
+                        slot["function"][field] = json!(joined);
+                    }
+                }
+            }
+        })
+        .await?;
+        emit_until(&calls, calls.len(), &mut emitted);
+
+        let raw = json!({
+            "model": model,
+            "choices": [{ "message": { "role": "assistant", "content": content, "tool_calls": calls } }],
+            "usage": usage,
+        });
+        Ok(parse_openai(raw, request, &self.name))
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.tools
+    }
+}
+
+// ============================================
+// Ollama
+// ============================================
+
+struct Ollama {
+    base_url: String,
+    tools: bool,
+}
+
+impl Ollama {
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": function_messages(&request.messages, true),
+            "stream": stream,
+            "options": { "num_predict": request.max_tokens },
+        });
+        if self.tools && !request.tools.is_empty() {
+            body["tools"] = json!(function_tools(&request.tools));
+        }
+        body
+    }
+
+    async fn post(&self, client: &reqwest::Client, body: &Value) -> anyhow::Result<reqwest::Response> {
+        send(client.post(format!("{}/api/chat", self.base_url)).json(body)).await
+    }
+}
+
+/// `message` text and `tool_calls` of an `/api/chat` response; token counts are
+/// copied into an OpenAI-style `usage` so context budgets work the same
+fn parse_ollama(mut raw: Value, request: &ChatRequest) -> ChatResponse {
+    raw["usage"] = json!({
+        "prompt_tokens": raw["prompt_eval_count"].as_u64().unwrap_or(0),
+        "completion_tokens": raw["eval_count"].as_u64().unwrap_or(0),
+    });
+    let message = &raw["message"];
+    let tool_calls = message["tool_calls"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|call| openai_tool_call(call, &request.tools))
+        .collect();
+    ChatResponse {
+        content: message["content"].as_str().unwrap_or_default().to_string(),
+        tool_calls,
+        model: raw["model"].as_str().unwrap_or(&request.model).to_string(),
+        raw,
+    }
+}
+
+#[async_trait]
+impl LlmProvider for Ollama {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    // Local models can take a while to load, so the timeouts are generous
+    async fn chat(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        let raw: Value = self.post(&client(300)?, &self.body(request, false)).await?.json().await?;
+        Ok(parse_ollama(raw, request))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<ChatResponse> {
+        let resp = self.post(&client(600)?, &self.body(request, true)).await?;
+
+        // One JSON object per line; the last (`done`) carries the token counts
+        let mut content = String::new();
+        let mut tool_calls: Vec<Value> = Vec::new();
+        let mut last = json!({});
+        each_line(resp, |line| {
+            let Ok(chunk) = serde_json::from_str::<Value>(line) else { return true };
+            if let Some(text) = chunk["message"]["content"].as_str() {
+                on_text(text);
+                content.push_str(text);
+            }
+            // Ollama sends each tool call whole
+            for call in chunk["message"]["tool_calls"].as_array().into_iter().flatten() {
+                if let Some(call) = openai_tool_call(call, &request.tools) {
+                    on_tool_call(&call);
+                }
+                tool_calls.push(call.clone());
+            }
+            let done = chunk["done"].as_bool().unwrap_or(false);
+            last = chunk;
+            !done
+        })
+        .await?;
+        last["message"] = json!({ "role": "assistant", "content": content, "tool_calls": tool_calls });
+        Ok(parse_ollama(last, request))
+    }
+
+    /// Structured output constrained to the tool's schema: small local models
+    /// follow that more reliably than a tool definition
+    async fn call_tool(&self, request: &ChatRequest, tool: &Value) -> anyhow::Result<Value> {
+        let mut body = self.body(request, false);
+        body["format"] = tool.get("input_schema").cloned().unwrap_or_else(|| json!({ "type": "object" }));
+        let raw: Value = self.post(&client(300)?, &body).await?.json().await?;
+        let content = raw["message"]["content"].as_str().unwrap_or_default();
+        serde_json::from_str(content)
+            .map_err(|e| anyhow::anyhow!("{} answer for {} is not valid JSON: {}", self.name(), tool["name"], e))
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.tools
+    }
+}
+
+// ============================================
+// Text extraction for unusual response shapes
+// ============================================
+
+fn extract_text_from_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let mut parts: Vec<String> = Vec::new();
+            for item in items {
+                if let Some(s) = item.as_str() {
+                    if !s.trim().is_empty() {
+                        parts.push(s.to_string());
+                    }
+                    continue;
+                }
+                if let Some(s) = item.get("text").and_then(|v| v.as_str()) {
+                    if !s.trim().is_empty() {
+                        parts.push(s.to_string());
+                    }
+                    continue;
+                }
+                if let Some(s) = item.get("content").and_then(|v| v.as_str()) {
+                    if !s.trim().is_empty() {
+                        parts.push(s.to_string());
+                    }
+                    continue;
+                }
+                if let Some(s) = item.get("value").and_then(|v| v.as_str()) {
+                    if !s.trim().is_empty() {
+                        parts.push(s.to_string());
+                    }
+                    continue;
+                }
+                if let Some(nested) = item.get("content") {
+                    let nested_text = extract_text_from_value(nested);
+                    if !nested_text.trim().is_empty() {
+                        parts.push(nested_text);
+                    }
+                }
+            }
+            parts.join("\n")
+        }
+        Value::Object(map) => {
+            for key in ["text", "content", "value"] {
+                if let Some(v) = map.get(key) {
+                    let text = extract_text_from_value(v);
+                    if !text.trim().is_empty() {
+                        return text;
+                    }
+                }
+            }
+            String::new()
+        }
+        _ => String::new(),
+    }
+}
+
+fn collect_string_values(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) => {
+            let trimmed = s.trim();
+            if !trimmed.is_empty() {
+                out.push(trimmed.to_string());
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_string_values(item, out);
+            }
+        }
+        Value::Object(map) => {
+            for (_k, v) in map {
+                collect_string_values(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn extract_assistant_content(parsed: &Value, provider: &str) -> String {
+    // Anthropic format: content is usually an array of blocks with .text
+    if provider == "anthropic" {
+        let text = extract_text_from_value(&parsed["content"]);
+        if !text.trim().is_empty() {
+            return text;
+        }
+    }
+
+    // OpenAI/OpenRouter common format.
+    let text = extract_text_from_value(&parsed["choices"][0]["message"]["content"]);
+    if !text.trim().is_empty() {
+        return text;
+    }
+
+    // Alternative formats seen across some compatible providers/models.
+    let text = extract_text_from_value(&parsed["choices"][0]["text"]);
+    if !text.trim().is_empty() {
+        return text;
+    }
+
+    let text = extract_text_from_value(&parsed["output_text"]);
+    if !text.trim().is_empty() {
+        return text;
+    }
+
+    let text = extract_text_from_value(&parsed["output"]);
+    if !text.trim().is_empty() {
+        return text;
+    }
+
+    // Provider-agnostic named fields seen in compatible APIs.
+    for key in ["response", "answer", "assistant", "final", "reasoning"] {
+        let text = extract_text_from_value(&parsed[key]);
+        if !text.trim().is_empty() {
+            return text;
+        }
+    }
+
+    // Last resort: deep scan for non-trivial strings and pick the longest.
+    let mut strings = Vec::new();
+    collect_string_values(parsed, &mut strings);
+    strings.retain(|s| s.len() >= 8);
+    if let Some(best) = strings.into_iter().max_by_key(|s| s.len()) {
+        return best;
+    }
+
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_calls_are_normalized() {
+        let tools = vec![json!({ "name": "google.gmail", "description": "Send email", "input_schema": { "type": "object" } })];
+        let request = ChatRequest::new("m", vec![json!({ "role": "user", "content": "email bob" })], tools);
+
+        let claude = json!({ "content": [
+            { "type": "text", "text": "Sending." },
+            { "type": "tool_use", "id": "toolu_1", "name": "google.gmail", "input": { "to": "bob@x.co" } }
+        ] });
+        let parsed = parse_anthropic(claude, "m");
+        assert_eq!(parsed.content, "Sending.");
+        assert_eq!(parsed.tool_calls[0].name, "google.gmail");
+
+        let openai = OpenAi { name: "openai".into(), api_key: None, base_url: OPENAI_API.into(), tools: true };
+        assert_eq!(openai.body(&request)["tools"][0]["function"]["name"], "google__gmail");
+        let raw = json!({ "choices": [{ "message": { "content": null, "tool_calls": [
+            { "id": "call_1", "type": "function", "function": { "name": "google__gmail", "arguments": "{\"to\":\"bob@x.co\"}" } }
+        ] } }] });
+        let parsed = parse_openai(raw, &request, "openai");
+        assert_eq!(parsed.tool_calls[0].name, "google.gmail");
+        assert_eq!(parsed.tool_calls[0].input["to"], "bob@x.co");
+        assert_eq!(parsed.content, "");
+
+        let ollama = Ollama { base_url: OLLAMA_API.into(), tools: true };
+        assert_eq!(ollama.body(&request, false)["tools"][0]["type"], "function");
+        let raw = json!({ "model": "llama3.1", "prompt_eval_count": 120, "eval_count": 8, "message": { "content": "", "tool_calls": [
+            { "function": { "name": "google__gmail", "arguments": { "to": "bob@x.co" } } }
+        ] } });
+        let parsed = parse_ollama(raw, &request);
+        assert_eq!(parsed.tool_calls[0].name, "google.gmail");
+        assert_eq!(parsed.raw["usage"]["prompt_tokens"], 120);
+
+        // A tool round fed back for another turn
+        let call = ToolCall { id: Some("toolu_1".into()), name: "google.gmail".into(), input: json!({ "to": "bob@x.co" }) };
+        let messages = vec![
+            json!({ "role": "user", "content": "email bob" }),
+            tool_use_message("Sending.", &[call]),
+            tool_results_message(&[("toolu_1".into(), "{\"sent\":true}".into(), false)]),
+        ];
+        let converted = function_messages(&messages, false);
+        assert_eq!(converted[1]["tool_calls"][0]["function"]["name"], "google__gmail");
+        assert_eq!(converted[1]["tool_calls"][0]["function"]["arguments"], "{\"to\":\"bob@x.co\"}");
+        assert_eq!(converted[2], json!({ "role": "tool", "content": "{\"sent\":true}", "tool_call_id": "toolu_1" }));
+        assert_eq!(function_messages(&messages, true)[1]["tool_calls"][0]["function"]["arguments"]["to"], "bob@x.co");
+    }
+
+    #[test]
+    fn test_cache_breakpoint() {
+        let system = format!("SOUL\n# TOOLS{}# MEMORY\nlikes golf", CACHE_BREAKPOINT);
+        let messages = vec![json!({ "role": "system", "content": system }), json!({ "role": "user", "content": "hi" })];
+        let request = ChatRequest::new("m", messages.clone(), vec![]);
+
+        let body = anthropic_body(&request, true);
+        assert_eq!(body["system"][0]["text"], "SOUL\n# TOOLS");
+        assert_eq!(body["system"][0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(body["system"][1]["text"], "# MEMORY\nlikes golf");
+        assert_eq!(function_messages(&messages, false)[0]["content"], "SOUL\n# TOOLS\n# MEMORY\nlikes golf");
+    }
+}
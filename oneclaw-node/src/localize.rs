@@ -0,0 +1,322 @@
+//! Localize - Tool results shown in the owner's language
+//!
+//! Job results and pre-formatted harness replies are written in English
+//! ("⭐ 4.8 (132 reviews)"). With `language` set in the preferences file
+//! (`es`, `pt-BR`, ...), text that goes to the user without passing through
+//! the LLM gets a local pass first:
+//! - Labels and phrases of the result formats are translated ("Found 5
+//!   Businesses" -> "5 negocios encontrados")
+//! - Status values ("**Status**: completed") are translated
+//! - Ratings, prices and percentages use the locale's separators, and ISO
+//!   dates its date order ("⭐ 4,8", "16/10/2026")
+//!
+//! Replies the LLM writes from tool results are asked to use the language and
+//! its formats instead. Languages without a locale here only get that.
+
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::config::NodeConfig;
+use crate::memory;
+
+/// How one language writes result text
+pub struct Locale {
+    /// Primary language subtag (`es` for `es-MX`)
+    pub language: &'static str,
+    /// English name, for prompts
+    pub name: &'static str,
+    decimal: char,
+    thousands: char,
+    /// chrono format of a calendar date
+    date: &'static str,
+    /// English phrase (`{n}` is a number) and its translation
+    phrases: &'static [(&'static str, &'static str)],
+    /// Status values
+    statuses: &'static [(&'static str, &'static str)],
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        language: "es",
+        name: "Spanish",
+        decimal: ',',
+        thousands: '.',
+        date: "%d/%m/%Y",
+        phrases: &[
+            ("Job Results", "Resultados del trabajo"),
+            ("Task", "Tarea"),
+            ("Status", "Estado"),
+            ("Compared to last run", "Comparado con la última ejecución"),
+            ("Found {n} Businesses", "{n} negocios encontrados"),
+            ("Found {n} Contacts", "{n} contactos encontrados"),
+            ("{n} New", "{n} nuevos"),
+            ("{n} Changed", "{n} con cambios"),
+            ("...and {n} more", "...y {n} más"),
+            ("{n} reviews", "{n} reseñas"),
+        ],
+        statuses: &[
+            ("completed", "completado"),
+            ("failed", "fallido"),
+            ("running", "en curso"),
+            ("pending", "pendiente"),
+            ("queued", "en cola"),
+            ("paused", "en pausa"),
+            ("cancelled", "cancelado"),
+        ],
+    },
+    Locale {
+        language: "pt",
+        name: "Portuguese",
+        decimal: ',',
+        thousands: '.',
+        date: "%d/%m/%Y",
+        phrases: &[
+            ("Job Results", "Resultados da tarefa"),
+            ("Task", "Tarefa"),
+            ("Status", "Status"),
+            ("Compared to last run", "Comparado com a última execução"),
+            ("Found {n} Businesses", "{n} empresas encontradas"),
+            ("Found {n} Contacts", "{n} contatos encontrados"),
+            ("{n} New", "{n} novas"),
+            ("{n} Changed", "{n} alteradas"),
+            ("...and {n} more", "...e mais {n}"),
+            ("{n} reviews", "{n} avaliações"),
+        ],
+        statuses: &[
+            ("completed", "concluída"),
+            ("failed", "falhou"),
+            ("running", "em andamento"),
+            ("pending", "pendente"),
+            ("queued", "na fila"),
+            ("paused", "pausada"),
+            ("cancelled", "cancelada"),
+        ],
+    },
+    Locale {
+        language: "fr",
+        name: "French",
+        decimal: ',',
+        thousands: '\u{202f}',
+        date: "%d/%m/%Y",
+        phrases: &[
+            ("Job Results", "Résultats de la tâche"),
+            ("Task", "Tâche"),
+            ("Status", "Statut"),
+            ("Compared to last run", "Par rapport à la dernière exécution"),
+            ("Found {n} Businesses", "{n} entreprises trouvées"),
+            ("Found {n} Contacts", "{n} contacts trouvés"),
+            ("{n} New", "{n} nouvelles"),
+            ("{n} Changed", "{n} modifiées"),
+            ("...and {n} more", "...et {n} de plus"),
+            ("{n} reviews", "{n} avis"),
+        ],
+        statuses: &[
+            ("completed", "terminée"),
+            ("failed", "échouée"),
+            ("running", "en cours"),
+            ("pending", "en attente"),
+            ("queued", "en file d'attente"),
+            ("paused", "en pause"),
+            ("cancelled", "annulée"),
+        ],
+    },
+    Locale {
+        language: "de",
+        name: "German",
+        decimal: ',',
+        thousands: '.',
+        date: "%d.%m.%Y",
+        phrases: &[
+            ("Job Results", "Auftragsergebnisse"),
+            ("Task", "Aufgabe"),
+            ("Status", "Status"),
+            ("Compared to last run", "Im Vergleich zum letzten Lauf"),
+            ("Found {n} Businesses", "{n} Unternehmen gefunden"),
+            ("Found {n} Contacts", "{n} Kontakte gefunden"),
+            ("{n} New", "{n} neu"),
+            ("{n} Changed", "{n} geändert"),
+            ("...and {n} more", "...und {n} weitere"),
+            ("{n} reviews", "{n} Bewertungen"),
+        ],
+        statuses: &[
+            ("completed", "abgeschlossen"),
+            ("failed", "fehlgeschlagen"),
+            ("running", "läuft"),
+            ("pending", "ausstehend"),
+            ("queued", "in der Warteschlange"),
+            ("paused", "pausiert"),
+            ("cancelled", "abgebrochen"),
+        ],
+    },
+    Locale {
+        language: "it",
+        name: "Italian",
+        decimal: ',',
+        thousands: '.',
+        date: "%d/%m/%Y",
+        phrases: &[
+            ("Job Results", "Risultati del lavoro"),
+            ("Task", "Attività"),
+            ("Status", "Stato"),
+            ("Compared to last run", "Rispetto all'ultima esecuzione"),
+            ("Found {n} Businesses", "{n} attività trovate"),
+            ("Found {n} Contacts", "{n} contatti trovati"),
+            ("{n} New", "{n} nuove"),
+            ("{n} Changed", "{n} modificate"),
+            ("...and {n} more", "...e altre {n}"),
+            ("{n} reviews", "{n} recensioni"),
+        ],
+        statuses: &[
+            ("completed", "completato"),
+            ("failed", "non riuscito"),
+            ("running", "in corso"),
+            ("pending", "in attesa"),
+            ("queued", "in coda"),
+            ("paused", "in pausa"),
+            ("cancelled", "annullato"),
+        ],
+    },
+];
+
+/// An English number as the result formats write it (`1,234.5`)
+const NUMBER: &str = r"\d{1,3}(?:,\d{3})+(?:\.\d+)?|\d+(?:\.\d+)?";
+
+/// The primary subtag of a language tag (`pt_BR` -> `pt`)
+fn primary(tag: &str) -> String {
+    tag.trim().split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+/// The locale for a language tag; None for English and languages without one
+pub fn for_language(tag: &str) -> Option<&'static Locale> {
+    let language = primary(tag);
+    LOCALES.iter().find(|l| l.language == language)
+}
+
+/// The user's language from the node's preferences file, unless it's English
+pub fn preferred_language(config: &NodeConfig) -> Option<String> {
+    memory::load_preferences(config)
+        .ok()
+        .and_then(|p| p.language)
+        .filter(|tag| !tag.trim().is_empty() && primary(tag) != "en")
+}
+
+/// The instruction for LLM-written replies in `tag`
+pub fn prompt_instruction(tag: &str) -> String {
+    let name = for_language(tag).map_or(tag, |l| l.name);
+    format!(
+        "Write the reply in {} (language code {}), including labels and status words from the tool results, and write numbers and dates the way {} speakers do.",
+        name, tag, name
+    )
+}
+
+/// `phrase` as a regex, `{n}` capturing a number. A leading `...` is left to
+/// the text, since `\b` can't anchor before it.
+fn phrase_regex(phrase: &str) -> Regex {
+    let pattern = regex::escape(phrase).replace(r"\{n\}", &format!("({})", NUMBER));
+    Regex::new(&format!(r"\b{}\b", pattern.trim_start_matches(r"\.\.\."))).expect("valid phrase")
+}
+
+impl Locale {
+    /// An English-formatted number with this locale's separators
+    pub fn number(&self, english: &str) -> String {
+        let (whole, fraction) = english.split_once('.').unwrap_or((english, ""));
+        let digits: Vec<char> = whole.chars().filter(char::is_ascii_digit).collect();
+        let mut out = String::new();
+        for (i, digit) in digits.iter().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(self.thousands);
+            }
+            out.push(*digit);
+        }
+        if !fraction.is_empty() {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// Result text (markdown) localized: phrases, status values, numbers, dates
+    pub fn text(&self, text: &str) -> String {
+        let mut text = text.to_string();
+
+        for (english, translated) in self.phrases {
+            text = phrase_regex(english)
+                .replace_all(&text, |caps: &Captures| {
+                    let n = caps.get(1).map(|m| self.number(m.as_str())).unwrap_or_default();
+                    translated.trim_start_matches("...").replace("{n}", &n)
+                })
+                .into_owned();
+        }
+
+        static STATUS: OnceLock<Regex> = OnceLock::new();
+        let status = STATUS.get_or_init(|| Regex::new(r"(?m)(:\**[ \t]*)([a-z_]+)[ \t]*$").expect("valid regex"));
+        text = status
+            .replace_all(&text, |caps: &Captures| {
+                match self.statuses.iter().find(|(english, _)| *english == &caps[2]) {
+                    Some((_, translated)) => format!("{}{}", &caps[1], translated),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned();
+
+        static NUMBERS: OnceLock<Regex> = OnceLock::new();
+        let numbers = NUMBERS.get_or_init(|| {
+            Regex::new(&format!(r"(⭐ |\$)({n})|({n})%", n = NUMBER)).expect("valid regex")
+        });
+        text = numbers
+            .replace_all(&text, |caps: &Captures| match (caps.get(1), caps.get(2), caps.get(3)) {
+                (Some(prefix), Some(n), _) => format!("{}{}", prefix.as_str(), self.number(n.as_str())),
+                (_, _, Some(n)) => format!("{}%", self.number(n.as_str())),
+                _ => caps[0].to_string(),
+            })
+            .into_owned();
+
+        static DATES: OnceLock<Regex> = OnceLock::new();
+        let dates = DATES.get_or_init(|| Regex::new(r"\b(\d{4}-\d{2}-\d{2})\b").expect("valid regex"));
+        dates
+            .replace_all(&text, |caps: &Captures| match chrono::NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d") {
+                Ok(date) => date.format(self.date).to_string(),
+                Err(_) => caps[0].to_string(),
+            })
+            .into_owned()
+    }
+}
+
+/// Result text for display in the user's language (unchanged without a locale)
+pub fn for_display(config: &NodeConfig, text: &str) -> String {
+    match preferred_language(config).as_deref().and_then(for_language) {
+        Some(locale) => locale.text(text),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized_results() {
+        assert!(for_language("en-US").is_none());
+        let es = for_language("es-MX").unwrap();
+        assert_eq!(es.number("1,234.5"), "1.234,5");
+        assert_eq!(es.number("132"), "132");
+
+        let english = "📊 **Job Results**\n\n**Status**: completed\n\n🏢 **Found 12 Businesses:**\n\n\
+                       1. **Miami Cool Air** 🎯 85 ⭐ 4.8 (1,132 reviews)\n   📞 (305) 555-0134\n   🌐 https://cool-air.example/v2.1\n\n\
+                       ...and 2 more\n\nOpen rate 42.5% since 2026-10-01, $1,250.00 spent";
+        let spanish = es.text(english);
+        assert!(spanish.contains("**Resultados del trabajo**"));
+        assert!(spanish.contains("**Estado**: completado"));
+        assert!(spanish.contains("**12 negocios encontrados:**"));
+        assert!(spanish.contains("⭐ 4,8 (1.132 reseñas)"));
+        assert!(spanish.contains("...y 2 más"));
+        assert!(spanish.contains("42,5% since 01/10/2026, $1.250,00 spent"));
+        // Phones and URLs are left alone
+        assert!(spanish.contains("📞 (305) 555-0134"));
+        assert!(spanish.contains("https://cool-air.example/v2.1"));
+
+        let german = for_language("de").unwrap().text("⭐ 4.8 on 2026-10-16");
+        assert_eq!(german, "⭐ 4,8 on 16.10.2026");
+    }
+}
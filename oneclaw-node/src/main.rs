@@ -1,20 +1,81 @@
+mod actions;
+mod activity;
 mod agent_os;
+mod auth;
 mod autonomous_jobs;
 mod autonomous_jobs_poller;
+mod backup;
+mod batch;
+mod bootstrap;
+mod bundle;
+mod calendar;
+mod canary;
+mod capabilities;
 mod channels;
 mod config;
+mod context_budget;
 mod conversation;
+mod crypto;
 mod daemon;
+mod debug_bundle;
+mod deliverability;
+mod dry_run;
+mod email_parse;
+mod embeddings;
 mod executor;
+mod followup;
+mod google_contacts;
+mod health;
 mod heartbeat;
 mod identity;
+mod impersonate;
 mod integration;
+mod lead_dedup;
 mod learning;
+mod llm;
+mod llm_audit;
+mod localize;
+mod managed;
 mod memory;
+mod model_router;
 mod monitor;
+mod node_key;
+mod notify;
 mod oauth_config;
+mod outreach;
+mod package;
+mod plan_cache;
+mod plugin;
+mod policy;
 mod receipt;
+mod report;
+mod response_style;
+mod retry;
+mod sanitize;
+mod scratchpad;
+mod self_check;
+mod self_update;
+mod service;
+mod shadow;
+mod sigv4;
+mod simulation;
+mod snooze;
+mod spend;
 mod store;
+mod store_cache;
+mod store_outbox;
+mod structured;
+mod sync;
+mod tagging;
+mod territory;
+mod tool_catalog;
+mod tool_insights;
+mod tool_limits;
+mod tool_server;
+mod top;
+mod upgrade;
+mod usage;
+mod web_search;
 mod workflow;
 
 use clap::{Parser, Subcommand};
@@ -34,6 +95,9 @@ enum Commands {
     Daemon {
         #[arg(short, long, default_value = "8787")]
         port: u16,
+        /// Take over from the daemon already running on the port (zero-downtime upgrade)
+        #[arg(long)]
+        takeover: bool,
     },
     /// Interactive onboarding wizard
     Onboard,
@@ -42,14 +106,108 @@ enum Commands {
         workflow: String,
         #[arg(short, long)]
         input: Option<String>,
+        /// Simulate every step instead of running it
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Show current config
     Config,
+    /// Live view of a running daemon: chats in flight, jobs, channels, spend, errors
+    Top {
+        #[arg(short, long, default_value = "8787")]
+        port: u16,
+        /// Seconds between refreshes
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+    },
+    /// Manage the OS service wrapper
+    Service {
+        #[command(subcommand)]
+        action: ServiceAction,
+    },
+    /// Encrypted backup/restore of Agent OS + preferences via the control plane
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+    /// Write config, Agent OS, store, workflows and templates to one encrypted file
+    ExportBundle {
+        file: std::path::PathBuf,
+        /// Env var holding the passphrase (default: sync.passphrase_env)
+        #[arg(long)]
+        passphrase_env: Option<String>,
+    },
+    /// Restore a node from a bundle written by export-bundle
+    ImportBundle {
+        file: std::path::PathBuf,
+        /// Env var holding the passphrase (default: sync.passphrase_env)
+        #[arg(long)]
+        passphrase_env: Option<String>,
+        /// Replace existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Encrypt what the SQLite store holds from before store.encrypt_messages was on
+    EncryptStore,
+    /// Install a workflow/playbook package (file, URL or registry name)
+    Install {
+        source: String,
+        /// Install packages that are unsigned or signed by a key not in packages.trusted_keys
+        #[arg(long)]
+        allow_untrusted: bool,
+        /// Replace existing workflows and templates
+        #[arg(long)]
+        force: bool,
+    },
+    /// Build a package from a directory and sign it with this node's key
+    Package {
+        dir: std::path::PathBuf,
+        /// Output file (default: <name>-<version>.json)
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Install the latest signed release and restart the service
+    SelfUpdate {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+        /// Install without restarting the daemon
+        #[arg(long)]
+        no_restart: bool,
+    },
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // Load .env so LLM/harness keys match .env.local at repo root (same as harness)
+#[derive(Subcommand)]
+enum ServiceAction {
+    /// Register the daemon as an auto-start OS service (Windows)
+    Install {
+        #[arg(short, long, default_value = "8787")]
+        port: u16,
+    },
+    /// Remove the OS service (Windows)
+    Uninstall,
+    /// Service entry point (invoked by the Service Control Manager)
+    #[command(hide = true)]
+    Run {
+        #[arg(short, long, default_value = "8787")]
+        port: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum SyncAction {
+    /// Encrypt and upload the current brain
+    Push,
+    /// Download and restore a brain backup
+    Restore {
+        /// Restore from another node's backup (e.g. before reinstalling)
+        #[arg(long)]
+        from_node: Option<String>,
+    },
+}
+
+/// Load .env so LLM/harness keys match .env.local at repo root (same as harness)
+pub(crate) fn load_dotenv() {
     for path in ["../.env.local", "../.env", ".env.local", ".env"] {
         if std::path::Path::new(path).exists() {
             if let Err(e) = dotenvy::from_path(path) {
@@ -60,32 +218,119 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    load_dotenv();
     use tracing_subscriber::{fmt, prelude::*, EnvFilter};
     tracing_subscriber::registry()
         .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
         .with(fmt::layer().with_target(false))
+        .with(debug_bundle::LogCapture)
         .init();
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Daemon { port } => {
-            daemon::start(port).await?;
+        Commands::Daemon { port, takeover } => {
+            daemon::start(port, takeover).await?;
         }
         Commands::Onboard => {
             onboard().await?;
         }
-        Commands::Run { workflow, input } => {
+        Commands::Run { workflow, input, dry_run } => {
             let input_json = input
                 .map(|s| serde_json::from_str(&s))
                 .transpose()?
                 .unwrap_or(serde_json::json!({}));
-            let receipt = workflow::run(&workflow, input_json).await?;
+            let receipt = workflow::run(&workflow, input_json, dry_run).await?;
             println!("{}", serde_json::to_string_pretty(&receipt)?);
         }
         Commands::Config => {
             let config = config::load()?;
             println!("{}", serde_yaml::to_string(&config)?);
         }
+        Commands::Top { port, interval } => {
+            let config = config::load()?;
+            let admin_token = std::env::var(&config.security.admin_token_env).ok().filter(|t| !t.trim().is_empty());
+            top::run(port, std::time::Duration::from_secs(interval.max(1)), admin_token).await?;
+        }
+        Commands::Service { action } => match action {
+            ServiceAction::Install { port } => service::install(port)?,
+            ServiceAction::Uninstall => service::uninstall()?,
+            ServiceAction::Run { port } => {
+                // The SCM dispatcher blocks and drives its own runtime
+                tokio::task::spawn_blocking(move || service::run(port)).await??;
+            }
+        },
+        Commands::Sync { action } => {
+            let config = config::load()?;
+            match action {
+                SyncAction::Push => {
+                    sync::push(config).await?;
+                    println!("✅ Brain backup uploaded");
+                }
+                SyncAction::Restore { from_node } => {
+                    let bundle = sync::restore(config, from_node.as_deref()).await?;
+                    println!("✅ Restored brain from node {} ({})", bundle.node_id, bundle.created_at.to_rfc3339());
+                }
+            }
+        }
+        Commands::ExportBundle { file, passphrase_env } => {
+            let config = config::load()?;
+            let env = passphrase_env.unwrap_or_else(|| config.sync.passphrase_env.clone());
+            let bundle = bundle::export(config, &file, &sync::passphrase(&env)?)?;
+            println!("✅ Exported node {} to {} ({})", bundle.node_id, file.display(), bundle.summary());
+            println!("   Secrets in your .env are not included; copy them separately.");
+        }
+        Commands::ImportBundle { file, passphrase_env, force } => {
+            // A fresh install has no config yet; fall back to the default env name
+            let env = passphrase_env.unwrap_or_else(|| {
+                config::load()
+                    .map(|c| c.sync.passphrase_env.clone())
+                    .unwrap_or_else(|_| config::SyncConfig::default().passphrase_env)
+            });
+            let bundle = bundle::import(&file, &sync::passphrase(&env)?, force)?;
+            println!("✅ Imported node {} from {} ({})", bundle.node_id, bundle.created_at.to_rfc3339(), bundle.summary());
+        }
+        Commands::EncryptStore => {
+            let config = config::load()?;
+            if !config.store.encrypt_messages {
+                anyhow::bail!("Turn on store.encrypt_messages first, so new messages are encrypted too");
+            }
+            // Fleet members each have their own store, beside the primary node's
+            let stores: Vec<config::StoreConfig> = std::iter::once(config.store.clone())
+                .chain(config.fleet.iter().map(|member| config.for_fleet_member(member).store))
+                .collect();
+            for store_config in stores.iter().filter(|s| s.store_type == "sqlite") {
+                let path = config::expand_path(&store_config.sqlite_path);
+                let store = store::SqliteStore::new(path.clone()).await?.with_content_key(store::content_key(store_config)?);
+                let sealed = store.seal_existing().await?;
+                println!("🔐 {}: encrypted {} stored values", path.display(), sealed);
+            }
+        }
+        Commands::Install { source, allow_untrusted, force } => {
+            let config = config::load()?;
+            let package = package::fetch(&source, config).await?;
+            package.verify(&config.packages.trusted_keys, allow_untrusted)?;
+            let installed = package::install(&package, config, force)?;
+            println!("✅ Installed {} {}", package.manifest.name, package.manifest.version);
+            for (kind, names) in [("Workflows", &installed.workflows), ("Templates", &installed.templates), ("Playbooks", &installed.playbooks)] {
+                if !names.is_empty() {
+                    println!("   {}: {}", kind, names.join(", "));
+                }
+            }
+        }
+        Commands::Package { dir, out } => {
+            let package = package::pack(&dir)?;
+            let out = out.unwrap_or_else(|| format!("{}-{}.json", package.manifest.name, package.manifest.version).into());
+            std::fs::write(&out, serde_json::to_vec_pretty(&package)?)?;
+            println!("✅ Wrote {} ({} files, signed by {})", out.display(), package.manifest.files.len(), package.manifest.public_key);
+        }
+        Commands::SelfUpdate { check, no_restart } => {
+            let config = config::load()?;
+            self_update::run(&config.update, check, no_restart).await?;
+        }
     }
     Ok(())
 }
@@ -115,7 +360,7 @@ async fn onboard() -> anyhow::Result<()> {
         _ => "private",
     };
     
-    println!("\nLLM: 1=anthropic, 2=openrouter, 3=openai");
+    println!("\nLLM: 1=anthropic, 2=openrouter, 3=openai, 4=ollama (local), 5=bedrock (AWS)");
     print!("Select [1]: ");
     io::stdout().flush()?;
     let mut llm_choice = String::new();
@@ -123,22 +368,72 @@ async fn onboard() -> anyhow::Result<()> {
     let (provider, api_key_env, model) = match llm_choice.trim() {
         "2" => ("openrouter", "OPENROUTER_API_KEY", "anthropic/claude-3.5-sonnet"),
         "3" => ("openai", "OPENAI_API_KEY", "gpt-4o"),
+        "4" => ("ollama", "OLLAMA_API_KEY", "llama3.1"),
+        "5" => ("bedrock", "AWS_SECRET_ACCESS_KEY", "anthropic.claude-3-5-sonnet-20241022-v2:0"),
         _ => ("anthropic", "ANTHROPIC_API_KEY", "claude-3-5-sonnet-20241022"),
     };
     
     let config = config::NodeConfig {
-        node: config::Node { id: node_id, name: name.to_string(), environment: environment.to_string() },
-        llm: config::LlmConfig { provider: provider.to_string(), api_key_env: api_key_env.to_string(), model: model.to_string() },
-        security: config::SecurityConfig { mode: "strict".to_string(), allowed_executors: vec!["http.request".to_string()] },
+        node: config::Node { id: node_id, name: name.to_string(), environment: environment.to_string(), workspace: None },
+        llm: config::LlmConfig { provider: provider.to_string(), api_key_env: api_key_env.to_string(), model: model.to_string(), base_url: None, supports_tools: None, region: None, context_window: None },
+        security: config::SecurityConfig {
+            mode: "strict".to_string(),
+            allowed_executors: vec!["http.request".to_string()],
+            admin_token_env: "ONECLAW_ADMIN_TOKEN".to_string(),
+            policies: vec![],
+            owners: vec![],
+        },
         http: config::HttpConfig { allowed_domains: vec!["*".to_string()] },
-        executors: config::ExecutorsConfig { enabled: vec!["http.request".to_string()] },
-        memory: config::MemoryConfig { session_max_messages: 50, preferences_path: "~/.oneclaw/memory/preferences.yaml".to_string() },
+        executors: config::ExecutorsConfig {
+            enabled: vec!["http.request".to_string()],
+            max_concurrency: Default::default(),
+            default_timeout_secs: 120,
+            timeouts: Default::default(),
+        },
+        memory: config::MemoryConfig {
+            session_max_messages: 50,
+            preferences_path: "~/.oneclaw/memory/preferences.yaml".to_string(),
+            session_idle_minutes: 30,
+            budgets: config::MemoryBudgets::default(),
+            compression: config::CompressionConfig::default(),
+        },
         artifacts: config::ArtifactsConfig { storage: "local".to_string(), path: "~/.oneclaw/artifacts".to_string() },
         logging: config::LoggingConfig { level: "info".to_string(), path: "~/.oneclaw/logs".to_string() },
         control_plane: config::ControlPlaneConfig { url: Some("http://104.131.111.116:3000".to_string()), token: None },
         channels: config::ChannelsConfig::default(),
         store: config::StoreConfig::default(),
         identity: config::IdentityConfig::default(),
+        sync: config::SyncConfig::default(),
+        scoring: config::ScoringConfig::default(),
+        reports: config::ReportsConfig::default(),
+        google_contacts: config::GoogleContactsConfig::default(),
+        calendar: config::CalendarConfig::default(),
+        actions: config::ActionsConfig::default(),
+        jobs: config::JobsConfig::default(),
+        tagging: config::TaggingConfig::default(),
+        shadow: config::ShadowConfig::default(),
+        packages: config::PackagesConfig::default(),
+        web_search: config::WebSearchConfig::default(),
+        backup: config::BackupConfig::default(),
+        batch: config::BatchConfig::default(),
+        routing: config::RoutingConfig::default(),
+        spend: config::SpendConfig::default(),
+        agent_loop: config::AgentLoopConfig::default(),
+        retry: config::RetryConfig::default(),
+        canary: config::CanaryConfig::default(),
+        deliverability: config::DeliverabilityConfig::default(),
+        plugins: config::PluginsConfig::default(),
+        tool_server: config::ToolServerConfig::default(),
+        upgrade: config::UpgradeConfig::default(),
+        update: config::UpdateConfig::default(),
+        tool_insights: config::ToolInsightsConfig::default(),
+        managed: config::ManagedConfig::default(),
+        embeddings: config::EmbeddingsConfig::default(),
+        llm_audit: config::LlmAuditConfig::default(),
+        tool_catalog: config::ToolCatalogConfig::default(),
+        bootstrap: config::BootstrapConfig::default(),
+        lead_dedup: config::LeadDedupConfig::default(),
+        fleet: vec![],
     };
     
     let config_path = config::config_path()?;
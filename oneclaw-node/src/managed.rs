@@ -0,0 +1,360 @@
+//! Managed Config - Fleet settings administered from the control plane
+//!
+//! With `managed.enabled`, the node pulls its managed fragment every
+//! `interval_minutes`:
+//!
+//! ```json
+//! { "version": "42",
+//!   "config": { "security": { "allowed_executors": ["..."] }, "spend": { "max_usd_per_hour": 2 } },
+//!   "persona": { "soul": "...", "playbooks": "..." } }
+//! ```
+//!
+//! `config` is merged into node.yaml when the config is loaded: managed values
+//! win, except under the paths listed in `managed.local_overrides`, where the
+//! node's own value stays. `node`, `control_plane` and `managed` are never
+//! managed. `persona` pillars are written to the workspace when a new version
+//! arrives, unless overridden as `persona.<pillar>`. The fragment is cached in
+//! `~/.oneclaw/managed.yaml`, so a node keeps its managed settings offline;
+//! config changes apply at the next restart.
+//!
+//! After each pull the node reports drift: every managed value the node's own
+//! differs from, and whether the local override or the managed value is in
+//! effect. Persona pillars edited since the managed version was written (by
+//! hand or by learning) are reported as diverged. `GET /config/drift` shows
+//! the same report.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::config::{self, NodeConfig};
+
+/// Top-level sections the control plane can't set
+const PROTECTED: &[&str] = &["node", "control_plane", "managed"];
+
+/// Persona pillars that can be managed, with their workspace files
+const PILLARS: &[(&str, &str)] = &[
+    ("soul", "SOUL.md"),
+    ("identity", "IDENTITY.md"),
+    ("skills", "SKILLS.md"),
+    ("playbooks", "PLAYBOOKS.md"),
+];
+
+/// What the control plane manages for this node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fragment {
+    pub version: String,
+    #[serde(default)]
+    pub config: Value,
+    #[serde(default)]
+    pub persona: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// Listed in `managed.local_overrides`: the node's value is in effect
+    LocalOverride,
+    /// The managed value replaced the node's
+    Managed,
+    /// A persona pillar changed on the node since the managed version was written
+    Diverged,
+}
+
+/// A managed value the node's own differs from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Drift {
+    pub path: String,
+    pub managed: Value,
+    pub local: Value,
+    pub resolution: Resolution,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub node_id: String,
+    /// Managed version in the cache (None when nothing is managed)
+    pub version: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    pub drift: Vec<Drift>,
+}
+
+fn cache_path() -> anyhow::Result<PathBuf> {
+    Ok(config::config_path()?.with_file_name("managed.yaml"))
+}
+
+/// The last fragment pulled, if any
+pub fn cached() -> Option<Fragment> {
+    let text = std::fs::read_to_string(cache_path().ok()?).ok()?;
+    match serde_yaml::from_str(&text) {
+        Ok(fragment) => Some(fragment),
+        Err(e) => {
+            tracing::warn!("Ignoring the managed config cache: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether `path` is under one of `overrides`
+fn covered(path: &str, overrides: &[String]) -> bool {
+    overrides.iter().any(|o| path == o || path.strip_prefix(o.as_str()).is_some_and(|rest| rest.starts_with('.')))
+}
+
+fn join(prefix: &str, key: &Value) -> Option<String> {
+    let key = key.as_str()?;
+    Some(if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) })
+}
+
+fn merge_at(local: &mut Value, managed: &Value, overrides: &[String], prefix: &str) {
+    let (Value::Mapping(local_map), Value::Mapping(managed_map)) = (&mut *local, managed) else {
+        if !covered(prefix, overrides) || local.is_null() {
+            *local = managed.clone();
+        }
+        return;
+    };
+    for (key, value) in managed_map {
+        let Some(path) = join(prefix, key) else { continue };
+        if prefix.is_empty() && PROTECTED.contains(&path.as_str()) {
+            continue;
+        }
+        match local_map.get_mut(key) {
+            Some(existing) => merge_at(existing, value, overrides, &path),
+            None => {
+                local_map.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Merge managed `config` into the node's `local` config; under `overrides`
+/// the local value stays (a managed value still fills in what's unset)
+pub fn merge(local: &mut Value, managed: &Value, overrides: &[String]) {
+    if managed.is_mapping() {
+        merge_at(local, managed, overrides, "");
+    }
+}
+
+fn local_overrides(local: &Value) -> Vec<String> {
+    serde_yaml::from_value(local["managed"]["local_overrides"].clone()).unwrap_or_default()
+}
+
+/// Merge the cached fragment into the node.yaml being loaded, if managed
+pub fn apply_cached(local: &mut Value) {
+    if local["managed"]["enabled"].as_bool() != Some(true) {
+        return;
+    }
+    if let Some(fragment) = cached() {
+        let overrides = local_overrides(local);
+        merge(local, &fragment.config, &overrides);
+        tracing::info!("Managed config {} applied", fragment.version);
+    }
+}
+
+fn drift_at(local: &Value, managed: &Value, overrides: &[String], prefix: &str, out: &mut Vec<Drift>) {
+    if let (Value::Mapping(local_map), Value::Mapping(managed_map)) = (local, managed) {
+        for (key, value) in managed_map {
+            let Some(path) = join(prefix, key) else { continue };
+            if prefix.is_empty() && PROTECTED.contains(&path.as_str()) {
+                continue;
+            }
+            if let Some(existing) = local_map.get(key) {
+                drift_at(existing, value, overrides, &path, out);
+            }
+        }
+        return;
+    }
+    if local.is_null() || local == managed {
+        return;
+    }
+    let resolution = if covered(prefix, overrides) { Resolution::LocalOverride } else { Resolution::Managed };
+    out.push(Drift { path: prefix.to_string(), managed: managed.clone(), local: local.clone(), resolution });
+}
+
+/// Managed config values the node's own (`local`, node.yaml as written) differs from
+pub fn config_drift(local: &Value, managed: &Value, overrides: &[String]) -> Vec<Drift> {
+    let mut out = Vec::new();
+    drift_at(local, managed, overrides, "", &mut out);
+    out
+}
+
+fn pillar_file(name: &str) -> Option<&'static str> {
+    PILLARS.iter().find(|(pillar, _)| *pillar == name).map(|(_, file)| *file)
+}
+
+/// Managed persona pillars that differ from the workspace's
+fn persona_drift(fragment: &Fragment, overrides: &[String], workspace: Option<&PathBuf>) -> Vec<Drift> {
+    let Some(workspace) = workspace else { return Vec::new() };
+    fragment
+        .persona
+        .iter()
+        .filter_map(|(name, managed)| {
+            let file = pillar_file(name)?;
+            let local = std::fs::read_to_string(workspace.join(file)).ok()?;
+            if local.trim() == managed.trim() {
+                return None;
+            }
+            let path = format!("persona.{}", name);
+            let resolution = if covered(&path, overrides) { Resolution::LocalOverride } else { Resolution::Diverged };
+            Some(Drift { path, managed: Value::String(managed.clone()), local: Value::String(local), resolution })
+        })
+        .collect()
+}
+
+/// Drift between the node and its cached managed fragment
+pub fn report(config: &NodeConfig) -> DriftReport {
+    let fragment = cached();
+    let mut drift = Vec::new();
+    if let Some(fragment) = &fragment {
+        let local: Value = config::config_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| serde_yaml::from_str(&text).ok())
+            .unwrap_or_default();
+        let overrides = &config.managed.local_overrides;
+        drift.extend(config_drift(&local, &fragment.config, overrides));
+        drift.extend(persona_drift(fragment, overrides, crate::agent_os::workspace_for(config).as_ref()));
+    }
+    DriftReport { node_id: config.node.id.clone(), version: fragment.map(|f| f.version), checked_at: Utc::now(), drift }
+}
+
+fn endpoint(config: &NodeConfig, resource: &str) -> anyhow::Result<String> {
+    let base = config.control_plane.url.as_deref()
+        .ok_or_else(|| anyhow::anyhow!("control_plane.url not configured"))?;
+    Ok(format!("{}/api/v1/nodes/{}/{}", base, config.node.id, resource))
+}
+
+fn request(config: &NodeConfig, method: reqwest::Method, url: &str, body: Vec<u8>) -> reqwest::RequestBuilder {
+    let mut req = reqwest::Client::new()
+        .request(method.clone(), url)
+        .header("Content-Type", "application/json")
+        .timeout(std::time::Duration::from_secs(30));
+    if let Some(token) = &config.control_plane.token {
+        req = req.header("Authorization", format!("Bearer {}", token));
+    }
+    for (name, value) in crate::node_key::signed_headers(&config.node.id, method.as_str(), url, &body) {
+        req = req.header(name, value);
+    }
+    req.body(body)
+}
+
+/// The node's managed fragment (None when the control plane manages nothing for it)
+pub async fn fetch(config: &NodeConfig) -> anyhow::Result<Option<Fragment>> {
+    let url = endpoint(config, "managed-config")?;
+    let resp = request(config, reqwest::Method::GET, &url, Vec::new()).send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        anyhow::bail!("Managed config download failed ({})", resp.status());
+    }
+    Ok(Some(resp.json().await?))
+}
+
+/// Write the managed persona pillars that aren't overridden
+fn write_persona(config: &NodeConfig, fragment: &Fragment) -> anyhow::Result<()> {
+    let workspace = crate::agent_os::workspace_for(config).ok_or_else(|| anyhow::anyhow!("No workspace directory"))?;
+    std::fs::create_dir_all(&workspace)?;
+    for (name, text) in &fragment.persona {
+        let Some(file) = pillar_file(name) else {
+            tracing::warn!("Managed persona has an unknown pillar: {}", name);
+            continue;
+        };
+        if !covered(&format!("persona.{}", name), &config.managed.local_overrides) {
+            std::fs::write(workspace.join(file), text)?;
+        }
+    }
+    Ok(())
+}
+
+/// Pull the fragment, cache it and write persona updates when it's new, then report drift
+pub async fn sync(config: &NodeConfig) -> anyhow::Result<DriftReport> {
+    let previous = cached().map(|f| f.version);
+    match fetch(config).await? {
+        Some(fragment) if previous.as_deref() != Some(fragment.version.as_str()) => {
+            write_persona(config, &fragment)?;
+            std::fs::write(cache_path()?, serde_yaml::to_string(&fragment)?)?;
+            tracing::info!("🛰️ Managed config {} pulled; config changes apply at the next restart", fragment.version);
+        }
+        Some(_) => {}
+        None if previous.is_some() => {
+            std::fs::remove_file(cache_path()?)?;
+            tracing::info!("🛰️ This node is no longer managed; restart to drop the managed config");
+        }
+        None => {}
+    }
+
+    let report = report(config);
+    let url = endpoint(config, "config-drift")?;
+    let resp = request(config, reqwest::Method::POST, &url, serde_json::to_vec(&report)?).send().await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Drift report upload failed ({})", resp.status());
+    }
+    Ok(report)
+}
+
+/// Pull and report every `managed.interval_minutes` until the process exits
+pub async fn run(config: &'static NodeConfig) {
+    let minutes = config.managed.interval_minutes.max(1);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(minutes * 60));
+
+    loop {
+        interval.tick().await;
+        match sync(config).await {
+            Ok(report) if !report.drift.is_empty() => {
+                tracing::info!("🛰️ {} managed setting(s) differ on this node", report.drift.len());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Managed config sync failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_with_overrides_and_drift() {
+        let local: Value = serde_yaml::from_str(
+            "node: { id: n1 }\n\
+             spend: { max_usd_per_hour: 5, burst_usd: 10 }\n\
+             security: { allowed_executors: [http.request] }\n\
+             managed: { enabled: true, local_overrides: [security.allowed_executors] }\n",
+        )
+        .unwrap();
+        let managed: Value = serde_yaml::from_str(
+            "node: { id: hijacked }\n\
+             spend: { max_usd_per_hour: 2 }\n\
+             security: { allowed_executors: [harness.execute] }\n\
+             routing: { rules: [] }\n",
+        )
+        .unwrap();
+        let overrides = local_overrides(&local);
+
+        let mut merged = local.clone();
+        merge(&mut merged, &managed, &overrides);
+        assert_eq!(merged["node"]["id"].as_str(), Some("n1"), "node is never managed");
+        assert_eq!(merged["spend"]["max_usd_per_hour"].as_u64(), Some(2));
+        assert_eq!(merged["spend"]["burst_usd"].as_u64(), Some(10));
+        assert_eq!(merged["security"]["allowed_executors"][0].as_str(), Some("http.request"));
+        assert!(merged["routing"].is_mapping(), "unset sections are filled in");
+
+        let drift = config_drift(&local, &managed, &overrides);
+        let summary: Vec<(&str, Resolution)> = drift.iter().map(|d| (d.path.as_str(), d.resolution)).collect();
+        assert_eq!(
+            summary,
+            vec![("spend.max_usd_per_hour", Resolution::Managed), ("security.allowed_executors", Resolution::LocalOverride)]
+        );
+
+        // A fragment without config leaves node.yaml alone
+        let mut untouched = local.clone();
+        merge(&mut untouched, &Value::Null, &overrides);
+        assert_eq!(untouched, local);
+
+        assert!(covered("spend.max_usd_per_hour", &["spend".to_string()]));
+        assert!(!covered("spending", &["spend".to_string()]));
+    }
+}
@@ -0,0 +1,264 @@
+//! Compression - Old exchanges about the same thing become one summary
+//!
+//! Long histories repeat themselves: the same lookups, the same questions about
+//! the same customers. With `memory.compression.enabled`, a background pass
+//! every `interval_hours`:
+//! 1. Finds users with at least `min_messages` unthreaded messages older than
+//!    `after_days`, and splits the next of them into exchanges (a user message
+//!    and the replies up to the next one). Each pass goes on after the last
+//!    exchange the one before looked at; after a restart they start over.
+//! 2. Embeds each exchange (the `embeddings` service, see `embeddings.rs`)
+//!    and clusters them: an exchange joins the first cluster whose centroid is at least
+//!    `similarity` (cosine) to it.
+//! 3. Replaces each cluster of two or more exchanges with one summary message
+//!    on the `memory` channel, dated at the cluster's last exchange.
+//!
+//! Exchanges with nothing similar are kept as they are, so every topic is still
+//! covered. Summaries are never compressed again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::NodeConfig;
+use crate::embeddings::{self, cosine};
+use crate::executor;
+use crate::store::{ConversationMessage, Store};
+
+/// Channel of the summary messages
+pub const SUMMARY_CHANNEL: &str = "memory";
+
+/// Old messages looked at per user and pass
+const MAX_MESSAGES_PER_PASS: usize = 500;
+
+/// Characters of an exchange that are embedded
+const MAX_EMBED_CHARS: usize = 2_000;
+
+/// Longest transcript sent for summarizing
+const MAX_TRANSCRIPT_CHARS: usize = 12_000;
+
+/// Split messages into exchanges: each user message starts one. Summaries
+/// end the exchange before them and aren't part of any.
+pub fn exchanges(messages: &[ConversationMessage]) -> Vec<Vec<&ConversationMessage>> {
+    let mut exchanges = Vec::new();
+    let mut current: Vec<&ConversationMessage> = Vec::new();
+    for msg in messages {
+        if msg.channel == SUMMARY_CHANNEL {
+            if !current.is_empty() {
+                exchanges.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if msg.role == "user" && !current.is_empty() {
+            exchanges.push(std::mem::take(&mut current));
+        }
+        current.push(msg);
+    }
+    if !current.is_empty() {
+        exchanges.push(current);
+    }
+    exchanges
+}
+
+/// Group embeddings greedily: each joins the first cluster whose centroid is
+/// at least `threshold` similar, or starts a new one. Returns indices, in order.
+pub fn cluster(embeddings: &[Vec<f32>], threshold: f32) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<(Vec<f32>, Vec<usize>)> = Vec::new();
+    for (i, embedding) in embeddings.iter().enumerate() {
+        match clusters.iter_mut().find(|(centroid, _)| cosine(centroid, embedding) >= threshold) {
+            Some((centroid, members)) => {
+                let n = members.len() as f32;
+                for (c, x) in centroid.iter_mut().zip(embedding) {
+                    *c = (*c * n + x) / (n + 1.0);
+                }
+                members.push(i);
+            }
+            None => clusters.push((embedding.clone(), vec![i])),
+        }
+    }
+    clusters.into_iter().map(|(_, members)| members).collect()
+}
+
+fn transcript(exchange: &[&ConversationMessage]) -> String {
+    exchange.iter().map(|m| format!("{}: {}\n", m.role, m.content)).collect()
+}
+
+async fn summarize(
+    cluster: &[&Vec<&ConversationMessage>],
+    executor_registry: &Arc<executor::Registry>,
+    config: &'static NodeConfig,
+) -> anyhow::Result<String> {
+    let mut text = String::new();
+    for exchange in cluster {
+        let block = format!("[{}]\n{}\n", exchange[0].created_at.format("%Y-%m-%d"), transcript(exchange));
+        if text.chars().count() + block.chars().count() > MAX_TRANSCRIPT_CHARS {
+            text.push_str("[...]\n");
+            break;
+        }
+        text.push_str(&block);
+    }
+
+    let input = serde_json::json!({
+        "messages": [
+            {
+                "role": "system",
+                "content": "These are past exchanges between a user and their business assistant, all about similar things. Merge them into one short paragraph the assistant can rely on later: what the user asked for, and what was answered or done. Keep every distinct fact, name, place, number and date that matters; drop repetition. No preamble."
+            },
+            {"role": "user", "content": text}
+        ],
+        "phase": crate::model_router::SUMMARY,
+    });
+
+    let result = executor_registry
+        .get("llm.chat")
+        .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?
+        .execute(input, config)
+        .await;
+
+    match result {
+        executor::ExecutorResult::Executed { output, .. } => {
+            Ok(output["content"].as_str().unwrap_or_default().trim().to_string())
+        }
+        _ => Ok(String::new()),
+    }
+}
+
+/// Compress `user_id`'s old history after `cursor` (the `created_at` and id of
+/// the last message looked at), and move `cursor` past the exchanges looked at
+/// this time. Returns how many messages were replaced.
+pub async fn compress_user(
+    store: &dyn Store,
+    executor_registry: &Arc<executor::Registry>,
+    config: &'static NodeConfig,
+    user_id: &str,
+    cursor: &mut Option<(DateTime<Utc>, i64)>,
+) -> anyhow::Result<usize> {
+    let compression = &config.memory.compression;
+    let before = Utc::now() - Duration::days(compression.after_days);
+    let messages = store.messages_before(user_id, before, *cursor, MAX_MESSAGES_PER_PASS).await?;
+    let mut exchanges = exchanges(&messages);
+    // The last exchange may go on past the page or `before`, so the next pass
+    // starts with it (unless it fills the page on its own)
+    if exchanges.len() > 1 || messages.len() < MAX_MESSAGES_PER_PASS {
+        exchanges.pop();
+    }
+    // Summaries are dated within what was looked at, so they're passed over too
+    let looked_at = exchanges.last().and_then(|e| e.last()).map(|m| (m.created_at, m.id));
+    if exchanges.len() < 2 {
+        *cursor = looked_at.or(*cursor);
+        return Ok(0);
+    }
+
+    let texts: Vec<String> = exchanges.iter().map(|e| transcript(e).chars().take(MAX_EMBED_CHARS).collect()).collect();
+    let embeddings = embeddings::embed(&config.embeddings, Some(store), &texts).await?;
+
+    let mut replaced = 0;
+    for members in cluster(&embeddings, compression.similarity) {
+        if members.len() < 2 {
+            continue;
+        }
+        let group: Vec<&Vec<&ConversationMessage>> = members.iter().map(|&i| &exchanges[i]).collect();
+        let summary = summarize(&group, executor_registry, config).await?;
+        if summary.is_empty() {
+            continue;
+        }
+        let first = group[0][0].created_at;
+        let last = group[group.len() - 1].last().map(|m| m.created_at).unwrap_or(first);
+        let ids: Vec<i64> = group.iter().flat_map(|e| e.iter().map(|m| m.id)).collect();
+        let message = ConversationMessage {
+            id: 0,
+            user_id: user_id.to_string(),
+            role: "assistant".to_string(),
+            content: format!(
+                "[Summary of {} similar exchanges, {} to {}] {}",
+                group.len(),
+                first.format("%Y-%m-%d"),
+                last.format("%Y-%m-%d"),
+                summary
+            ),
+            channel: SUMMARY_CHANNEL.to_string(),
+            tool_calls: None,
+            created_at: last,
+        };
+        store.replace_messages(user_id, &ids, &message).await?;
+        replaced += ids.len();
+    }
+    *cursor = looked_at;
+    Ok(replaced)
+}
+
+/// Compress old histories every `memory.compression.interval_hours` until the process exits
+pub async fn run(store: Arc<dyn Store>, executor_registry: Arc<executor::Registry>, config: &'static NodeConfig) {
+    let compression = &config.memory.compression;
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(compression.interval_hours.max(1) * 3600));
+    // Where each user's next pass starts
+    let mut cursors: HashMap<String, Option<(DateTime<Utc>, i64)>> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        if !embeddings::ready(&config.embeddings) {
+            continue;
+        }
+        let before = Utc::now() - Duration::days(compression.after_days);
+        let users = match store.compressible_users(before, compression.min_messages.max(2)).await {
+            Ok(users) => users,
+            Err(e) => {
+                tracing::warn!("Compression: could not load users: {}", e);
+                continue;
+            }
+        };
+
+        for user_id in users {
+            let cursor = cursors.entry(user_id.clone()).or_default();
+            match compress_user(store.as_ref(), &executor_registry, config, &user_id, cursor).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("🗜️ Compressed {} old message(s) of {}", n, user_id),
+                Err(e) => tracing::warn!("Compression failed for {}: {}", user_id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: i64, role: &str, channel: &str) -> ConversationMessage {
+        ConversationMessage {
+            id,
+            user_id: "u1".to_string(),
+            role: role.to_string(),
+            content: format!("message {}", id),
+            channel: channel.to_string(),
+            tool_calls: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_exchanges_and_clusters() {
+        let messages = vec![
+            message(1, "user", "telegram"),
+            message(2, "assistant", "telegram"),
+            message(3, "tool", "telegram"),
+            message(4, "assistant", SUMMARY_CHANNEL),
+            message(5, "assistant", "telegram"),
+            message(6, "user", "telegram"),
+        ];
+        let ids: Vec<Vec<i64>> = exchanges(&messages).iter().map(|e| e.iter().map(|m| m.id).collect()).collect();
+        assert_eq!(ids, vec![vec![1, 2, 3], vec![5], vec![6]]);
+
+        let embeddings = vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![0.95, 0.1, 0.0],
+            vec![0.0, 0.0, 1.0],
+            vec![0.9, 0.0, 0.1],
+        ];
+        assert_eq!(cluster(&embeddings, 0.85), vec![vec![0, 2, 4], vec![1], vec![3]]);
+        // Nothing is similar enough at a strict threshold
+        assert_eq!(cluster(&embeddings, 0.999).len(), 5);
+    }
+}
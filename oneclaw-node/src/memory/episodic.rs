@@ -0,0 +1,133 @@
+//! Episodic memory - One summary per past session
+//!
+//! When a message starts a new session, the sessions before it that have no
+//! episode yet are summarized in the background (a few sentences each: what was
+//! asked, what was done, what's still open) and stored. Turns recall the newest
+//! episodes that fit `memory.budgets.episodic_chars`.
+
+use std::sync::Arc;
+
+use crate::config::NodeConfig;
+use crate::executor;
+use crate::store::{ConversationMessage, Episode, Store};
+
+use super::working;
+
+/// Stored messages scanned for unsummarized sessions
+const LOOKBACK_MESSAGES: usize = 200;
+
+/// Sessions summarized per pass (older ones without an episode are skipped)
+const MAX_SESSIONS_PER_PASS: usize = 3;
+
+/// Episodes considered for a prompt before the budget applies
+const RECALL_LIMIT: usize = 10;
+
+/// Longest transcript sent for summarizing
+const MAX_TRANSCRIPT_CHARS: usize = 12_000;
+
+/// Summarize `user_id`'s finished sessions that don't have an episode yet.
+/// Returns how many episodes were written.
+pub async fn close_sessions(
+    store: Arc<dyn Store>,
+    executor_registry: Arc<executor::Registry>,
+    config: &'static NodeConfig,
+    user_id: &str,
+) -> anyhow::Result<usize> {
+    let history = store.get_conversation(user_id, LOOKBACK_MESSAGES).await?;
+    let last_end = store.recent_episodes(user_id, 1).await?.first().map(|e| e.ended_at);
+
+    let sessions = working::sessions(&history, working::idle_gap(&config.memory));
+    let Some((_current, finished)) = sessions.split_last() else {
+        return Ok(0);
+    };
+    let pending: Vec<Vec<&ConversationMessage>> = finished
+        .iter()
+        .map(|session| session.iter().filter(|m| last_end.is_none_or(|end| m.created_at > end)).collect::<Vec<_>>())
+        .filter(|session| session.iter().any(|m| m.role == "user"))
+        .collect();
+
+    let mut written = 0;
+    for session in pending.iter().rev().take(MAX_SESSIONS_PER_PASS).rev() {
+        let summary = summarize(session, &executor_registry, config).await?;
+        if summary.is_empty() {
+            continue;
+        }
+        let (first, last) = (session[0], session[session.len() - 1]);
+        store
+            .add_episode(&Episode {
+                user_id: user_id.to_string(),
+                started_at: first.created_at,
+                ended_at: last.created_at,
+                summary,
+                message_count: session.len(),
+            })
+            .await?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+async fn summarize(
+    session: &[&ConversationMessage],
+    executor_registry: &Arc<executor::Registry>,
+    config: &'static NodeConfig,
+) -> anyhow::Result<String> {
+    let mut transcript = String::new();
+    for msg in session {
+        let line = format!("{}: {}\n", msg.role, msg.content);
+        if transcript.chars().count() + line.chars().count() > MAX_TRANSCRIPT_CHARS {
+            transcript.push_str("[...]\n");
+            break;
+        }
+        transcript.push_str(&line);
+    }
+
+    let input = serde_json::json!({
+        "messages": [
+            {
+                "role": "system",
+                "content": "You summarize past conversations between a user and their business assistant so the assistant can remember them later. In 2-4 plain sentences, say what the user asked for, what was done or found, and anything left open or promised. Include names, places and numbers that matter. No preamble."
+            },
+            {"role": "user", "content": transcript}
+        ],
+        "phase": crate::model_router::SUMMARY,
+    });
+
+    let result = executor_registry
+        .get("llm.chat")
+        .ok_or_else(|| anyhow::anyhow!("LLM executor not found"))?
+        .execute(input, config)
+        .await;
+
+    match result {
+        executor::ExecutorResult::Executed { output, .. } => {
+            Ok(output["content"].as_str().unwrap_or_default().trim().to_string())
+        }
+        _ => Ok(String::new()),
+    }
+}
+
+/// "Earlier Conversations" prompt block: the newest episodes that fit
+/// `budget_chars`, oldest first
+pub async fn recall(store: &dyn Store, user_id: &str, budget_chars: usize) -> anyhow::Result<Option<String>> {
+    let episodes = store.recent_episodes(user_id, RECALL_LIMIT).await?;
+    Ok(prompt_block(&episodes, budget_chars))
+}
+
+fn prompt_block(newest_first: &[Episode], budget_chars: usize) -> Option<String> {
+    let mut used = 0;
+    let mut lines: Vec<String> = Vec::new();
+    for episode in newest_first {
+        let line = format!("- {}: {}", episode.ended_at.format("%Y-%m-%d"), episode.summary.replace('\n', " "));
+        used += line.chars().count() + 1;
+        if used > budget_chars {
+            break;
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(format!("## Earlier Conversations\nSummaries of past sessions with this user, oldest first:\n{}", lines.join("\n")))
+}
@@ -0,0 +1,32 @@
+//! Memory - What the agent remembers, in three tiers
+//!
+//! - Working (`working.rs`): the current session's messages, sent verbatim. A
+//!   session ends after `memory.session_idle_minutes` without a message.
+//! - Episodic (`episodic.rs`): one short summary per past session, written in
+//!   the background when the next session starts and kept in the store.
+//! - Semantic (`semantic.rs`): facts and preferences: MEMORY.md entries (written
+//!   by reflection) and `preferences.yaml`. Only entries relevant to the message
+//!   are recalled.
+//!
+//! Each tier fills its own prompt budget (`memory.budgets`), so a long session
+//! can't crowd out what the agent knows about the user, and vice versa.
+//!
+//! Optionally, `compression.rs` shrinks old history in the background: similar
+//! old exchanges are replaced by one summary each.
+
+pub mod compression;
+pub mod episodic;
+pub mod semantic;
+pub mod working;
+
+pub use semantic::{load_preferences, save_preferences, Preferences};
+
+use serde::Serialize;
+
+/// Characters each tier put into one turn's prompt
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TierUsage {
+    pub working_chars: usize,
+    pub episodic_chars: usize,
+    pub semantic_chars: usize,
+}
@@ -0,0 +1,148 @@
+//! Semantic memory - Facts and preferences
+//!
+//! MEMORY.md (in the Agent OS workspace) holds what the agent has learned, one
+//! `## ` entry per fact or learning update. When it outgrows
+//! `memory.budgets.semantic_chars`, only the entries sharing the most words with
+//! the current message are recalled (newer entries win ties), or, with
+//! `embeddings.memory_search`, the entries closest to it by embedding. Structured
+//! preferences (territories, timezone, defaults) live in `preferences.yaml`.
+
+use serde::{Deserialize, Serialize};
+use crate::config;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Preferences {
+    pub user_id: Option<String>,
+    pub default_execution_mode: Option<String>,
+    pub preferred_location: Option<String>,
+    pub preferred_output_format: Option<String>,
+    #[serde(default)]
+    pub custom: std::collections::HashMap<String, serde_json::Value>,
+    /// Named service areas usable as `"territory"` in discovery tools and plans
+    #[serde(default)]
+    pub territories: Vec<crate::territory::Territory>,
+    /// Reject planner-generated locations outside every territory
+    #[serde(default)]
+    pub restrict_to_territories: bool,
+    /// IANA timezone ("America/Chicago") for time-window policies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Language tag ("es", "pt-BR") tool results are shown in (English if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+pub fn load_preferences(config: &config::NodeConfig) -> anyhow::Result<Preferences> {
+    let path = config::expand_path(&config.memory.preferences_path);
+    if !path.exists() {
+        return Ok(Preferences::default());
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    Ok(serde_yaml::from_str(&contents)?)
+}
+
+pub fn save_preferences(config: &config::NodeConfig, prefs: &Preferences) -> anyhow::Result<()> {
+    let path = config::expand_path(&config.memory.preferences_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_yaml::to_string(prefs)?)?;
+    Ok(())
+}
+
+/// Placeholder bodies in the MEMORY.md template
+const PLACEHOLDER_PREFIX: &str = "(Will be populated";
+
+/// The MEMORY section for a turn about `message`: all of `memory` if it fits the
+/// budget, otherwise the most relevant entries, in file order
+pub fn recall(memory: &str, message: &str, budget_chars: usize) -> String {
+    if memory.chars().count() <= budget_chars {
+        return memory.to_string();
+    }
+
+    let entries = entries(memory);
+    let query = words(message);
+    let mut ranked: Vec<(usize, usize)> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (i, words(entry).intersection(&query).count()))
+        .collect();
+    // Most shared words first, then newest (entries are appended)
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+    fit(&entries, ranked.into_iter().map(|(i, _)| i), budget_chars)
+}
+
+/// Like `recall`, but entries are ranked by embedding similarity to `message`
+/// (`embeddings.memory_search`); entry vectors are cached in `store`
+pub async fn recall_by_embedding(
+    memory: &str,
+    message: &str,
+    budget_chars: usize,
+    config: &config::EmbeddingsConfig,
+    store: &dyn crate::store::Store,
+) -> anyhow::Result<String> {
+    if memory.chars().count() <= budget_chars {
+        return Ok(memory.to_string());
+    }
+
+    let entries = entries(memory);
+    let mut texts = entries.clone();
+    texts.push(message.to_string());
+    let mut vectors = crate::embeddings::embed(config, Some(store), &texts).await?;
+    let query = vectors.pop().unwrap_or_default();
+    let mut ranked: Vec<(usize, f32)> = vectors
+        .iter()
+        .enumerate()
+        .map(|(i, vector)| (i, crate::embeddings::cosine(vector, &query)))
+        .collect();
+    // Most similar first, then newest
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+    Ok(fit(&entries, ranked.into_iter().map(|(i, _)| i), budget_chars))
+}
+
+/// The entries at `ranked` (best first) that fit `budget_chars`, in file order
+fn fit(entries: &[String], ranked: impl Iterator<Item = usize>, budget_chars: usize) -> String {
+    let mut used = 0;
+    let mut picked: Vec<usize> = Vec::new();
+    for i in ranked {
+        let len = entries[i].chars().count() + 2;
+        if used + len <= budget_chars {
+            used += len;
+            picked.push(i);
+        }
+    }
+    picked.sort();
+    picked.into_iter().map(|i| entries[i].as_str()).collect::<Vec<_>>().join("\n\n")
+}
+
+/// `## ` sections of MEMORY.md without separators or template placeholders
+fn entries(memory: &str) -> Vec<String> {
+    let mut entries: Vec<String> = Vec::new();
+    for line in memory.lines() {
+        if line.trim() == "---" {
+            continue;
+        }
+        if line.starts_with("## ") || entries.is_empty() {
+            entries.push(String::new());
+        }
+        let entry = entries.last_mut().expect("pushed above");
+        entry.push_str(line);
+        entry.push('\n');
+    }
+    entries
+        .into_iter()
+        .map(|e| e.trim().to_string())
+        .filter(|e| {
+            let body = e.split_once('\n').map(|(_, body)| body.trim()).unwrap_or_default();
+            !body.is_empty() && !body.starts_with(PLACEHOLDER_PREFIX)
+        })
+        .collect()
+}
+
+/// Lowercased words of 4+ letters
+fn words(text: &str) -> std::collections::HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 4)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
@@ -0,0 +1,100 @@
+//! Working memory - The current session, verbatim
+//!
+//! Stored messages are split into sessions wherever more than
+//! `memory.session_idle_minutes` passed between two messages. A turn sees the
+//! current session's newest messages, up to `memory.session_max_messages` and
+//! `memory.budgets.working_chars`; earlier sessions reach the prompt as
+//! episodes instead.
+
+use chrono::Duration;
+
+use crate::config::MemoryConfig;
+use crate::context_budget::HistoryWindow;
+use crate::store::ConversationMessage;
+
+pub fn idle_gap(config: &MemoryConfig) -> Duration {
+    Duration::minutes(config.session_idle_minutes.max(1))
+}
+
+/// `history` (oldest first) split into sessions; the last one is the current session
+pub fn sessions(history: &[ConversationMessage], idle: Duration) -> Vec<&[ConversationMessage]> {
+    let mut sessions = Vec::new();
+    let mut start = 0;
+    for i in 1..history.len() {
+        if history[i].created_at - history[i - 1].created_at > idle {
+            sessions.push(&history[start..i]);
+            start = i;
+        }
+    }
+    if start < history.len() {
+        sessions.push(&history[start..]);
+    }
+    sessions
+}
+
+/// The part of `history` (oldest first) a turn sees. The newest message is
+/// always included, even over budget.
+pub fn window(mut history: Vec<ConversationMessage>, config: &MemoryConfig) -> (Vec<ConversationMessage>, HistoryWindow) {
+    let fetched = history.len();
+    let session_len = sessions(&history, idle_gap(config)).last().map_or(0, |s| s.len());
+
+    let mut keep = 0;
+    let mut chars = 0;
+    for msg in history.iter().rev().take(session_len.min(config.session_max_messages)) {
+        chars += msg.content.chars().count();
+        if keep > 0 && chars > config.budgets.working_chars {
+            break;
+        }
+        keep += 1;
+    }
+    let history = history.split_off(fetched - keep);
+
+    let truncated = keep < fetched;
+    let window = HistoryWindow {
+        included: history.len(),
+        max_messages: config.session_max_messages,
+        truncated,
+        truncated_before: history.first().filter(|_| truncated).map(|m| m.created_at),
+    };
+    (history, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_window_keeps_current_session_within_budget() {
+        let at = |minute: i64| Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap() + Duration::minutes(minute);
+        let message = |id: i64, minute: i64, content: &str| ConversationMessage {
+            id,
+            user_id: "u1".to_string(),
+            role: if id % 2 == 1 { "user" } else { "assistant" }.to_string(),
+            content: content.to_string(),
+            channel: "http".to_string(),
+            tool_calls: None,
+            created_at: at(minute),
+        };
+        let history = vec![
+            message(1, 0, "find roofers in Austin"),
+            message(2, 1, "found 12 roofers"),
+            message(3, 120, "any update on the Miami job?"),
+            message(4, 121, "it finished, 40 leads"),
+            message(5, 125, "send me the top ten"),
+        ];
+        let mut config: MemoryConfig = serde_yaml::from_str("session_max_messages: 50\npreferences_path: p.yaml").unwrap();
+        assert_eq!(sessions(&history, idle_gap(&config)).len(), 2);
+
+        let (kept, window) = window(history.clone(), &config);
+        assert_eq!(kept.iter().map(|m| m.id).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert!(window.truncated);
+        assert_eq!(window.truncated_before, Some(at(120)));
+
+        config.budgets.working_chars = 45;
+        let (kept, _) = self::window(history.clone(), &config);
+        assert_eq!(kept.iter().map(|m| m.id).collect::<Vec<_>>(), vec![4, 5]);
+        config.budgets.working_chars = 1;
+        assert_eq!(self::window(history, &config).0.len(), 1);
+    }
+}
@@ -0,0 +1,413 @@
+//! Model Router - Which provider and model each LLM call goes to
+//!
+//! Every call names its phase:
+//! - `main`: chat turns
+//! - `followup`: the summary after tool calls
+//! - `capabilities`: answering "what can you do?"
+//! - `planning`: job plans, recovery plans and plan adaptation
+//! - `tagging`: conversation tagging
+//! - `reflection`: post-turn learning
+//! - `summary`: episodic memory summaries
+//!
+//! The first `routing.rules` entry for the phase picks the model (and optionally
+//! another provider). Phases without a rule keep their default: the `llm` config,
+//! or Anthropic with the planner model for planning and tagging. When the chosen
+//! provider still answers 429 or 5xx after the retries `retry.rs` allows, the
+//! call is tried once on `routing.fallback`.
+//!
+//! On local-only and Bedrock nodes (`llm::routed`) rules and fallbacks that
+//! would send calls to another provider are ignored.
+//!
+//! Each node has its own router (`AppState::router`), so fleet members route by
+//! their own config.
+//!
+//! Every call passes the node's spend throttle (`spend.rs`) first and is
+//! charged to it afterwards.
+
+use std::time::Instant;
+
+use serde_json::Value;
+
+use crate::config::{LlmConfig, NodeConfig, RouteRule, RoutingConfig};
+use crate::llm::{self, ApiError, ChatRequest, ChatResponse, OnText, OnToolCall, ToolCall};
+use crate::llm_audit::Recorder;
+use crate::{retry, spend};
+
+pub const MAIN: &str = "main";
+pub const FOLLOWUP: &str = "followup";
+pub const CAPABILITIES: &str = "capabilities";
+pub const PLANNING: &str = "planning";
+pub const TAGGING: &str = "tagging";
+pub const REFLECTION: &str = "reflection";
+pub const SUMMARY: &str = "summary";
+
+const PHASES: [&str; 7] = [MAIN, FOLLOWUP, CAPABILITIES, PLANNING, TAGGING, REFLECTION, SUMMARY];
+
+#[derive(Default)]
+pub struct ModelRouter {
+    rules: Vec<RouteRule>,
+    fallback: Option<LlmConfig>,
+    /// The node's `llm`, when all its calls have to stay there (`llm::routed`)
+    routed: Option<LlmConfig>,
+    /// Where the node's exchanges are recorded (`llm_audit.rs`)
+    audit: Option<Recorder>,
+}
+
+/// Errors worth another provider: rate limits and server errors
+fn retryable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ApiError>().is_some_and(|e| e.status == 429 || e.status >= 500)
+}
+
+/// A fallback being tried after the routed provider failed
+struct Fallback<'a> {
+    config: &'a LlmConfig,
+    primary: String,
+    error: anyhow::Error,
+}
+
+impl Fallback<'_> {
+    /// `error` from the fallback, with what failed first
+    fn failed(self, error: anyhow::Error) -> anyhow::Error {
+        anyhow::anyhow!("{} (fallback {} after {}: {})", error, self.config.provider, self.primary, self.error)
+    }
+}
+
+/// One `phase` chat on `config`'s provider: retried, audited and charged
+async fn chat_on(audit: Option<&Recorder>, phase: &str, config: &LlmConfig, messages: &[Value], tools: &[Value]) -> anyhow::Result<ChatResponse> {
+    let provider = llm::provider(config)?;
+    let tools = if provider.supports_tools() { tools.to_vec() } else { vec![] };
+    let mut request = ChatRequest::new(&config.model, messages.to_vec(), tools);
+    let started = Instant::now();
+    let result = llm::chat_with_retry(provider.as_ref(), &mut request).await;
+    if let Some(audit) = audit {
+        audit.record(phase, provider.name(), &request, result.as_ref().map(|r| &r.raw), started);
+    }
+    let response = result?;
+    spend::record_llm(provider.name(), &response.model, &response.raw, 1.0);
+    Ok(response)
+}
+
+/// Where a streamed chat's output goes, across the routed provider and its fallback
+struct Stream<'a, 'f> {
+    on_text: &'a mut OnText<'f>,
+    on_tool_call: &'a mut OnToolCall<'f>,
+    /// Set once a tool call has been passed on
+    passed_on: bool,
+}
+
+/// `chat_on`, streamed into `stream`. Once a tool call has been passed on, a
+/// failure is no longer retried.
+async fn stream_on(
+    audit: Option<&Recorder>,
+    phase: &str,
+    config: &LlmConfig,
+    messages: &[Value],
+    tools: &[Value],
+    stream: &mut Stream<'_, '_>,
+) -> anyhow::Result<ChatResponse> {
+    let provider = llm::provider(config)?;
+    let tools = if provider.supports_tools() { tools.to_vec() } else { vec![] };
+    let request = ChatRequest::new(&config.model, messages.to_vec(), tools);
+    let started = Instant::now();
+    let mut attempt = 1;
+    let result = loop {
+        let Stream { on_text, on_tool_call, passed_on } = &mut *stream;
+        let mut on_tool_call = |call: &ToolCall| {
+            *passed_on = true;
+            on_tool_call(call);
+        };
+        let error = match provider.chat_stream(&request, *on_text, &mut on_tool_call).await {
+            Ok(response) => break Ok(response),
+            Err(e) if stream.passed_on => break Err(anyhow::anyhow!("{} (after starting tools)", e)),
+            Err(e) => e,
+        };
+        if let Err(e) = retry::wait_to_retry(provider.name(), attempt, error).await {
+            break Err(e);
+        }
+        attempt += 1;
+    };
+    if let Some(audit) = audit {
+        audit.record(phase, provider.name(), &request, result.as_ref().map(|r| &r.raw), started);
+    }
+    let response = result?;
+    spend::record_llm(provider.name(), &response.model, &response.raw, 1.0);
+    Ok(response)
+}
+
+/// `LlmProvider::call_tool` on `config`'s provider: retried, audited and charged
+async fn call_tool_on(audit: Option<&Recorder>, phase: &str, config: &LlmConfig, messages: &[Value], max_tokens: u32, tool: &Value) -> anyhow::Result<Value> {
+    let provider = llm::provider(config)?;
+    let model = config.model.as_str();
+    let mut request = ChatRequest::new(model, messages.to_vec(), vec![]);
+    request.max_tokens = max_tokens;
+    let started = Instant::now();
+    let result = retry::with_backoff(provider.name(), || provider.call_tool(&request, tool)).await;
+    // call_tool returns only the tool input, so that stands in for the response
+    let audited = ChatRequest {
+        tools: vec![tool.clone()],
+        tool_choice: tool["name"].as_str().map(str::to_string),
+        max_tokens,
+        ..ChatRequest::new(model, messages.to_vec(), vec![])
+    };
+    let called = result.as_ref().map(|input| serde_json::json!({ "tool_call": { "name": tool["name"], "input": input } }));
+    if let Some(audit) = audit {
+        audit.record(phase, provider.name(), &audited, called.as_ref().map_err(|e| *e), started);
+    }
+    let input = result?;
+    // call_tool doesn't return usage: estimate ~4 characters per token
+    if let Some(throttle) = spend::throttle() {
+        let prompt_chars: usize = messages.iter().chain([tool]).map(|v| v.to_string().len()).sum();
+        let completion_chars = input.to_string().len();
+        throttle.charge_llm(provider.name(), model, prompt_chars.div_ceil(4) as u64, completion_chars.div_ceil(4) as u64);
+    }
+    Ok(input)
+}
+
+impl ModelRouter {
+    pub fn new(config: &RoutingConfig, llm: &LlmConfig) -> Self {
+        Self {
+            rules: config.rules.clone(),
+            fallback: config.fallback.clone(),
+            routed: llm::routed(llm).cloned(),
+            audit: None,
+        }
+    }
+
+    /// Record every exchange with `audit`
+    pub fn with_audit(mut self, audit: Option<Recorder>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// The router for the node `config` describes
+    pub fn for_node(config: &NodeConfig) -> Self {
+        let routing = &config.routing;
+        for rule in &routing.rules {
+            if !PHASES.contains(&rule.phase.as_str()) {
+                tracing::warn!("routing: unknown phase {:?} (expected one of {})", rule.phase, PHASES.join(", "));
+            }
+        }
+        if !routing.rules.is_empty() || routing.fallback.is_some() {
+            tracing::info!(
+                "🔀 Model routing for {}: {} rule(s), fallback {}",
+                config.node.id,
+                routing.rules.len(),
+                routing.fallback.as_ref().map_or("none".to_string(), |f| format!("{} {}", f.provider, f.model))
+            );
+        }
+        match config.llm.provider.as_str() {
+            "ollama" => tracing::info!("🏠 Local LLM: all model calls of {} go to Ollama ({})", config.node.id, config.llm.model),
+            "bedrock" => tracing::info!(
+                "☁️ Bedrock: all model calls of {} go to {} in {}",
+                config.node.id, config.llm.model, llm::bedrock_region(&config.llm)
+            ),
+            _ => {}
+        }
+        Self::new(routing, &config.llm)
+    }
+
+    /// The node's `llm` when every call has to go there, else None
+    pub fn routed(&self) -> Option<&LlmConfig> {
+        self.routed.as_ref()
+    }
+
+    /// Whether `config` keeps calls where `routed` wants them
+    fn allowed(&self, config: &LlmConfig) -> bool {
+        self.routed.as_ref().is_none_or(|routed| routed.provider == config.provider)
+    }
+
+    /// `primary`, or `spend.degrade_to` while the spend bucket is empty (waiting
+    /// for it to refill when there is no cheaper model)
+    async fn within_spend(&self, phase: &str, primary: LlmConfig) -> anyhow::Result<LlmConfig> {
+        let Some(throttle) = spend::throttle().filter(|t| t.is_empty()) else {
+            return Ok(primary);
+        };
+        match throttle.degrade_to().filter(|cheaper| self.allowed(cheaper)) {
+            Some(cheaper) => {
+                if cheaper.model != primary.model {
+                    tracing::info!("💸 Spend limit: {} call uses {} instead of {}", phase, cheaper.model, primary.model);
+                }
+                Ok(cheaper.clone())
+            }
+            None => {
+                throttle.wait().await?;
+                Ok(primary)
+            }
+        }
+    }
+
+    /// The config a `phase` call uses when it would otherwise go to `default`
+    pub fn route(&self, phase: &str, default: &LlmConfig) -> LlmConfig {
+        let Some(rule) = self.rules.iter().find(|rule| rule.phase == phase) else {
+            return default.clone();
+        };
+
+        let mut config = default.clone();
+        if let Some(provider) = rule.provider.as_ref().filter(|p| **p != default.provider) {
+            config = LlmConfig {
+                provider: provider.clone(),
+                api_key_env: crate::config::default_api_key_env(provider).unwrap_or_default().to_string(),
+                model: String::new(),
+                base_url: None,
+                supports_tools: None,
+                region: None,
+                context_window: None,
+            };
+        }
+        config.model = rule.model.clone();
+        if let Some(api_key_env) = &rule.api_key_env {
+            config.api_key_env = api_key_env.clone();
+        }
+        if let Some(base_url) = &rule.base_url {
+            config.base_url = Some(base_url.clone());
+        }
+
+        if !self.allowed(&config) {
+            tracing::warn!("routing: ignoring {} rule for {} on a {} node", phase, config.provider, default.provider);
+            return default.clone();
+        }
+        config
+    }
+
+    /// The fallback for calls that failed on `primary`, unless it is the same model
+    fn fallback_for(&self, primary: &LlmConfig) -> Option<&LlmConfig> {
+        self.fallback
+            .as_ref()
+            .filter(|f| (f.provider != primary.provider || f.model != primary.model) && self.allowed(f))
+    }
+
+    /// After `error` on `primary`: the fallback to try once if it was a
+    /// 429/5xx, otherwise the error
+    fn fallback_after(&self, phase: &str, primary: &LlmConfig, error: anyhow::Error) -> anyhow::Result<Fallback<'_>> {
+        let Some(fallback) = self.fallback_for(primary).filter(|_| retryable(&error)) else {
+            return Err(error);
+        };
+        tracing::warn!(
+            "{} {} failed for {} ({}), trying fallback {} {}",
+            primary.provider, primary.model, phase, error, fallback.provider, fallback.model
+        );
+        Ok(Fallback { config: fallback, primary: primary.provider.clone(), error })
+    }
+
+    /// Chat for `phase`; tools are dropped for models that can't take them.
+    /// Returns the response and the provider that answered.
+    pub async fn chat(
+        &self,
+        phase: &str,
+        default: &LlmConfig,
+        messages: &[Value],
+        tools: &[Value],
+    ) -> anyhow::Result<(ChatResponse, String)> {
+        let primary = self.within_spend(phase, self.route(phase, default)).await?;
+        let error = match chat_on(self.audit.as_ref(), phase, &primary, messages, tools).await {
+            Ok(response) => return Ok((response, primary.provider)),
+            Err(e) => e,
+        };
+        let fallback = self.fallback_after(phase, &primary, error)?;
+        match chat_on(self.audit.as_ref(), phase, fallback.config, messages, tools).await {
+            Ok(response) => Ok((response, fallback.config.provider.clone())),
+            Err(e) => Err(fallback.failed(e)),
+        }
+    }
+
+    /// `chat`, streamed (see `LlmProvider::chat_stream`). Once a tool call has
+    /// been passed on, a failure is no longer retried and doesn't fall back:
+    /// the tool is already running.
+    pub async fn chat_stream(
+        &self,
+        phase: &str,
+        default: &LlmConfig,
+        messages: &[Value],
+        tools: &[Value],
+        on_text: &mut OnText<'_>,
+        on_tool_call: &mut OnToolCall<'_>,
+    ) -> anyhow::Result<(ChatResponse, String)> {
+        let mut stream = Stream { on_text, on_tool_call, passed_on: false };
+        let primary = self.within_spend(phase, self.route(phase, default)).await?;
+        let error = match stream_on(self.audit.as_ref(), phase, &primary, messages, tools, &mut stream).await {
+            Ok(response) => return Ok((response, primary.provider)),
+            Err(e) => e,
+        };
+        let fallback = self.fallback_after(phase, &primary, error)?;
+        match stream_on(self.audit.as_ref(), phase, fallback.config, messages, tools, &mut stream).await {
+            Ok(response) => Ok((response, fallback.config.provider.clone())),
+            Err(e) => Err(fallback.failed(e)),
+        }
+    }
+
+    /// Prompt tokens a `phase` call would use, counted by the routed provider
+    /// (no fallback: this is only a measurement)
+    pub async fn count_tokens(&self, phase: &str, default: &LlmConfig, messages: &[Value], tools: &[Value]) -> anyhow::Result<u64> {
+        let config = self.route(phase, default);
+        let provider = llm::provider(&config)?;
+        let tools = if provider.supports_tools() { tools.to_vec() } else { vec![] };
+        provider.count_tokens(&ChatRequest::new(&config.model, messages.to_vec(), tools)).await
+    }
+
+    /// `LlmProvider::call_tool` for `phase`
+    pub async fn call_tool(
+        &self,
+        phase: &str,
+        default: &LlmConfig,
+        messages: &[Value],
+        max_tokens: u32,
+        tool: &Value,
+    ) -> anyhow::Result<Value> {
+        let primary = self.within_spend(phase, self.route(phase, default)).await?;
+        let error = match call_tool_on(self.audit.as_ref(), phase, &primary, messages, max_tokens, tool).await {
+            Ok(input) => return Ok(input),
+            Err(e) => e,
+        };
+        let fallback = self.fallback_after(phase, &primary, error)?;
+        call_tool_on(self.audit.as_ref(), phase, fallback.config, messages, max_tokens, tool)
+            .await
+            .map_err(|e| fallback.failed(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_and_fallback() {
+        let config: RoutingConfig = serde_yaml::from_str(
+            r#"
+rules:
+  - phase: planning
+    model: claude-3-5-haiku-20241022
+  - phase: followup
+    provider: openai
+    model: gpt-4o-mini
+fallback:
+  provider: openrouter
+  api_key_env: OPENROUTER_API_KEY
+  model: anthropic/claude-sonnet-4
+"#,
+        )
+        .unwrap();
+        let default: LlmConfig = serde_yaml::from_str(
+            "provider: anthropic\napi_key_env: ANTHROPIC_API_KEY\nmodel: claude-sonnet-4-20250514",
+        )
+        .unwrap();
+        let router = ModelRouter::new(&config, &default);
+
+        let planning = router.route(PLANNING, &default);
+        assert_eq!((planning.provider.as_str(), planning.model.as_str()), ("anthropic", "claude-3-5-haiku-20241022"));
+        let followup = router.route(FOLLOWUP, &default);
+        assert_eq!((followup.provider.as_str(), followup.api_key_env.as_str()), ("openai", "OPENAI_API_KEY"));
+        assert_eq!(router.route(MAIN, &default).model, default.model);
+
+        assert!(retryable(&ApiError { status: 429, body: String::new() }.into()));
+        assert!(!retryable(&ApiError { status: 400, body: String::new() }.into()));
+        assert_eq!(router.fallback_for(&default).map(|f| f.provider.as_str()), Some("openrouter"));
+        assert!(router.fallback_for(config.fallback.as_ref().unwrap()).is_none());
+
+        // A local-only node keeps every call on its own Ollama
+        let local: LlmConfig = serde_yaml::from_str("provider: ollama\napi_key_env: \"\"\nmodel: llama3.1").unwrap();
+        let router = ModelRouter::new(&config, &local);
+        assert_eq!(router.routed().map(|r| r.provider.as_str()), Some("ollama"));
+        assert_eq!(router.route(FOLLOWUP, &local).provider, "ollama");
+        assert!(router.fallback_for(&local).is_none());
+    }
+}
@@ -4,9 +4,18 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::{Duration, Instant};
 
+/// How long finished jobs stay in a user's chat context ("how did that search go?")
+const RECENT_JOB_WINDOW: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobStatus {
     pub job_id: String,
+    /// Owning user (set for jobs started from chat)
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// What the user asked for, e.g. "Find HVAC companies in Miami"
+    #[serde(default)]
+    pub description: Option<String>,
     pub status: String,
     pub started_at: std::time::SystemTime,
     pub last_update: std::time::SystemTime,
@@ -51,6 +60,7 @@ impl Default for MonitorConfig {
     }
 }
 
+#[derive(Clone)]
 pub struct JobMonitor {
     jobs: Arc<RwLock<HashMap<String, JobStatus>>>,
     config: MonitorConfig,
@@ -157,6 +167,8 @@ impl JobMonitor {
                                 let mut jobs_guard = jobs.write().await;
                                 jobs_guard.insert(job_id.clone(), JobStatus {
                                     job_id: job_id.clone(),
+                                    user_id: None,
+                                    description: None,
                                     status: status.to_string(),
                                     started_at: std::time::SystemTime::now(),
                                     last_update: std::time::SystemTime::now(),
@@ -186,6 +198,109 @@ impl JobMonitor {
         jobs.get(job_id).cloned()
     }
 
+    /// Register a job started on behalf of a user so it shows up in their chat context
+    pub async fn track_job(&self, job_id: &str, user_id: &str, description: &str) {
+        let now = std::time::SystemTime::now();
+        self.jobs.write().await.insert(job_id.to_string(), JobStatus {
+            job_id: job_id.to_string(),
+            user_id: Some(user_id.to_string()),
+            description: Some(description.to_string()),
+            status: "running".to_string(),
+            started_at: now,
+            last_update: now,
+            current_step: None,
+            progress: 0.0,
+            logs: vec![],
+            warnings: vec![],
+        });
+    }
+
+    /// Record the latest status reported by the harness for a tracked job
+    pub async fn record_progress(&self, job_id: &str, status: &str, current_step: Option<String>, progress: f32) {
+        if let Some(job) = self.jobs.write().await.get_mut(job_id) {
+            job.status = status.to_string();
+            job.current_step = current_step;
+            job.progress = progress;
+            job.last_update = std::time::SystemTime::now();
+        }
+    }
+
+    /// Update just the status of a tracked job (e.g. after a pause/resume from chat)
+    pub async fn set_status(&self, job_id: &str, status: &str) {
+        if let Some(job) = self.jobs.write().await.get_mut(job_id) {
+            job.status = status.to_string();
+            job.last_update = std::time::SystemTime::now();
+        }
+    }
+
+    /// Jobs a user should be able to refer to: running ones plus recently finished ones
+    pub async fn jobs_for_user(&self, user_id: &str) -> Vec<JobStatus> {
+        let jobs = self.jobs.read().await;
+        let mut result: Vec<JobStatus> = jobs
+            .values()
+            .filter(|j| j.user_id.as_deref() == Some(user_id))
+            .filter(|j| {
+                is_active(&j.status)
+                    || j.last_update.elapsed().map(|age| age < RECENT_JOB_WINDOW).unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        result.sort_by_key(|j| j.started_at);
+        result
+    }
+
+    /// Every tracked job, for a daemon taking over (see `upgrade.rs`)
+    pub async fn snapshot(&self) -> Vec<JobStatus> {
+        self.jobs.read().await.values().cloned().collect()
+    }
+
+    /// Track jobs handed over by the daemon this one took over from
+    pub async fn restore(&self, jobs: Vec<JobStatus>) {
+        let mut tracked = self.jobs.write().await;
+        for job in jobs {
+            tracked.insert(job.job_id.clone(), job);
+        }
+    }
+
+    /// Every job still running, oldest first
+    pub async fn active_jobs(&self) -> Vec<JobStatus> {
+        let mut jobs: Vec<JobStatus> = self.jobs.read().await.values().filter(|j| is_active(&j.status)).cloned().collect();
+        jobs.sort_by_key(|j| j.started_at);
+        jobs
+    }
+
+    /// Compact "Active Jobs" block for the system prompt, or None if the user has no jobs
+    pub async fn active_jobs_prompt(&self, user_id: &str) -> Option<String> {
+        let jobs = self.jobs_for_user(user_id).await;
+        if jobs.is_empty() {
+            return None;
+        }
+
+        let mut block = String::from(
+            "## Active Jobs\nBackground jobs for this user (live status from the job monitor). \
+             When the user asks about \"that search\" or \"the job\", match it to one of these and \
+             answer from this status - never guess. Use the `job.control` tool to pause, resume, \
+             skip a step of, or cancel one of these jobs when asked.\n",
+        );
+        for job in &jobs {
+            let started_mins = job.started_at.elapsed().map(|d| d.as_secs() / 60).unwrap_or(0);
+            block.push_str(&format!(
+                "- `{}` \"{}\" - {}",
+                job.job_id,
+                job.description.as_deref().unwrap_or("(no description)"),
+                job.status,
+            ));
+            if let Some(step) = &job.current_step {
+                block.push_str(&format!(", step {}", step));
+            }
+            if is_active(&job.status) {
+                block.push_str(&format!(", {:.0}% done", job.progress * 100.0));
+            }
+            block.push_str(&format!(", started {}m ago\n", started_mins));
+        }
+        Some(block)
+    }
+
     pub async fn handle_action(&self, action: MonitorAction, harness_url: &str, job_id: &str) -> anyhow::Result<()> {
         let client = reqwest::Client::new();
         
@@ -214,6 +329,10 @@ impl JobMonitor {
     }
 }
 
+fn is_active(status: &str) -> bool {
+    !matches!(status, "completed" | "failed" | "cancelled")
+}
+
 impl Default for JobMonitor {
     fn default() -> Self {
         Self::new(MonitorConfig::default())
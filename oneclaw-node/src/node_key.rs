@@ -0,0 +1,145 @@
+//! Node Key - Persistent Ed25519 identity for this node
+//!
+//! Generated on first run and stored at `~/.oneclaw/node_key`. The public key is
+//! exposed on `/health`, and outbound requests to the control plane, harness and
+//! webhooks carry a signature so receivers can tell which node sent them.
+//!
+//! Signed requests carry these headers:
+//! - `X-OneClaw-Node-Id`: the sending node's id (a fleet member's own)
+//! - `X-OneClaw-Public-Key`: base64 Ed25519 public key
+//! - `X-OneClaw-Timestamp`: unix seconds
+//! - `X-OneClaw-Signature`: base64 signature over the canonical string
+//!
+//! Canonical string: `{timestamp}\n{METHOD}\n{path?query}\n{hex(sha256(body))}`
+
+use base64::Engine;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static NODE_KEY: OnceLock<NodeKey> = OnceLock::new();
+
+pub struct NodeKey {
+    keypair: Ed25519KeyPair,
+    public_key: String,
+}
+
+impl NodeKey {
+    fn from_pkcs8(pkcs8: &[u8]) -> anyhow::Result<Self> {
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8)
+            .map_err(|e| anyhow::anyhow!("Invalid node key: {}", e))?;
+        let public_key = base64::engine::general_purpose::STANDARD.encode(keypair.public_key().as_ref());
+        Ok(Self { keypair, public_key })
+    }
+
+    /// Base64-encoded Ed25519 public key
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Sign arbitrary bytes, returning a base64 signature
+    pub fn sign(&self, message: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.keypair.sign(message).as_ref())
+    }
+
+    /// Build signature headers for an outbound request from node `node_id`
+    pub fn signed_headers(&self, node_id: &str, method: &str, url: &str, body: &[u8]) -> Vec<(&'static str, String)> {
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let canonical = canonical_string(&timestamp, method, url, body);
+
+        vec![
+            ("X-OneClaw-Node-Id", node_id.to_string()),
+            ("X-OneClaw-Public-Key", self.public_key.clone()),
+            ("X-OneClaw-Timestamp", timestamp),
+            ("X-OneClaw-Signature", self.sign(canonical.as_bytes())),
+        ]
+    }
+}
+
+pub fn key_path() -> anyhow::Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("No home dir"))?;
+    Ok(home.join(".oneclaw").join("node_key"))
+}
+
+/// Load the node keypair, generating and persisting one on first run
+pub fn load_or_generate() -> anyhow::Result<&'static NodeKey> {
+    if let Some(key) = NODE_KEY.get() {
+        return Ok(key);
+    }
+
+    let path = key_path()?;
+    let key = if path.exists() {
+        let encoded = std::fs::read_to_string(&path)?;
+        let pkcs8 = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| anyhow::anyhow!("Corrupt node key at {:?}: {}", path, e))?;
+        NodeKey::from_pkcs8(&pkcs8)?
+    } else {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng)
+            .map_err(|_| anyhow::anyhow!("Failed to generate node key"))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Created owner-only, so the key is never readable by others, even briefly
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        std::io::Write::write_all(&mut file, base64::engine::general_purpose::STANDARD.encode(pkcs8.as_ref()).as_bytes())?;
+
+        let key = NodeKey::from_pkcs8(pkcs8.as_ref())?;
+        tracing::info!(public_key = %key.public_key(), "Generated new node keypair at {:?}", path);
+        key
+    };
+
+    NODE_KEY.set(key).ok();
+    Ok(NODE_KEY.get().unwrap())
+}
+
+/// Signature headers for an outbound request from node `node_id` (a fleet
+/// member's own id), or none if the key is unavailable
+pub fn signed_headers(node_id: &str, method: &str, url: &str, body: &[u8]) -> Vec<(&'static str, String)> {
+    match load_or_generate() {
+        Ok(key) => key.signed_headers(node_id, method, url, body),
+        Err(e) => {
+            tracing::warn!("Node key unavailable, sending unsigned request: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Whether `signature` (base64) is `public_key`'s signature of a request, as
+/// sent in the `X-OneClaw-*` headers; `url` may be just the path and query
+pub fn verify(public_key: &str, signature: &str, timestamp: &str, method: &str, url: &str, body: &[u8]) -> bool {
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let (Ok(key), Ok(signature)) = (b64.decode(public_key), b64.decode(signature)) else {
+        return false;
+    };
+    let canonical = canonical_string(timestamp, method, url, body);
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key)
+        .verify(canonical.as_bytes(), &signature)
+        .is_ok()
+}
+
+fn canonical_string(timestamp: &str, method: &str, url: &str, body: &[u8]) -> String {
+    let path = url::Url::parse(url)
+        .map(|u| match u.query() {
+            Some(q) => format!("{}?{}", u.path(), q),
+            None => u.path().to_string(),
+        })
+        .unwrap_or_else(|_| url.to_string());
+
+    let body_hash = ring::digest::digest(&ring::digest::SHA256, body)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    format!("{}\n{}\n{}\n{}", timestamp, method.to_uppercase(), path, body_hash)
+}
@@ -0,0 +1,158 @@
+//! Notify - Proactive messages to a user on every linked channel
+//!
+//! `POST /notify` (and anything else that needs to reach a user unprompted, like
+//! heartbeat checks or autonomous jobs) names a user, and the message goes out on
+//! each channel that user has an identity on. Every identity gets a receipt:
+//! delivered, failed with the provider error, or skipped when its channel isn't
+//! running or can't start a conversation with that identity.
+
+use serde::Serialize;
+
+use crate::channels::outbound::Priority;
+use crate::channels::registry::ChannelRegistry;
+use crate::channels::OutgoingMessage;
+use crate::snooze::Snoozes;
+use crate::store::{Identity, Store};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+    Skipped,
+}
+
+/// What happened for one linked identity
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt {
+    /// Identity provider ("telegram", "email", ...)
+    pub provider: String,
+    pub provider_id: String,
+    /// Channel the message went out on, when there is one
+    pub channel: Option<String>,
+    pub status: DeliveryStatus,
+    /// Provider error, or why the identity was skipped
+    pub error: Option<String>,
+}
+
+/// Channel and chat id that reach an identity directly. Discord and Matrix user
+/// ids aren't conversations (a DM room would have to be opened first), and HTTP
+/// or voice callers have nowhere to be reached.
+fn route(identity: &Identity) -> Result<(&'static str, String), &'static str> {
+    match identity.provider.as_str() {
+        // A private Telegram chat has the user's id; groups are linked by chat id
+        "telegram" | "telegram_group" => Ok(("telegram", identity.provider_id.clone())),
+        "email" => Ok(("email", identity.provider_id.clone())),
+        // chat.postMessage to a user id opens the DM
+        "slack" => Ok(("slack", identity.provider_id.clone())),
+        "whatsapp" => Ok(("whatsapp", identity.provider_id.clone())),
+        "discord" | "matrix" => Err("no direct-message route for this provider"),
+        _ => Err("provider can't be messaged"),
+    }
+}
+
+/// Send `message` to every identity linked to `user_id`, optionally only on the
+/// channels named in `only`. Delivered messages can be snoozed from the chat.
+pub async fn notify_user(
+    store: &dyn Store,
+    channels: &ChannelRegistry,
+    snoozes: &Snoozes,
+    user_id: &str,
+    message: &str,
+    only: Option<&[String]>,
+) -> anyhow::Result<Vec<Receipt>> {
+    let mut receipts = vec![];
+    for identity in store.get_user_identities(user_id).await? {
+        let mut receipt = Receipt {
+            provider: identity.provider.clone(),
+            provider_id: identity.provider_id.clone(),
+            channel: None,
+            status: DeliveryStatus::Skipped,
+            error: None,
+        };
+        let (name, chat_id) = match route(&identity) {
+            Ok(route) => route,
+            Err(reason) => {
+                receipt.error = Some(reason.to_string());
+                receipts.push(receipt);
+                continue;
+            }
+        };
+        receipt.channel = Some(name.to_string());
+        if only.is_some_and(|only| !only.iter().any(|c| c == name)) {
+            receipt.error = Some("channel not requested".to_string());
+            receipts.push(receipt);
+            continue;
+        }
+        let Some(outbound) = channels.outbound(name).await else {
+            receipt.error = Some(format!("{} channel is not running", name));
+            receipts.push(receipt);
+            continue;
+        };
+
+        let msg = OutgoingMessage {
+            channel_type: outbound.channel_type(),
+            channel_id: chat_id.clone(),
+            content: message.to_string(),
+            reply_to: None,
+            thread_id: None,
+            metadata: serde_json::json!({ "subject": "Notification" }),
+            attachments: vec![],
+            buttons: vec![],
+        };
+        match outbound.send_with_priority(msg, Priority::Notification).await {
+            Ok(()) => {
+                snoozes.remember(name, &chat_id, message);
+                receipt.status = DeliveryStatus::Delivered;
+            }
+            Err(e) => {
+                tracing::warn!("Notification to {} via {} failed: {}", user_id, name, e);
+                receipt.status = DeliveryStatus::Failed;
+                receipt.error = Some(e.to_string());
+            }
+        }
+        receipts.push(receipt);
+    }
+
+    let delivered = receipts.iter().filter(|r| r.status == DeliveryStatus::Delivered).count();
+    tracing::info!("🔔 Notified {} on {} of {} linked identities", user_id, delivered, receipts.len());
+    Ok(receipts)
+}
+
+/// Send `message` to each of `owners` (`security.owners`: user ids or
+/// `provider:id`). Owners who haven't messaged the node yet can't be reached.
+pub async fn notify_owners(store: &dyn Store, channels: &ChannelRegistry, snoozes: &Snoozes, owners: &[String], message: &str) {
+    for owner in owners {
+        match crate::impersonate::resolve_target(store, owner).await {
+            Ok(Some(user_id)) => {
+                if let Err(e) = notify_user(store, channels, snoozes, &user_id, message, None).await {
+                    tracing::warn!("Failed to notify owner {}: {}", owner, e);
+                }
+            }
+            _ => tracing::warn!("Owner {} hasn't messaged the node yet; can't notify them", owner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(provider: &str, provider_id: &str) -> Identity {
+        Identity {
+            user_id: "user_1".to_string(),
+            provider: provider.to_string(),
+            provider_id: provider_id.to_string(),
+            username: None,
+            linked_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_routes_for_providers() {
+        assert_eq!(route(&identity("telegram_group", "-1001")), Ok(("telegram", "-1001".to_string())));
+        assert_eq!(route(&identity("email", "a@b.co")), Ok(("email", "a@b.co".to_string())));
+        assert!(route(&identity("discord", "42")).is_err());
+        assert!(route(&identity("http", "anonymous")).is_err());
+    }
+}
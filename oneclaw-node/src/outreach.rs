@@ -0,0 +1,211 @@
+//! Outreach - Call scripts and voicemail drafts for phone outreach
+//!
+//! Deterministic, template-based drafting from a lead's discovery, enrichment and
+//! audit data. Built-in templates can be overridden per workspace:
+//!
+//! - `<workspace>/outreach/call-script.md`
+//! - `<workspace>/outreach/voicemail.md`
+//!
+//! Templates use `{{placeholder}}` syntax: business_name, contact_name, city,
+//! rating, review_count, website, audit_score, top_issue, top_recommendation,
+//! sender_name.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Planner actions handled on the node instead of the harness
+pub const LOCAL_ACTIONS: &[&str] = &["call-script", "voicemail"];
+
+const DEFAULT_CALL_SCRIPT: &str = "\
+**Opener**
+Hi, is this {{contact_name}}? This is {{sender_name}} - I'll be quick. I was looking at {{business_name}} online and noticed something worth two minutes of your time.
+
+**Hook**
+You've built a great reputation in {{city}} - {{rating}} stars across {{review_count}} reviews. But {{top_issue}}.
+
+**Value**
+{{top_recommendation}} Businesses we work with usually see more calls from search within a few weeks.
+
+**Ask**
+Would you be open to a 15-minute call this week so I can walk you through the full audit (your site scored {{audit_score}}/100)?
+
+**If they're busy**
+No problem - can I email you the audit report and follow up Thursday?
+
+**Objection: \"We already have someone\"**
+Totally fair. The report is free either way - it's a useful second opinion for whoever handles your site.
+";
+
+const DEFAULT_VOICEMAIL: &str = "\
+Hi {{contact_name}}, this is {{sender_name}}. I ran a quick audit of {{business_name}}'s website and found that {{top_issue}}. \
+I put together a short report with fixes - your site scored {{audit_score}} out of 100. \
+I'll send it over by email, or call me back at your convenience. Thanks!
+";
+
+/// Template kinds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DraftKind {
+    CallScript,
+    Voicemail,
+}
+
+impl DraftKind {
+    pub fn from_action(action: &str) -> Option<Self> {
+        match action {
+            "call-script" | "call_script" => Some(Self::CallScript),
+            "voicemail" => Some(Self::Voicemail),
+            _ => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::CallScript => "call-script.md",
+            Self::Voicemail => "voicemail.md",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::CallScript => DEFAULT_CALL_SCRIPT,
+            Self::Voicemail => DEFAULT_VOICEMAIL,
+        }
+    }
+}
+
+/// Placeholder values for one lead, built from harness lead/audit JSON
+pub fn lead_context(lead: &Value, contact: Option<&Value>, audit: Option<&Value>, sender_name: &str) -> HashMap<&'static str, String> {
+    let text = |v: &Value| v.as_str().map(str::to_string);
+    let number = |v: &Value| v.as_f64().map(|n| format!("{}", n));
+
+    let top_issue = audit
+        .and_then(|a| a["issues"].as_array())
+        .and_then(|issues| {
+            issues
+                .iter()
+                .find(|i| i["type"] == "critical")
+                .or_else(|| issues.first())
+        });
+
+    let mut ctx = HashMap::new();
+    ctx.insert("business_name", text(&lead["name"]).unwrap_or_else(|| "your business".to_string()));
+    ctx.insert(
+        "contact_name",
+        contact.and_then(|c| text(&c["name"])).unwrap_or_else(|| "there".to_string()),
+    );
+    ctx.insert("city", text(&lead["city"]).unwrap_or_else(|| "your area".to_string()));
+    ctx.insert("rating", number(&lead["rating"]).unwrap_or_else(|| "great".to_string()));
+    ctx.insert(
+        "review_count",
+        number(&lead["reviewCount"]).unwrap_or_else(|| "many".to_string()),
+    );
+    ctx.insert("website", text(&lead["website"]).unwrap_or_default());
+    ctx.insert(
+        "audit_score",
+        audit.and_then(|a| number(&a["score"])).unwrap_or_else(|| "—".to_string()),
+    );
+    ctx.insert(
+        "top_issue",
+        top_issue
+            .and_then(|i| text(&i["message"]))
+            .map(|m| lowercase_first(&m))
+            .unwrap_or_else(|| "your site isn't showing up for some of the searches your customers use".to_string()),
+    );
+    ctx.insert(
+        "top_recommendation",
+        top_issue
+            .and_then(|i| text(&i["recommendation"]))
+            .unwrap_or_else(|| "A few targeted fixes to your site and listings usually close that gap.".to_string()),
+    );
+    ctx.insert("sender_name", sender_name.to_string());
+    ctx
+}
+
+/// Render a draft, preferring the workspace template over the built-in one
+pub fn draft(kind: DraftKind, ctx: &HashMap<&'static str, String>, workspace: Option<&Path>) -> String {
+    let template = workspace
+        .map(|w| w.join("outreach").join(kind.file_name()))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_else(|| kind.default_template().to_string());
+    render(&template, ctx)
+}
+
+/// Replace `{{key}}` placeholders; unknown placeholders are left as-is
+pub fn render(template: &str, ctx: &HashMap<&'static str, String>) -> String {
+    let mut out = template.to_string();
+    for (key, value) in ctx {
+        out = out.replace(&format!("{{{{{}}}}}", key), value);
+    }
+    out
+}
+
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Pull node-local steps (call scripts, voicemails) out of a plan before it is
+/// sent to the harness; they run on the job's results once it completes.
+pub fn take_local_steps(plan: &mut crate::autonomous_jobs::JobPlan) -> Vec<crate::autonomous_jobs::JobStep> {
+    let (local, remote): (Vec<_>, Vec<_>) = std::mem::take(&mut plan.steps)
+        .into_iter()
+        .partition(|step| LOCAL_ACTIONS.contains(&step.action.as_str()));
+    plan.steps = remote;
+    local
+}
+
+/// Leads a step drafts for: `params.limit` (default 3)
+fn step_limit(step: &crate::autonomous_jobs::JobStep) -> usize {
+    step.params["limit"].as_u64().unwrap_or(3) as usize
+}
+
+/// The leads `draft_for_results` drafts for, each with the action drafting it
+pub fn drafted_leads<'a>(steps: &'a [crate::autonomous_jobs::JobStep], results: &'a Value) -> Vec<(&'a str, &'a Value)> {
+    let businesses = results["businesses"].as_array().map(Vec::as_slice).unwrap_or_default();
+    steps
+        .iter()
+        .filter(|step| DraftKind::from_action(&step.action).is_some())
+        .flat_map(|step| businesses.iter().take(step_limit(step)).map(move |business| (step.action.as_str(), business)))
+        .collect()
+}
+
+/// Draft local steps for the top leads of a finished job (best score first).
+/// `params.limit` caps the number of leads per step (default 3).
+pub fn draft_for_results(
+    steps: &[crate::autonomous_jobs::JobStep],
+    results: &Value,
+    sender_name: &str,
+    workspace: Option<&Path>,
+) -> String {
+    let businesses = results["businesses"].as_array().cloned().unwrap_or_default();
+    let contacts = results["contacts"].as_array().cloned().unwrap_or_default();
+    let mut output = String::new();
+
+    for step in steps {
+        let Some(kind) = DraftKind::from_action(&step.action) else { continue };
+        let limit = step_limit(step);
+        let (emoji, title) = match kind {
+            DraftKind::CallScript => ("☎️", "Call Script"),
+            DraftKind::Voicemail => ("📼", "Voicemail"),
+        };
+
+        for business in businesses.iter().take(limit) {
+            let contact = contacts.iter().find(|c| c["businessId"] == business["id"]);
+            let audit = Some(&business["metadata"]["audit"]).filter(|a| a.is_object());
+            let ctx = lead_context(business, contact, audit, sender_name);
+            output.push_str(&format!(
+                "{} **{}: {}**\n\n{}\n",
+                emoji,
+                title,
+                ctx["business_name"],
+                draft(kind, &ctx, workspace).trim_end()
+            ));
+            output.push('\n');
+        }
+    }
+    output
+}
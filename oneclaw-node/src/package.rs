@@ -0,0 +1,338 @@
+//! Packages - Shareable workflow and playbook bundles
+//!
+//! A package is one JSON file: a manifest, the files it lists and the publisher's
+//! signature. Files go by their top-level directory:
+//! - `workflows/*.yaml` → `~/.oneclaw/workflows/`
+//! - `templates/**` → the workspace (e.g. `templates/outreach/call-script.md`
+//!   overrides `<workspace>/outreach/call-script.md`)
+//! - `playbooks/*.md` → a section of `<workspace>/PLAYBOOKS.md` marked with the
+//!   package name, replaced when the package is reinstalled
+//!
+//! The manifest pins each file's SHA-256 and is signed with the publisher's
+//! Ed25519 key (their node key, see `oneclaw package`), so the signature covers
+//! every file. Packages install when signed by a key in `packages.trusted_keys`;
+//! anything else needs `--allow-untrusted`. A tampered package never installs.
+//!
+//! ```bash
+//! oneclaw package ./local-seo-audit-sweep        # author: build + sign
+//! oneclaw install local-seo-audit-sweep-1.0.0.json
+//! oneclaw install https://example.com/local-seo-audit-sweep.json
+//! oneclaw install local-seo-audit-sweep          # via packages.registry_url
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::{agent_os, config, node_key, workflow};
+
+/// Top-level directories a package may contain
+const SECTIONS: &[&str] = &["workflows", "templates", "playbooks"];
+
+/// What a package is and which files it carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    /// Base64 Ed25519 key the package is signed with
+    #[serde(default)]
+    pub public_key: String,
+    #[serde(default)]
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    /// Hex SHA-256 of the contents
+    pub sha256: String,
+}
+
+/// A package file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Package {
+    pub manifest: Manifest,
+    /// Base64 contents by path
+    pub files: BTreeMap<String, String>,
+    /// Base64 signature over the JSON-serialized manifest
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// Where a package's files ended up
+#[derive(Debug, Default)]
+pub struct Installed {
+    pub workflows: Vec<String>,
+    pub templates: Vec<String>,
+    pub playbooks: Vec<String>,
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Split a package path into its section and the rest, rejecting anything that
+/// could land outside the section's directory
+fn split_path(path: &str) -> anyhow::Result<(&str, PathBuf)> {
+    let (section, rest) = path.split_once('/').unwrap_or((path, ""));
+    if !SECTIONS.contains(&section) {
+        anyhow::bail!("Unsupported package path {} (expected {})", path, SECTIONS.join("/, "));
+    }
+    let relative = PathBuf::from_iter(rest.split('/').filter(|p| !p.is_empty()));
+    if relative.as_os_str().is_empty() || relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        anyhow::bail!("Invalid package path: {}", path);
+    }
+    Ok((section, relative))
+}
+
+impl Package {
+    /// Every listed file is present with the pinned hash, and nothing else is
+    fn verify_contents(&self) -> anyhow::Result<()> {
+        for file in &self.manifest.files {
+            split_path(&file.path)?;
+            let contents = self.files.get(&file.path)
+                .ok_or_else(|| anyhow::anyhow!("Package is missing {}", file.path))?;
+            if sha256_hex(&b64().decode(contents)?) != file.sha256 {
+                anyhow::bail!("{} does not match its manifest hash", file.path);
+            }
+        }
+        if let Some(extra) = self.files.keys().find(|p| !self.manifest.files.iter().any(|f| &f.path == *p)) {
+            anyhow::bail!("{} is not listed in the manifest", extra);
+        }
+        Ok(())
+    }
+
+    /// Whether the manifest carries a valid signature by `manifest.public_key`
+    fn signature_valid(&self) -> anyhow::Result<bool> {
+        let Some(signature) = &self.signature else {
+            return Ok(false);
+        };
+        let key = b64().decode(&self.manifest.public_key)?;
+        let signature = b64().decode(signature)?;
+        let message = serde_json::to_vec(&self.manifest)?;
+        Ok(ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key)
+            .verify(&message, &signature)
+            .is_ok())
+    }
+
+    /// Check contents and publisher. A bad hash or signature always fails;
+    /// unsigned or untrusted packages only pass with `allow_untrusted`.
+    pub fn verify(&self, trusted_keys: &[String], allow_untrusted: bool) -> anyhow::Result<()> {
+        self.verify_contents()?;
+        let trusted = match self.signature {
+            None => false,
+            Some(_) if !self.signature_valid()? => anyhow::bail!("Package signature is invalid"),
+            Some(_) => trusted_keys.iter().any(|k| k == &self.manifest.public_key),
+        };
+        if !trusted && !allow_untrusted {
+            anyhow::bail!(
+                "{} is {} (add its key {} to packages.trusted_keys or pass --allow-untrusted)",
+                self.manifest.name,
+                if self.signature.is_some() { "signed by an untrusted key" } else { "unsigned" },
+                if self.manifest.public_key.is_empty() { "-" } else { &self.manifest.public_key },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Build and sign a package from `dir` (a `manifest.yaml` with name, version,
+/// description and author, plus `workflows/`, `templates/` and `playbooks/`)
+pub fn pack(dir: &Path) -> anyhow::Result<Package> {
+    let mut manifest: Manifest = serde_yaml::from_str(&std::fs::read_to_string(dir.join("manifest.yaml"))?)?;
+    let mut files = BTreeMap::new();
+    for section in SECTIONS {
+        collect(&dir.join(section), section, &mut files)?;
+    }
+    if files.is_empty() {
+        anyhow::bail!("No files under {}", SECTIONS.join("/, "));
+    }
+
+    manifest.files = files
+        .iter()
+        .map(|(path, bytes)| ManifestFile { path: path.clone(), sha256: sha256_hex(bytes) })
+        .collect();
+    let key = node_key::load_or_generate()?;
+    manifest.public_key = key.public_key().to_string();
+    let signature = key.sign(&serde_json::to_vec(&manifest)?);
+
+    Ok(Package {
+        manifest,
+        files: files.into_iter().map(|(path, bytes)| (path, b64().encode(bytes))).collect(),
+        signature: Some(signature),
+    })
+}
+
+fn collect(dir: &Path, prefix: &str, files: &mut BTreeMap<String, Vec<u8>>) -> anyhow::Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            collect(&entry.path(), &path, files)?;
+        } else {
+            files.insert(path, std::fs::read(entry.path())?);
+        }
+    }
+    Ok(())
+}
+
+/// Read a package from a file, a URL or the registry (by name)
+pub async fn fetch(source: &str, config: &config::NodeConfig) -> anyhow::Result<Package> {
+    let bytes = if Path::new(source).exists() {
+        std::fs::read(source)?
+    } else {
+        let url = if source.starts_with("http://") || source.starts_with("https://") {
+            source.to_string()
+        } else {
+            let registry = config.packages.registry_url.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("{} is not a file or URL, and packages.registry_url is not set", source)
+            })?;
+            format!("{}/{}.json", registry.trim_end_matches('/'), source)
+        };
+        let resp = reqwest::get(&url).await?;
+        if !resp.status().is_success() {
+            anyhow::bail!("Could not download {} ({})", url, resp.status());
+        }
+        resp.bytes().await?.to_vec()
+    };
+    serde_json::from_slice(&bytes).map_err(|e| anyhow::anyhow!("Not a OneClaw package: {}", e))
+}
+
+/// Install a verified package. Files that exist with different contents are only
+/// replaced with `force`.
+pub fn install(package: &Package, config: &config::NodeConfig, force: bool) -> anyhow::Result<Installed> {
+    let workspace = agent_os::workspace_for(config);
+    let mut writes = vec![];
+    let mut playbooks = vec![];
+    let mut installed = Installed::default();
+
+    for file in &package.manifest.files {
+        let contents = b64().decode(&package.files[&file.path])?;
+        let (section, relative) = split_path(&file.path)?;
+        let name = relative.to_string_lossy().to_string();
+        match section {
+            "workflows" => {
+                writes.push((workflow::workflows_dir().join(&relative), contents));
+                installed.workflows.push(name);
+            }
+            "templates" => {
+                let workspace = workspace.as_ref().ok_or_else(|| anyhow::anyhow!("No workspace directory"))?;
+                writes.push((workspace.join(&relative), contents));
+                installed.templates.push(name);
+            }
+            _ => {
+                playbooks.push(String::from_utf8(contents)?);
+                installed.playbooks.push(name);
+            }
+        }
+    }
+
+    let conflicts: Vec<String> = writes
+        .iter()
+        .filter(|(path, contents)| std::fs::read(path).is_ok_and(|current| current != *contents))
+        .map(|(path, _)| path.display().to_string())
+        .collect();
+    if !force && !conflicts.is_empty() {
+        anyhow::bail!("Install would overwrite {} (pass --force to replace them)", conflicts.join(", "));
+    }
+
+    for (path, contents) in writes {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)?;
+    }
+
+    if !playbooks.is_empty() {
+        let path = workspace.ok_or_else(|| anyhow::anyhow!("No workspace directory"))?.join("PLAYBOOKS.md");
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        std::fs::write(&path, with_playbook_section(&current, &package.manifest.name, &playbooks.join("\n\n")))?;
+    }
+
+    tracing::info!("📦 Installed {} {}", package.manifest.name, package.manifest.version);
+    Ok(installed)
+}
+
+/// `playbooks` with the package's section added, or replaced if it is already there
+fn with_playbook_section(playbooks: &str, package: &str, content: &str) -> String {
+    let (start, end) = (format!("<!-- package:{} -->", package), format!("<!-- /package:{} -->", package));
+    let section = format!("{}\n{}\n{}", start, content.trim(), end);
+    if let (Some(i), Some(j)) = (playbooks.find(&start), playbooks.find(&end)) {
+        if i < j {
+            return format!("{}{}{}", &playbooks[..i], section, &playbooks[j + end.len()..]);
+        }
+    }
+    if playbooks.trim().is_empty() {
+        format!("{}\n", section)
+    } else {
+        format!("{}\n\n{}\n", playbooks.trim_end(), section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn signed_package(workflow: &[u8]) -> Package {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let manifest = Manifest {
+            name: "seo-sweep".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            author: String::new(),
+            public_key: b64().encode(keypair.public_key().as_ref()),
+            files: vec![ManifestFile { path: "workflows/seo_sweep.yaml".to_string(), sha256: sha256_hex(workflow) }],
+        };
+        let signature = b64().encode(keypair.sign(&serde_json::to_vec(&manifest).unwrap()).as_ref());
+        Package {
+            files: BTreeMap::from([("workflows/seo_sweep.yaml".to_string(), b64().encode(workflow))]),
+            manifest,
+            signature: Some(signature),
+        }
+    }
+
+    #[test]
+    fn test_package_verification() {
+        let package = signed_package(b"id: seo_sweep\n");
+        let trusted = vec![package.manifest.public_key.clone()];
+        assert!(package.verify(&trusted, false).is_ok());
+        assert!(package.verify(&[], false).is_err());
+        assert!(package.verify(&[], true).is_ok());
+
+        // Swapped contents fail the hash; a re-pinned hash fails the signature
+        let mut tampered = package.clone();
+        tampered.files.insert("workflows/seo_sweep.yaml".to_string(), b64().encode(b"id: evil\n"));
+        assert!(tampered.verify(&trusted, true).is_err());
+        tampered.manifest.files[0].sha256 = sha256_hex(b"id: evil\n");
+        assert!(tampered.verify(&trusted, true).is_err());
+
+        assert!(split_path("workflows/../../.bashrc").is_err());
+        assert!(split_path("bin/tool").is_err());
+    }
+
+    #[test]
+    fn test_playbook_section_replaced_on_reinstall() {
+        let first = with_playbook_section("# Playbooks\n", "seo-sweep", "v1");
+        let second = with_playbook_section(&first, "seo-sweep", "v2");
+        assert_eq!(second, "# Playbooks\n\n<!-- package:seo-sweep -->\nv2\n<!-- /package:seo-sweep -->\n");
+    }
+}
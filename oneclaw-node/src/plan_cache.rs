@@ -0,0 +1,274 @@
+//! Plan Cache - Reuse successful job plans for similar requests
+//!
+//! Plans whose jobs completed are saved to `<workspace>/plans.json`, keyed by an
+//! intent signature: the request's words minus stopwords, numbers and the
+//! values that ended up in step params (niche, location, ...). Slot values vary
+//! between requests; the remaining words describe what the user wants done.
+//!
+//! A new request matching a cached signature skips the full planning call:
+//! - same normalized request: the cached steps are reused as-is
+//! - otherwise: a cheap LLM call adapts the cached steps to the new request
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::agent_os::ToolDefinition;
+use crate::autonomous_jobs::{self, JobPlan, JobStep};
+use crate::model_router::ModelRouter;
+
+/// Most plans kept; least recently used are evicted
+const MAX_CACHED_PLANS: usize = 50;
+
+/// Share of the new request's intent words a cached signature must cover
+const MIN_SIMILARITY: f64 = 0.6;
+
+/// Cheap model for adapting a cached plan
+const ADAPT_MODEL: &str = "claude-3-5-haiku-20241022";
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "in", "on", "at", "of", "for", "to", "and", "or", "with", "me", "my", "i",
+    "please", "can", "you", "some", "all", "any", "that", "this", "from", "near", "around", "by",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPlan {
+    pub signature: BTreeSet<String>,
+    /// Normalized request the plan was generated for
+    pub request: String,
+    pub steps: Vec<CachedStep>,
+    pub hits: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedStep {
+    pub action: String,
+    pub params: Value,
+}
+
+/// How a plan was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanSource {
+    Generated,
+    Reused,
+    Adapted,
+}
+
+/// Reuse or adapt a cached plan when one matches, otherwise run the full planner
+pub async fn plan_or_generate(
+    router: &ModelRouter,
+    user_message: &str,
+    planner_context: Option<&str>,
+    tools: &[ToolDefinition],
+    workspace: Option<&Path>,
+) -> anyhow::Result<(JobPlan, PlanSource)> {
+    let cached = workspace.and_then(|ws| lookup(&load(ws), user_message).cloned());
+
+    if let Some(cached) = cached {
+        if cached.request == normalize(user_message) {
+            tracing::info!("♻️ Reusing cached plan for \"{}\"", cached.request);
+            return Ok((to_plan(user_message, &cached.steps), PlanSource::Reused));
+        }
+        match adapt(router, &cached, user_message, planner_context, tools).await {
+            Ok(plan) if !plan.steps.is_empty() => {
+                tracing::info!("♻️ Adapted cached plan from \"{}\"", cached.request);
+                return Ok((plan, PlanSource::Adapted));
+            }
+            Ok(_) => tracing::warn!("Plan adaptation returned no steps, planning from scratch"),
+            Err(e) => tracing::warn!("Plan adaptation failed, planning from scratch: {}", e),
+        }
+    }
+
+    let plan = autonomous_jobs::generate_job_plan(router, user_message, planner_context, tools).await?;
+    Ok((plan, PlanSource::Generated))
+}
+
+/// Save a plan whose job completed successfully
+pub fn remember(workspace: &Path, user_message: &str, plan: &JobPlan) -> anyhow::Result<()> {
+    let mut plans = load(workspace);
+    let request = normalize(user_message);
+    let now = Utc::now();
+
+    if let Some(existing) = plans.iter_mut().find(|p| p.request == request) {
+        existing.hits += 1;
+        existing.last_used_at = now;
+        existing.steps = to_cached_steps(plan);
+    } else {
+        plans.push(CachedPlan {
+            signature: signature(user_message, plan),
+            request,
+            steps: to_cached_steps(plan),
+            hits: 1,
+            created_at: now,
+            last_used_at: now,
+        });
+    }
+
+    plans.sort_by_key(|p| std::cmp::Reverse(p.last_used_at));
+    plans.truncate(MAX_CACHED_PLANS);
+    save(workspace, &plans)
+}
+
+/// Best cached plan for a request, if any covers enough of its intent
+pub fn lookup<'a>(plans: &'a [CachedPlan], user_message: &str) -> Option<&'a CachedPlan> {
+    let words = intent_words(user_message);
+    if words.is_empty() {
+        return None;
+    }
+
+    plans
+        .iter()
+        .filter(|p| !p.signature.is_empty() && p.signature.is_subset(&words))
+        .map(|p| (p, p.signature.len() as f64 / words.len() as f64))
+        .filter(|(_, score)| *score >= MIN_SIMILARITY)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(p, _)| p)
+}
+
+/// Intent signature: request words that aren't slot values from the plan's params
+pub fn signature(user_message: &str, plan: &JobPlan) -> BTreeSet<String> {
+    let mut slot_words = BTreeSet::new();
+    for step in &plan.steps {
+        collect_param_words(&step.params, &mut slot_words);
+    }
+    intent_words(user_message)
+        .into_iter()
+        .filter(|w| !slot_words.contains(w))
+        .collect()
+}
+
+async fn adapt(
+    router: &ModelRouter,
+    cached: &CachedPlan,
+    user_message: &str,
+    planner_context: Option<&str>,
+    tools: &[ToolDefinition],
+) -> anyhow::Result<JobPlan> {
+    let prompt = format!(
+        r#"This plan worked for a similar request. Adapt it to the new request: change params to match,
+and only add or remove steps if the new request clearly needs it.
+{}
+Previous request: "{}"
+Previous plan: {}
+
+New request: "{}"
+
+Submit the adapted plan with the submit_plan tool.
+"#,
+        planner_context.map(|c| format!("\n{}\n", c)).unwrap_or_default(),
+        cached.request,
+        serde_json::to_string(&cached.steps)?,
+        user_message
+    );
+
+    let tool = autonomous_jobs::submit_plan_tool(&autonomous_jobs::available_actions(tools));
+    let input = autonomous_jobs::planner_tool_call(router, crate::model_router::PLANNING, &prompt, ADAPT_MODEL, 1000, &tool).await?;
+    Ok(JobPlan {
+        description: user_message.to_string(),
+        steps: autonomous_jobs::parse_plan_steps(&input)?,
+    })
+}
+
+fn to_plan(user_message: &str, steps: &[CachedStep]) -> JobPlan {
+    JobPlan {
+        description: user_message.to_string(),
+        steps: steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| JobStep {
+                id: nanoid::nanoid!(10),
+                order: (i + 1) as i32,
+                action: step.action.clone(),
+                params: step.params.clone(),
+                status: "pending".to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn to_cached_steps(plan: &JobPlan) -> Vec<CachedStep> {
+    plan.steps
+        .iter()
+        .map(|s| CachedStep { action: s.action.clone(), params: s.params.clone() })
+        .collect()
+}
+
+/// Lowercased words, punctuation stripped
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn normalize(text: &str) -> String {
+    words(text).collect::<Vec<_>>().join(" ")
+}
+
+fn intent_words(text: &str) -> BTreeSet<String> {
+    words(text)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()) && !w.chars().all(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+fn collect_param_words(value: &Value, out: &mut BTreeSet<String>) {
+    match value {
+        // Step references like "{from_step_1}" aren't slot values
+        Value::String(s) if !s.contains('{') => out.extend(words(s)),
+        Value::Array(items) => items.iter().for_each(|v| collect_param_words(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_param_words(v, out)),
+        _ => {}
+    }
+}
+
+fn cache_path(workspace: &Path) -> PathBuf {
+    workspace.join("plans.json")
+}
+
+fn load(workspace: &Path) -> Vec<CachedPlan> {
+    std::fs::read_to_string(cache_path(workspace))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(workspace: &Path, plans: &[CachedPlan]) -> anyhow::Result<()> {
+    std::fs::create_dir_all(workspace)?;
+    std::fs::write(cache_path(workspace), serde_json::to_string_pretty(plans)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(steps: Value) -> JobPlan {
+        let steps: Vec<CachedStep> = serde_json::from_value(steps).unwrap();
+        to_plan("", &steps)
+    }
+
+    #[test]
+    fn test_similar_requests_share_a_signature() {
+        let cached_plan = plan(serde_json::json!([
+            { "action": "discover", "params": { "niche": "hvac", "location": "Miami, FL", "limit": 50 } },
+            { "action": "enrich", "params": { "businesses": "{from_step_1}" } },
+        ]));
+        let message = "Find HVAC companies in Miami, FL and get me the point of contact";
+        let cached = CachedPlan {
+            signature: signature(message, &cached_plan),
+            request: normalize(message),
+            steps: to_cached_steps(&cached_plan),
+            hits: 1,
+            created_at: Utc::now(),
+            last_used_at: Utc::now(),
+        };
+        assert!(!cached.signature.contains("hvac") && !cached.signature.contains("miami"));
+
+        let plans = vec![cached];
+        assert!(lookup(&plans, "find plumbing companies in Denver and get me the point of contact").is_some());
+        assert!(lookup(&plans, "audit the website for joe's pizza").is_none());
+    }
+}
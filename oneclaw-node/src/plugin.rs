@@ -0,0 +1,406 @@
+//! Plugins - Executors that run as subprocesses
+//!
+//! Teams can add tools in Python, Node or anything else without rebuilding
+//! the node. Each manifest in `plugins.dir` describes one executor and the
+//! command that runs it:
+//!
+//! ```yaml
+//! id: crm.lookup
+//! version: 0.1.0
+//! description: Look up a contact in our CRM by email
+//! permissions: [network]
+//! command: python3
+//! args: [crm_lookup.py]          # run from the manifest's directory
+//! env: { CRM_URL: "https://crm.example.com" }
+//! capabilities:
+//!   input_schema: { type: object, properties: { email: { type: string } } }
+//! ```
+//!
+//! The process starts on first use and keeps running. It speaks JSON-RPC 2.0
+//! over stdin/stdout, one message per line:
+//! - `execute` with `{"input": ...}` answers with the tool's output, or an error
+//! - `health` answers with anything; it's sent every `plugins.health_check_secs`
+//!
+//! stderr goes to the node's log. A plugin that exits, times out or fails its
+//! health check is restarted; after `plugins.max_restarts` failures in a row it
+//! is left stopped until it's called again. Calls to one plugin run one at a time.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use crate::config::{NodeConfig, PluginsConfig};
+use crate::executor::{Executor, ExecutorManifest, ExecutorResult};
+
+/// Longest a plugin gets to answer a health check
+const HEALTH_TIMEOUT_SECS: u64 = 5;
+
+/// A manifest file in `plugins.dir`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    #[serde(flatten)]
+    pub executor: ExecutorManifest,
+    /// Program to run; a relative path with a separator resolves against the manifest's directory
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+/// Shown on `GET /plugins`
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginStatus {
+    pub id: String,
+    pub manifest: PathBuf,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    /// Failures since the last answer; at `plugins.max_restarts` the plugin is left stopped
+    pub failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    pid: Option<u32>,
+    started: bool,
+    restarts: u32,
+    failures: u32,
+    last_error: Option<String>,
+}
+
+/// Why a call got no result
+#[derive(Debug)]
+enum CallError {
+    /// The plugin answered with a JSON-RPC error
+    Rpc(String),
+    /// The process couldn't be started, died or stopped answering
+    Process(String),
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Rpc(message) | CallError::Process(message) => f.write_str(message),
+        }
+    }
+}
+
+/// A running plugin process
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+}
+
+impl Process {
+    fn alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Send one request and wait for the response with its id; other lines are skipped
+    async fn request(&mut self, method: &str, params: Value) -> anyhow::Result<Result<Value, String>> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut line = serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+        line.push(b'\n');
+        self.stdin.write_all(&line).await?;
+        self.stdin.flush().await?;
+
+        loop {
+            let Some(line) = self.stdout.next_line().await? else {
+                anyhow::bail!("plugin exited");
+            };
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                tracing::debug!("Skipping non-JSON plugin output: {}", line);
+                continue;
+            };
+            if message["id"].as_u64() != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+                return Ok(Err(text));
+            }
+            return Ok(Ok(message.get("result").cloned().unwrap_or(Value::Null)));
+        }
+    }
+}
+
+pub struct Plugin {
+    manifest: PluginManifest,
+    path: PathBuf,
+    call_timeout: Duration,
+    process: tokio::sync::Mutex<Option<Process>>,
+    health: std::sync::Mutex<Health>,
+}
+
+impl Plugin {
+    pub fn new(manifest: PluginManifest, path: PathBuf, config: &PluginsConfig) -> Self {
+        Self {
+            manifest,
+            path,
+            call_timeout: Duration::from_secs(config.call_timeout_secs),
+            process: tokio::sync::Mutex::new(None),
+            health: std::sync::Mutex::new(Health::default()),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.manifest.executor.id
+    }
+
+    pub fn manifest(&self) -> &ExecutorManifest {
+        &self.manifest.executor
+    }
+
+    pub fn status(&self) -> PluginStatus {
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        PluginStatus {
+            id: self.id().to_string(),
+            manifest: self.path.clone(),
+            running: health.pid.is_some(),
+            pid: health.pid,
+            restarts: health.restarts,
+            failures: health.failures,
+            last_error: health.last_error.clone(),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Health)) {
+        f(&mut self.health.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    fn spawn(&self) -> anyhow::Result<Process> {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let program = Path::new(&self.manifest.command);
+        let program = if program.is_relative() && program.components().count() > 1 {
+            dir.join(program)
+        } else {
+            program.to_path_buf()
+        };
+        let mut child = Command::new(program)
+            .args(&self.manifest.args)
+            .envs(&self.manifest.env)
+            .current_dir(dir)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Couldn't start plugin {}: {}", self.id(), e))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("no stdout"))?;
+        if let Some(stderr) = child.stderr.take() {
+            let id = self.id().to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::info!("[plugin {}] {}", id, line);
+                }
+            });
+        }
+
+        let pid = child.id();
+        self.update(|h| {
+            if h.started {
+                h.restarts += 1;
+            }
+            h.started = true;
+            h.pid = pid;
+        });
+        tracing::info!("🔌 Started plugin {} (pid {})", self.id(), pid.unwrap_or_default());
+        Ok(Process { child, stdin, stdout: BufReader::new(stdout).lines(), next_id: 0 })
+    }
+
+    fn failed(&self, error: &str) {
+        tracing::warn!("Plugin {} failed: {}", self.id(), error);
+        self.update(|h| {
+            h.pid = None;
+            h.failures += 1;
+            h.last_error = Some(error.to_string());
+        });
+    }
+
+    /// Call `method`, starting (or restarting) the process if it isn't running.
+    /// A process that breaks or doesn't answer in `timeout` is killed.
+    async fn call_with(
+        &self,
+        process: &mut Option<Process>,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, CallError> {
+        if process.as_mut().is_some_and(|p| !p.alive()) {
+            *process = None;
+            self.failed("process exited");
+        }
+        let running = match process {
+            Some(running) => running,
+            None => match self.spawn() {
+                Ok(started) => process.insert(started),
+                Err(e) => {
+                    self.failed(&e.to_string());
+                    return Err(CallError::Process(e.to_string()));
+                }
+            },
+        };
+
+        let error = match tokio::time::timeout(timeout, running.request(method, params)).await {
+            Ok(Ok(answer)) => {
+                self.update(|h| h.failures = 0);
+                return answer.map_err(CallError::Rpc);
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("no answer to {} within {}s", method, timeout.as_secs()),
+        };
+        // Dropping the process kills it; the next call starts a new one
+        *process = None;
+        self.failed(&error);
+        Err(CallError::Process(format!("Plugin {}: {}", self.id(), error)))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, CallError> {
+        let mut process = self.process.lock().await;
+        self.call_with(&mut process, method, params, self.call_timeout).await
+    }
+
+    /// Ping the plugin, restarting it if it died. Plugins never started, busy
+    /// with a call, or past `max_restarts` failures are left alone.
+    async fn check(&self, max_restarts: u32) {
+        let (started, failures) = {
+            let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+            (health.started, health.failures)
+        };
+        if !started || failures >= max_restarts {
+            return;
+        }
+        let Ok(mut process) = self.process.try_lock() else {
+            return;
+        };
+        let timeout = Duration::from_secs(HEALTH_TIMEOUT_SECS);
+        if let Err(CallError::Process(e)) = self.call_with(&mut process, "health", serde_json::json!({}), timeout).await {
+            if failures + 1 >= max_restarts {
+                tracing::error!("Plugin {} keeps failing; leaving it stopped until its next call: {}", self.id(), e);
+            }
+        }
+    }
+}
+
+/// Every plugin manifest in `config.dir`; unreadable ones are logged and skipped
+pub fn load(config: &PluginsConfig) -> Vec<Arc<Plugin>> {
+    if !config.enabled {
+        return vec![];
+    }
+    let dir = crate::config::expand_path(&config.dir);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml" | "json")))
+        .collect();
+    paths.sort();
+
+    let mut plugins = Vec::new();
+    for path in paths {
+        let manifest = std::fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| serde_yaml::from_str::<PluginManifest>(&text).map_err(anyhow::Error::from));
+        match manifest {
+            Ok(manifest) => plugins.push(Arc::new(Plugin::new(manifest, path, config))),
+            Err(e) => tracing::warn!("Skipping plugin manifest {}: {}", path.display(), e),
+        }
+    }
+    plugins
+}
+
+/// Health-check `plugins` every `health_check_secs` for as long as the node runs
+pub fn supervise(plugins: Vec<Arc<Plugin>>, config: &PluginsConfig) {
+    if plugins.is_empty() {
+        return;
+    }
+    let (interval, max_restarts) = (Duration::from_secs(config.health_check_secs.max(1)), config.max_restarts);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            for plugin in &plugins {
+                plugin.check(max_restarts).await;
+            }
+        }
+    });
+}
+
+/// A plugin as an executor in the registry
+pub struct PluginExecutor(pub Arc<Plugin>);
+
+#[async_trait]
+impl Executor for PluginExecutor {
+    fn manifest(&self) -> ExecutorManifest {
+        self.0.manifest().clone()
+    }
+
+    async fn execute(&self, input: Value, _config: &NodeConfig) -> ExecutorResult {
+        let start = Instant::now();
+        match self.0.call("execute", serde_json::json!({ "input": input })).await {
+            Ok(output) => ExecutorResult::Executed { output, duration_ms: start.elapsed().as_millis() as u64 },
+            Err(e) => ExecutorResult::Error { error: e.to_string() },
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_plugin_answers_and_restarts_after_crash() {
+        let dir = std::env::temp_dir().join(format!("oneclaw-plugin-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Echoes each request id back with a result; exits when asked to crash
+        std::fs::write(
+            dir.join("echo.sh"),
+            "while read -r line; do\n  case \"$line\" in *crash*) exit 1;; esac\n  id=$(echo \"$line\" | sed 's/.*\"id\":\\([0-9]*\\).*/\\1/')\n  echo \"{\\\"jsonrpc\\\":\\\"2.0\\\",\\\"id\\\":$id,\\\"result\\\":{\\\"ok\\\":true}}\"\ndone\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("echo.yaml"),
+            "id: test.echo\nversion: 0.1.0\ndescription: Echo\npermissions: []\ncommand: sh\nargs: [echo.sh]\n",
+        )
+        .unwrap();
+
+        let config = PluginsConfig { dir: dir.to_string_lossy().to_string(), ..Default::default() };
+        let plugins = load(&config);
+        assert_eq!(plugins.len(), 1);
+        let plugin = &plugins[0];
+        let execute = |input: Value| plugin.call("execute", serde_json::json!({ "input": input }));
+
+        assert_eq!(execute(serde_json::json!({ "email": "ana@acme.co" })).await.unwrap(), serde_json::json!({ "ok": true }));
+        assert!(matches!(execute(serde_json::json!({ "crash": true })).await, Err(CallError::Process(_))));
+        assert!(!plugin.status().running);
+
+        assert!(execute(serde_json::json!({})).await.is_ok());
+        let status = plugin.status();
+        assert!(status.running);
+        assert_eq!((status.restarts, status.failures), (1, 0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,169 @@
+//! Policy - Time-window denial rules for tool calls
+//!
+//! `security.policies` limits when matching tools may run, for businesses with
+//! compliance norms around outreach timing:
+//!
+//! ```yaml
+//! security:
+//!   policies:
+//!     - name: email-business-hours
+//!       tools: ["google.gmail", "*email*"]
+//!       hours: "08:00-18:00"
+//!       clock: recipient
+//!     - name: no-paid-tools-on-weekends
+//!       paid_only: true
+//!       days: [mon, tue, wed, thu, fri]
+//! ```
+//!
+//! A call outside a matching policy's window is denied like any other policy
+//! denial (`ExecutorResult::Denied`), naming the policy and the local time.
+
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+use crate::config::{PolicyClock, TimePolicy};
+use crate::executor::DenialReason;
+
+/// What the policies need to know about the tool being called
+pub struct ToolMeta<'a> {
+    pub id: &'a str,
+    /// Costs money per call
+    pub paid: bool,
+}
+
+/// `pattern` with `*` matching any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn applies(policy: &TimePolicy, tool: &ToolMeta) -> bool {
+    (!policy.paid_only || tool.paid)
+        && (policy.tools.is_empty() || policy.tools.iter().any(|p| wildcard_match(p, tool.id)))
+}
+
+/// "08:00-18:00"; a window whose end is before its start runs overnight
+fn parse_hours(hours: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = hours.split_once('-')?;
+    Some((
+        NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+        NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+    ))
+}
+
+fn parse_tz(name: Option<&str>) -> Option<Tz> {
+    let name = name?;
+    match name.parse() {
+        Ok(tz) => Some(tz),
+        Err(_) => {
+            tracing::warn!("Unknown timezone '{}' in policy check", name);
+            None
+        }
+    }
+}
+
+/// Why `policy` denies a call at `local`, if it does
+fn violation(policy: &TimePolicy, local: DateTime<Tz>) -> Option<String> {
+    let day = local.weekday().to_string().to_lowercase();
+    if !policy.days.is_empty() && !policy.days.iter().any(|d| d.to_lowercase().starts_with(&day)) {
+        return Some(format!("only on {}", policy.days.join(", ")));
+    }
+    let hours = policy.hours.as_deref()?;
+    // A window that can't be read denies rather than silently allowing everything
+    let Some((start, end)) = parse_hours(hours) else {
+        return Some(format!("invalid hours '{}'", hours));
+    };
+    let now = local.time();
+    let inside = if start <= end { start <= now && now < end } else { now >= start || now < end };
+    (!inside).then(|| format!("only between {}", hours))
+}
+
+/// The first policy that denies calling `tool` with `input` at `now`.
+/// `user_timezone` is the user's IANA timezone (UTC when unset or unknown).
+pub fn check(
+    policies: &[TimePolicy],
+    tool: &ToolMeta,
+    input: &Value,
+    user_timezone: Option<&str>,
+    now: DateTime<Utc>,
+) -> Option<DenialReason> {
+    let user_tz = parse_tz(user_timezone).unwrap_or(Tz::UTC);
+    policies.iter().filter(|p| applies(p, tool)).find_map(|policy| {
+        let tz = match policy.clock {
+            PolicyClock::User => user_tz,
+            PolicyClock::Recipient => {
+                let recipient = input["recipient_timezone"].as_str().or(input["timezone"].as_str());
+                parse_tz(recipient).unwrap_or(user_tz)
+            }
+        };
+        let local = now.with_timezone(&tz);
+        let reason = violation(policy, local)?;
+        let clock = match policy.clock {
+            PolicyClock::User => "user",
+            PolicyClock::Recipient => "recipient",
+        };
+        Some(DenialReason {
+            rule: format!("security.policies.{}", policy.name),
+            attempted: tool.id.to_string(),
+            policy: format!(
+                "{} may run {} ({} time, {}); it's {} there",
+                tool.id,
+                reason,
+                clock,
+                tz.name(),
+                local.format("%a %H:%M"),
+            ),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn policy(yaml: &str) -> TimePolicy {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_time_window_policies() {
+        let email = policy("name: email-hours\ntools: ['*email*', google.gmail]\nhours: '08:00-18:00'\nclock: recipient");
+        let weekdays = policy("name: paid-weekdays\npaid_only: true\ndays: [mon, tue, wed, thu, fri]");
+        let policies = [email, weekdays];
+        let gmail = ToolMeta { id: "google.gmail", paid: false };
+        let enrich = ToolMeta { id: "enrich-contacts", paid: true };
+
+        // Tuesday 20:00 UTC is 15:00 in Chicago but 21:00 in Berlin
+        let tuesday = Utc.with_ymd_and_hms(2026, 3, 10, 20, 0, 0).unwrap();
+        let chicago = serde_json::json!({ "to": "a@b.co", "recipient_timezone": "America/Chicago" });
+        assert!(check(&policies, &gmail, &chicago, None, tuesday).is_none());
+        let berlin = serde_json::json!({ "to": "a@b.co", "recipient_timezone": "Europe/Berlin" });
+        let denied = check(&policies, &gmail, &berlin, None, tuesday).unwrap();
+        assert_eq!(denied.rule, "security.policies.email-hours");
+        assert!(check(&policies, &enrich, &berlin, None, tuesday).is_none());
+
+        // Saturday 02:00 UTC is still Friday evening in Los Angeles
+        let saturday = Utc.with_ymd_and_hms(2026, 3, 14, 2, 0, 0).unwrap();
+        assert!(check(&policies, &enrich, &Value::Null, Some("America/Los_Angeles"), saturday).is_none());
+        assert!(check(&policies, &enrich, &Value::Null, Some("Europe/London"), saturday).is_some());
+
+        assert!(wildcard_match("*email*", "outreach.email.send"));
+        assert!(!wildcard_match("google.*", "harness.execute"));
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::{config, executor::{DenialReason, ExecutorResult}};
+use crate::{config, context_budget::ContextBudget, executor::{DenialReason, ExecutorResult}};
+
+/// Where chat turn receipts go, under the artifacts path
+const TURNS_DIR: &str = "turns";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowReceipt {
@@ -36,6 +39,18 @@ pub struct DebugInfo {
     pub total_duration_ms: u64,
 }
 
+/// What a chat turn used: its LLM calls' tokens and how its prompt fitted the
+/// context window (system prompt, history and tool tokens, counted or estimated)
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnReceipt {
+    pub turn_id: String,
+    pub user_id: String,
+    pub channel: String,
+    pub completed_at: String,
+    pub duration_ms: u64,
+    pub budget: ContextBudget,
+}
+
 fn artifacts_path() -> anyhow::Result<std::path::PathBuf> {
     let config = config::load()?;
     Ok(config::expand_path(&config.artifacts.path))
@@ -51,6 +66,16 @@ pub fn write_receipt(receipt: &WorkflowReceipt) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn write_turn_receipt(receipt: &TurnReceipt) -> anyhow::Result<()> {
+    let path = artifacts_path()?.join(TURNS_DIR).join(format!("{}.json", receipt.turn_id));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(receipt)?)?;
+    tracing::debug!(turn_id = %receipt.turn_id, "Turn receipt written");
+    Ok(())
+}
+
 pub fn read_receipt(run_id: &str) -> anyhow::Result<Option<WorkflowReceipt>> {
     let path = artifacts_path()?.join(run_id).join("receipt.json");
     if !path.exists() { return Ok(None); }
@@ -63,7 +88,7 @@ pub fn list_receipts() -> anyhow::Result<Vec<String>> {
     let mut receipts = Vec::new();
     for entry in std::fs::read_dir(&path)? {
         let entry = entry?;
-        if entry.file_type()?.is_dir() {
+        if entry.file_type()?.is_dir() && entry.file_name() != TURNS_DIR {
             if let Some(name) = entry.file_name().to_str() {
                 receipts.push(name.to_string());
             }
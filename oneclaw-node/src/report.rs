@@ -0,0 +1,231 @@
+//! Reports - Branded audit report artifacts
+//!
+//! Turns website-audit tool output into a standalone HTML report (and a PDF when
+//! a headless Chrome/Chromium is available), saved under `<workspace>/reports/`
+//! so it can be attached to a channel message and forwarded to the prospect.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::config::ReportsConfig;
+
+/// Files produced for one report
+#[derive(Debug, Clone)]
+pub struct ReportArtifact {
+    pub html_path: PathBuf,
+    pub pdf_path: Option<PathBuf>,
+}
+
+impl ReportArtifact {
+    /// Best file to deliver: the PDF if rendered, otherwise the HTML
+    pub fn deliverable(&self) -> &Path {
+        self.pdf_path.as_deref().unwrap_or(&self.html_path)
+    }
+}
+
+/// Locate audit output inside a tool result (harness responses nest it under `output`/`result`)
+pub fn find_audit(output: &Value) -> Option<&Value> {
+    [output, &output["output"], &output["result"]]
+        .into_iter()
+        .find(|v| v["score"].is_number() && v["issues"].is_array() && v["categoryScores"].is_object())
+}
+
+/// Reports directory inside the workspace
+pub fn reports_dir(workspace: &Path) -> PathBuf {
+    workspace.join("reports")
+}
+
+/// Render and save a report for one audit
+pub async fn generate(
+    audit: &Value,
+    business_name: &str,
+    url: &str,
+    branding: &ReportsConfig,
+    brand_fallback: &str,
+    workspace: &Path,
+) -> anyhow::Result<ReportArtifact> {
+    let dir = reports_dir(workspace);
+    std::fs::create_dir_all(&dir)?;
+
+    let stem = format!(
+        "audit-{}-{}",
+        slugify(business_name),
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let html_path = dir.join(format!("{}.html", stem));
+    std::fs::write(&html_path, render_html(audit, business_name, url, branding, brand_fallback))?;
+
+    let pdf_path = if branding.pdf {
+        let pdf_path = dir.join(format!("{}.pdf", stem));
+        match render_pdf(&html_path, &pdf_path, branding.pdf_command.as_deref()).await {
+            Ok(()) => Some(pdf_path),
+            Err(e) => {
+                tracing::warn!("PDF rendering skipped, delivering HTML: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    tracing::info!("📄 Audit report saved: {}", html_path.display());
+    Ok(ReportArtifact { html_path, pdf_path })
+}
+
+/// Standalone branded HTML (inline CSS so it survives being forwarded)
+pub fn render_html(
+    audit: &Value,
+    business_name: &str,
+    url: &str,
+    branding: &ReportsConfig,
+    brand_fallback: &str,
+) -> String {
+    let brand = branding.brand_name.as_deref().unwrap_or(brand_fallback);
+    let accent = &branding.accent_color;
+    let score = audit["score"].as_f64().unwrap_or(0.0);
+    let analyzed_at = audit["analyzedAt"].as_str().unwrap_or("");
+
+    let mut categories = String::new();
+    if let Some(scores) = audit["categoryScores"].as_object() {
+        for (label, key) in [
+            ("SEO", "seo"),
+            ("AI Visibility", "aiVisibility"),
+            ("Local Presence", "localPresence"),
+            ("Technical", "technical"),
+        ] {
+            let value = scores.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+            categories.push_str(&format!(
+                r#"<div class="cat"><div class="cat-label">{}</div><div class="bar"><div class="fill" style="width:{:.0}%"></div></div><div class="cat-score">{:.0}</div></div>"#,
+                label, value.clamp(0.0, 100.0), value
+            ));
+        }
+    }
+
+    let mut issues = String::new();
+    for issue in audit["issues"].as_array().into_iter().flatten() {
+        let severity = issue["type"].as_str().unwrap_or("info");
+        issues.push_str(&format!(
+            r#"<li class="issue {}"><strong>{}</strong> {}{}</li>"#,
+            escape(severity),
+            escape(&severity.to_uppercase()),
+            escape(issue["message"].as_str().unwrap_or("")),
+            issue["recommendation"]
+                .as_str()
+                .map(|r| format!(r#"<div class="rec">→ {}</div>"#, escape(r)))
+                .unwrap_or_default()
+        ));
+    }
+    if issues.is_empty() {
+        issues.push_str(r#"<li class="issue info">No issues found.</li>"#);
+    }
+
+    let logo = branding
+        .logo_url
+        .as_deref()
+        .map(|l| format!(r#"<img class="logo" src="{}" alt="{}">"#, escape(l), escape(brand)))
+        .unwrap_or_default();
+    let footer = branding
+        .footer
+        .clone()
+        .unwrap_or_else(|| format!("Prepared by {}", brand));
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Website Audit: {business}</title>
+<style>
+  body {{ font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2937; margin: 0; }}
+  header {{ background: {accent}; color: #fff; padding: 32px 48px; display: flex; align-items: center; gap: 16px; }}
+  header .logo {{ height: 40px; }}
+  header h1 {{ margin: 0; font-size: 24px; }}
+  header .sub {{ opacity: .85; font-size: 14px; }}
+  main {{ padding: 32px 48px; }}
+  .score {{ font-size: 64px; font-weight: 700; color: {accent}; }}
+  .score span {{ font-size: 24px; color: #6b7280; }}
+  .cat {{ display: flex; align-items: center; gap: 12px; margin: 8px 0; }}
+  .cat-label {{ width: 140px; }}
+  .bar {{ flex: 1; background: #e5e7eb; border-radius: 4px; height: 10px; }}
+  .fill {{ background: {accent}; height: 10px; border-radius: 4px; }}
+  .cat-score {{ width: 40px; text-align: right; }}
+  ul {{ list-style: none; padding: 0; }}
+  .issue {{ border-left: 4px solid #9ca3af; padding: 8px 12px; margin: 8px 0; background: #f9fafb; }}
+  .issue.critical {{ border-color: #dc2626; }}
+  .issue.warning {{ border-color: #f59e0b; }}
+  .rec {{ color: #4b5563; margin-top: 4px; }}
+  footer {{ padding: 16px 48px; color: #6b7280; font-size: 12px; border-top: 1px solid #e5e7eb; }}
+</style>
+</head>
+<body>
+<header>{logo}<div><h1>Website Audit: {business}</h1><div class="sub">{url}</div></div></header>
+<main>
+  <div class="score">{score:.0}<span>/100</span></div>
+  <h2>Category Scores</h2>
+  {categories}
+  <h2>Findings</h2>
+  <ul>{issues}</ul>
+</main>
+<footer>{footer}{analyzed}</footer>
+</body>
+</html>
+"#,
+        business = escape(business_name),
+        url = escape(url),
+        accent = escape(accent),
+        logo = logo,
+        score = score,
+        categories = categories,
+        issues = issues,
+        footer = escape(&footer),
+        analyzed = if analyzed_at.is_empty() {
+            String::new()
+        } else {
+            format!(" · Analyzed {}", escape(analyzed_at))
+        },
+    )
+}
+
+/// Print HTML to PDF with a headless Chrome/Chromium
+async fn render_pdf(html_path: &Path, pdf_path: &Path, command: Option<&str>) -> anyhow::Result<()> {
+    let candidates: Vec<&str> = match command {
+        Some(cmd) => vec![cmd],
+        None => vec!["chromium", "chromium-browser", "google-chrome", "google-chrome-stable"],
+    };
+
+    for browser in candidates {
+        let status = tokio::process::Command::new(browser)
+            .arg("--headless")
+            .arg("--disable-gpu")
+            .arg("--no-pdf-header-footer")
+            .arg(format!("--print-to-pdf={}", pdf_path.display()))
+            .arg(html_path)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .await;
+        if let Ok(status) = status {
+            if status.success() && pdf_path.exists() {
+                return Ok(());
+            }
+        }
+    }
+    anyhow::bail!("no headless Chrome/Chromium found (set reports.pdf_command)")
+}
+
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "business".to_string() } else { slug }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,231 @@
+//! Response Style - Per-channel length and formatting limits for replies
+//!
+//! Each channel has a longest reply and a set of markdown features it can show
+//! (`channels.<name>.style`, falling back to built-in defaults). The limits go to
+//! the LLM as a system prompt block and are enforced again before sending:
+//! markdown the channel can't show is removed, and a reply over `max_chars` is
+//! cut at a paragraph, line or sentence break and ends with "send 'more' for the
+//! rest". The remainder is kept per chat until the user sends "more".
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::{ChannelsConfig, Formatting};
+
+/// What the user sends to get the rest of a cut-off reply
+pub const MORE_COMMAND: &str = "more";
+
+const CONTINUATION_NOTE: &str = "\n\n… send 'more' for the rest";
+
+const ALL_FORMATTING: &[Formatting] = &[
+    Formatting::Bold,
+    Formatting::Italic,
+    Formatting::Headings,
+    Formatting::Lists,
+    Formatting::Links,
+    Formatting::Code,
+    Formatting::Tables,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseStyle {
+    pub max_chars: Option<usize>,
+    pub formatting: Vec<Formatting>,
+}
+
+impl ResponseStyle {
+    /// Provider limits and rendering for known channels
+    pub fn default_for(channel: &str) -> Self {
+        use Formatting::*;
+        let (max_chars, formatting): (Option<usize>, &[Formatting]) = match channel {
+            "telegram" => (Some(4000), &[Bold, Italic, Lists, Links, Code]),
+            "discord" => (Some(2000), &[Bold, Italic, Headings, Lists, Links, Code]),
+            "slack" => (Some(3000), &[Bold, Italic, Lists, Links, Code]),
+            "whatsapp" => (Some(4000), &[Bold, Italic, Lists, Code]),
+            "voice" => (Some(600), &[]),
+            _ => (None, ALL_FORMATTING),
+        };
+        Self { max_chars, formatting: formatting.to_vec() }
+    }
+
+    /// The style for `channel`: configured overrides over the channel defaults
+    pub fn for_channel(channels: &ChannelsConfig, channel: &str) -> Self {
+        let configured = channels.style_for(channel);
+        let defaults = Self::default_for(channel);
+        Self {
+            max_chars: configured.max_chars.or(defaults.max_chars),
+            formatting: configured.formatting.clone().unwrap_or(defaults.formatting),
+        }
+    }
+
+    fn allows(&self, feature: Formatting) -> bool {
+        self.formatting.contains(&feature)
+    }
+
+    /// System prompt block describing the limits (none when the channel has none)
+    pub fn prompt_block(&self, channel: &str) -> Option<String> {
+        if self.max_chars.is_none() && ALL_FORMATTING.iter().all(|f| self.allows(*f)) {
+            return None;
+        }
+        let mut block = format!("## Response Format\nYou are replying on {}.", title_case(channel));
+        if let Some(max) = self.max_chars {
+            block.push_str(&format!(" Keep each reply under {} characters.", max));
+        }
+        if self.formatting.is_empty() {
+            block.push_str("\nPlain text only: no markdown, lists, links or code.");
+        } else {
+            let (allowed, banned): (Vec<_>, Vec<_>) = ALL_FORMATTING.iter().partition(|f| self.allows(**f));
+            let names = |features: Vec<&Formatting>| features.into_iter().map(|f| label(*f)).collect::<Vec<_>>().join(", ");
+            block.push_str(&format!("\nFormatting you can use: {}.", names(allowed)));
+            if !banned.is_empty() {
+                block.push_str(&format!(" Don't use: {}.", names(banned)));
+            }
+        }
+        Some(block)
+    }
+
+    /// Reply text ready to send, and the part held back for "more"
+    pub fn apply(&self, text: &str) -> (String, Option<String>) {
+        let text = self.strip_unsupported(text);
+        match self.max_chars {
+            Some(max) => split(&text, max),
+            None => (text, None),
+        }
+    }
+
+    /// Remove markdown the channel would show as raw symbols
+    fn strip_unsupported(&self, text: &str) -> String {
+        let heading = regex::Regex::new(r"^\s*#{1,6}\s+").unwrap();
+        let mut out = text
+            .lines()
+            .filter(|line| self.allows(Formatting::Code) || !line.trim_start().starts_with("```"))
+            .map(|line| {
+                let mut line = line.to_string();
+                if !self.allows(Formatting::Headings) {
+                    line = heading.replace(&line, "").into_owned();
+                }
+                if !self.allows(Formatting::Lists) {
+                    let trimmed = line.trim_start();
+                    if let Some(item) = ["- ", "* ", "• "].iter().find_map(|bullet| trimmed.strip_prefix(bullet)) {
+                        line = item.to_string();
+                    }
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if !self.allows(Formatting::Bold) {
+            out = out.replace("**", "").replace("__", "");
+        }
+        if !self.allows(Formatting::Code) {
+            out = out.replace('`', "");
+        }
+        if !self.allows(Formatting::Links) {
+            let link = regex::Regex::new(r"\[([^\]]+)\]\((\S+?)\)").unwrap();
+            out = link.replace_all(&out, "$1 ($2)").into_owned();
+        }
+        out
+    }
+}
+
+/// Cut `text` to fit `max_chars` (continuation note included) at the latest
+/// paragraph, line, sentence or word break in the second half
+fn split(text: &str, max_chars: usize) -> (String, Option<String>) {
+    if text.chars().count() <= max_chars {
+        return (text.to_string(), None);
+    }
+    let budget = max_chars.saturating_sub(CONTINUATION_NOTE.chars().count()).max(1);
+    let cut = text.char_indices().nth(budget).map(|(i, _)| i).unwrap_or(text.len());
+    let head = &text[..cut];
+    let at = ["\n\n", "\n", ". ", " "]
+        .iter()
+        .find_map(|sep| head.rfind(sep).filter(|&i| i >= head.len() / 2).map(|i| i + sep.len()))
+        .unwrap_or(cut);
+    let (first, rest) = text.split_at(at);
+    (format!("{}{}", first.trim_end(), CONTINUATION_NOTE), Some(rest.trim_start().to_string()))
+}
+
+fn label(feature: Formatting) -> &'static str {
+    match feature {
+        Formatting::Bold => "**bold**",
+        Formatting::Italic => "_italics_",
+        Formatting::Headings => "# headings",
+        Formatting::Lists => "bullet lists",
+        Formatting::Links => "links",
+        Formatting::Code => "`code`",
+        Formatting::Tables => "tables",
+    }
+}
+
+fn title_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Remainders of cut-off replies, per channel and chat
+#[derive(Default)]
+pub struct Continuations {
+    pending: Mutex<HashMap<(String, String), String>>,
+}
+
+impl Continuations {
+    /// `text` styled for sending; the remainder (if any) replaces whatever the chat had pending
+    pub fn prepare(&self, style: &ResponseStyle, channel: &str, chat_id: &str, text: &str) -> String {
+        let (reply, rest) = style.apply(text);
+        let key = (channel.to_string(), chat_id.to_string());
+        let mut pending = self.pending.lock().unwrap();
+        match rest {
+            Some(rest) => pending.insert(key, rest),
+            None => pending.remove(&key),
+        };
+        reply
+    }
+
+    /// The next part of a cut-off reply, when `text` asks for "more" and one is pending
+    pub fn more(&self, style: &ResponseStyle, channel: &str, chat_id: &str, text: &str) -> Option<String> {
+        if !text.trim().trim_end_matches(['.', '!']).eq_ignore_ascii_case(MORE_COMMAND) {
+            return None;
+        }
+        let rest = self.pending.lock().unwrap().remove(&(channel.to_string(), chat_id.to_string()))?;
+        Some(self.prepare(style, channel, chat_id, &rest))
+    }
+
+    /// Every pending remainder as (channel, chat, text), for a daemon taking over
+    pub fn export(&self) -> Vec<(String, String, String)> {
+        self.pending.lock().unwrap().iter().map(|((channel, chat), rest)| (channel.clone(), chat.clone(), rest.clone())).collect()
+    }
+
+    /// Remainders handed over by the daemon this one took over from
+    pub fn import(&self, pending: Vec<(String, String, String)>) {
+        self.pending.lock().unwrap().extend(pending.into_iter().map(|(channel, chat, rest)| ((channel, chat), rest)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_continue() {
+        let voice = ResponseStyle::default_for("voice");
+        let (spoken, rest) = voice.apply("## Hours\n- **Mon-Fri** 8-5\n- See [our site](https://smithhvac.com)");
+        assert_eq!(spoken, "Hours\nMon-Fri 8-5\nSee our site (https://smithhvac.com)");
+        assert!(rest.is_none());
+        assert!(ResponseStyle::default_for("http").prompt_block("http").is_none());
+
+        let style = ResponseStyle { max_chars: Some(60), formatting: ALL_FORMATTING.to_vec() };
+        let text = "First paragraph is here.\n\nSecond paragraph is a bit longer than that one.";
+        let continuations = Continuations::default();
+        let first = continuations.prepare(&style, "telegram", "42", text);
+        assert_eq!(first, format!("First paragraph is here.{}", CONTINUATION_NOTE));
+        assert!(continuations.more(&style, "telegram", "7", "more").is_none());
+        assert!(continuations.more(&style, "telegram", "42", "tell me more").is_none());
+        let second = continuations.more(&style, "telegram", "42", "More").unwrap();
+        assert_eq!(second, "Second paragraph is a bit longer than that one.");
+        assert!(continuations.more(&style, "telegram", "42", "more").is_none());
+    }
+}
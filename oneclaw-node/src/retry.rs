@@ -0,0 +1,143 @@
+//! Retry - Backoff for LLM calls that failed on something transient
+//!
+//! Provider errors fall into three kinds, each with its own attempt budget:
+//! - rate limited or overloaded (429, Anthropic's 529 `overloaded_error`):
+//!   up to `retry.rate_limit_attempts` attempts
+//! - other server and network errors (5xx, connection failures, timeouts):
+//!   up to `retry.max_attempts` attempts
+//! - anything else (400s: bad requests, auth, prompts that are too long):
+//!   fails at once, since trying again would get the same answer
+//!
+//! Waits grow exponentially from `retry.base_delay_ms` up to `retry.max_delay_ms`,
+//! with jitter so nodes hitting the same limit don't retry in lockstep. When the
+//! budget is used up, the model router's fallback provider gets the call.
+//!
+//! Process-wide and set once at startup.
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::config::RetryConfig;
+use crate::llm::ApiError;
+
+static POLICY: OnceLock<RetryConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// 429 or overloaded: the provider asked us to slow down
+    RateLimited,
+    /// 5xx or network: worth trying again soon
+    Transient,
+    /// Won't succeed on retry
+    Permanent,
+}
+
+/// Retry LLM calls by `config` for the rest of the process
+pub fn activate(config: &RetryConfig) {
+    let _ = POLICY.set(config.clone());
+}
+
+/// The process-wide policy (the defaults until `activate`)
+pub fn policy() -> &'static RetryConfig {
+    POLICY.get_or_init(RetryConfig::default)
+}
+
+/// What kind of failure `error` is
+pub fn classify(error: &anyhow::Error) -> Failure {
+    if let Some(api) = error.downcast_ref::<ApiError>() {
+        return match api.status {
+            429 | 529 => Failure::RateLimited,
+            _ if api.body.contains("overloaded_error") => Failure::RateLimited,
+            500.. => Failure::Transient,
+            _ => Failure::Permanent,
+        };
+    }
+    match error.downcast_ref::<reqwest::Error>() {
+        Some(e) if e.is_status() => Failure::Permanent,
+        Some(_) => Failure::Transient,
+        None => Failure::Permanent,
+    }
+}
+
+impl RetryConfig {
+    /// Attempts allowed in all for a failure of `kind`
+    pub fn attempts(&self, kind: Failure) -> u32 {
+        match kind {
+            Failure::RateLimited => self.rate_limit_attempts,
+            Failure::Transient => self.max_attempts,
+            Failure::Permanent => 1,
+        }
+    }
+
+    /// The exponential wait after failed attempt `attempt` (1-based), before jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self.base_delay_ms.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        Duration::from_millis(exponential.min(self.max_delay_ms))
+    }
+
+    /// `backoff` with jitter: somewhere between half of it and all of it
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff(attempt).mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+}
+
+/// Run `call` until it succeeds, fails permanently or the budget for its
+/// failure kind is used up; `name` labels the log lines
+pub async fn with_backoff<T, F, Fut>(name: &str, mut call: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(error) => wait_to_retry(name, attempt, error).await?,
+        }
+        attempt += 1;
+    }
+}
+
+/// After failed attempt `attempt` (1-based): wait out the backoff before the
+/// next one, or give `error` back when the budget for its kind is used up
+pub async fn wait_to_retry(name: &str, attempt: u32, error: anyhow::Error) -> anyhow::Result<()> {
+    let policy = policy();
+    let kind = classify(&error);
+    if attempt >= policy.attempts(kind) {
+        if attempt > 1 {
+            tracing::warn!("{} still failing after {} attempts, giving up", name, attempt);
+        }
+        return Err(error);
+    }
+    let delay = policy.delay(attempt);
+    tracing::warn!("{} failed on attempt {} ({:?}), retrying in {}ms: {}", name, attempt, kind, delay.as_millis(), error);
+    tokio::time::sleep(delay).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_and_backoff() {
+        let api = |status: u16, body: &str| anyhow::Error::from(ApiError { status, body: body.to_string() });
+        assert_eq!(classify(&api(429, "")), Failure::RateLimited);
+        assert_eq!(classify(&api(529, "")), Failure::RateLimited);
+        assert_eq!(classify(&api(500, r#"{"type":"error","error":{"type":"overloaded_error"}}"#)), Failure::RateLimited);
+        assert_eq!(classify(&api(502, "")), Failure::Transient);
+        assert_eq!(classify(&api(400, "prompt is too long")), Failure::Permanent);
+        assert_eq!(classify(&anyhow::anyhow!("LLM did not call respond")), Failure::Permanent);
+
+        let config: RetryConfig = serde_yaml::from_str("base_delay_ms: 100\nmax_delay_ms: 1000").unwrap();
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(3), Duration::from_millis(400));
+        assert_eq!(config.backoff(10), Duration::from_millis(1000));
+        let jittered = config.delay(2);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+        assert_eq!((config.attempts(Failure::RateLimited), config.attempts(Failure::Permanent)), (5, 1));
+    }
+}
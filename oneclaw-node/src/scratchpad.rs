@@ -0,0 +1,126 @@
+//! Scratchpad - Working notes the agent keeps outside its context window
+//!
+//! `scratchpad.write` and `scratchpad.read` give each job its own notes file in
+//! the workspace (`scratchpad/<job>.md`). Long jobs keep intermediate findings,
+//! partial results and what's left to do there instead of carrying them in the
+//! conversation; a later turn (or a restarted node) reads them back. The job is
+//! the harness job id, or any short name for ad-hoc work.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Largest scratchpad; appends past it are refused (rewrite it shorter instead)
+pub const MAX_CHARS: usize = 32_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A scratchpad as `scratchpad.read` lists it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PadInfo {
+    pub job: String,
+    pub chars: usize,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Where scratchpads live in `workspace`
+pub fn dir(workspace: &Path) -> PathBuf {
+    workspace.join("scratchpad")
+}
+
+/// The notes file for `job`; ids are reduced to letters, digits, `-` and `_`
+fn path(dir: &Path, job: &str) -> anyhow::Result<PathBuf> {
+    let name: String = job
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .take(64)
+        .collect();
+    if name.trim_matches('_').is_empty() {
+        anyhow::bail!("job must name the job or task the notes belong to");
+    }
+    Ok(dir.join(format!("{}.md", name)))
+}
+
+/// `job`'s notes (empty when there are none yet)
+pub fn read(dir: &Path, job: &str) -> anyhow::Result<String> {
+    let path = path(dir, job)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    Ok(fs::read_to_string(path)?)
+}
+
+/// Append `text` to `job`'s notes, or replace them with it; returns the new length in characters
+pub fn write(dir: &Path, job: &str, text: &str, replace: bool) -> anyhow::Result<usize> {
+    let path = path(dir, job)?;
+    let mut notes = if replace { String::new() } else { read(dir, job)? };
+    if !notes.is_empty() && !notes.ends_with('\n') {
+        notes.push('\n');
+    }
+    notes.push_str(text.trim_end());
+    notes.push('\n');
+
+    let chars = notes.chars().count();
+    if chars > MAX_CHARS {
+        anyhow::bail!(
+            "scratchpad for {} would be {} characters (max {}); rewrite it shorter with replace: true",
+            job, chars, MAX_CHARS
+        );
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(path, notes)?;
+    Ok(chars)
+}
+
+/// Every scratchpad, most recently updated first
+pub fn list(dir: &Path) -> anyhow::Result<Vec<PadInfo>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(vec![]);
+    };
+    let mut pads = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(job) = path.file_stem().filter(|_| path.extension().is_some_and(|e| e == "md")) else {
+            continue;
+        };
+        pads.push(PadInfo {
+            job: job.to_string_lossy().to_string(),
+            chars: fs::read_to_string(&path)?.chars().count(),
+            updated_at: entry.metadata()?.modified()?.into(),
+        });
+    }
+    pads.sort_by_key(|pad| std::cmp::Reverse(pad.updated_at));
+    Ok(pads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_read_and_list() {
+        let dir = std::env::temp_dir().join(format!("oneclaw-scratchpad-test-{}", std::process::id()));
+
+        assert_eq!(read(&dir, "job_42").unwrap(), "");
+        write(&dir, "job_42", "Austin: 12 roofers found", false).unwrap();
+        write(&dir, "job_42", "Next: enrich the top 5", false).unwrap();
+        assert_eq!(read(&dir, "job_42").unwrap(), "Austin: 12 roofers found\nNext: enrich the top 5\n");
+        write(&dir, "job_42", "Done", true).unwrap();
+        assert_eq!(read(&dir, "job_42").unwrap(), "Done\n");
+
+        // Ids can't leave the scratchpad directory
+        write(&dir, "../escape", "x", false).unwrap();
+        assert!(dir.join("___escape.md").exists());
+        assert!(read(&dir, "/").is_err());
+
+        assert!(write(&dir, "big", &"x".repeat(MAX_CHARS + 1), false).is_err());
+        assert_eq!(list(&dir).unwrap().len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,356 @@
+//! Self-Check - Heartbeat health checks with simple remediations
+//!
+//! Every heartbeat tick checks that the node can still do its job, and fixes
+//! what it safely can:
+//! - Store writable (probe write); reported only
+//! - LLM API key accepted by the provider; reported only
+//! - Harness reachable; the tool catalog is re-fetched when it changed or never loaded,
+//!   and changes to a loaded one are passed on (see `tool_catalog.rs`)
+//! - Free disk space; oversized logs are rotated, more aggressively when space is low
+//! - Channel listeners that stopped with an error are restarted
+//!
+//! Problems and actions taken go to the ops alert channel (`HEARTBEAT_OPS_CHANNEL`,
+//! e.g. `telegram:123456`), only when they differ from the last report so a
+//! standing problem isn't repeated every tick.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::agent_os::{self, ToolDefinition};
+use crate::channels::OutgoingMessage;
+use crate::config::{self, NodeConfig};
+use crate::health::{ComponentStatus, HealthState};
+use crate::store::Store;
+use crate::tool_catalog::{self, CatalogDiff};
+
+/// Logs over this size are rotated
+const MAX_LOG_BYTES: u64 = 50 * 1024 * 1024;
+
+/// With disk space low, logs over this size are rotated too
+const LOW_DISK_LOG_BYTES: u64 = 1024 * 1024;
+
+/// User whose preferences row the store probe writes to
+const PROBE_USER: &str = "__selfcheck__";
+
+/// What one self-check run found and did
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Findings {
+    pub problems: Vec<String>,
+    pub actions: Vec<String>,
+}
+
+impl Findings {
+    pub fn is_clear(&self) -> bool {
+        self.problems.is_empty() && self.actions.is_empty()
+    }
+}
+
+pub struct SelfCheck {
+    config: &'static NodeConfig,
+    store: Arc<dyn Store>,
+    health: Arc<HealthState>,
+    harness_url: String,
+    /// Shared with the daemon, so a re-fetch is seen by chat turns
+    harness_tools: Arc<RwLock<Vec<ToolDefinition>>>,
+    min_free_disk_mb: u64,
+    /// `<channel>:<chat id>` reports go to
+    ops_channel: Option<String>,
+    last_reported: tokio::sync::Mutex<Findings>,
+    /// Where changes to a loaded tool catalog go
+    catalog_changes: Option<tokio::sync::mpsc::UnboundedSender<CatalogDiff>>,
+}
+
+impl SelfCheck {
+    pub fn new(
+        config: &'static NodeConfig,
+        store: Arc<dyn Store>,
+        health: Arc<HealthState>,
+        harness_url: String,
+        harness_tools: Arc<RwLock<Vec<ToolDefinition>>>,
+        min_free_disk_mb: u64,
+        ops_channel: Option<String>,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            health,
+            harness_url,
+            harness_tools,
+            min_free_disk_mb,
+            ops_channel,
+            last_reported: tokio::sync::Mutex::new(Findings::default()),
+            catalog_changes: None,
+        }
+    }
+
+    /// Pass catalog changes found by the harness check to `changes`
+    pub fn with_catalog_changes(mut self, changes: tokio::sync::mpsc::UnboundedSender<CatalogDiff>) -> Self {
+        self.catalog_changes = Some(changes);
+        self
+    }
+
+    /// Run all checks and remediations
+    pub async fn run(&self) -> Findings {
+        let mut findings = Findings::default();
+        self.check_store(&mut findings).await;
+        self.check_llm_key(&mut findings).await;
+        self.check_harness(&mut findings).await;
+        self.check_disk(&mut findings).await;
+        self.restart_failed(&mut findings).await;
+        findings
+    }
+
+    async fn check_store(&self, findings: &mut Findings) {
+        let probe = serde_json::json!({ "checked_at": chrono::Utc::now() });
+        let written = async {
+            // Preferences reference users, so the probe user has to exist first
+            if self.store.get_user(PROBE_USER).await?.is_none() {
+                self.store.create_user(PROBE_USER).await?;
+            }
+            self.store.set_preferences(PROBE_USER, probe).await
+        };
+        if let Err(e) = written.await {
+            findings.problems.push(format!("Store is not writable: {}", e));
+        }
+    }
+
+    async fn check_llm_key(&self, findings: &mut Findings) {
+        let llm = &self.config.llm;
+        // Local servers have no key to check; readiness probes whether they're up
+        if !crate::llm::needs_api_key(&llm.provider) {
+            return;
+        }
+        let key = match std::env::var(&llm.api_key_env) {
+            Ok(key) if !key.trim().is_empty() => key,
+            _ => {
+                findings.problems.push(format!("LLM API key missing: {}", llm.api_key_env));
+                return;
+            }
+        };
+
+        let client = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+            Ok(c) => c,
+            Err(e) => {
+                findings.problems.push(format!("LLM key check failed: {}", e));
+                return;
+            }
+        };
+        // Endpoints that need a valid key (OpenRouter's model list is public)
+        let request = match llm.provider.as_str() {
+            "anthropic" => client
+                .get("https://api.anthropic.com/v1/models")
+                .header("x-api-key", key)
+                .header("anthropic-version", "2023-06-01"),
+            "openrouter" => client.get("https://openrouter.ai/api/v1/auth/key").bearer_auth(key),
+            "openai" => client.get("https://api.openai.com/v1/models").bearer_auth(key),
+            other => {
+                findings.problems.push(format!("Unknown LLM provider: {}", other));
+                return;
+            }
+        };
+
+        match request.send().await {
+            Ok(resp) if matches!(resp.status().as_u16(), 401 | 403) => {
+                findings.problems.push(format!("{} rejected the API key in {} ({})", llm.provider, llm.api_key_env, resp.status()));
+            }
+            Ok(resp) if resp.status().is_server_error() => {
+                findings.problems.push(format!("{} returned {}", llm.provider, resp.status()));
+            }
+            Ok(_) => {}
+            Err(e) => findings.problems.push(format!("{} unreachable: {}", llm.provider, e)),
+        }
+    }
+
+    /// Re-fetch the tool catalog; replace it when it changed (e.g. discovery failed at startup)
+    async fn check_harness(&self, findings: &mut Findings) {
+        let tools = match agent_os::fetch_harness_tools(&self.config.node.id, &self.harness_url).await {
+            Ok(tools) => tools,
+            Err(e) => {
+                findings.problems.push(format!("Harness unreachable at {}: {}", self.harness_url, e));
+                self.health.set_component("harness", ComponentStatus::Failed(e.to_string())).await;
+                return;
+            }
+        };
+        if tools.is_empty() {
+            findings.problems.push(format!("Harness at {} returned no tools", self.harness_url));
+            return;
+        }
+
+        let replaced = match self.harness_tools.write() {
+            Ok(mut current) => {
+                let diff = tool_catalog::diff(&current, &tools);
+                let previous = current.len();
+                if !diff.is_empty() {
+                    *current = tools;
+                }
+                (!diff.is_empty()).then(|| (previous, current.len(), diff))
+            }
+            Err(_) => None,
+        };
+        if let Some((previous, count, diff)) = replaced {
+            findings.actions.push(format!("Re-fetched harness tool catalog: {} tools (was {})", count, previous));
+            self.health.set_component("harness", ComponentStatus::Ready(format!("{} tools", count))).await;
+            // A catalog that never loaded isn't a change
+            if let (Some(changes), true) = (&self.catalog_changes, previous > 0) {
+                let _ = changes.send(diff);
+            }
+        }
+    }
+
+    async fn check_disk(&self, findings: &mut Findings) {
+        let dir = match self.config.store.store_type.as_str() {
+            "hosted" | "postgres" => config::expand_path("~/.oneclaw"),
+            _ => config::expand_path(&self.config.store.sqlite_path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| config::expand_path("~/.oneclaw")),
+        };
+        let low = match free_disk_mb(&dir).await {
+            Some(free_mb) if free_mb < self.min_free_disk_mb => {
+                findings.problems.push(format!(
+                    "Low disk space: {} MB free on {} (minimum {} MB)",
+                    free_mb,
+                    dir.display(),
+                    self.min_free_disk_mb
+                ));
+                true
+            }
+            _ => false,
+        };
+
+        let threshold = if low { LOW_DISK_LOG_BYTES } else { MAX_LOG_BYTES };
+        for path in self.log_files() {
+            match rotate_if_larger(&path, threshold) {
+                Ok(Some(size)) => findings.actions.push(format!("Rotated {} ({} MB)", path.display(), size / (1024 * 1024))),
+                Ok(None) => {}
+                Err(e) => findings.problems.push(format!("Could not rotate {}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    /// The shadow log and `*.log` / `*.jsonl` files in `logging.path`
+    fn log_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![config::expand_path(&self.config.shadow.log_path)];
+        if let Ok(entries) = std::fs::read_dir(config::expand_path(&self.config.logging.path)) {
+            files.extend(
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("log" | "jsonl"))),
+            );
+        }
+        files
+    }
+
+    async fn restart_failed(&self, findings: &mut Findings) {
+        for (name, status) in self.health.components().await {
+            if let ComponentStatus::Failed(error) = status {
+                if self.health.restart(&name).await {
+                    findings.actions.push(format!("Restarted {} (failed: {})", name, error));
+                }
+            }
+        }
+    }
+
+    /// Send `findings` to the ops channel if they differ from the last report.
+    /// An all-clear is sent once after problems go away.
+    pub async fn report(&self, findings: &Findings) {
+        let mut last = self.last_reported.lock().await;
+        if *findings == *last {
+            return;
+        }
+        let message = render(&self.config.node.name, findings);
+        tracing::info!("🩺 {}", message);
+
+        let Some((channel_name, chat_id)) = self.ops_channel.as_deref().and_then(|c| c.split_once(':')) else {
+            tracing::debug!("No ops channel configured (HEARTBEAT_OPS_CHANNEL); self-check report logged only");
+            *last = findings.clone();
+            return;
+        };
+        // The channel may not be up yet on the first tick; report again next time
+        let Some(channel) = self.health.channel(channel_name).await else {
+            tracing::warn!("Ops channel {} is not available; self-check report not delivered", channel_name);
+            return;
+        };
+        let sent = channel.send(OutgoingMessage {
+            channel_type: channel.channel_type(),
+            channel_id: chat_id.to_string(),
+            content: message,
+            reply_to: None,
+            thread_id: None,
+            metadata: serde_json::json!({}),
+            attachments: vec![],
+            buttons: vec![],
+        }).await;
+        match sent {
+            Ok(()) => *last = findings.clone(),
+            Err(e) => tracing::warn!("Failed to deliver self-check report: {}", e),
+        }
+    }
+}
+
+fn render(node_name: &str, findings: &Findings) -> String {
+    if findings.is_clear() {
+        return format!("✅ Self-check on {}: all clear", node_name);
+    }
+    let mut out = format!("🩺 Self-check on {}", node_name);
+    if !findings.problems.is_empty() {
+        out.push_str("\n\nProblems:");
+        for problem in &findings.problems {
+            out.push_str(&format!("\n• {}", problem));
+        }
+    }
+    if !findings.actions.is_empty() {
+        out.push_str("\n\nActions taken:");
+        for action in &findings.actions {
+            out.push_str(&format!("\n• {}", action));
+        }
+    }
+    out
+}
+
+/// Free space on the filesystem holding `dir`, via `df` (None where unavailable)
+async fn free_disk_mb(dir: &Path) -> Option<u64> {
+    if !cfg!(unix) {
+        return None;
+    }
+    let output = tokio::process::Command::new("df").arg("-Pk").arg(dir).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    available_kb(&String::from_utf8_lossy(&output.stdout)).map(|kb| kb / 1024)
+}
+
+/// "Available" column of POSIX `df -Pk` output
+fn available_kb(df_output: &str) -> Option<u64> {
+    df_output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()
+}
+
+/// Move `path` to `<path>.1` (replacing an older rotation) if it is over `max_bytes`.
+/// Returns the rotated size.
+fn rotate_if_larger(path: &Path, max_bytes: u64) -> std::io::Result<Option<u64>> {
+    let size = match std::fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if size <= max_bytes {
+        return Ok(None);
+    }
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    std::fs::rename(path, rotated)?;
+    Ok(Some(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_kb_from_df() {
+        let df = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n/dev/sda1        102400000  51200000  48000000      52% /\n";
+        assert_eq!(available_kb(df), Some(48_000_000));
+        assert_eq!(available_kb("df: /nope: No such file or directory\n"), None);
+    }
+}
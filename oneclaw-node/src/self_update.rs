@@ -0,0 +1,205 @@
+//! Self-update - `oneclaw self-update` keeps a node on the latest release
+//!
+//! The release endpoint (`update.release_url`) answers
+//! `GET ?target=<os>-<arch>&current=<version>` with the latest release:
+//!
+//! ```json
+//! { "version": "0.2.0", "url": "https://.../oneclaw-linux-x86_64",
+//!   "sha256": "<hex>", "signature": "<base64>", "notes": "..." }
+//! ```
+//!
+//! The signature is an Ed25519 signature over
+//! `oneclaw-release:<version>:<target>:<sha256>`, so a release can't be replayed
+//! for another platform, and an older one is never installed over a newer one.
+//! It must verify against the key built into release binaries
+//! (`ONECLAW_RELEASE_KEY` at build time) or one in `update.trusted_keys`.
+//!
+//! A verified binary replaces the running one, and the daemon is restarted
+//! through the service manager: the Windows service, or the systemd unit
+//! `update.systemd_unit`. Without one, the daemon has to be restarted by hand.
+
+use std::path::Path;
+
+use base64::Engine;
+use serde::Deserialize;
+
+use crate::config::UpdateConfig;
+
+/// Release signing key, set when release binaries are built
+const RELEASE_KEY: Option<&str> = option_env!("ONECLAW_RELEASE_KEY");
+
+/// A release as described by the release endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub version: String,
+    pub url: String,
+    /// Hex SHA-256 of the binary
+    pub sha256: String,
+    /// Base64 Ed25519 signature, see `signed_message`
+    pub signature: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// This platform, as release targets are named (`linux-x86_64`, `windows-x86_64`, ...)
+pub fn target() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// Whether `candidate` is a later version than `current` (`0.10.0` > `0.9.3`)
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    }
+    parts(candidate) > parts(current)
+}
+
+fn signed_message(release: &Release, target: &str) -> String {
+    format!("oneclaw-release:{}:{}:{}", release.version, target, release.sha256.to_lowercase())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// `binary` is `release` for `target`, signed by one of `keys`
+pub fn verify(release: &Release, target: &str, binary: &[u8], keys: &[String]) -> anyhow::Result<()> {
+    if keys.is_empty() {
+        anyhow::bail!("No release signing key: this build has none, and update.trusted_keys is empty");
+    }
+    if sha256_hex(binary) != release.sha256.to_lowercase() {
+        anyhow::bail!("Downloaded binary doesn't match the release's SHA-256");
+    }
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let signature = b64.decode(&release.signature)?;
+    let message = signed_message(release, target);
+    let signed = keys.iter().filter_map(|key| b64.decode(key).ok()).any(|key| {
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key)
+            .verify(message.as_bytes(), &signature)
+            .is_ok()
+    });
+    if !signed {
+        anyhow::bail!("Release {} isn't signed by a trusted release key", release.version);
+    }
+    Ok(())
+}
+
+/// The latest release for this platform
+pub async fn latest(config: &UpdateConfig) -> anyhow::Result<Release> {
+    Ok(reqwest::Client::new()
+        .get(&config.release_url)
+        .query(&[("target", target().as_str()), ("current", env!("CARGO_PKG_VERSION"))])
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Swap the binary at `exe` for `binary`. The running process keeps its old
+/// image; on Windows the old file is moved aside to `<exe>.old` first.
+fn replace(exe: &Path, binary: &[u8]) -> anyhow::Result<()> {
+    let staged = exe.with_file_name(format!("{}.new", exe.file_name().unwrap_or_default().to_string_lossy()));
+    std::fs::write(&staged, binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(windows)]
+    {
+        let old = exe.with_file_name(format!("{}.old", exe.file_name().unwrap_or_default().to_string_lossy()));
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old)?;
+    }
+    std::fs::rename(&staged, exe)?;
+    Ok(())
+}
+
+/// `oneclaw self-update`: install the latest release if it's newer, then restart
+/// the service unless `no_restart`. With `check`, only report what's available.
+pub async fn run(config: &UpdateConfig, check: bool, no_restart: bool) -> anyhow::Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = latest(config).await?;
+    if !is_newer(&release.version, current) {
+        println!("✅ Up to date ({})", current);
+        return Ok(());
+    }
+    println!("⬆️  {} is available (running {})", release.version, current);
+    if let Some(notes) = &release.notes {
+        println!("{}", notes);
+    }
+    if check {
+        return Ok(());
+    }
+
+    let binary = reqwest::Client::new()
+        .get(&release.url)
+        .timeout(std::time::Duration::from_secs(600))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let keys: Vec<String> = RELEASE_KEY.map(String::from).into_iter().chain(config.trusted_keys.iter().cloned()).collect();
+    verify(&release, &target(), &binary, &keys)?;
+
+    let exe = std::env::current_exe()?;
+    replace(&exe, &binary)?;
+    println!("✅ Installed {} at {}", release.version, exe.display());
+
+    if no_restart {
+        println!("Restart the daemon to run it");
+    } else if crate::service::restart(&config.systemd_unit)? {
+        println!("🔁 Restarted the daemon");
+    } else {
+        println!("No service manager runs the daemon; restart it to run {}", release.version);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    #[test]
+    fn test_release_verification() {
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("v1.0.0", "0.9.9"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let keys = vec![b64.encode(keypair.public_key().as_ref())];
+        let binary = b"\x7fELF new oneclaw";
+        let mut release = Release {
+            version: "0.2.0".to_string(),
+            url: "https://example.com/oneclaw-linux-x86_64".to_string(),
+            sha256: sha256_hex(binary),
+            signature: String::new(),
+            notes: None,
+        };
+        release.signature = b64.encode(keypair.sign(signed_message(&release, "linux-x86_64").as_bytes()).as_ref());
+
+        assert!(verify(&release, "linux-x86_64", binary, &keys).is_ok());
+        assert!(verify(&release, "linux-x86_64", binary, &[]).is_err());
+        // Another platform's release, or a swapped binary
+        assert!(verify(&release, "windows-x86_64", binary, &keys).is_err());
+        assert!(verify(&release, "linux-x86_64", b"tampered", &keys).is_err());
+        // A re-pinned hash breaks the signature
+        release.sha256 = sha256_hex(b"tampered");
+        assert!(verify(&release, "linux-x86_64", b"tampered", &keys).is_err());
+    }
+}
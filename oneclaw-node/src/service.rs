@@ -0,0 +1,211 @@
+//! Service - Run the node daemon as an OS service
+//!
+//! Windows: registers with the Service Control Manager via `windows-service`.
+//! `oneclaw service install` creates an auto-start service that launches
+//! `oneclaw service run`, which hands control to the SCM dispatcher.
+//!
+//! Other platforms: run `oneclaw daemon` under systemd/launchd instead.
+//!
+//! `restart` is how `oneclaw self-update` gets the daemon onto a new binary:
+//! the Windows service, or a systemd unit on Linux.
+
+#[cfg(windows)]
+pub use windows_impl::{install, restart, run, uninstall};
+
+#[cfg(not(windows))]
+pub fn install(_port: u16) -> anyhow::Result<()> {
+    anyhow::bail!("`oneclaw service install` is only supported on Windows. Use a systemd unit (oneclaw-node.service) on Linux.")
+}
+
+#[cfg(not(windows))]
+pub fn uninstall() -> anyhow::Result<()> {
+    anyhow::bail!("`oneclaw service uninstall` is only supported on Windows.")
+}
+
+#[cfg(not(windows))]
+pub fn run(_port: u16) -> anyhow::Result<()> {
+    anyhow::bail!("`oneclaw service run` is only used by the Windows Service Control Manager. Use `oneclaw daemon` instead.")
+}
+
+/// Restart the systemd `unit` running the daemon; false if there is no such unit
+#[cfg(not(windows))]
+pub fn restart(unit: &str) -> anyhow::Result<bool> {
+    let installed = std::process::Command::new("systemctl")
+        .args(["cat", unit])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !installed {
+        return Ok(false);
+    }
+    let status = std::process::Command::new("systemctl").args(["restart", unit]).status()?;
+    if !status.success() {
+        anyhow::bail!("systemctl restart {} failed ({})", unit, status);
+    }
+    Ok(true)
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use std::ffi::OsString;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+    use windows_service::{
+        define_windows_service,
+        service::{
+            ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+            ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+        },
+        service_control_handler::{self, ServiceControlHandlerResult},
+        service_dispatcher,
+        service_manager::{ServiceManager, ServiceManagerAccess},
+    };
+
+    const SERVICE_NAME: &str = "OneClawNode";
+    const SERVICE_DISPLAY_NAME: &str = "OneClaw Node";
+
+    static SERVICE_PORT: OnceLock<u16> = OnceLock::new();
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    /// Register the service with the SCM (requires an elevated prompt)
+    pub fn install(port: u16) -> anyhow::Result<()> {
+        let manager = ServiceManager::local_computer(
+            None::<&str>,
+            ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+        )?;
+
+        let service_info = ServiceInfo {
+            name: OsString::from(SERVICE_NAME),
+            display_name: OsString::from(SERVICE_DISPLAY_NAME),
+            service_type: ServiceType::OWN_PROCESS,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: std::env::current_exe()?,
+            launch_arguments: vec![
+                OsString::from("service"),
+                OsString::from("run"),
+                OsString::from("--port"),
+                OsString::from(port.to_string()),
+            ],
+            dependencies: vec![],
+            account_name: None,
+            account_password: None,
+        };
+
+        let service = manager.create_service(&service_info, ServiceAccess::CHANGE_CONFIG)?;
+        service.set_description("OneClaw Node Runtime - AI agent daemon")?;
+        println!("✅ Installed Windows service '{}' (port {})", SERVICE_NAME, port);
+        println!("Start it with: sc start {}", SERVICE_NAME);
+        Ok(())
+    }
+
+    pub fn uninstall() -> anyhow::Result<()> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+        let service = manager.open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )?;
+
+        if service.query_status()?.current_state != ServiceState::Stopped {
+            let _ = service.stop();
+        }
+        service.delete()?;
+        println!("✅ Removed Windows service '{}'", SERVICE_NAME);
+        Ok(())
+    }
+
+    /// Stop and start the service so it runs the binary now on disk; false if it isn't installed
+    pub fn restart(_unit: &str) -> anyhow::Result<bool> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+        let Ok(service) = manager.open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::START,
+        ) else {
+            return Ok(false);
+        };
+
+        if service.query_status()?.current_state != ServiceState::Stopped {
+            service.stop()?;
+            for _ in 0..60 {
+                if service.query_status()?.current_state == ServiceState::Stopped {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(500));
+            }
+        }
+        service.start::<&str>(&[])?;
+        Ok(true)
+    }
+
+    /// Entry point when launched by the SCM. Blocks until the service stops.
+    pub fn run(port: u16) -> anyhow::Result<()> {
+        // The SCM starts services in System32; resolve .env and templates next to the binary
+        if let Some(exe_dir) = std::env::current_exe()?.parent() {
+            std::env::set_current_dir(exe_dir)?;
+        }
+        crate::load_dotenv();
+
+        SERVICE_PORT.set(port).ok();
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main)?;
+        Ok(())
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Err(e) = run_service() {
+            tracing::error!("Windows service error: {}", e);
+        }
+    }
+
+    fn run_service() -> anyhow::Result<()> {
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let shutdown_tx = std::sync::Mutex::new(Some(shutdown_tx));
+
+        let event_handler = move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                if let Some(tx) = shutdown_tx.lock().ok().and_then(|mut guard| guard.take()) {
+                    let _ = tx.send(());
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        let status_handle = service_control_handler::register(SERVICE_NAME, event_handler)?;
+
+        let set_state = |state: ServiceState| {
+            status_handle.set_service_status(ServiceStatus {
+                service_type: ServiceType::OWN_PROCESS,
+                current_state: state,
+                controls_accepted: if state == ServiceState::Running {
+                    ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+                } else {
+                    ServiceControlAccept::empty()
+                },
+                exit_code: ServiceExitCode::Win32(0),
+                checkpoint: 0,
+                wait_hint: Duration::default(),
+                process_id: None,
+            })
+        };
+
+        set_state(ServiceState::Running)?;
+
+        let port = SERVICE_PORT.get().copied().unwrap_or(8787);
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(async {
+            tokio::select! {
+                result = crate::daemon::start(port, false) => {
+                    if let Err(e) = result {
+                        tracing::error!("Daemon exited with error: {}", e);
+                    }
+                }
+                _ = shutdown_rx => {
+                    tracing::info!("🛑 Service stop requested");
+                }
+            }
+        });
+
+        set_state(ServiceState::Stopped)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Shadow Mode - Read-only observer for new deployments
+//!
+//! With `shadow.enabled`, the node reads messages, talks to the LLM and plans
+//! jobs as usual, but nothing leaves it: executor calls (harness tools, Gmail,
+//! contacts, HTTP), harness job creation and channel sends are written to the
+//! shadow log instead. A business can watch what the agent would have done,
+//! via `GET /shadow`, before turning real execution on.
+//!
+//! Shadow mode is process-wide and set once at startup.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::channels::{ChannelType, OutgoingMessage};
+use crate::config::{self, ShadowConfig};
+
+/// Log file, set when shadow mode is on
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializes appends from concurrent handlers
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShadowKind {
+    /// An executor call (harness tool, Gmail, HTTP, ...)
+    ToolCall,
+    /// An autonomous job that would have been created in the harness
+    Job,
+    /// A chat message to a user
+    Message,
+    /// A file upload to a user
+    File,
+}
+
+/// Something the node would have done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowAction {
+    pub at: DateTime<Utc>,
+    pub kind: ShadowKind,
+    /// Executor id, or `<channel>:<chat>` for sends
+    pub target: String,
+    pub detail: serde_json::Value,
+}
+
+/// Turn shadow mode on for this process if the config asks for it
+pub fn activate(config: &ShadowConfig) {
+    if config.enabled && LOG_PATH.set(config::expand_path(&config.log_path)).is_ok() {
+        tracing::warn!("👻 Shadow mode: nothing will be executed or sent; logging to {}", config.log_path);
+    }
+}
+
+pub fn active() -> bool {
+    LOG_PATH.get().is_some()
+}
+
+/// Log a would-be action (no-op when shadow mode is off)
+pub fn record(kind: ShadowKind, target: &str, detail: serde_json::Value) {
+    let Some(path) = LOG_PATH.get() else {
+        return;
+    };
+    tracing::info!("👻 Would have done {:?} -> {}", kind, target);
+
+    let action = ShadowAction { at: Utc::now(), kind, target: target.to_string(), detail };
+    let _guard = LOG_LOCK.lock();
+    let written = (|| -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&action)?)?;
+        Ok(())
+    })();
+    if let Err(e) = written {
+        tracing::warn!("Failed to write shadow log: {}", e);
+    }
+}
+
+/// In shadow mode, log `msg` and return true so the channel skips sending it
+pub fn intercept_send(msg: &OutgoingMessage) -> bool {
+    if !active() {
+        return false;
+    }
+    record(
+        ShadowKind::Message,
+        &format!("{}:{}", msg.channel_type, msg.channel_id),
+        serde_json::json!({ "content": msg.content, "reply_to": msg.reply_to, "attachments": msg.attachments }),
+    );
+    true
+}
+
+/// In shadow mode, log a file upload and return true so the channel skips it
+pub fn intercept_file(channel_type: ChannelType, channel_id: &str, path: &std::path::Path, caption: Option<&str>) -> bool {
+    if !active() {
+        return false;
+    }
+    record(
+        ShadowKind::File,
+        &format!("{}:{}", channel_type, channel_id),
+        serde_json::json!({ "path": path, "caption": caption }),
+    );
+    true
+}
+
+/// The newest `limit` logged actions, newest first
+pub fn recent(limit: usize) -> anyhow::Result<Vec<ShadowAction>> {
+    let Some(path) = LOG_PATH.get() else {
+        return Ok(vec![]);
+    };
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
@@ -0,0 +1,163 @@
+//! SigV4 - AWS Signature Version 4 request signing
+//!
+//! Shared by S3 backups (`backup.rs`) and the Bedrock LLM provider (`llm.rs`).
+//! Signed requests carry `host`, `x-amz-content-sha256`, `x-amz-date` and, with
+//! temporary credentials, `x-amz-security-token`.
+
+use chrono::{DateTime, Utc};
+
+pub struct Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+    /// Set for temporary (STS / SSO) credentials
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optional `AWS_SESSION_TOKEN`
+    pub fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            session_token: env("AWS_SESSION_TOKEN"),
+            ..Self::from_env_vars("AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY")?
+        })
+    }
+
+    /// Long-lived keys from the named env vars
+    pub fn from_env_vars(access_key_env: &str, secret_key_env: &str) -> anyhow::Result<Self> {
+        let required = |name: &str| env(name).ok_or_else(|| anyhow::anyhow!("{} not set", name));
+        Ok(Self {
+            access_key: required(access_key_env)?,
+            secret_key: required(secret_key_env)?,
+            session_token: None,
+        })
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// A request to sign. `path` and `query` must already be canonical
+/// (`uri_encode` / `canonical_query`) and sent exactly as signed.
+pub struct Request<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    pub query: &'a str,
+    pub body: &'a [u8],
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::sign(&key, data.as_bytes()).as_ref().to_vec()
+}
+
+/// SigV4 URI encoding: everything but unreserved characters (and `/` in paths)
+pub fn uri_encode(value: &str, keep_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            b'/' if keep_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn canonical_query(query: &[(&str, &str)]) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .iter()
+        .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+        .collect();
+    pairs.sort();
+    pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&")
+}
+
+/// Headers to send with `request` (`Authorization` included), signed at `now`
+pub fn sign(
+    credentials: &Credentials,
+    region: &str,
+    service: &str,
+    request: &Request,
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let payload_hash = sha256_hex(request.body);
+
+    let mut headers = vec![
+        ("host", request.host.to_string()),
+        ("x-amz-content-sha256", payload_hash.clone()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method, request.path, request.query, canonical_headers, signed_headers, payload_hash
+    );
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+
+    let key = hmac(format!("AWS4{}", credentials.secret_key).as_bytes(), &date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, service);
+    let key = hmac(&key, "aws4_request");
+    let signature: String = hmac(&key, &string_to_sign).iter().map(|b| format!("{:02x}", b)).collect();
+
+    // reqwest sets Host from the URL
+    headers.retain(|(name, _)| *name != "host");
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key, scope, signed_headers, signature
+        ),
+    ));
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_encoding_and_signature() {
+        assert_eq!(uri_encode("/bucket/oneclaw/node 1/a+b.bundle", true), "/bucket/oneclaw/node%201/a%2Bb.bundle");
+        assert_eq!(canonical_query(&[("prefix", "oneclaw/n1/"), ("list-type", "2")]), "list-type=2&prefix=oneclaw%2Fn1%2F");
+
+        let credentials = Credentials {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let request = Request {
+            method: "GET",
+            host: "examplebucket.s3.amazonaws.com",
+            path: "/test.txt",
+            query: "",
+            body: b"",
+        };
+        let now = Utc.with_ymd_and_hms(2013, 5, 24, 0, 0, 0).unwrap();
+        let headers = sign(&credentials, "us-east-1", "s3", &request, now);
+        let authorization = &headers.iter().find(|(name, _)| *name == "authorization").unwrap().1;
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20130524/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=14f6a0997b2b70a86f4726658a6575b5109092ccb5fd328f51b369c44b4ac958"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Simulation - Dry-run a job plan against mock executors
+//!
+//! Runs each step of a plan against a fixture executor instead of the harness,
+//! so users can preview what a multi-step job will do and roughly what it will
+//! cost before running it for real. Nothing leaves the node: discovery returns
+//! sample businesses, enrichment sample contacts, audits a sample score.
+//!
+//! Call counts follow step references: `enrich` on `{from_step_1}` runs once
+//! per business step 1 would find. Costs come from the live tool catalog.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::agent_os::ToolDefinition;
+use crate::autonomous_jobs::{self, JobPlan, JobStep};
+
+/// Harness default for discovery `limit`
+const DEFAULT_DISCOVER_LIMIT: u64 = 50;
+
+/// Leads drafted by call-script / voicemail steps when `limit` is unset
+const DEFAULT_DRAFT_LIMIT: u64 = 3;
+
+/// Sample rows returned per step (counts and costs use the full numbers)
+const MAX_FIXTURE_ROWS: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedStep {
+    pub order: i32,
+    pub action: String,
+    pub params: Value,
+    /// Harness calls this step would make
+    pub calls: u64,
+    pub estimated_cost_usd: f64,
+    /// Representative output from the mock executor
+    pub output: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    pub description: String,
+    pub steps: Vec<SimulatedStep>,
+    pub estimated_cost_usd: f64,
+    /// Fixture results in the harness `/results` shape
+    pub results: Value,
+}
+
+/// Run a plan against mock executors
+pub fn simulate(plan: &JobPlan, tools: &[ToolDefinition]) -> Simulation {
+    let mut steps: Vec<SimulatedStep> = Vec::new();
+    let mut businesses: Vec<Value> = Vec::new();
+    let mut contacts: Vec<Value> = Vec::new();
+
+    for step in &plan.steps {
+        let calls = call_count(step, &steps);
+        let output = mock_output(step, calls);
+        if let Some(found) = output["businesses"].as_array() {
+            businesses.extend(found.iter().cloned());
+        }
+        if let Some(found) = output["contacts"].as_array() {
+            contacts.extend(found.iter().cloned());
+        }
+
+        let unit_cost = autonomous_jobs::harness_tool_for(&step.action)
+            .and_then(|tool_id| tools.iter().find(|t| t.id == tool_id))
+            .and_then(|t| t.cost_estimate)
+            .unwrap_or(0.0);
+
+        steps.push(SimulatedStep {
+            order: step.order,
+            action: step.action.clone(),
+            params: step.params.clone(),
+            calls,
+            estimated_cost_usd: unit_cost * calls as f64,
+            output,
+        });
+    }
+
+    Simulation {
+        description: plan.description.clone(),
+        estimated_cost_usd: steps.iter().map(|s| s.estimated_cost_usd).sum(),
+        steps,
+        results: json!({
+            "job": { "description": plan.description, "status": "simulated" },
+            "businesses": businesses,
+            "contacts": contacts,
+        }),
+    }
+}
+
+/// Chat-friendly preview: steps with call counts and costs, then sample results
+pub fn format_preview(sim: &Simulation) -> String {
+    let mut output = format!("🧪 **Simulated job** ({} steps, nothing was run)\n\n", sim.steps.len());
+    for step in &sim.steps {
+        output.push_str(&format!("{}. **{}**", step.order, step.action));
+        if step.calls > 1 {
+            output.push_str(&format!(" × {}", step.calls));
+        }
+        if step.estimated_cost_usd > 0.0 {
+            output.push_str(&format!(" - ~${:.2}", step.estimated_cost_usd));
+        }
+        output.push('\n');
+    }
+    output.push_str(&format!("\n💵 **Estimated cost:** ~${:.2}\n\n", sim.estimated_cost_usd));
+    output.push_str("**Sample output:**\n\n");
+    output.push_str(&crate::autonomous_jobs_poller::format_job_results(&sim.results));
+    output
+}
+
+/// Calls a step makes: once, or once per item of the step it references
+fn call_count(step: &JobStep, previous: &[SimulatedStep]) -> u64 {
+    match step.action.as_str() {
+        "discover" => 1,
+        action if crate::outreach::LOCAL_ACTIONS.contains(&action) => {
+            step.params["limit"].as_u64().unwrap_or(DEFAULT_DRAFT_LIMIT)
+        }
+        _ => referenced_step(&step.params)
+            .and_then(|order| previous.iter().find(|s| s.order == order))
+            .map(|s| s.output["totalFound"].as_u64().unwrap_or(s.calls))
+            .unwrap_or(1),
+    }
+}
+
+/// First `{from_step_N}` reference in a step's params
+fn referenced_step(params: &Value) -> Option<i32> {
+    let text = params.to_string();
+    let start = text.find("{from_step_")? + "{from_step_".len();
+    let end = start + text[start..].find('}')?;
+    text[start..end].parse().ok()
+}
+
+fn mock_output(step: &JobStep, calls: u64) -> Value {
+    let rows = (calls as usize).min(MAX_FIXTURE_ROWS);
+    match step.action.as_str() {
+        "discover" => {
+            let niche = step.params["niche"].as_str().unwrap_or("business");
+            let location = step.params["location"]
+                .as_str()
+                .or_else(|| step.params["territory"].as_str())
+                .unwrap_or("your area");
+            let limit = step.params["limit"].as_u64().unwrap_or(DEFAULT_DISCOVER_LIMIT);
+            let businesses: Vec<Value> = (1..=(limit as usize).min(MAX_FIXTURE_ROWS))
+                .map(|i| json!({
+                    "name": format!("Sample {} #{}", niche, i),
+                    "city": location,
+                    "rating": 4.9 - 0.2 * i as f64,
+                    "reviewCount": 40 * i,
+                    "phone": format!("(555) 010-{:04}", i),
+                    "website": format!("https://sample-{}-{}.example", niche.replace(' ', "-"), i),
+                    "score": 90 - 5 * i as i64,
+                }))
+                .collect();
+            json!({ "businesses": businesses, "totalFound": limit })
+        }
+        "enrich" => json!({
+            "contacts": (1..=rows).map(|i| json!({
+                "name": format!("Sample Owner {}", i),
+                "role": "Owner",
+                "email": format!("owner{}@example.com", i),
+                "phone": format!("(555) 020-{:04}", i),
+            })).collect::<Vec<_>>(),
+        }),
+        "audit" => json!({
+            "score": 62,
+            "issues": [{ "severity": "critical", "title": "No Google Business Profile posts in 90 days" }],
+        }),
+        "analyze" => json!({ "summary": "Sample analysis of the discovered businesses" }),
+        action if crate::outreach::LOCAL_ACTIONS.contains(&action) => {
+            json!({ "drafts": rows, "note": "Drafted on the node from the job's results" })
+        }
+        _ => json!({}),
+    }
+}
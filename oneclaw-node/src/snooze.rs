@@ -0,0 +1,187 @@
+//! Snooze - Put off proactive notifications
+//!
+//! Job outcomes and other proactive notifications are remembered per chat.
+//! Replying "snooze this for 2 hours" (or tapping a snooze button, which sends
+//! `/snooze 1h`) stores the notification in the `snoozes` table, and the
+//! scheduler loop redelivers it on the same channel once it's due. Snoozes are
+//! persisted, so a restart doesn't lose them.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::channels::outbound::Priority;
+use crate::channels::{Button, Channel, OutgoingMessage};
+use crate::store::Store;
+
+/// How often the scheduler looks for due snoozes
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Snooze length when the request doesn't name one ("snooze this")
+const DEFAULT_SNOOZE_MINUTES: i64 = 60;
+
+/// Longest snooze accepted; longer requests aren't treated as snoozes
+const MAX_SNOOZE_DAYS: i64 = 30;
+
+/// Snooze buttons for a notification; a press sends `/snooze <duration>`
+pub fn buttons() -> Vec<Vec<Button>> {
+    vec![vec![
+        Button::new("⏰ 1h", "/snooze 1h"),
+        Button::new("3h", "/snooze 3h"),
+        Button::new("Tomorrow", "/snooze 24h"),
+    ]]
+}
+
+/// How long to snooze for, if `text` is a snooze request
+/// ("snooze", "/snooze 2h", "snooze this for 30 minutes", "snooze until tomorrow")
+pub fn parse_request(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let rest = text.strip_prefix('/').unwrap_or(&text).strip_prefix("snooze")?;
+    if rest.starts_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+
+    let amount = regex::Regex::new(r"(\d+)\s*(m|mins?|minutes?|h|hrs?|hours?|d|days?)\b").expect("valid regex");
+    if let Some(caps) = amount.captures(rest) {
+        let n: i64 = caps[1].parse().ok()?;
+        let duration = match &caps[2][..1] {
+            "m" => Duration::try_minutes(n),
+            "h" => Duration::try_hours(n),
+            _ => Duration::try_days(n),
+        }?;
+        return (duration <= Duration::days(MAX_SNOOZE_DAYS)).then_some(duration);
+    }
+    if rest.contains("half an hour") {
+        Some(Duration::minutes(30))
+    } else if rest.contains("an hour") {
+        Some(Duration::hours(1))
+    } else if rest.contains("tomorrow") {
+        Some(Duration::days(1))
+    } else {
+        Some(Duration::minutes(DEFAULT_SNOOZE_MINUTES))
+    }
+}
+
+pub struct Snoozes {
+    store: Arc<dyn Store>,
+    /// Latest snoozable notification per (channel, chat)
+    last_notification: std::sync::Mutex<HashMap<(String, String), String>>,
+    /// Where to redeliver, by channel name; registered as channels come up
+    channels: RwLock<HashMap<String, Arc<dyn Channel>>>,
+}
+
+impl Snoozes {
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        Self {
+            store,
+            last_notification: std::sync::Mutex::new(HashMap::new()),
+            channels: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn register_channel(&self, name: &str, channel: Arc<dyn Channel>) {
+        self.channels.write().await.insert(name.to_string(), channel);
+    }
+
+    /// Remember a notification so "snooze this" in the same chat refers to it
+    pub fn remember(&self, channel: &str, chat_id: &str, content: &str) {
+        if let Ok(mut last) = self.last_notification.lock() {
+            last.insert((channel.to_string(), chat_id.to_string()), content.to_string());
+        }
+    }
+
+    /// Snooze `content` (or the chat's latest notification) for `duration`.
+    /// Returns when it will be redelivered, or None if there is nothing to snooze.
+    pub async fn snooze(
+        &self,
+        channel: &str,
+        chat_id: &str,
+        content: Option<&str>,
+        duration: Duration,
+    ) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let key = (channel.to_string(), chat_id.to_string());
+        let content = match content {
+            Some(content) => content.to_string(),
+            None => match self.last_notification.lock().ok().and_then(|mut last| last.remove(&key)) {
+                Some(content) => content,
+                None => return Ok(None),
+            },
+        };
+
+        let deliver_at = Utc::now()
+            .checked_add_signed(duration)
+            .ok_or_else(|| anyhow::anyhow!("Snooze of {} is too long", duration))?;
+        self.store.add_snooze(channel, chat_id, &content, deliver_at).await?;
+        tracing::info!("⏰ Snoozed notification in {}:{} until {}", channel, chat_id, deliver_at);
+        Ok(Some(deliver_at))
+    }
+
+    /// Redeliver due snoozes until the process exits
+    pub async fn run(self: Arc<Self>) {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.deliver_due().await {
+                tracing::warn!("Snooze check failed: {}", e);
+            }
+        }
+    }
+
+    async fn deliver_due(&self) -> anyhow::Result<()> {
+        for snooze in self.store.due_snoozes(Utc::now()).await? {
+            // The channel may not be up yet right after a restart; try again next tick
+            let Some(channel) = self.channels.read().await.get(&snooze.channel).cloned() else {
+                continue;
+            };
+
+            let sent = channel.send_with_priority(OutgoingMessage {
+                channel_type: channel.channel_type(),
+                channel_id: snooze.chat_id.clone(),
+                content: format!("⏰ Snoozed reminder\n\n{}", snooze.content),
+                reply_to: None,
+                thread_id: None,
+                metadata: serde_json::json!({}),
+                attachments: vec![],
+                buttons: buttons(),
+            }, Priority::Notification).await;
+
+            match sent {
+                Ok(()) => {
+                    self.remember(&snooze.channel, &snooze.chat_id, &snooze.content);
+                    self.store.remove_snooze(snooze.id).await?;
+                }
+                Err(e) => tracing::warn!("Failed to redeliver snoozed notification {}: {}", snooze.id, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Confirmation for a snooze request
+pub fn confirmation(deliver_at: Option<DateTime<Utc>>) -> String {
+    match deliver_at {
+        Some(at) => format!("⏰ Snoozed - I'll remind you again at {} UTC", at.format("%b %-d, %H:%M")),
+        None => "Nothing to snooze here yet.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snooze_requests() {
+        assert_eq!(parse_request("snooze this for 2 hours"), Some(Duration::hours(2)));
+        assert_eq!(parse_request("/snooze 30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_request("Snooze until tomorrow"), Some(Duration::days(1)));
+        assert_eq!(parse_request("snooze"), Some(Duration::minutes(DEFAULT_SNOOZE_MINUTES)));
+        assert_eq!(parse_request("/snooze 30d"), Some(Duration::days(30)));
+        // Too long, including lengths chrono can't represent
+        assert_eq!(parse_request("/snooze 31d"), None);
+        assert_eq!(parse_request("/snooze 999999999d"), None);
+        assert_eq!(parse_request("/snooze 99999999999999h"), None);
+        assert_eq!(parse_request("snoozed leads in Miami"), None);
+        assert_eq!(parse_request("find HVAC companies"), None);
+    }
+}
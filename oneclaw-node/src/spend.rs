@@ -0,0 +1,246 @@
+//! Spend - Node-wide throttle on what LLM and tool calls cost
+//!
+//! A token bucket in dollars: it holds up to `spend.burst_usd` and refills at
+//! `spend.max_usd_per_hour`. Every LLM call is charged from the usage its
+//! provider reports (priced per model, see `price`), every paid harness tool at
+//! the cost it reports, else its catalog estimate. Calls are charged after they
+//! finish, so the bucket can go below zero; it is empty until it has refilled
+//! past zero again.
+//!
+//! While the bucket is empty:
+//! - LLM calls switch to `spend.degrade_to` (a cheaper model) when set, and
+//!   otherwise wait for the bucket to refill
+//! - paid tools wait for the bucket to refill
+//!
+//! Waits give up after `spend.queue_timeout_secs` and the call fails with a
+//! spend-limit error. This sits on top of any per-user limits and guards the
+//! owner against runaway loops. Off unless `max_usd_per_hour` is set.
+//!
+//! Process-wide and set once at startup.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::{LlmConfig, ModelPrice, SpendConfig};
+
+static THROTTLE: OnceLock<SpendThrottle> = OnceLock::new();
+
+/// `spend.prices` for costing calls, whether or not the throttle is on
+static PRICES: OnceLock<SpendConfig> = OnceLock::new();
+
+/// Longest single sleep while waiting, so waits notice refills from the clock
+const POLL: Duration = Duration::from_secs(5);
+
+pub struct SpendThrottle {
+    config: SpendConfig,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    /// Dollars available; negative after charges the bucket couldn't cover
+    level: f64,
+    updated: Instant,
+    /// Charged since startup
+    spent: f64,
+    /// Whether the empty bucket was already logged
+    empty_logged: bool,
+}
+
+/// Shown on `GET /spend`
+#[derive(Debug, Clone, Serialize)]
+pub struct SpendStatus {
+    pub max_usd_per_hour: f64,
+    pub available_usd: f64,
+    pub spent_usd: f64,
+    /// Whether calls are degraded or waiting right now
+    pub throttled: bool,
+}
+
+/// Throttle calls by `config` for the rest of the process
+pub fn activate(config: &SpendConfig) {
+    let _ = PRICES.set(config.clone());
+    if let Some(max) = config.max_usd_per_hour {
+        if THROTTLE.set(SpendThrottle::new(config, Instant::now())).is_ok() {
+            tracing::info!("💸 Spend throttle: ${:.2}/hour (burst ${:.2})", max, config.burst_usd.unwrap_or(max));
+        }
+    }
+}
+
+/// The throttle, when `spend.max_usd_per_hour` is set
+pub fn throttle() -> Option<&'static SpendThrottle> {
+    THROTTLE.get()
+}
+
+/// Dollars an LLM reply cost by the usage in its raw response, at `share` of
+/// list price (0.5 for batches); None when the provider reported no usage
+pub fn llm_cost_usd(provider: &str, model: &str, raw: &serde_json::Value, share: f64) -> Option<f64> {
+    let (prompt, completion) = crate::context_budget::usage(raw)?;
+    // Cache reads cost a tenth of the input price, cache writes a quarter more
+    let (read, written) = crate::context_budget::cache_usage(raw);
+    let prompt = prompt.saturating_sub(read + written) as f64 + read as f64 * 0.1 + written as f64 * 1.25;
+    let price = price(PRICES.get_or_init(SpendConfig::default), provider, model);
+    Some((prompt * price.input + completion as f64 * price.output) * share / 1_000_000.0)
+}
+
+/// Charge an LLM reply by the usage in its raw response, at `share` of list
+/// price (0.5 for batches); nothing when the throttle is off
+pub fn record_llm(provider: &str, model: &str, raw: &serde_json::Value, share: f64) {
+    let Some(throttle) = throttle() else { return };
+    if let Some(cost) = llm_cost_usd(provider, model, raw, share) {
+        throttle.charge(cost);
+    }
+}
+
+/// Dollars per million tokens for `model`: the longest `spend.prices` key the
+/// model id contains, else the built-in table, else Sonnet-class prices
+pub fn price(config: &SpendConfig, provider: &str, model: &str) -> ModelPrice {
+    if provider == "ollama" {
+        return ModelPrice { input: 0.0, output: 0.0 };
+    }
+    let configured = config.prices.iter().filter(|(key, _)| model.contains(key.as_str())).max_by_key(|(key, _)| key.len());
+    if let Some((_, price)) = configured {
+        return *price;
+    }
+    let (input, output) = match model {
+        m if m.contains("opus") => (15.0, 75.0),
+        m if m.contains("claude-3-haiku") => (0.25, 1.25),
+        m if m.contains("haiku") => (0.8, 4.0),
+        m if m.contains("gpt-4o-mini") => (0.15, 0.6),
+        m if m.contains("gpt-4o") => (2.5, 10.0),
+        _ => (3.0, 15.0),
+    };
+    ModelPrice { input, output }
+}
+
+impl SpendThrottle {
+    fn new(config: &SpendConfig, now: Instant) -> Self {
+        let capacity = config.burst_usd.or(config.max_usd_per_hour).unwrap_or_default();
+        Self {
+            config: config.clone(),
+            bucket: Mutex::new(Bucket { level: capacity, updated: now, spent: 0.0, empty_logged: false }),
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        self.config.burst_usd.or(self.config.max_usd_per_hour).unwrap_or_default()
+    }
+
+    fn rate_per_sec(&self) -> f64 {
+        self.config.max_usd_per_hour.unwrap_or_default() / 3600.0
+    }
+
+    /// The bucket level at `now`, after refilling
+    fn level_at(&self, now: Instant) -> f64 {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.level = (bucket.level + elapsed * self.rate_per_sec()).min(self.capacity());
+        bucket.updated = now;
+        if bucket.level > 0.0 {
+            bucket.empty_logged = false;
+        } else if !bucket.empty_logged {
+            bucket.empty_logged = true;
+            tracing::warn!("💸 Spend limit reached (${:.2}/hour); throttling LLM and paid tool calls", self.config.max_usd_per_hour.unwrap_or_default());
+        }
+        bucket.level
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.level_at(Instant::now()) <= 0.0
+    }
+
+    /// How long until the bucket is above zero again
+    fn refill_wait(&self, now: Instant) -> Duration {
+        let level = self.level_at(now);
+        let rate = self.rate_per_sec();
+        if level > 0.0 || rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        // Just past zero, so the next check sees credit
+        Duration::from_secs_f64(-level / rate + 0.5)
+    }
+
+    fn queue_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.queue_timeout_secs)
+    }
+
+    fn over_limit(&self) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Spend limit of ${:.2}/hour reached; try again in a few minutes",
+            self.config.max_usd_per_hour.unwrap_or_default()
+        )
+    }
+
+    /// Charge `usd` to the bucket
+    pub fn charge(&self, usd: f64) {
+        if usd <= 0.0 {
+            return;
+        }
+        self.level_at(Instant::now());
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.level -= usd;
+        bucket.spent += usd;
+    }
+
+    /// Charge an LLM call by its token counts
+    pub fn charge_llm(&self, provider: &str, model: &str, prompt_tokens: u64, completion_tokens: u64) {
+        let price = price(&self.config, provider, model);
+        self.charge((prompt_tokens as f64 * price.input + completion_tokens as f64 * price.output) / 1_000_000.0);
+    }
+
+    /// The cheaper model LLM calls use while the bucket is empty
+    pub fn degrade_to(&self) -> Option<&LlmConfig> {
+        self.config.degrade_to.as_ref()
+    }
+
+    /// Wait until the bucket has credit (LLM calls and paid tools)
+    pub async fn wait(&self) -> anyhow::Result<()> {
+        let deadline = Instant::now() + self.queue_timeout();
+        loop {
+            let wait = self.refill_wait(Instant::now());
+            if wait.is_zero() {
+                return Ok(());
+            }
+            if Instant::now() + wait > deadline {
+                return Err(self.over_limit());
+            }
+            tokio::time::sleep(wait.min(POLL)).await;
+        }
+    }
+
+    pub fn status(&self) -> SpendStatus {
+        let available = self.level_at(Instant::now());
+        SpendStatus {
+            max_usd_per_hour: self.config.max_usd_per_hour.unwrap_or_default(),
+            available_usd: available,
+            spent_usd: self.bucket.lock().unwrap().spent,
+            throttled: available <= 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_empties_and_refills() {
+        let config: SpendConfig = serde_yaml::from_str("max_usd_per_hour: 3.6\nburst_usd: 1.0").unwrap();
+        let start = Instant::now();
+        let throttle = SpendThrottle::new(&config, start);
+        assert_eq!(throttle.refill_wait(start), Duration::ZERO);
+
+        // 1M input tokens on Sonnet: $3, well past the $1 burst
+        throttle.charge_llm("anthropic", "claude-sonnet-4-20250514", 1_000_000, 0);
+        assert!(throttle.level_at(start) < -1.9);
+        // $0.001/s: back above zero after ~2000s
+        let wait = throttle.refill_wait(start);
+        assert!(wait > Duration::from_secs(1990) && wait < Duration::from_secs(2010));
+        assert!(throttle.level_at(start + Duration::from_secs(2100)) > 0.0);
+        assert!(throttle.level_at(start + Duration::from_secs(100_000)) <= 1.0);
+
+        assert_eq!(price(&config, "ollama", "llama3.1").input, 0.0);
+        assert_eq!(price(&config, "anthropic", "claude-3-5-haiku-20241022").output, 4.0);
+    }
+}
@@ -2,12 +2,22 @@
 //! 
 //! Supports:
 //! - SqliteStore: Local SQLite database (free tier, fully private)
+//! - PostgresStore: A Postgres database several nodes share (same schema)
 //! - HostedStore: OneClaw Harness API (paid tier, synced)
+//!
+//! With `store.encrypt_messages`, SqliteStore and PostgresStore seal message
+//! content with AES-256-GCM before writing it, so a copied database doesn't
+//! expose conversations. Rows written before encryption was turned on stay
+//! readable.
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
+use rusqlite::OptionalExtension;
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::Row as _;
 use tokio_rusqlite::Connection;
 
 // ============================================
@@ -41,6 +51,188 @@ pub struct ConversationMessage {
     pub created_at: DateTime<Utc>,
 }
 
+/// What a user message is about (see `tagging.rs`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Intent {
+    LeadGen,
+    Support,
+    Scheduling,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+    Frustrated,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MessageTags {
+    pub intent: Intent,
+    pub sentiment: Sentiment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaggedMessage {
+    #[serde(flatten)]
+    pub message: ConversationMessage,
+    #[serde(flatten)]
+    pub tags: MessageTags,
+}
+
+/// Filter for tagged messages; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagQuery {
+    pub intent: Option<Intent>,
+    pub sentiment: Option<Sentiment>,
+    pub channel: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: usize,
+}
+
+/// A proactive notification put off until `deliver_at` (see `snooze.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snooze {
+    pub id: i64,
+    pub channel: String,          // Channel name to redeliver on ("telegram")
+    pub chat_id: String,
+    pub content: String,
+    pub deliver_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Summary of one past conversation session (episodic memory, see `memory/episodic.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Episode {
+    pub user_id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub summary: String,
+    pub message_count: usize,
+}
+
+/// A submitted Anthropic message batch (see `batch.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmBatch {
+    pub id: String,                // Batch id from the API ("msgbatch_...")
+    pub status: String,            // "in_progress", "ended", "lost"
+    pub requests: serde_json::Value, // custom_id -> what's needed to apply its result
+    pub submitted_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// What one chat turn, tool call or job cost (see `usage.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub kind: String,              // "chat", "tool", "job"
+    pub subject: String,           // Channel, tool id or job id
+    pub user_id: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub estimated_usd: Option<f64>, // Catalog estimate (tools and jobs)
+    pub cost_usd: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How one tool call went (see `tool_insights.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub tool: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,     // What failed calls reported, shortened
+    pub created_at: DateTime<Utc>,
+}
+
+/// A cached embedding: the vector `model` gave for the text hashing to `hash`
+/// (see `embeddings.rs`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingRecord {
+    pub hash: String,
+    pub vector: Vec<f32>,
+}
+
+/// One LLM call as sent and answered, keys redacted (see `llm_audit.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmExchange {
+    pub id: String,
+    /// The chat turn and the job the call was made for
+    pub turn_id: Option<String>,
+    pub job_id: Option<String>,
+    pub phase: String,
+    pub provider: String,
+    pub model: String,
+    pub request: serde_json::Value,
+    /// The provider's response body; None when the call failed
+    pub response: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filter for the LLM audit log; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmAuditQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub turn_id: Option<String>,
+    pub job_id: Option<String>,
+    pub limit: usize,
+}
+
+/// What became of one outgoing message (see `channels/delivery.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: String,
+    pub channel: String,
+    pub chat_id: String,
+    /// queued, retrying, sent, failed or dropped
+    pub status: String,
+    pub attempts: u32,
+    /// The provider's id for the sent message
+    pub provider_message_id: Option<String>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Filter for deliveries; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliveryQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    pub channel: Option<String>,
+    pub limit: usize,
+}
+
+/// A business outreach was drafted for or sent to (see `lead_dedup.rs`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactedLead {
+    pub business: String,
+    /// Website or email domain; None for free mail providers
+    pub domain: Option<String>,
+    pub email: Option<String>,
+    /// The job or named campaign the contact was part of
+    pub campaign: Option<String>,
+    pub action: String,            // "email", "call-script", "voicemail"
+    /// Contacted although it matched an earlier contact: the match
+    pub override_of: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filter for contacted leads; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContactedLeadQuery {
+    pub since: Option<DateTime<Utc>>,
+    /// Only contacts made despite a match
+    pub overridden: bool,
+    pub limit: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Preferences {
     pub user_id: String,
@@ -65,23 +257,241 @@ pub trait Store: Send + Sync {
     
     // Conversation operations
     async fn get_conversation(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<ConversationMessage>>;
-    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>) -> anyhow::Result<i64>;
+    /// Messages of one thread; `None` is the user's messages outside any thread
+    async fn get_thread(&self, user_id: &str, thread_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<ConversationMessage>>;
+    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>, thread_id: Option<&str>) -> anyhow::Result<i64>;
     async fn clear_conversation(&self, user_id: &str) -> anyhow::Result<()>;
     
+    // Compression operations (unthreaded messages only; see `memory::compression`)
+    /// Users with at least `min_messages` messages from before `before`
+    async fn compressible_users(&self, before: DateTime<Utc>, min_messages: usize) -> anyhow::Result<Vec<String>>;
+    /// The user's oldest messages from before `before` and after the message
+    /// `after` (its `created_at` and id), in chronological order
+    async fn messages_before(
+        &self,
+        user_id: &str,
+        before: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConversationMessage>>;
+    /// Replace messages `ids` with one message (and drop their tags), in one go
+    async fn replace_messages(&self, user_id: &str, ids: &[i64], summary: &ConversationMessage) -> anyhow::Result<i64>;
+    
+    // Episode operations (past session summaries; cleared with the conversation)
+    async fn add_episode(&self, episode: &Episode) -> anyhow::Result<()>;
+    async fn recent_episodes(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<Episode>>;
+    
+    // Tagging operations (user messages only)
+    async fn untagged_messages(&self, limit: usize) -> anyhow::Result<Vec<ConversationMessage>>;
+    async fn tag_message(&self, message_id: i64, tags: MessageTags) -> anyhow::Result<()>;
+    async fn tagged_messages(&self, query: &TagQuery) -> anyhow::Result<Vec<TaggedMessage>>;
+    
+    // Snooze operations
+    async fn add_snooze(&self, channel: &str, chat_id: &str, content: &str, deliver_at: DateTime<Utc>) -> anyhow::Result<i64>;
+    async fn due_snoozes(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Snooze>>;
+    async fn remove_snooze(&self, id: i64) -> anyhow::Result<()>;
+    
     // Preferences operations
     async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>>;
     async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()>;
+    
+    // LLM batch operations
+    async fn add_llm_batch(&self, batch: &LlmBatch) -> anyhow::Result<()>;
+    async fn open_llm_batches(&self) -> anyhow::Result<Vec<LlmBatch>>;
+    async fn end_llm_batch(&self, id: &str, status: &str) -> anyhow::Result<()>;
+    
+    // Usage operations
+    async fn add_usage(&self, record: &UsageRecord) -> anyhow::Result<()>;
+    async fn usage_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<UsageRecord>>;
+    
+    // Tool telemetry operations
+    async fn add_tool_call(&self, record: &ToolCallRecord) -> anyhow::Result<()>;
+    async fn tool_calls_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<ToolCallRecord>>;
+    
+    // Embedding cache operations
+    async fn embeddings(&self, model: &str, hashes: &[String]) -> anyhow::Result<Vec<EmbeddingRecord>>;
+    async fn add_embeddings(&self, model: &str, records: &[EmbeddingRecord]) -> anyhow::Result<()>;
+    
+    // LLM audit log operations
+    async fn add_llm_exchange(&self, exchange: &LlmExchange) -> anyhow::Result<()>;
+    async fn llm_exchanges(&self, query: &LlmAuditQuery) -> anyhow::Result<Vec<LlmExchange>>;
+    /// Keep the newest `keep` exchanges, and none from before `before`
+    async fn prune_llm_exchanges(&self, keep: usize, before: DateTime<Utc>) -> anyhow::Result<usize>;
+    
+    // Delivery operations
+    /// Insert or update (by id)
+    async fn save_delivery(&self, delivery: &Delivery) -> anyhow::Result<()>;
+    async fn get_delivery(&self, id: &str) -> anyhow::Result<Option<Delivery>>;
+    async fn deliveries(&self, query: &DeliveryQuery) -> anyhow::Result<Vec<Delivery>>;
+    async fn prune_deliveries(&self, before: DateTime<Utc>) -> anyhow::Result<usize>;
+    
+    // Outreach contact operations (newest first)
+    async fn add_contacted_lead(&self, lead: &ContactedLead) -> anyhow::Result<()>;
+    async fn contacted_leads(&self, query: &ContactedLeadQuery) -> anyhow::Result<Vec<ContactedLead>>;
 }
 
 // ============================================
 // SQLite Store (Local/Private)
 // ============================================
 
+/// Marks an encrypted `content` value: `enc:v1:<nonce>:<ciphertext>` (base64)
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Shown instead of content that can't be decrypted (wrong or rotated key)
+const UNREADABLE_CONTENT: &str = "[encrypted message - cannot decrypt with the current key]";
+
+/// The columns sealed when message encryption is on, as (table, column)
+const SEALED_COLUMNS: &[(&str, &str)] = &[
+    ("conversations", "content"),
+    ("conversations", "tool_calls"),
+    ("snoozes", "content"),
+    ("episodes", "summary"),
+    ("preferences", "data"),
+    ("identities", "username"),
+    ("contacted_leads", "email"),
+    ("llm_exchanges", "request"),
+    ("llm_exchanges", "response"),
+];
+
+/// Message encryption key from the keychain item or the env var named in the
+/// store config
+pub fn content_key(config: &crate::config::StoreConfig) -> anyhow::Result<[u8; 32]> {
+    if let Some(item) = &config.encryption_key_keychain {
+        let value = crate::crypto::keychain_secret(item)
+            .map_err(|e| anyhow::anyhow!("Store encryption key not found in the keychain: {}", e))?;
+        return crate::crypto::decode_key(&value)
+            .map_err(|e| anyhow::anyhow!("Store encryption key in keychain item {} is invalid: {}", item, e));
+    }
+    let value = std::env::var(&config.encryption_key_env).map_err(|_| {
+        anyhow::anyhow!("Store encryption key not found in env: {}", config.encryption_key_env)
+    })?;
+    crate::crypto::decode_key(&value)
+        .map_err(|e| anyhow::anyhow!("Store encryption key in {} is invalid: {}", config.encryption_key_env, e))
+}
+
+/// `content` as stored: sealed when `key` is set
+pub fn seal_with(key: Option<&[u8; 32]>, content: &str) -> anyhow::Result<String> {
+    let Some(key) = key else {
+        return Ok(content.to_string());
+    };
+    let blob = crate::crypto::seal(key, content.as_bytes())?;
+    Ok(format!("{}{}:{}", ENCRYPTED_PREFIX, blob.nonce, blob.ciphertext))
+}
+
+/// Stored content as plain text; content that isn't sealed is returned as is
+pub fn open_with(key: Option<&[u8; 32]>, stored: String) -> String {
+    let Some(sealed) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+        return stored;
+    };
+    let opened = match (key, sealed.split_once(':')) {
+        (Some(key), Some((nonce, ciphertext))) => {
+            let blob = crate::crypto::SealedBlob {
+                salt: String::new(),
+                nonce: nonce.to_string(),
+                ciphertext: ciphertext.to_string(),
+            };
+            crate::crypto::open(key, &blob).ok().and_then(|plain| String::from_utf8(plain).ok())
+        }
+        _ => None,
+    };
+    opened.unwrap_or_else(|| {
+        tracing::warn!("Could not decrypt a stored message (is the store encryption key set?)");
+        UNREADABLE_CONTENT.to_string()
+    })
+}
+
 pub struct SqliteStore {
     conn: Connection,
+    /// Seals message content when set
+    content_key: Option<[u8; 32]>,
 }
 
 impl SqliteStore {
+    /// Encrypt message content written from now on
+    pub fn with_content_key(mut self, key: [u8; 32]) -> Self {
+        self.content_key = Some(key);
+        self
+    }
+
+    fn seal_content(&self, content: &str) -> anyhow::Result<String> {
+        seal_with(self.content_key.as_ref(), content)
+    }
+
+    fn open_content(&self, stored: String) -> String {
+        open_with(self.content_key.as_ref(), stored)
+    }
+
+    fn open_messages(&self, messages: Vec<ConversationMessage>) -> Vec<ConversationMessage> {
+        messages
+            .into_iter()
+            .map(|m| {
+                let content = self.open_content(m.content);
+                let tool_calls = m.tool_calls.map(|calls| self.open_content(calls));
+                ConversationMessage { content, tool_calls, ..m }
+            })
+            .collect()
+    }
+
+    fn open_identity(&self, identity: Identity) -> Identity {
+        let username = identity.username.map(|name| self.open_content(name));
+        Identity { username, ..identity }
+    }
+
+    fn open_contacted_lead(&self, lead: ContactedLead) -> ContactedLead {
+        let email = lead.email.map(|email| self.open_content(email));
+        ContactedLead { email, ..lead }
+    }
+
+    /// Seal the sensitive values written before encryption was turned on, then
+    /// rebuild the file so no plaintext is left in its free pages. Returns how
+    /// many values were sealed.
+    pub async fn seal_existing(&self) -> anyhow::Result<usize> {
+        if self.content_key.is_none() {
+            anyhow::bail!("No store encryption key set");
+        }
+        let mut sealed = 0;
+        for (table, column) in SEALED_COLUMNS {
+            let select = format!(
+                "SELECT rowid, {col} FROM {table} WHERE {col} IS NOT NULL AND {col} NOT LIKE '{prefix}%'",
+                col = column,
+                table = table,
+                prefix = ENCRYPTED_PREFIX,
+            );
+            let plain: Vec<(i64, String)> = self.conn.call(move |conn| {
+                let mut stmt = conn.prepare(&select)?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                Ok(rows.collect::<Result<Vec<_>, _>>()?)
+            }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+            if plain.is_empty() {
+                continue;
+            }
+            
+            let rows = plain
+                .into_iter()
+                .map(|(rowid, value)| Ok((rowid, self.seal_content(&value)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            sealed += rows.len();
+            let update = format!("UPDATE {} SET {} = ? WHERE rowid = ?", table, column);
+            self.conn.call(move |conn| {
+                let tx = conn.transaction()?;
+                {
+                    let mut stmt = tx.prepare(&update)?;
+                    for (rowid, value) in &rows {
+                        stmt.execute(rusqlite::params![value, rowid])?;
+                    }
+                }
+                tx.commit()?;
+                Ok(())
+            }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        }
+        
+        self.conn.call(|conn| {
+            conn.execute_batch("VACUUM")?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(sealed)
+    }
+
     pub async fn new(path: PathBuf) -> anyhow::Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
@@ -119,22 +529,161 @@ impl SqliteStore {
                     channel TEXT NOT NULL,
                     tool_calls TEXT,
                     created_at TEXT NOT NULL,
+                    thread_id TEXT,
                     FOREIGN KEY (user_id) REFERENCES users(id)
                 );
                 
                 CREATE INDEX IF NOT EXISTS idx_conversations_user ON conversations(user_id, created_at DESC);
                 
+                CREATE TABLE IF NOT EXISTS message_tags (
+                    message_id INTEGER PRIMARY KEY,
+                    intent TEXT NOT NULL,
+                    sentiment TEXT NOT NULL,
+                    tagged_at TEXT NOT NULL,
+                    FOREIGN KEY (message_id) REFERENCES conversations(id)
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_message_tags_sentiment ON message_tags(sentiment, intent);
+                
+                CREATE TABLE IF NOT EXISTS snoozes (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    channel TEXT NOT NULL,
+                    chat_id TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    deliver_at TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_snoozes_due ON snoozes(deliver_at);
+                
                 CREATE TABLE IF NOT EXISTS preferences (
                     user_id TEXT PRIMARY KEY,
                     data TEXT NOT NULL,
                     updated_at TEXT NOT NULL,
                     FOREIGN KEY (user_id) REFERENCES users(id)
                 );
+                
+                CREATE TABLE IF NOT EXISTS episodes (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    user_id TEXT NOT NULL,
+                    started_at TEXT NOT NULL,
+                    ended_at TEXT NOT NULL,
+                    summary TEXT NOT NULL,
+                    message_count INTEGER NOT NULL,
+                    UNIQUE (user_id, started_at),
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_episodes_user ON episodes(user_id, ended_at DESC);
+                
+                CREATE TABLE IF NOT EXISTS llm_batches (
+                    id TEXT PRIMARY KEY,
+                    status TEXT NOT NULL,
+                    requests TEXT NOT NULL,
+                    submitted_at TEXT NOT NULL,
+                    ended_at TEXT
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_llm_batches_status ON llm_batches(status);
+                
+                CREATE TABLE IF NOT EXISTS usage (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    kind TEXT NOT NULL,
+                    subject TEXT NOT NULL,
+                    user_id TEXT,
+                    prompt_tokens INTEGER NOT NULL,
+                    completion_tokens INTEGER NOT NULL,
+                    estimated_usd REAL,
+                    cost_usd REAL NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_usage_created ON usage(created_at);
+                
+                CREATE TABLE IF NOT EXISTS tool_calls (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    tool TEXT NOT NULL,
+                    success INTEGER NOT NULL,
+                    duration_ms INTEGER NOT NULL,
+                    error TEXT,
+                    created_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_tool_calls_created ON tool_calls(created_at);
+                
+                CREATE TABLE IF NOT EXISTS embeddings (
+                    model TEXT NOT NULL,
+                    hash TEXT NOT NULL,
+                    dimensions INTEGER NOT NULL,
+                    vector BLOB NOT NULL,
+                    created_at TEXT NOT NULL,
+                    PRIMARY KEY (model, hash)
+                );
+                
+                CREATE TABLE IF NOT EXISTS llm_exchanges (
+                    id TEXT PRIMARY KEY,
+                    turn_id TEXT,
+                    job_id TEXT,
+                    phase TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    request TEXT NOT NULL,
+                    response TEXT,
+                    error TEXT,
+                    duration_ms INTEGER NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_llm_exchanges_created ON llm_exchanges(created_at);
+                CREATE INDEX IF NOT EXISTS idx_llm_exchanges_turn ON llm_exchanges(turn_id);
+                CREATE INDEX IF NOT EXISTS idx_llm_exchanges_job ON llm_exchanges(job_id);
+                
+                CREATE TABLE IF NOT EXISTS deliveries (
+                    id TEXT PRIMARY KEY,
+                    channel TEXT NOT NULL,
+                    chat_id TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    attempts INTEGER NOT NULL,
+                    provider_message_id TEXT,
+                    error TEXT,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_deliveries_created ON deliveries(created_at);
+                CREATE INDEX IF NOT EXISTS idx_deliveries_status ON deliveries(status);
+                
+                CREATE TABLE IF NOT EXISTS contacted_leads (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    business TEXT NOT NULL,
+                    domain TEXT,
+                    email TEXT,
+                    campaign TEXT,
+                    action TEXT NOT NULL,
+                    override_of TEXT,
+                    created_at TEXT NOT NULL
+                );
+                
+                CREATE INDEX IF NOT EXISTS idx_contacted_leads_created ON contacted_leads(created_at);
             "#)?;
+            
+            // Databases from before threads were tracked lack the column
+            let has_thread: bool = conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('conversations') WHERE name = 'thread_id'",
+                [],
+                |row| row.get::<_, i64>(0).map(|n| n > 0),
+            )?;
+            if !has_thread {
+                conn.execute("ALTER TABLE conversations ADD COLUMN thread_id TEXT", [])?;
+            }
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_conversations_thread ON conversations(user_id, thread_id, created_at DESC)",
+                [],
+            )?;
             Ok(())
         }).await?;
         
-        Ok(Self { conn })
+        Ok(Self { conn, content_key: None })
     }
 }
 
@@ -180,7 +729,7 @@ impl Store for SqliteStore {
         let provider = provider.to_string();
         let provider_id = provider_id.to_string();
         
-        self.conn.call(move |conn| {
+        let identity = self.conn.call(move |conn| {
             let mut stmt = conn.prepare(
                 "SELECT user_id, provider, provider_id, username, linked_at FROM identities WHERE provider = ? AND provider_id = ?"
             )?;
@@ -197,14 +746,15 @@ impl Store for SqliteStore {
             } else {
                 Ok(None)
             }
-        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(identity.map(|identity| self.open_identity(identity)))
     }
     
     async fn link_identity(&self, user_id: &str, provider: &str, provider_id: &str, username: Option<&str>) -> anyhow::Result<()> {
         let user_id = user_id.to_string();
         let provider = provider.to_string();
         let provider_id = provider_id.to_string();
-        let username = username.map(|s| s.to_string());
+        let username = username.map(|name| self.seal_content(name)).transpose()?;
         let now = Utc::now().to_rfc3339();
         
         self.conn.call(move |conn| {
@@ -219,7 +769,7 @@ impl Store for SqliteStore {
     async fn get_user_identities(&self, user_id: &str) -> anyhow::Result<Vec<Identity>> {
         let user_id = user_id.to_string();
         
-        self.conn.call(move |conn| {
+        let identities = self.conn.call(move |conn| {
             let mut stmt = conn.prepare(
                 "SELECT user_id, provider, provider_id, username, linked_at FROM identities WHERE user_id = ?"
             )?;
@@ -238,13 +788,14 @@ impl Store for SqliteStore {
                 identities.push(row?);
             }
             Ok(identities)
-        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(identities.into_iter().map(|identity| self.open_identity(identity)).collect())
     }
     
     async fn get_conversation(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
         let user_id = user_id.to_string();
         
-        self.conn.call(move |conn| {
+        let messages = self.conn.call(move |conn| {
             let mut stmt = conn.prepare(
                 "SELECT id, user_id, role, content, channel, tool_calls, created_at 
                  FROM conversations 
@@ -252,17 +803,7 @@ impl Store for SqliteStore {
                  ORDER BY created_at DESC 
                  LIMIT ?"
             )?;
-            let rows = stmt.query_map(rusqlite::params![user_id, limit], |row| {
-                Ok(ConversationMessage {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    role: row.get(2)?,
-                    content: row.get(3)?,
-                    channel: row.get(4)?,
-                    tool_calls: row.get(5)?,
-                    created_at: row.get::<_, String>(6)?.parse().unwrap_or_else(|_| Utc::now()),
-                })
-            })?;
+            let rows = stmt.query_map(rusqlite::params![user_id, limit], conversation_row)?;
             
             let mut messages: Vec<ConversationMessage> = Vec::new();
             for row in rows {
@@ -271,21 +812,47 @@ impl Store for SqliteStore {
             // Reverse to get chronological order
             messages.reverse();
             Ok(messages)
-        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(self.open_messages(messages))
     }
     
-    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>) -> anyhow::Result<i64> {
+    async fn get_thread(&self, user_id: &str, thread_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let user_id = user_id.to_string();
+        let thread_id = thread_id.map(str::to_string);
+        
+        let messages = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, user_id, role, content, channel, tool_calls, created_at 
+                 FROM conversations 
+                 WHERE user_id = ? AND thread_id IS ? 
+                 ORDER BY created_at DESC 
+                 LIMIT ?"
+            )?;
+            let rows = stmt.query_map(rusqlite::params![user_id, thread_id, limit], conversation_row)?;
+            
+            let mut messages: Vec<ConversationMessage> = Vec::new();
+            for row in rows {
+                messages.push(row?);
+            }
+            messages.reverse();
+            Ok(messages)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>, thread_id: Option<&str>) -> anyhow::Result<i64> {
         let user_id = user_id.to_string();
         let role = role.to_string();
-        let content = content.to_string();
+        let content = self.seal_content(content)?;
         let channel = channel.to_string();
-        let tool_calls = tool_calls.map(|s| s.to_string());
+        let tool_calls = tool_calls.map(|calls| self.seal_content(calls)).transpose()?;
+        let thread_id = thread_id.map(str::to_string);
         let now = Utc::now().to_rfc3339();
         
         self.conn.call(move |conn| {
             conn.execute(
-                "INSERT INTO conversations (user_id, role, content, channel, tool_calls, created_at) VALUES (?, ?, ?, ?, ?, ?)",
-                rusqlite::params![user_id, role, content, channel, tool_calls, now],
+                "INSERT INTO conversations (user_id, role, content, channel, tool_calls, created_at, thread_id) VALUES (?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![user_id, role, content, channel, tool_calls, now, thread_id],
             )?;
             Ok(conn.last_insert_rowid())
         }).await.map_err(|e| anyhow::anyhow!("{}", e))
@@ -295,100 +862,2067 @@ impl Store for SqliteStore {
         let user_id = user_id.to_string();
         
         self.conn.call(move |conn| {
+            conn.execute(
+                "DELETE FROM message_tags WHERE message_id IN (SELECT id FROM conversations WHERE user_id = ?)",
+                [&user_id],
+            )?;
             conn.execute("DELETE FROM conversations WHERE user_id = ?", [&user_id])?;
+            conn.execute("DELETE FROM episodes WHERE user_id = ?", [&user_id])?;
             Ok(())
         }).await.map_err(|e| anyhow::anyhow!("{}", e))
     }
     
-    async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>> {
+    async fn compressible_users(&self, before: DateTime<Utc>, min_messages: usize) -> anyhow::Result<Vec<String>> {
+        let before = before.to_rfc3339();
+        
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT user_id 
+                 FROM conversations 
+                 WHERE created_at < ? AND thread_id IS NULL 
+                 GROUP BY user_id 
+                 HAVING COUNT(*) >= ?"
+            )?;
+            let rows = stmt.query_map(rusqlite::params![before, min_messages as i64], |row| row.get(0))?;
+            Ok(rows.collect::<Result<Vec<String>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn messages_before(
+        &self,
+        user_id: &str,
+        before: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConversationMessage>> {
+        let user_id = user_id.to_string();
+        let before = before.to_rfc3339();
+        let (after, after_id) = after.unwrap_or_default();
+        let after = after.to_rfc3339();
+        
+        let messages = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, user_id, role, content, channel, tool_calls, created_at 
+                 FROM conversations 
+                 WHERE user_id = ? AND created_at < ? AND thread_id IS NULL 
+                   AND (created_at > ? OR (created_at = ? AND id > ?)) 
+                 ORDER BY created_at, id 
+                 LIMIT ?"
+            )?;
+            let rows = stmt.query_map(rusqlite::params![user_id, before, after, after, after_id, limit], conversation_row)?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn replace_messages(&self, user_id: &str, ids: &[i64], summary: &ConversationMessage) -> anyhow::Result<i64> {
         let user_id = user_id.to_string();
+        let ids = ids.to_vec();
+        let content = self.seal_content(&summary.content)?;
+        let role = summary.role.clone();
+        let channel = summary.channel.clone();
+        let created_at = summary.created_at.to_rfc3339();
         
         self.conn.call(move |conn| {
-            let mut stmt = conn.prepare("SELECT user_id, data, updated_at FROM preferences WHERE user_id = ?")?;
-            let mut rows = stmt.query([&user_id])?;
-            
-            if let Some(row) = rows.next()? {
-                let data_str: String = row.get(1)?;
-                let data: serde_json::Value = serde_json::from_str(&data_str).unwrap_or(serde_json::json!({}));
-                Ok(Some(Preferences {
-                    user_id: row.get(0)?,
-                    data,
-                    updated_at: row.get::<_, String>(2)?.parse().unwrap_or_else(|_| Utc::now()),
-                }))
-            } else {
-                Ok(None)
+            let tx = conn.transaction()?;
+            for id in &ids {
+                tx.execute("DELETE FROM message_tags WHERE message_id = ?", [id])?;
+                tx.execute("DELETE FROM conversations WHERE id = ? AND user_id = ?", rusqlite::params![id, user_id])?;
             }
+            tx.execute(
+                "INSERT INTO conversations (user_id, role, content, channel, tool_calls, created_at, thread_id) VALUES (?, ?, ?, ?, NULL, ?, NULL)",
+                rusqlite::params![user_id, role, content, channel, created_at],
+            )?;
+            let id = tx.last_insert_rowid();
+            tx.commit()?;
+            Ok(id)
         }).await.map_err(|e| anyhow::anyhow!("{}", e))
     }
     
-    async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()> {
-        let user_id = user_id.to_string();
-        let data_str = serde_json::to_string(&data)?;
-        let now = Utc::now().to_rfc3339();
+    async fn add_episode(&self, episode: &Episode) -> anyhow::Result<()> {
+        let episode = Episode { summary: self.seal_content(&episode.summary)?, ..episode.clone() };
         
         self.conn.call(move |conn| {
+            // A session is summarized once, even if two turns race to close it
             conn.execute(
-                "INSERT OR REPLACE INTO preferences (user_id, data, updated_at) VALUES (?, ?, ?)",
-                [&user_id, &data_str, &now],
+                "INSERT OR IGNORE INTO episodes (user_id, started_at, ended_at, summary, message_count) VALUES (?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    episode.user_id,
+                    episode.started_at.to_rfc3339(),
+                    episode.ended_at.to_rfc3339(),
+                    episode.summary,
+                    episode.message_count as i64,
+                ],
             )?;
             Ok(())
         }).await.map_err(|e| anyhow::anyhow!("{}", e))
     }
-}
-
-// ============================================
-// Hosted Store (Harness API)
-// ============================================
-
-pub struct HostedStore {
-    api_url: String,
-    token: String,
-    client: reqwest::Client,
-}
-
-impl HostedStore {
-    pub fn new(api_url: String, token: String) -> Self {
-        Self {
-            api_url,
-            token,
-            client: reqwest::Client::new(),
-        }
-    }
-}
-
-#[async_trait]
-impl Store for HostedStore {
-    async fn get_user(&self, user_id: &str) -> anyhow::Result<Option<User>> {
-        let resp = self.client
-            .get(format!("{}/api/v1/users/{}", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await?;
-        
-        if resp.status() == 404 {
-            return Ok(None);
-        }
+    
+    async fn recent_episodes(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<Episode>> {
+        let user_id = user_id.to_string();
         
-        let user: User = resp.json().await?;
-        Ok(Some(user))
+        let episodes = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT user_id, started_at, ended_at, summary, message_count 
+                 FROM episodes 
+                 WHERE user_id = ? 
+                 ORDER BY ended_at DESC 
+                 LIMIT ?"
+            )?;
+            let rows = stmt.query_map(rusqlite::params![user_id, limit as i64], |row| {
+                Ok(Episode {
+                    user_id: row.get(0)?,
+                    started_at: row.get::<_, String>(1)?.parse().unwrap_or_else(|_| Utc::now()),
+                    ended_at: row.get::<_, String>(2)?.parse().unwrap_or_else(|_| Utc::now()),
+                    summary: row.get(3)?,
+                    message_count: row.get::<_, i64>(4)? as usize,
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(episodes
+            .into_iter()
+            .map(|episode| {
+                let summary = self.open_content(episode.summary);
+                Episode { summary, ..episode }
+            })
+            .collect())
     }
     
-    async fn create_user(&self, user_id: &str) -> anyhow::Result<User> {
-        let resp = self.client
-            .post(format!("{}/api/v1/users", self.api_url))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&serde_json::json!({ "id": user_id }))
-            .send()
-            .await?;
+    async fn untagged_messages(&self, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let messages = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT c.id, c.user_id, c.role, c.content, c.channel, c.tool_calls, c.created_at 
+                 FROM conversations c 
+                 LEFT JOIN message_tags t ON t.message_id = c.id 
+                 WHERE c.role = 'user' AND t.message_id IS NULL 
+                 ORDER BY c.id 
+                 LIMIT ?"
+            )?;
+            let rows = stmt.query_map([limit], conversation_row)?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn tag_message(&self, message_id: i64, tags: MessageTags) -> anyhow::Result<()> {
+        let intent = tag_str(tags.intent);
+        let sentiment = tag_str(tags.sentiment);
+        let now = Utc::now().to_rfc3339();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO message_tags (message_id, intent, sentiment, tagged_at) VALUES (?, ?, ?, ?)",
+                rusqlite::params![message_id, intent, sentiment, now],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn tagged_messages(&self, query: &TagQuery) -> anyhow::Result<Vec<TaggedMessage>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(intent) = query.intent {
+            filters.push("t.intent = ?");
+            params.push(tag_str(intent));
+        }
+        if let Some(sentiment) = query.sentiment {
+            filters.push("t.sentiment = ?");
+            params.push(tag_str(sentiment));
+        }
+        if let Some(channel) = &query.channel {
+            filters.push("c.channel = ?");
+            params.push(channel.clone());
+        }
+        if let Some(since) = query.since {
+            filters.push("c.created_at >= ?");
+            params.push(since.to_rfc3339());
+        }
+        let sql = format!(
+            "SELECT c.id, c.user_id, c.role, c.content, c.channel, c.tool_calls, c.created_at, t.intent, t.sentiment 
+             FROM conversations c 
+             JOIN message_tags t ON t.message_id = c.id 
+             {} 
+             ORDER BY c.created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let messages: Vec<TaggedMessage> = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                Ok((conversation_row(row)?, row.get::<_, String>(7)?, row.get::<_, String>(8)?))
+            })?;
+            
+            let mut messages = vec![];
+            for row in rows {
+                let (message, intent, sentiment) = row?;
+                // Skip rows tagged with values this build doesn't know
+                if let (Some(intent), Some(sentiment)) = (parse_tag(&intent), parse_tag(&sentiment)) {
+                    messages.push(TaggedMessage { message, tags: MessageTags { intent, sentiment } });
+                }
+            }
+            Ok(messages)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(messages
+            .into_iter()
+            .map(|t| {
+                let content = self.open_content(t.message.content);
+                TaggedMessage { message: ConversationMessage { content, ..t.message }, tags: t.tags }
+            })
+            .collect())
+    }
+    
+    async fn add_snooze(&self, channel: &str, chat_id: &str, content: &str, deliver_at: DateTime<Utc>) -> anyhow::Result<i64> {
+        let channel = channel.to_string();
+        let chat_id = chat_id.to_string();
+        let content = self.seal_content(content)?;
+        let now = Utc::now().to_rfc3339();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO snoozes (channel, chat_id, content, deliver_at, created_at) VALUES (?, ?, ?, ?, ?)",
+                rusqlite::params![channel, chat_id, content, deliver_at.to_rfc3339(), now],
+            )?;
+            Ok(conn.last_insert_rowid())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn due_snoozes(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Snooze>> {
+        let snoozes = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, channel, chat_id, content, deliver_at, created_at 
+                 FROM snoozes 
+                 WHERE deliver_at <= ? 
+                 ORDER BY deliver_at"
+            )?;
+            let rows = stmt.query_map([now.to_rfc3339()], |row| {
+                Ok(Snooze {
+                    id: row.get(0)?,
+                    channel: row.get(1)?,
+                    chat_id: row.get(2)?,
+                    content: row.get(3)?,
+                    deliver_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
+                    created_at: row.get::<_, String>(5)?.parse().unwrap_or_else(|_| Utc::now()),
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(snoozes
+            .into_iter()
+            .map(|snooze| {
+                let content = self.open_content(snooze.content);
+                Snooze { content, ..snooze }
+            })
+            .collect())
+    }
+    
+    async fn remove_snooze(&self, id: i64) -> anyhow::Result<()> {
+        self.conn.call(move |conn| {
+            conn.execute("DELETE FROM snoozes WHERE id = ?", [id])?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>> {
+        let user_id = user_id.to_string();
+        
+        let row: Option<(String, String, String)> = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT user_id, data, updated_at FROM preferences WHERE user_id = ?")?;
+            let mut rows = stmt.query([&user_id])?;
+            
+            if let Some(row) = rows.next()? {
+                Ok(Some((row.get(0)?, row.get(1)?, row.get(2)?)))
+            } else {
+                Ok(None)
+            }
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(row.map(|(user_id, data, updated_at)| Preferences {
+            user_id,
+            data: serde_json::from_str(&self.open_content(data)).unwrap_or(serde_json::json!({})),
+            updated_at: updated_at.parse().unwrap_or_else(|_| Utc::now()),
+        }))
+    }
+    
+    async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()> {
+        let user_id = user_id.to_string();
+        let data_str = self.seal_content(&serde_json::to_string(&data)?)?;
+        let now = Utc::now().to_rfc3339();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO preferences (user_id, data, updated_at) VALUES (?, ?, ?)",
+                [&user_id, &data_str, &now],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_llm_batch(&self, batch: &LlmBatch) -> anyhow::Result<()> {
+        let batch = batch.clone();
+        let requests = serde_json::to_string(&batch.requests)?;
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO llm_batches (id, status, requests, submitted_at, ended_at) VALUES (?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    batch.id,
+                    batch.status,
+                    requests,
+                    batch.submitted_at.to_rfc3339(),
+                    batch.ended_at.map(|at| at.to_rfc3339()),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn open_llm_batches(&self) -> anyhow::Result<Vec<LlmBatch>> {
+        self.conn.call(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, status, requests, submitted_at, ended_at 
+                 FROM llm_batches 
+                 WHERE ended_at IS NULL 
+                 ORDER BY submitted_at"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(LlmBatch {
+                    id: row.get(0)?,
+                    status: row.get(1)?,
+                    requests: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or(serde_json::json!({})),
+                    submitted_at: row.get::<_, String>(3)?.parse().unwrap_or_else(|_| Utc::now()),
+                    ended_at: row.get::<_, Option<String>>(4)?.and_then(|at| at.parse().ok()),
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn end_llm_batch(&self, id: &str, status: &str) -> anyhow::Result<()> {
+        let id = id.to_string();
+        let status = status.to_string();
+        let now = Utc::now().to_rfc3339();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "UPDATE llm_batches SET status = ?, ended_at = ? WHERE id = ?",
+                [&status, &now, &id],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_usage(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        let record = record.clone();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO usage (kind, subject, user_id, prompt_tokens, completion_tokens, estimated_usd, cost_usd, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    record.kind,
+                    record.subject,
+                    record.user_id,
+                    record.prompt_tokens as i64,
+                    record.completion_tokens as i64,
+                    record.estimated_usd,
+                    record.cost_usd,
+                    record.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn usage_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<UsageRecord>> {
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT kind, subject, user_id, prompt_tokens, completion_tokens, estimated_usd, cost_usd, created_at 
+                 FROM usage 
+                 WHERE created_at >= ? 
+                 ORDER BY created_at"
+            )?;
+            let rows = stmt.query_map([since.to_rfc3339()], |row| {
+                Ok(UsageRecord {
+                    kind: row.get(0)?,
+                    subject: row.get(1)?,
+                    user_id: row.get(2)?,
+                    prompt_tokens: row.get::<_, i64>(3)? as u64,
+                    completion_tokens: row.get::<_, i64>(4)? as u64,
+                    estimated_usd: row.get(5)?,
+                    cost_usd: row.get(6)?,
+                    created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_tool_call(&self, record: &ToolCallRecord) -> anyhow::Result<()> {
+        let record = record.clone();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO tool_calls (tool, success, duration_ms, error, created_at) VALUES (?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    record.tool,
+                    record.success,
+                    record.duration_ms as i64,
+                    record.error,
+                    record.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn tool_calls_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<ToolCallRecord>> {
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT tool, success, duration_ms, error, created_at 
+                 FROM tool_calls 
+                 WHERE created_at >= ? 
+                 ORDER BY created_at"
+            )?;
+            let rows = stmt.query_map([since.to_rfc3339()], |row| {
+                Ok(ToolCallRecord {
+                    tool: row.get(0)?,
+                    success: row.get(1)?,
+                    duration_ms: row.get::<_, i64>(2)? as u64,
+                    error: row.get(3)?,
+                    created_at: row.get::<_, String>(4)?.parse().unwrap_or_else(|_| Utc::now()),
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn embeddings(&self, model: &str, hashes: &[String]) -> anyhow::Result<Vec<EmbeddingRecord>> {
+        let model = model.to_string();
+        let hashes = hashes.to_vec();
+        
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare("SELECT vector FROM embeddings WHERE model = ? AND hash = ?")?;
+            let mut found = Vec::new();
+            for hash in hashes {
+                let vector: Option<Vec<u8>> = stmt
+                    .query_row(rusqlite::params![model, hash], |row| row.get(0))
+                    .optional()?;
+                if let Some(vector) = vector {
+                    found.push(EmbeddingRecord { hash, vector: crate::embeddings::decode(&vector) });
+                }
+            }
+            Ok(found)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_embeddings(&self, model: &str, records: &[EmbeddingRecord]) -> anyhow::Result<()> {
+        let model = model.to_string();
+        let records = records.to_vec();
+        
+        self.conn.call(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR REPLACE INTO embeddings (model, hash, dimensions, vector, created_at) VALUES (?, ?, ?, ?, ?)"
+                )?;
+                let now = Utc::now().to_rfc3339();
+                for record in &records {
+                    stmt.execute(rusqlite::params![
+                        model,
+                        record.hash,
+                        record.vector.len() as i64,
+                        crate::embeddings::encode(&record.vector),
+                        now,
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_llm_exchange(&self, exchange: &LlmExchange) -> anyhow::Result<()> {
+        let request = self.seal_content(&exchange.request.to_string())?;
+        let response = exchange.response.as_ref().map(|r| self.seal_content(&r.to_string())).transpose()?;
+        let exchange = exchange.clone();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO llm_exchanges (id, turn_id, job_id, phase, provider, model, request, response, error, duration_ms, created_at) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    exchange.id,
+                    exchange.turn_id,
+                    exchange.job_id,
+                    exchange.phase,
+                    exchange.provider,
+                    exchange.model,
+                    request,
+                    response,
+                    exchange.error,
+                    exchange.duration_ms as i64,
+                    exchange.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn llm_exchanges(&self, query: &LlmAuditQuery) -> anyhow::Result<Vec<LlmExchange>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(since) = query.since {
+            filters.push("created_at >= ?");
+            params.push(since.to_rfc3339());
+        }
+        if let Some(turn_id) = &query.turn_id {
+            filters.push("turn_id = ?");
+            params.push(turn_id.clone());
+        }
+        if let Some(job_id) = &query.job_id {
+            filters.push("job_id = ?");
+            params.push(job_id.clone());
+        }
+        let sql = format!(
+            "SELECT id, turn_id, job_id, phase, provider, model, request, response, error, duration_ms, created_at 
+             FROM llm_exchanges 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let rows: Vec<(LlmExchange, String, Option<String>)> = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                let exchange = LlmExchange {
+                    id: row.get(0)?,
+                    turn_id: row.get(1)?,
+                    job_id: row.get(2)?,
+                    phase: row.get(3)?,
+                    provider: row.get(4)?,
+                    model: row.get(5)?,
+                    request: serde_json::Value::Null,
+                    response: None,
+                    error: row.get(8)?,
+                    duration_ms: row.get::<_, i64>(9)? as u64,
+                    created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
+                };
+                Ok((exchange, row.get(6)?, row.get(7)?))
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        
+        // Unreadable (sealed with another key) bodies come back as the notice text
+        let parse = |stored: String| {
+            let opened = self.open_content(stored);
+            serde_json::from_str(&opened).unwrap_or(serde_json::Value::String(opened))
+        };
+        Ok(rows
+            .into_iter()
+            .map(|(exchange, request, response)| LlmExchange {
+                request: parse(request),
+                response: response.map(parse),
+                ..exchange
+            })
+            .collect())
+    }
+    
+    async fn prune_llm_exchanges(&self, keep: usize, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        self.conn.call(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM llm_exchanges 
+                 WHERE created_at < ? 
+                    OR id NOT IN (SELECT id FROM llm_exchanges ORDER BY created_at DESC LIMIT ?)",
+                rusqlite::params![before.to_rfc3339(), keep as i64],
+            )?;
+            Ok(removed)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }    
+    async fn save_delivery(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        let delivery = delivery.clone();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO deliveries (id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) 
+                 ON CONFLICT(id) DO UPDATE SET 
+                    status = excluded.status, 
+                    attempts = excluded.attempts, 
+                    provider_message_id = excluded.provider_message_id, 
+                    error = excluded.error, 
+                    updated_at = excluded.updated_at",
+                rusqlite::params![
+                    delivery.id,
+                    delivery.channel,
+                    delivery.chat_id,
+                    delivery.status,
+                    delivery.attempts,
+                    delivery.provider_message_id,
+                    delivery.error,
+                    delivery.created_at.to_rfc3339(),
+                    delivery.updated_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn get_delivery(&self, id: &str) -> anyhow::Result<Option<Delivery>> {
+        let id = id.to_string();
+        
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at 
+                 FROM deliveries WHERE id = ?"
+            )?;
+            let mut rows = stmt.query_map([id], delivery_row)?;
+            Ok(rows.next().transpose()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn deliveries(&self, query: &DeliveryQuery) -> anyhow::Result<Vec<Delivery>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(since) = query.since {
+            filters.push("created_at >= ?");
+            params.push(since.to_rfc3339());
+        }
+        if let Some(status) = &query.status {
+            filters.push("status = ?");
+            params.push(status.clone());
+        }
+        if let Some(channel) = &query.channel {
+            filters.push("channel = ?");
+            params.push(channel.clone());
+        }
+        let sql = format!(
+            "SELECT id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at 
+             FROM deliveries 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), delivery_row)?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn prune_deliveries(&self, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        self.conn.call(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM deliveries WHERE created_at < ?",
+                [before.to_rfc3339()],
+            )?;
+            Ok(removed)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn add_contacted_lead(&self, lead: &ContactedLead) -> anyhow::Result<()> {
+        let email = lead.email.as_deref().map(|e| self.seal_content(e)).transpose()?;
+        let lead = lead.clone();
+        
+        self.conn.call(move |conn| {
+            conn.execute(
+                "INSERT INTO contacted_leads (business, domain, email, campaign, action, override_of, created_at) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    lead.business,
+                    lead.domain,
+                    email,
+                    lead.campaign,
+                    lead.action,
+                    lead.override_of,
+                    lead.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(())
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+    
+    async fn contacted_leads(&self, query: &ContactedLeadQuery) -> anyhow::Result<Vec<ContactedLead>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(since) = query.since {
+            filters.push("created_at >= ?");
+            params.push(since.to_rfc3339());
+        }
+        if query.overridden {
+            filters.push("override_of IS NOT NULL");
+        }
+        let sql = format!(
+            "SELECT business, domain, email, campaign, action, override_of, created_at 
+             FROM contacted_leads 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let leads = self.conn.call(move |conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                Ok(ContactedLead {
+                    business: row.get(0)?,
+                    domain: row.get(1)?,
+                    email: row.get(2)?,
+                    campaign: row.get(3)?,
+                    action: row.get(4)?,
+                    override_of: row.get(5)?,
+                    created_at: row.get::<_, String>(6)?.parse().unwrap_or_else(|_| Utc::now()),
+                })
+            })?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        }).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+        
+        Ok(leads.into_iter().map(|lead| self.open_contacted_lead(lead)).collect())
+    }
+}
+
+/// `deliveries` row in column order id, channel, chat_id, status, attempts,
+/// provider_message_id, error, created_at, updated_at
+fn delivery_row(row: &rusqlite::Row) -> rusqlite::Result<Delivery> {
+    Ok(Delivery {
+        id: row.get(0)?,
+        channel: row.get(1)?,
+        chat_id: row.get(2)?,
+        status: row.get(3)?,
+        attempts: row.get(4)?,
+        provider_message_id: row.get(5)?,
+        error: row.get(6)?,
+        created_at: row.get::<_, String>(7)?.parse().unwrap_or_else(|_| Utc::now()),
+        updated_at: row.get::<_, String>(8)?.parse().unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// `conversations` row in column order id, user_id, role, content, channel, tool_calls, created_at
+fn conversation_row(row: &rusqlite::Row) -> rusqlite::Result<ConversationMessage> {
+    Ok(ConversationMessage {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        channel: row.get(4)?,
+        tool_calls: row.get(5)?,
+        created_at: row.get::<_, String>(6)?.parse().unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Stored form of a tag enum (its serde name)
+fn tag_str<T: Serialize>(tag: T) -> String {
+    serde_json::to_value(tag)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_tag<T: serde::de::DeserializeOwned>(value: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+}
+
+// ============================================
+// Postgres Store (Shared)
+// ============================================
+
+/// Key of the advisory lock nodes take while creating the schema ("oneclaw")
+const SCHEMA_LOCK: i64 = 0x006f_6e65_636c_6177;
+
+/// The SqliteStore schema in Postgres types; timestamps are TIMESTAMPTZ
+const POSTGRES_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS users (
+        id TEXT PRIMARY KEY,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE TABLE IF NOT EXISTS identities (
+        user_id TEXT NOT NULL REFERENCES users(id),
+        provider TEXT NOT NULL,
+        provider_id TEXT NOT NULL,
+        username TEXT,
+        linked_at TIMESTAMPTZ NOT NULL,
+        PRIMARY KEY (provider, provider_id)
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_identities_user ON identities(user_id);
+    
+    CREATE TABLE IF NOT EXISTS conversations (
+        id BIGSERIAL PRIMARY KEY,
+        user_id TEXT NOT NULL REFERENCES users(id),
+        role TEXT NOT NULL,
+        content TEXT NOT NULL,
+        channel TEXT NOT NULL,
+        tool_calls TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        thread_id TEXT
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_conversations_user ON conversations(user_id, created_at DESC);
+    CREATE INDEX IF NOT EXISTS idx_conversations_thread ON conversations(user_id, thread_id, created_at DESC);
+    
+    CREATE TABLE IF NOT EXISTS message_tags (
+        message_id BIGINT PRIMARY KEY REFERENCES conversations(id),
+        intent TEXT NOT NULL,
+        sentiment TEXT NOT NULL,
+        tagged_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_message_tags_sentiment ON message_tags(sentiment, intent);
+    
+    CREATE TABLE IF NOT EXISTS snoozes (
+        id BIGSERIAL PRIMARY KEY,
+        channel TEXT NOT NULL,
+        chat_id TEXT NOT NULL,
+        content TEXT NOT NULL,
+        deliver_at TIMESTAMPTZ NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_snoozes_due ON snoozes(deliver_at);
+    
+    CREATE TABLE IF NOT EXISTS preferences (
+        user_id TEXT PRIMARY KEY REFERENCES users(id),
+        data TEXT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE TABLE IF NOT EXISTS episodes (
+        id BIGSERIAL PRIMARY KEY,
+        user_id TEXT NOT NULL REFERENCES users(id),
+        started_at TIMESTAMPTZ NOT NULL,
+        ended_at TIMESTAMPTZ NOT NULL,
+        summary TEXT NOT NULL,
+        message_count BIGINT NOT NULL,
+        UNIQUE (user_id, started_at)
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_episodes_user ON episodes(user_id, ended_at DESC);
+    
+    CREATE TABLE IF NOT EXISTS llm_batches (
+        id TEXT PRIMARY KEY,
+        status TEXT NOT NULL,
+        requests TEXT NOT NULL,
+        submitted_at TIMESTAMPTZ NOT NULL,
+        ended_at TIMESTAMPTZ
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_llm_batches_status ON llm_batches(status);
+    
+    CREATE TABLE IF NOT EXISTS usage (
+        id BIGSERIAL PRIMARY KEY,
+        kind TEXT NOT NULL,
+        subject TEXT NOT NULL,
+        user_id TEXT,
+        prompt_tokens BIGINT NOT NULL,
+        completion_tokens BIGINT NOT NULL,
+        estimated_usd DOUBLE PRECISION,
+        cost_usd DOUBLE PRECISION NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_usage_created ON usage(created_at);
+    
+    CREATE TABLE IF NOT EXISTS tool_calls (
+        id BIGSERIAL PRIMARY KEY,
+        tool TEXT NOT NULL,
+        success BOOLEAN NOT NULL,
+        duration_ms BIGINT NOT NULL,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_tool_calls_created ON tool_calls(created_at);
+    
+    CREATE TABLE IF NOT EXISTS embeddings (
+        model TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        dimensions BIGINT NOT NULL,
+        vector BYTEA NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL,
+        PRIMARY KEY (model, hash)
+    );
+    
+    CREATE TABLE IF NOT EXISTS llm_exchanges (
+        id TEXT PRIMARY KEY,
+        turn_id TEXT,
+        job_id TEXT,
+        phase TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        request TEXT NOT NULL,
+        response TEXT,
+        error TEXT,
+        duration_ms BIGINT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_llm_exchanges_created ON llm_exchanges(created_at);
+    CREATE INDEX IF NOT EXISTS idx_llm_exchanges_turn ON llm_exchanges(turn_id);
+    CREATE INDEX IF NOT EXISTS idx_llm_exchanges_job ON llm_exchanges(job_id);
+    
+    CREATE TABLE IF NOT EXISTS deliveries (
+        id TEXT PRIMARY KEY,
+        channel TEXT NOT NULL,
+        chat_id TEXT NOT NULL,
+        status TEXT NOT NULL,
+        attempts BIGINT NOT NULL,
+        provider_message_id TEXT,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_deliveries_created ON deliveries(created_at);
+    CREATE INDEX IF NOT EXISTS idx_deliveries_status ON deliveries(status);
+    
+    CREATE TABLE IF NOT EXISTS contacted_leads (
+        id BIGSERIAL PRIMARY KEY,
+        business TEXT NOT NULL,
+        domain TEXT,
+        email TEXT,
+        campaign TEXT,
+        action TEXT NOT NULL,
+        override_of TEXT,
+        created_at TIMESTAMPTZ NOT NULL
+    );
+    
+    CREATE INDEX IF NOT EXISTS idx_contacted_leads_created ON contacted_leads(created_at);
+"#;
+
+/// Postgres connection URL from the env var named in the store config
+pub fn postgres_url(config: &crate::config::StoreConfig) -> anyhow::Result<String> {
+    std::env::var(&config.postgres_url_env)
+        .map_err(|_| anyhow::anyhow!("Postgres URL not found in env: {}", config.postgres_url_env))
+}
+
+/// The SqliteStore schema on a Postgres server, for nodes that share one
+/// database (SQLite files on a network share get corrupted)
+pub struct PostgresStore {
+    pool: PgPool,
+    /// Seals message content when set
+    content_key: Option<[u8; 32]>,
+}
+
+impl PostgresStore {
+    /// Encrypt message content written from now on
+    pub fn with_content_key(mut self, key: [u8; 32]) -> Self {
+        self.content_key = Some(key);
+        self
+    }
+
+    fn seal_content(&self, content: &str) -> anyhow::Result<String> {
+        seal_with(self.content_key.as_ref(), content)
+    }
+
+    fn open_content(&self, stored: String) -> String {
+        open_with(self.content_key.as_ref(), stored)
+    }
+
+    fn open_messages(&self, messages: Vec<ConversationMessage>) -> Vec<ConversationMessage> {
+        messages
+            .into_iter()
+            .map(|m| {
+                let content = self.open_content(m.content);
+                let tool_calls = m.tool_calls.map(|calls| self.open_content(calls));
+                ConversationMessage { content, tool_calls, ..m }
+            })
+            .collect()
+    }
+
+    fn open_identity(&self, identity: Identity) -> Identity {
+        let username = identity.username.map(|name| self.open_content(name));
+        Identity { username, ..identity }
+    }
+
+    fn open_contacted_lead(&self, lead: ContactedLead) -> ContactedLead {
+        let email = lead.email.map(|email| self.open_content(email));
+        ContactedLead { email, ..lead }
+    }
+
+    /// Connect with a pool of up to `max_connections` and create the schema
+    pub async fn new(url: &str, max_connections: u32) -> anyhow::Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections.max(1))
+            .acquire_timeout(std::time::Duration::from_secs(10))
+            .connect(url)
+            .await
+            .map_err(|e| anyhow::anyhow!("Could not connect to Postgres: {}", e))?;
+        
+        // Nodes starting together create the schema one at a time
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)").bind(SCHEMA_LOCK).execute(&mut *tx).await?;
+        sqlx::raw_sql(POSTGRES_SCHEMA).execute(&mut *tx).await?;
+        tx.commit().await?;
+        
+        Ok(Self { pool, content_key: None })
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn get_user(&self, user_id: &str) -> anyhow::Result<Option<User>> {
+        let row = sqlx::query("SELECT id, created_at, updated_at FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        row.map(|row| {
+            Ok(User {
+                id: row.try_get(0)?,
+                created_at: row.try_get(1)?,
+                updated_at: row.try_get(2)?,
+            })
+        })
+        .transpose()
+    }
+    
+    async fn create_user(&self, user_id: &str) -> anyhow::Result<User> {
+        // Another node may have created the user since it was looked up
+        let row = sqlx::query(
+            "INSERT INTO users (id, created_at, updated_at) VALUES ($1, $2, $2) 
+             ON CONFLICT (id) DO UPDATE SET updated_at = users.updated_at 
+             RETURNING id, created_at, updated_at"
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(User {
+            id: row.try_get(0)?,
+            created_at: row.try_get(1)?,
+            updated_at: row.try_get(2)?,
+        })
+    }
+    
+    async fn get_identity(&self, provider: &str, provider_id: &str) -> anyhow::Result<Option<Identity>> {
+        let row = sqlx::query(
+            "SELECT user_id, provider, provider_id, username, linked_at FROM identities WHERE provider = $1 AND provider_id = $2"
+        )
+        .bind(provider)
+        .bind(provider_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let identity = row.as_ref().map(pg_identity_row).transpose()?;
+        Ok(identity.map(|identity| self.open_identity(identity)))
+    }
+    
+    async fn link_identity(&self, user_id: &str, provider: &str, provider_id: &str, username: Option<&str>) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO identities (user_id, provider, provider_id, username, linked_at) VALUES ($1, $2, $3, $4, $5) 
+             ON CONFLICT (provider, provider_id) DO UPDATE SET 
+                user_id = excluded.user_id, 
+                username = excluded.username, 
+                linked_at = excluded.linked_at"
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(provider_id)
+        .bind(username.map(|name| self.seal_content(name)).transpose()?)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn get_user_identities(&self, user_id: &str) -> anyhow::Result<Vec<Identity>> {
+        let rows = sqlx::query(
+            "SELECT user_id, provider, provider_id, username, linked_at FROM identities WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter().map(|row| Ok(self.open_identity(pg_identity_row(row)?))).collect()
+    }
+    
+    async fn get_conversation(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, role, content, channel, tool_calls, created_at 
+             FROM conversations 
+             WHERE user_id = $1 
+             ORDER BY created_at DESC, id DESC 
+             LIMIT $2"
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let mut messages = rows.iter().map(pg_conversation_row).collect::<Result<Vec<_>, _>>()?;
+        // Reverse to get chronological order
+        messages.reverse();
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn get_thread(&self, user_id: &str, thread_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, role, content, channel, tool_calls, created_at 
+             FROM conversations 
+             WHERE user_id = $1 AND thread_id IS NOT DISTINCT FROM $2 
+             ORDER BY created_at DESC, id DESC 
+             LIMIT $3"
+        )
+        .bind(user_id)
+        .bind(thread_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let mut messages = rows.iter().map(pg_conversation_row).collect::<Result<Vec<_>, _>>()?;
+        messages.reverse();
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>, thread_id: Option<&str>) -> anyhow::Result<i64> {
+        let content = self.seal_content(content)?;
+        let tool_calls = tool_calls.map(|calls| self.seal_content(calls)).transpose()?;
+        
+        let id = sqlx::query_scalar(
+            "INSERT INTO conversations (user_id, role, content, channel, tool_calls, created_at, thread_id) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id"
+        )
+        .bind(user_id)
+        .bind(role)
+        .bind(content)
+        .bind(channel)
+        .bind(tool_calls)
+        .bind(Utc::now())
+        .bind(thread_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+    
+    async fn clear_conversation(&self, user_id: &str) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM message_tags WHERE message_id IN (SELECT id FROM conversations WHERE user_id = $1)")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM conversations WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM episodes WHERE user_id = $1").bind(user_id).execute(&mut *tx).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    
+    async fn compressible_users(&self, before: DateTime<Utc>, min_messages: usize) -> anyhow::Result<Vec<String>> {
+        let users = sqlx::query_scalar(
+            "SELECT user_id 
+             FROM conversations 
+             WHERE created_at < $1 AND thread_id IS NULL 
+             GROUP BY user_id 
+             HAVING COUNT(*) >= $2"
+        )
+        .bind(before)
+        .bind(min_messages as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+    
+    async fn messages_before(
+        &self,
+        user_id: &str,
+        before: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConversationMessage>> {
+        let (after, after_id) = after.unwrap_or_default();
+        let rows = sqlx::query(
+            "SELECT id, user_id, role, content, channel, tool_calls, created_at 
+             FROM conversations 
+             WHERE user_id = $1 AND created_at < $2 AND thread_id IS NULL 
+               AND (created_at, id) > ($3, $4) 
+             ORDER BY created_at, id 
+             LIMIT $5"
+        )
+        .bind(user_id)
+        .bind(before)
+        .bind(after)
+        .bind(after_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let messages = rows.iter().map(pg_conversation_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn replace_messages(&self, user_id: &str, ids: &[i64], summary: &ConversationMessage) -> anyhow::Result<i64> {
+        let content = self.seal_content(&summary.content)?;
+        
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM message_tags WHERE message_id = ANY($1)").bind(ids).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM conversations WHERE id = ANY($1) AND user_id = $2")
+            .bind(ids)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let id = sqlx::query_scalar(
+            "INSERT INTO conversations (user_id, role, content, channel, tool_calls, created_at, thread_id) 
+             VALUES ($1, $2, $3, $4, NULL, $5, NULL) RETURNING id"
+        )
+        .bind(user_id)
+        .bind(&summary.role)
+        .bind(content)
+        .bind(&summary.channel)
+        .bind(summary.created_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+    
+    async fn add_episode(&self, episode: &Episode) -> anyhow::Result<()> {
+        let summary = self.seal_content(&episode.summary)?;
+        
+        // A session is summarized once, even if two turns race to close it
+        sqlx::query(
+            "INSERT INTO episodes (user_id, started_at, ended_at, summary, message_count) VALUES ($1, $2, $3, $4, $5) 
+             ON CONFLICT (user_id, started_at) DO NOTHING"
+        )
+        .bind(&episode.user_id)
+        .bind(episode.started_at)
+        .bind(episode.ended_at)
+        .bind(summary)
+        .bind(episode.message_count as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn recent_episodes(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<Episode>> {
+        let rows = sqlx::query(
+            "SELECT user_id, started_at, ended_at, summary, message_count 
+             FROM episodes 
+             WHERE user_id = $1 
+             ORDER BY ended_at DESC 
+             LIMIT $2"
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(Episode {
+                    user_id: row.try_get(0)?,
+                    started_at: row.try_get(1)?,
+                    ended_at: row.try_get(2)?,
+                    summary: self.open_content(row.try_get(3)?),
+                    message_count: row.try_get::<i64, _>(4)? as usize,
+                })
+            })
+            .collect()
+    }
+    
+    async fn untagged_messages(&self, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let rows = sqlx::query(
+            "SELECT c.id, c.user_id, c.role, c.content, c.channel, c.tool_calls, c.created_at 
+             FROM conversations c 
+             LEFT JOIN message_tags t ON t.message_id = c.id 
+             WHERE c.role = 'user' AND t.message_id IS NULL 
+             ORDER BY c.id 
+             LIMIT $1"
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let messages = rows.iter().map(pg_conversation_row).collect::<Result<Vec<_>, _>>()?;
+        Ok(self.open_messages(messages))
+    }
+    
+    async fn tag_message(&self, message_id: i64, tags: MessageTags) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO message_tags (message_id, intent, sentiment, tagged_at) VALUES ($1, $2, $3, $4) 
+             ON CONFLICT (message_id) DO UPDATE SET 
+                intent = excluded.intent, 
+                sentiment = excluded.sentiment, 
+                tagged_at = excluded.tagged_at"
+        )
+        .bind(message_id)
+        .bind(tag_str(tags.intent))
+        .bind(tag_str(tags.sentiment))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn tagged_messages(&self, query: &TagQuery) -> anyhow::Result<Vec<TaggedMessage>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(intent) = query.intent {
+            params.push(tag_str(intent));
+            filters.push(format!("t.intent = ${}", params.len()));
+        }
+        if let Some(sentiment) = query.sentiment {
+            params.push(tag_str(sentiment));
+            filters.push(format!("t.sentiment = ${}", params.len()));
+        }
+        if let Some(channel) = &query.channel {
+            params.push(channel.clone());
+            filters.push(format!("c.channel = ${}", params.len()));
+        }
+        if let Some(since) = query.since {
+            params.push(since.to_rfc3339());
+            filters.push(format!("c.created_at >= ${}::timestamptz", params.len()));
+        }
+        let sql = format!(
+            "SELECT c.id, c.user_id, c.role, c.content, c.channel, c.tool_calls, c.created_at, t.intent, t.sentiment 
+             FROM conversations c 
+             JOIN message_tags t ON t.message_id = c.id 
+             {} 
+             ORDER BY c.created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let mut select = sqlx::query(&sql);
+        for param in params {
+            select = select.bind(param);
+        }
+        let rows = select.fetch_all(&self.pool).await?;
+        
+        let mut messages = vec![];
+        for row in &rows {
+            let message = pg_conversation_row(row)?;
+            let (intent, sentiment): (String, String) = (row.try_get(7)?, row.try_get(8)?);
+            // Skip rows tagged with values this build doesn't know
+            if let (Some(intent), Some(sentiment)) = (parse_tag(&intent), parse_tag(&sentiment)) {
+                let content = self.open_content(message.content);
+                messages.push(TaggedMessage {
+                    message: ConversationMessage { content, ..message },
+                    tags: MessageTags { intent, sentiment },
+                });
+            }
+        }
+        Ok(messages)
+    }
+    
+    async fn add_snooze(&self, channel: &str, chat_id: &str, content: &str, deliver_at: DateTime<Utc>) -> anyhow::Result<i64> {
+        let content = self.seal_content(content)?;
+        
+        let id = sqlx::query_scalar(
+            "INSERT INTO snoozes (channel, chat_id, content, deliver_at, created_at) VALUES ($1, $2, $3, $4, $5) RETURNING id"
+        )
+        .bind(channel)
+        .bind(chat_id)
+        .bind(content)
+        .bind(deliver_at)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+    
+    async fn due_snoozes(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Snooze>> {
+        let rows = sqlx::query(
+            "SELECT id, channel, chat_id, content, deliver_at, created_at 
+             FROM snoozes 
+             WHERE deliver_at <= $1 
+             ORDER BY deliver_at"
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(Snooze {
+                    id: row.try_get(0)?,
+                    channel: row.try_get(1)?,
+                    chat_id: row.try_get(2)?,
+                    content: self.open_content(row.try_get(3)?),
+                    deliver_at: row.try_get(4)?,
+                    created_at: row.try_get(5)?,
+                })
+            })
+            .collect()
+    }
+    
+    async fn remove_snooze(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM snoozes WHERE id = $1").bind(id).execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>> {
+        let row = sqlx::query("SELECT user_id, data, updated_at FROM preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        row.map(|row| {
+            let data_str: String = row.try_get(1)?;
+            Ok(Preferences {
+                user_id: row.try_get(0)?,
+                data: serde_json::from_str(&self.open_content(data_str)).unwrap_or(serde_json::json!({})),
+                updated_at: row.try_get(2)?,
+            })
+        })
+        .transpose()
+    }
+    
+    async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO preferences (user_id, data, updated_at) VALUES ($1, $2, $3) 
+             ON CONFLICT (user_id) DO UPDATE SET data = excluded.data, updated_at = excluded.updated_at"
+        )
+        .bind(user_id)
+        .bind(self.seal_content(&serde_json::to_string(&data)?)?)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn add_llm_batch(&self, batch: &LlmBatch) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO llm_batches (id, status, requests, submitted_at, ended_at) VALUES ($1, $2, $3, $4, $5) 
+             ON CONFLICT (id) DO UPDATE SET 
+                status = excluded.status, 
+                requests = excluded.requests, 
+                submitted_at = excluded.submitted_at, 
+                ended_at = excluded.ended_at"
+        )
+        .bind(&batch.id)
+        .bind(&batch.status)
+        .bind(serde_json::to_string(&batch.requests)?)
+        .bind(batch.submitted_at)
+        .bind(batch.ended_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn open_llm_batches(&self) -> anyhow::Result<Vec<LlmBatch>> {
+        let rows = sqlx::query(
+            "SELECT id, status, requests, submitted_at, ended_at 
+             FROM llm_batches 
+             WHERE ended_at IS NULL 
+             ORDER BY submitted_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(LlmBatch {
+                    id: row.try_get(0)?,
+                    status: row.try_get(1)?,
+                    requests: serde_json::from_str(&row.try_get::<String, _>(2)?).unwrap_or(serde_json::json!({})),
+                    submitted_at: row.try_get(3)?,
+                    ended_at: row.try_get(4)?,
+                })
+            })
+            .collect()
+    }
+    
+    async fn end_llm_batch(&self, id: &str, status: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE llm_batches SET status = $1, ended_at = $2 WHERE id = $3")
+            .bind(status)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+    
+    async fn add_usage(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO usage (kind, subject, user_id, prompt_tokens, completion_tokens, estimated_usd, cost_usd, created_at) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        )
+        .bind(&record.kind)
+        .bind(&record.subject)
+        .bind(&record.user_id)
+        .bind(record.prompt_tokens as i64)
+        .bind(record.completion_tokens as i64)
+        .bind(record.estimated_usd)
+        .bind(record.cost_usd)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn usage_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<UsageRecord>> {
+        let rows = sqlx::query(
+            "SELECT kind, subject, user_id, prompt_tokens, completion_tokens, estimated_usd, cost_usd, created_at 
+             FROM usage 
+             WHERE created_at >= $1 
+             ORDER BY created_at"
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(UsageRecord {
+                    kind: row.try_get(0)?,
+                    subject: row.try_get(1)?,
+                    user_id: row.try_get(2)?,
+                    prompt_tokens: row.try_get::<i64, _>(3)? as u64,
+                    completion_tokens: row.try_get::<i64, _>(4)? as u64,
+                    estimated_usd: row.try_get(5)?,
+                    cost_usd: row.try_get(6)?,
+                    created_at: row.try_get(7)?,
+                })
+            })
+            .collect()
+    }
+    
+    async fn add_tool_call(&self, record: &ToolCallRecord) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO tool_calls (tool, success, duration_ms, error, created_at) VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(&record.tool)
+        .bind(record.success)
+        .bind(record.duration_ms as i64)
+        .bind(&record.error)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn tool_calls_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<ToolCallRecord>> {
+        let rows = sqlx::query(
+            "SELECT tool, success, duration_ms, error, created_at 
+             FROM tool_calls 
+             WHERE created_at >= $1 
+             ORDER BY created_at"
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(ToolCallRecord {
+                    tool: row.try_get(0)?,
+                    success: row.try_get(1)?,
+                    duration_ms: row.try_get::<i64, _>(2)? as u64,
+                    error: row.try_get(3)?,
+                    created_at: row.try_get(4)?,
+                })
+            })
+            .collect()
+    }
+    
+    async fn embeddings(&self, model: &str, hashes: &[String]) -> anyhow::Result<Vec<EmbeddingRecord>> {
+        let rows = sqlx::query("SELECT hash, vector FROM embeddings WHERE model = $1 AND hash = ANY($2)")
+            .bind(model)
+            .bind(hashes)
+            .fetch_all(&self.pool)
+            .await?;
+        
+        rows.iter()
+            .map(|row| {
+                let vector: Vec<u8> = row.try_get(1)?;
+                Ok(EmbeddingRecord { hash: row.try_get(0)?, vector: crate::embeddings::decode(&vector) })
+            })
+            .collect()
+    }
+    
+    async fn add_embeddings(&self, model: &str, records: &[EmbeddingRecord]) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(
+                "INSERT INTO embeddings (model, hash, dimensions, vector, created_at) VALUES ($1, $2, $3, $4, $5) 
+                 ON CONFLICT (model, hash) DO UPDATE SET 
+                    dimensions = excluded.dimensions, 
+                    vector = excluded.vector, 
+                    created_at = excluded.created_at"
+            )
+            .bind(model)
+            .bind(&record.hash)
+            .bind(record.vector.len() as i64)
+            .bind(crate::embeddings::encode(&record.vector))
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+    
+    async fn add_llm_exchange(&self, exchange: &LlmExchange) -> anyhow::Result<()> {
+        let request = self.seal_content(&exchange.request.to_string())?;
+        let response = exchange.response.as_ref().map(|r| self.seal_content(&r.to_string())).transpose()?;
+        
+        sqlx::query(
+            "INSERT INTO llm_exchanges (id, turn_id, job_id, phase, provider, model, request, response, error, duration_ms, created_at) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
+        )
+        .bind(&exchange.id)
+        .bind(&exchange.turn_id)
+        .bind(&exchange.job_id)
+        .bind(&exchange.phase)
+        .bind(&exchange.provider)
+        .bind(&exchange.model)
+        .bind(request)
+        .bind(response)
+        .bind(&exchange.error)
+        .bind(exchange.duration_ms as i64)
+        .bind(exchange.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn llm_exchanges(&self, query: &LlmAuditQuery) -> anyhow::Result<Vec<LlmExchange>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(since) = query.since {
+            params.push(since.to_rfc3339());
+            filters.push(format!("created_at >= ${}::timestamptz", params.len()));
+        }
+        if let Some(turn_id) = &query.turn_id {
+            params.push(turn_id.clone());
+            filters.push(format!("turn_id = ${}", params.len()));
+        }
+        if let Some(job_id) = &query.job_id {
+            params.push(job_id.clone());
+            filters.push(format!("job_id = ${}", params.len()));
+        }
+        let sql = format!(
+            "SELECT id, turn_id, job_id, phase, provider, model, request, response, error, duration_ms, created_at 
+             FROM llm_exchanges 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let mut select = sqlx::query(&sql);
+        for param in params {
+            select = select.bind(param);
+        }
+        let rows = select.fetch_all(&self.pool).await?;
+        
+        // Unreadable (sealed with another key) bodies come back as the notice text
+        let parse = |stored: String| {
+            let opened = self.open_content(stored);
+            serde_json::from_str(&opened).unwrap_or(serde_json::Value::String(opened))
+        };
+        rows.iter()
+            .map(|row| {
+                Ok(LlmExchange {
+                    id: row.try_get(0)?,
+                    turn_id: row.try_get(1)?,
+                    job_id: row.try_get(2)?,
+                    phase: row.try_get(3)?,
+                    provider: row.try_get(4)?,
+                    model: row.try_get(5)?,
+                    request: parse(row.try_get(6)?),
+                    response: row.try_get::<Option<String>, _>(7)?.map(parse),
+                    error: row.try_get(8)?,
+                    duration_ms: row.try_get::<i64, _>(9)? as u64,
+                    created_at: row.try_get(10)?,
+                })
+            })
+            .collect()
+    }
+    
+    async fn prune_llm_exchanges(&self, keep: usize, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        let removed = sqlx::query(
+            "DELETE FROM llm_exchanges 
+             WHERE created_at < $1 
+                OR id NOT IN (SELECT id FROM llm_exchanges ORDER BY created_at DESC LIMIT $2)"
+        )
+        .bind(before)
+        .bind(keep as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(removed.rows_affected() as usize)
+    }
+    
+    async fn save_delivery(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO deliveries (id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
+             ON CONFLICT (id) DO UPDATE SET 
+                status = excluded.status, 
+                attempts = excluded.attempts, 
+                provider_message_id = excluded.provider_message_id, 
+                error = excluded.error, 
+                updated_at = excluded.updated_at"
+        )
+        .bind(&delivery.id)
+        .bind(&delivery.channel)
+        .bind(&delivery.chat_id)
+        .bind(&delivery.status)
+        .bind(delivery.attempts as i64)
+        .bind(&delivery.provider_message_id)
+        .bind(&delivery.error)
+        .bind(delivery.created_at)
+        .bind(delivery.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn get_delivery(&self, id: &str) -> anyhow::Result<Option<Delivery>> {
+        let row = sqlx::query(
+            "SELECT id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at 
+             FROM deliveries WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(row.as_ref().map(pg_delivery_row).transpose()?)
+    }
+    
+    async fn deliveries(&self, query: &DeliveryQuery) -> anyhow::Result<Vec<Delivery>> {
+        let mut filters = vec![];
+        let mut params: Vec<String> = vec![];
+        if let Some(since) = query.since {
+            params.push(since.to_rfc3339());
+            filters.push(format!("created_at >= ${}::timestamptz", params.len()));
+        }
+        if let Some(status) = &query.status {
+            params.push(status.clone());
+            filters.push(format!("status = ${}", params.len()));
+        }
+        if let Some(channel) = &query.channel {
+            params.push(channel.clone());
+            filters.push(format!("channel = ${}", params.len()));
+        }
+        let sql = format!(
+            "SELECT id, channel, chat_id, status, attempts, provider_message_id, error, created_at, updated_at 
+             FROM deliveries 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let mut select = sqlx::query(&sql);
+        for param in params {
+            select = select.bind(param);
+        }
+        let rows = select.fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(pg_delivery_row).collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    async fn prune_deliveries(&self, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        let removed = sqlx::query("DELETE FROM deliveries WHERE created_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(removed.rows_affected() as usize)
+    }
+    
+    async fn add_contacted_lead(&self, lead: &ContactedLead) -> anyhow::Result<()> {
+        let email = lead.email.as_deref().map(|e| self.seal_content(e)).transpose()?;
+        sqlx::query(
+            "INSERT INTO contacted_leads (business, domain, email, campaign, action, override_of, created_at) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
+        )
+        .bind(&lead.business)
+        .bind(&lead.domain)
+        .bind(email)
+        .bind(&lead.campaign)
+        .bind(&lead.action)
+        .bind(&lead.override_of)
+        .bind(lead.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    
+    async fn contacted_leads(&self, query: &ContactedLeadQuery) -> anyhow::Result<Vec<ContactedLead>> {
+        let mut filters = vec![];
+        if query.since.is_some() {
+            filters.push("created_at >= $1");
+        }
+        if query.overridden {
+            filters.push("override_of IS NOT NULL");
+        }
+        let sql = format!(
+            "SELECT business, domain, email, campaign, action, override_of, created_at 
+             FROM contacted_leads 
+             {} 
+             ORDER BY created_at DESC 
+             LIMIT {}",
+            if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) },
+            query.limit,
+        );
+        
+        let mut select = sqlx::query(&sql);
+        if let Some(since) = query.since {
+            select = select.bind(since);
+        }
+        let rows = select.fetch_all(&self.pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(self.open_contacted_lead(ContactedLead {
+                    business: row.try_get(0)?,
+                    domain: row.try_get(1)?,
+                    email: row.try_get(2)?,
+                    campaign: row.try_get(3)?,
+                    action: row.try_get(4)?,
+                    override_of: row.try_get(5)?,
+                    created_at: row.try_get(6)?,
+                }))
+            })
+            .collect()
+    }
+}
+
+/// `identities` row in column order user_id, provider, provider_id, username, linked_at
+fn pg_identity_row(row: &PgRow) -> sqlx::Result<Identity> {
+    Ok(Identity {
+        user_id: row.try_get(0)?,
+        provider: row.try_get(1)?,
+        provider_id: row.try_get(2)?,
+        username: row.try_get(3)?,
+        linked_at: row.try_get(4)?,
+    })
+}
+
+/// `conversations` row in column order id, user_id, role, content, channel, tool_calls, created_at
+fn pg_conversation_row(row: &PgRow) -> sqlx::Result<ConversationMessage> {
+    Ok(ConversationMessage {
+        id: row.try_get(0)?,
+        user_id: row.try_get(1)?,
+        role: row.try_get(2)?,
+        content: row.try_get(3)?,
+        channel: row.try_get(4)?,
+        tool_calls: row.try_get(5)?,
+        created_at: row.try_get(6)?,
+    })
+}
+
+/// `deliveries` row in the column order of `delivery_row`
+fn pg_delivery_row(row: &PgRow) -> sqlx::Result<Delivery> {
+    Ok(Delivery {
+        id: row.try_get(0)?,
+        channel: row.try_get(1)?,
+        chat_id: row.try_get(2)?,
+        status: row.try_get(3)?,
+        attempts: row.try_get::<i64, _>(4)? as u32,
+        provider_message_id: row.try_get(5)?,
+        error: row.try_get(6)?,
+        created_at: row.try_get(7)?,
+        updated_at: row.try_get(8)?,
+    })
+}
+
+// ============================================
+// Hosted Store (Harness API)
+// ============================================
+
+/// Sent with replayed writes: when the write was made (see `store_outbox.rs`)
+const WRITTEN_AT_HEADER: &str = "X-OneClaw-Written-At";
+
+/// Queued writes replayed per outbox read
+const REPLAY_BATCH: usize = 100;
+
+/// How often queued writes are retried in the background
+const REPLAY_EVERY: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Whether a request failed because the control plane can't be reached, as
+/// opposed to turning it down
+fn unreachable(result: &reqwest::Result<reqwest::Response>) -> bool {
+    match result {
+        Err(e) => e.is_connect() || e.is_timeout(),
+        Ok(resp) => matches!(resp.status().as_u16(), 502..=504),
+    }
+}
+
+#[derive(Clone)]
+pub struct HostedStore {
+    api_url: String,
+    token: String,
+    /// The node whose data this is, for the request signatures
+    node_id: String,
+    client: reqwest::Client,
+    /// Keeps writes made while the control plane is unreachable
+    outbox: Option<Arc<crate::store_outbox::Outbox>>,
+}
+
+impl HostedStore {
+    pub fn new(api_url: String, token: String, node_id: String) -> Self {
+        Self {
+            api_url,
+            token,
+            node_id,
+            client: reqwest::Client::builder()
+                .connect_timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            outbox: None,
+        }
+    }
+    
+    /// Queue writes in `outbox` while offline and replay them on reconnect
+    pub fn with_outbox(mut self, outbox: crate::store_outbox::Outbox) -> Self {
+        self.outbox = Some(Arc::new(outbox));
+        self
+    }
+    
+    /// Send a write. When the control plane can't be reached, or earlier writes
+    /// are still queued, the write is queued instead and None returned.
+    async fn write(&self, method: reqwest::Method, path: &str, body: Option<&serde_json::Value>) -> anyhow::Result<Option<reqwest::Response>> {
+        let Some(outbox) = &self.outbox else {
+            return Ok(Some(self.request(method, path, body).send().await?));
+        };
+        if outbox.pending() > 0 {
+            self.replay().await;
+        }
+        if outbox.pending() == 0 {
+            let result = self.request(method.clone(), path, body).send().await;
+            if !unreachable(&result) {
+                return Ok(Some(result?));
+            }
+            tracing::warn!("Control plane unreachable; queueing store writes until it's back");
+        }
+        outbox.push(method.as_str(), path, body).await?;
+        Ok(None)
+    }
+    
+    /// Replay queued writes, oldest first, until the queue is empty or the
+    /// control plane can't be reached. Returns how many were taken off the queue.
+    pub async fn replay(&self) -> usize {
+        let Some(outbox) = &self.outbox else { return 0 };
+        let Some(_replaying) = outbox.start_replay() else { return 0 };
+        let mut replayed = 0;
+        'replay: loop {
+            let writes = match outbox.oldest(REPLAY_BATCH).await {
+                Ok(writes) if !writes.is_empty() => writes,
+                Ok(_) => break,
+                Err(e) => {
+                    tracing::warn!("Could not read the store outbox: {}", e);
+                    break;
+                }
+            };
+            for write in writes {
+                let method = reqwest::Method::from_bytes(write.method.as_bytes()).unwrap_or(reqwest::Method::POST);
+                let result = self
+                    .request(method, &write.path, write.body.as_ref())
+                    .header(WRITTEN_AT_HEADER, write.queued_at.to_rfc3339())
+                    .send()
+                    .await;
+                if unreachable(&result) {
+                    break 'replay;
+                }
+                match result {
+                    Ok(resp) if resp.status() == reqwest::StatusCode::CONFLICT => {
+                        tracing::info!("Dropped a queued {} {}: the control plane has a newer version", write.method, write.path);
+                    }
+                    Ok(resp) if !resp.status().is_success() => {
+                        tracing::warn!("Dropped a queued {} {}: {}", write.method, write.path, resp.status());
+                    }
+                    Err(e) => tracing::warn!("Dropped a queued {} {}: {}", write.method, write.path, e),
+                    Ok(_) => {}
+                }
+                if let Err(e) = outbox.remove(write.id).await {
+                    tracing::warn!("Could not update the store outbox: {}", e);
+                    break 'replay;
+                }
+                replayed += 1;
+            }
+        }
+        if replayed > 0 {
+            tracing::info!("📤 Replayed {} queued store writes ({} still queued)", replayed, outbox.pending());
+        }
+        replayed
+    }
+    
+    /// Replay queued writes before a read that should see them
+    async fn catch_up(&self) {
+        if self.outbox.as_ref().is_some_and(|outbox| outbox.pending() > 0) {
+            self.replay().await;
+        }
+    }
+    
+    /// Retry queued writes every `REPLAY_EVERY`, for the rest of the process
+    pub fn replay_in_background(&self) {
+        if self.outbox.is_none() {
+            return;
+        }
+        let store = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REPLAY_EVERY).await;
+                store.catch_up().await;
+            }
+        });
+    }
+    
+    /// Build an authenticated, node-signed request against the control plane
+    fn request(&self, method: reqwest::Method, path: &str, body: Option<&serde_json::Value>) -> reqwest::RequestBuilder {
+        let url = format!("{}{}", self.api_url, path);
+        let body_bytes = body
+            .map(|b| serde_json::to_vec(b).unwrap_or_default())
+            .unwrap_or_default();
+        
+        let mut req = self.client
+            .request(method.clone(), &url)
+            .header("Authorization", format!("Bearer {}", self.token));
+        for (name, value) in crate::node_key::signed_headers(&self.node_id, method.as_str(), &url, &body_bytes) {
+            req = req.header(name, value);
+        }
+        if body.is_some() {
+            req = req.header("Content-Type", "application/json").body(body_bytes);
+        }
+        req
+    }
+}
+
+#[async_trait]
+impl Store for HostedStore {
+    async fn get_user(&self, user_id: &str) -> anyhow::Result<Option<User>> {
+        self.catch_up().await;
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/users/{}", user_id), None)
+            .send()
+            .await?;
+        
+        if resp.status() == 404 {
+            return Ok(None);
+        }
+        
+        let user: User = resp.json().await?;
+        Ok(Some(user))
+    }
+    
+    async fn create_user(&self, user_id: &str) -> anyhow::Result<User> {
+        let body = serde_json::json!({ "id": user_id });
+        let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/users", Some(&body)).await? else {
+            // Queued; the control plane creates it on replay
+            let now = Utc::now();
+            return Ok(User { id: user_id.to_string(), created_at: now, updated_at: now });
+        };
         
         let user: User = resp.json().await?;
         Ok(user)
     }
     
     async fn get_identity(&self, provider: &str, provider_id: &str) -> anyhow::Result<Option<Identity>> {
-        let resp = self.client
-            .get(format!("{}/api/v1/identities/{}:{}", self.api_url, provider, provider_id))
-            .header("Authorization", format!("Bearer {}", self.token))
+        self.catch_up().await;
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/identities/{}:{}", provider, provider_id), None)
             .send()
             .await?;
         
@@ -401,25 +2935,20 @@ impl Store for HostedStore {
     }
     
     async fn link_identity(&self, user_id: &str, provider: &str, provider_id: &str, username: Option<&str>) -> anyhow::Result<()> {
-        self.client
-            .post(format!("{}/api/v1/identities", self.api_url))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&serde_json::json!({
-                "user_id": user_id,
-                "provider": provider,
-                "provider_id": provider_id,
-                "username": username
-            }))
-            .send()
-            .await?;
+        let body = serde_json::json!({
+            "user_id": user_id,
+            "provider": provider,
+            "provider_id": provider_id,
+            "username": username
+        });
+        self.write(reqwest::Method::POST, "/api/v1/identities", Some(&body)).await?;
         
         Ok(())
     }
     
     async fn get_user_identities(&self, user_id: &str) -> anyhow::Result<Vec<Identity>> {
-        let resp = self.client
-            .get(format!("{}/api/v1/users/{}/identities", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/users/{}/identities", user_id), None)
             .send()
             .await?;
         
@@ -428,9 +2957,9 @@ impl Store for HostedStore {
     }
     
     async fn get_conversation(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
-        let resp = self.client
-            .get(format!("{}/api/v1/users/{}/conversations?limit={}", self.api_url, user_id, limit))
-            .header("Authorization", format!("Bearer {}", self.token))
+        self.catch_up().await;
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/users/{}/conversations?limit={}", user_id, limit), None)
             .send()
             .await?;
         
@@ -438,37 +2967,181 @@ impl Store for HostedStore {
         Ok(messages)
     }
     
-    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>) -> anyhow::Result<i64> {
-        let resp = self.client
-            .post(format!("{}/api/v1/users/{}/conversations", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&serde_json::json!({
-                "role": role,
-                "content": content,
-                "channel": channel,
-                "tool_calls": tool_calls
-            }))
+    async fn get_thread(&self, user_id: &str, thread_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        self.catch_up().await;
+        // An empty `thread_id` asks for the messages outside any thread
+        let thread: String = url::form_urlencoded::byte_serialize(thread_id.unwrap_or_default().as_bytes()).collect();
+        let resp = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/api/v1/users/{}/conversations?limit={}&thread_id={}", user_id, limit, thread),
+                None,
+            )
             .send()
             .await?;
         
+        let messages: Vec<ConversationMessage> = resp.json().await?;
+        Ok(messages)
+    }
+    
+    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>, thread_id: Option<&str>) -> anyhow::Result<i64> {
+        let body = serde_json::json!({
+            "role": role,
+            "content": content,
+            "channel": channel,
+            "tool_calls": tool_calls,
+            "thread_id": thread_id,
+            "created_at": Utc::now()
+        });
+        let Some(resp) = self.write(reqwest::Method::POST, &format!("/api/v1/users/{}/conversations", user_id), Some(&body)).await? else {
+            return Ok(0);
+        };
+        
         let result: serde_json::Value = resp.json().await?;
         Ok(result["id"].as_i64().unwrap_or(0))
     }
     
     async fn clear_conversation(&self, user_id: &str) -> anyhow::Result<()> {
-        self.client
-            .delete(format!("{}/api/v1/users/{}/conversations", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
+        self.write(reqwest::Method::DELETE, &format!("/api/v1/users/{}/conversations", user_id), None).await?;
+        
+        Ok(())
+    }
+    
+    async fn compressible_users(&self, before: DateTime<Utc>, min_messages: usize) -> anyhow::Result<Vec<String>> {
+        let before: String = url::form_urlencoded::byte_serialize(before.to_rfc3339().as_bytes()).collect();
+        let resp = self
+            .request(
+                reqwest::Method::GET,
+                &format!("/api/v1/conversations/compressible?before={}&min_messages={}", before, min_messages),
+                None,
+            )
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn messages_before(
+        &self,
+        user_id: &str,
+        before: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConversationMessage>> {
+        let before: String = url::form_urlencoded::byte_serialize(before.to_rfc3339().as_bytes()).collect();
+        let mut path = format!("/api/v1/users/{}/conversations?before={}&limit={}&order=oldest", user_id, before, limit);
+        if let Some((after, after_id)) = after {
+            let after: String = url::form_urlencoded::byte_serialize(after.to_rfc3339().as_bytes()).collect();
+            path.push_str(&format!("&after={}&after_id={}", after, after_id));
+        }
+        let resp = self
+            .request(reqwest::Method::GET, &path, None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn replace_messages(&self, user_id: &str, ids: &[i64], summary: &ConversationMessage) -> anyhow::Result<i64> {
+        let body = serde_json::json!({
+            "ids": ids,
+            "role": summary.role,
+            "content": summary.content,
+            "channel": summary.channel,
+            "created_at": summary.created_at.to_rfc3339()
+        });
+        let Some(resp) = self.write(reqwest::Method::POST, &format!("/api/v1/users/{}/conversations/replace", user_id), Some(&body)).await? else {
+            return Ok(0);
+        };
+        let resp = resp.error_for_status()?;
+        
+        let result: serde_json::Value = resp.json().await?;
+        Ok(result["id"].as_i64().unwrap_or(0))
+    }
+    
+    async fn add_episode(&self, episode: &Episode) -> anyhow::Result<()> {
+        let body = serde_json::to_value(episode)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, &format!("/api/v1/users/{}/episodes", episode.user_id), Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn recent_episodes(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<Episode>> {
+        self.catch_up().await;
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/users/{}/episodes?limit={}", user_id, limit), None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn untagged_messages(&self, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/conversations/untagged?limit={}", limit), None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn tag_message(&self, message_id: i64, tags: MessageTags) -> anyhow::Result<()> {
+        let body = serde_json::to_value(tags)?;
+        if let Some(resp) = self.write(reqwest::Method::PUT, &format!("/api/v1/conversations/{}/tags", message_id), Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn tagged_messages(&self, query: &TagQuery) -> anyhow::Result<Vec<TaggedMessage>> {
+        let body = serde_json::to_value(query)?;
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/conversations/tagged", Some(&body))
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn add_snooze(&self, channel: &str, chat_id: &str, content: &str, deliver_at: DateTime<Utc>) -> anyhow::Result<i64> {
+        let body = serde_json::json!({
+            "channel": channel,
+            "chat_id": chat_id,
+            "content": content,
+            "deliver_at": deliver_at,
+        });
+        let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/snoozes", Some(&body)).await? else {
+            return Ok(0);
+        };
+        
+        let result: serde_json::Value = resp.json().await?;
+        Ok(result["id"].as_i64().unwrap_or(0))
+    }
+    
+    async fn due_snoozes(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Snooze>> {
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/snoozes?due_before={}", now.timestamp()), None)
             .send()
             .await?;
         
+        Ok(resp.json().await?)
+    }
+    
+    async fn remove_snooze(&self, id: i64) -> anyhow::Result<()> {
+        if let Some(resp) = self.write(reqwest::Method::DELETE, &format!("/api/v1/snoozes/{}", id), None).await? {
+            resp.error_for_status()?;
+        }
+        
         Ok(())
     }
     
     async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>> {
-        let resp = self.client
-            .get(format!("{}/api/v1/users/{}/preferences", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
+        self.catch_up().await;
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/users/{}/preferences", user_id), None)
             .send()
             .await?;
         
@@ -481,15 +3154,189 @@ impl Store for HostedStore {
     }
     
     async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()> {
-        self.client
-            .put(format!("{}/api/v1/users/{}/preferences", self.api_url, user_id))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .json(&data)
+        self.write(reqwest::Method::PUT, &format!("/api/v1/users/{}/preferences", user_id), Some(&data)).await?;
+        
+        Ok(())
+    }
+    
+    async fn add_llm_batch(&self, batch: &LlmBatch) -> anyhow::Result<()> {
+        let body = serde_json::to_value(batch)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/llm-batches", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn open_llm_batches(&self) -> anyhow::Result<Vec<LlmBatch>> {
+        let resp = self
+            .request(reqwest::Method::GET, "/api/v1/llm-batches?open=true", None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn end_llm_batch(&self, id: &str, status: &str) -> anyhow::Result<()> {
+        let body = serde_json::json!({ "status": status, "ended_at": Utc::now() });
+        if let Some(resp) = self.write(reqwest::Method::PATCH, &format!("/api/v1/llm-batches/{}", id), Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }    
+    async fn add_usage(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        let body = serde_json::to_value(record)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/usage", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn usage_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<UsageRecord>> {
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/usage?since={}", since.timestamp()), None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn add_tool_call(&self, record: &ToolCallRecord) -> anyhow::Result<()> {
+        let body = serde_json::to_value(record)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/tool-calls", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn tool_calls_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<ToolCallRecord>> {
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/tool-calls?since={}", since.timestamp()), None)
+            .send()
+            .await?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn embeddings(&self, model: &str, hashes: &[String]) -> anyhow::Result<Vec<EmbeddingRecord>> {
+        let body = serde_json::json!({ "model": model, "hashes": hashes });
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/embeddings/lookup", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn add_embeddings(&self, model: &str, records: &[EmbeddingRecord]) -> anyhow::Result<()> {
+        let body = serde_json::json!({ "model": model, "embeddings": records });
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/embeddings", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn add_llm_exchange(&self, exchange: &LlmExchange) -> anyhow::Result<()> {
+        let body = serde_json::to_value(exchange)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/llm-audit", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn llm_exchanges(&self, query: &LlmAuditQuery) -> anyhow::Result<Vec<LlmExchange>> {
+        let body = serde_json::to_value(query)?;
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/llm-audit/query", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn prune_llm_exchanges(&self, keep: usize, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        let body = serde_json::json!({ "keep": keep, "before": before });
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/llm-audit/prune", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        let result: serde_json::Value = resp.json().await?;
+        
+        Ok(result["removed"].as_u64().unwrap_or(0) as usize)
+    }
+    
+    async fn save_delivery(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        let body = serde_json::to_value(delivery)?;
+        if let Some(resp) = self.write(reqwest::Method::PUT, &format!("/api/v1/deliveries/{}", delivery.id), Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
+        Ok(())
+    }
+    
+    async fn get_delivery(&self, id: &str) -> anyhow::Result<Option<Delivery>> {
+        let resp = self
+            .request(reqwest::Method::GET, &format!("/api/v1/deliveries/{}", id), None)
             .send()
             .await?;
         
+        if resp.status() == 404 {
+            return Ok(None);
+        }
+        
+        Ok(Some(resp.error_for_status()?.json().await?))
+    }
+    
+    async fn deliveries(&self, query: &DeliveryQuery) -> anyhow::Result<Vec<Delivery>> {
+        let body = serde_json::to_value(query)?;
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/deliveries/query", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(resp.json().await?)
+    }
+    
+    async fn prune_deliveries(&self, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        let body = serde_json::json!({ "before": before });
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/deliveries/prune", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        let result: serde_json::Value = resp.json().await?;
+        
+        Ok(result["removed"].as_u64().unwrap_or(0) as usize)
+    }
+    
+    async fn add_contacted_lead(&self, lead: &ContactedLead) -> anyhow::Result<()> {
+        let body = serde_json::to_value(lead)?;
+        if let Some(resp) = self.write(reqwest::Method::POST, "/api/v1/contacted-leads", Some(&body)).await? {
+            resp.error_for_status()?;
+        }
+        
         Ok(())
     }
+    
+    async fn contacted_leads(&self, query: &ContactedLeadQuery) -> anyhow::Result<Vec<ContactedLead>> {
+        let body = serde_json::to_value(query)?;
+        let resp = self
+            .request(reqwest::Method::POST, "/api/v1/contacted-leads/query", Some(&body))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(resp.json().await?)
+    }
 }
 
 // ============================================
@@ -498,7 +3345,8 @@ impl Store for HostedStore {
 
 pub enum StoreType {
     Sqlite(PathBuf),
-    Hosted { api_url: String, token: String },
+    Postgres { url: String, max_connections: u32 },
+    Hosted { api_url: String, token: String, node_id: String },
 }
 
 pub async fn create_store(store_type: StoreType) -> anyhow::Result<Box<dyn Store>> {
@@ -507,9 +3355,165 @@ pub async fn create_store(store_type: StoreType) -> anyhow::Result<Box<dyn Store
             let store = SqliteStore::new(path).await?;
             Ok(Box::new(store))
         }
-        StoreType::Hosted { api_url, token } => {
-            let store = HostedStore::new(api_url, token);
+        StoreType::Postgres { url, max_connections } => {
+            let store = PostgresStore::new(&url, max_connections).await?;
+            Ok(Box::new(store))
+        }
+        StoreType::Hosted { api_url, token, node_id } => {
+            let store = HostedStore::new(api_url, token, node_id);
             Ok(Box::new(store))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_encrypted_content_roundtrip() {
+        let path = std::env::temp_dir().join(format!("oneclaw-store-test-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let plain = SqliteStore::new(path.clone()).await.unwrap();
+        plain.create_user("u1").await.unwrap();
+        plain.add_message("u1", "user", "written before encryption", "http", None, None).await.unwrap();
+        drop(plain);
+
+        let store = SqliteStore::new(path.clone()).await.unwrap().with_content_key([7u8; 32]);
+        store.add_message("u1", "user", "find HVAC leads in Miami", "http", None, None).await.unwrap();
+
+        let raw: String = store.conn.call(|conn| {
+            Ok(conn.query_row("SELECT content FROM conversations ORDER BY id DESC LIMIT 1", [], |row| row.get(0))?)
+        }).await.unwrap();
+        assert!(raw.starts_with(ENCRYPTED_PREFIX));
+        assert!(!raw.contains("HVAC"));
+
+        let contents: Vec<String> = store.get_conversation("u1", 10).await.unwrap().into_iter().map(|m| m.content).collect();
+        assert!(contents.contains(&"written before encryption".to_string()));
+        assert!(contents.contains(&"find HVAC leads in Miami".to_string()));
+
+        // Tool calls kept with a reply are sealed too
+        let calls = r#"[{"tool":"maps.places","input":{"query":"HVAC Miami"}}]"#;
+        store.add_message("u1", "assistant", "Found 3", "http", Some(calls), None).await.unwrap();
+        let raw: String = store.conn.call(|conn| {
+            Ok(conn.query_row("SELECT tool_calls FROM conversations ORDER BY id DESC LIMIT 1", [], |row| row.get(0))?)
+        }).await.unwrap();
+        assert!(raw.starts_with(ENCRYPTED_PREFIX));
+        let last = store.get_conversation("u1", 10).await.unwrap().pop().unwrap();
+        assert_eq!(last.tool_calls.as_deref(), Some(calls));
+
+        // Preferences and usernames too; what was written before gets sealed on request
+        store.set_preferences("u1", serde_json::json!({ "city": "Miami" })).await.unwrap();
+        store.link_identity("u1", "telegram", "42", Some("Ana Díaz")).await.unwrap();
+        assert_eq!(store.get_preferences("u1").await.unwrap().unwrap().data["city"], "Miami");
+        assert_eq!(store.get_identity("telegram", "42").await.unwrap().unwrap().username.as_deref(), Some("Ana Díaz"));
+        assert_eq!(store.seal_existing().await.unwrap(), 1);
+        let plain: i64 = store.conn.call(|conn| {
+            Ok(conn.query_row(
+                "SELECT (SELECT COUNT(*) FROM conversations WHERE content NOT LIKE 'enc:v1:%') 
+                      + (SELECT COUNT(*) FROM preferences WHERE data NOT LIKE 'enc:v1:%') 
+                      + (SELECT COUNT(*) FROM identities WHERE username NOT LIKE 'enc:v1:%')",
+                [],
+                |row| row.get(0),
+            )?)
+        }).await.unwrap();
+        assert_eq!(plain, 0);
+
+        let wrong_key = SqliteStore::new(path.clone()).await.unwrap().with_content_key([8u8; 32]);
+        let contents: Vec<String> = wrong_key.get_conversation("u1", 10).await.unwrap().into_iter().map(|m| m.content).collect();
+        assert!(contents.contains(&UNREADABLE_CONTENT.to_string()));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_threads_are_separate() {
+        let path = std::env::temp_dir().join(format!("oneclaw-store-threads-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = SqliteStore::new(path.clone()).await.unwrap();
+        store.create_user("u1").await.unwrap();
+        store.add_message("u1", "user", "hi", "slack", None, None).await.unwrap();
+        store.add_message("u1", "user", "audit acme.com", "slack", None, Some("1700000000.0001")).await.unwrap();
+        store.add_message("u1", "user", "find roofers", "slack", None, Some("1700000000.0002")).await.unwrap();
+        store.add_message("u1", "assistant", "Auditing acme.com", "slack", None, Some("1700000000.0001")).await.unwrap();
+
+        let thread: Vec<String> = store.get_thread("u1", Some("1700000000.0001"), 10).await.unwrap().into_iter().map(|m| m.content).collect();
+        assert_eq!(thread, vec!["audit acme.com", "Auditing acme.com"]);
+        let unthreaded = store.get_thread("u1", None, 10).await.unwrap();
+        assert_eq!(unthreaded.len(), 1);
+        assert_eq!(unthreaded[0].content, "hi");
+        assert_eq!(store.get_conversation("u1", 10).await.unwrap().len(), 4);
+
+        // Compression pages through unthreaded messages after the last one looked at
+        store.add_message("u1", "assistant", "hello", "slack", None, None).await.unwrap();
+        let before = Utc::now() + chrono::Duration::minutes(1);
+        let first = store.messages_before("u1", before, None, 1).await.unwrap();
+        assert_eq!(first[0].content, "hi");
+        let next = store.messages_before("u1", before, Some((first[0].created_at, first[0].id)), 10).await.unwrap();
+        assert_eq!(next.into_iter().map(|m| m.content).collect::<Vec<_>>(), vec!["hello"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Runs against the server in `ONECLAW_TEST_POSTGRES_URL`; skipped without one
+    #[tokio::test]
+    async fn test_postgres_store() {
+        let Ok(url) = std::env::var("ONECLAW_TEST_POSTGRES_URL") else { return };
+        let store = PostgresStore::new(&url, 2).await.unwrap().with_content_key([7u8; 32]);
+        // A second node on the same database
+        let other = PostgresStore::new(&url, 2).await.unwrap().with_content_key([7u8; 32]);
+
+        let user = format!("u-{}", nanoid::nanoid!(8));
+        let created = store.create_user(&user).await.unwrap();
+        assert_eq!(other.create_user(&user).await.unwrap().created_at, created.created_at);
+        store.link_identity(&user, "telegram", &user, Some("ana")).await.unwrap();
+        assert_eq!(other.get_identity("telegram", &user).await.unwrap().unwrap().user_id, user);
+
+        store.add_message(&user, "user", "hi", "slack", None, None).await.unwrap();
+        let id = store.add_message(&user, "user", "audit acme.com", "slack", None, Some("t1")).await.unwrap();
+        other.add_message(&user, "assistant", "Auditing acme.com", "slack", None, Some("t1")).await.unwrap();
+        let thread: Vec<String> = other.get_thread(&user, Some("t1"), 10).await.unwrap().into_iter().map(|m| m.content).collect();
+        assert_eq!(thread, vec!["audit acme.com", "Auditing acme.com"]);
+        assert_eq!(store.get_thread(&user, None, 10).await.unwrap()[0].content, "hi");
+
+        let raw: String = sqlx::query_scalar("SELECT content FROM conversations WHERE id = $1")
+            .bind(id)
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert!(raw.starts_with(ENCRYPTED_PREFIX));
+
+        store.tag_message(id, MessageTags { intent: Intent::Support, sentiment: Sentiment::Neutral }).await.unwrap();
+        let query = TagQuery { intent: Some(Intent::Support), since: Some(created.created_at), limit: 50, ..Default::default() };
+        let tagged = other.tagged_messages(&query).await.unwrap();
+        assert!(tagged.iter().any(|t| t.message.id == id && t.message.content == "audit acme.com"));
+
+        let mut delivery = crate::channels::delivery::queued(crate::channels::ChannelType::Telegram, &user);
+        store.save_delivery(&delivery).await.unwrap();
+        delivery.status = "sent".to_string();
+        delivery.attempts = 1;
+        store.save_delivery(&delivery).await.unwrap();
+        let saved = other.get_delivery(&delivery.id).await.unwrap().unwrap();
+        assert_eq!((saved.status.as_str(), saved.attempts), ("sent", 1));
+
+        let lead = ContactedLead {
+            business: format!("Acme {}", user),
+            domain: Some("acme.com".to_string()),
+            email: Some("owner@acme.com".to_string()),
+            campaign: Some(user.clone()),
+            action: "email".to_string(),
+            override_of: Some("Acme (acme.com): email on 2026-09-01, same domain".to_string()),
+            created_at: Utc::now(),
+        };
+        store.add_contacted_lead(&lead).await.unwrap();
+        let query = ContactedLeadQuery { since: Some(created.created_at), overridden: true, limit: 50 };
+        let contacts = other.contacted_leads(&query).await.unwrap();
+        let found = contacts.iter().find(|c| c.business == lead.business).unwrap();
+        assert_eq!(found.email.as_deref(), Some("owner@acme.com"));
+
+        store.clear_conversation(&user).await.unwrap();
+        assert!(other.get_conversation(&user, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,456 @@
+//! Store cache - Redis in front of the store for the lookups every turn makes
+//!
+//! Each chat turn resolves the sender's identity and user, reads the recent
+//! conversation and writes the new messages; against the hosted store that's
+//! several round trips before the model is even called. With
+//! `store.cache.enabled`, `CachedStore` keeps the hot part in Redis:
+//! - users and identities, by id
+//! - the newest `store.cache.max_messages` of each conversation and thread
+//!
+//! Writes go to the store first and then to the cache (write-through), so the
+//! store stays the source of truth. Clearing or compressing a conversation
+//! drops its cached messages, and every key expires after `store.cache.ttl_secs`
+//! without use. When Redis is slow or down, calls fall through to the store.
+//!
+//! Cached values are sealed with the store encryption key when
+//! `store.encrypt_messages` is on. Nodes sharing one store must share the Redis
+//! server and `store.cache.key_prefix` too, or their caches go stale.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::StoreCacheConfig;
+use crate::store::{
+    open_with, seal_with, ContactedLead, ContactedLeadQuery, ConversationMessage, Delivery, DeliveryQuery,
+    EmbeddingRecord, Episode, Identity, LlmAuditQuery, LlmBatch, LlmExchange, MessageTags, Preferences, Snooze, Store,
+    TagQuery, TaggedMessage, ToolCallRecord, UsageRecord, User,
+};
+
+/// How long a Redis call may take before the store is asked instead
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Key of the unthreaded messages in a user's thread keys
+const NO_THREAD: &str = "-";
+
+/// A `Store` with its users, identities and recent messages cached in Redis
+pub struct CachedStore {
+    inner: Arc<dyn Store>,
+    conn: ConnectionManager,
+    prefix: String,
+    ttl_secs: i64,
+    max_messages: usize,
+    /// Seals cached values when set
+    content_key: Option<[u8; 32]>,
+}
+
+impl CachedStore {
+    /// Connect to the Redis server in `config.url_env` and cache `inner`
+    pub async fn connect(inner: Arc<dyn Store>, config: &StoreCacheConfig) -> anyhow::Result<Self> {
+        let url = std::env::var(&config.url_env)
+            .map_err(|_| anyhow::anyhow!("Redis URL not found in env: {}", config.url_env))?;
+        let client = redis::Client::open(url)?;
+        let conn = tokio::time::timeout(Duration::from_secs(5), client.get_connection_manager())
+            .await
+            .map_err(|_| anyhow::anyhow!("Timed out connecting to Redis"))??;
+        Ok(Self {
+            inner,
+            conn,
+            prefix: config.key_prefix.clone(),
+            ttl_secs: config.ttl_secs.max(1) as i64,
+            max_messages: config.max_messages.max(1),
+            content_key: None,
+        })
+    }
+
+    /// Seal cached values with the store encryption key
+    pub fn with_content_key(mut self, key: [u8; 32]) -> Self {
+        self.content_key = Some(key);
+        self
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    fn conversation_key(&self, user_id: &str) -> String {
+        self.key(&format!("conv:{}", user_id))
+    }
+
+    fn thread_key(&self, user_id: &str, thread_id: Option<&str>) -> String {
+        self.key(&format!("thread:{}:{}", user_id, thread_id.unwrap_or(NO_THREAD)))
+    }
+
+    /// The set of a user's cached thread keys, to drop them with the conversation
+    fn threads_key(&self, user_id: &str) -> String {
+        self.key(&format!("threads:{}", user_id))
+    }
+
+    /// Run `pipe`; None when Redis fails or takes too long
+    async fn query<T: redis::FromRedisValue>(&self, pipe: &redis::Pipeline) -> Option<T> {
+        let mut conn = self.conn.clone();
+        match tokio::time::timeout(REDIS_TIMEOUT, pipe.query_async::<T>(&mut conn)).await {
+            Ok(Ok(value)) => Some(value),
+            Ok(Err(e)) => {
+                tracing::debug!("Store cache: {}", e);
+                None
+            }
+            Err(_) => {
+                tracing::debug!("Store cache: Redis timed out");
+                None
+            }
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let (value,): (Option<String>,) = self.query(redis::pipe().get(key)).await?;
+        decode(self.content_key.as_ref(), &value?)
+    }
+
+    async fn set<T: Serialize>(&self, key: &str, value: &T) {
+        if let Some(value) = encode(self.content_key.as_ref(), value) {
+            let _: Option<()> = self.query(redis::pipe().set_ex(key, value, self.ttl_secs as u64).ignore()).await;
+        }
+    }
+
+    /// The newest `limit` messages in the list at `key`, or None when the list
+    /// can't answer for them
+    async fn cached_messages(&self, key: &str, limit: usize) -> Option<Vec<ConversationMessage>> {
+        let (values,): (Vec<String>,) = self.query(redis::pipe().lrange(key, 0, -1)).await?;
+        if !serves(values.len(), limit, self.max_messages) {
+            return None;
+        }
+        let skip = values.len().saturating_sub(limit);
+        values[skip..].iter().map(|value| decode(self.content_key.as_ref(), value)).collect()
+    }
+
+    /// Cache the newest of `messages` (all the store has, up to `max_messages`)
+    /// in the list at `key`
+    async fn fill(&self, key: &str, messages: &[ConversationMessage], thread_of: Option<&str>) {
+        let skip = messages.len().saturating_sub(self.max_messages);
+        let values: Option<Vec<String>> =
+            messages[skip..].iter().map(|m| encode(self.content_key.as_ref(), m)).collect();
+        let Some(values) = values.filter(|values| !values.is_empty()) else {
+            return;
+        };
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .del(key)
+            .ignore()
+            .rpush(key, values)
+            .ignore()
+            .expire(key, self.ttl_secs)
+            .ignore();
+        if let Some(user_id) = thread_of {
+            let threads = self.threads_key(user_id);
+            pipe.sadd(&threads, key).ignore().expire(&threads, self.ttl_secs).ignore();
+        }
+        let _: Option<()> = self.query(&pipe).await;
+    }
+
+    /// Messages from the list at `key`, or from `load` (asked for at least
+    /// `max_messages`) filling the list
+    async fn messages<F>(&self, key: &str, limit: usize, thread_of: Option<&str>, load: F) -> anyhow::Result<Vec<ConversationMessage>>
+    where
+        F: std::future::Future<Output = anyhow::Result<Vec<ConversationMessage>>>,
+    {
+        if let Some(messages) = self.cached_messages(key, limit).await {
+            return Ok(messages);
+        }
+        let mut messages = load.await?;
+        self.fill(key, &messages, thread_of).await;
+        let skip = messages.len().saturating_sub(limit);
+        messages.drain(..skip);
+        Ok(messages)
+    }
+
+    /// Drop a user's cached conversation and threads
+    async fn forget_messages(&self, user_id: &str) {
+        let threads = self.threads_key(user_id);
+        let (mut keys,): (Vec<String>,) = self.query(redis::pipe().smembers(&threads)).await.unwrap_or_default();
+        keys.push(threads);
+        keys.push(self.conversation_key(user_id));
+        let _: Option<()> = self.query(redis::pipe().del(keys).ignore()).await;
+    }
+}
+
+/// Whether a cached list of `cached` messages holds the newest `limit`: it has
+/// that many, or it's the whole conversation (lists are capped at `max`)
+fn serves(cached: usize, limit: usize, max: usize) -> bool {
+    cached > 0 && (limit <= cached || cached < max)
+}
+
+fn encode<T: Serialize>(key: Option<&[u8; 32]>, value: &T) -> Option<String> {
+    let json = serde_json::to_string(value).ok()?;
+    seal_with(key, &json).ok()
+}
+
+/// A cached value; None (a miss) when it can't be opened or parsed
+fn decode<T: DeserializeOwned>(key: Option<&[u8; 32]>, value: &str) -> Option<T> {
+    serde_json::from_str(&open_with(key, value.to_string())).ok()
+}
+
+#[async_trait]
+impl Store for CachedStore {
+    async fn get_user(&self, user_id: &str) -> anyhow::Result<Option<User>> {
+        let key = self.key(&format!("user:{}", user_id));
+        if let Some(user) = self.get(&key).await {
+            return Ok(Some(user));
+        }
+        let user = self.inner.get_user(user_id).await?;
+        if let Some(user) = &user {
+            self.set(&key, user).await;
+        }
+        Ok(user)
+    }
+
+    async fn create_user(&self, user_id: &str) -> anyhow::Result<User> {
+        let user = self.inner.create_user(user_id).await?;
+        self.set(&self.key(&format!("user:{}", user_id)), &user).await;
+        Ok(user)
+    }
+
+    async fn get_identity(&self, provider: &str, provider_id: &str) -> anyhow::Result<Option<Identity>> {
+        let key = self.key(&format!("identity:{}:{}", provider, provider_id));
+        if let Some(identity) = self.get(&key).await {
+            return Ok(Some(identity));
+        }
+        let identity = self.inner.get_identity(provider, provider_id).await?;
+        if let Some(identity) = &identity {
+            self.set(&key, identity).await;
+        }
+        Ok(identity)
+    }
+
+    async fn link_identity(&self, user_id: &str, provider: &str, provider_id: &str, username: Option<&str>) -> anyhow::Result<()> {
+        self.inner.link_identity(user_id, provider, provider_id, username).await?;
+        let identity = Identity {
+            user_id: user_id.to_string(),
+            provider: provider.to_string(),
+            provider_id: provider_id.to_string(),
+            username: username.map(str::to_string),
+            linked_at: Utc::now(),
+        };
+        self.set(&self.key(&format!("identity:{}:{}", provider, provider_id)), &identity).await;
+        Ok(())
+    }
+
+    async fn get_conversation(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let load = self.inner.get_conversation(user_id, limit.max(self.max_messages));
+        self.messages(&self.conversation_key(user_id), limit, None, load).await
+    }
+
+    async fn get_thread(&self, user_id: &str, thread_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        let load = self.inner.get_thread(user_id, thread_id, limit.max(self.max_messages));
+        self.messages(&self.thread_key(user_id, thread_id), limit, Some(user_id), load).await
+    }
+
+    async fn add_message(&self, user_id: &str, role: &str, content: &str, channel: &str, tool_calls: Option<&str>, thread_id: Option<&str>) -> anyhow::Result<i64> {
+        let id = self.inner.add_message(user_id, role, content, channel, tool_calls, thread_id).await?;
+        let message = ConversationMessage {
+            id,
+            user_id: user_id.to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            channel: channel.to_string(),
+            tool_calls: tool_calls.map(str::to_string),
+            created_at: Utc::now(),
+        };
+        if let Some(value) = encode(self.content_key.as_ref(), &message) {
+            // Only lists already cached are appended to; a partial list would
+            // pass for the whole conversation
+            let mut pipe = redis::pipe();
+            pipe.atomic();
+            for key in [self.conversation_key(user_id), self.thread_key(user_id, thread_id)] {
+                pipe.rpush_exists(&key, &value)
+                    .ignore()
+                    .ltrim(&key, -(self.max_messages as isize), -1)
+                    .ignore()
+                    .expire(&key, self.ttl_secs)
+                    .ignore();
+            }
+            let _: Option<()> = self.query(&pipe).await;
+        }
+        Ok(id)
+    }
+
+    async fn clear_conversation(&self, user_id: &str) -> anyhow::Result<()> {
+        self.inner.clear_conversation(user_id).await?;
+        self.forget_messages(user_id).await;
+        Ok(())
+    }
+
+    async fn replace_messages(&self, user_id: &str, ids: &[i64], summary: &ConversationMessage) -> anyhow::Result<i64> {
+        let id = self.inner.replace_messages(user_id, ids, summary).await?;
+        self.forget_messages(user_id).await;
+        Ok(id)
+    }
+
+    async fn get_user_identities(&self, user_id: &str) -> anyhow::Result<Vec<Identity>> {
+        self.inner.get_user_identities(user_id).await
+    }
+
+    async fn compressible_users(&self, before: DateTime<Utc>, min_messages: usize) -> anyhow::Result<Vec<String>> {
+        self.inner.compressible_users(before, min_messages).await
+    }
+
+    async fn messages_before(
+        &self,
+        user_id: &str,
+        before: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, i64)>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ConversationMessage>> {
+        self.inner.messages_before(user_id, before, after, limit).await
+    }
+
+    async fn add_episode(&self, episode: &Episode) -> anyhow::Result<()> {
+        self.inner.add_episode(episode).await
+    }
+
+    async fn recent_episodes(&self, user_id: &str, limit: usize) -> anyhow::Result<Vec<Episode>> {
+        self.inner.recent_episodes(user_id, limit).await
+    }
+
+    async fn untagged_messages(&self, limit: usize) -> anyhow::Result<Vec<ConversationMessage>> {
+        self.inner.untagged_messages(limit).await
+    }
+
+    async fn tag_message(&self, message_id: i64, tags: MessageTags) -> anyhow::Result<()> {
+        self.inner.tag_message(message_id, tags).await
+    }
+
+    async fn tagged_messages(&self, query: &TagQuery) -> anyhow::Result<Vec<TaggedMessage>> {
+        self.inner.tagged_messages(query).await
+    }
+
+    async fn add_snooze(&self, channel: &str, chat_id: &str, content: &str, deliver_at: DateTime<Utc>) -> anyhow::Result<i64> {
+        self.inner.add_snooze(channel, chat_id, content, deliver_at).await
+    }
+
+    async fn due_snoozes(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Snooze>> {
+        self.inner.due_snoozes(now).await
+    }
+
+    async fn remove_snooze(&self, id: i64) -> anyhow::Result<()> {
+        self.inner.remove_snooze(id).await
+    }
+
+    async fn get_preferences(&self, user_id: &str) -> anyhow::Result<Option<Preferences>> {
+        self.inner.get_preferences(user_id).await
+    }
+
+    async fn set_preferences(&self, user_id: &str, data: serde_json::Value) -> anyhow::Result<()> {
+        self.inner.set_preferences(user_id, data).await
+    }
+
+    async fn add_llm_batch(&self, batch: &LlmBatch) -> anyhow::Result<()> {
+        self.inner.add_llm_batch(batch).await
+    }
+
+    async fn open_llm_batches(&self) -> anyhow::Result<Vec<LlmBatch>> {
+        self.inner.open_llm_batches().await
+    }
+
+    async fn end_llm_batch(&self, id: &str, status: &str) -> anyhow::Result<()> {
+        self.inner.end_llm_batch(id, status).await
+    }
+
+    async fn add_usage(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        self.inner.add_usage(record).await
+    }
+
+    async fn usage_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<UsageRecord>> {
+        self.inner.usage_since(since).await
+    }
+
+    async fn add_tool_call(&self, record: &ToolCallRecord) -> anyhow::Result<()> {
+        self.inner.add_tool_call(record).await
+    }
+
+    async fn tool_calls_since(&self, since: DateTime<Utc>) -> anyhow::Result<Vec<ToolCallRecord>> {
+        self.inner.tool_calls_since(since).await
+    }
+
+    async fn embeddings(&self, model: &str, hashes: &[String]) -> anyhow::Result<Vec<EmbeddingRecord>> {
+        self.inner.embeddings(model, hashes).await
+    }
+
+    async fn add_embeddings(&self, model: &str, records: &[EmbeddingRecord]) -> anyhow::Result<()> {
+        self.inner.add_embeddings(model, records).await
+    }
+
+    async fn add_llm_exchange(&self, exchange: &LlmExchange) -> anyhow::Result<()> {
+        self.inner.add_llm_exchange(exchange).await
+    }
+
+    async fn llm_exchanges(&self, query: &LlmAuditQuery) -> anyhow::Result<Vec<LlmExchange>> {
+        self.inner.llm_exchanges(query).await
+    }
+
+    async fn prune_llm_exchanges(&self, keep: usize, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        self.inner.prune_llm_exchanges(keep, before).await
+    }
+
+    async fn save_delivery(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        self.inner.save_delivery(delivery).await
+    }
+
+    async fn get_delivery(&self, id: &str) -> anyhow::Result<Option<Delivery>> {
+        self.inner.get_delivery(id).await
+    }
+
+    async fn deliveries(&self, query: &DeliveryQuery) -> anyhow::Result<Vec<Delivery>> {
+        self.inner.deliveries(query).await
+    }
+
+    async fn prune_deliveries(&self, before: DateTime<Utc>) -> anyhow::Result<usize> {
+        self.inner.prune_deliveries(before).await
+    }
+
+    async fn add_contacted_lead(&self, lead: &ContactedLead) -> anyhow::Result<()> {
+        self.inner.add_contacted_lead(lead).await
+    }
+
+    async fn contacted_leads(&self, query: &ContactedLeadQuery) -> anyhow::Result<Vec<ContactedLead>> {
+        self.inner.contacted_leads(query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serving_and_sealed_values() {
+        // A full list answers for up to its length; a short one is everything
+        assert!(serves(100, 30, 100));
+        assert!(!serves(100, 200, 100));
+        assert!(serves(12, 30, 100));
+        assert!(!serves(0, 30, 100));
+
+        let message = ConversationMessage {
+            id: 7,
+            user_id: "u1".to_string(),
+            role: "user".to_string(),
+            content: "call me at 555-0100".to_string(),
+            channel: "telegram".to_string(),
+            tool_calls: None,
+            created_at: Utc::now(),
+        };
+        let key = [9u8; 32];
+        let sealed = encode(Some(&key), &message).unwrap();
+        assert!(!sealed.contains("555-0100"));
+        let opened: ConversationMessage = decode(Some(&key), &sealed).unwrap();
+        assert_eq!((opened.id, opened.content.as_str()), (7, "call me at 555-0100"));
+        // A value sealed with another key is a miss
+        assert!(decode::<ConversationMessage>(Some(&[1u8; 32]), &sealed).is_none());
+        let plain = encode(None, &message).unwrap();
+        assert!(decode::<ConversationMessage>(None, &plain).is_some());
+    }
+}
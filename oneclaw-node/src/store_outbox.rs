@@ -0,0 +1,217 @@
+//! Store outbox - Hosted store writes kept locally while offline
+//!
+//! When the control plane can't be reached (no connection, a timeout, or a
+//! 502/503/504 from its proxy), `HostedStore` puts the write here instead of
+//! losing it: messages, identities, preferences, episodes, telemetry. The
+//! outbox is a table in a local SQLite file (`store.outbox_path`, in WAL mode)
+//! and survives restarts.
+//!
+//! Queued writes are replayed in the order they were made, as soon as the
+//! control plane answers again: in the background, before the next write, and
+//! before conversation reads, so a turn never reads history with gaps. While
+//! anything is queued, new writes queue behind it to keep that order.
+//!
+//! Conflicts are settled by time:
+//! - each replayed write carries `X-OneClaw-Written-At`, when it was made; a
+//!   409 means the control plane has something newer and the write is dropped
+//! - a queued replacement (PUT) of the same resource, e.g. a user's
+//!   preferences, replaces the earlier one, so only the latest is replayed
+//!
+//! With `store.encrypt_messages`, queued bodies are sealed with the store key.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{DateTime, Utc};
+use tokio_rusqlite::Connection;
+
+use crate::store::{open_with, seal_with};
+
+/// Writes kept at most; past this, writes fail as they did without an outbox
+const MAX_QUEUED: usize = 50_000;
+
+/// A write waiting for the control plane
+#[derive(Debug, Clone)]
+pub struct QueuedWrite {
+    pub id: i64,
+    pub method: String,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+    pub queued_at: DateTime<Utc>,
+}
+
+pub struct Outbox {
+    conn: Connection,
+    /// Writes in the table, so the hot path needn't ask SQLite
+    pending: AtomicUsize,
+    /// Held while replaying, so writes are replayed once and in order
+    replaying: tokio::sync::Mutex<()>,
+    /// Seals queued bodies when set
+    content_key: Option<[u8; 32]>,
+}
+
+impl Outbox {
+    pub async fn open(path: PathBuf) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path).await?;
+        let pending = conn
+            .call(|conn| {
+                conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+                conn.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS pending_writes (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        method TEXT NOT NULL,
+                        path TEXT NOT NULL,
+                        body TEXT,
+                        queued_at TEXT NOT NULL
+                    );",
+                )?;
+                let count: i64 = conn.query_row("SELECT COUNT(*) FROM pending_writes", [], |row| row.get(0))?;
+                Ok(count as usize)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(Self {
+            conn,
+            pending: AtomicUsize::new(pending),
+            replaying: tokio::sync::Mutex::new(()),
+            content_key: None,
+        })
+    }
+
+    /// Seal queued bodies from now on
+    pub fn with_content_key(mut self, key: [u8; 32]) -> Self {
+        self.content_key = Some(key);
+        self
+    }
+
+    /// Writes waiting
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// The replay lock, or None when a replay is already running
+    pub fn start_replay(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        self.replaying.try_lock().ok()
+    }
+
+    /// Queue a write; a PUT replaces the PUTs to the same path queued before it
+    pub async fn push(&self, method: &str, path: &str, body: Option<&serde_json::Value>) -> anyhow::Result<()> {
+        if self.pending() >= MAX_QUEUED {
+            anyhow::bail!("The store outbox is full ({} writes); the control plane has been unreachable too long", MAX_QUEUED);
+        }
+        let body = body.map(|body| seal_with(self.content_key.as_ref(), &body.to_string())).transpose()?;
+        let method = method.to_string();
+        let path = path.to_string();
+        let now = Utc::now().to_rfc3339();
+        let pending = self
+            .conn
+            .call(move |conn| {
+                if method == "PUT" {
+                    conn.execute("DELETE FROM pending_writes WHERE method = 'PUT' AND path = ?", [&path])?;
+                }
+                conn.execute(
+                    "INSERT INTO pending_writes (method, path, body, queued_at) VALUES (?, ?, ?, ?)",
+                    rusqlite::params![method, path, body, now],
+                )?;
+                let count: i64 = conn.query_row("SELECT COUNT(*) FROM pending_writes", [], |row| row.get(0))?;
+                Ok(count as usize)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        self.pending.store(pending, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// The oldest `limit` queued writes, oldest first
+    pub async fn oldest(&self, limit: usize) -> anyhow::Result<Vec<QueuedWrite>> {
+        let rows = self
+            .conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare("SELECT id, method, path, body, queued_at FROM pending_writes ORDER BY id LIMIT ?")?;
+                let rows = stmt.query_map([limit], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                })?;
+                Ok(rows.collect::<Result<Vec<_>, _>>()?)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, method, path, body, queued_at)| QueuedWrite {
+                id,
+                method,
+                path,
+                body: body.and_then(|body| serde_json::from_str(&open_with(self.content_key.as_ref(), body)).ok()),
+                queued_at: DateTime::parse_from_rfc3339(&queued_at).map(|t| t.with_timezone(&Utc)).unwrap_or_else(|_| Utc::now()),
+            })
+            .collect())
+    }
+
+    /// Take a write off the queue (replayed, or dropped)
+    pub async fn remove(&self, id: i64) -> anyhow::Result<()> {
+        let removed = self
+            .conn
+            .call(move |conn| Ok(conn.execute("DELETE FROM pending_writes WHERE id = ?", [id])?))
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        if removed > 0 {
+            let _ = self.pending.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_sub(1)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_queue_order_coalescing_and_sealing() {
+        let path = std::env::temp_dir().join(format!("oneclaw-outbox-test-{}.db", nanoid::nanoid!()));
+        let outbox = Outbox::open(path.clone()).await.unwrap().with_content_key([7u8; 32]);
+        outbox.push("POST", "/api/v1/users/u1/conversations", Some(&json!({ "content": "call me at 555-0100" }))).await.unwrap();
+        outbox.push("PUT", "/api/v1/users/u1/preferences", Some(&json!({ "tone": "formal" }))).await.unwrap();
+        outbox.push("DELETE", "/api/v1/snoozes/3", None).await.unwrap();
+        outbox.push("PUT", "/api/v1/users/u1/preferences", Some(&json!({ "tone": "casual" }))).await.unwrap();
+        assert_eq!(outbox.pending(), 3);
+
+        let writes = outbox.oldest(10).await.unwrap();
+        let order: Vec<(&str, &str)> = writes.iter().map(|w| (w.method.as_str(), w.path.as_str())).collect();
+        assert_eq!(
+            order,
+            vec![
+                ("POST", "/api/v1/users/u1/conversations"),
+                ("DELETE", "/api/v1/snoozes/3"),
+                ("PUT", "/api/v1/users/u1/preferences"),
+            ]
+        );
+        assert_eq!(writes[0].body.as_ref().unwrap()["content"], "call me at 555-0100");
+        assert_eq!(writes[2].body.as_ref().unwrap()["tone"], "casual");
+        let on_disk: Vec<u8> = [path.clone(), path.with_extension("db-wal")]
+            .iter()
+            .filter_map(|file| std::fs::read(file).ok())
+            .flatten()
+            .collect();
+        assert!(!on_disk.is_empty() && !on_disk.windows(8).any(|w| w == b"555-0100"));
+
+        outbox.remove(writes[0].id).await.unwrap();
+        drop(outbox);
+        // Still queued after a restart
+        let reopened = Outbox::open(path.clone()).await.unwrap();
+        assert_eq!(reopened.pending(), 2);
+        drop(reopened);
+        for extension in ["db", "db-wal", "db-shm"] {
+            let _ = std::fs::remove_file(path.with_extension(extension));
+        }
+    }
+}
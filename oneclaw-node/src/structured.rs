@@ -0,0 +1,252 @@
+//! Structured Output - JSON answers for API consumers
+//!
+//! A `/chat` request with a `response_format` (a JSON schema) gets its reply as
+//! data too. Once the turn has its final reply, the model restates it through a
+//! `respond` tool whose input schema is the requested one, so providers with
+//! native tool use return JSON instead of prose.
+//!
+//! The answer is checked against the schema. Small mismatches are repaired
+//! locally: numbers and booleans sent as strings, or a single item where an
+//! array was asked for. Anything else goes back to the model once with the
+//! problems listed. Checks cover the common keywords: `type`, `properties`,
+//! `required`, `additionalProperties: false`, `items` and `enum`.
+//!
+//! The same repair and checks screen the arguments of harness tool calls
+//! against the tool's `params_schema` before they're sent (`daemon.rs`).
+
+use serde_json::{json, Value};
+
+use crate::config::LlmConfig;
+use crate::model_router::{self, ModelRouter};
+
+const TOOL_NAME: &str = "respond";
+
+/// Key a non-object schema is wrapped under (tool inputs are always objects)
+const WRAPPED: &str = "value";
+
+const MAX_TOKENS: u32 = 4096;
+
+/// The structured answer, or the problems left after repair
+#[derive(Debug, Clone, Default)]
+pub struct Structured {
+    pub value: Option<Value>,
+    pub errors: Vec<String>,
+    /// LLM calls made
+    pub llm_calls: u32,
+}
+
+/// Restate `reply`, the answer to `messages` after the turn's tool `results`,
+/// as JSON matching `schema`
+pub async fn respond(router: &ModelRouter, default: &LlmConfig, messages: &[Value], results: &[(&str, &Value)], reply: &str, schema: &Value) -> Structured {
+    let wraps = schema["type"] != "object";
+    let input_schema = if wraps {
+        json!({ "type": "object", "properties": { WRAPPED: schema }, "required": [WRAPPED] })
+    } else {
+        schema.clone()
+    };
+    let tool = json!({
+        "name": TOOL_NAME,
+        "description": "Give your answer as structured data matching this schema.",
+        "input_schema": input_schema,
+    });
+
+    let mut messages = messages.to_vec();
+    for (tool, output) in results {
+        messages.push(json!({
+            "role": "system",
+            "content": format!("[Tool Result: {}]\n{}", tool, crate::followup::truncated_json(output)),
+        }));
+    }
+    messages.push(json!({ "role": "assistant", "content": reply }));
+    messages.push(json!({
+        "role": "user",
+        "content": format!("Now give that answer through the `{}` tool. Use only facts from this conversation.", TOOL_NAME),
+    }));
+
+    let mut structured = Structured::default();
+    for attempt in 0..2 {
+        structured.llm_calls += 1;
+        let input = match router.call_tool(model_router::MAIN, default, &messages, MAX_TOKENS, &tool).await {
+            Ok(input) => input,
+            Err(e) => {
+                structured.errors = vec![format!("Structured output failed: {}", e)];
+                return structured;
+            }
+        };
+        let mut value = if wraps { input[WRAPPED].clone() } else { input.clone() };
+        repair(schema, &mut value);
+        let errors = validate(schema, &value);
+        if errors.is_empty() || attempt == 1 {
+            structured.value = Some(value);
+            structured.errors = errors;
+            break;
+        }
+
+        tracing::info!("Structured output didn't match the schema, asking again: {}", errors.join("; "));
+        messages.push(json!({ "role": "assistant", "content": input.to_string() }));
+        messages.push(json!({
+            "role": "user",
+            "content": format!(
+                "That doesn't match the schema:\n- {}\nCall `{}` again with a corrected answer.",
+                errors.join("\n- "),
+                TOOL_NAME
+            ),
+        }));
+    }
+    structured
+}
+
+/// Where `value` breaks `schema`, one message per problem (empty when it matches)
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "$", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if !types(schema).is_empty() && !types(schema).iter().any(|t| is_type(value, t)) {
+        errors.push(format!("{}: expected {}, got {}", path, types(schema).join(" or "), type_name(value)));
+        return;
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} is not one of {}", path, value, Value::Array(allowed.clone())));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        for key in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(key) {
+                errors.push(format!("{}: missing required field {:?}", path, key));
+            }
+        }
+        for (key, field) in object {
+            match schema["properties"].get(key) {
+                Some(property) => check(property, field, &format!("{}.{}", path, key), errors),
+                None if schema["additionalProperties"] == false => {
+                    errors.push(format!("{}: unexpected field {:?}", path, key));
+                }
+                None => {}
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            check(items, item, &format!("{}[{}]", path, i), errors);
+        }
+    }
+}
+
+/// Fix what the model commonly gets almost right, in place
+pub fn repair(schema: &Value, value: &mut Value) {
+    let allowed = types(schema);
+    if !allowed.is_empty() && !allowed.iter().any(|t| is_type(value, t)) {
+        if let Some(coerced) = allowed.iter().find_map(|t| coerce(value, t, schema)) {
+            *value = coerced;
+        }
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        for (key, field) in object.iter_mut() {
+            if let Some(property) = schema["properties"].get(key) {
+                repair(property, field);
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array_mut()) {
+        for item in array {
+            repair(items, item);
+        }
+    }
+}
+
+fn coerce(value: &Value, to: &str, schema: &Value) -> Option<Value> {
+    let text = value.as_str().map(str::trim);
+    match to {
+        "integer" => text?.parse::<i64>().ok().map(Value::from),
+        "number" => text?.parse::<f64>().ok().map(Value::from),
+        "boolean" => text?.parse::<bool>().ok().map(Value::from),
+        "string" if value.is_number() || value.is_boolean() => Some(Value::from(value.to_string())),
+        "array" if !value.is_null() && types(&schema["items"]).iter().any(|t| is_type(value, t) || coerce(value, t, &schema["items"]).is_some()) => {
+            Some(json!([value]))
+        }
+        _ => None,
+    }
+}
+
+/// The schema's `type` keyword as a list (empty when any type goes)
+fn types(schema: &Value) -> Vec<&str> {
+    match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    }
+}
+
+fn is_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_repair() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer" },
+                "status": { "type": "string", "enum": ["done", "running"] },
+                "leads": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" }, "rating": { "type": "number" } },
+                        "required": ["name"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["count", "leads"]
+        });
+
+        let good = json!({ "count": 2, "status": "done", "leads": [{ "name": "Apex Roofing", "rating": 4.8 }] });
+        assert!(validate(&schema, &good).is_empty());
+
+        let mut close = json!({ "count": "2", "leads": { "name": "Apex Roofing", "rating": "4.8" } });
+        repair(&schema, &mut close);
+        assert_eq!(close, json!({ "count": 2, "leads": [{ "name": "Apex Roofing", "rating": 4.8 }] }));
+
+        let bad = json!({ "count": 2.5, "status": "lost", "leads": [{ "rating": 4, "phone": "555" }] });
+        assert_eq!(
+            validate(&schema, &bad),
+            vec![
+                "$.count: expected integer, got number",
+                "$.leads[0]: missing required field \"name\"",
+                "$.leads[0]: unexpected field \"phone\"",
+                "$.status: \"lost\" is not one of [\"done\",\"running\"]",
+            ]
+        );
+    }
+}
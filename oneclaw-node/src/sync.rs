@@ -0,0 +1,134 @@
+//! Brain Sync - Encrypted backup of Agent OS + preferences to the control plane
+//!
+//! Opt-in via `sync.enabled` in node.yaml. Everything is encrypted locally with a
+//! passphrase from `sync.passphrase_env` before upload, so the control plane only
+//! ever stores ciphertext. A reinstalled node restores its learned persona with:
+//!
+//! ```bash
+//! oneclaw sync restore --from-node <old-node-id>
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{agent_os, config, crypto, memory, node_key};
+
+/// Plaintext contents of a sync bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrainBundle {
+    pub node_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub agent_os: agent_os::AgentOS,
+    pub preferences: memory::Preferences,
+}
+
+/// What the control plane stores
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredBackup {
+    created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    blob: crypto::SealedBlob,
+}
+
+/// The sync passphrase from `env` (also seals node bundles)
+pub fn passphrase(env: &str) -> anyhow::Result<String> {
+    let value = std::env::var(env).map_err(|_| {
+        anyhow::anyhow!("Sync passphrase not found in env: {}", env)
+    })?;
+    if value.trim().is_empty() {
+        anyhow::bail!("Sync passphrase in {} is empty", env);
+    }
+    Ok(value)
+}
+
+fn backup_url(config: &config::NodeConfig, node_id: &str) -> anyhow::Result<String> {
+    let base = config.control_plane.url.as_deref()
+        .ok_or_else(|| anyhow::anyhow!("control_plane.url not configured"))?;
+    Ok(format!("{}/api/v1/nodes/{}/brain-backup", base, node_id))
+}
+
+/// Encrypt the current brain and preferences and upload them
+pub async fn push(config: &config::NodeConfig) -> anyhow::Result<()> {
+    let bundle = BrainBundle {
+        node_id: config.node.id.clone(),
+        created_at: chrono::Utc::now(),
+        agent_os: agent_os::AgentOS::load(config.node.workspace.as_deref().map(config::expand_path))?,
+        preferences: memory::load_preferences(config).unwrap_or_default(),
+    };
+
+    let plaintext = serde_json::to_vec(&bundle)?;
+    let blob = crypto::seal_with_passphrase(&passphrase(&config.sync.passphrase_env)?, &plaintext)?;
+    let body = serde_json::to_vec(&StoredBackup { created_at: bundle.created_at, blob })?;
+
+    let url = backup_url(config, &config.node.id)?;
+    let mut req = reqwest::Client::new()
+        .put(&url)
+        .header("Content-Type", "application/json");
+    if let Some(token) = &config.control_plane.token {
+        req = req.header("Authorization", format!("Bearer {}", token));
+    }
+    for (name, value) in node_key::signed_headers(&config.node.id, "PUT", &url, &body) {
+        req = req.header(name, value);
+    }
+
+    let resp = req.body(body).send().await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        anyhow::bail!("Brain backup upload failed ({}): {}", status, text);
+    }
+
+    tracing::info!("☁️ Brain backup uploaded ({} bytes encrypted)", plaintext.len());
+    Ok(())
+}
+
+/// Download and decrypt a backup (from this node or a previous one)
+pub async fn fetch(config: &config::NodeConfig, from_node: Option<&str>) -> anyhow::Result<BrainBundle> {
+    let node_id = from_node.unwrap_or(&config.node.id);
+    let url = backup_url(config, node_id)?;
+
+    let mut req = reqwest::Client::new().get(&url);
+    if let Some(token) = &config.control_plane.token {
+        req = req.header("Authorization", format!("Bearer {}", token));
+    }
+    for (name, value) in node_key::signed_headers(&config.node.id, "GET", &url, &[]) {
+        req = req.header(name, value);
+    }
+
+    let resp = req.send().await?;
+    if resp.status() == 404 {
+        anyhow::bail!("No brain backup found for node {}", node_id);
+    }
+    if !resp.status().is_success() {
+        anyhow::bail!("Brain backup download failed ({})", resp.status());
+    }
+
+    let stored: StoredBackup = resp.json().await?;
+    let plaintext = crypto::open_with_passphrase(&passphrase(&config.sync.passphrase_env)?, &stored.blob)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Restore a backup into the local workspace and preferences file
+pub async fn restore(config: &config::NodeConfig, from_node: Option<&str>) -> anyhow::Result<BrainBundle> {
+    let mut bundle = fetch(config, from_node).await?;
+    bundle.agent_os.dir = config.node.workspace.as_deref().map(config::expand_path);
+    bundle.agent_os.save()?;
+    memory::save_preferences(config, &bundle.preferences)?;
+    tracing::info!(
+        "🧠 Restored brain from node {} (backup {})",
+        bundle.node_id,
+        bundle.created_at.to_rfc3339()
+    );
+    Ok(bundle)
+}
+
+/// Push in the background after the brain changes, if sync is enabled
+pub fn push_in_background(config: &'static config::NodeConfig) {
+    if !config.sync.enabled {
+        return;
+    }
+    tokio::spawn(async move {
+        if let Err(e) = push(config).await {
+            tracing::warn!("Brain sync failed: {}", e);
+        }
+    });
+}
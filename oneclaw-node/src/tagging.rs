@@ -0,0 +1,216 @@
+//! Conversation Tagging
+//!
+//! A background pass that labels stored user messages with an intent
+//! (lead-gen, support, scheduling, other) and a sentiment. Untagged messages
+//! are sent to a small model in batches through a forced `submit_tags` tool
+//! call; the tags feed `GET /analytics/conversations`. With `batch.enabled` the
+//! calls go through the Message Batches API instead (see `batch.rs`).
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::batch::{self, BatchQueue};
+use crate::config::TaggingConfig;
+use crate::model_router::ModelRouter;
+use crate::store::{ConversationMessage, MessageTags, Store, TaggedMessage};
+
+/// Longest excerpt of a message sent for classification
+const MAX_EXCERPT_CHARS: usize = 500;
+
+const SUBMIT_TAGS: &str = "submit_tags";
+
+/// `submit_tags` tool the classifier must call
+fn submit_tags_tool() -> serde_json::Value {
+    serde_json::json!({
+        "name": SUBMIT_TAGS,
+        "description": "Submit one intent and sentiment label per message",
+        "input_schema": {
+            "type": "object",
+            "properties": {
+                "tags": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "integer" },
+                            "intent": { "type": "string", "enum": ["lead_gen", "support", "scheduling", "other"] },
+                            "sentiment": { "type": "string", "enum": ["positive", "neutral", "negative", "frustrated"] }
+                        },
+                        "required": ["id", "intent", "sentiment"]
+                    }
+                }
+            },
+            "required": ["tags"]
+        }
+    })
+}
+
+/// Classification prompt listing `messages` by id
+fn prompt(messages: &[ConversationMessage]) -> String {
+    let listing: String = messages
+        .iter()
+        .map(|m| format!("[{}] ({}) {}\n", m.id, m.channel, m.content.chars().take(MAX_EXCERPT_CHARS).collect::<String>()))
+        .collect();
+
+    format!(
+        r#"Label each message a user sent to a business assistant.
+
+intent:
+- lead_gen: finding, enriching, auditing or contacting prospects
+- support: something is broken, confusing or not working as expected
+- scheduling: meetings, calls, reminders, calendar changes
+- other: anything else
+
+sentiment: positive, neutral, negative, or frustrated (repeated asks, complaints, annoyance)
+
+Messages:
+{}
+Submit one label per message id with the submit_tags tool."#,
+        listing
+    )
+}
+
+/// Tags from a `submit_tags` input, for the listed message ids only
+pub fn parse_tags(input: &serde_json::Value, ids: &[i64]) -> Vec<(i64, MessageTags)> {
+    input["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| {
+            let id = tag["id"].as_i64()?;
+            let tags: MessageTags = serde_json::from_value(tag.clone()).ok()?;
+            ids.contains(&id).then_some((id, tags))
+        })
+        .collect()
+}
+
+/// Classify a batch of messages in one call; messages the model skipped are left out
+pub async fn classify(
+    router: &ModelRouter,
+    messages: &[ConversationMessage],
+    model: &str,
+) -> anyhow::Result<Vec<(i64, MessageTags)>> {
+    let input = crate::autonomous_jobs::planner_tool_call(
+        router,
+        crate::model_router::TAGGING,
+        &prompt(messages),
+        model,
+        2000,
+        &submit_tags_tool(),
+    )
+    .await?;
+    let ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+    Ok(parse_tags(&input, &ids))
+}
+
+/// The same classification as a Messages API body for a batch
+pub fn batch_params(messages: &[ConversationMessage], model: &str) -> serde_json::Value {
+    let mut request = crate::llm::ChatRequest::new(
+        model,
+        vec![serde_json::json!({ "role": "user", "content": prompt(messages) })],
+        vec![submit_tags_tool()],
+    );
+    request.max_tokens = 2000;
+    request.tool_choice = Some(SUBMIT_TAGS.to_string());
+    crate::llm::anthropic_body(&request, true)
+}
+
+/// The `submit_tags` input in a batched classification reply
+pub fn submitted_tags(response: &crate::llm::ChatResponse) -> Option<&serde_json::Value> {
+    response.tool_calls.iter().find(|call| call.name == SUBMIT_TAGS).map(|call| &call.input)
+}
+
+/// Tag new messages every `interval_secs` until the process exits. With
+/// `batches`, each pass is queued for the next batch instead (one in flight at a time).
+pub async fn run(store: Arc<dyn Store>, router: Arc<ModelRouter>, config: TaggingConfig, batches: Option<Arc<BatchQueue>>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.interval_secs.max(30)));
+
+    loop {
+        interval.tick().await;
+
+        if std::env::var("ANTHROPIC_API_KEY").unwrap_or_default().is_empty() && router.routed().is_none() {
+            continue;
+        }
+        let messages = match store.untagged_messages(config.batch_size.max(1)).await {
+            Ok(messages) if !messages.is_empty() => messages,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!("Tagging: could not load messages: {}", e);
+                continue;
+            }
+        };
+
+        if let Some(batches) = &batches {
+            match batches.in_flight(store.as_ref(), batch::TAGGING).await {
+                Ok(false) => {
+                    let ids: Vec<i64> = messages.iter().map(|m| m.id).collect();
+                    batches.push(batch::TAGGING, batch_params(&messages, &config.model), serde_json::json!(ids));
+                }
+                Ok(true) => {}
+                Err(e) => tracing::warn!("Tagging: could not check open batches: {}", e),
+            }
+            continue;
+        }
+
+        match classify(&router, &messages, &config.model).await {
+            Ok(tags) => {
+                for (id, tags) in &tags {
+                    if let Err(e) = store.tag_message(*id, *tags).await {
+                        tracing::warn!("Tagging: could not save tags for message {}: {}", id, e);
+                    }
+                }
+                tracing::debug!("🏷️ Tagged {}/{} messages", tags.len(), messages.len());
+            }
+            Err(e) => tracing::warn!("Tagging pass failed: {}", e),
+        }
+    }
+}
+
+/// Window start from `7d`, `24h`, `30m` or an RFC 3339 timestamp
+pub fn parse_since(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+    let (amount, unit): (&str, fn(i64) -> Option<Duration>) = if let Some(n) = value.strip_suffix('d') {
+        (n, Duration::try_days)
+    } else if let Some(n) = value.strip_suffix('h') {
+        (n, Duration::try_hours)
+    } else if let Some(n) = value.strip_suffix('m') {
+        (n, Duration::try_minutes)
+    } else {
+        return None;
+    };
+    let span = unit(amount.parse().ok()?)?;
+    Utc::now().checked_sub_signed(span)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConversationAnalytics {
+    pub since: Option<DateTime<Utc>>,
+    pub total: usize,
+    pub by_intent: BTreeMap<String, usize>,
+    pub by_sentiment: BTreeMap<String, usize>,
+    pub by_channel: BTreeMap<String, usize>,
+    /// Most recent matches first, capped at the requested limit
+    pub messages: Vec<TaggedMessage>,
+}
+
+/// Counts over every match, plus the newest `limit` of them
+pub fn summarize(mut messages: Vec<TaggedMessage>, since: Option<DateTime<Utc>>, limit: usize) -> ConversationAnalytics {
+    let label = |tag: serde_json::Value| tag.as_str().unwrap_or_default().to_string();
+    let mut by_intent = BTreeMap::new();
+    let mut by_sentiment = BTreeMap::new();
+    let mut by_channel = BTreeMap::new();
+    for m in &messages {
+        *by_intent.entry(label(serde_json::json!(m.tags.intent))).or_insert(0) += 1;
+        *by_sentiment.entry(label(serde_json::json!(m.tags.sentiment))).or_insert(0) += 1;
+        *by_channel.entry(m.message.channel.clone()).or_insert(0) += 1;
+    }
+
+    let total = messages.len();
+    messages.truncate(limit);
+    ConversationAnalytics { since, total, by_intent, by_sentiment, by_channel, messages }
+}
@@ -0,0 +1,218 @@
+//! Territories - User-defined named service areas
+//!
+//! A territory is a named set of zip codes, cities and/or radii, stored in
+//! preferences:
+//!
+//! ```yaml
+//! territories:
+//!   - name: north-denver
+//!     zips: ["80221", "80229", "80233"]
+//!     cities: ["Thornton, CO", "Northglenn, CO"]
+//!     radii:
+//!       - { lat: 39.868, lng: -104.972, miles: 10 }
+//! restrict_to_territories: true
+//! ```
+//!
+//! Tools and plans can take `"territory": "north-denver"` instead of a location;
+//! it is expanded to concrete locations before execution. With
+//! `restrict_to_territories`, planner-generated locations outside every
+//! territory are rejected.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::autonomous_jobs::JobPlan;
+use crate::memory::Preferences;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Radius {
+    pub lat: f64,
+    pub lng: f64,
+    pub miles: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Territory {
+    pub name: String,
+    #[serde(default)]
+    pub zips: Vec<String>,
+    #[serde(default)]
+    pub cities: Vec<String>,
+    #[serde(default)]
+    pub radii: Vec<Radius>,
+}
+
+impl Territory {
+    /// Concrete search locations for discovery tools (cities, then zips)
+    pub fn locations(&self) -> Vec<String> {
+        self.cities.iter().chain(self.zips.iter()).cloned().collect()
+    }
+
+    /// Whether a free-text location falls inside this territory.
+    /// Matches zip codes, city names (ignoring state suffix) and "lat,lng" coordinates.
+    pub fn contains(&self, location: &str) -> bool {
+        let location = location.trim();
+        if location.is_empty() {
+            return false;
+        }
+
+        if let Some((lat, lng)) = parse_coordinates(location) {
+            return self.radii.iter().any(|r| haversine_miles(r.lat, r.lng, lat, lng) <= r.miles);
+        }
+
+        if let Some(zip) = extract_zip(location) {
+            if self.zips.iter().any(|z| z.trim() == zip) {
+                return true;
+            }
+        }
+
+        let city = city_part(location);
+        self.cities.iter().any(|c| city_part(c) == city)
+    }
+}
+
+/// Look up a territory by name (case-insensitive, spaces and dashes equivalent)
+pub fn find<'a>(territories: &'a [Territory], name: &str) -> Option<&'a Territory> {
+    let wanted = normalize_name(name);
+    territories.iter().find(|t| normalize_name(&t.name) == wanted)
+}
+
+/// Validate territory definitions before saving
+pub fn validate(territories: &[Territory]) -> anyhow::Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    for t in territories {
+        if t.name.trim().is_empty() {
+            anyhow::bail!("Territory name cannot be empty");
+        }
+        if !seen.insert(normalize_name(&t.name)) {
+            anyhow::bail!("Duplicate territory: {}", t.name);
+        }
+        if t.zips.is_empty() && t.cities.is_empty() && t.radii.is_empty() {
+            anyhow::bail!("Territory '{}' needs at least one zip, city or radius", t.name);
+        }
+        for r in &t.radii {
+            if !(-90.0..=90.0).contains(&r.lat) || !(-180.0..=180.0).contains(&r.lng) || r.miles <= 0.0 {
+                anyhow::bail!("Territory '{}' has an invalid radius", t.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Expand `"territory": "<name>"` in tool params into concrete locations.
+/// Leaves params untouched when no territory is referenced.
+pub fn resolve_params(params: &mut Value, territories: &[Territory]) -> anyhow::Result<()> {
+    let Some(name) = params.get("territory").and_then(|v| v.as_str()).map(str::to_string) else {
+        return Ok(());
+    };
+    let territory = find(territories, &name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown territory: {}", name))?;
+
+    let locations = territory.locations();
+    if let Some(obj) = params.as_object_mut() {
+        if !obj.contains_key("location") {
+            if let Some(first) = locations.first() {
+                obj.insert("location".to_string(), Value::String(first.clone()));
+            }
+        }
+        obj.insert("locations".to_string(), serde_json::json!(locations));
+        if !territory.radii.is_empty() {
+            obj.insert("radii".to_string(), serde_json::json!(territory.radii));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve territory references in every plan step and, if the user restricts
+/// searches to their territories, reject steps whose locations fall outside them.
+pub fn apply_to_plan(plan: &mut JobPlan, prefs: &Preferences) -> anyhow::Result<()> {
+    for step in &mut plan.steps {
+        resolve_params(&mut step.params, &prefs.territories)?;
+    }
+
+    if !prefs.restrict_to_territories || prefs.territories.is_empty() {
+        return Ok(());
+    }
+
+    let mut outside = vec![];
+    for step in &plan.steps {
+        for location in step_locations(&step.params) {
+            if !prefs.territories.iter().any(|t| t.contains(&location)) {
+                outside.push(format!("step {} ({}): {}", step.order, step.action, location));
+            }
+        }
+    }
+
+    if !outside.is_empty() {
+        anyhow::bail!(
+            "Plan searches outside your territories ({}): {}",
+            prefs.territories.iter().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", "),
+            outside.join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// System prompt block listing the user's territories, or None if they have none
+pub fn prompt_block(prefs: &Preferences) -> Option<String> {
+    if prefs.territories.is_empty() {
+        return None;
+    }
+
+    let mut block = String::from(
+        "## Territories\nThe user's named service areas. When they mention one, pass \
+         `\"territory\": \"<name>\"` to discovery tools instead of a location.\n",
+    );
+    for t in &prefs.territories {
+        block.push_str(&format!("- {}: {}", t.name, t.locations().join(", ")));
+        if !t.radii.is_empty() {
+            block.push_str(&format!(" (+{} radius area(s))", t.radii.len()));
+        }
+        block.push('\n');
+    }
+    if prefs.restrict_to_territories {
+        block.push_str("Only search inside these territories.\n");
+    }
+    Some(block)
+}
+
+fn step_locations(params: &Value) -> Vec<String> {
+    let mut locations = vec![];
+    if let Some(location) = params.get("location").and_then(|v| v.as_str()) {
+        locations.push(location.to_string());
+    }
+    if let Some(list) = params.get("locations").and_then(|v| v.as_array()) {
+        locations.extend(list.iter().filter_map(|v| v.as_str().map(str::to_string)));
+    }
+    locations.sort();
+    locations.dedup();
+    locations
+}
+
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '_'], "-")
+}
+
+/// "Thornton, CO" -> "thornton"
+fn city_part(location: &str) -> String {
+    location.split(',').next().unwrap_or("").trim().to_lowercase()
+}
+
+fn extract_zip(location: &str) -> Option<&str> {
+    location
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|part| part.len() == 5)
+}
+
+fn parse_coordinates(location: &str) -> Option<(f64, f64)> {
+    let (lat, lng) = location.split_once(',')?;
+    Some((lat.trim().parse().ok()?, lng.trim().parse().ok()?))
+}
+
+fn haversine_miles(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    const EARTH_RADIUS_MILES: f64 = 3958.8;
+    let (dlat, dlng) = ((lat2 - lat1).to_radians(), (lng2 - lng1).to_radians());
+    let a = (dlat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_MILES * a.sqrt().asin()
+}
@@ -0,0 +1,199 @@
+//! Tool catalog - Telling the owner when the harness's tools change
+//!
+//! The heartbeat self-check re-fetches the harness tool catalog (see
+//! `self_check.rs`). When a refresh adds, removes or changes tools (description,
+//! parameters, cost or tier), the agent's behavior changes with it, so the
+//! difference is announced instead of applied silently:
+//! - `security.owners` get a message ("3 new tools available: ...")
+//!   when `tool_catalog.notify_owners`
+//! - with `tool_catalog.update_skills`, the "Tool Catalog Changes" section of
+//!   SKILLS.md gets the change, on trial first when `canary.enabled` (like
+//!   other learning updates), saved right away otherwise
+//!
+//! The catalog loaded at startup isn't a change.
+
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::agent_os::ToolDefinition;
+use crate::daemon::AppState;
+
+/// Heading of the SKILLS.md section this module owns
+pub const SECTION_HEADING: &str = "## Tool Catalog Changes";
+
+/// Changes kept in the SKILLS.md section
+const MAX_ENTRIES: usize = 20;
+
+/// Tool names listed per line of the owner message
+const MAX_NAMED: usize = 10;
+
+/// What one refresh changed
+#[derive(Debug, Clone, Default)]
+pub struct CatalogDiff {
+    pub added: Vec<ToolDefinition>,
+    pub removed: Vec<String>,
+    /// Same id, different description, parameters, cost or tier
+    pub changed: Vec<String>,
+}
+
+impl CatalogDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// How `new` differs from `old`
+pub fn diff(old: &[ToolDefinition], new: &[ToolDefinition]) -> CatalogDiff {
+    let definition = |tool: &ToolDefinition| serde_json::to_value(tool).unwrap_or_default();
+    let mut diff = CatalogDiff::default();
+    for tool in new {
+        match old.iter().find(|t| t.id == tool.id) {
+            None => diff.added.push(tool.clone()),
+            Some(before) if definition(before) != definition(tool) => diff.changed.push(tool.id.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old.iter().filter(|t| !new.iter().any(|n| n.id == t.id)).map(|t| t.id.clone()).collect();
+    diff
+}
+
+/// "3 new tools available: a, b, c"
+fn line(names: &[&str], one: &str, many: &str) -> String {
+    let mut named = names.iter().take(MAX_NAMED).copied().collect::<Vec<_>>().join(", ");
+    if names.len() > MAX_NAMED {
+        named.push_str(&format!(" and {} more", names.len() - MAX_NAMED));
+    }
+    format!("• {} {}: {}", names.len(), if names.len() == 1 { one } else { many }, named)
+}
+
+/// The owner message for `diff`
+pub fn summary(diff: &CatalogDiff) -> String {
+    let mut lines = vec!["🧰 The tool catalog changed:".to_string()];
+    if !diff.added.is_empty() {
+        let names: Vec<&str> = diff.added.iter().map(|t| t.id.as_str()).collect();
+        lines.push(line(&names, "new tool available", "new tools available"));
+    }
+    if !diff.removed.is_empty() {
+        let names: Vec<&str> = diff.removed.iter().map(String::as_str).collect();
+        lines.push(line(&names, "tool removed", "tools removed"));
+    }
+    if !diff.changed.is_empty() {
+        let names: Vec<&str> = diff.changed.iter().map(String::as_str).collect();
+        lines.push(line(&names, "tool changed", "tools changed"));
+    }
+    lines.join("\n")
+}
+
+/// The SKILLS.md section with `diff` added in front of the changes already in
+/// `skills`
+pub fn section(skills: &str, diff: &CatalogDiff, date: chrono::NaiveDate) -> String {
+    let mut entries: Vec<String> = diff
+        .added
+        .iter()
+        .map(|t| format!("- {}: new **{}**: {}", date, t.id, t.description.lines().next().unwrap_or_default().trim()))
+        .collect();
+    entries.extend(diff.removed.iter().map(|id| format!("- {}: removed **{}**; don't plan with it.", date, id)));
+    entries.extend(diff.changed.iter().map(|id| format!("- {}: changed **{}**; check its parameters before calling.", date, id)));
+
+    // The entries already there, after the new ones
+    if let Some(start) = skills.find(SECTION_HEADING) {
+        let after = start + SECTION_HEADING.len();
+        let end = skills[after..].find("\n## ").map_or(skills.len(), |i| after + i);
+        entries.extend(skills[after..end].lines().filter(|l| l.starts_with("- ")).map(str::to_string));
+    }
+    entries.truncate(MAX_ENTRIES);
+
+    format!(
+        "{}\n\n_Changes to the harness tool catalog, newest first; updated automatically._\n\n{}",
+        SECTION_HEADING,
+        entries.join("\n")
+    )
+}
+
+/// Put `diff` into SKILLS.md, on trial when the canary is on
+fn update_skills(state: &AppState, diff: &CatalogDiff) -> anyhow::Result<()> {
+    let before = state.canary.current(&state.agent_os);
+    let section = section(&before.skills, diff, chrono::Utc::now().date_naive());
+    let skills = crate::tool_insights::with_section(&before.skills, &section);
+    let candidate = crate::agent_os::AgentOS { skills, ..before.clone() };
+    if state.canary.enabled() {
+        state.canary.propose(before, candidate);
+    } else {
+        candidate.save()?;
+        tracing::info!("🧠 SKILLS.md updated with tool catalog changes");
+        crate::sync::push_in_background(state.config);
+    }
+    Ok(())
+}
+
+/// Announce each catalog change the self-check reports, until the process exits
+pub async fn run(state: Arc<AppState>, mut changes: mpsc::UnboundedReceiver<CatalogDiff>) {
+    let config = &state.config.tool_catalog;
+    while let Some(diff) = changes.recv().await {
+        let message = summary(&diff);
+        tracing::info!("{}", message);
+        if config.notify_owners {
+            let owners = &state.config.security.owners;
+            if owners.is_empty() {
+                tracing::debug!("No security.owners to tell about the tool catalog change");
+            }
+            crate::notify::notify_owners(state.store.as_ref(), &state.channels, &state.snoozes, owners, &message).await;
+        }
+        if config.update_skills {
+            if let Err(e) = update_skills(&state, &diff) {
+                tracing::warn!("Could not update SKILLS.md with the tool catalog change: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(id: &str, description: &str) -> ToolDefinition {
+        ToolDefinition {
+            id: id.to_string(),
+            description: description.to_string(),
+            params_schema: None,
+            cost_estimate: None,
+            tier: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_summary_and_section() {
+        let old = vec![tool("web.search", "Search the web"), tool("enrich", "Enrich contacts"), tool("old.scraper", "Scrape")];
+        let mut new = vec![tool("web.search", "Search the web"), tool("enrich", "Enrich contacts and companies")];
+        new.push(tool("maps.places", "Find businesses on a map\nMore detail"));
+        new.push(tool("email.verify", "Verify an address"));
+
+        let diff = diff(&old, &new);
+        assert_eq!(diff.added.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["maps.places", "email.verify"]);
+        assert_eq!(diff.removed, vec!["old.scraper"]);
+        assert_eq!(diff.changed, vec!["enrich"]);
+        assert!(super::diff(&new, &new).is_empty());
+
+        assert_eq!(
+            summary(&diff),
+            "🧰 The tool catalog changed:\n• 2 new tools available: maps.places, email.verify\n• 1 tool removed: old.scraper\n• 1 tool changed: enrich"
+        );
+
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let first = section("# Skills\n", &diff, date);
+        assert!(first.contains("- 2026-10-16: new **maps.places**: Find businesses on a map\n"));
+        assert!(first.contains("- 2026-10-16: removed **old.scraper**"));
+
+        // Later changes go in front of the earlier ones
+        let skills = crate::tool_insights::with_section("# Skills\n\n## Fallbacks\n\nRetry once.\n", &first);
+        let later = CatalogDiff { removed: vec!["email.verify".to_string()], ..Default::default() };
+        let second = section(&skills, &later, date.succ_opt().unwrap());
+        let entries: Vec<&str> = second.lines().filter(|l| l.starts_with("- ")).collect();
+        assert_eq!(entries.len(), 5);
+        assert!(entries[0].starts_with("- 2026-10-17: removed **email.verify**"));
+        let updated = crate::tool_insights::with_section(&skills, &second);
+        assert_eq!(updated.matches(SECTION_HEADING).count(), 1);
+        assert!(updated.contains("## Fallbacks\n\nRetry once."));
+    }
+}
@@ -0,0 +1,287 @@
+//! Tool insights - What SKILLS.md says about tools, from how calls went
+//!
+//! Every tool call that runs is recorded in the store: the tool, whether it
+//! succeeded, how long it took and what a failure reported. Every
+//! `tool_insights.interval_hours`, the calls of the last `window_days` are added
+//! up per tool: success rate, typical (median) and slow (90th percentile)
+//! latency, and the most common failure modes. Each tool with `min_calls` calls
+//! gets a line in the "Measured Tool Performance" section of SKILLS.md, with a
+//! recommendation when it's unreliable or slow.
+//!
+//! The section is rewritten as a whole rather than appended to. Like other
+//! learning updates, the change goes on trial first when `canary.enabled` (see
+//! `canary.rs`), and is saved right away otherwise.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::daemon::AppState;
+use crate::store::{Store, ToolCallRecord};
+
+/// Heading of the SKILLS.md section this module owns
+pub const SECTION_HEADING: &str = "## Measured Tool Performance";
+
+/// Longest failure message kept with a call
+const MAX_ERROR_CHARS: usize = 200;
+
+/// Failure modes listed per tool
+const MAX_FAILURE_MODES: usize = 3;
+
+/// Below this success rate a tool is flagged as unreliable
+const UNRELIABLE_BELOW: f64 = 0.8;
+
+/// Tools whose slow calls (90th percentile) take longer are flagged as slow
+const SLOW_P90_MS: u64 = 60_000;
+
+/// What one tool's calls in the window add up to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: usize,
+    pub successes: usize,
+    pub median_ms: u64,
+    pub p90_ms: u64,
+    /// Most common failure modes with their counts, most frequent first
+    pub failure_modes: Vec<(String, usize)>,
+}
+
+impl ToolStats {
+    pub fn success_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.calls as f64
+    }
+}
+
+/// The record of a call of `tool`; `error` is what a failed call reported
+pub fn call_record(tool: &str, error: Option<&str>, duration_ms: u64) -> ToolCallRecord {
+    ToolCallRecord {
+        tool: tool.to_string(),
+        success: error.is_none(),
+        duration_ms,
+        error: error.map(|e| e.chars().take(MAX_ERROR_CHARS).collect()),
+        created_at: Utc::now(),
+    }
+}
+
+/// Store `record`; telemetry is bookkeeping, so failures are only logged
+pub async fn record(store: &dyn Store, record: ToolCallRecord) {
+    if let Err(e) = store.add_tool_call(&record).await {
+        tracing::warn!("Failed to record a call of {}: {}", record.tool, e);
+    }
+}
+
+/// A failure message with its specifics (ids, counts, durations) blanked, so
+/// the same failure groups together
+fn failure_mode(error: &str) -> String {
+    let mut mode = String::new();
+    let mut in_number = false;
+    for c in error.trim().to_lowercase().chars().take(80) {
+        if c.is_ascii_digit() {
+            if !in_number {
+                mode.push('N');
+            }
+            in_number = true;
+        } else {
+            mode.push(c);
+            in_number = false;
+        }
+    }
+    mode
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+/// Per-tool stats of `records`, for tools with at least `min_calls` calls
+pub fn aggregate(records: &[ToolCallRecord], min_calls: usize) -> Vec<ToolStats> {
+    let mut by_tool: BTreeMap<&str, Vec<&ToolCallRecord>> = BTreeMap::new();
+    for record in records {
+        by_tool.entry(&record.tool).or_default().push(record);
+    }
+
+    by_tool
+        .into_iter()
+        .filter(|(_, calls)| calls.len() >= min_calls.max(1))
+        .map(|(tool, calls)| {
+            let mut durations: Vec<u64> = calls.iter().map(|c| c.duration_ms).collect();
+            durations.sort_unstable();
+            let mut modes: HashMap<String, usize> = HashMap::new();
+            for error in calls.iter().filter_map(|c| c.error.as_deref()) {
+                *modes.entry(failure_mode(error)).or_default() += 1;
+            }
+            let mut failure_modes: Vec<(String, usize)> = modes.into_iter().collect();
+            failure_modes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            failure_modes.truncate(MAX_FAILURE_MODES);
+            ToolStats {
+                tool: tool.to_string(),
+                calls: calls.len(),
+                successes: calls.iter().filter(|c| c.success).count(),
+                median_ms: percentile(&durations, 0.5),
+                p90_ms: percentile(&durations, 0.9),
+                failure_modes,
+            }
+        })
+        .collect()
+}
+
+fn duration(ms: u64) -> String {
+    match ms {
+        0..=999 => "under 1s".to_string(),
+        1_000..=119_999 => format!("{}s", ms / 1_000),
+        _ => format!("{}m", ms / 60_000),
+    }
+}
+
+fn recommendation(stats: &ToolStats) -> Option<&'static str> {
+    let unreliable = stats.success_rate() < UNRELIABLE_BELOW;
+    let slow = stats.p90_ms > SLOW_P90_MS;
+    match (unreliable, slow) {
+        (true, true) => Some("Unreliable and slow: check inputs first, tell the user it can take a while, and have a fallback ready."),
+        (true, false) => Some("Unreliable: check inputs before calling and have a fallback ready."),
+        (false, true) => Some("Slow: tell the user it can take a while before calling."),
+        (false, false) => None,
+    }
+}
+
+/// The SKILLS.md section for `stats` (None without any tool to report)
+pub fn section(stats: &[ToolStats], window_days: i64) -> Option<String> {
+    if stats.is_empty() {
+        return None;
+    }
+    let mut section = format!(
+        "{}\n\n_Measured over the last {} days; rewritten automatically._\n",
+        SECTION_HEADING, window_days
+    );
+    for s in stats {
+        section.push_str(&format!(
+            "\n- **{}**: {:.0}% success over {} calls; usually {}, slow calls {}.",
+            s.tool,
+            s.success_rate() * 100.0,
+            s.calls,
+            duration(s.median_ms),
+            duration(s.p90_ms)
+        ));
+        if !s.failure_modes.is_empty() {
+            let modes: Vec<String> = s.failure_modes.iter().map(|(mode, n)| format!("\"{}\" ({}x)", mode, n)).collect();
+            section.push_str(&format!(" Common failures: {}.", modes.join(", ")));
+        }
+        if let Some(advice) = recommendation(s) {
+            section.push(' ');
+            section.push_str(advice);
+        }
+    }
+    Some(section)
+}
+
+/// `skills` with the section headed like `section`'s first line (the
+/// measured-performance one, the tool catalog one) replaced by `section`, or
+/// added at the end
+pub fn with_section(skills: &str, section: &str) -> String {
+    let heading = section.lines().next().unwrap_or(SECTION_HEADING);
+    let Some(start) = skills.find(heading) else {
+        return format!("{}\n\n{}\n", skills.trim_end(), section);
+    };
+    let after = start + heading.len();
+    let end = skills[after..].find("\n## ").map_or(skills.len(), |i| after + i + 1);
+    let rest = &skills[end..];
+    if rest.is_empty() {
+        format!("{}{}\n", &skills[..start], section)
+    } else {
+        format!("{}{}\n\n{}", &skills[..start], section, rest)
+    }
+}
+
+/// Update SKILLS.md from the tool calls of the window, if anything changed
+async fn update(state: &AppState) -> anyhow::Result<()> {
+    let config = &state.config.tool_insights;
+    let since = Utc::now() - Duration::days(config.window_days);
+    let records = state.store.tool_calls_since(since).await?;
+    let Some(section) = section(&aggregate(&records, config.min_calls), config.window_days) else {
+        return Ok(());
+    };
+
+    let before = state.canary.current(&state.agent_os);
+    let skills = with_section(&before.skills, &section);
+    if skills == before.skills {
+        return Ok(());
+    }
+    let candidate = crate::agent_os::AgentOS { skills, ..before.clone() };
+    if state.canary.enabled() {
+        state.canary.propose(before, candidate);
+    } else {
+        candidate.save()?;
+        tracing::info!("🧠 SKILLS.md updated with measured tool performance");
+        crate::sync::push_in_background(state.config);
+    }
+    Ok(())
+}
+
+/// Refresh the measured-performance section every `interval_hours` until the process exits
+pub async fn run(state: Arc<AppState>) {
+    let hours = state.config.tool_insights.interval_hours.max(1);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(hours * 3600));
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = update(&state).await {
+            tracing::warn!("Tool insights update failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(tool: &str, duration_ms: u64, error: Option<&str>) -> ToolCallRecord {
+        ToolCallRecord {
+            tool: tool.to_string(),
+            success: error.is_none(),
+            duration_ms,
+            error: error.map(String::from),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_stats_and_skills_section() {
+        let mut records = vec![call("web.search", 800, None), call("web.search", 1_200, None)];
+        for i in 0..6 {
+            records.push(call("enrich-contacts", 20_000 + i * 1_000, None));
+        }
+        records.push(call("enrich-contacts", 300_000, Some("enrich-contacts timed out after 300s and was cancelled")));
+        records.push(call("enrich-contacts", 300_000, Some("enrich-contacts timed out after 300s and was cancelled")));
+        records.push(call("enrich-contacts", 90_000, Some("Apify run 8f2k1 failed")));
+
+        let stats = aggregate(&records, 3);
+        assert_eq!(stats.len(), 1, "web.search has too few calls");
+        let enrich = &stats[0];
+        assert_eq!((enrich.calls, enrich.successes), (9, 6));
+        assert_eq!(enrich.median_ms, 24_000);
+        assert_eq!(enrich.p90_ms, 300_000);
+        assert_eq!(enrich.failure_modes[0], ("enrich-contacts timed out after Ns and was cancelled".to_string(), 2));
+
+        let section = section(&stats, 7).unwrap();
+        assert!(section.contains("**enrich-contacts**: 67% success over 9 calls; usually 24s, slow calls 5m."));
+        assert!(section.contains("Unreliable and slow"));
+
+        // The section is replaced in place, not appended again
+        let skills = "# Skills\n\nFind leads.\n\n## Fallbacks\n\nRetry once.\n";
+        let once = with_section(skills, &section);
+        assert!(once.ends_with(&format!("{}\n", section)));
+        let followed = once + "\n## Notes\n\nKeep it short.\n";
+        let updated = with_section(&followed, "## Measured Tool Performance\n\nnew");
+        assert_eq!(updated.matches(SECTION_HEADING).count(), 1);
+        assert!(updated.contains("## Measured Tool Performance\n\nnew\n\n## Notes\n\nKeep it short.\n"));
+        assert!(updated.contains("## Fallbacks\n\nRetry once."));
+    }
+}
@@ -0,0 +1,69 @@
+//! Tool Limits - Node-wide concurrency caps per tool
+//!
+//! `executors.max_concurrency` caps how many calls to a tool run at once across
+//! every chat, channel and job on the node, e.g. `enrich-contacts: 2` to stay
+//! within an upstream quota. Calls over the cap wait in line (first come, first
+//! served) and the wait is reported as `queued_ms` on the tool call.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A slot to run a limited tool; the next queued call starts when it's dropped
+pub struct Slot {
+    _permit: Option<OwnedSemaphorePermit>,
+    /// Time spent waiting for the slot
+    pub queued_ms: u64,
+}
+
+#[derive(Default)]
+pub struct ToolLimits {
+    semaphores: HashMap<String, Arc<Semaphore>>,
+}
+
+impl ToolLimits {
+    /// Limits from `executors.max_concurrency`; a limit of 0 is treated as 1
+    pub fn new(limits: &HashMap<String, usize>) -> Self {
+        let semaphores = limits
+            .iter()
+            .map(|(tool, max)| (tool.clone(), Arc::new(Semaphore::new((*max).max(1)))))
+            .collect();
+        Self { semaphores }
+    }
+
+    /// Wait for a free slot for `tool` (immediate for tools without a limit)
+    pub async fn acquire(&self, tool: &str) -> Slot {
+        let Some(semaphore) = self.semaphores.get(tool) else {
+            return Slot { _permit: None, queued_ms: 0 };
+        };
+        let start = Instant::now();
+        if semaphore.available_permits() == 0 {
+            tracing::info!("⏳ {} is at its concurrency limit; queued", tool);
+        }
+        // The semaphore is never closed
+        let permit = Arc::clone(semaphore).acquire_owned().await.ok();
+        Slot { _permit: permit, queued_ms: start.elapsed().as_millis() as u64 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_calls_over_the_limit_wait() {
+        let limits = ToolLimits::new(&HashMap::from([("enrich".to_string(), 1)]));
+        let first = limits.acquire("enrich").await;
+        assert_eq!(limits.acquire("search").await.queued_ms, 0);
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            drop(first);
+        });
+        let second = limits.acquire("enrich").await;
+        assert!(second.queued_ms >= 40);
+        release.await.unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! Tool Server - The node serving its own executors with the harness API
+//!
+//! With `tool_server.enabled`, the node answers the two calls every node makes
+//! to its harness, backed by the local executors listed in `tool_server.tools`:
+//! - `GET /harness/tools`: the tool catalog (`{"tools": [...]}`)
+//! - `POST /harness/execute`: `{"workflowId", "input"}` runs one tool and
+//!   returns its output, or `{"error"}`
+//!
+//! Another node whose `HARNESS_URL` points at `http://<this node>/harness` uses
+//! these tools like harness workflows. A node pointed at its own `/harness`
+//! runs fully offline; its catalog is then loaded directly at startup.
+//!
+//! Requests must carry node signatures (`node_key.rs`) from this node or a key in
+//! `tool_server.trusted_keys`, at most `MAX_SKEW_SECS` old.
+
+use serde_json::{json, Value};
+
+use crate::agent_os::ToolDefinition;
+use crate::config::ToolServerConfig;
+use crate::executor::{CostModel, ExecutorResult, Registry};
+
+/// Oldest (or furthest in the future) signature timestamp accepted
+const MAX_SKEW_SECS: i64 = 300;
+
+/// Never served: they forward calls to the harness (`job.control` only for
+/// the jobs of the user whose conversation calls it)
+const NEVER_SERVED: &[&str] = &["harness.execute", "job.control"];
+
+/// The signature headers of a request (`X-OneClaw-*`)
+pub struct Signature<'a> {
+    pub public_key: Option<&'a str>,
+    pub timestamp: Option<&'a str>,
+    pub signature: Option<&'a str>,
+}
+
+/// Ids of the executors this node serves
+fn served<'a>(config: &'a ToolServerConfig, registry: &Registry) -> Vec<&'a str> {
+    config
+        .tools
+        .iter()
+        .map(String::as_str)
+        .filter(|id| !NEVER_SERVED.contains(id) && registry.get(id).is_some())
+        .collect()
+}
+
+/// The served tools as harness catalog entries
+pub fn catalog(config: &ToolServerConfig, registry: &Registry) -> Vec<ToolDefinition> {
+    served(config, registry)
+        .into_iter()
+        .filter_map(|id| registry.get(id))
+        .map(|executor| {
+            let manifest = executor.manifest();
+            ToolDefinition {
+                cost_estimate: matches!(manifest.capabilities.cost_model, CostModel::Free).then_some(0.0),
+                params_schema: manifest.capabilities.input_schema,
+                description: manifest.description,
+                id: manifest.id,
+                tier: None,
+            }
+        })
+        .collect()
+}
+
+/// `GET /tools` body, in the harness's field names
+pub fn catalog_json(tools: &[ToolDefinition]) -> Value {
+    let tools: Vec<Value> = tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "id": tool.id,
+                "description": tool.description,
+                "paramsSchema": tool.params_schema,
+                "estimatedCostUsd": tool.cost_estimate,
+                "tier": tool.tier,
+            })
+        })
+        .collect();
+    json!({ "tools": tools })
+}
+
+/// Whether a request to `path_and_query` was signed by this node or a trusted one
+pub fn authorize(
+    config: &ToolServerConfig,
+    own_key: Option<&str>,
+    signature: &Signature,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+    now: i64,
+) -> Result<(), String> {
+    let (Some(public_key), Some(timestamp), Some(sig)) = (signature.public_key, signature.timestamp, signature.signature) else {
+        return Err("Unsigned request".to_string());
+    };
+    if own_key != Some(public_key) && !config.trusted_keys.iter().any(|k| k == public_key) {
+        return Err(format!("Node key {} is not in tool_server.trusted_keys", public_key));
+    }
+    let age = timestamp.parse::<i64>().map(|ts| (now - ts).abs()).unwrap_or(i64::MAX);
+    if age > MAX_SKEW_SECS {
+        return Err("Signature timestamp is too old".to_string());
+    }
+    if !crate::node_key::verify(public_key, sig, timestamp, method, path_and_query, body) {
+        return Err("Invalid signature".to_string());
+    }
+    Ok(())
+}
+
+/// Run an `/execute` request; the error carries the HTTP status
+pub async fn execute(
+    config: &ToolServerConfig,
+    registry: &Registry,
+    node: &crate::config::NodeConfig,
+    request: &Value,
+) -> Result<Value, (u16, String)> {
+    let Some(id) = request["workflowId"].as_str() else {
+        return Err((400, "workflowId required".to_string()));
+    };
+    if !served(config, registry).contains(&id) {
+        return Err((404, format!("{} is not served by this node", id)));
+    }
+    let executor = registry.get(id).ok_or((404, format!("Unknown tool {}", id)))?;
+
+    let input = request.get("input").cloned().unwrap_or(json!({}));
+    match executor.execute(input, node).await {
+        ExecutorResult::Executed { output, .. } => Ok(output),
+        ExecutorResult::Error { error } => Ok(json!({ "error": error })),
+        ExecutorResult::Denied { denial_reason } => Err((403, denial_reason.policy)),
+    }
+}
+
+/// Whether `harness_url` is this node's own `/harness` on `port`
+pub fn is_self(harness_url: &str, port: u16) -> bool {
+    url::Url::parse(harness_url).is_ok_and(|url| {
+        matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"))
+            && url.port_or_known_default() == Some(port)
+            && url.path().trim_end_matches('/') == "/harness"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_and_authorization() {
+        let registry = Registry::load().unwrap();
+        let config: ToolServerConfig =
+            serde_yaml::from_str("enabled: true\ntools: [web.search, harness.execute, no.such]\ntrusted_keys: [peer]").unwrap();
+        let tools = catalog(&config, &registry);
+        assert_eq!(tools.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["web.search"]);
+        assert_eq!(catalog_json(&tools)["tools"][0]["paramsSchema"]["required"][0], "query");
+
+        let unsigned = Signature { public_key: None, timestamp: None, signature: None };
+        assert!(authorize(&config, Some("me"), &unsigned, "GET", "/harness/tools", b"", 1000).is_err());
+        let stranger = Signature { public_key: Some("stranger"), timestamp: Some("1000"), signature: Some("x") };
+        assert!(authorize(&config, Some("me"), &stranger, "GET", "/harness/tools", b"", 1000).unwrap_err().contains("trusted_keys"));
+        let stale = Signature { public_key: Some("peer"), timestamp: Some("100"), signature: Some("x") };
+        assert!(authorize(&config, Some("me"), &stale, "GET", "/harness/tools", b"", 1000).unwrap_err().contains("too old"));
+
+        assert!(is_self("http://localhost:8787/harness", 8787));
+        assert!(!is_self("http://localhost:9000", 8787));
+        assert!(!is_self("https://oneclaw.chat", 8787));
+    }
+}
@@ -0,0 +1,256 @@
+//! Top - `oneclaw top`, a live terminal view of a running daemon
+//!
+//! Polls the daemon's `GET /status` and redraws the screen: chat turns in
+//! flight, running jobs, channel status, spend and recent errors. For watching
+//! a node over SSH without opening the web dashboard. Ctrl-C quits.
+//!
+//! `/status` is an admin endpoint, so the admin token (the env var named by
+//! `security.admin_token_env`) has to be set where `oneclaw top` runs.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Clear the screen and move the cursor home
+const CLEAR: &str = "\x1b[2J\x1b[H";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// Rows shown per section before "… N more"
+const MAX_ROWS: usize = 8;
+
+/// Redraw every `interval` from the daemon on `port` until interrupted,
+/// authorized with `admin_token`
+pub async fn run(port: u16, interval: Duration, admin_token: Option<String>) -> anyhow::Result<()> {
+    let url = format!("http://127.0.0.1:{}/status", port);
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let mut previous_spend: Option<(Instant, f64)> = None;
+
+    loop {
+        let screen = match fetch(&client, &url, admin_token.as_deref()).await {
+            Ok(status) => {
+                let spent = status["spend"]["spent_usd"].as_f64();
+                let rate = match (previous_spend, spent) {
+                    (Some((at, before)), Some(now)) if at.elapsed() > Duration::ZERO => {
+                        Some((now - before).max(0.0) * 3600.0 / at.elapsed().as_secs_f64())
+                    }
+                    _ => None,
+                };
+                previous_spend = spent.map(|spent| (Instant::now(), spent));
+                render(&status, rate, Utc::now(), width())
+            }
+            Err(e) => format!("{}oneclaw top{}  {}\n\n{}Can't reach the daemon at {}: {}{}\nRetrying…\n", BOLD, RESET, Utc::now().format("%H:%M:%S"), RED, url, e, RESET),
+        };
+        let mut stdout = std::io::stdout();
+        write!(stdout, "{}{}", CLEAR, screen)?;
+        stdout.flush()?;
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn fetch(client: &reqwest::Client, url: &str, admin_token: Option<&str>) -> anyhow::Result<Value> {
+    let mut request = client.get(url);
+    if let Some(token) = admin_token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    if response.status() == reqwest::StatusCode::FORBIDDEN {
+        anyhow::bail!("{}", response.text().await.unwrap_or_default());
+    }
+    Ok(response.error_for_status()?.json().await?)
+}
+
+/// Terminal width from `COLUMNS`, else 100
+fn width() -> usize {
+    std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).filter(|w| *w >= 40).unwrap_or(100)
+}
+
+/// The screen for one `/status` response; `spend_rate` is dollars per hour since the last poll
+pub fn render(status: &Value, spend_rate: Option<f64>, now: DateTime<Utc>, width: usize) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}oneclaw top{}  {} ({})  up {}  {}",
+        BOLD,
+        RESET,
+        status["node_name"].as_str().unwrap_or("node"),
+        status["node_id"].as_str().unwrap_or("?"),
+        duration(status["uptime_secs"].as_u64().unwrap_or(0)),
+        now.format("%H:%M:%S")
+    );
+    out.push('\n');
+
+    let turns: Vec<String> = rows(&status["turns"], |turn| {
+        let age = timestamp(&turn["started_at"]).map(|t| duration((now - t).num_seconds().max(0) as u64)).unwrap_or_default();
+        format!(
+            "{:>6}  {:<10} {:<16} {}",
+            age,
+            turn["channel"].as_str().unwrap_or(""),
+            turn["user_id"].as_str().unwrap_or(""),
+            turn["message"].as_str().unwrap_or("")
+        )
+    });
+    section(&mut out, "Conversations in flight", &turns, width);
+
+    let jobs: Vec<String> = rows(&status["jobs"], |job| {
+        let age = job["started_at"]["secs_since_epoch"]
+            .as_i64()
+            .map(|secs| duration((now.timestamp() - secs).max(0) as u64))
+            .unwrap_or_default();
+        format!(
+            "{:>6}  {:<10} {:>4.0}%  {}  {}",
+            age,
+            job["status"].as_str().unwrap_or(""),
+            job["progress"].as_f64().unwrap_or(0.0) * 100.0,
+            job["description"].as_str().or(job["job_id"].as_str()).unwrap_or(""),
+            job["current_step"].as_str().map(|step| format!("{}({}){}", DIM, step, RESET)).unwrap_or_default()
+        )
+    });
+    section(&mut out, "Running jobs", &jobs, width);
+
+    let channels: Vec<String> = rows(&status["channels"], |channel| {
+        let state = if channel["connected"] == true {
+            format!("{}connected{}", GREEN, RESET)
+        } else {
+            format!("{}down{}", RED, RESET)
+        };
+        let last = timestamp(&channel["last_message_at"])
+            .map(|t| format!("last message {} ago", duration((now - t).num_seconds().max(0) as u64)))
+            .unwrap_or_default();
+        format!(
+            "{:<10} {}  {}{}",
+            channel["name"].as_str().unwrap_or(""),
+            state,
+            last,
+            channel["error"].as_str().map(|e| format!("  {}{}{}", RED, e, RESET)).unwrap_or_default()
+        )
+    });
+    section(&mut out, "Channels", &channels, width);
+
+    let spend = match status["spend"].as_object() {
+        None => vec!["throttle off (spend.max_usd_per_hour)".to_string()],
+        Some(spend) => {
+            let number = |key: &str| spend.get(key).and_then(Value::as_f64).unwrap_or(0.0);
+            vec![format!(
+                "${:.2}/h now  ${:.2} spent  ${:.2} of ${:.2}/h available{}",
+                spend_rate.unwrap_or(0.0),
+                number("spent_usd"),
+                number("available_usd"),
+                number("max_usd_per_hour"),
+                if spend.get("throttled") == Some(&Value::Bool(true)) { format!("  {}THROTTLED{}", RED, RESET) } else { String::new() }
+            )]
+        }
+    };
+    section(&mut out, "Spend", &spend, width);
+
+    let errors: Vec<String> = rows(&status["errors"], |error| {
+        format!(
+            "{}  {:<14} {}",
+            timestamp(&error["at"]).map(|t| t.format("%H:%M:%S").to_string()).unwrap_or_default(),
+            error["source"].as_str().unwrap_or(""),
+            error["message"].as_str().unwrap_or("").replace('\n', " ")
+        )
+    });
+    section(&mut out, "Recent errors", &errors, width);
+    out
+}
+
+fn rows(list: &Value, row: impl Fn(&Value) -> String) -> Vec<String> {
+    list.as_array().map(|items| items.iter().map(row).collect()).unwrap_or_default()
+}
+
+fn section(out: &mut String, title: &str, rows: &[String], width: usize) {
+    let _ = writeln!(out, "{}{} ({}){}", BOLD, title, rows.len(), RESET);
+    if rows.is_empty() {
+        let _ = writeln!(out, "  {}none{}", DIM, RESET);
+    }
+    for row in rows.iter().take(MAX_ROWS) {
+        let _ = writeln!(out, "  {}", truncate(row, width.saturating_sub(2)));
+    }
+    if rows.len() > MAX_ROWS {
+        let _ = writeln!(out, "  {}… {} more{}", DIM, rows.len() - MAX_ROWS, RESET);
+    }
+    out.push('\n');
+}
+
+/// `text` cut to `width` visible characters (escape codes don't count)
+fn truncate(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut visible = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        if c == '\x1b' {
+            in_escape = true;
+        }
+        if !in_escape {
+            if visible == width {
+                out.push('…');
+                out.push_str(RESET);
+                break;
+            }
+            visible += 1;
+        }
+        out.push(c);
+        if in_escape && c == 'm' {
+            in_escape = false;
+        }
+    }
+    out
+}
+
+fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    value.as_str().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.with_timezone(&Utc))
+}
+
+/// `42s`, `5m`, `3h12m`, `2d4h`
+fn duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h{}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_status() {
+        let now = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+        let status = serde_json::json!({
+            "node_id": "n1",
+            "node_name": "Office",
+            "uptime_secs": 7500,
+            "turns": [{ "user_id": "u1", "channel": "telegram", "message": "find roofers in Austin", "started_at": "2026-10-16T11:59:48Z" }],
+            "jobs": [],
+            "channels": [{ "name": "slack", "connected": false, "error": "invalid_auth", "last_message_at": null }],
+            "spend": null,
+            "errors": [{ "at": "2026-10-16T11:58:00Z", "source": "llm", "message": "LLM API error 529: overloaded" }]
+        });
+        let screen = render(&status, None, now, 100);
+        assert!(screen.contains("Office (n1)  up 2h5m"));
+        assert!(screen.contains("Conversations in flight (1)"));
+        assert!(screen.contains("12s  telegram   u1               find roofers in Austin"));
+        assert!(screen.contains("Running jobs (0)"));
+        assert!(screen.contains("invalid_auth"));
+        assert!(screen.contains("throttle off"));
+        assert!(screen.contains("11:58:00  llm            LLM API error 529: overloaded"));
+
+        assert_eq!(truncate(&format!("{}abcdef{}", RED, RESET), 3), format!("{}abc…{}", RED, RESET));
+    }
+}
@@ -0,0 +1,226 @@
+//! Upgrade - A new daemon binary takes over from the running one
+//!
+//! `oneclaw daemon --takeover` replaces a running daemon without downtime:
+//! 1. The new daemon sets itself up, then asks the old one for its state with
+//!    `POST /upgrade/handoff` on localhost, signed with the node key. Only this
+//!    node's own key is accepted.
+//! 2. The old daemon stops its channel listeners and hands over the jobs it was
+//!    watching for chats, its job monitor and the replies waiting on "more".
+//!    Telegram polling stops without confirming anything it hasn't handled, so
+//!    the new daemon resumes from the saved offset.
+//! 3. The new daemon binds the port next to the old one (`SO_REUSEPORT` on
+//!    Unix), starts its channels and keeps watching the handed-over jobs.
+//! 4. After `upgrade.overlap_secs` the old daemon stops accepting connections,
+//!    finishes the requests and chat turns in flight (for up to
+//!    `upgrade.drain_secs`) and exits.
+//!
+//! Only daemons started with `--takeover` set `SO_REUSEPORT`, so no other
+//! process can share a normal daemon's port. Where the old daemon holds the
+//! port exclusively (it was started normally, or there's no `SO_REUSEPORT`),
+//! the new daemon binds once the old one lets go of it at the end of the overlap.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpSocket};
+
+use crate::activity::Activity;
+use crate::autonomous_jobs::{JobPlan, JobStep};
+use crate::config::{ToolServerConfig, UpgradeConfig};
+use crate::monitor::JobStatus;
+
+pub const HANDOFF_PATH: &str = "/upgrade/handoff";
+
+/// Extra seconds past the overlap a new daemon waits for the port
+const BIND_GRACE_SECS: u64 = 10;
+
+/// A job started from a Telegram chat, watched until its results go back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobWatch {
+    pub job_id: String,
+    pub user_id: String,
+    pub channel_id: String,
+    pub reply_to: Option<String>,
+    pub thread_id: Option<String>,
+    /// What the user asked for, and the plan as generated (cached once the job succeeds)
+    pub request: String,
+    pub planned: JobPlan,
+    /// Steps drafted locally from the results (call scripts, voicemails)
+    pub local_steps: Vec<JobStep>,
+    pub estimate: f64,
+    pub harness_url: String,
+}
+
+/// What the old daemon hands to the new one
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Handoff {
+    pub watches: Vec<JobWatch>,
+    pub jobs: Vec<JobStatus>,
+    /// Rest of cut-off replies, as (channel, chat, text)
+    pub continuations: Vec<(String, String, String)>,
+}
+
+/// Job watches running in this daemon, and whether it has handed off
+#[derive(Default)]
+pub struct Upgrade {
+    watches: Mutex<HashMap<String, JobWatch>>,
+    handed_off: tokio::sync::watch::Sender<bool>,
+}
+
+impl Upgrade {
+    /// Register a watch so a handoff can pass it on; false once this daemon has
+    /// handed off, when the watch stays here until the daemon exits
+    pub fn track(&self, watch: &JobWatch) -> bool {
+        let mut watches = self.watches.lock().unwrap();
+        if self.is_handed_off() {
+            return false;
+        }
+        watches.insert(watch.job_id.clone(), watch.clone());
+        true
+    }
+
+    /// The watch of `job_id` is done; false if it was handed off in the meantime
+    /// (the new daemon reports the results then)
+    pub fn finish(&self, job_id: &str) -> bool {
+        self.watches.lock().unwrap().remove(job_id).is_some()
+    }
+
+    /// Mark this daemon handed off and take its watches; None if it already was
+    pub fn hand_off(&self) -> Option<Vec<JobWatch>> {
+        let mut watches = self.watches.lock().unwrap();
+        if self.handed_off.send_replace(true) {
+            return None;
+        }
+        Some(watches.drain().map(|(_, watch)| watch).collect())
+    }
+
+    pub fn is_handed_off(&self) -> bool {
+        *self.handed_off.borrow()
+    }
+
+    /// Resolves once this daemon has handed off
+    pub async fn handed_over(&self) {
+        let _ = self.handed_off.subscribe().wait_for(|handed_off| *handed_off).await;
+    }
+}
+
+/// Whether a handoff request was signed by this node, recently
+pub fn authorize(own_key: &str, signature: &crate::tool_server::Signature, body: &[u8], now: i64) -> Result<(), String> {
+    // No trusted keys: only this node itself
+    let config = ToolServerConfig { trusted_keys: vec![], ..Default::default() };
+    crate::tool_server::authorize(&config, Some(own_key), signature, "POST", HANDOFF_PATH, body, now)
+}
+
+/// Ask the daemon on `port`, running node `node_id`, to hand over
+pub async fn request_handoff(node_id: &str, port: u16) -> anyhow::Result<Handoff> {
+    let url = format!("http://127.0.0.1:{}{}", port, HANDOFF_PATH);
+    let mut request = reqwest::Client::new().post(&url).timeout(Duration::from_secs(30));
+    for (name, value) in crate::node_key::signed_headers(node_id, "POST", &url, &[]) {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("No daemon to take over from on port {}", port))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("The daemon on port {} refused the handoff ({}): {}", port, status, response.text().await.unwrap_or_default());
+    }
+    Ok(response.json().await?)
+}
+
+/// Whether something already answers on `port`
+pub async fn in_use(port: u16) -> bool {
+    tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok()
+}
+
+/// Listen on `port`. With `takeover`, next to the daemon being taken over
+/// from: the port is shared where that daemon allows it, and a port still held
+/// exclusively is retried until the overlap should be over.
+pub async fn bind(port: u16, config: &UpgradeConfig, takeover: bool) -> std::io::Result<TcpListener> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(config.overlap_secs + BIND_GRACE_SECS);
+    loop {
+        match listen(addr, takeover) {
+            Err(e) if takeover && e.kind() == std::io::ErrorKind::AddrInUse && tokio::time::Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// A listener on `addr`; with `share`, another daemon may listen on it too
+fn listen(addr: SocketAddr, share: bool) -> std::io::Result<TcpListener> {
+    let socket = TcpSocket::new_v4()?;
+    #[cfg(unix)]
+    {
+        socket.set_reuseaddr(true)?;
+        if share {
+            socket.set_reuseport(true)?;
+        }
+    }
+    socket.bind(addr)?;
+    socket.listen(1024)
+}
+
+/// After a handoff: wait for the chat turns in flight, up to `upgrade.drain_secs`
+pub async fn drain(activity: &Activity, config: &UpgradeConfig) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(config.drain_secs);
+    loop {
+        let turns = activity.turns().len();
+        if turns == 0 {
+            break;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            tracing::warn!("Exiting with {} chat turn(s) still in flight", turns);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch(job_id: &str) -> JobWatch {
+        JobWatch {
+            job_id: job_id.to_string(),
+            user_id: "u1".to_string(),
+            channel_id: "42".to_string(),
+            reply_to: None,
+            thread_id: None,
+            request: "Find HVAC companies in Miami".to_string(),
+            planned: JobPlan { description: "Find HVAC companies".to_string(), steps: vec![] },
+            local_steps: vec![],
+            estimate: 0.5,
+            harness_url: "http://localhost:9000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hand_off_takes_running_watches_once() {
+        let upgrade = Upgrade::default();
+        assert!(upgrade.track(&watch("job_1")));
+        assert!(upgrade.track(&watch("job_2")));
+        assert!(upgrade.finish("job_1"));
+
+        let handed = upgrade.hand_off().unwrap();
+        assert_eq!(handed.len(), 1);
+        assert_eq!(handed[0].job_id, "job_2");
+        // The new daemon reports job_2 now; a job started during the drain stays here
+        assert!(!upgrade.finish("job_2"));
+        assert!(!upgrade.track(&watch("job_3")));
+        assert!(upgrade.hand_off().is_none());
+
+        let handoff = Handoff { watches: handed, ..Default::default() };
+        let json = serde_json::to_string(&handoff).unwrap();
+        let back: Handoff = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.watches[0].request, "Find HVAC companies in Miami");
+    }
+}
@@ -0,0 +1,179 @@
+//! Usage - What chat turns, tool calls and jobs actually cost
+//!
+//! Every finished chat turn is recorded with the tokens its LLM calls used, as
+//! reported by the provider, and their cost by `spend.prices`. Every paid tool
+//! call is recorded with its catalog `cost_estimate` next to the cost the tool
+//! reported (`cost_usd` in its output; the estimate when it reports none), and
+//! every autonomous job the same way, estimated from its plan.
+//!
+//! Records go to the store; `GET /usage?days=N` adds them up per day.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::context_budget::ContextBudget;
+use crate::store::{Store, UsageRecord};
+
+/// Days `GET /usage` covers by default, and at most
+pub const DEFAULT_DAYS: u32 = 7;
+pub const MAX_DAYS: u32 = 90;
+
+/// Costs added up over a day or a whole report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub turns: u32,
+    pub tool_calls: u32,
+    pub jobs: u32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub chat_usd: f64,
+    pub tools_usd: f64,
+    pub jobs_usd: f64,
+    /// Catalog estimates for the tool calls and jobs, to compare with what they cost
+    pub estimated_usd: f64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+/// Shown on `GET /usage`
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub since: DateTime<Utc>,
+    /// Oldest first; days without usage are left out
+    pub days: Vec<DailyUsage>,
+    pub total: UsageTotals,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &UsageRecord) {
+        match record.kind.as_str() {
+            "chat" => {
+                self.turns += 1;
+                self.chat_usd += record.cost_usd;
+            }
+            "tool" => {
+                self.tool_calls += 1;
+                self.tools_usd += record.cost_usd;
+            }
+            "job" => {
+                self.jobs += 1;
+                self.jobs_usd += record.cost_usd;
+            }
+            _ => {}
+        }
+        self.prompt_tokens += record.prompt_tokens;
+        self.completion_tokens += record.completion_tokens;
+        self.estimated_usd += record.estimated_usd.unwrap_or_default();
+        self.cost_usd += record.cost_usd;
+    }
+}
+
+/// A chat turn on `channel`, from its budget
+pub fn chat(channel: &str, user_id: &str, budget: &ContextBudget) -> UsageRecord {
+    UsageRecord {
+        kind: "chat".to_string(),
+        subject: channel.to_string(),
+        user_id: Some(user_id.to_string()),
+        prompt_tokens: budget.prompt_tokens,
+        completion_tokens: budget.completion_tokens,
+        estimated_usd: None,
+        cost_usd: budget.cost_usd,
+        created_at: Utc::now(),
+    }
+}
+
+/// A call of tool `tool_id`, at the cost its `output` reports or else `estimate`
+pub fn tool(tool_id: &str, estimate: Option<f64>, output: &Value) -> UsageRecord {
+    UsageRecord {
+        kind: "tool".to_string(),
+        subject: tool_id.to_string(),
+        user_id: None,
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        estimated_usd: estimate,
+        cost_usd: reported_cost(output).or(estimate).unwrap_or_default(),
+        created_at: Utc::now(),
+    }
+}
+
+/// A finished job, at the cost its `results` report or else `estimate`
+pub fn job(job_id: &str, user_id: &str, estimate: f64, results: &Value) -> UsageRecord {
+    UsageRecord {
+        kind: "job".to_string(),
+        subject: job_id.to_string(),
+        user_id: Some(user_id.to_string()),
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        estimated_usd: Some(estimate),
+        cost_usd: reported_cost(results).unwrap_or(estimate),
+        created_at: Utc::now(),
+    }
+}
+
+/// The cost a harness response reports: `cost_usd` or `costUsd`, at the top
+/// level or under `job` / `usage`
+pub fn reported_cost(output: &Value) -> Option<f64> {
+    [output, &output["job"], &output["usage"]]
+        .into_iter()
+        .find_map(|v| v["cost_usd"].as_f64().or_else(|| v["costUsd"].as_f64()))
+}
+
+/// Store `record`; usage is bookkeeping, so failures are only logged
+pub async fn record(store: &dyn Store, record: UsageRecord) {
+    if let Err(e) = store.add_usage(&record).await {
+        tracing::warn!("Failed to record {} usage for {}: {}", record.kind, record.subject, e);
+    }
+}
+
+/// `records` added up per UTC day
+pub fn report(since: DateTime<Utc>, records: &[UsageRecord]) -> UsageReport {
+    let mut days: BTreeMap<NaiveDate, UsageTotals> = BTreeMap::new();
+    let mut total = UsageTotals::default();
+    for record in records.iter().filter(|r| r.created_at >= since) {
+        days.entry(record.created_at.date_naive()).or_default().add(record);
+        total.add(record);
+    }
+    UsageReport {
+        since,
+        days: days.into_iter().map(|(date, totals)| DailyUsage { date, totals }).collect(),
+        total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_adds_up_per_day() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let budget = ContextBudget { prompt_tokens: 1200, completion_tokens: 300, cost_usd: 0.0081, ..Default::default() };
+        let mut turn = chat("telegram", "u1", &budget);
+        turn.created_at = at("2026-10-15T23:59:00Z");
+        let mut reported = tool("enrich-contact", Some(0.05), &serde_json::json!({ "costUsd": 0.03 }));
+        reported.created_at = at("2026-10-16T08:00:00Z");
+        let mut estimated = tool("audit-website", Some(0.02), &serde_json::json!({ "score": 71 }));
+        estimated.created_at = at("2026-10-16T09:00:00Z");
+        let mut finished = job("job_1", "u1", 1.5, &serde_json::json!({ "job": { "cost_usd": 1.25 } }));
+        finished.created_at = at("2026-10-16T10:00:00Z");
+
+        let report = report(at("2026-10-15T00:00:00Z"), &[turn, reported, estimated, finished]);
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.days[0].totals.turns, 1);
+        let today = &report.days[1].totals;
+        assert_eq!((today.tool_calls, today.jobs), (2, 1));
+        assert!((today.tools_usd - 0.05).abs() < 1e-9);
+        assert!((today.estimated_usd - 1.57).abs() < 1e-9);
+        assert!((report.total.cost_usd - 1.3081).abs() < 1e-9);
+        assert_eq!(report.total.prompt_tokens, 1200);
+    }
+}
@@ -0,0 +1,136 @@
+//! Web Search - Backends for the local `web.search` executor
+//!
+//! Lets the agent answer current-events and competitor questions without the
+//! harness. The backend is picked by `web_search.backend`:
+//! - `brave`: Brave Search API (`BRAVE_API_KEY`)
+//! - `serpapi`: Google results through SerpAPI (`SERPAPI_API_KEY`)
+//! - `searxng`: a self-hosted SearXNG instance (`web_search.searxng_url`)
+//!
+//! Titles and snippets are untrusted page text and go through
+//! `sanitize::tool_text` before the LLM sees them.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::WebSearchConfig;
+use crate::sanitize;
+
+const BRAVE_API: &str = "https://api.search.brave.com/res/v1/web/search";
+const SERPAPI_API: &str = "https://serpapi.com/search.json";
+
+/// Longest snippet passed on to the LLM
+const MAX_SNIPPET_CHARS: usize = 400;
+
+/// Most results a single search may ask for
+const MAX_RESULTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// Search with the configured backend; `count` defaults to `max_results`
+pub async fn search(config: &WebSearchConfig, query: &str, count: Option<usize>) -> anyhow::Result<Vec<SearchResult>> {
+    let count = count.unwrap_or(config.max_results).clamp(1, MAX_RESULTS);
+    let backend = config.backend.as_deref()
+        .ok_or_else(|| anyhow::anyhow!("web_search.backend not configured"))?;
+    let api_key = || -> anyhow::Result<String> {
+        let env = config.key_env().unwrap_or_default();
+        std::env::var(&env)
+            .ok()
+            .filter(|k| !k.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} not set", env))
+    };
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(20))
+        .build()?;
+
+    let (resp, parse): (_, fn(&Value) -> Vec<SearchResult>) = match backend {
+        "brave" => (
+            client.get(BRAVE_API)
+                .header("X-Subscription-Token", api_key()?)
+                .header("Accept", "application/json")
+                .query(&[("q", query), ("count", &count.to_string())])
+                .send()
+                .await?,
+            parse_brave,
+        ),
+        "serpapi" => (
+            client.get(SERPAPI_API)
+                .query(&[("engine", "google"), ("q", query), ("num", &count.to_string()), ("api_key", &api_key()?)])
+                .send()
+                .await?,
+            parse_serpapi,
+        ),
+        "searxng" => {
+            let base = config.searxng_url.as_deref()
+                .ok_or_else(|| anyhow::anyhow!("web_search.searxng_url not configured"))?;
+            (
+                client.get(format!("{}/search", base.trim_end_matches('/')))
+                    .query(&[("q", query), ("format", "json")])
+                    .send()
+                    .await?,
+                parse_searxng,
+            )
+        }
+        other => anyhow::bail!("Unknown web_search.backend: {} (brave, serpapi or searxng)", other),
+    };
+
+    if !resp.status().is_success() {
+        anyhow::bail!("{} search error {}: {}", backend, resp.status(), resp.text().await.unwrap_or_default());
+    }
+    let body: Value = resp.json().await?;
+    Ok(parse(&body).into_iter().take(count).collect())
+}
+
+/// Results from `items`, reading title/url/snippet from the given fields
+fn results(items: &Value, url_field: &str, snippet_field: &str) -> Vec<SearchResult> {
+    items
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let url = item[url_field].as_str()?;
+            Some(SearchResult {
+                title: sanitize::tool_text(item["title"].as_str().unwrap_or_default(), 200),
+                url: url.to_string(),
+                snippet: sanitize::tool_text(item[snippet_field].as_str().unwrap_or_default(), MAX_SNIPPET_CHARS),
+            })
+        })
+        .collect()
+}
+
+fn parse_brave(body: &Value) -> Vec<SearchResult> {
+    results(&body["web"]["results"], "url", "description")
+}
+
+fn parse_serpapi(body: &Value) -> Vec<SearchResult> {
+    results(&body["organic_results"], "link", "snippet")
+}
+
+fn parse_searxng(body: &Value) -> Vec<SearchResult> {
+    results(&body["results"], "url", "content")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backends() {
+        let brave = serde_json::json!({ "web": { "results": [
+            { "title": "Smith <strong>HVAC</strong>", "url": "https://smithhvac.com", "description": "Denver&#39;s <strong>HVAC</strong> experts" }
+        ] } });
+        let parsed = parse_brave(&brave);
+        assert_eq!(parsed[0].title, "Smith HVAC");
+        assert_eq!(parsed[0].snippet, "Denver's HVAC experts");
+
+        let serpapi = serde_json::json!({ "organic_results": [{ "title": "A", "link": "https://a.com", "snippet": "a" }, { "title": "no link" }] });
+        assert_eq!(parse_serpapi(&serpapi).len(), 1);
+
+        let searxng = serde_json::json!({ "results": [{ "title": "B", "url": "https://b.com", "content": "b" }] });
+        assert_eq!(parse_searxng(&searxng)[0].url, "https://b.com");
+    }
+}
@@ -8,7 +8,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::{config, executor, receipt};
+use crate::{config, executor, memory, policy, receipt};
 
 /// Workflow specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,13 +65,18 @@ pub struct Step {
     pub condition: Option<String>,
 }
 
+/// Installed workflows (`~/.oneclaw/workflows`)
+pub fn workflows_dir() -> PathBuf {
+    config::expand_path("~/.oneclaw/workflows")
+}
+
 /// Load workflow spec from file or registry
 pub fn load_spec(workflow_id: &str) -> anyhow::Result<WorkflowSpec> {
     // First, try loading from workflows/ directory
     let paths = vec![
         PathBuf::from(format!("workflows/{}.yaml", workflow_id)),
         PathBuf::from(format!("workflows/{}.yml", workflow_id)),
-        config::expand_path(&format!("~/.oneclaw/workflows/{}.yaml", workflow_id)),
+        workflows_dir().join(format!("{}.yaml", workflow_id)),
     ];
     
     for path in paths {
@@ -106,8 +111,8 @@ fn merge_inputs_with_defaults(spec: &WorkflowSpec, provided: Value) -> Value {
     Value::Object(merged)
 }
 
-/// Run a workflow
-pub async fn run(workflow_id: &str, inputs: Value) -> anyhow::Result<receipt::WorkflowReceipt> {
+/// Run a workflow; with `dry_run`, steps return simulated outputs (see `dry_run.rs`)
+pub async fn run(workflow_id: &str, inputs: Value, dry_run: bool) -> anyhow::Result<receipt::WorkflowReceipt> {
     let start_time = chrono::Utc::now();
     let run_id = nanoid::nanoid!();
     let config = config::load()?;
@@ -122,7 +127,9 @@ pub async fn run(workflow_id: &str, inputs: Value) -> anyhow::Result<receipt::Wo
     let spec = load_spec(workflow_id)?;
     
     // Initialize executor registry
-    let registry = executor::Registry::load()?;
+    let registry = executor::Registry::load()?
+        .with_plugins(&config.plugins)
+        .with_router(std::sync::Arc::new(crate::model_router::ModelRouter::for_node(config)));
     
     // Merge provided inputs with defaults from spec
     let merged_inputs = merge_inputs_with_defaults(&spec, inputs.clone());
@@ -191,8 +198,34 @@ pub async fn run(workflow_id: &str, inputs: Value) -> anyhow::Result<receipt::Wo
             }
         };
         
+        // Time-window policies
+        let paid = executor.manifest().capabilities.cost_model.is_paid();
+        let timezone = memory::load_preferences(config).ok().and_then(|p| p.timezone);
+        let tool = policy::ToolMeta { id: &step.executor, paid };
+        if let Some(denial) = policy::check(&config.security.policies, &tool, &resolved_input, timezone.as_deref(), chrono::Utc::now()) {
+            step_receipts.push(receipt::StepReceipt {
+                step_id: step.id.clone(),
+                executor: step.executor.clone(),
+                status: "denied".to_string(),
+                request: resolved_input.clone(),
+                response: serde_json::json!(null),
+                denial_reason: Some(denial),
+                error: None,
+                duration_ms: 0,
+            });
+            continue;
+        }
+
         // Execute
-        let result = executor.execute(resolved_input.clone(), config);
+        let result = if dry_run {
+            let output_schema = executor.manifest().capabilities.output_schema;
+            executor::ExecutorResult::Executed {
+                output: crate::dry_run::simulated(config, &step.executor, output_schema.as_ref()),
+                duration_ms: 0,
+            }
+        } else {
+            executor.execute(resolved_input.clone(), config).await
+        };
         let step_receipt = receipt::StepReceipt::from_result(
             &step.id,
             &step.executor,
@@ -231,7 +264,7 @@ pub async fn run(workflow_id: &str, inputs: Value) -> anyhow::Result<receipt::Wo
         started_at: start_time.to_rfc3339(),
         completed_at: end_time.to_rfc3339(),
         status: status.to_string(),
-        mode: config.node.environment.clone(),
+        mode: if dry_run { "dry_run".to_string() } else { config.node.environment.clone() },
         steps: step_receipts,
         inputs: merged_inputs,
         outputs,
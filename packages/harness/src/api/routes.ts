@@ -433,21 +433,24 @@ app.post('/jobs/:id/cancel', async (c) => {
 /**
  * Create and execute a multi-step autonomous job
  * POST /jobs/execute
- * Body: { userId, description, plan: JobStep[] }
+ * Body: { userId, description, plan: JobStep[], scoring?: Partial<ScoringWeights>, stepTimeouts?: StepTimeouts }
  */
 app.post('/jobs/execute', async (c) => {
   try {
     const { getDatabase } = await import('../database');
     const db = getDatabase();
     const body = await c.req.json();
-    const { userId, description, plan } = body;
+    const { userId, description, plan, scoring, stepTimeouts } = body;
 
     if (!userId || !description || !plan || !Array.isArray(plan)) {
       return c.json({ error: 'Missing required fields: userId, description, plan' }, 400);
     }
 
-    // Create job in SQLite
-    const job = db.createJob({ userId, description, plan });
+    // Create job in SQLite (scoring = lead-scoring weights, stepTimeouts = step limits from the node config)
+    const metadata: Record<string, any> = {};
+    if (scoring) metadata.scoring = scoring;
+    if (stepTimeouts) metadata.stepTimeouts = stepTimeouts;
+    const job = db.createJob({ userId, description, plan, metadata });
 
     // Start async execution (don't block response)
     executeJobAsync(job.id, db);
@@ -486,8 +489,12 @@ app.get('/autonomous-jobs/:id/status', async (c) => {
         id: step.id,
         order: step.order,
         action: step.action,
+        params: step.params,
         status: step.status,
         result: step.result,
+        error: step.error,
+        startedAt: step.startedAt,
+        timeoutMs: stepTimeoutMs(step, job),
       })),
       error: job.error,
       elapsedMs: Date.now() - job.startedAt.getTime(),
@@ -515,6 +522,7 @@ app.get('/autonomous-jobs/:id/results', async (c) => {
     const businesses = db.getBusinessesByJob(jobId);
     const contacts = db.getContactsByJob(jobId);
     const logs = db.getLogsByJob(jobId, 50);
+    const diff = job.plan.find(step => step.action === 'discover' && step.result?.diff)?.result.diff ?? null;
 
     return c.json({
       job: {
@@ -526,6 +534,7 @@ app.get('/autonomous-jobs/:id/results', async (c) => {
       },
       businesses,
       contacts,
+      diff,
       logs,
     });
   } catch (error) {
@@ -579,24 +588,78 @@ app.get('/autonomous-jobs/search/businesses', async (c) => {
 });
 
 /**
- * Cancel an autonomous job
- * POST /autonomous-jobs/:id/cancel
+ * Apply a job control transition (pause / resume / skip / cancel)
+ * Returns 404 for unknown jobs and 409 for transitions invalid in the current state.
  */
-app.post('/autonomous-jobs/:id/cancel', async (c) => {
+async function controlJob(c: any, apply: (db: any, jobId: string) => any) {
   try {
-    const { getDatabase } = await import('../database');
+    const { getDatabase, JobTransitionError } = await import('../database');
     const db = getDatabase();
     const jobId = c.req.param('id');
 
-    const job = db.updateJobStatus(jobId, 'cancelled');
-    if (!job) {
-      return c.json({ error: 'Job not found' }, 404);
+    try {
+      const job = apply(db, jobId);
+      if (!job) {
+        return c.json({ error: 'Job not found' }, 404);
+      }
+      db.addLog({ jobId, level: 'info', message: `Job control: ${c.req.path.split('/').pop()}` });
+      return c.json({ success: true, job });
+    } catch (error) {
+      if (error instanceof JobTransitionError) {
+        return c.json({ error: error.message }, 409);
+      }
+      throw error;
     }
-
-    return c.json({ success: true, job });
   } catch (error) {
     return c.json({ error: redactSecrets(String(error)) }, 500);
   }
+}
+
+/**
+ * Cancel an autonomous job
+ * POST /autonomous-jobs/:id/cancel
+ */
+app.post('/autonomous-jobs/:id/cancel', (c) => controlJob(c, (db, jobId) => db.cancelJob(jobId)));
+
+/**
+ * Pause an autonomous job after its current step
+ * POST /autonomous-jobs/:id/pause
+ */
+app.post('/autonomous-jobs/:id/pause', (c) => controlJob(c, (db, jobId) => db.pauseJob(jobId)));
+
+/**
+ * Resume a paused autonomous job
+ * POST /autonomous-jobs/:id/resume
+ */
+app.post('/autonomous-jobs/:id/resume', (c) => controlJob(c, (db, jobId) => db.resumeJob(jobId)));
+
+/**
+ * Skip a pending step
+ * POST /autonomous-jobs/:id/skip
+ * Body: { step?: number | string } - step order or action name; defaults to the next pending step
+ */
+app.post('/autonomous-jobs/:id/skip', async (c) => {
+  const body = await c.req.json().catch(() => ({}));
+  return controlJob(c, (db, jobId) => db.skipJobStep(jobId, body.step));
+});
+
+/**
+ * Recover a failed job: replace its failed step (and the rest of the plan)
+ * with recovery steps and resume execution from there
+ * POST /autonomous-jobs/:id/recover
+ * Body: { steps: JobStep[] }
+ */
+app.post('/autonomous-jobs/:id/recover', async (c) => {
+  const body = await c.req.json().catch(() => ({}));
+  if (!Array.isArray(body.steps) || body.steps.length === 0) {
+    return c.json({ error: 'Missing required field: steps' }, 400);
+  }
+  return controlJob(c, (db, jobId) => {
+    const recovered = db.recoverJob(jobId, body.steps);
+    if (!recovered) return null;
+    executeJobAsync(jobId, db, recovered.resumeFrom);
+    return recovered.job;
+  });
 });
 
 // =============================================================================
@@ -682,18 +745,65 @@ async function tool_search_businesses(params: { userId: string; query: string; l
 // Register these as callable tools (daemon will fetch via /tools endpoint)
 // Note: These are exported for the daemon to discover and use
 
+/**
+ * Step limits sent by the node: { defaultMs?, actions?: { [action]: ms } }
+ */
+interface StepTimeouts {
+  defaultMs?: number;
+  actions?: Record<string, number>;
+}
+
+/** Limit for steps when neither the node nor the step sets one */
+const DEFAULT_STEP_TIMEOUT_MS = 10 * 60 * 1000;
+
+class StepTimeoutError extends Error {}
+
+/**
+ * Time limit for one step: the step's own `timeoutMs` param, then the node's
+ * per-action and default limits
+ */
+function stepTimeoutMs(step: any, job: any): number {
+  const limits: StepTimeouts = job.metadata?.stepTimeouts || {};
+  return (
+    step.params?.timeoutMs ??
+    limits.actions?.[step.action] ??
+    limits.defaultMs ??
+    DEFAULT_STEP_TIMEOUT_MS
+  );
+}
+
+/**
+ * Watchdog: reject when a step runs past its limit. The stuck call itself
+ * can't be aborted; its late result is ignored.
+ */
+async function withStepTimeout<T>(work: Promise<T>, timeoutMs: number, step: any): Promise<T> {
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  const watchdog = new Promise<never>((_, reject) => {
+    timer = setTimeout(
+      () => reject(new StepTimeoutError(`Step ${step.order} (${step.action}) timed out after ${Math.round(timeoutMs / 1000)}s`)),
+      timeoutMs
+    );
+  });
+  try {
+    return await Promise.race([work, watchdog]);
+  } finally {
+    clearTimeout(timer);
+  }
+}
+
 /**
  * Helper function to execute job asynchronously
- * This runs in the background and updates the database as it progresses
+ * This runs in the background and updates the database as it progresses.
+ * `startIndex` resumes a recovered job at its replacement steps.
  */
-async function executeJobAsync(jobId: string, db: any) {
+async function executeJobAsync(jobId: string, db: any, startIndex = 0) {
   try {
     // Mark job as running
     db.updateJobStatus(jobId, 'running');
     db.addLog({
       jobId,
       level: 'info',
-      message: 'Job execution started',
+      message: startIndex > 0 ? `Job execution resumed at step ${startIndex + 1}` : 'Job execution started',
     });
 
     const job = db.getJob(jobId);
@@ -702,13 +812,28 @@ async function executeJobAsync(jobId: string, db: any) {
     }
 
     // Execute each step in the plan
-    for (let i = 0; i < job.plan.length; i++) {
-      const step = job.plan[i];
+    for (let i = startIndex; i < job.plan.length; i++) {
+      // Honor control changes made since the last step (pause / skip / cancel)
+      const latest = await waitWhilePaused(jobId, db);
+      if (!latest || latest.status === 'cancelled') {
+        db.addLog({ jobId, level: 'info', message: 'Job cancelled, stopping execution' });
+        return;
+      }
+
+      const step = latest.plan[i];
+      if (step.status === 'skipped') {
+        db.advanceJobStep(jobId);
+        db.addLog({
+          jobId,
+          level: 'info',
+          step: step.order,
+          message: `Step ${step.order} skipped by user`,
+        });
+        continue;
+      }
 
       // Update step status
-      step.status = 'running';
-      step.startedAt = new Date();
-      db.updateJob(jobId, { plan: job.plan });
+      db.updateJobStep(jobId, i, { status: 'running', startedAt: new Date() });
       db.addLog({
         jobId,
         level: 'info',
@@ -717,14 +842,11 @@ async function executeJobAsync(jobId: string, db: any) {
       });
 
       try {
-        // Execute the step based on action type
-        const result = await executeStep(step, db, jobId);
+        // Execute the step based on action type, failing it if it runs past its limit
+        const result = await withStepTimeout(executeStep(step, db, jobId), stepTimeoutMs(step, latest), step);
         
         // Update step as completed
-        step.status = 'completed';
-        step.completedAt = new Date();
-        step.result = result;
-        db.updateJob(jobId, { plan: job.plan });
+        db.updateJobStep(jobId, i, { status: 'completed', completedAt: new Date(), result });
         db.advanceJobStep(jobId);
         
         db.addLog({
@@ -736,22 +858,26 @@ async function executeJobAsync(jobId: string, db: any) {
 
       } catch (error) {
         // Step failed
-        step.status = 'failed';
-        step.error = String(error);
-        step.completedAt = new Date();
-        db.updateJob(jobId, { plan: job.plan });
+        db.updateJobStep(jobId, i, { status: 'failed', error: String(error), completedAt: new Date() });
         
         db.addLog({
           jobId,
           level: 'error',
           step: step.order,
-          message: `Step ${step.order} failed: ${error}`,
+          message: error instanceof StepTimeoutError
+            ? `Watchdog: ${error.message}, marking it failed`
+            : `Step ${step.order} failed: ${error}`,
         });
 
         throw error; // Stop execution on first error
       }
     }
 
+    // A cancel during the last step wins over completion
+    if (db.getJob(jobId)?.status === 'cancelled') {
+      return;
+    }
+
     // All steps completed successfully
     db.updateJobStatus(jobId, 'completed');
     db.addLog({
@@ -771,6 +897,23 @@ async function executeJobAsync(jobId: string, db: any) {
   }
 }
 
+/**
+ * Block while a job is paused; returns the latest job row (null if deleted)
+ */
+async function waitWhilePaused(jobId: string, db: any) {
+  let job = db.getJob(jobId);
+  let logged = false;
+  while (job && job.status === 'paused') {
+    if (!logged) {
+      db.addLog({ jobId, level: 'info', message: 'Job paused, waiting for resume' });
+      logged = true;
+    }
+    await new Promise(resolve => setTimeout(resolve, 2000));
+    job = db.getJob(jobId);
+  }
+  return job;
+}
+
 /**
  * Execute a single job step
  * Maps step actions to actual workflow execution
@@ -816,7 +959,45 @@ async function executeStep(step: any, db: any, jobId: string) {
         metadata: b,
       }));
       
-      db.createBusinesses(businesses);
+      const created = db.createBusinesses(businesses);
+
+      // Score leads deterministically so ranking doesn't depend on the LLM
+      const { scoreBusiness, resolveWeights } = await import('../scoring');
+      const weights = resolveWeights(db.getJob(jobId)?.metadata?.scoring);
+      for (const business of created) {
+        const { score, breakdown } = scoreBusiness(business, weights);
+        business.score = score;
+        db.updateBusinessScore(business.id, score, breakdown);
+      }
+
+      // Report only what's new or changed compared to the last run of this query
+      const { diffBusinesses } = await import('../database/diff');
+      const current = db.getJob(jobId);
+      const previous = current ? db.findPreviousDiscovery(current.userId, params, jobId) : null;
+      job.output.diff = diffBusinesses(
+        previous ? db.getBusinessesByJob(previous.id) : [],
+        created,
+        previous?.id ?? null,
+        previous?.completedAt ?? previous?.startedAt ?? null
+      );
+      db.addLog({ jobId, level: 'info', step: step.order, message: job.output.diff.summary });
+    }
+
+    // Re-score the audited lead with its audit findings
+    if (action === 'audit' && job.output) {
+      const { scoreBusiness, resolveWeights } = await import('../scoring');
+      const weights = resolveWeights(db.getJob(jobId)?.metadata?.scoring);
+      const audited = db.getBusinessesByJob(jobId).find((b: any) =>
+        (params.url && b.website && normalizeUrl(b.website) === normalizeUrl(params.url)) ||
+        (params.businessName && b.name === params.businessName)
+      );
+      if (audited) {
+        const { score, breakdown } = scoreBusiness(audited, weights, job.output);
+        // Keep the audit summary on the lead for call scripts and reports
+        db.updateBusinessScore(audited.id, score, breakdown, {
+          audit: { score: job.output.score, issues: job.output.issues ?? [] },
+        });
+      }
     }
 
     // Store contacts if this is an enrichment step
@@ -842,6 +1023,10 @@ async function executeStep(step: any, db: any, jobId: string) {
   }
 }
 
+function normalizeUrl(url: string): string {
+  return url.toLowerCase().replace(/^https?:\/\//, '').replace(/^www\./, '').replace(/\/+$/, '');
+}
+
 /**
  * Switch execution method for a running job
  * POST /jobs/:id/switch-method
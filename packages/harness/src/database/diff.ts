@@ -0,0 +1,105 @@
+/**
+ * Discovery Result Diffing
+ *
+ * Repeated discovery runs (scheduled sweeps, "find HVAC in Miami" again) are
+ * compared against the user's previous run of the same query so we can report
+ * only what's new or changed ("8 new since last week") instead of re-dumping
+ * the same list.
+ */
+
+import { Business } from './schema';
+
+/** Fields compared to decide whether a known business changed */
+const TRACKED_FIELDS = ['rating', 'reviewCount', 'phone', 'website', 'address'] as const;
+
+export interface BusinessChange {
+  business: Business;
+  changes: Record<string, { from: any; to: any }>;
+}
+
+export interface DiscoveryDiff {
+  previousJobId: string | null;
+  previousRunAt: Date | null;
+  new: Business[];
+  changed: BusinessChange[];
+  unchangedCount: number;
+  summary: string;
+}
+
+/**
+ * Normalized identity of a discovery query (same niche + location = same query)
+ */
+export function discoveryKey(params: Record<string, any> = {}): string {
+  const niche = String(params.niche ?? params.keyword ?? params.query ?? '').trim().toLowerCase();
+  const location = String(params.location ?? params.city ?? '').trim().toLowerCase();
+  return `${niche}|${location}`;
+}
+
+/**
+ * Stable identity of a business across runs: Google place ID, else name + address
+ */
+function businessKey(b: Business): string {
+  if (b.placeId) return `place:${b.placeId}`;
+  return `name:${(b.name || '').trim().toLowerCase()}|${(b.address || '').trim().toLowerCase()}`;
+}
+
+/**
+ * Compare the current run's businesses against the previous run
+ */
+export function diffBusinesses(
+  previous: Business[],
+  current: Business[],
+  previousJobId: string | null,
+  previousRunAt: Date | null
+): DiscoveryDiff {
+  const known = new Map(previous.map(b => [businessKey(b), b]));
+  const added: Business[] = [];
+  const changed: BusinessChange[] = [];
+  let unchangedCount = 0;
+
+  for (const business of current) {
+    const before = known.get(businessKey(business));
+    if (!before) {
+      added.push(business);
+      continue;
+    }
+
+    const changes: Record<string, { from: any; to: any }> = {};
+    for (const field of TRACKED_FIELDS) {
+      const from = before[field] ?? null;
+      const to = business[field] ?? null;
+      if (from !== to) {
+        changes[field] = { from, to };
+      }
+    }
+
+    if (Object.keys(changes).length > 0) {
+      changed.push({ business, changes });
+    } else {
+      unchangedCount++;
+    }
+  }
+
+  return {
+    previousJobId,
+    previousRunAt,
+    new: added,
+    changed,
+    unchangedCount,
+    summary: summarize(added.length, changed.length, previousRunAt),
+  };
+}
+
+function summarize(added: number, changed: number, previousRunAt: Date | null): string {
+  if (!previousRunAt) {
+    return 'First run for this search';
+  }
+
+  const days = Math.floor((Date.now() - previousRunAt.getTime()) / 86_400_000);
+  const since = days === 0 ? 'earlier today' : days === 1 ? 'yesterday' : days < 14 ? `${days} days ago` : `${Math.floor(days / 7)} weeks ago`;
+
+  if (added === 0 && changed === 0) {
+    return `No new or changed businesses since the last run (${since})`;
+  }
+  return `${added} new, ${changed} changed since the last run (${since})`;
+}
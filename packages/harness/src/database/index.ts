@@ -25,6 +25,12 @@ import {
   serializeJobLog,
   deserializeJobLog,
 } from './schema';
+import { discoveryKey } from './diff';
+
+/**
+ * Thrown when a control action isn't valid for the job's current state
+ */
+export class JobTransitionError extends Error {}
 
 export class JobDatabase {
   private db: Database.Database;
@@ -47,6 +53,47 @@ export class JobDatabase {
 
     // Create schema
     this.db.exec(SCHEMA_SQL);
+    this.migrateJobStatuses();
+    this.migrateBusinessScore();
+  }
+
+  /**
+   * Add the lead score column to databases created before scoring
+   */
+  private migrateBusinessScore() {
+    const columns = this.db.prepare('PRAGMA table_info(businesses)').all() as Array<{ name: string }>;
+    if (!columns.some(c => c.name === 'score')) {
+      this.db.exec('ALTER TABLE businesses ADD COLUMN score REAL');
+    }
+  }
+
+  /**
+   * Databases created before job control have a status CHECK without 'paused'.
+   * SQLite can't alter a CHECK constraint, so rebuild the jobs table.
+   */
+  private migrateJobStatuses() {
+    const row = this.db
+      .prepare("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'jobs'")
+      .get() as { sql: string } | undefined;
+    if (!row || row.sql.includes("'paused'")) return;
+
+    const newSql = row.sql
+      .replace(/CREATE TABLE (IF NOT EXISTS )?"?jobs"?/, 'CREATE TABLE jobs_new')
+      .replace("'running',", "'running', 'paused',");
+
+    this.db.pragma('foreign_keys = OFF');
+    this.db.transaction(() => {
+      this.db.exec(newSql);
+      this.db.exec('INSERT INTO jobs_new SELECT * FROM jobs');
+      this.db.exec('DROP TABLE jobs');
+      this.db.exec('ALTER TABLE jobs_new RENAME TO jobs');
+      this.db.exec(`
+        CREATE INDEX IF NOT EXISTS idx_jobs_user_id ON jobs(user_id);
+        CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status);
+        CREATE INDEX IF NOT EXISTS idx_jobs_created_at ON jobs(created_at DESC);
+      `);
+    })();
+    this.db.pragma('foreign_keys = ON');
   }
 
   /**
@@ -67,6 +114,7 @@ export class JobDatabase {
     userId: string;
     description: string;
     plan: JobStep[];
+    metadata?: Record<string, any>;
   }): Job {
     const job: Job = {
       id: nanoid(),
@@ -77,7 +125,7 @@ export class JobDatabase {
       currentStep: 0,
       totalSteps: data.plan.length,
       startedAt: new Date(),
-      metadata: {},
+      metadata: data.metadata || {},
       createdAt: new Date(),
       updatedAt: new Date(),
     };
@@ -181,6 +229,123 @@ export class JobDatabase {
     return this.updateJob(jobId, { currentStep });
   }
 
+  /**
+   * Update a single plan step without clobbering control changes
+   * (e.g. a step marked 'skipped' while another step was running)
+   */
+  updateJobStep(jobId: string, index: number, updates: Partial<JobStep>): Job | null {
+    const job = this.getJob(jobId);
+    if (!job || !job.plan[index]) return null;
+
+    job.plan[index] = { ...job.plan[index], ...updates };
+    return this.updateJob(jobId, { plan: job.plan });
+  }
+
+  //
+  // Job Control (pause / resume / skip / cancel)
+  //
+
+  /**
+   * Pause a pending or running job. The engine finishes the in-flight step,
+   * then waits before starting the next one.
+   */
+  pauseJob(jobId: string): Job | null {
+    const job = this.getJob(jobId);
+    if (!job) return null;
+    if (job.status !== 'pending' && job.status !== 'running') {
+      throw new JobTransitionError(`Cannot pause a ${job.status} job`);
+    }
+    return this.updateJobStatus(jobId, 'paused');
+  }
+
+  /**
+   * Resume a paused job
+   */
+  resumeJob(jobId: string): Job | null {
+    const job = this.getJob(jobId);
+    if (!job) return null;
+    if (job.status !== 'paused') {
+      throw new JobTransitionError(`Cannot resume a ${job.status} job`);
+    }
+    return this.updateJobStatus(jobId, 'running');
+  }
+
+  /**
+   * Mark a pending step as skipped. Targets the step with the given order or
+   * action name, or the next pending step when no target is given.
+   */
+  skipJobStep(jobId: string, target?: number | string): Job | null {
+    const job = this.getJob(jobId);
+    if (!job) return null;
+    if (job.status === 'completed' || job.status === 'failed' || job.status === 'cancelled') {
+      throw new JobTransitionError(`Cannot skip steps of a ${job.status} job`);
+    }
+
+    const index = job.plan.findIndex(step => {
+      if (step.status !== 'pending') return false;
+      if (target === undefined) return true;
+      return typeof target === 'number' ? step.order === target : step.action === target;
+    });
+    if (index === -1) {
+      throw new JobTransitionError(
+        target === undefined ? 'No pending step to skip' : `No pending step matching '${target}'`
+      );
+    }
+
+    return this.updateJobStep(jobId, index, { status: 'skipped' });
+  }
+
+  /**
+   * Cancel a job that hasn't finished yet
+   */
+  cancelJob(jobId: string): Job | null {
+    const job = this.getJob(jobId);
+    if (!job) return null;
+    if (job.status === 'completed' || job.status === 'failed' || job.status === 'cancelled') {
+      throw new JobTransitionError(`Cannot cancel a ${job.status} job`);
+    }
+    return this.updateJobStatus(jobId, 'cancelled');
+  }
+
+  /**
+   * Replace a failed job's failed step and everything after it with recovery
+   * steps, and put the job back to running. Returns the index to resume from.
+   */
+  recoverJob(jobId: string, steps: JobStep[]): { job: Job; resumeFrom: number } | null {
+    const job = this.getJob(jobId);
+    if (!job) return null;
+    if (job.status !== 'failed') {
+      throw new JobTransitionError(`Cannot recover a ${job.status} job`);
+    }
+
+    const resumeFrom = job.plan.findIndex(step => step.status === 'failed');
+    if (resumeFrom === -1) {
+      throw new JobTransitionError('Job has no failed step to recover from');
+    }
+
+    const plan = [
+      ...job.plan.slice(0, resumeFrom),
+      ...steps.map((step, i) => ({
+        ...step,
+        order: resumeFrom + i + 1,
+        status: 'pending' as const,
+        result: undefined,
+        error: undefined,
+        startedAt: undefined,
+        completedAt: undefined,
+      })),
+    ];
+    const updated = this.updateJob(jobId, {
+      status: 'running',
+      plan,
+      currentStep: resumeFrom,
+      totalSteps: plan.length,
+      error: undefined,
+      completedAt: undefined,
+    });
+    return updated ? { job: updated, resumeFrom } : null;
+  }
+
   /**
    * Search jobs by description keyword
    */
@@ -228,7 +393,9 @@ export class JobDatabase {
    * Get businesses by job ID
    */
   getBusinessesByJob(jobId: string): Business[] {
-    const stmt = this.db.prepare('SELECT * FROM businesses WHERE job_id = ?');
+    const stmt = this.db.prepare(
+      'SELECT * FROM businesses WHERE job_id = ? ORDER BY score IS NULL, score DESC'
+    );
     const rows = stmt.all(jobId);
     return rows.map(deserializeBusiness);
   }
@@ -265,6 +432,42 @@ export class JobDatabase {
     return transaction(businesses);
   }
 
+  /**
+   * Find the user's most recent earlier job that ran the same discovery query
+   */
+  findPreviousDiscovery(userId: string, params: Record<string, any>, excludeJobId: string): Job | null {
+    const key = discoveryKey(params);
+    const jobs = this.getJobsByUser(userId, 100);
+    return jobs.find(job =>
+      job.id !== excludeJobId &&
+      job.plan.some(step =>
+        step.action === 'discover' &&
+        step.status === 'completed' &&
+        discoveryKey(step.params) === key
+      )
+    ) ?? null;
+  }
+
+  /**
+   * Store a lead score and its per-factor breakdown
+   */
+  updateBusinessScore(
+    businessId: string,
+    score: number,
+    breakdown: Record<string, number>,
+    extraMetadata: Record<string, any> = {}
+  ) {
+    const row = this.db.prepare('SELECT metadata FROM businesses WHERE id = ?').get(businessId) as
+      | { metadata: string }
+      | undefined;
+    if (!row) return;
+
+    const metadata = { ...JSON.parse(row.metadata || '{}'), ...extraMetadata, scoreBreakdown: breakdown };
+    this.db
+      .prepare('UPDATE businesses SET score = ?, metadata = ? WHERE id = ?')
+      .run(score, JSON.stringify(metadata), businessId);
+  }
+
   /**
    * Search businesses across all jobs
    */
@@ -9,7 +9,7 @@ export interface Job {
   id: string; // UUID
   userId: string; // From daemon identity system
   description: string; // Original user request
-  status: 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
+  status: 'pending' | 'running' | 'paused' | 'completed' | 'failed' | 'cancelled';
   plan: JobStep[]; // Serialized as JSON
   currentStep: number; // Index of current step (0-based)
   totalSteps: number;
@@ -47,6 +47,7 @@ export interface Business {
   reviewCount?: number;
   googleMapsUrl?: string;
   placeId?: string;
+  score?: number; // 0-100 lead score (see scoring/)
   metadata: Record<string, any>; // Additional data (hours, categories, etc.)
   createdAt: Date;
 }
@@ -90,7 +91,7 @@ CREATE TABLE IF NOT EXISTS jobs (
   id TEXT PRIMARY KEY,
   user_id TEXT NOT NULL,
   description TEXT NOT NULL,
-  status TEXT NOT NULL CHECK(status IN ('pending', 'running', 'completed', 'failed', 'cancelled')),
+  status TEXT NOT NULL CHECK(status IN ('pending', 'running', 'paused', 'completed', 'failed', 'cancelled')),
   plan TEXT NOT NULL, -- JSON serialized JobStep[]
   current_step INTEGER NOT NULL DEFAULT 0,
   total_steps INTEGER NOT NULL DEFAULT 0,
@@ -122,6 +123,7 @@ CREATE TABLE IF NOT EXISTS businesses (
   review_count INTEGER,
   google_maps_url TEXT,
   place_id TEXT,
+  score REAL, -- 0-100 lead score
   metadata TEXT DEFAULT '{}', -- JSON object
   created_at INTEGER NOT NULL,
   FOREIGN KEY (job_id) REFERENCES jobs(id) ON DELETE CASCADE
@@ -244,6 +246,7 @@ export function deserializeBusiness(row: any): Business {
     reviewCount: row.review_count,
     googleMapsUrl: row.google_maps_url,
     placeId: row.place_id,
+    score: row.score ?? undefined,
     metadata: JSON.parse(row.metadata || '{}'),
     createdAt: new Date(row.created_at),
   };
@@ -0,0 +1,105 @@
+/**
+ * Lead Scoring
+ *
+ * Deterministic 0-100 lead score computed after discovery and audit steps, so
+ * ranking doesn't depend on the LLM eyeballing JSON. Weights come from the
+ * node's `scoring` config (sent with each job) and fall back to defaults.
+ */
+
+import { Business } from '../database/schema';
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+export interface ScoringWeights {
+  /** Weight of the Google rating (normalized from 0-5) */
+  rating: number;
+  /** Weight of review volume (normalized against reviewCountTarget) */
+  reviewCount: number;
+  /** Weight of having a website */
+  website: number;
+  /** Weight of audit findings - a weaker audit score means a bigger opportunity */
+  audit: number;
+  /** Review count that earns the full review score */
+  reviewCountTarget: number;
+}
+
+export const DEFAULT_WEIGHTS: ScoringWeights = {
+  rating: 0.25,
+  reviewCount: 0.25,
+  website: 0.2,
+  audit: 0.3,
+  reviewCountTarget: 200,
+};
+
+export interface LeadScore {
+  score: number; // 0-100
+  breakdown: Record<string, number>; // Per-factor contribution (0-100 scale)
+}
+
+/** Audit fields used for scoring (subset of AuditToolOutput) */
+export interface AuditSignals {
+  score: number; // 0-100, higher = healthier site
+  issues?: Array<{ type: 'critical' | 'warning' | 'info' }>;
+}
+
+// =============================================================================
+// SCORING
+// =============================================================================
+
+/**
+ * Merge partial weights from the node config over the defaults
+ */
+export function resolveWeights(partial?: Partial<ScoringWeights> | null): ScoringWeights {
+  return { ...DEFAULT_WEIGHTS, ...(partial || {}) };
+}
+
+/**
+ * Score a lead. Without audit data the audit weight is redistributed over the
+ * other factors, so scores before and after auditing stay comparable.
+ */
+export function scoreBusiness(
+  business: Pick<Business, 'rating' | 'reviewCount' | 'website'>,
+  weights: ScoringWeights,
+  audit?: AuditSignals | null
+): LeadScore {
+  const factors: Record<string, { value: number; weight: number }> = {
+    rating: { value: clamp((business.rating ?? 0) / 5), weight: weights.rating },
+    reviewCount: {
+      value: clamp((business.reviewCount ?? 0) / Math.max(1, weights.reviewCountTarget)),
+      weight: weights.reviewCount,
+    },
+    website: { value: business.website ? 1 : 0, weight: weights.website },
+  };
+
+  if (audit) {
+    // Weak audit score and critical issues = more room for the pitch
+    const critical = (audit.issues || []).filter(i => i.type === 'critical').length;
+    const opportunity = clamp((100 - audit.score) / 100 + critical * 0.05);
+    factors.audit = { value: opportunity, weight: weights.audit };
+  }
+
+  const totalWeight = Object.values(factors).reduce((sum, f) => sum + f.weight, 0);
+  if (totalWeight <= 0) {
+    return { score: 0, breakdown: {} };
+  }
+
+  const breakdown: Record<string, number> = {};
+  let score = 0;
+  for (const [name, factor] of Object.entries(factors)) {
+    const contribution = (factor.value * factor.weight) / totalWeight;
+    breakdown[name] = round(contribution * 100);
+    score += contribution;
+  }
+
+  return { score: round(score * 100), breakdown };
+}
+
+function clamp(value: number): number {
+  return Math.min(1, Math.max(0, value));
+}
+
+function round(value: number): number {
+  return Math.round(value * 10) / 10;
+}